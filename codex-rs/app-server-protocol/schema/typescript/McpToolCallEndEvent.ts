@@ -3,6 +3,7 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { CallToolResult } from "./CallToolResult";
 import type { McpInvocation } from "./McpInvocation";
+import type { McpToolCallErrorDetails } from "./McpToolCallErrorDetails";
 
 export type McpToolCallEndEvent = { 
 /**
@@ -12,4 +13,8 @@ call_id: string, invocation: McpInvocation, duration: string,
 /**
  * Result of the tool call. Note this could be an error.
  */
-result: { Ok : CallToolResult } | { Err : string }, };
+result: { Ok : CallToolResult } | { Err : string }, 
+/**
+ * Structured details for a failed call, when the failure carried any.
+ */
+error_details: McpToolCallErrorDetails | null, };
@@ -0,0 +1,23 @@
+// GENERATED CODE! DO NOT MODIFY BY HAND!
+
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { McpAuthStatus } from "./McpAuthStatus";
+import type { JsonValue } from "./serde_json/JsonValue";
+
+/**
+ * Structured context for a failed MCP tool call, kept alongside the
+ * flattened error message in [`McpToolCallEndEvent::result`].
+ */
+export type McpToolCallErrorDetails = { 
+/**
+ * JSON-RPC error code, when the server answered with an MCP error.
+ */
+code: number | null, 
+/**
+ * `data` attached to the MCP error response.
+ */
+data: JsonValue | null, 
+/**
+ * Auth status of the server at the time of the failure.
+ */
+auth_status: McpAuthStatus | null, };
@@ -1,5 +1,20 @@
 // GENERATED CODE! DO NOT MODIFY BY HAND!
 
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { JsonValue } from "../serde_json/JsonValue";
+import type { McpAuthStatus } from "./McpAuthStatus";
 
-export type McpToolCallError = { message: string, };
+export type McpToolCallError = { message: string, 
+/**
+ * JSON-RPC error code, when the server answered with an MCP error.
+ */
+code: number | null, 
+/**
+ * `data` attached to the MCP error response.
+ */
+data: JsonValue | null, 
+/**
+ * Auth status of the server at the time of the failure, so clients can
+ * offer a login flow for auth failures.
+ */
+authStatus: McpAuthStatus | null, };
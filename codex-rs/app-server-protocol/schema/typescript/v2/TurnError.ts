@@ -2,5 +2,6 @@
 
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { CodexErrorInfo } from "./CodexErrorInfo";
+import type { TurnReasonCode } from "./TurnReasonCode";
 
-export type TurnError = { message: string, codexErrorInfo: CodexErrorInfo | null, additionalDetails: string | null, };
+export type TurnError = { message: string, codexErrorInfo: CodexErrorInfo | null, additionalDetails: string | null, reasonCode: TurnReasonCode | null, };
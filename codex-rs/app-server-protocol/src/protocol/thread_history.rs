@@ -417,9 +417,10 @@ impl ThreadHistoryBuilder {
             ),
             Err(message) => (
                 None,
-                Some(McpToolCallError {
-                    message: message.clone(),
-                }),
+                Some(McpToolCallError::new(
+                    message.clone(),
+                    payload.error_details.clone(),
+                )),
             ),
         };
         let item = ThreadItem::McpToolCall {
@@ -710,6 +711,7 @@ impl ThreadHistoryBuilder {
             message: payload.message.clone(),
             codex_error_info: payload.codex_error_info.clone().map(Into::into),
             additional_details: None,
+            reason_code: None,
         });
     }
 
@@ -1552,6 +1554,7 @@ mod tests {
                 },
                 duration: Duration::from_millis(8),
                 result: Err("boom".into()),
+                error_details: None,
             }),
         ];
 
@@ -1600,6 +1603,9 @@ mod tests {
                 result: None,
                 error: Some(McpToolCallError {
                     message: "boom".into(),
+                    code: None,
+                    data: None,
+                    auth_status: None,
                 }),
                 duration_ms: Some(8),
             }
@@ -2155,6 +2161,7 @@ mod tests {
                     }
                 ),
                 additional_details: None,
+                reason_code: None,
             })
         );
     }
@@ -33,6 +33,7 @@ use codex_protocol::protocol::AskForApproval as CoreAskForApproval;
 use codex_protocol::protocol::CodexErrorInfo as CoreCodexErrorInfo;
 use codex_protocol::protocol::CreditsSnapshot as CoreCreditsSnapshot;
 use codex_protocol::protocol::ExecCommandStatus as CoreExecCommandStatus;
+use codex_protocol::protocol::McpToolCallErrorDetails as CoreMcpToolCallErrorDetails;
 use codex_protocol::protocol::ModelRerouteReason as CoreModelRerouteReason;
 use codex_protocol::protocol::NetworkAccess as CoreNetworkAccess;
 use codex_protocol::protocol::PatchApplyStatus as CorePatchApplyStatus;
@@ -2375,6 +2376,20 @@ pub struct TurnError {
     pub codex_error_info: Option<CodexErrorInfo>,
     #[serde(default)]
     pub additional_details: Option<String>,
+    // Stable cause for interrupted turns; clients localize from this rather
+    // than from `message`, which is English.
+    #[serde(default)]
+    pub reason_code: Option<TurnReasonCode>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+#[ts(export_to = "v2/")]
+pub enum TurnReasonCode {
+    UserInterrupt,
+    ReplacedByNewTurn,
+    ReviewEnded,
+    Shutdown,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, TS)]
@@ -3049,6 +3064,26 @@ pub struct McpToolCallResult {
 #[ts(export_to = "v2/")]
 pub struct McpToolCallError {
     pub message: String,
+    /// JSON-RPC error code, when the server answered with an MCP error.
+    #[ts(type = "number | null")]
+    pub code: Option<i64>,
+    /// `data` attached to the MCP error response.
+    pub data: Option<JsonValue>,
+    /// Auth status of the server at the time of the failure, so clients can
+    /// offer a login flow for auth failures.
+    pub auth_status: Option<McpAuthStatus>,
+}
+
+impl McpToolCallError {
+    pub fn new(message: String, details: Option<CoreMcpToolCallErrorDetails>) -> Self {
+        let details = details.unwrap_or_default();
+        Self {
+            message,
+            code: details.code,
+            data: details.data,
+            auth_status: details.auth_status.map(McpAuthStatus::from),
+        }
+    }
 }
 
 // === Server Notifications ===
@@ -842,6 +842,7 @@ pub(crate) async fn apply_bespoke_event_handling(
                 message: ev.message,
                 codex_error_info: ev.codex_error_info.map(V2CodexErrorInfo::from),
                 additional_details: None,
+                reason_code: None,
             };
             handle_error(conversation_id, turn_error.clone(), &thread_state).await;
             outgoing
@@ -860,6 +861,7 @@ pub(crate) async fn apply_bespoke_event_handling(
                 message: ev.message,
                 codex_error_info: ev.codex_error_info.map(V2CodexErrorInfo::from),
                 additional_details: ev.additional_details,
+                reason_code: None,
             };
             outgoing
                 .send_server_notification(ServerNotification::Error(ErrorNotification {
@@ -2025,9 +2027,10 @@ async fn construct_mcp_tool_call_end_notification(
         ),
         Err(message) => (
             None,
-            Some(McpToolCallError {
-                message: message.clone(),
-            }),
+            Some(McpToolCallError::new(
+                message.clone(),
+                end_event.error_details.clone(),
+            )),
         ),
     };
 
@@ -2167,6 +2170,7 @@ mod tests {
                 message: "boom".to_string(),
                 codex_error_info: Some(V2CodexErrorInfo::InternalServerError),
                 additional_details: None,
+                reason_code: None,
             },
             &thread_state,
         )
@@ -2179,6 +2183,7 @@ mod tests {
                 message: "boom".to_string(),
                 codex_error_info: Some(V2CodexErrorInfo::InternalServerError),
                 additional_details: None,
+                reason_code: None,
             })
         );
         Ok(())
@@ -2225,6 +2230,7 @@ mod tests {
                 message: "oops".to_string(),
                 codex_error_info: None,
                 additional_details: None,
+                reason_code: None,
             },
             &thread_state,
         )
@@ -2265,6 +2271,7 @@ mod tests {
                 message: "bad".to_string(),
                 codex_error_info: Some(V2CodexErrorInfo::Other),
                 additional_details: None,
+                reason_code: None,
             },
             &thread_state,
         )
@@ -2292,6 +2299,7 @@ mod tests {
                         message: "bad".to_string(),
                         codex_error_info: Some(V2CodexErrorInfo::Other),
                         additional_details: None,
+                        reason_code: None,
                     })
                 );
             }
@@ -2516,6 +2524,7 @@ mod tests {
                 message: "a1".to_string(),
                 codex_error_info: Some(V2CodexErrorInfo::BadRequest),
                 additional_details: None,
+                reason_code: None,
             },
             &thread_state,
         )
@@ -2530,6 +2539,7 @@ mod tests {
                 message: "b1".to_string(),
                 codex_error_info: None,
                 additional_details: None,
+                reason_code: None,
             },
             &thread_state,
         )
@@ -2552,6 +2562,7 @@ mod tests {
                         message: "a1".to_string(),
                         codex_error_info: Some(V2CodexErrorInfo::BadRequest),
                         additional_details: None,
+                        reason_code: None,
                     })
                 );
             }
@@ -2570,6 +2581,7 @@ mod tests {
                         message: "b1".to_string(),
                         codex_error_info: None,
                         additional_details: None,
+                        reason_code: None,
                     })
                 );
             }
@@ -2651,6 +2663,7 @@ mod tests {
             },
             duration: Duration::from_nanos(92708),
             result: Ok(result),
+            error_details: None,
         };
 
         let thread_id = ThreadId::new().to_string();
@@ -2694,6 +2707,7 @@ mod tests {
             },
             duration: Duration::from_millis(1),
             result: Err("boom".to_string()),
+            error_details: None,
         };
 
         let thread_id = ThreadId::new().to_string();
@@ -2717,6 +2731,9 @@ mod tests {
                 result: None,
                 error: Some(McpToolCallError {
                     message: "boom".to_string(),
+                    code: None,
+                    data: None,
+                    auth_status: None,
                 }),
                 duration_ms: Some(1),
             },
@@ -209,6 +209,45 @@ impl ConfigLayerStack {
         }
     }
 
+    /// Returns a copy of the stack where the project layer for
+    /// `dot_codex_folder` holds `config`. A missing layer is added after the
+    /// existing project layers, which only holds for the folder closest to
+    /// the cwd. A disabled layer stays disabled.
+    pub fn with_project_config(
+        &self,
+        dot_codex_folder: &AbsolutePathBuf,
+        config: TomlValue,
+    ) -> Self {
+        let name = ConfigLayerSource::Project {
+            dot_codex_folder: dot_codex_folder.clone(),
+        };
+        let mut layers = self.layers.clone();
+        match layers.iter().position(|layer| layer.name == name) {
+            Some(index) => {
+                let disabled_reason = layers[index].disabled_reason.clone();
+                layers[index] = ConfigLayerEntry {
+                    disabled_reason,
+                    ..ConfigLayerEntry::new(name, config)
+                };
+            }
+            None => {
+                let index = layers
+                    .iter()
+                    .position(|layer| layer.name.precedence() > name.precedence())
+                    .unwrap_or(layers.len());
+                layers.insert(index, ConfigLayerEntry::new(name, config));
+            }
+        }
+
+        // Project layers sit above the user layer, so its index is unchanged.
+        Self {
+            layers,
+            user_layer_index: self.user_layer_index,
+            requirements: self.requirements.clone(),
+            requirements_toml: self.requirements_toml.clone(),
+        }
+    }
+
     pub fn effective_config(&self) -> TomlValue {
         let mut merged = TomlValue::Table(toml::map::Map::new());
         for layer in self.get_layers(ConfigLayerStackOrdering::LowestPrecedenceFirst, false) {
@@ -133,6 +133,7 @@ use crate::context_manager::ContextManager;
 use crate::context_manager::TotalTokenUsageBreakdown;
 use crate::environment_context::EnvironmentContext;
 use crate::error::CodexErr;
+use crate::error::McpStartupFailedError;
 use crate::error::Result as CodexResult;
 #[cfg(test)]
 use crate::exec::StreamOutput;
@@ -510,6 +511,23 @@ impl Codex {
         state.session_configuration.thread_config_snapshot()
     }
 
+    /// Metadata of the model the next turn runs with, config overrides applied.
+    pub(crate) async fn model_info(&self) -> ModelInfo {
+        let (model, config) = {
+            let state = self.session.state.lock().await;
+            let configuration = &state.session_configuration;
+            (
+                configuration.collaboration_mode.model().to_string(),
+                Arc::clone(&configuration.original_config_do_not_use),
+            )
+        };
+        self.session
+            .services
+            .models_manager
+            .get_model_info(&model, &config)
+            .await
+    }
+
     pub(crate) fn state_db(&self) -> Option<state_db::StateDbHandle> {
         self.session.state_db()
     }
@@ -1456,14 +1474,7 @@ impl Session {
                 .required_startup_failures(&required_mcp_servers)
                 .await;
             if !failures.is_empty() {
-                let details = failures
-                    .iter()
-                    .map(|failure| format!("{}: {}", failure.server, failure.error))
-                    .collect::<Vec<_>>()
-                    .join("; ");
-                return Err(anyhow::anyhow!(
-                    "required MCP servers failed to initialize: {details}"
-                ));
+                return Err(McpStartupFailedError { failures }.into());
             }
         }
 
@@ -1549,6 +1560,11 @@ impl Session {
         state.token_info().map(|info| info.total_token_usage)
     }
 
+    pub(crate) async fn token_usage_info(&self) -> Option<TokenUsageInfo> {
+        let state = self.state.lock().await;
+        state.token_info()
+    }
+
     pub(crate) async fn get_estimated_token_count(
         &self,
         turn_context: &TurnContext,
@@ -3506,6 +3522,9 @@ async fn submission_loop(sess: Arc<Session>, config: Arc<Config>, rx_sub: Receiv
             Op::RunUserShellCommand { command } => {
                 handlers::run_user_shell_command(&sess, sub.id.clone(), command).await;
             }
+            Op::ApplyUserPatch { call_id, patch } => {
+                handlers::apply_user_patch(&sess, sub.id.clone(), call_id, patch).await;
+            }
             Op::ResolveElicitation {
                 server_name,
                 request_id,
@@ -3543,8 +3562,10 @@ mod handlers {
     use crate::rollout::session_index;
     use crate::tasks::CompactTask;
     use crate::tasks::UndoTask;
+    use crate::tasks::UserPatchTask;
     use crate::tasks::UserShellCommandMode;
     use crate::tasks::UserShellCommandTask;
+    use crate::tasks::execute_user_patch;
     use crate::tasks::execute_user_shell_command;
     use codex_protocol::custom_prompts::CustomPrompt;
     use codex_protocol::protocol::CodexErrorInfo;
@@ -3704,6 +3725,39 @@ mod handlers {
         .await;
     }
 
+    pub async fn apply_user_patch(
+        sess: &Arc<Session>,
+        sub_id: String,
+        call_id: String,
+        patch: String,
+    ) {
+        if let Some((turn_context, cancellation_token)) =
+            sess.active_turn_context_and_cancellation_token().await
+        {
+            let session = Arc::clone(sess);
+            tokio::spawn(async move {
+                execute_user_patch(
+                    session,
+                    turn_context,
+                    call_id,
+                    patch,
+                    cancellation_token,
+                    UserShellCommandMode::ActiveTurnAuxiliary,
+                )
+                .await;
+            });
+            return;
+        }
+
+        let turn_context = sess.new_default_turn_with_sub_id(sub_id).await;
+        sess.spawn_task(
+            Arc::clone(&turn_context),
+            Vec::new(),
+            UserPatchTask::new(call_id, patch),
+        )
+        .await;
+    }
+
     pub async fn resolve_elicitation(
         sess: &Arc<Session>,
         server_name: String,
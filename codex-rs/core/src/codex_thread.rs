@@ -11,11 +11,13 @@ use codex_protocol::config_types::Personality;
 use codex_protocol::models::ContentItem;
 use codex_protocol::models::ResponseInputItem;
 use codex_protocol::models::ResponseItem;
+use codex_protocol::openai_models::ModelInfo;
 use codex_protocol::openai_models::ReasoningEffort;
 use codex_protocol::protocol::AskForApproval;
 use codex_protocol::protocol::SandboxPolicy;
 use codex_protocol::protocol::SessionSource;
 use codex_protocol::protocol::TokenUsage;
+use codex_protocol::protocol::TokenUsageInfo;
 use codex_protocol::user_input::UserInput;
 use std::path::PathBuf;
 use tokio::sync::watch;
@@ -84,10 +86,22 @@ impl CodexThread {
         self.codex.agent_status.clone()
     }
 
-    pub(crate) async fn total_token_usage(&self) -> Option<TokenUsage> {
+    pub async fn total_token_usage(&self) -> Option<TokenUsage> {
         self.codex.session.total_token_usage().await
     }
 
+    /// Cumulative usage and the usage of the last model response, once the
+    /// model has reported any.
+    pub async fn token_usage_info(&self) -> Option<TokenUsageInfo> {
+        self.codex.session.token_usage_info().await
+    }
+
+    /// Metadata of the model the next turn runs with, including its context
+    /// window and auto-compaction limit.
+    pub async fn model_info(&self) -> ModelInfo {
+        self.codex.model_info().await
+    }
+
     /// Records a user-role session-prefix message without creating a new user turn boundary.
     pub(crate) async fn inject_user_message_without_turn(&self, message: String) {
         let pending_item = ResponseInputItem::Message {
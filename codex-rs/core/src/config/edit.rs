@@ -821,6 +821,19 @@ impl ConfigEditsBuilder {
         self
     }
 
+    /// Enable or disable an MCP server without removing its `[mcp_servers.<name>]` block.
+    pub fn set_mcp_server_enabled(mut self, name: &str, enabled: bool) -> Self {
+        self.edits.push(ConfigEdit::SetPath {
+            segments: vec![
+                "mcp_servers".to_string(),
+                name.to_string(),
+                "enabled".to_string(),
+            ],
+            value: value(enabled),
+        });
+        self
+    }
+
     pub fn set_windows_sandbox_mode(mut self, mode: &str) -> Self {
         let segments = if let Some(profile) = self.profile.as_ref() {
             vec![
@@ -1684,6 +1697,32 @@ foo = { command = "cmd" , enabled = false }
         assert_eq!(contents, expected);
     }
 
+    #[test]
+    fn set_mcp_server_enabled_keeps_server_block() {
+        let tmp = tempdir().expect("tmpdir");
+        let codex_home = tmp.path();
+        std::fs::write(
+            codex_home.join(CONFIG_TOML_FILE),
+            r#"[mcp_servers.docs]
+command = "docs-server" # keep me
+"#,
+        )
+        .expect("seed config");
+
+        ConfigEditsBuilder::new(codex_home)
+            .set_mcp_server_enabled("docs", false)
+            .apply_blocking()
+            .expect("persist");
+
+        let contents =
+            std::fs::read_to_string(codex_home.join(CONFIG_TOML_FILE)).expect("read config");
+        let expected = r#"[mcp_servers.docs]
+command = "docs-server" # keep me
+enabled = false
+"#;
+        assert_eq!(contents, expected);
+    }
+
     #[test]
     fn blocking_clear_path_noop_when_missing() {
         let tmp = tempdir().expect("tmpdir");
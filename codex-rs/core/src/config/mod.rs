@@ -331,6 +331,10 @@ pub struct Config {
     /// Vector database settings used by analysis tools.
     pub vector_db: VectorDbConfig,
 
+    /// Extra gitignore-style patterns skipped when tools list or search the
+    /// workspace. See [`crate::workspace_ignore`].
+    pub ignore_globs: Vec<String>,
+
     /// Maximum number of agent threads that can be open concurrently.
     pub agent_max_threads: Option<usize>,
 
@@ -1158,6 +1162,10 @@ pub struct ConfigToml {
     /// Vector database settings used by analysis tools.
     pub vector_db: Option<VectorDbConfigToml>,
 
+    /// Extra gitignore-style patterns skipped when listing or searching the
+    /// workspace, on top of `.gitignore` and `.codexignore`.
+    pub ignore_globs: Option<Vec<String>>,
+
     /// Agent-related settings (thread limits, etc.).
     pub agents: Option<AgentsToml>,
 
@@ -2092,6 +2100,7 @@ impl Config {
             vector_db: cfg
                 .vector_db
                 .map_or_else(VectorDbConfig::default, VectorDbConfig::from),
+            ignore_globs: cfg.ignore_globs.unwrap_or_default(),
             agent_max_threads,
             agent_max_depth,
             agent_roles,
@@ -4729,6 +4738,8 @@ model_verbosity = "high"
                 feedback_enabled: true,
                 tui_alternate_screen: AltScreenMode::Auto,
                 vector_db: VectorDbConfig::default(),
+                ignore_globs: Vec::new(),
+                ignore_globs: Vec::new(),
                 tui_status_line: None,
                 tui_theme: None,
                 otel: OtelConfig::default(),
@@ -4853,6 +4864,7 @@ model_verbosity = "high"
             feedback_enabled: true,
             tui_alternate_screen: AltScreenMode::Auto,
             vector_db: VectorDbConfig::default(),
+            ignore_globs: Vec::new(),
             tui_status_line: None,
             tui_theme: None,
             otel: OtelConfig::default(),
@@ -4975,6 +4987,7 @@ model_verbosity = "high"
             feedback_enabled: true,
             tui_alternate_screen: AltScreenMode::Auto,
             vector_db: VectorDbConfig::default(),
+            ignore_globs: Vec::new(),
             tui_status_line: None,
             tui_theme: None,
             otel: OtelConfig::default(),
@@ -5083,6 +5096,7 @@ model_verbosity = "high"
             feedback_enabled: true,
             tui_alternate_screen: AltScreenMode::Auto,
             vector_db: VectorDbConfig::default(),
+            ignore_globs: Vec::new(),
             tui_status_line: None,
             tui_theme: None,
             otel: OtelConfig::default(),
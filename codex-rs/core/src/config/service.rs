@@ -225,6 +225,36 @@ impl ConfigService {
             .await
     }
 
+    /// Writes a value into a project config layer of `cwd`: the
+    /// `.codex/config.toml` in `cwd` itself, unless `params.file_path` names
+    /// the config file of another project layer loaded for `cwd`.
+    pub async fn write_project_value(
+        &self,
+        cwd: &AbsolutePathBuf,
+        params: ConfigValueWriteParams,
+    ) -> Result<ConfigWriteResponse, ConfigServiceError> {
+        let edits = vec![(params.key_path, params.value, params.merge_strategy)];
+        self.apply_project_edits(cwd, params.file_path, params.expected_version, edits)
+            .await
+    }
+
+    /// [`Self::batch_write`] into a project config layer of `cwd`, chosen
+    /// as by [`Self::write_project_value`].
+    pub async fn batch_write_project(
+        &self,
+        cwd: &AbsolutePathBuf,
+        params: ConfigBatchWriteParams,
+    ) -> Result<ConfigWriteResponse, ConfigServiceError> {
+        let edits = params
+            .edits
+            .into_iter()
+            .map(|edit| (edit.key_path, edit.value, edit.merge_strategy))
+            .collect();
+
+        self.apply_project_edits(cwd, params.file_path, params.expected_version, edits)
+            .await
+    }
+
     pub async fn load_user_saved_config(
         &self,
     ) -> Result<codex_app_server_protocol::UserSavedConfig, ConfigServiceError> {
@@ -281,49 +311,7 @@ impl ConfigService {
         }
 
         let mut user_config = user_layer.config.clone();
-        let mut parsed_segments = Vec::new();
-        let mut config_edits = Vec::new();
-
-        for (key_path, value, strategy) in edits.into_iter() {
-            let segments = parse_key_path(&key_path).map_err(|message| {
-                ConfigServiceError::write(ConfigWriteErrorCode::ConfigValidationError, message)
-            })?;
-            let original_value = value_at_path(&user_config, &segments).cloned();
-            let parsed_value = parse_value(value).map_err(|message| {
-                ConfigServiceError::write(ConfigWriteErrorCode::ConfigValidationError, message)
-            })?;
-
-            apply_merge(&mut user_config, &segments, parsed_value.as_ref(), strategy).map_err(
-                |err| match err {
-                    MergeError::PathNotFound => ConfigServiceError::write(
-                        ConfigWriteErrorCode::ConfigPathNotFound,
-                        "Path not found",
-                    ),
-                    MergeError::Validation(message) => ConfigServiceError::write(
-                        ConfigWriteErrorCode::ConfigValidationError,
-                        message,
-                    ),
-                },
-            )?;
-
-            let updated_value = value_at_path(&user_config, &segments).cloned();
-            if original_value != updated_value {
-                let edit = match updated_value {
-                    Some(value) => ConfigEdit::SetPath {
-                        segments: segments.clone(),
-                        value: toml_value_to_item(&value).map_err(|err| {
-                            ConfigServiceError::anyhow("failed to build config edits", err)
-                        })?,
-                    },
-                    None => ConfigEdit::ClearPath {
-                        segments: segments.clone(),
-                    },
-                };
-                config_edits.push(edit);
-            }
-
-            parsed_segments.push(segments);
-        }
+        let (config_edits, parsed_segments) = merge_edits(&mut user_config, edits)?;
 
         validate_config(&user_config).map_err(|err| {
             ConfigServiceError::write(
@@ -349,7 +337,8 @@ impl ConfigService {
                 .map_err(|err| ConfigServiceError::anyhow("failed to persist config.toml", err))?;
         }
 
-        let overridden = first_overridden_edit(&updated_layers, &effective, &parsed_segments);
+        let overridden =
+            first_overridden_edit(&updated_layers, &effective, &user_config, &parsed_segments);
         let status = overridden
             .as_ref()
             .map(|_| WriteStatus::OkOverridden)
@@ -372,6 +361,131 @@ impl ConfigService {
         })
     }
 
+    async fn apply_project_edits(
+        &self,
+        cwd: &AbsolutePathBuf,
+        file_path: Option<String>,
+        expected_version: Option<String>,
+        edits: Vec<(String, JsonValue, MergeStrategy)>,
+    ) -> Result<ConfigWriteResponse, ConfigServiceError> {
+        let layers = load_config_layers_state(
+            &self.codex_home,
+            Some(cwd.clone()),
+            &self.cli_overrides,
+            self.loader_overrides.clone(),
+            self.cloud_requirements.clone(),
+        )
+        .await
+        .map_err(|err| ConfigServiceError::io("failed to load configuration", err))?;
+
+        let cwd_folder = cwd
+            .join(".codex")
+            .map_err(|err| ConfigServiceError::io("failed to resolve project config path", err))?;
+        let dot_codex_folder = match file_path {
+            None => cwd_folder,
+            Some(path) => {
+                let provided_path = AbsolutePathBuf::from_absolute_path(PathBuf::from(path))
+                    .map_err(|err| {
+                        ConfigServiceError::io("failed to resolve project config path", err)
+                    })?;
+                // Only the config files of the project's layers for `cwd`,
+                // which all lie between the project root and `cwd`.
+                layers
+                    .get_layers(ConfigLayerStackOrdering::LowestPrecedenceFirst, true)
+                    .into_iter()
+                    .filter_map(|layer| match &layer.name {
+                        ConfigLayerSource::Project { dot_codex_folder } => {
+                            Some(dot_codex_folder.clone())
+                        }
+                        _ => None,
+                    })
+                    .chain(std::iter::once(cwd_folder))
+                    .find(|folder| {
+                        paths_match(folder.as_path().join(CONFIG_TOML_FILE), &provided_path)
+                    })
+                    .ok_or_else(|| {
+                        ConfigServiceError::write(
+                            ConfigWriteErrorCode::ConfigLayerReadonly,
+                            "Only writes to a project config between the project root and cwd are allowed",
+                        )
+                    })?
+            }
+        };
+        if paths_match(&self.codex_home, &dot_codex_folder) {
+            return Err(ConfigServiceError::write(
+                ConfigWriteErrorCode::ConfigLayerReadonly,
+                "The project config of cwd is the user config",
+            ));
+        }
+        let config_path = dot_codex_folder
+            .join(CONFIG_TOML_FILE)
+            .map_err(|err| ConfigServiceError::io("failed to resolve project config path", err))?;
+
+        let name = ConfigLayerSource::Project {
+            dot_codex_folder: dot_codex_folder.clone(),
+        };
+        let project_layer = layers
+            .get_layers(ConfigLayerStackOrdering::LowestPrecedenceFirst, true)
+            .into_iter()
+            .find(|layer| layer.name == name);
+        // A folder without a layer has no config yet, so no version to match.
+        if let Some(expected) = expected_version.as_deref()
+            && project_layer.is_none_or(|layer| expected != layer.version)
+        {
+            return Err(ConfigServiceError::write(
+                ConfigWriteErrorCode::ConfigVersionConflict,
+                "Configuration was modified since last read. Fetch latest version and retry.",
+            ));
+        }
+
+        let mut project_config = project_layer
+            .map(|layer| layer.config.clone())
+            .unwrap_or_else(|| TomlValue::Table(toml::map::Map::new()));
+        let (config_edits, parsed_segments) = merge_edits(&mut project_config, edits)?;
+
+        validate_config(&project_config).map_err(|err| {
+            ConfigServiceError::write(
+                ConfigWriteErrorCode::ConfigValidationError,
+                format!("Invalid configuration: {err}"),
+            )
+        })?;
+
+        let updated_layers = layers.with_project_config(&dot_codex_folder, project_config.clone());
+        let effective = updated_layers.effective_config();
+        validate_config(&effective).map_err(|err| {
+            ConfigServiceError::write(
+                ConfigWriteErrorCode::ConfigValidationError,
+                format!("Invalid configuration: {err}"),
+            )
+        })?;
+
+        if !config_edits.is_empty() {
+            ConfigEditsBuilder::new(dot_codex_folder.as_path())
+                .with_edits(config_edits)
+                .apply()
+                .await
+                .map_err(|err| ConfigServiceError::anyhow("failed to persist config.toml", err))?;
+        }
+
+        let overridden = first_overridden_edit(
+            &updated_layers,
+            &effective,
+            &project_config,
+            &parsed_segments,
+        );
+        let status = overridden
+            .as_ref()
+            .map(|_| WriteStatus::OkOverridden)
+            .unwrap_or(WriteStatus::Ok);
+
+        Ok(ConfigWriteResponse {
+            status,
+            version: ConfigLayerEntry::new(name, project_config).version,
+            file_path: config_path,
+            overridden_metadata: overridden,
+        })
+    }
+
     /// Loads a "thread-agnostic" config, which means the config layers do not
     /// include any in-repo .codex/ folders because there is no cwd/project root
     /// associated with this query.
@@ -388,6 +502,58 @@ impl ConfigService {
     }
 }
 
+/// Merges `edits` into `config` and returns the edits to persist along with
+/// the parsed key path of each.
+fn merge_edits(
+    config: &mut TomlValue,
+    edits: Vec<(String, JsonValue, MergeStrategy)>,
+) -> Result<(Vec<ConfigEdit>, Vec<Vec<String>>), ConfigServiceError> {
+    let mut parsed_segments = Vec::new();
+    let mut config_edits = Vec::new();
+
+    for (key_path, value, strategy) in edits.into_iter() {
+        let segments = parse_key_path(&key_path).map_err(|message| {
+            ConfigServiceError::write(ConfigWriteErrorCode::ConfigValidationError, message)
+        })?;
+        let original_value = value_at_path(config, &segments).cloned();
+        let parsed_value = parse_value(value).map_err(|message| {
+            ConfigServiceError::write(ConfigWriteErrorCode::ConfigValidationError, message)
+        })?;
+
+        apply_merge(config, &segments, parsed_value.as_ref(), strategy).map_err(
+            |err| match err {
+                MergeError::PathNotFound => ConfigServiceError::write(
+                    ConfigWriteErrorCode::ConfigPathNotFound,
+                    "Path not found",
+                ),
+                MergeError::Validation(message) => {
+                    ConfigServiceError::write(ConfigWriteErrorCode::ConfigValidationError, message)
+                }
+            },
+        )?;
+
+        let updated_value = value_at_path(config, &segments).cloned();
+        if original_value != updated_value {
+            let edit = match updated_value {
+                Some(value) => ConfigEdit::SetPath {
+                    segments: segments.clone(),
+                    value: toml_value_to_item(&value).map_err(|err| {
+                        ConfigServiceError::anyhow("failed to build config edits", err)
+                    })?,
+                },
+                None => ConfigEdit::ClearPath {
+                    segments: segments.clone(),
+                },
+            };
+            config_edits.push(edit);
+        }
+
+        parsed_segments.push(segments);
+    }
+
+    Ok((config_edits, parsed_segments))
+}
+
 async fn create_empty_user_layer(
     config_toml: &AbsolutePathBuf,
 ) -> Result<ConfigLayerEntry, ConfigServiceError> {
@@ -642,19 +808,17 @@ fn override_message(layer: &ConfigLayerSource) -> String {
 fn compute_override_metadata(
     layers: &ConfigLayerStack,
     effective: &TomlValue,
+    written: &TomlValue,
     segments: &[String],
 ) -> Option<OverriddenMetadata> {
-    let user_value = match layers.get_user_layer() {
-        Some(user_layer) => value_at_path(&user_layer.config, segments),
-        None => return None,
-    };
+    let written_value = value_at_path(written, segments);
     let effective_value = value_at_path(effective, segments);
 
-    if user_value.is_some() && user_value == effective_value {
+    if written_value.is_some() && written_value == effective_value {
         return None;
     }
 
-    if user_value.is_none() && effective_value.is_none() {
+    if written_value.is_none() && effective_value.is_none() {
         return None;
     }
 
@@ -673,10 +837,11 @@ fn compute_override_metadata(
 fn first_overridden_edit(
     layers: &ConfigLayerStack,
     effective: &TomlValue,
+    written: &TomlValue,
     edits: &[Vec<String>],
 ) -> Option<OverriddenMetadata> {
     for segments in edits {
-        if let Some(meta) = compute_override_metadata(layers, effective, segments) {
+        if let Some(meta) = compute_override_metadata(layers, effective, written, segments) {
             return Some(meta);
         }
     }
@@ -1047,6 +1212,61 @@ personality = true
         );
     }
 
+    #[tokio::test]
+    async fn write_project_value_targets_cwd_project_layer() {
+        let codex_home = tempdir().expect("tempdir");
+        std::fs::write(codex_home.path().join(CONFIG_TOML_FILE), "model = \"user\"").unwrap();
+        let project = tempdir().expect("tempdir");
+        let cwd = AbsolutePathBuf::from_absolute_path(project.path()).unwrap();
+
+        let service = ConfigService::new_with_defaults(codex_home.path().to_path_buf());
+        let response = service
+            .write_project_value(
+                &cwd,
+                ConfigValueWriteParams {
+                    file_path: None,
+                    key_path: "model".to_string(),
+                    value: serde_json::json!("project"),
+                    merge_strategy: MergeStrategy::Replace,
+                    expected_version: None,
+                },
+            )
+            .await
+            .expect("write succeeds");
+
+        let project_config = project.path().join(".codex").join(CONFIG_TOML_FILE);
+        assert_eq!(response.file_path.as_path(), project_config.as_path());
+        let contents = std::fs::read_to_string(&project_config).expect("read project config");
+        assert!(contents.contains("model = \"project\""));
+        let user_contents =
+            std::fs::read_to_string(codex_home.path().join(CONFIG_TOML_FILE)).expect("read config");
+        assert_eq!(user_contents, "model = \"user\"");
+
+        let error = service
+            .write_project_value(
+                &cwd,
+                ConfigValueWriteParams {
+                    file_path: Some(
+                        codex_home
+                            .path()
+                            .join(CONFIG_TOML_FILE)
+                            .display()
+                            .to_string(),
+                    ),
+                    key_path: "model".to_string(),
+                    value: serde_json::json!("elsewhere"),
+                    merge_strategy: MergeStrategy::Replace,
+                    expected_version: None,
+                },
+            )
+            .await
+            .expect_err("user config is not a project layer");
+        assert_eq!(
+            error.write_error_code(),
+            Some(ConfigWriteErrorCode::ConfigLayerReadonly)
+        );
+    }
+
     #[tokio::test]
     async fn invalid_user_value_rejected_even_if_overridden_by_managed() {
         let tmp = tempdir().expect("tempdir");
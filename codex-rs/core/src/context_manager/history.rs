@@ -8,6 +8,7 @@ use crate::truncate::approx_token_count;
 use crate::truncate::approx_tokens_from_byte_count_i64;
 use crate::truncate::truncate_function_output_items_with_policy;
 use crate::truncate::truncate_text;
+use crate::user_patch::is_user_patch_text;
 use crate::user_shell_command::is_user_shell_command_text;
 use codex_protocol::models::BaseInstructions;
 use codex_protocol::models::ContentItem;
@@ -567,7 +568,10 @@ pub(crate) fn is_user_turn_boundary(item: &ResponseItem) -> bool {
     for content_item in content {
         match content_item {
             ContentItem::InputText { text } => {
-                if is_session_prefix(text) || is_user_shell_command_text(text) {
+                if is_session_prefix(text)
+                    || is_user_shell_command_text(text)
+                    || is_user_patch_text(text)
+                {
                     return false;
                 }
             }
@@ -12,6 +12,7 @@ use codex_async_utils::CancelErr;
 use codex_protocol::ThreadId;
 use codex_protocol::protocol::CodexErrorInfo;
 use codex_protocol::protocol::ErrorEvent;
+use codex_protocol::protocol::McpStartupFailure;
 use codex_protocol::protocol::RateLimitSnapshot;
 use reqwest::StatusCode;
 use serde_json;
@@ -158,6 +159,10 @@ pub enum CodexErr {
     #[error("{0}")]
     RefreshTokenFailed(RefreshTokenFailedError),
 
+    /// One or more MCP servers marked `required` failed to start.
+    #[error("{0}")]
+    McpStartupFailed(McpStartupFailedError),
+
     #[error("Fatal error: {0}")]
     Fatal(String),
 
@@ -213,6 +218,7 @@ impl CodexErr {
             | CodexErr::Spawn
             | CodexErr::SessionConfiguredNotFirstEvent
             | CodexErr::UsageLimitReached(_)
+            | CodexErr::McpStartupFailed(_)
             | CodexErr::ServerOverloaded => false,
             CodexErr::Stream(..)
             | CodexErr::Timeout
@@ -261,6 +267,19 @@ impl std::fmt::Display for ResponseStreamFailed {
     }
 }
 
+#[derive(Debug, Clone, Error)]
+#[error(
+    "required MCP servers failed to initialize: {}",
+    failures
+        .iter()
+        .map(|failure| format!("{}: {}", failure.server, failure.error))
+        .collect::<Vec<_>>()
+        .join("; ")
+)]
+pub struct McpStartupFailedError {
+    pub failures: Vec<McpStartupFailure>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Error)]
 #[error("{message}")]
 pub struct RefreshTokenFailedError {
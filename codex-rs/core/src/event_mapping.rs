@@ -21,6 +21,7 @@ use uuid::Uuid;
 use crate::instructions::SkillInstructions;
 use crate::instructions::UserInstructions;
 use crate::session_prefix::is_session_prefix;
+use crate::user_patch::is_user_patch_text;
 use crate::user_shell_command::is_user_shell_command_text;
 use crate::web_search::web_search_action_detail;
 
@@ -44,7 +45,10 @@ fn parse_user_message(message: &[ContentItem]) -> Option<UserMessageItem> {
                 {
                     continue;
                 }
-                if is_session_prefix(text) || is_user_shell_command_text(text) {
+                if is_session_prefix(text)
+                    || is_user_shell_command_text(text)
+                    || is_user_patch_text(text)
+                {
                     return None;
                 }
                 content.push(UserInput::Text {
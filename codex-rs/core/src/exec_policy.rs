@@ -17,6 +17,7 @@ use codex_execpolicy::Policy;
 use codex_execpolicy::PolicyParser;
 use codex_execpolicy::RuleMatch;
 use codex_execpolicy::blocking_append_allow_prefix_rule;
+use codex_execpolicy::blocking_remove_allow_prefix_rule;
 use codex_protocol::approvals::ExecPolicyAmendment;
 use codex_protocol::protocol::AskForApproval;
 use codex_protocol::protocol::SandboxPolicy;
@@ -295,12 +296,79 @@ impl ExecPolicyManager {
     }
 }
 
+/// Removes the allow rule that accepting `amendment` appended to
+/// `$CODEX_HOME/rules/default.rules`, so sessions started afterwards prompt
+/// again. Sessions that already loaded the rule keep it. Returns whether the
+/// rule was present.
+pub async fn revoke_execpolicy_amendment(
+    codex_home: &Path,
+    amendment: &ExecPolicyAmendment,
+) -> Result<bool, ExecPolicyUpdateError> {
+    let policy_path = default_policy_path(codex_home);
+    let prefix = amendment.command.clone();
+    spawn_blocking({
+        let policy_path = policy_path.clone();
+        move || blocking_remove_allow_prefix_rule(&policy_path, &prefix)
+    })
+    .await
+    .map_err(|source| ExecPolicyUpdateError::JoinBlockingTask { source })?
+    .map_err(|source| ExecPolicyUpdateError::AppendRule {
+        path: policy_path,
+        source,
+    })
+}
+
 impl Default for ExecPolicyManager {
     fn default() -> Self {
         Self::new(Arc::new(Policy::empty()))
     }
 }
 
+/// Outcome of assessing a command before exec, without running it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecApprovalPreview {
+    /// Runs without asking. `bypass_sandbox` is set when a rule allows the
+    /// command outright, so it runs outside the sandbox.
+    Allowed {
+        bypass_sandbox: bool,
+    },
+    NeedsApproval {
+        reason: Option<String>,
+    },
+    Forbidden {
+        reason: String,
+    },
+}
+
+/// Assesses `command` against the rules in `config_stack` and the given
+/// policies the same way an exec tool call is assessed before it runs.
+pub async fn preview_exec_approval(
+    config_stack: &ConfigLayerStack,
+    command: &[String],
+    approval_policy: AskForApproval,
+    sandbox_policy: &SandboxPolicy,
+) -> Result<ExecApprovalPreview, ExecPolicyError> {
+    let manager = ExecPolicyManager::load(config_stack).await?;
+    let requirement = manager
+        .create_exec_approval_requirement_for_command(ExecApprovalRequest {
+            command,
+            approval_policy,
+            sandbox_policy,
+            sandbox_permissions: SandboxPermissions::UseDefault,
+            prefix_rule: None,
+        })
+        .await;
+    Ok(match requirement {
+        ExecApprovalRequirement::Skip { bypass_sandbox, .. } => {
+            ExecApprovalPreview::Allowed { bypass_sandbox }
+        }
+        ExecApprovalRequirement::NeedsApproval { reason, .. } => {
+            ExecApprovalPreview::NeedsApproval { reason }
+        }
+        ExecApprovalRequirement::Forbidden { reason } => ExecApprovalPreview::Forbidden { reason },
+    })
+}
+
 pub async fn check_execpolicy_for_warnings(
     config_stack: &ConfigLayerStack,
 ) -> Result<Option<ExecPolicyError>, ExecPolicyError> {
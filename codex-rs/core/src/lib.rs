@@ -84,6 +84,7 @@ mod seatbelt_permissions;
 mod thread_manager;
 pub mod web_search;
 pub mod windows_sandbox_read_grants;
+pub mod workspace_ignore;
 pub use thread_manager::NewThread;
 pub use thread_manager::ThreadManager;
 #[deprecated(note = "use ThreadManager")]
@@ -122,6 +123,7 @@ pub use rollout::find_conversation_path_by_id_str;
 pub use rollout::find_thread_name_by_id;
 pub use rollout::find_thread_path_by_id_str;
 pub use rollout::find_thread_path_by_name_str;
+pub use rollout::last_turn_id;
 pub use rollout::list::Cursor;
 pub use rollout::list::ThreadItem;
 pub use rollout::list::ThreadSortKey;
@@ -129,12 +131,23 @@ pub use rollout::list::ThreadsPage;
 pub use rollout::list::parse_cursor;
 pub use rollout::list::read_head_for_summary;
 pub use rollout::list::read_session_meta_line;
+pub use rollout::nth_user_message_after_turn;
 pub use rollout::policy::EventPersistenceMode;
+pub use rollout::postgres::PersistedApproval;
+pub use rollout::postgres::PersistedUsage;
+pub use rollout::postgres::RolloutLinePages as PostgresRolloutLinePages;
+pub use rollout::postgres::WebStateReconciliation;
+pub use rollout::postgres::WebStateStore;
+pub use rollout::postgres::load_rollout_items as load_postgres_rollout_items;
+pub use rollout::postgres::load_rollout_lines as load_postgres_rollout_lines;
 pub use rollout::rollout_date_parts;
+pub use rollout::session_index::append_thread_name;
 pub use rollout::session_index::find_thread_names_by_ids;
+pub use rollout::user_turns_after_turn;
 mod function_tool;
 mod state;
 mod tasks;
+mod user_patch;
 mod user_shell_command;
 pub mod util;
 pub(crate) use codex_protocol::protocol;
@@ -145,10 +158,14 @@ pub(crate) use codex_shell_command::parse_command;
 pub(crate) use codex_shell_command::powershell;
 
 pub use client::X_CODEX_TURN_METADATA_HEADER;
+pub use exec_policy::ExecApprovalPreview;
 pub use exec_policy::ExecPolicyError;
+pub use exec_policy::ExecPolicyUpdateError;
 pub use exec_policy::check_execpolicy_for_warnings;
 pub use exec_policy::format_exec_policy_error_with_source;
 pub use exec_policy::load_exec_policy;
+pub use exec_policy::preview_exec_approval;
+pub use exec_policy::revoke_execpolicy_amendment;
 pub use file_watcher::FileWatcherEvent;
 pub use safety::get_platform_sandbox;
 pub use tools::spec::parse_tool_input_schema;
@@ -11,8 +11,11 @@ use async_channel::unbounded;
 use codex_protocol::mcp::Resource;
 use codex_protocol::mcp::ResourceTemplate;
 use codex_protocol::mcp::Tool;
+use codex_protocol::protocol::EventMsg;
 use codex_protocol::protocol::McpListToolsResponseEvent;
+use codex_protocol::protocol::McpStartupFailure;
 use codex_protocol::protocol::SandboxPolicy;
+use futures::future::join_all;
 use serde_json::Value;
 
 use crate::AuthManager;
@@ -191,8 +194,101 @@ pub async fn collect_mcp_snapshot(config: &Config) -> McpListToolsResponseEvent
     let auth_status_entries =
         compute_auth_statuses(mcp_servers.iter(), config.mcp_oauth_credentials_store_mode).await;
 
+    collect_mcp_snapshot_for_servers(config, auth.as_ref(), &mcp_servers, auth_status_entries)
+        .await
+        .0
+}
+
+/// What [`collect_mcp_snapshot_with_timeout`] found.
+#[derive(Debug, Clone)]
+pub struct McpSnapshotWithTimeout {
+    pub snapshot: McpListToolsResponseEvent,
+    /// Sorted names of the servers that did not answer in time
+    pub timed_out: Vec<String>,
+    /// Servers that failed to start, with their startup errors
+    pub failed: Vec<McpStartupFailure>,
+}
+
+/// Like [`collect_mcp_snapshot`], but starts every server in its own
+/// connection manager and gives each at most `per_server_timeout`, so one
+/// slow or hung server does not hold up the rest. Returns the merged snapshot
+/// with the servers that timed out or failed to start; auth statuses are
+/// reported for all servers. Disabled servers are not started.
+pub async fn collect_mcp_snapshot_with_timeout(
+    config: &Config,
+    per_server_timeout: Duration,
+) -> McpSnapshotWithTimeout {
+    let auth_manager = AuthManager::shared(
+        config.codex_home.clone(),
+        false,
+        config.cli_auth_credentials_store_mode,
+    );
+    let auth = auth_manager.auth().await;
+    let mcp_servers = effective_mcp_servers(config, auth.as_ref());
+    let auth_status_entries =
+        compute_auth_statuses(mcp_servers.iter(), config.mcp_oauth_credentials_store_mode).await;
+
+    let mut snapshot = McpListToolsResponseEvent {
+        tools: HashMap::new(),
+        resources: HashMap::new(),
+        resource_templates: HashMap::new(),
+        auth_statuses: auth_status_entries
+            .iter()
+            .map(|(name, entry)| (name.clone(), entry.auth_status))
+            .collect(),
+    };
+
+    let enabled_servers = mcp_servers.iter().filter(|(_, server)| server.enabled);
+    let results = join_all(enabled_servers.map(|(name, server)| {
+        let servers = HashMap::from([(name.clone(), server.clone())]);
+        let entries = auth_status_entries
+            .get(name)
+            .map(|entry| (name.clone(), entry.clone()))
+            .into_iter()
+            .collect();
+        let auth = auth.as_ref();
+        async move {
+            let result = tokio::time::timeout(
+                per_server_timeout,
+                collect_mcp_snapshot_for_servers(config, auth, &servers, entries),
+            )
+            .await;
+            (name.clone(), result)
+        }
+    }))
+    .await;
+
+    let mut timed_out = Vec::new();
+    let mut failed = Vec::new();
+    for (name, result) in results {
+        match result {
+            Ok((server_snapshot, server_failures)) => {
+                snapshot.tools.extend(server_snapshot.tools);
+                snapshot.resources.extend(server_snapshot.resources);
+                snapshot
+                    .resource_templates
+                    .extend(server_snapshot.resource_templates);
+                failed.extend(server_failures);
+            }
+            Err(_) => timed_out.push(name),
+        }
+    }
+    timed_out.sort();
+    failed.sort_by(|a, b| a.server.cmp(&b.server));
+    McpSnapshotWithTimeout {
+        snapshot,
+        timed_out,
+        failed,
+    }
+}
+
+async fn collect_mcp_snapshot_for_servers(
+    config: &Config,
+    auth: Option<&CodexAuth>,
+    mcp_servers: &HashMap<String, McpServerConfig>,
+    auth_status_entries: HashMap<String, crate::mcp::auth::McpAuthStatusEntry>,
+) -> (McpListToolsResponseEvent, Vec<McpStartupFailure>) {
     let (tx_event, rx_event) = unbounded();
-    drop(rx_event);
 
     // Use ReadOnly sandbox policy for MCP snapshot collection (safest default)
     let sandbox_state = SandboxState {
@@ -203,23 +299,30 @@ pub async fn collect_mcp_snapshot(config: &Config) -> McpListToolsResponseEvent
     };
 
     let (mcp_connection_manager, cancel_token) = McpConnectionManager::new(
-        &mcp_servers,
+        mcp_servers,
         config.mcp_oauth_credentials_store_mode,
         auth_status_entries.clone(),
         &config.permissions.approval_policy,
         tx_event,
         sandbox_state,
         config.codex_home.clone(),
-        codex_apps_tools_cache_key(auth.as_ref()),
+        codex_apps_tools_cache_key(auth),
     )
     .await;
+    // Cancel on drop too, so a caller's timeout also tears down the servers.
+    let _cancel_guard = cancel_token.drop_guard();
 
     let snapshot =
         collect_mcp_snapshot_from_manager(&mcp_connection_manager, auth_status_entries).await;
-
-    cancel_token.cancel();
-
-    snapshot
+    // Every server has started or failed by now; the summary follows.
+    let mut failed = Vec::new();
+    while let Ok(event) = rx_event.recv().await {
+        if let EventMsg::McpStartupComplete(summary) = event.msg {
+            failed = summary.failed;
+            break;
+        }
+    }
+    (snapshot, failed)
 }
 
 pub fn split_qualified_tool_name(qualified_name: &str) -> Option<(String, String)> {
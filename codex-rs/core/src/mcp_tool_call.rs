@@ -10,10 +10,12 @@ use crate::codex::TurnContext;
 use crate::config::types::AppToolApproval;
 use crate::connectors;
 use crate::mcp::CODEX_APPS_MCP_SERVER_NAME;
+use crate::mcp::auth::compute_auth_statuses;
 use crate::protocol::EventMsg;
 use crate::protocol::McpInvocation;
 use crate::protocol::McpToolCallBeginEvent;
 use crate::protocol::McpToolCallEndEvent;
+use crate::protocol::McpToolCallErrorDetails;
 use codex_protocol::mcp::CallToolResult;
 use codex_protocol::models::FunctionCallOutputBody;
 use codex_protocol::models::FunctionCallOutputPayload;
@@ -26,6 +28,7 @@ use codex_protocol::request_user_input::RequestUserInputArgs;
 use codex_protocol::request_user_input::RequestUserInputQuestion;
 use codex_protocol::request_user_input::RequestUserInputQuestionOption;
 use codex_protocol::request_user_input::RequestUserInputResponse;
+use codex_rmcp_client::McpRequestError;
 use rmcp::model::ToolAnnotations;
 use serde::Serialize;
 use std::sync::Arc;
@@ -124,8 +127,12 @@ pub(crate) async fn handle_mcp_tool_call(
                 let start = Instant::now();
                 let result = sess
                     .call_tool(&server, &tool_name, arguments_value.clone())
-                    .await
-                    .map_err(|e| format!("tool call error: {e:?}"));
+                    .await;
+                let error_details = match &result {
+                    Ok(_) => None,
+                    Err(err) => Some(mcp_tool_call_error_details(turn_context, &server, err).await),
+                };
+                let result = result.map_err(|e| format!("tool call error: {e:?}"));
                 let result = sanitize_mcp_tool_result_for_model(
                     turn_context
                         .model_info
@@ -141,6 +148,7 @@ pub(crate) async fn handle_mcp_tool_call(
                     invocation,
                     duration: start.elapsed(),
                     result: result.clone(),
+                    error_details,
                 });
                 notify_mcp_tool_call_event(
                     sess.as_ref(),
@@ -193,8 +201,12 @@ pub(crate) async fn handle_mcp_tool_call(
     // Perform the tool call.
     let result = sess
         .call_tool(&server, &tool_name, arguments_value.clone())
-        .await
-        .map_err(|e| format!("tool call error: {e:?}"));
+        .await;
+    let error_details = match &result {
+        Ok(_) => None,
+        Err(err) => Some(mcp_tool_call_error_details(turn_context, &server, err).await),
+    };
+    let result = result.map_err(|e| format!("tool call error: {e:?}"));
     let result = sanitize_mcp_tool_result_for_model(
         turn_context
             .model_info
@@ -210,6 +222,7 @@ pub(crate) async fn handle_mcp_tool_call(
         invocation,
         duration: start.elapsed(),
         result: result.clone(),
+        error_details,
     });
 
     notify_mcp_tool_call_event(sess.as_ref(), turn_context, tool_call_end_event.clone()).await;
@@ -223,6 +236,43 @@ pub(crate) async fn handle_mcp_tool_call(
     ResponseInputItem::McpToolCallOutput { call_id, result }
 }
 
+/// Recovers the structured MCP error (code and data) from a failed tool call
+/// and records the server's auth status at the time of the failure, so clients
+/// can tell auth failures apart from tool errors.
+async fn mcp_tool_call_error_details(
+    turn_context: &TurnContext,
+    server: &str,
+    err: &anyhow::Error,
+) -> McpToolCallErrorDetails {
+    let (code, data) = match err
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<McpRequestError>())
+        .and_then(|failed| failed.error.as_ref())
+    {
+        Some(error) => (Some(i64::from(error.code.0)), error.data.clone()),
+        None => (None, None),
+    };
+
+    let config = &turn_context.config;
+    let server_name = server.to_string();
+    let auth_status = match config.mcp_servers.get().get(server) {
+        Some(server_config) => compute_auth_statuses(
+            std::iter::once((&server_name, server_config)),
+            config.mcp_oauth_credentials_store_mode,
+        )
+        .await
+        .remove(server)
+        .map(|entry| entry.auth_status),
+        None => None,
+    };
+
+    McpToolCallErrorDetails {
+        code,
+        data,
+        auth_status,
+    }
+}
+
 fn sanitize_mcp_tool_result_for_model(
     supports_image_input: bool,
     result: Result<CallToolResult, String>,
@@ -607,6 +657,7 @@ async fn notify_mcp_tool_call_skip(
         invocation,
         duration: Duration::ZERO,
         result: Err(message.clone()),
+        error_details: None,
     });
     notify_mcp_tool_call_event(sess, turn_context, tool_call_end_event).await;
     Err(message)
@@ -2,9 +2,14 @@ use std::io::ErrorKind;
 use std::path::Path;
 
 use crate::error::CodexErr;
+use crate::error::McpStartupFailedError;
 use crate::rollout::SESSIONS_SUBDIR;
 
 pub(crate) fn map_session_init_error(err: &anyhow::Error, codex_home: &Path) -> CodexErr {
+    if let Some(mcp_err) = err.downcast_ref::<McpStartupFailedError>() {
+        return CodexErr::McpStartupFailed(mcp_err.clone());
+    }
+
     if let Some(mapped) = err
         .chain()
         .filter_map(|cause| cause.downcast_ref::<std::io::Error>())
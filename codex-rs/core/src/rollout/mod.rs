@@ -27,6 +27,9 @@ pub use recorder::RolloutRecorder;
 pub use recorder::RolloutRecorderParams;
 pub use session_index::find_thread_name_by_id;
 pub use session_index::find_thread_path_by_name_str;
+pub use truncation::last_turn_id;
+pub use truncation::nth_user_message_after_turn;
+pub use truncation::user_turns_after_turn;
 
 #[cfg(test)]
 pub mod tests;
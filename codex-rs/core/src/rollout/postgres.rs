@@ -1,8 +1,12 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::io::Error as IoError;
 use std::io::ErrorKind;
 
 use codex_protocol::ThreadId;
 use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use codex_protocol::protocol::TokenUsage;
 use sqlx::PgPool;
 use sqlx::Postgres;
 use sqlx::QueryBuilder;
@@ -67,6 +71,107 @@ async fn ensure_schema(pool: &PgPool) -> std::io::Result<()> {
     .await
     .map_err(|err| IoError::other(format!("failed to ensure rollout index: {err}")))?;
 
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS codex_web_pending_approvals (
+            approval_id TEXT PRIMARY KEY,
+            instance_id TEXT NOT NULL,
+            thread_id UUID NOT NULL,
+            turn_id TEXT NOT NULL,
+            event_type TEXT NOT NULL,
+            payload JSONB NOT NULL,
+            created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        )
+        "#,
+    )
+    .execute(pool)
+    .await
+    .map_err(|err| {
+        IoError::other(format!(
+            "failed to ensure codex_web_pending_approvals table: {err}"
+        ))
+    })?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS codex_web_pending_turns (
+            thread_id UUID NOT NULL,
+            turn_id TEXT NOT NULL,
+            instance_id TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'running',
+            updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            PRIMARY KEY (thread_id, turn_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await
+    .map_err(|err| {
+        IoError::other(format!(
+            "failed to ensure codex_web_pending_turns table: {err}"
+        ))
+    })?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS codex_web_thread_prefs (
+            thread_id UUID PRIMARY KEY,
+            pinned BOOLEAN NOT NULL DEFAULT FALSE,
+            updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+        )
+        "#,
+    )
+    .execute(pool)
+    .await
+    .map_err(|err| {
+        IoError::other(format!(
+            "failed to ensure codex_web_thread_prefs table: {err}"
+        ))
+    })?;
+
+    sqlx::query(
+        r#"
+        ALTER TABLE codex_web_thread_prefs
+        ADD COLUMN IF NOT EXISTS notifications_muted BOOLEAN NOT NULL DEFAULT FALSE
+        "#,
+    )
+    .execute(pool)
+    .await
+    .map_err(|err| {
+        IoError::other(format!(
+            "failed to ensure codex_web_thread_prefs.notifications_muted column: {err}"
+        ))
+    })?;
+
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS codex_web_usage (
+            id BIGSERIAL PRIMARY KEY,
+            thread_id UUID NOT NULL,
+            model TEXT NOT NULL,
+            recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+            input_tokens BIGINT NOT NULL,
+            cached_input_tokens BIGINT NOT NULL,
+            output_tokens BIGINT NOT NULL,
+            reasoning_output_tokens BIGINT NOT NULL,
+            total_tokens BIGINT NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await
+    .map_err(|err| IoError::other(format!("failed to ensure codex_web_usage table: {err}")))?;
+
+    sqlx::query(
+        r#"
+        CREATE INDEX IF NOT EXISTS codex_web_usage_recorded_at_idx
+        ON codex_web_usage(recorded_at)
+        "#,
+    )
+    .execute(pool)
+    .await
+    .map_err(|err| IoError::other(format!("failed to ensure usage index: {err}")))?;
+
     Ok(())
 }
 
@@ -114,7 +219,8 @@ pub(crate) async fn append_rollout_items(
     Ok(())
 }
 
-pub(crate) async fn load_rollout_items(thread_id: ThreadId) -> std::io::Result<Vec<RolloutItem>> {
+/// Load every persisted rollout item for `thread_id`, oldest first.
+pub async fn load_rollout_items(thread_id: ThreadId) -> std::io::Result<Vec<RolloutItem>> {
     let pool = connect_rollout_pool().await?;
     let thread_uuid = thread_uuid(thread_id)?;
 
@@ -148,6 +254,536 @@ pub(crate) async fn load_rollout_items(thread_id: ThreadId) -> std::io::Result<V
     Ok(items)
 }
 
+/// Load every persisted rollout item for `thread_id` as rollout-file lines,
+/// oldest first, stamped with the time each row was stored.
+pub async fn load_rollout_lines(thread_id: ThreadId) -> std::io::Result<Vec<RolloutLine>> {
+    let pool = connect_rollout_pool().await?;
+    let thread_uuid = thread_uuid(thread_id)?;
+
+    let rows: Vec<(String, Json<serde_json::Value>)> = sqlx::query_as(
+        r#"
+        SELECT to_char(created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"'), item
+        FROM codex_rollout_items
+        WHERE thread_id = $1
+        ORDER BY id ASC
+        "#,
+    )
+    .bind(thread_uuid)
+    .fetch_all(&pool)
+    .await
+    .map_err(|err| IoError::other(format!("failed to load rollout items from Postgres: {err}")))?;
+
+    if rows.is_empty() {
+        return Err(IoError::new(
+            ErrorKind::NotFound,
+            format!("no rollout history found in Postgres for thread {thread_id}"),
+        ));
+    }
+
+    rows.into_iter()
+        .map(|(timestamp, Json(value))| {
+            let item: RolloutItem = serde_json::from_value(value)
+                .map_err(|err| IoError::other(format!("failed to decode rollout item: {err}")))?;
+            Ok(RolloutLine { timestamp, item })
+        })
+        .collect()
+}
+
+/// Reads the persisted rollout lines of one thread a page at a time, oldest
+/// first, so a long thread never has to be held in memory at once.
+pub struct RolloutLinePages {
+    pool: PgPool,
+    thread_uuid: Uuid,
+    /// Row id of the last line returned, so pages stay stable while the
+    /// thread keeps appending.
+    after_id: i64,
+}
+
+impl RolloutLinePages {
+    pub async fn open(thread_id: ThreadId) -> std::io::Result<Self> {
+        Ok(Self {
+            pool: connect_rollout_pool().await?,
+            thread_uuid: thread_uuid(thread_id)?,
+            after_id: 0,
+        })
+    }
+
+    /// The next `limit` lines, or none once every line has been returned.
+    pub async fn next_page(&mut self, limit: i64) -> std::io::Result<Vec<RolloutLine>> {
+        let rows: Vec<(i64, String, Json<serde_json::Value>)> = sqlx::query_as(
+            r#"
+            SELECT id,
+                   to_char(created_at AT TIME ZONE 'UTC', 'YYYY-MM-DD"T"HH24:MI:SS.MS"Z"'),
+                   item
+            FROM codex_rollout_items
+            WHERE thread_id = $1 AND id > $2
+            ORDER BY id ASC
+            LIMIT $3
+            "#,
+        )
+        .bind(self.thread_uuid)
+        .bind(self.after_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| {
+            IoError::other(format!("failed to load rollout items from Postgres: {err}"))
+        })?;
+
+        let mut lines = Vec::with_capacity(rows.len());
+        for (id, timestamp, Json(value)) in rows {
+            let item: RolloutItem = serde_json::from_value(value)
+                .map_err(|err| IoError::other(format!("failed to decode rollout item: {err}")))?;
+            lines.push(RolloutLine { timestamp, item });
+            self.after_id = id;
+        }
+        Ok(lines)
+    }
+}
+
+/// Approval request metadata persisted by the web server. The response channel
+/// is process-local, so this only carries what is needed to re-emit the request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersistedApproval {
+    pub approval_id: String,
+    pub thread_id: ThreadId,
+    pub turn_id: String,
+    /// SSE event type the request was originally emitted under.
+    pub event_type: String,
+    pub payload: serde_json::Value,
+}
+
+/// Token usage of one model response, recorded by the web server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersistedUsage {
+    pub thread_id: ThreadId,
+    pub model: String,
+    /// Unix seconds
+    pub recorded_at: i64,
+    pub usage: TokenUsage,
+}
+
+/// Result of reconciling persisted web-server state after a restart.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WebStateReconciliation {
+    /// Approvals that were still waiting for a decision when the server stopped.
+    pub approvals: Vec<PersistedApproval>,
+    /// Turns that were running when the server stopped, now marked
+    /// `interrupted_by_restart`.
+    pub interrupted_turns: Vec<(ThreadId, String)>,
+}
+
+/// Postgres-backed store for web-server approval and turn state, sharing the
+/// rollout database. Only available when `CODEX_ROLLOUT_POSTGRES_URL` is set.
+pub struct WebStateStore {
+    pool: PgPool,
+    instance_id: String,
+}
+
+impl WebStateStore {
+    /// Connects to the rollout database, returning `None` when Postgres
+    /// persistence is not configured.
+    pub async fn connect_from_env(instance_id: &str) -> std::io::Result<Option<Self>> {
+        if rollout_postgres_url_from_env().is_none() {
+            return Ok(None);
+        }
+        let pool = connect_rollout_pool().await?;
+        Ok(Some(Self {
+            pool,
+            instance_id: instance_id.to_string(),
+        }))
+    }
+
+    /// Round-trips a trivial query to confirm the database is reachable.
+    pub async fn ping(&self) -> std::io::Result<()> {
+        sqlx::query("SELECT 1")
+            .execute(&self.pool)
+            .await
+            .map_err(|err| IoError::other(format!("failed to reach rollout database: {err}")))?;
+        Ok(())
+    }
+
+    pub async fn save_approval(&self, approval: &PersistedApproval) -> std::io::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO codex_web_pending_approvals
+                (approval_id, instance_id, thread_id, turn_id, event_type, payload)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (approval_id) DO UPDATE
+            SET instance_id = EXCLUDED.instance_id,
+                thread_id = EXCLUDED.thread_id,
+                turn_id = EXCLUDED.turn_id,
+                event_type = EXCLUDED.event_type,
+                payload = EXCLUDED.payload
+            "#,
+        )
+        .bind(approval.approval_id.as_str())
+        .bind(self.instance_id.as_str())
+        .bind(thread_uuid(approval.thread_id)?)
+        .bind(approval.turn_id.as_str())
+        .bind(approval.event_type.as_str())
+        .bind(Json(&approval.payload))
+        .execute(&self.pool)
+        .await
+        .map_err(|err| IoError::other(format!("failed to persist pending approval: {err}")))?;
+        Ok(())
+    }
+
+    pub async fn remove_approval(&self, approval_id: &str) -> std::io::Result<()> {
+        sqlx::query("DELETE FROM codex_web_pending_approvals WHERE approval_id = $1")
+            .bind(approval_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|err| IoError::other(format!("failed to remove pending approval: {err}")))?;
+        Ok(())
+    }
+
+    pub async fn save_turn(&self, thread_id: ThreadId, turn_id: &str) -> std::io::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO codex_web_pending_turns (thread_id, turn_id, instance_id, status)
+            VALUES ($1, $2, $3, 'running')
+            ON CONFLICT (thread_id, turn_id) DO UPDATE
+            SET instance_id = EXCLUDED.instance_id,
+                status = 'running',
+                updated_at = NOW()
+            "#,
+        )
+        .bind(thread_uuid(thread_id)?)
+        .bind(turn_id)
+        .bind(self.instance_id.as_str())
+        .execute(&self.pool)
+        .await
+        .map_err(|err| IoError::other(format!("failed to persist pending turn: {err}")))?;
+        Ok(())
+    }
+
+    pub async fn remove_turn(&self, thread_id: ThreadId, turn_id: &str) -> std::io::Result<()> {
+        sqlx::query("DELETE FROM codex_web_pending_turns WHERE thread_id = $1 AND turn_id = $2")
+            .bind(thread_uuid(thread_id)?)
+            .bind(turn_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|err| IoError::other(format!("failed to remove pending turn: {err}")))?;
+        Ok(())
+    }
+
+    /// Marks the running turns `owner` wrote as interrupted by a restart and
+    /// returns everything that still needs to be surfaced to clients.
+    ///
+    /// Only rows written by that instance id are reconciled, which keeps a
+    /// restarting instance from claiming its peers' state when several
+    /// instances share one database.
+    pub async fn reconcile_after_restart(
+        &self,
+        owner: &str,
+    ) -> std::io::Result<WebStateReconciliation> {
+        let turn_rows: Vec<(Uuid, String)> = sqlx::query_as(
+            r#"
+            UPDATE codex_web_pending_turns
+            SET status = 'interrupted_by_restart', updated_at = NOW()
+            WHERE instance_id = $1
+            RETURNING thread_id, turn_id
+            "#,
+        )
+        .bind(owner)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| IoError::other(format!("failed to reconcile pending turns: {err}")))?;
+
+        let approval_rows: Vec<(String, Uuid, String, String, Json<serde_json::Value>)> =
+            sqlx::query_as(
+                r#"
+                SELECT approval_id, thread_id, turn_id, event_type, payload
+                FROM codex_web_pending_approvals
+                WHERE instance_id = $1
+                ORDER BY created_at ASC
+                "#,
+            )
+            .bind(owner)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|err| IoError::other(format!("failed to load pending approvals: {err}")))?;
+
+        let mut reconciliation = WebStateReconciliation::default();
+        for (thread_uuid, turn_id) in turn_rows {
+            reconciliation
+                .interrupted_turns
+                .push((thread_id_from_uuid(thread_uuid)?, turn_id));
+        }
+        for (approval_id, thread_uuid, turn_id, event_type, Json(payload)) in approval_rows {
+            reconciliation.approvals.push(PersistedApproval {
+                approval_id,
+                thread_id: thread_id_from_uuid(thread_uuid)?,
+                turn_id,
+                event_type,
+                payload,
+            });
+        }
+        Ok(reconciliation)
+    }
+
+    pub async fn set_thread_pinned(
+        &self,
+        thread_id: ThreadId,
+        pinned: bool,
+    ) -> std::io::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO codex_web_thread_prefs (thread_id, pinned)
+            VALUES ($1, $2)
+            ON CONFLICT (thread_id) DO UPDATE
+            SET pinned = EXCLUDED.pinned, updated_at = NOW()
+            "#,
+        )
+        .bind(thread_uuid(thread_id)?)
+        .bind(pinned)
+        .execute(&self.pool)
+        .await
+        .map_err(|err| IoError::other(format!("failed to persist thread pin: {err}")))?;
+        Ok(())
+    }
+
+    pub async fn pinned_threads(&self) -> std::io::Result<HashSet<ThreadId>> {
+        let rows: Vec<Uuid> =
+            sqlx::query_scalar("SELECT thread_id FROM codex_web_thread_prefs WHERE pinned")
+                .fetch_all(&self.pool)
+                .await
+                .map_err(|err| IoError::other(format!("failed to load thread pins: {err}")))?;
+        rows.into_iter().map(thread_id_from_uuid).collect()
+    }
+
+    pub async fn set_thread_notifications_muted(
+        &self,
+        thread_id: ThreadId,
+        muted: bool,
+    ) -> std::io::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO codex_web_thread_prefs (thread_id, notifications_muted)
+            VALUES ($1, $2)
+            ON CONFLICT (thread_id) DO UPDATE
+            SET notifications_muted = EXCLUDED.notifications_muted, updated_at = NOW()
+            "#,
+        )
+        .bind(thread_uuid(thread_id)?)
+        .bind(muted)
+        .execute(&self.pool)
+        .await
+        .map_err(|err| IoError::other(format!("failed to persist notification opt-out: {err}")))?;
+        Ok(())
+    }
+
+    pub async fn notifications_muted_threads(&self) -> std::io::Result<HashSet<ThreadId>> {
+        let rows: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT thread_id FROM codex_web_thread_prefs WHERE notifications_muted",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| IoError::other(format!("failed to load notification opt-outs: {err}")))?;
+        rows.into_iter().map(thread_id_from_uuid).collect()
+    }
+
+    /// First and last rollout write of every stored thread, in Unix seconds.
+    pub async fn persisted_threads(&self) -> std::io::Result<HashMap<ThreadId, (i64, i64)>> {
+        let rows: Vec<(Uuid, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT thread_id,
+                   EXTRACT(EPOCH FROM MIN(created_at))::BIGINT,
+                   EXTRACT(EPOCH FROM MAX(created_at))::BIGINT
+            FROM codex_rollout_items
+            GROUP BY thread_id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| IoError::other(format!("failed to list stored threads: {err}")))?;
+
+        rows.into_iter()
+            .map(|(uuid, created_at, updated_at)| {
+                Ok((thread_id_from_uuid(uuid)?, (created_at, updated_at)))
+            })
+            .collect()
+    }
+
+    /// Model of the latest turn of each of `thread_ids` that has one.
+    pub async fn latest_turn_models(
+        &self,
+        thread_ids: &[ThreadId],
+    ) -> std::io::Result<HashMap<ThreadId, String>> {
+        let uuids = thread_ids
+            .iter()
+            .map(|thread_id| thread_uuid(*thread_id))
+            .collect::<std::io::Result<Vec<Uuid>>>()?;
+        let rows: Vec<(Uuid, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT DISTINCT ON (thread_id) thread_id, item->'payload'->>'model'
+            FROM codex_rollout_items
+            WHERE thread_id = ANY($1) AND item->>'type' = 'turn_context'
+            ORDER BY thread_id, id DESC
+            "#,
+        )
+        .bind(&uuids)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| IoError::other(format!("failed to read thread models: {err}")))?;
+
+        let mut models = HashMap::new();
+        for (uuid, model) in rows {
+            if let Some(model) = model {
+                models.insert(thread_id_from_uuid(uuid)?, model);
+            }
+        }
+        Ok(models)
+    }
+
+    /// Stored rollout size and last write per thread: `(bytes, Unix seconds)`.
+    pub async fn rollout_usage(&self) -> std::io::Result<HashMap<ThreadId, (u64, i64)>> {
+        let rows: Vec<(Uuid, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT thread_id,
+                   SUM(pg_column_size(item))::BIGINT,
+                   EXTRACT(EPOCH FROM MAX(created_at))::BIGINT
+            FROM codex_rollout_items
+            GROUP BY thread_id
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| IoError::other(format!("failed to load rollout usage: {err}")))?;
+
+        rows.into_iter()
+            .map(|(uuid, bytes, updated_at)| {
+                Ok((
+                    thread_id_from_uuid(uuid)?,
+                    (u64::try_from(bytes).unwrap_or_default(), updated_at),
+                ))
+            })
+            .collect()
+    }
+
+    pub async fn record_usage(
+        &self,
+        thread_id: ThreadId,
+        model: &str,
+        usage: &TokenUsage,
+    ) -> std::io::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO codex_web_usage
+                (thread_id, model, input_tokens, cached_input_tokens, output_tokens,
+                 reasoning_output_tokens, total_tokens)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+        )
+        .bind(thread_uuid(thread_id)?)
+        .bind(model)
+        .bind(usage.input_tokens)
+        .bind(usage.cached_input_tokens)
+        .bind(usage.output_tokens)
+        .bind(usage.reasoning_output_tokens)
+        .bind(usage.total_tokens)
+        .execute(&self.pool)
+        .await
+        .map_err(|err| IoError::other(format!("failed to record token usage: {err}")))?;
+        Ok(())
+    }
+
+    /// Usage recorded in `[from, to)`, both in Unix seconds, oldest first.
+    pub async fn usage_between(&self, from: i64, to: i64) -> std::io::Result<Vec<PersistedUsage>> {
+        let rows: Vec<(Uuid, String, i64, i64, i64, i64, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT thread_id, model, EXTRACT(EPOCH FROM recorded_at)::BIGINT,
+                   input_tokens, cached_input_tokens, output_tokens,
+                   reasoning_output_tokens, total_tokens
+            FROM codex_web_usage
+            WHERE recorded_at >= TO_TIMESTAMP($1) AND recorded_at < TO_TIMESTAMP($2)
+            ORDER BY id ASC
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|err| IoError::other(format!("failed to load token usage: {err}")))?;
+
+        rows.into_iter()
+            .map(
+                |(
+                    uuid,
+                    model,
+                    recorded_at,
+                    input_tokens,
+                    cached_input_tokens,
+                    output_tokens,
+                    reasoning_output_tokens,
+                    total_tokens,
+                )| {
+                    Ok(PersistedUsage {
+                        thread_id: thread_id_from_uuid(uuid)?,
+                        model,
+                        recorded_at,
+                        usage: TokenUsage {
+                            input_tokens,
+                            cached_input_tokens,
+                            output_tokens,
+                            reasoning_output_tokens,
+                            total_tokens,
+                        },
+                    })
+                },
+            )
+            .collect()
+    }
+
+    /// Deletes the rollout history and web state of `thread_ids` and returns
+    /// the number of rollout items removed.
+    pub async fn prune_threads(&self, thread_ids: &[ThreadId]) -> std::io::Result<u64> {
+        let uuids = thread_ids
+            .iter()
+            .map(|thread_id| thread_uuid(*thread_id))
+            .collect::<std::io::Result<Vec<Uuid>>>()?;
+        let mut tx = self.pool.begin().await.map_err(|err| {
+            IoError::other(format!(
+                "failed to begin Postgres transaction for pruning: {err}"
+            ))
+        })?;
+
+        let removed = sqlx::query("DELETE FROM codex_rollout_items WHERE thread_id = ANY($1)")
+            .bind(&uuids)
+            .execute(&mut *tx)
+            .await
+            .map_err(|err| IoError::other(format!("failed to prune rollout items: {err}")))?
+            .rows_affected();
+        for table in [
+            "codex_web_pending_approvals",
+            "codex_web_pending_turns",
+            "codex_web_thread_prefs",
+            "codex_web_usage",
+        ] {
+            sqlx::query(&format!("DELETE FROM {table} WHERE thread_id = ANY($1)"))
+                .bind(&uuids)
+                .execute(&mut *tx)
+                .await
+                .map_err(|err| IoError::other(format!("failed to prune {table}: {err}")))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|err| IoError::other(format!("failed to commit pruning: {err}")))?;
+        Ok(removed)
+    }
+}
+
+fn thread_id_from_uuid(uuid: Uuid) -> std::io::Result<ThreadId> {
+    ThreadId::from_string(&uuid.to_string()).map_err(|err| {
+        IoError::new(
+            ErrorKind::InvalidData,
+            format!("invalid thread id {uuid}: {err}"),
+        )
+    })
+}
+
 fn thread_uuid(thread_id: ThreadId) -> std::io::Result<Uuid> {
     Uuid::parse_str(thread_id.to_string().as_str()).map_err(|err| {
         IoError::new(
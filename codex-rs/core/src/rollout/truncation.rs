@@ -68,6 +68,56 @@ pub(crate) fn truncate_rollout_before_nth_user_message_from_start(
     items[..cut_idx].to_vec()
 }
 
+/// Return the `nth_user_message` to pass to `ThreadManager::fork_thread` so the fork
+/// keeps every item up to and including the turn started with `turn_id`.
+///
+/// Returns `usize::MAX` when `turn_id` is the last turn in `items`, and `None` when no
+/// `TurnStarted` event carries `turn_id` or its user message was rolled back.
+pub fn nth_user_message_after_turn(items: &[RolloutItem], turn_id: &str) -> Option<usize> {
+    let turn_starts: Vec<(usize, &str)> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, item)| match item {
+            RolloutItem::EventMsg(EventMsg::TurnStarted(event)) => {
+                Some((idx, event.turn_id.as_str()))
+            }
+            _ => None,
+        })
+        .collect();
+    let position = turn_starts.iter().position(|(_, id)| *id == turn_id)?;
+    let start = turn_starts[position].0;
+    let end = turn_starts
+        .get(position + 1)
+        .map_or(items.len(), |(idx, _)| *idx);
+
+    let user_positions = user_message_positions_in_rollout(items);
+    if !user_positions.iter().any(|idx| (start..end).contains(idx)) {
+        return None;
+    }
+    if end == items.len() {
+        return Some(usize::MAX);
+    }
+    Some(user_positions.iter().filter(|idx| **idx < end).count())
+}
+
+/// Return the `num_turns` for `Op::ThreadRollback` that rolls the thread back to the end
+/// of the turn started with `turn_id`: the number of user turns after it.
+///
+/// Returns `None` under the same conditions as [`nth_user_message_after_turn`].
+pub fn user_turns_after_turn(items: &[RolloutItem], turn_id: &str) -> Option<usize> {
+    let kept = nth_user_message_after_turn(items, turn_id)?;
+    let total = user_message_positions_in_rollout(items).len();
+    Some(total.saturating_sub(kept))
+}
+
+/// Return the id of the last turn started in `items`, if any.
+pub fn last_turn_id(items: &[RolloutItem]) -> Option<String> {
+    items.iter().rev().find_map(|item| match item {
+        RolloutItem::EventMsg(EventMsg::TurnStarted(event)) => Some(event.turn_id.clone()),
+        _ => None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -76,6 +126,7 @@ mod tests {
     use codex_protocol::models::ContentItem;
     use codex_protocol::models::ReasoningItemReasoningSummary;
     use codex_protocol::protocol::ThreadRolledBackEvent;
+    use codex_protocol::protocol::TurnStartedEvent;
     use pretty_assertions::assert_eq;
 
     fn user_msg(text: &str) -> ResponseItem {
@@ -218,4 +269,78 @@ mod tests {
             serde_json::to_value(&expected).unwrap()
         );
     }
+
+    fn turn_started(turn_id: &str) -> RolloutItem {
+        RolloutItem::EventMsg(EventMsg::TurnStarted(TurnStartedEvent {
+            turn_id: turn_id.to_string(),
+            model_context_window: None,
+            collaboration_mode_kind: Default::default(),
+        }))
+    }
+
+    #[test]
+    fn fork_point_keeps_the_requested_turn() {
+        let rollout = vec![
+            turn_started("t1"),
+            RolloutItem::ResponseItem(user_msg("u1")),
+            RolloutItem::ResponseItem(assistant_msg("a1")),
+            turn_started("t2"),
+            RolloutItem::ResponseItem(user_msg("u2")),
+            RolloutItem::ResponseItem(assistant_msg("a2")),
+        ];
+
+        let nth = nth_user_message_after_turn(&rollout, "t1");
+        assert_eq!(nth, Some(1));
+        let truncated = truncate_rollout_before_nth_user_message_from_start(&rollout, 1);
+        assert_eq!(
+            serde_json::to_value(&truncated).unwrap(),
+            serde_json::to_value(&rollout[..4]).unwrap()
+        );
+        assert_eq!(
+            nth_user_message_after_turn(&rollout, "t2"),
+            Some(usize::MAX)
+        );
+        assert_eq!(nth_user_message_after_turn(&rollout, "t3"), None);
+        assert_eq!(last_turn_id(&rollout).as_deref(), Some("t2"));
+    }
+
+    #[test]
+    fn fork_point_skips_rolled_back_turns() {
+        let rollout = vec![
+            turn_started("t1"),
+            RolloutItem::ResponseItem(user_msg("u1")),
+            turn_started("t2"),
+            RolloutItem::ResponseItem(user_msg("u2")),
+            RolloutItem::EventMsg(EventMsg::ThreadRolledBack(ThreadRolledBackEvent {
+                num_turns: 1,
+            })),
+            turn_started("t3"),
+            RolloutItem::ResponseItem(user_msg("u3")),
+        ];
+
+        assert_eq!(nth_user_message_after_turn(&rollout, "t2"), None);
+        assert_eq!(nth_user_message_after_turn(&rollout, "t1"), Some(1));
+    }
+
+    #[test]
+    fn rollback_count_covers_the_turns_after_the_requested_turn() {
+        let rollout = vec![
+            turn_started("t1"),
+            RolloutItem::ResponseItem(user_msg("u1")),
+            turn_started("t2"),
+            RolloutItem::ResponseItem(user_msg("u2")),
+            RolloutItem::EventMsg(EventMsg::ThreadRolledBack(ThreadRolledBackEvent {
+                num_turns: 1,
+            })),
+            turn_started("t3"),
+            RolloutItem::ResponseItem(user_msg("u3")),
+            turn_started("t4"),
+            RolloutItem::ResponseItem(user_msg("u4")),
+        ];
+
+        assert_eq!(user_turns_after_turn(&rollout, "t1"), Some(2));
+        assert_eq!(user_turns_after_turn(&rollout, "t3"), Some(1));
+        assert_eq!(user_turns_after_turn(&rollout, "t4"), Some(0));
+        assert_eq!(user_turns_after_turn(&rollout, "t2"), None);
+    }
 }
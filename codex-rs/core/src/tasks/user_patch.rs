@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use codex_apply_patch::MaybeApplyPatchVerified;
+use codex_async_utils::CancelErr;
+use codex_async_utils::OrCancelExt;
+use codex_protocol::user_input::UserInput;
+use tokio_util::sync::CancellationToken;
+
+use crate::apply_patch;
+use crate::apply_patch::InternalApplyPatchInvocation;
+use crate::apply_patch::convert_apply_patch_to_protocol;
+use crate::codex::TurnContext;
+use crate::protocol::EventMsg;
+use crate::protocol::TurnStartedEvent;
+use crate::state::TaskKind;
+use crate::tools::events::ToolEmitter;
+use crate::tools::events::ToolEventCtx;
+use crate::tools::handlers::apply_patch::file_paths_for_action;
+use crate::tools::orchestrator::ToolOrchestrator;
+use crate::tools::runtimes::apply_patch::ApplyPatchRequest;
+use crate::tools::runtimes::apply_patch::ApplyPatchRuntime;
+use crate::tools::sandboxing::ToolCtx;
+use crate::tools::sandboxing::ToolError;
+use crate::user_patch::user_patch_record_item;
+
+use super::SessionTask;
+use super::SessionTaskContext;
+use super::UserShellCommandMode;
+use crate::codex::Session;
+use codex_protocol::models::ResponseInputItem;
+use codex_protocol::models::ResponseItem;
+
+const USER_PATCH_TOOL_NAME: &str = "user_patch";
+
+#[derive(Clone)]
+pub(crate) struct UserPatchTask {
+    call_id: String,
+    patch: String,
+}
+
+impl UserPatchTask {
+    pub(crate) fn new(call_id: String, patch: String) -> Self {
+        Self { call_id, patch }
+    }
+}
+
+#[async_trait]
+impl SessionTask for UserPatchTask {
+    fn kind(&self) -> TaskKind {
+        TaskKind::Regular
+    }
+
+    async fn run(
+        self: Arc<Self>,
+        session: Arc<SessionTaskContext>,
+        turn_context: Arc<TurnContext>,
+        _input: Vec<UserInput>,
+        cancellation_token: CancellationToken,
+    ) -> Option<String> {
+        execute_user_patch(
+            session.clone_session(),
+            turn_context,
+            self.call_id.clone(),
+            self.patch.clone(),
+            cancellation_token,
+            UserShellCommandMode::StandaloneTurn,
+        )
+        .await;
+        None
+    }
+}
+
+/// Applies a patch the user supplied by hand. It goes through the same safety
+/// assessment, approval flow and sandboxed runtime as the `apply_patch` tool,
+/// and reports progress with the usual `PatchApply*` events.
+pub(crate) async fn execute_user_patch(
+    session: Arc<Session>,
+    turn_context: Arc<TurnContext>,
+    call_id: String,
+    patch: String,
+    cancellation_token: CancellationToken,
+    mode: UserShellCommandMode,
+) {
+    session
+        .services
+        .otel_manager
+        .counter("codex.task.user_patch", 1, &[]);
+
+    if mode == UserShellCommandMode::StandaloneTurn {
+        let event = EventMsg::TurnStarted(TurnStartedEvent {
+            turn_id: turn_context.sub_id.clone(),
+            model_context_window: turn_context.model_context_window(),
+            collaboration_mode_kind: turn_context.collaboration_mode.mode,
+        });
+        session.send_event(turn_context.as_ref(), event).await;
+    }
+
+    let turn = turn_context.as_ref();
+    let command = vec!["apply_patch".to_string(), patch.clone()];
+    let action = match codex_apply_patch::maybe_parse_apply_patch_verified(&command, &turn.cwd) {
+        MaybeApplyPatchVerified::Body(action) => action,
+        MaybeApplyPatchVerified::CorrectnessError(err) => {
+            let message = format!("patch verification failed: {err}");
+            reject(&session, turn, &call_id, &patch, message, mode).await;
+            return;
+        }
+        MaybeApplyPatchVerified::ShellParseError(_) | MaybeApplyPatchVerified::NotApplyPatch => {
+            let message = "invalid patch input".to_string();
+            reject(&session, turn, &call_id, &patch, message, mode).await;
+            return;
+        }
+    };
+
+    let apply = match apply_patch::apply_patch(turn, action).await {
+        InternalApplyPatchInvocation::DelegateToExec(apply) => apply,
+        InternalApplyPatchInvocation::Output(result) => {
+            let message = match result {
+                Ok(output) => output,
+                Err(err) => err.to_string(),
+            };
+            reject(&session, turn, &call_id, &patch, message, mode).await;
+            return;
+        }
+    };
+
+    let changes = convert_apply_patch_to_protocol(&apply.action);
+    let file_paths = file_paths_for_action(&apply.action);
+    let emitter = ToolEmitter::apply_patch(changes.clone(), apply.auto_approved);
+    emitter
+        .begin(ToolEventCtx::new(session.as_ref(), turn, &call_id, None))
+        .await;
+
+    let req = ApplyPatchRequest {
+        action: apply.action,
+        file_paths,
+        changes,
+        exec_approval_requirement: apply.exec_approval_requirement,
+        timeout_ms: None,
+        codex_exe: turn.codex_linux_sandbox_exe.clone(),
+    };
+    let mut orchestrator = ToolOrchestrator::new();
+    let mut runtime = ApplyPatchRuntime::new();
+    let tool_ctx = ToolCtx {
+        session: session.as_ref(),
+        turn,
+        call_id: call_id.clone(),
+        tool_name: USER_PATCH_TOOL_NAME.to_string(),
+    };
+    let out = match orchestrator
+        .run(
+            &mut runtime,
+            &req,
+            &tool_ctx,
+            turn,
+            turn.approval_policy.value(),
+        )
+        .or_cancel(&cancellation_token)
+        .await
+    {
+        Ok(result) => result.map(|result| result.output),
+        Err(CancelErr::Cancelled) => Err(ToolError::Rejected("patch aborted by user".to_string())),
+    };
+    let result = emitter
+        .finish(
+            ToolEventCtx::new(session.as_ref(), turn, &call_id, None),
+            out,
+        )
+        .await;
+    let (success, output) = match result {
+        Ok(output) => (true, output),
+        Err(err) => (false, err.to_string()),
+    };
+    persist_user_patch(&session, turn, &patch, success, &output, mode).await;
+}
+
+/// Reports a patch that never reached the runtime as declined.
+async fn reject(
+    session: &Session,
+    turn_context: &TurnContext,
+    call_id: &str,
+    patch: &str,
+    message: String,
+    mode: UserShellCommandMode,
+) {
+    let emitter = ToolEmitter::apply_patch(HashMap::new(), false);
+    let ctx = ToolEventCtx::new(session, turn_context, call_id, None);
+    emitter.begin(ctx).await;
+    let ctx = ToolEventCtx::new(session, turn_context, call_id, None);
+    let _ = emitter
+        .finish(ctx, Err(ToolError::Rejected(message.clone())))
+        .await;
+    persist_user_patch(session, turn_context, patch, false, &message, mode).await;
+}
+
+async fn persist_user_patch(
+    session: &Session,
+    turn_context: &TurnContext,
+    patch: &str,
+    success: bool,
+    output: &str,
+    mode: UserShellCommandMode,
+) {
+    let output_item = user_patch_record_item(patch, success, output);
+
+    if mode == UserShellCommandMode::StandaloneTurn {
+        session
+            .record_conversation_items(turn_context, std::slice::from_ref(&output_item))
+            .await;
+        return;
+    }
+
+    let response_input_item = match output_item {
+        ResponseItem::Message { role, content, .. } => ResponseInputItem::Message { role, content },
+        _ => unreachable!("user patch record should always be a message"),
+    };
+
+    if let Err(items) = session
+        .inject_response_items(vec![response_input_item])
+        .await
+    {
+        let response_items = items
+            .into_iter()
+            .map(ResponseItem::from)
+            .collect::<Vec<_>>();
+        session
+            .record_conversation_items(turn_context, &response_items)
+            .await;
+    }
+}
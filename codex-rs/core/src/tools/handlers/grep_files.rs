@@ -14,6 +14,7 @@ use crate::tools::context::ToolPayload;
 use crate::tools::handlers::parse_arguments;
 use crate::tools::registry::ToolHandler;
 use crate::tools::registry::ToolKind;
+use crate::workspace_ignore::WorkspaceIgnore;
 
 pub struct GrepFilesHandler;
 
@@ -82,8 +83,16 @@ impl ToolHandler for GrepFilesHandler {
             }
         });
 
-        let search_results =
-            run_rg_search(pattern, include.as_deref(), &search_path, limit, &turn.cwd).await?;
+        let ignore = WorkspaceIgnore::new(&turn.cwd, &turn.config.ignore_globs);
+        let search_results = run_rg_search(
+            pattern,
+            include.as_deref(),
+            &search_path,
+            limit,
+            &turn.cwd,
+            &ignore,
+        )
+        .await?;
 
         if search_results.is_empty() {
             Ok(ToolOutput::Function {
@@ -112,6 +121,7 @@ async fn run_rg_search(
     search_path: &Path,
     limit: usize,
     cwd: &Path,
+    ignore: &WorkspaceIgnore,
 ) -> Result<Vec<String>, FunctionCallError> {
     let mut command = Command::new("rg");
     command
@@ -120,7 +130,8 @@ async fn run_rg_search(
         .arg("--sortr=modified")
         .arg("--regexp")
         .arg(pattern)
-        .arg("--no-messages");
+        .arg("--no-messages")
+        .args(ignore.rg_args());
 
     if let Some(glob) = include {
         command.arg("--glob").arg(glob);
@@ -207,7 +218,8 @@ mod tests {
         std::fs::write(dir.join("match_two.txt"), "alpha delta").unwrap();
         std::fs::write(dir.join("other.txt"), "omega").unwrap();
 
-        let results = run_rg_search("alpha", None, dir, 10, dir).await?;
+        let results =
+            run_rg_search("alpha", None, dir, 10, dir, &WorkspaceIgnore::default()).await?;
         assert_eq!(results.len(), 2);
         assert!(results.iter().any(|path| path.ends_with("match_one.txt")));
         assert!(results.iter().any(|path| path.ends_with("match_two.txt")));
@@ -224,7 +236,15 @@ mod tests {
         std::fs::write(dir.join("match_one.rs"), "alpha beta gamma").unwrap();
         std::fs::write(dir.join("match_two.txt"), "alpha delta").unwrap();
 
-        let results = run_rg_search("alpha", Some("*.rs"), dir, 10, dir).await?;
+        let results = run_rg_search(
+            "alpha",
+            Some("*.rs"),
+            dir,
+            10,
+            dir,
+            &WorkspaceIgnore::default(),
+        )
+        .await?;
         assert_eq!(results.len(), 1);
         assert!(results.iter().all(|path| path.ends_with("match_one.rs")));
         Ok(())
@@ -241,7 +261,8 @@ mod tests {
         std::fs::write(dir.join("two.txt"), "alpha two").unwrap();
         std::fs::write(dir.join("three.txt"), "alpha three").unwrap();
 
-        let results = run_rg_search("alpha", None, dir, 2, dir).await?;
+        let results =
+            run_rg_search("alpha", None, dir, 2, dir, &WorkspaceIgnore::default()).await?;
         assert_eq!(results.len(), 2);
         Ok(())
     }
@@ -255,11 +276,30 @@ mod tests {
         let dir = temp.path();
         std::fs::write(dir.join("one.txt"), "omega").unwrap();
 
-        let results = run_rg_search("alpha", None, dir, 5, dir).await?;
+        let results =
+            run_rg_search("alpha", None, dir, 5, dir, &WorkspaceIgnore::default()).await?;
         assert!(results.is_empty());
         Ok(())
     }
 
+    #[tokio::test]
+    async fn run_search_skips_workspace_ignored_dirs() -> anyhow::Result<()> {
+        if !rg_available() {
+            return Ok(());
+        }
+        let temp = tempdir().expect("create temp dir");
+        let dir = temp.path();
+        std::fs::create_dir(dir.join("node_modules")).unwrap();
+        std::fs::write(dir.join("node_modules/dep.js"), "alpha").unwrap();
+        std::fs::write(dir.join("app.js"), "alpha").unwrap();
+
+        let ignore = WorkspaceIgnore::new(dir, &[]);
+        let results = run_rg_search("alpha", None, dir, 10, dir, &ignore).await?;
+        assert_eq!(results.len(), 1);
+        assert!(results[0].ends_with("app.js"));
+        Ok(())
+    }
+
     fn rg_available() -> bool {
         StdCommand::new("rg")
             .arg("--version")
@@ -17,6 +17,7 @@ use crate::tools::context::ToolPayload;
 use crate::tools::handlers::parse_arguments;
 use crate::tools::registry::ToolHandler;
 use crate::tools::registry::ToolKind;
+use crate::workspace_ignore::WorkspaceIgnore;
 
 pub struct ListDirHandler;
 
@@ -53,7 +54,7 @@ impl ToolHandler for ListDirHandler {
     }
 
     async fn handle(&self, invocation: ToolInvocation) -> Result<ToolOutput, FunctionCallError> {
-        let ToolInvocation { payload, .. } = invocation;
+        let ToolInvocation { payload, turn, .. } = invocation;
 
         let arguments = match payload {
             ToolPayload::Function { arguments } => arguments,
@@ -98,7 +99,8 @@ impl ToolHandler for ListDirHandler {
             ));
         }
 
-        let entries = list_dir_slice(&path, offset, limit, depth).await?;
+        let ignore = WorkspaceIgnore::new(&turn.cwd, &turn.config.ignore_globs);
+        let entries = list_dir_slice(&path, offset, limit, depth, &ignore).await?;
         let mut output = Vec::with_capacity(entries.len() + 1);
         output.push(format!("Absolute path: {}", path.display()));
         output.extend(entries);
@@ -114,9 +116,10 @@ async fn list_dir_slice(
     offset: usize,
     limit: usize,
     depth: usize,
+    ignore: &WorkspaceIgnore,
 ) -> Result<Vec<String>, FunctionCallError> {
     let mut entries = Vec::new();
-    collect_entries(path, Path::new(""), depth, &mut entries).await?;
+    collect_entries(path, Path::new(""), depth, ignore, &mut entries).await?;
 
     if entries.is_empty() {
         return Ok(Vec::new());
@@ -152,6 +155,7 @@ async fn collect_entries(
     dir_path: &Path,
     relative_prefix: &Path,
     depth: usize,
+    ignore: &WorkspaceIgnore,
     entries: &mut Vec<DirEntry>,
 ) -> Result<(), FunctionCallError> {
     let mut queue = VecDeque::new();
@@ -171,6 +175,11 @@ async fn collect_entries(
                 FunctionCallError::RespondToModel(format!("failed to inspect entry: {err}"))
             })?;
 
+            let kind = DirEntryKind::from(&file_type);
+            if ignore.is_ignored(&entry.path(), kind == DirEntryKind::Directory) {
+                continue;
+            }
+
             let file_name = entry.file_name();
             let relative_path = if prefix.as_os_str().is_empty() {
                 PathBuf::from(&file_name)
@@ -181,7 +190,6 @@ async fn collect_entries(
             let display_name = format_entry_component(&file_name);
             let display_depth = prefix.components().count();
             let sort_key = format_entry_name(&relative_path);
-            let kind = DirEntryKind::from(&file_type);
             dir_entries.push((
                 entry.path(),
                 relative_path,
@@ -306,7 +314,7 @@ mod tests {
             symlink(dir_path.join("entry.txt"), &link_path).expect("create symlink");
         }
 
-        let entries = list_dir_slice(dir_path, 1, 20, 3)
+        let entries = list_dir_slice(dir_path, 1, 20, 3, &WorkspaceIgnore::default())
             .await
             .expect("list directory");
 
@@ -340,7 +348,7 @@ mod tests {
             .await
             .expect("create sub dir");
 
-        let err = list_dir_slice(dir_path, 10, 1, 2)
+        let err = list_dir_slice(dir_path, 10, 1, 2, &WorkspaceIgnore::default())
             .await
             .expect_err("offset exceeds entries");
         assert_eq!(
@@ -367,7 +375,7 @@ mod tests {
             .await
             .expect("write deeper");
 
-        let entries_depth_one = list_dir_slice(dir_path, 1, 10, 1)
+        let entries_depth_one = list_dir_slice(dir_path, 1, 10, 1, &WorkspaceIgnore::default())
             .await
             .expect("list depth 1");
         assert_eq!(
@@ -375,7 +383,7 @@ mod tests {
             vec!["nested/".to_string(), "root.txt".to_string(),]
         );
 
-        let entries_depth_two = list_dir_slice(dir_path, 1, 20, 2)
+        let entries_depth_two = list_dir_slice(dir_path, 1, 20, 2, &WorkspaceIgnore::default())
             .await
             .expect("list depth 2");
         assert_eq!(
@@ -388,7 +396,7 @@ mod tests {
             ]
         );
 
-        let entries_depth_three = list_dir_slice(dir_path, 1, 30, 3)
+        let entries_depth_three = list_dir_slice(dir_path, 1, 30, 3, &WorkspaceIgnore::default())
             .await
             .expect("list depth 3");
         assert_eq!(
@@ -420,7 +428,7 @@ mod tests {
             .await
             .expect("write b child");
 
-        let first_page = list_dir_slice(dir_path, 1, 2, 2)
+        let first_page = list_dir_slice(dir_path, 1, 2, 2, &WorkspaceIgnore::default())
             .await
             .expect("list page one");
         assert_eq!(
@@ -432,7 +440,7 @@ mod tests {
             ]
         );
 
-        let second_page = list_dir_slice(dir_path, 3, 2, 2)
+        let second_page = list_dir_slice(dir_path, 3, 2, 2, &WorkspaceIgnore::default())
             .await
             .expect("list page two");
         assert_eq!(
@@ -455,7 +463,7 @@ mod tests {
             .await
             .expect("write gamma");
 
-        let entries = list_dir_slice(dir_path, 2, usize::MAX, 1)
+        let entries = list_dir_slice(dir_path, 2, usize::MAX, 1, &WorkspaceIgnore::default())
             .await
             .expect("list without overflow");
         assert_eq!(
@@ -476,7 +484,7 @@ mod tests {
                 .expect("write file");
         }
 
-        let entries = list_dir_slice(dir_path, 1, 25, 1)
+        let entries = list_dir_slice(dir_path, 1, 25, 1, &WorkspaceIgnore::default())
             .await
             .expect("list directory");
         assert_eq!(entries.len(), 26);
@@ -498,7 +506,8 @@ mod tests {
         tokio::fs::write(nested.join("child.txt"), b"child").await?;
         tokio::fs::write(deeper.join("grandchild.txt"), b"deep").await?;
 
-        let entries_depth_three = list_dir_slice(dir_path, 1, 3, 3).await?;
+        let entries_depth_three =
+            list_dir_slice(dir_path, 1, 3, 3, &WorkspaceIgnore::default()).await?;
         assert_eq!(
             entries_depth_three,
             vec![
@@ -511,4 +520,30 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn skips_workspace_ignored_entries() -> anyhow::Result<()> {
+        let temp = tempdir()?;
+        let dir_path = temp.path();
+        tokio::fs::create_dir_all(dir_path.join("node_modules/react")).await?;
+        tokio::fs::create_dir(dir_path.join("src")).await?;
+        tokio::fs::write(dir_path.join("src/main.rs"), b"fn main() {}").await?;
+        tokio::fs::write(dir_path.join(".codexignore"), b"*.snap\n").await?;
+        tokio::fs::write(dir_path.join("src/output.snap"), b"snapshot").await?;
+
+        let ignore = WorkspaceIgnore::new(dir_path, &[]);
+        let entries = list_dir_slice(dir_path, 1, 20, 2, &ignore).await?;
+        assert_eq!(
+            entries,
+            vec![
+                ".codexignore".to_string(),
+                "src/".to_string(),
+                "  main.rs".to_string(),
+            ]
+        );
+
+        let entries = list_dir_slice(dir_path, 1, 20, 1, &WorkspaceIgnore::default()).await?;
+        assert!(entries.contains(&"node_modules/".to_string()));
+        Ok(())
+    }
 }
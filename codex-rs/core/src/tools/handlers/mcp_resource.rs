@@ -589,6 +589,7 @@ async fn emit_tool_call_end(
                 invocation,
                 duration,
                 result,
+                error_details: None,
             }),
         )
         .await;
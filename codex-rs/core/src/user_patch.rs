@@ -0,0 +1,55 @@
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+
+pub const USER_PATCH_OPEN: &str = "<user_patch>";
+pub const USER_PATCH_CLOSE: &str = "</user_patch>";
+
+pub fn is_user_patch_text(text: &str) -> bool {
+    let trimmed = text.trim_start();
+    let lowered = trimmed.to_ascii_lowercase();
+    lowered.starts_with(USER_PATCH_OPEN)
+}
+
+pub fn format_user_patch_record(patch: &str, success: bool, output: &str) -> String {
+    let status = if success { "applied" } else { "failed" };
+    format!(
+        "{USER_PATCH_OPEN}\n<patch>\n{patch}\n</patch>\n<result>\nStatus: {status}\nOutput:\n{output}\n</result>\n{USER_PATCH_CLOSE}"
+    )
+}
+
+/// Model-visible record of a patch the user applied by hand, so the next turn
+/// does not work from stale file contents.
+pub fn user_patch_record_item(patch: &str, success: bool, output: &str) -> ResponseItem {
+    ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText {
+            text: format_user_patch_record(patch, success, output),
+        }],
+        end_turn: None,
+        phase: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn detects_user_patch_text() {
+        assert!(is_user_patch_text(
+            "<user_patch>\n*** Begin Patch\n</user_patch>"
+        ));
+        assert!(is_user_patch_text("  <USER_PATCH>\n</user_patch>"));
+        assert!(!is_user_patch_text("apply <user_patch>"));
+    }
+
+    #[test]
+    fn formats_record_with_status() {
+        assert_eq!(
+            format_user_patch_record("*** Begin Patch\n*** End Patch", true, "Done!"),
+            "<user_patch>\n<patch>\n*** Begin Patch\n*** End Patch\n</patch>\n<result>\nStatus: applied\nOutput:\nDone!\n</result>\n</user_patch>"
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! Ignore rules shared by everything that walks a workspace.
+//!
+//! Tools and endpoints that list or search files would otherwise descend into
+//! dependency and build directories. A [`WorkspaceIgnore`] combines, in order
+//! of precedence (later rules win, so a `!pattern` can re-include a path):
+//!
+//! 1. [`DEFAULT_IGNORE_GLOBS`]
+//! 2. the workspace root's `.gitignore`
+//! 3. the workspace root's [`CODEXIGNORE_FILE`]
+//! 4. `ignore_globs` from config
+//!
+//! Patterns use gitignore syntax and are matched relative to the workspace
+//! root. Only the root's ignore files are read; nested `.gitignore` files are
+//! honored where the underlying walker supports them (e.g. `rg`).
+
+use ignore::gitignore::Gitignore;
+use ignore::gitignore::GitignoreBuilder;
+use std::path::Path;
+use std::path::PathBuf;
+use tracing::warn;
+
+/// Workspace-level ignore file, in gitignore syntax.
+pub const CODEXIGNORE_FILE: &str = ".codexignore";
+
+/// Directories skipped unless an ignore file re-includes them.
+pub const DEFAULT_IGNORE_GLOBS: &[&str] = &[".git/", "node_modules/", "target/"];
+
+/// Ignore rules for one workspace root. The default value ignores nothing.
+#[derive(Debug, Clone)]
+pub struct WorkspaceIgnore {
+    root: PathBuf,
+    matcher: Gitignore,
+    extra_globs: Vec<String>,
+}
+
+impl Default for WorkspaceIgnore {
+    fn default() -> Self {
+        Self {
+            root: PathBuf::new(),
+            matcher: Gitignore::empty(),
+            extra_globs: Vec::new(),
+        }
+    }
+}
+
+impl WorkspaceIgnore {
+    /// Builds the rules for `root`. Unreadable ignore files and invalid
+    /// patterns are logged and skipped rather than failing the walk.
+    pub fn new(root: &Path, extra_globs: &[String]) -> Self {
+        let mut builder = GitignoreBuilder::new(root);
+        for glob in DEFAULT_IGNORE_GLOBS {
+            add_line(&mut builder, glob);
+        }
+        for file in [".gitignore", CODEXIGNORE_FILE] {
+            let path = root.join(file);
+            if path.is_file()
+                && let Some(err) = builder.add(&path)
+            {
+                warn!("failed to read {}: {err}", path.display());
+            }
+        }
+        for glob in extra_globs {
+            add_line(&mut builder, glob);
+        }
+        let matcher = builder.build().unwrap_or_else(|err| {
+            warn!("invalid ignore rules for {}: {err}", root.display());
+            Gitignore::empty()
+        });
+        Self {
+            root: root.to_path_buf(),
+            matcher,
+            extra_globs: extra_globs.to_vec(),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Whether `path`, or a directory containing it, is ignored. Paths outside
+    /// the workspace root are never ignored.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        if self.root.as_os_str().is_empty() || !path.starts_with(&self.root) {
+            return false;
+        }
+        self.matcher
+            .matched_path_or_any_parents(path, is_dir)
+            .is_ignore()
+    }
+
+    /// Arguments that make `rg` apply the same rules. `rg` reads `.gitignore`
+    /// files itself, including nested ones.
+    pub fn rg_args(&self) -> Vec<String> {
+        if self.root.as_os_str().is_empty() {
+            return Vec::new();
+        }
+        let mut args = vec!["--no-require-git".to_string()];
+        let codexignore = self.root.join(CODEXIGNORE_FILE);
+        if codexignore.is_file() {
+            args.push("--ignore-file".to_string());
+            args.push(codexignore.to_string_lossy().into_owned());
+        }
+        let globs = DEFAULT_IGNORE_GLOBS
+            .iter()
+            .copied()
+            .chain(self.extra_globs.iter().map(String::as_str));
+        for glob in globs {
+            let glob = glob.trim().trim_end_matches('/');
+            if glob.is_empty() || glob.starts_with('#') || glob.starts_with('!') {
+                continue;
+            }
+            // `--glob` overrides ignore files, so skip globs that an ignore
+            // file re-includes.
+            if !self.is_ignored(&self.root.join(glob), true) {
+                continue;
+            }
+            args.push("--glob".to_string());
+            args.push(format!("!{glob}"));
+        }
+        args
+    }
+}
+
+fn add_line(builder: &mut GitignoreBuilder, glob: &str) {
+    if let Err(err) = builder.add_line(None, glob) {
+        warn!("invalid ignore pattern `{glob}`: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    #[test]
+    fn defaults_ignore_dependency_dirs_and_ignore_files_can_reinclude() {
+        let temp = tempdir().expect("create tempdir");
+        let root = temp.path();
+        std::fs::write(root.join(".gitignore"), "*.log\n").expect("write .gitignore");
+        std::fs::write(root.join(CODEXIGNORE_FILE), "!target/\n").expect("write .codexignore");
+        let ignore = WorkspaceIgnore::new(root, &["fixtures/large/".to_string()]);
+
+        assert!(ignore.is_ignored(&root.join("node_modules"), true));
+        assert!(ignore.is_ignored(&root.join("web/node_modules/react/index.js"), false));
+        assert!(ignore.is_ignored(&root.join("build.log"), false));
+        assert!(ignore.is_ignored(&root.join("fixtures/large/blob.bin"), false));
+        assert!(!ignore.is_ignored(&root.join("target/debug"), true));
+        assert!(!ignore.is_ignored(&root.join("src/main.rs"), false));
+        assert!(!ignore.is_ignored(Path::new("/elsewhere/node_modules"), true));
+        assert!(!WorkspaceIgnore::default().is_ignored(&root.join("node_modules"), true));
+    }
+
+    #[test]
+    fn rg_args_exclude_ignored_globs_and_read_codexignore() {
+        let temp = tempdir().expect("create tempdir");
+        let root = temp.path();
+        let codexignore = root.join(CODEXIGNORE_FILE);
+        std::fs::write(&codexignore, "!target/\n").expect("write .codexignore");
+        let ignore = WorkspaceIgnore::new(root, &["*.min.js".to_string()]);
+        assert_eq!(
+            ignore.rg_args(),
+            vec![
+                "--no-require-git".to_string(),
+                "--ignore-file".to_string(),
+                codexignore.to_string_lossy().into_owned(),
+                "--glob".to_string(),
+                "!.git".to_string(),
+                "--glob".to_string(),
+                "!node_modules".to_string(),
+                "--glob".to_string(),
+                "!*.min.js".to_string(),
+            ]
+        );
+        assert!(WorkspaceIgnore::default().rg_args().is_empty());
+    }
+}
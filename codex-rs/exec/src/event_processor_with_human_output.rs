@@ -366,6 +366,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     result,
                     invocation,
                     duration,
+                    error_details: _,
                 } = tool_call_end_event;
 
                 let duration = format!(" in {}", format_duration(duration));
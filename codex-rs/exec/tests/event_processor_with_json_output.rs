@@ -391,6 +391,7 @@ fn mcp_tool_call_begin_and_end_emit_item_events() {
                 structured_content: None,
                 meta: None,
             }),
+            error_details: None,
         }),
     );
     let end_events = ep.collect_thread_events(&end);
@@ -440,6 +441,7 @@ fn mcp_tool_call_failure_sets_failed_status() {
             invocation,
             duration: Duration::from_millis(5),
             result: Err("tool exploded".to_string()),
+            error_details: None,
         }),
     );
     let events = ep.collect_thread_events(&end);
@@ -509,6 +511,7 @@ fn mcp_tool_call_defaults_arguments_and_preserves_structured_content() {
                 structured_content: Some(json!({ "status": "ok" })),
                 meta: None,
             }),
+            error_details: None,
         }),
     );
     let events = ep.collect_thread_events(&end);
@@ -60,17 +60,7 @@ pub fn blocking_append_allow_prefix_rule(
     policy_path: &Path,
     prefix: &[String],
 ) -> Result<(), AmendError> {
-    if prefix.is_empty() {
-        return Err(AmendError::EmptyPrefix);
-    }
-
-    let tokens = prefix
-        .iter()
-        .map(serde_json::to_string)
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|source| AmendError::SerializePrefix { source })?;
-    let pattern = format!("[{}]", tokens.join(", "));
-    let rule = format!(r#"prefix_rule(pattern={pattern}, decision="allow")"#);
+    let rule = allow_prefix_rule(prefix)?;
 
     let dir = policy_path
         .parent()
@@ -90,6 +80,74 @@ pub fn blocking_append_allow_prefix_rule(
     append_locked_line(policy_path, &rule)
 }
 
+/// Removes the rule [`blocking_append_allow_prefix_rule`] writes for `prefix`.
+/// Returns whether the rule was present; a missing policy file holds no rules.
+/// Like appending, this performs blocking I/O under an advisory lock.
+pub fn blocking_remove_allow_prefix_rule(
+    policy_path: &Path,
+    prefix: &[String],
+) -> Result<bool, AmendError> {
+    let rule = allow_prefix_rule(prefix)?;
+    let mut file = match OpenOptions::new().read(true).write(true).open(policy_path) {
+        Ok(file) => file,
+        Err(source) if source.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(source) => {
+            return Err(AmendError::OpenPolicyFile {
+                path: policy_path.to_path_buf(),
+                source,
+            });
+        }
+    };
+    file.lock().map_err(|source| AmendError::LockPolicyFile {
+        path: policy_path.to_path_buf(),
+        source,
+    })?;
+
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)
+        .map_err(|source| AmendError::ReadPolicyFile {
+            path: policy_path.to_path_buf(),
+            source,
+        })?;
+    if !contents.lines().any(|existing| existing == rule) {
+        return Ok(false);
+    }
+    let remaining: String = contents
+        .lines()
+        .filter(|existing| *existing != rule)
+        .map(|line| format!("{line}\n"))
+        .collect();
+
+    let write_error = |source| AmendError::WritePolicyFile {
+        path: policy_path.to_path_buf(),
+        source,
+    };
+    file.set_len(0).map_err(write_error)?;
+    file.seek(SeekFrom::Start(0))
+        .map_err(|source| AmendError::SeekPolicyFile {
+            path: policy_path.to_path_buf(),
+            source,
+        })?;
+    file.write_all(remaining.as_bytes()).map_err(write_error)?;
+    Ok(true)
+}
+
+fn allow_prefix_rule(prefix: &[String]) -> Result<String, AmendError> {
+    if prefix.is_empty() {
+        return Err(AmendError::EmptyPrefix);
+    }
+
+    let tokens = prefix
+        .iter()
+        .map(serde_json::to_string)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|source| AmendError::SerializePrefix { source })?;
+    let pattern = format!("[{}]", tokens.join(", "));
+    Ok(format!(
+        r#"prefix_rule(pattern={pattern}, decision="allow")"#
+    ))
+}
+
 fn append_locked_line(policy_path: &Path, line: &str) -> Result<(), AmendError> {
     let mut file = OpenOptions::new()
         .create(true)
@@ -190,6 +248,27 @@ prefix_rule(pattern=["echo", "Hello, world!"], decision="allow")
         );
     }
 
+    #[test]
+    fn removes_only_the_matching_rule() {
+        let tmp = tempdir().expect("create temp dir");
+        let policy_path = tmp.path().join("rules").join("default.rules");
+        let echo = [String::from("echo"), String::from("Hello, world!")];
+        blocking_append_allow_prefix_rule(&policy_path, &[String::from("ls")]).expect("append ls");
+        blocking_append_allow_prefix_rule(&policy_path, &echo).expect("append echo");
+
+        assert!(blocking_remove_allow_prefix_rule(&policy_path, &echo).expect("remove echo"));
+        assert!(!blocking_remove_allow_prefix_rule(&policy_path, &echo).expect("remove again"));
+
+        let contents = std::fs::read_to_string(&policy_path).expect("read policy");
+        assert_eq!(
+            contents,
+            r#"prefix_rule(pattern=["ls"], decision="allow")
+"#
+        );
+        let missing = tmp.path().join("missing.rules");
+        assert!(!blocking_remove_allow_prefix_rule(&missing, &echo).expect("missing file"));
+    }
+
     #[test]
     fn inserts_newline_when_missing_before_append() {
         let tmp = tempdir().expect("create temp dir");
@@ -383,6 +383,20 @@ pub enum Op {
         command: String,
     },
 
+    /// Apply a patch the user supplied (for example an edited version of a
+    /// diff the agent proposed).
+    ///
+    /// The patch uses the `apply_patch` envelope format and goes through the
+    /// same safety checks, approval flow and sandbox as the `apply_patch`
+    /// tool. Progress is reported with `PatchApply*` events carrying
+    /// `call_id`, and the result is recorded in the conversation history.
+    ApplyUserPatch {
+        /// Identifier for the `PatchApply*` events of this patch.
+        call_id: String,
+        /// The patch in `apply_patch` envelope format.
+        patch: String,
+    },
+
     /// Request the list of available models.
     ListModels,
 }
@@ -1765,6 +1779,22 @@ pub struct McpToolCallEndEvent {
     pub duration: Duration,
     /// Result of the tool call. Note this could be an error.
     pub result: Result<CallToolResult, String>,
+    /// Structured details for a failed call, when the failure carried any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_details: Option<McpToolCallErrorDetails>,
+}
+
+/// Structured context for a failed MCP tool call, kept alongside the
+/// flattened error message in [`McpToolCallEndEvent::result`].
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema, TS, PartialEq)]
+pub struct McpToolCallErrorDetails {
+    /// JSON-RPC error code, when the server answered with an MCP error.
+    #[ts(type = "number | null")]
+    pub code: Option<i64>,
+    /// `data` attached to the MCP error response.
+    pub data: Option<serde_json::Value>,
+    /// Auth status of the server at the time of the failure.
+    pub auth_status: Option<McpAuthStatus>,
 }
 
 impl McpToolCallEndEvent {
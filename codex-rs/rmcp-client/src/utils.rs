@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::env;
+use std::fmt;
 use std::time::Duration;
 
 use anyhow::Context;
@@ -9,6 +10,7 @@ use reqwest::ClientBuilder;
 use reqwest::header::HeaderMap;
 use reqwest::header::HeaderName;
 use reqwest::header::HeaderValue;
+use rmcp::ErrorData;
 use rmcp::service::ServiceError;
 use tokio::time;
 
@@ -24,12 +26,40 @@ where
         let result = time::timeout(duration, fut)
             .await
             .with_context(|| anyhow!("timed out awaiting {label} after {duration:?}"))?;
-        result.map_err(|err| anyhow!("{label} failed: {err}"))
+        result.map_err(|err| service_error(err, label))
     } else {
-        fut.await.map_err(|err| anyhow!("{label} failed: {err}"))
+        fut.await.map_err(|err| service_error(err, label))
     }
 }
 
+/// A failed MCP request. Reads `"{label} failed: {err}"` like any other
+/// request error, and keeps the JSON-RPC error the server answered with so
+/// callers can downcast to recover its code and data.
+#[derive(Debug)]
+pub struct McpRequestError {
+    message: String,
+    /// `None` when the request failed without an answer from the server,
+    /// e.g. because the transport closed
+    pub error: Option<ErrorData>,
+}
+
+impl fmt::Display for McpRequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for McpRequestError {}
+
+fn service_error(err: ServiceError, label: &str) -> anyhow::Error {
+    let message = format!("{label} failed: {err}");
+    let error = match err {
+        ServiceError::McpError(error) => Some(error),
+        _ => None,
+    };
+    anyhow::Error::new(McpRequestError { message, error })
+}
+
 pub(crate) fn create_env_for_mcp_server(
     extra_env: Option<HashMap<String, String>>,
     env_vars: &[String],
@@ -198,6 +228,25 @@ mod tests {
         }
     }
 
+    #[test]
+    fn service_error_keeps_the_message_and_the_mcp_error() {
+        let data = ErrorData::new(
+            rmcp::model::ErrorCode(-32001),
+            "unauthorized",
+            Some(serde_json::json!({"reason": "expired"})),
+        );
+        let expected = format!(
+            "tools/call failed: {}",
+            ServiceError::McpError(data.clone())
+        );
+
+        let err = service_error(ServiceError::McpError(data.clone()), "tools/call");
+        assert_eq!(format!("{err:#}"), expected);
+        assert_eq!(err.chain().count(), 1);
+        let failed = err.downcast_ref::<McpRequestError>();
+        assert_eq!(failed.and_then(|failed| failed.error.clone()), Some(data));
+    }
+
     #[tokio::test]
     async fn create_env_honors_overrides() {
         let value = "custom".to_string();
@@ -2658,6 +2658,7 @@ impl ChatWidget {
             invocation,
             duration,
             result,
+            error_details: _,
         } = ev;
 
         let extra_cell = match self
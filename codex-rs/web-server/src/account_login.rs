@@ -0,0 +1,195 @@
+//! ChatGPT sign-in started through `POST /api/v2/auth/login`.
+//!
+//! Each login runs its own callback server from `codex-login` and is kept
+//! under a generated `login_id` until it completes, times out or is
+//! cancelled; concurrent logins only ever touch their own entry. The OAuth
+//! client redirects to one fixed port, and a callback server that finds it
+//! taken cancels the login holding it, so on that port a second login is
+//! refused until the first one is done. Once the callback server is done,
+//! `AuthManager` reloads the stored tokens and the outcome is published as
+//! `account/login/completed` to every SSE stream, per-thread and
+//! `GET /api/v2/events` alike.
+
+use codex_app_server_protocol::AccountLoginCompletedNotification;
+use codex_app_server_protocol::ServerNotification;
+use codex_core::auth::CLIENT_ID;
+use codex_login::ServerOptions;
+use codex_login::ShutdownHandle;
+use codex_login::run_login_server;
+use codex_protocol::config_types::ForcedLoginMethod;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::event_pump::ThreadEvent;
+use crate::event_stream::EventStreamProcessor;
+use crate::state::WebServerState;
+
+/// How long a login waits for its OAuth callback before it is abandoned.
+pub const CHATGPT_LOGIN_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+const NOTIFICATIONS_CAPACITY: usize = 64;
+
+/// ChatGPT logins in progress, by `login_id`, and the account notifications
+/// sent to every SSE stream.
+#[derive(Debug)]
+pub struct AccountLogins {
+    active: Mutex<HashMap<String, ShutdownHandle>>,
+    notifications: broadcast::Sender<ThreadEvent>,
+    /// Held while a login checks the port and starts its callback server
+    starting: tokio::sync::Mutex<()>,
+    /// Port of each login's callback server; `None` uses the port the
+    /// ChatGPT OAuth client redirects to
+    callback_port: Option<u16>,
+}
+
+impl Default for AccountLogins {
+    fn default() -> Self {
+        let (notifications, _) = broadcast::channel(NOTIFICATIONS_CAPACITY);
+        Self {
+            active: Mutex::new(HashMap::new()),
+            notifications,
+            starting: tokio::sync::Mutex::new(()),
+            callback_port: None,
+        }
+    }
+}
+
+impl AccountLogins {
+    /// Logins whose callback servers listen on `port`, `0` picking a free
+    /// port for each, e.g. in tests.
+    pub fn with_callback_port(port: u16) -> Self {
+        Self {
+            callback_port: Some(port),
+            ..Self::default()
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ThreadEvent> {
+        self.notifications.subscribe()
+    }
+
+    pub fn is_active(&self, login_id: &str) -> bool {
+        self.active
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains_key(login_id)
+    }
+
+    fn any_active(&self) -> Option<String> {
+        self.active
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .keys()
+            .next()
+            .cloned()
+    }
+
+    /// Stops the callback server of `login_id`. Returns whether that login
+    /// was in progress; its completion is still published, as a failure.
+    pub fn cancel(&self, login_id: &str) -> bool {
+        let shutdown = self
+            .active
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(login_id);
+        match shutdown {
+            Some(shutdown) => {
+                shutdown.shutdown();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Sends `notification` to every open SSE stream.
+    pub fn publish(&self, notification: &ServerNotification) {
+        let event_type = EventStreamProcessor::event_type_name(notification);
+        let data = serde_json::to_string(notification).unwrap_or_default();
+        let _ = self.notifications.send(ThreadEvent::new(event_type, data));
+    }
+}
+
+/// Starts a ChatGPT login and returns its `login_id` and the URL the user
+/// opens to sign in.
+pub async fn start_chatgpt_login(state: &WebServerState) -> Result<(String, String), ApiError> {
+    if state.auth_manager.is_external_auth_active() {
+        return Err(ApiError::InvalidRequest(
+            "ChatGPT login is unavailable while auth is managed externally".to_string(),
+        ));
+    }
+    let config = state.config_loader.load().await?;
+    if matches!(config.forced_login_method, Some(ForcedLoginMethod::Api)) {
+        return Err(ApiError::InvalidRequest(
+            "ChatGPT login is disabled. Use API key login instead.".to_string(),
+        ));
+    }
+    let defaults = ServerOptions::new(
+        state.codex_home.clone(),
+        CLIENT_ID.to_string(),
+        config.forced_chatgpt_workspace_id.clone(),
+        state.credential_store_mode,
+    );
+    let logins = state.account_logins.clone();
+    let port = logins.callback_port.unwrap_or(defaults.port);
+    let starting = logins.starting.lock().await;
+    if port != 0
+        && let Some(login_id) = logins.any_active()
+    {
+        return Err(ApiError::LoginInProgress { login_id });
+    }
+    let options = ServerOptions {
+        open_browser: false,
+        port,
+        ..defaults
+    };
+    let server = run_login_server(options)
+        .map_err(|err| ApiError::InternalError(format!("Failed to start login server: {err}")))?;
+
+    let login_id = Uuid::new_v4().to_string();
+    let auth_url = server.auth_url.clone();
+    let shutdown = server.cancel_handle();
+    logins
+        .active
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(login_id.clone(), shutdown.clone());
+    drop(starting);
+
+    let auth_manager = state.auth_manager.clone();
+    let completed_id = login_id.clone();
+    tokio::spawn(async move {
+        let error =
+            match tokio::time::timeout(CHATGPT_LOGIN_TIMEOUT, server.block_until_done()).await {
+                Ok(Ok(())) => None,
+                Ok(Err(err)) => Some(format!("Login server error: {err}")),
+                Err(_elapsed) => {
+                    shutdown.shutdown();
+                    Some("Login timed out".to_string())
+                }
+            };
+        logins
+            .active
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&completed_id);
+        // Reloaded first, so a client that reads the account on the
+        // notification already sees it.
+        if error.is_none() {
+            auth_manager.reload();
+        }
+        logins.publish(&ServerNotification::AccountLoginCompleted(
+            AccountLoginCompletedNotification {
+                login_id: Some(completed_id),
+                success: error.is_none(),
+                error,
+            },
+        ));
+    });
+
+    Ok((login_id, auth_url))
+}
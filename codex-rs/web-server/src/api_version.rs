@@ -0,0 +1,170 @@
+//! API version negotiation and v1 deprecation signaling.
+//!
+//! Every response carries `X-Codex-Api-Version`. The version comes from the
+//! `/api/v{n}` path prefix; unversioned routes (e.g. `/health`) use the
+//! version a client pins with the same request header, defaulting to the
+//! latest. v1 responses also carry `Deprecation` and, once a removal date is
+//! configured, `Sunset`, and each v1 request is counted per route in the
+//! server metrics.
+
+use axum::body::Body;
+use axum::extract::MatchedPath;
+use axum::extract::State;
+use axum::http::HeaderValue;
+use axum::http::Request;
+use axum::middleware::Next;
+use axum::response::IntoResponse;
+use axum::response::Response;
+use chrono::NaiveDate;
+use codex_app_server_protocol::DeprecationNoticeNotification;
+
+use crate::error::ApiError;
+use crate::state::WebServerState;
+
+/// Request/response header naming the API version a request is served with.
+pub const API_VERSION_HEADER: &str = "x-codex-api-version";
+
+/// Date (`YYYY-MM-DD`) after which the v1 routes may be removed, announced in
+/// the `Sunset` header.
+pub const V1_SUNSET_ENV: &str = "CODEX_WEB_V1_SUNSET";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    pub const LATEST: Self = Self::V2;
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::V1 => "1",
+            Self::V2 => "2",
+        }
+    }
+
+    /// Parses a header value: `1`, `2`, `v1` or `v2`.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().trim_start_matches(['v', 'V']) {
+            "1" => Some(Self::V1),
+            "2" => Some(Self::V2),
+            _ => None,
+        }
+    }
+
+    /// The version named by an `/api/v{n}/...` path.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let mut segments = path.trim_start_matches('/').split('/');
+        match (segments.next(), segments.next()) {
+            (Some("api"), Some("v1")) => Some(Self::V1),
+            (Some("api"), Some("v2")) => Some(Self::V2),
+            _ => None,
+        }
+    }
+}
+
+/// How v1 deprecation is announced.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ApiVersionPolicy {
+    pub v1_sunset: Option<NaiveDate>,
+}
+
+impl ApiVersionPolicy {
+    /// Reads the v1 sunset date from `CODEX_WEB_V1_SUNSET`.
+    pub fn from_env() -> Self {
+        let v1_sunset = std::env::var(V1_SUNSET_ENV).ok().and_then(|value| {
+            match NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d") {
+                Ok(date) => Some(date),
+                Err(err) => {
+                    tracing::warn!("Ignoring invalid {V1_SUNSET_ENV}={value:?}: {err}");
+                    None
+                }
+            }
+        });
+        Self { v1_sunset }
+    }
+
+    /// `Sunset` header value (an HTTP-date), when a date is configured.
+    pub fn v1_sunset_header(&self) -> Option<String> {
+        self.v1_sunset
+            .map(|date| date.format("%a, %d %b %Y 00:00:00 GMT").to_string())
+    }
+
+    /// Sent once at the start of every v1 event stream.
+    pub fn v1_deprecation_notice(&self) -> DeprecationNoticeNotification {
+        let removal = match self.v1_sunset {
+            Some(date) => format!("v1 routes may be removed after {date}."),
+            None => "v1 routes will be removed in a future release.".to_string(),
+        };
+        DeprecationNoticeNotification {
+            summary: "The v1 API is deprecated".to_string(),
+            details: Some(format!(
+                "{removal} Use /api/v2/threads/{{id}}/events and the other /api/v2 routes instead."
+            )),
+        }
+    }
+}
+
+/// Resolves the request's [`ApiVersion`], stores it as a request extension
+/// for handlers, and tags the response with it.
+///
+/// A pinned version that contradicts the path is rejected rather than
+/// silently served with the path's behavior.
+pub async fn api_version_middleware(
+    State(state): State<WebServerState>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Response {
+    let path_version = ApiVersion::from_path(request.uri().path());
+    let pinned = request
+        .headers()
+        .get(API_VERSION_HEADER)
+        .map(|value| value.to_str().ok().and_then(ApiVersion::parse));
+
+    let version = match (path_version, pinned) {
+        (_, Some(None)) => {
+            return ApiError::InvalidRequest(format!(
+                "Unsupported {API_VERSION_HEADER}; expected 1 or 2"
+            ))
+            .into_response();
+        }
+        (Some(path_version), Some(Some(pinned))) if path_version != pinned => {
+            return ApiError::InvalidRequest(format!(
+                "{API_VERSION_HEADER}: {} does not match this v{} route",
+                pinned.as_str(),
+                path_version.as_str()
+            ))
+            .into_response();
+        }
+        (Some(version), _) | (None, Some(Some(version))) => version,
+        (None, None) => ApiVersion::LATEST,
+    };
+
+    if version == ApiVersion::V1 {
+        let route = request
+            .extensions()
+            .get::<MatchedPath>()
+            .map_or(request.uri().path(), MatchedPath::as_str);
+        state
+            .metrics
+            .record_v1_request(&format!("{} {route}", request.method()));
+    }
+    request.extensions_mut().insert(version);
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert(
+        API_VERSION_HEADER,
+        HeaderValue::from_static(version.as_str()),
+    );
+    if version == ApiVersion::V1 {
+        headers.insert("deprecation", HeaderValue::from_static("true"));
+        if let Some(sunset) = state.api_version_policy.v1_sunset_header()
+            && let Ok(value) = HeaderValue::from_str(&sunset)
+        {
+            headers.insert("sunset", value);
+        }
+    }
+    response
+}
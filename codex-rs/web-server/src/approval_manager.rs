@@ -1,14 +1,100 @@
+use codex_app_server_protocol::CommandExecutionRequestApprovalParams;
+use codex_app_server_protocol::FileChangeRequestApprovalParams;
+use codex_core::CodexThread;
+use codex_core::PersistedApproval;
+use codex_core::WebStateStore;
+use codex_core::error::Result as CodexResult;
 use codex_protocol::ThreadId;
+use codex_protocol::approvals::ExecPolicyAmendment;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::Op;
+use codex_protocol::protocol::ReviewDecision;
+use serde::Serialize;
+use serde_json::Value;
+use serde_json::json;
 use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::PoisonError;
 use std::time::Duration;
 use std::time::Instant;
 use tokio::sync::Mutex;
+use tokio::sync::broadcast;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use utoipa::ToSchema;
 
+use crate::event_pump::ThreadEvent;
+use crate::sse_envelope::server_notification;
 use crate::state::ApprovalContext;
 use crate::state::ApprovalDecision;
 use crate::state::ApprovalResponse;
 use crate::state::ApprovalType;
+use crate::state::WebServerState;
+use crate::thread_status::StatusTransition;
+use crate::thread_status::ThreadStatusRegistry;
+
+/// Seconds a client has to answer an approval request. Unset uses
+/// [`DEFAULT_APPROVAL_TIMEOUT`].
+pub const APPROVAL_TIMEOUT_SECS_ENV: &str = "CODEX_WEB_APPROVAL_TIMEOUT_SECS";
+pub const DEFAULT_APPROVAL_TIMEOUT: Duration = Duration::from_secs(900);
+/// How often approvals past their timeout are expired.
+pub const APPROVAL_SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Expired approval ids remembered so a late answer is told it was late,
+/// oldest dropped first. This list is searched on every answer.
+const RECENTLY_EXPIRED: usize = 256;
+/// `approval/expired` announcements a slow event stream may fall behind by
+/// before it lags, like `CHANGES_CAPACITY` of the other broadcast channels.
+/// Unrelated to how many expired ids are remembered.
+const EXPIRED_CAPACITY: usize = 256;
+
+/// Reads the approval timeout from `CODEX_WEB_APPROVAL_TIMEOUT_SECS`, keeping
+/// the default when it is unset, `0` or invalid.
+pub fn approval_timeout_from_env() -> Duration {
+    let Ok(value) = std::env::var(APPROVAL_TIMEOUT_SECS_ENV) else {
+        return DEFAULT_APPROVAL_TIMEOUT;
+    };
+    match value.trim().parse::<u64>() {
+        Ok(0) => DEFAULT_APPROVAL_TIMEOUT,
+        Ok(secs) => Duration::from_secs(secs),
+        Err(err) => {
+            tracing::warn!("Ignoring invalid {APPROVAL_TIMEOUT_SECS_ENV}={value:?}: {err}");
+            DEFAULT_APPROVAL_TIMEOUT
+        }
+    }
+}
+
+/// Why an answer to an approval request was not delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalErrorCode {
+    /// The thread has no pending approval with this ID (404)
+    NotFound,
+    /// The answer arrived after the approval timed out (408)
+    Timeout,
+    /// The thread stopped waiting for the answer (410)
+    ChannelClosed,
+    /// An execpolicy amendment was accepted for a request that proposed none
+    /// (400)
+    NoAmendmentProposed,
+}
+
+impl ApprovalErrorCode {
+    /// English description; clients localize from the code.
+    pub fn message(self) -> &'static str {
+        match self {
+            ApprovalErrorCode::NotFound => "Approval request not found",
+            ApprovalErrorCode::Timeout => "Approval request has timed out",
+            ApprovalErrorCode::ChannelClosed => "Approval request is no longer awaited",
+            ApprovalErrorCode::NoAmendmentProposed => {
+                "Approval request does not propose an execpolicy amendment"
+            }
+        }
+    }
+}
 
 pub struct ApprovalManager {
     pending_approvals: Arc<Mutex<HashMap<String, ApprovalContext>>>,
@@ -19,86 +105,763 @@ impl ApprovalManager {
         Self { pending_approvals }
     }
 
-    /// Register a new approval request
-    #[allow(dead_code)]
-    pub async fn register_approval(
+    /// Respond to an approval request of `thread_id`
+    pub async fn respond_to_approval(
         &self,
-        approval_id: String,
         thread_id: ThreadId,
-        item_id: String,
-        approval_type: ApprovalType,
-        response_channel: tokio::sync::oneshot::Sender<ApprovalResponse>,
-        timeout: Duration,
-    ) {
-        let context = ApprovalContext {
-            thread_id,
-            item_id,
-            approval_type,
-            response_channel,
-            created_at: Instant::now(),
-            timeout,
-        };
-
+        approval_id: &str,
+        decision: ApprovalDecision,
+    ) -> Result<(), ApprovalErrorCode> {
         let mut approvals = self.pending_approvals.lock().await;
-        approvals.insert(approval_id, context);
+        let context = take_live(&mut approvals, thread_id, approval_id)?;
+        context
+            .response_channel
+            .send(ApprovalResponse::Decided(decision))
+            .map_err(|_| ApprovalErrorCode::ChannelClosed)
     }
 
-    /// Respond to an approval request
-    pub async fn respond_to_approval(
+    /// Approves a command approval request and accepts the execpolicy
+    /// amendment it proposed, so core persists the allow rule. Returns the
+    /// accepted amendment.
+    pub async fn accept_execpolicy_amendment(
         &self,
+        thread_id: ThreadId,
         approval_id: &str,
-        decision: ApprovalDecision,
-    ) -> Result<(), String> {
+    ) -> Result<ExecPolicyAmendment, ApprovalErrorCode> {
         let mut approvals = self.pending_approvals.lock().await;
+        let pending = approvals
+            .get(approval_id)
+            .filter(|ctx| ctx.thread_id == thread_id);
+        let amendment = match pending {
+            None => return Err(ApprovalErrorCode::NotFound),
+            Some(ApprovalContext {
+                approval_type:
+                    ApprovalType::CommandExecution {
+                        proposed_execpolicy_amendment: Some(amendment),
+                        ..
+                    },
+                ..
+            }) => amendment.clone(),
+            // Left pending so the client can still answer it plainly.
+            Some(_) => return Err(ApprovalErrorCode::NoAmendmentProposed),
+        };
+        let context = take_live(&mut approvals, thread_id, approval_id)?;
+        context
+            .response_channel
+            .send(ApprovalResponse::AcceptedAmendment(amendment.clone()))
+            .map_err(|_| ApprovalErrorCode::ChannelClosed)?;
+        Ok(amendment)
+    }
+
+    /// Get approval context (for inspection)
+    #[allow(dead_code)]
+    pub async fn get_approval(&self, approval_id: &str) -> Option<ApprovalInfo> {
+        let approvals = self.pending_approvals.lock().await;
+        approvals
+            .get(approval_id)
+            .map(|ctx| ApprovalInfo::new(approval_id, ctx))
+    }
+
+    /// The approvals `thread_id` is waiting on that have not timed out,
+    /// oldest first.
+    pub async fn list_for_thread(&self, thread_id: ThreadId) -> Vec<ApprovalInfo> {
+        let approvals = self.pending_approvals.lock().await;
+        let mut pending: Vec<(&String, &ApprovalContext)> = approvals
+            .iter()
+            .filter(|(_, ctx)| ctx.thread_id == thread_id && ctx.created_at.elapsed() < ctx.timeout)
+            .collect();
+        pending.sort_by_key(|(_, ctx)| ctx.created_at);
+        pending
+            .into_iter()
+            .map(|(approval_id, ctx)| ApprovalInfo::new(approval_id, ctx))
+            .collect()
+    }
+}
+
+/// Removes the pending approval, failing if it is unknown, belongs to
+/// another thread or timed out. Another thread's approval is left pending.
+fn take_live(
+    approvals: &mut HashMap<String, ApprovalContext>,
+    thread_id: ThreadId,
+    approval_id: &str,
+) -> Result<ApprovalContext, ApprovalErrorCode> {
+    if approvals
+        .get(approval_id)
+        .is_none_or(|ctx| ctx.thread_id != thread_id)
+    {
+        return Err(ApprovalErrorCode::NotFound);
+    }
+    let context = approvals
+        .remove(approval_id)
+        .ok_or(ApprovalErrorCode::NotFound)?;
+    // Check if approval has timed out
+    if context.created_at.elapsed() >= context.timeout {
+        return Err(ApprovalErrorCode::Timeout);
+    }
+    Ok(context)
+}
+
+/// Public approval information (without sensitive channel data)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ApprovalInfo {
+    /// Id to answer with
+    pub approval_id: String,
+    pub thread_id: String,
+    pub turn_id: String,
+    pub item_id: String,
+    #[serde(flatten)]
+    pub request: PendingRequest,
+    /// Seconds since the request was sent
+    pub elapsed_secs: u64,
+    /// Seconds left to answer before the approval is denied
+    pub expires_in_secs: u64,
+}
+
+impl ApprovalInfo {
+    fn new(approval_id: &str, ctx: &ApprovalContext) -> Self {
+        let elapsed = ctx.created_at.elapsed();
+        let request = match &ctx.approval_type {
+            ApprovalType::CommandExecution {
+                command,
+                cwd,
+                reason,
+                proposed_execpolicy_amendment,
+                ..
+            } => PendingRequest::CommandExecution {
+                command: command.clone(),
+                cwd: cwd.clone(),
+                reason: reason.clone(),
+                proposed_execpolicy_amendment: proposed_execpolicy_amendment
+                    .as_ref()
+                    .map(|amendment| amendment.command.clone()),
+            },
+            ApprovalType::FileChange { reason, grant_root } => PendingRequest::FileChange {
+                reason: reason.clone(),
+                grant_root: grant_root.clone(),
+            },
+        };
+        Self {
+            approval_id: approval_id.to_string(),
+            thread_id: ctx.thread_id.to_string(),
+            turn_id: ctx.turn_id.clone(),
+            item_id: ctx.item_id.clone(),
+            request,
+            elapsed_secs: elapsed.as_secs(),
+            expires_in_secs: ctx.timeout.saturating_sub(elapsed).as_secs(),
+        }
+    }
+}
+
+/// What a pending approval asks for.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PendingRequest {
+    CommandExecution {
+        command: Vec<String>,
+        #[schema(value_type = String)]
+        cwd: PathBuf,
+        reason: Option<String>,
+        /// Command prefix the client may allow instead of approving once
+        proposed_execpolicy_amendment: Option<Vec<String>>,
+    },
+    FileChange {
+        reason: Option<String>,
+        #[schema(value_type = Option<String>)]
+        grant_root: Option<PathBuf>,
+    },
+}
+
+/// How an answer is submitted back to the thread that asked.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApprovalReply {
+    Exec {
+        approval_id: String,
+        turn_id: String,
+    },
+    Patch {
+        approval_id: String,
+    },
+}
+
+impl ApprovalReply {
+    /// The reply for an approval recovered from the state store, by the event
+    /// type it was emitted under.
+    pub fn from_persisted(approval: &PersistedApproval) -> Option<Self> {
+        match approval.event_type.as_str() {
+            "item/commandExecution/requestApproval" => Some(Self::Exec {
+                approval_id: approval.approval_id.clone(),
+                turn_id: approval.turn_id.clone(),
+            }),
+            "item/fileChange/requestApproval" => Some(Self::Patch {
+                approval_id: approval.approval_id.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn approval_id(&self) -> &str {
+        match self {
+            Self::Exec { approval_id, .. } | Self::Patch { approval_id } => approval_id,
+        }
+    }
+
+    pub fn op(&self, decision: ReviewDecision) -> Op {
+        match self {
+            Self::Exec {
+                approval_id,
+                turn_id,
+            } => Op::ExecApproval {
+                id: approval_id.clone(),
+                turn_id: Some(turn_id.clone()),
+                decision,
+            },
+            Self::Patch { approval_id } => Op::PatchApproval {
+                id: approval_id.clone(),
+                decision,
+            },
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Exec { .. } => "exec",
+            Self::Patch { .. } => "patch",
+        }
+    }
+}
+
+/// An approval request registered in `pending_approvals`, ready to be sent
+/// to the client.
+pub struct RegisteredApproval {
+    pub event_type: &'static str,
+    /// SSE data: the app-server request params as JSON
+    pub data: String,
+    pub reply: ApprovalReply,
+    pub response: oneshot::Receiver<ApprovalResponse>,
+}
+
+/// Registers the pending approval for an approval request event and persists
+/// it when a state store is configured. Returns `None` for other events.
+pub async fn register_approval_request(
+    state: &WebServerState,
+    thread_id: ThreadId,
+    msg: &EventMsg,
+) -> Option<RegisteredApproval> {
+    let (approval_type, reply, turn_id, item_id) = match msg {
+        EventMsg::ExecApprovalRequest(ev) => (
+            ApprovalType::CommandExecution {
+                command: ev.command.clone(),
+                cwd: ev.cwd.clone(),
+                reason: ev.reason.clone(),
+                approval_id: ev.approval_id.clone(),
+                network_approval_context: ev.network_approval_context.clone(),
+                proposed_execpolicy_amendment: ev.proposed_execpolicy_amendment.clone(),
+            },
+            ApprovalReply::Exec {
+                approval_id: ev.effective_approval_id(),
+                turn_id: ev.turn_id.clone(),
+            },
+            ev.turn_id.clone(),
+            ev.call_id.clone(),
+        ),
+        EventMsg::ApplyPatchApprovalRequest(ev) => (
+            ApprovalType::FileChange {
+                reason: ev.reason.clone(),
+                grant_root: ev.grant_root.clone(),
+            },
+            ApprovalReply::Patch {
+                approval_id: ev.call_id.clone(),
+            },
+            ev.turn_id.clone(),
+            ev.call_id.clone(),
+        ),
+        _ => return None,
+    };
+
+    let approval_id = reply.approval_id().to_string();
+    let (tx, rx) = oneshot::channel();
+    let context = ApprovalContext {
+        thread_id,
+        turn_id,
+        item_id,
+        approval_type,
+        response_channel: tx,
+        created_at: Instant::now(),
+        timeout: state.approval_expiry.timeout(),
+    };
+    let (event_type, params) = request_params(&context);
+    state
+        .pending_approvals
+        .lock()
+        .await
+        .insert(approval_id.clone(), context);
+
+    if let Some(store) = &state.state_store {
+        let persisted = PersistedApproval {
+            approval_id,
+            thread_id,
+            turn_id: params.turn_id().to_string(),
+            event_type: event_type.to_string(),
+            payload: serde_json::to_value(&params).unwrap_or_default(),
+        };
+        if let Err(e) = store.save_approval(&persisted).await {
+            tracing::warn!("Failed to persist {} approval: {}", reply.kind(), e);
+        }
+    }
+
+    Some(RegisteredApproval {
+        event_type,
+        data: serde_json::to_string(&params).unwrap_or_default(),
+        reply,
+        response: rx,
+    })
+}
+
+/// The SSE event type and app-server request params that announce a
+/// pending approval.
+fn request_params(context: &ApprovalContext) -> (&'static str, RequestParams) {
+    match &context.approval_type {
+        ApprovalType::CommandExecution {
+            command,
+            cwd,
+            reason,
+            approval_id,
+            network_approval_context,
+            proposed_execpolicy_amendment,
+        } => (
+            "item/commandExecution/requestApproval",
+            RequestParams::CommandExecution(CommandExecutionRequestApprovalParams {
+                thread_id: context.thread_id.to_string(),
+                turn_id: context.turn_id.clone(),
+                item_id: context.item_id.clone(),
+                approval_id: approval_id.clone(),
+                reason: reason.clone(),
+                network_approval_context: network_approval_context
+                    .clone()
+                    .map(std::convert::Into::into),
+                command: Some(command.join(" ")),
+                cwd: Some(cwd.clone()),
+                command_actions: None,
+                proposed_execpolicy_amendment: proposed_execpolicy_amendment
+                    .clone()
+                    .map(std::convert::Into::into),
+            }),
+        ),
+        ApprovalType::FileChange { reason, grant_root } => (
+            "item/fileChange/requestApproval",
+            RequestParams::FileChange(FileChangeRequestApprovalParams {
+                thread_id: context.thread_id.to_string(),
+                turn_id: context.turn_id.clone(),
+                item_id: context.item_id.clone(),
+                reason: reason.clone(),
+                grant_root: grant_root.clone(),
+            }),
+        ),
+    }
+}
 
-        if let Some(context) = approvals.remove(approval_id) {
-            // Check if approval has timed out
-            if context.created_at.elapsed() >= context.timeout {
-                return Err("Approval request has timed out".to_string());
+/// Request events for the pending approvals of `thread_id` that have not
+/// timed out, oldest first, each with its approval id. A stream that connects
+/// after a request was published sends these so the client can still answer.
+pub async fn pending_approval_requests(
+    state: &WebServerState,
+    thread_id: ThreadId,
+) -> Vec<(String, ThreadEvent)> {
+    let approvals = state.pending_approvals.lock().await;
+    let mut pending: Vec<(&String, &ApprovalContext)> = approvals
+        .iter()
+        .filter(|(_, ctx)| ctx.thread_id == thread_id && ctx.created_at.elapsed() < ctx.timeout)
+        .collect();
+    pending.sort_by_key(|(_, ctx)| ctx.created_at);
+    pending
+        .into_iter()
+        .map(|(approval_id, ctx)| {
+            let (event_type, params) = request_params(ctx);
+            let data = serde_json::to_string(&params).unwrap_or_default();
+            (approval_id.clone(), ThreadEvent::new(event_type, data))
+        })
+        .collect()
+}
+
+/// The approval id an approval request event asks about, or `None` for
+/// other events.
+pub fn requested_approval_id(event: &ThreadEvent) -> Option<String> {
+    if !matches!(
+        event.event_type,
+        "item/commandExecution/requestApproval" | "item/fileChange/requestApproval"
+    ) {
+        return None;
+    }
+    let params: Value = serde_json::from_str(&event.data).ok()?;
+    params
+        .get("approvalId")
+        .or_else(|| params.get("itemId"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+#[derive(Serialize)]
+#[serde(untagged)]
+enum RequestParams {
+    CommandExecution(CommandExecutionRequestApprovalParams),
+    FileChange(FileChangeRequestApprovalParams),
+}
+
+impl RequestParams {
+    fn turn_id(&self) -> &str {
+        match self {
+            Self::CommandExecution(params) => &params.turn_id,
+            Self::FileChange(params) => &params.turn_id,
+        }
+    }
+}
+
+/// Waits for the client's answer and submits it through `submit`. A dropped
+/// response channel (the approval expired or was discarded) is submitted as a
+/// denial; an approval resolved externally or cancelled submits nothing.
+/// Afterwards the approval is marked resolved and its persisted copy removed.
+pub fn spawn_approval_waiter<S, F>(
+    response: oneshot::Receiver<ApprovalResponse>,
+    reply: ApprovalReply,
+    submit: S,
+    thread_id: ThreadId,
+    statuses: Arc<ThreadStatusRegistry>,
+    store: Option<Arc<WebStateStore>>,
+) -> JoinHandle<()>
+where
+    S: FnOnce(Op) -> F + Send + 'static,
+    F: Future<Output = CodexResult<String>> + Send,
+{
+    tokio::spawn(async move {
+        let decision = match response.await {
+            Ok(ApprovalResponse::Decided(ApprovalDecision::Approve)) => {
+                Some(ReviewDecision::Approved)
+            }
+            Ok(ApprovalResponse::Decided(ApprovalDecision::ApproveForSession)) => {
+                Some(ReviewDecision::ApprovedForSession)
             }
+            Ok(ApprovalResponse::AcceptedAmendment(amendment)) => {
+                Some(ReviewDecision::ApprovedExecpolicyAmendment {
+                    proposed_execpolicy_amendment: amendment,
+                })
+            }
+            Ok(ApprovalResponse::Decided(ApprovalDecision::Decline)) | Err(_) => {
+                Some(ReviewDecision::Denied)
+            }
+            Ok(ApprovalResponse::ResolvedExternally | ApprovalResponse::Cancelled) => None,
+        };
+        if let Some(decision) = decision
+            && let Err(e) = submit(reply.op(decision)).await
+        {
+            tracing::error!("Failed to submit {} approval: {}", reply.kind(), e);
+        }
+        statuses.apply(
+            thread_id,
+            StatusTransition::ApprovalResolved {
+                approval_id: reply.approval_id().to_string(),
+            },
+        );
+        if let Some(store) = store
+            && let Err(e) = store.remove_approval(reply.approval_id()).await
+        {
+            tracing::warn!("Failed to clear persisted {} approval: {}", reply.kind(), e);
+        }
+    })
+}
+
+/// Handles an approval request from a thread's event stream: registers it,
+/// starts waiting for the answer, and returns the events to publish to the
+/// thread's streams. Returns nothing for other events.
+pub async fn handle_approval_request(
+    state: &WebServerState,
+    thread: &Arc<CodexThread>,
+    thread_id: ThreadId,
+    event: &Event,
+) -> Vec<ThreadEvent> {
+    let Some(request) = register_approval_request(state, thread_id, &event.msg).await else {
+        return Vec::new();
+    };
+    let thread = Arc::clone(thread);
+    spawn_approval_waiter(
+        request.response,
+        request.reply,
+        move |op| async move { thread.submit(op).await },
+        thread_id,
+        state.thread_statuses.clone(),
+        state.state_store.clone(),
+    );
+    vec![ThreadEvent::new(request.event_type, request.data)]
+}
+
+/// How a pending approval was resolved without an answer from a web client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalResolutionSource {
+    /// The command or patch started, so the approval was answered elsewhere
+    /// (e.g. by a TUI sharing the thread).
+    External,
+}
 
-            let response = ApprovalResponse { decision };
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApprovalResolved {
+    pub thread_id: ThreadId,
+    pub approval_id: String,
+    pub source: ApprovalResolutionSource,
+}
+
+impl ApprovalResolved {
+    pub const EVENT_TYPE: &str = "approval/resolved";
 
-            // Send response through channel
-            context
+    pub fn payload(&self) -> Value {
+        server_notification(
+            Self::EVENT_TYPE,
+            json!({
+                "threadId": self.thread_id.to_string(),
+                "approvalId": self.approval_id,
+                "source": self.source,
+            }),
+        )
+    }
+}
+
+/// Drops pending approvals of `thread_id` that `event` shows were resolved
+/// without a web answer, so a late answer from the client is rejected instead
+/// of submitted twice. Their waiters are told to submit nothing.
+///
+/// The begin events only carry the call id, so approvals are matched on the
+/// call they gate; an exec approval with its own `approval_id` is registered
+/// and reported under that id.
+pub async fn resolve_external_approvals(
+    state: &WebServerState,
+    thread_id: ThreadId,
+    event: &Event,
+) -> Vec<ApprovalResolved> {
+    let call_id = match &event.msg {
+        EventMsg::ExecCommandBegin(ev) => ev.call_id.as_str(),
+        EventMsg::PatchApplyBegin(ev) => ev.call_id.as_str(),
+        _ => return Vec::new(),
+    };
+
+    let resolved: Vec<(String, ApprovalContext)> = state
+        .pending_approvals
+        .lock()
+        .await
+        .extract_if(|_, ctx| ctx.thread_id == thread_id && ctx.item_id == call_id)
+        .collect();
+
+    resolved
+        .into_iter()
+        .map(|(approval_id, ctx)| {
+            // The waiter may already be gone if the stream was dropped.
+            let _ = ctx
                 .response_channel
-                .send(response)
-                .map_err(|_| "Failed to send approval response".to_string())?;
+                .send(ApprovalResponse::ResolvedExternally);
+            ApprovalResolved {
+                thread_id,
+                approval_id,
+                source: ApprovalResolutionSource::External,
+            }
+        })
+        .collect()
+}
+
+/// Why a pending approval was cancelled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalCancelReason {
+    TurnCompleted,
+    TurnAborted,
+    /// The server stopped while the approval was pending; resuming the thread
+    /// denies it
+    ServerRestart,
+}
+
+/// A pending approval whose turn ended before anyone answered it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApprovalCancelled {
+    pub thread_id: ThreadId,
+    pub turn_id: String,
+    pub approval_id: String,
+    pub reason: ApprovalCancelReason,
+}
+
+impl ApprovalCancelled {
+    pub const EVENT_TYPE: &str = "approval/cancelled";
+
+    pub fn payload(&self) -> Value {
+        server_notification(
+            Self::EVENT_TYPE,
+            json!({
+                "threadId": self.thread_id.to_string(),
+                "turnId": self.turn_id,
+                "approvalId": self.approval_id,
+                "reason": self.reason,
+            }),
+        )
+    }
+}
+
+/// Drops the pending approvals of the turn that `event` ends, so they no
+/// longer show as pending and a late answer is rejected. Their waiters are
+/// told to submit nothing.
+pub async fn cancel_orphaned_approvals(
+    state: &WebServerState,
+    thread_id: ThreadId,
+    event: &Event,
+) -> Vec<ApprovalCancelled> {
+    let (turn_id, reason) = match &event.msg {
+        EventMsg::TurnComplete(ev) => (
+            Some(ev.turn_id.as_str()),
+            ApprovalCancelReason::TurnCompleted,
+        ),
+        // An abort without a turn id ends whatever turn was running, and a
+        // thread runs one turn at a time.
+        EventMsg::TurnAborted(ev) => (ev.turn_id.as_deref(), ApprovalCancelReason::TurnAborted),
+        _ => return Vec::new(),
+    };
 
-            Ok(())
-        } else {
-            Err("Approval request not found".to_string())
+    let cancelled: Vec<(String, ApprovalContext)> = state
+        .pending_approvals
+        .lock()
+        .await
+        .extract_if(|_, ctx| {
+            ctx.thread_id == thread_id && turn_id.is_none_or(|turn_id| ctx.turn_id == turn_id)
+        })
+        .collect();
+
+    cancelled
+        .into_iter()
+        .map(|(approval_id, ctx)| {
+            // The waiter may already be gone if the stream was dropped.
+            let _ = ctx.response_channel.send(ApprovalResponse::Cancelled);
+            ApprovalCancelled {
+                thread_id,
+                turn_id: ctx.turn_id,
+                approval_id,
+                reason,
+            }
+        })
+        .collect()
+}
+
+/// A pending approval nobody answered before its timeout. It was denied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApprovalExpired {
+    pub thread_id: ThreadId,
+    pub turn_id: String,
+    pub approval_id: String,
+}
+
+impl ApprovalExpired {
+    pub const EVENT_TYPE: &str = "approval/expired";
+
+    pub fn payload(&self) -> Value {
+        server_notification(
+            Self::EVENT_TYPE,
+            json!({
+                "threadId": self.thread_id.to_string(),
+                "turnId": self.turn_id,
+                "approvalId": self.approval_id,
+            }),
+        )
+    }
+}
+
+/// The approval timeout, and the approvals that ran past it, broadcast to
+/// the event pumps of their threads.
+#[derive(Debug)]
+pub struct ApprovalExpiry {
+    timeout: Duration,
+    expired: broadcast::Sender<ApprovalExpired>,
+    recent: std::sync::Mutex<VecDeque<String>>,
+}
+
+impl Default for ApprovalExpiry {
+    fn default() -> Self {
+        Self::new(DEFAULT_APPROVAL_TIMEOUT)
+    }
+}
+
+impl ApprovalExpiry {
+    pub fn new(timeout: Duration) -> Self {
+        let (expired, _) = broadcast::channel(EXPIRED_CAPACITY);
+        Self {
+            timeout,
+            expired,
+            recent: std::sync::Mutex::new(VecDeque::new()),
         }
     }
 
-    /// Clean up expired approval requests
-    #[allow(dead_code)]
-    pub async fn cleanup_expired(&self) {
-        let mut approvals = self.pending_approvals.lock().await;
-        approvals.retain(|_, ctx| ctx.created_at.elapsed() < ctx.timeout);
+    /// How long a client has to answer an approval request.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
     }
 
-    /// Get approval context (for inspection)
-    #[allow(dead_code)]
-    pub async fn get_approval(&self, approval_id: &str) -> Option<ApprovalInfo> {
-        let approvals = self.pending_approvals.lock().await;
-        approvals.get(approval_id).map(|ctx| ApprovalInfo {
-            thread_id: ctx.thread_id.to_string(),
-            item_id: ctx.item_id.clone(),
-            approval_type: ctx.approval_type.clone(),
-            elapsed: ctx.created_at.elapsed(),
-            timeout: ctx.timeout,
-        })
+    pub fn subscribe(&self) -> broadcast::Receiver<ApprovalExpired> {
+        self.expired.subscribe()
+    }
+
+    /// Whether `approval_id` was expired recently, so an answer to it is late
+    /// rather than unknown.
+    pub fn expired_recently(&self, approval_id: &str) -> bool {
+        self.recent
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .any(|expired| expired == approval_id)
+    }
+
+    fn remember(&self, approval_id: &str) {
+        let mut recent = self.recent.lock().unwrap_or_else(PoisonError::into_inner);
+        if recent.len() == RECENTLY_EXPIRED {
+            recent.pop_front();
+        }
+        recent.push_back(approval_id.to_string());
     }
 }
 
-/// Public approval information (without sensitive channel data)
-#[allow(dead_code)]
-#[derive(Debug, Clone)]
-pub struct ApprovalInfo {
-    pub thread_id: String,
-    pub item_id: String,
-    pub approval_type: ApprovalType,
-    pub elapsed: Duration,
-    pub timeout: Duration,
+/// Denies every pending approval past its timeout and announces it as
+/// `approval/expired`. Returns the expired approvals.
+pub async fn expire_approvals(state: &WebServerState) -> Vec<ApprovalExpired> {
+    let expired: Vec<(String, ApprovalContext)> = state
+        .pending_approvals
+        .lock()
+        .await
+        .extract_if(|_, ctx| ctx.created_at.elapsed() >= ctx.timeout)
+        .collect();
+
+    expired
+        .into_iter()
+        .map(|(approval_id, ctx)| {
+            // The waiter submits the denial; it may already be gone if the
+            // thread stopped.
+            let _ = ctx
+                .response_channel
+                .send(ApprovalResponse::Decided(ApprovalDecision::Decline));
+            state.approval_expiry.remember(&approval_id);
+            let expired = ApprovalExpired {
+                thread_id: ctx.thread_id,
+                turn_id: ctx.turn_id,
+                approval_id,
+            };
+            // Nobody may be listening.
+            let _ = state.approval_expiry.expired.send(expired.clone());
+            expired
+        })
+        .collect()
+}
+
+/// Expires approvals every [`APPROVAL_SWEEP_INTERVAL`].
+pub fn spawn_approval_sweeper(state: WebServerState) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(APPROVAL_SWEEP_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            let expired = expire_approvals(&state).await;
+            if !expired.is_empty() {
+                tracing::info!("Denied {} approvals nobody answered in time", expired.len());
+            }
+        }
+    })
 }
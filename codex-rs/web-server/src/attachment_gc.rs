@@ -0,0 +1,381 @@
+//! Background policy that deletes old attachments.
+//!
+//! Uploads are kept on disk until they are deleted, so a long-running server
+//! would otherwise fill its disk. A sweep deletes every attachment uploaded
+//! longer ago than the TTL, its file and metadata together, unless a running
+//! or queued turn uses it. Files left without their pair, a file without
+//! metadata or metadata without a file, are deleted once they are as old; an
+//! upload writes its file before its metadata, so a fresh file alone is not
+//! taken for an orphan. A file that identical uploads share is deleted with
+//! the last attachment using it.
+
+use codex_protocol::ThreadId;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+use tokio::fs;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::attachments;
+use crate::attachments::AttachmentMetadata;
+use crate::state::WebServerState;
+use crate::turn_attachments;
+
+/// Hours after their upload that attachments are deleted. Unset uses
+/// [`DEFAULT_ATTACHMENT_TTL`]; `0` disables the policy.
+pub const ATTACHMENT_TTL_HOURS_ENV: &str = "CODEX_WEB_ATTACHMENT_TTL_HOURS";
+pub const DEFAULT_ATTACHMENT_TTL: Duration = Duration::from_secs(72 * 3600);
+
+const MAX_SWEEP_INTERVAL: Duration = Duration::from_secs(3600);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttachmentGcPolicy {
+    pub ttl: Duration,
+    pub sweep_interval: Duration,
+}
+
+impl AttachmentGcPolicy {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            sweep_interval: (ttl / 24).clamp(Duration::from_secs(1), MAX_SWEEP_INTERVAL),
+        }
+    }
+
+    /// Reads the policy from `CODEX_WEB_ATTACHMENT_TTL_HOURS`. Returns `None`
+    /// when the policy is disabled.
+    pub fn from_env() -> Option<Self> {
+        let Ok(value) = std::env::var(ATTACHMENT_TTL_HOURS_ENV) else {
+            return Some(Self::new(DEFAULT_ATTACHMENT_TTL));
+        };
+        match value.trim().parse::<u64>() {
+            Ok(0) => None,
+            Ok(hours) => Some(Self::new(Duration::from_secs(hours.saturating_mul(3600)))),
+            Err(err) => {
+                tracing::warn!("Ignoring invalid {ATTACHMENT_TTL_HOURS_ENV}={value:?}: {err}");
+                Some(Self::new(DEFAULT_ATTACHMENT_TTL))
+            }
+        }
+    }
+
+    pub fn spawn(self, state: WebServerState) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.sweep_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                let sweep = sweep_attachments(&state, self.ttl).await;
+                tracing::info!(
+                    "Attachment sweep deleted {} attachments and {} orphaned files, reclaiming {} bytes",
+                    sweep.deleted.len(),
+                    sweep.orphans_deleted,
+                    sweep.reclaimed_bytes
+                );
+            }
+        })
+    }
+}
+
+/// What one sweep deleted.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AttachmentSweep {
+    /// Ids of attachments deleted with their metadata
+    pub deleted: Vec<String>,
+    /// Files without metadata and metadata without a file
+    pub orphans_deleted: usize,
+    pub reclaimed_bytes: u64,
+}
+
+/// Deletes the attachments uploaded at least `ttl` ago, in every namespace,
+/// except those of running and queued turns.
+pub async fn sweep_attachments(state: &WebServerState, ttl: Duration) -> AttachmentSweep {
+    let in_use = attachments_in_use(state).await;
+    let ttl = i64::try_from(ttl.as_secs()).unwrap_or(i64::MAX);
+    let cutoff = unix_seconds(SystemTime::now()).saturating_sub(ttl);
+
+    let mut sweep = AttachmentSweep::default();
+    for dir in attachment_dirs(state).await {
+        let _blobs = state.attachment_blobs.lock().await;
+        for removal in plan_dir(&dir, cutoff, &in_use).await {
+            let mut failed = false;
+            for (path, len) in removal.files {
+                match fs::remove_file(&path).await {
+                    Ok(()) => sweep.reclaimed_bytes += len,
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(err) => {
+                        tracing::warn!("Failed to delete {}: {err}", path.display());
+                        failed = true;
+                    }
+                }
+            }
+            if failed {
+                continue;
+            }
+            if removal.complete {
+                sweep.deleted.push(removal.id);
+            } else {
+                sweep.orphans_deleted += 1;
+            }
+        }
+    }
+    sweep
+}
+
+/// Deletes, or with `dry_run` only lists, the attachments uploaded before
+/// `cutoff` that neither queued input nor the attachment journal of a thread
+/// outside `pruned` refers to. Each one goes through
+/// [`attachments::remove_attachment`], so a file that another attachment
+/// came to share since it was planned stays. Returns the files deleted with
+/// their sizes. A namespace file without metadata is left to the sweep.
+pub async fn vacuum_attachments(
+    state: &WebServerState,
+    cutoff: SystemTime,
+    pruned: &[ThreadId],
+    dry_run: bool,
+) -> Vec<(PathBuf, u64)> {
+    let in_use = attachments_referenced(state, pruned).await;
+    let cutoff = unix_seconds(cutoff);
+    let root = fs::canonicalize(&state.attachments_dir).await.ok();
+
+    let mut deleted = Vec::new();
+    for dir in attachment_dirs(state).await {
+        let planned = {
+            let _blobs = state.attachment_blobs.lock().await;
+            plan_dir(&dir, cutoff, &in_use).await
+        };
+        let flat = fs::canonicalize(&dir).await.ok() == root;
+        for removal in planned {
+            if !removal.has_metadata && !flat {
+                continue;
+            }
+            if dry_run {
+                deleted.extend(removal.files);
+                continue;
+            }
+            if let Err(err) = attachments::remove_attachment(state, &dir, &removal.id).await {
+                tracing::warn!("Vacuum failed to remove attachment {}: {err}", removal.id);
+                continue;
+            }
+            for (path, len) in removal.files {
+                if fs::symlink_metadata(&path).await.is_err() {
+                    deleted.push((path, len));
+                }
+            }
+        }
+    }
+    deleted
+}
+
+/// The root attachments directory and its namespace directories.
+async fn attachment_dirs(state: &WebServerState) -> Vec<PathBuf> {
+    let attachments_dir = state.attachments_dir.as_path();
+    let mut dirs = vec![attachments_dir.to_path_buf()];
+    if let Ok(mut entries) = fs::read_dir(attachments_dir).await {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if entry
+                .file_type()
+                .await
+                .is_ok_and(|file_type| file_type.is_dir())
+            {
+                dirs.push(entry.path());
+            }
+        }
+    }
+    dirs
+}
+
+/// One id found in a directory, with whichever of its files exist.
+struct Entry {
+    id: String,
+    file: Option<std::fs::Metadata>,
+    sidecar: Option<std::fs::Metadata>,
+    metadata: Option<AttachmentMetadata>,
+}
+
+/// Files to delete for one id.
+struct Removal {
+    id: String,
+    files: Vec<(PathBuf, u64)>,
+    /// Whether it is an attachment with both its file and metadata, rather
+    /// than an orphan
+    complete: bool,
+    has_metadata: bool,
+}
+
+/// Plans the deletion of the ids in `dir` uploaded before `cutoff` whose
+/// file is not in `in_use`. Runs under the blob lock.
+async fn plan_dir(dir: &Path, cutoff: i64, in_use: &HashSet<PathBuf>) -> Vec<Removal> {
+    let mut planned = Vec::new();
+    let Ok(mut entries) = fs::read_dir(dir).await else {
+        return planned;
+    };
+    let mut ids = HashSet::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let file_name = entry.file_name();
+        let Some(name) = file_name.to_str() else {
+            continue;
+        };
+        let id = name.strip_suffix(".json").unwrap_or(name);
+        if Uuid::parse_str(id).is_ok() {
+            ids.insert(id.to_string());
+        }
+    }
+
+    let mut found = Vec::new();
+    for id in ids {
+        let file = fs::symlink_metadata(dir.join(&id)).await.ok();
+        let metadata_path = dir.join(format!("{id}.json"));
+        let sidecar = fs::symlink_metadata(&metadata_path).await.ok();
+        let metadata = match &sidecar {
+            Some(_) => fs::read_to_string(&metadata_path)
+                .await
+                .ok()
+                .and_then(|json| serde_json::from_str::<AttachmentMetadata>(&json).ok()),
+            None => None,
+        };
+        found.push(Entry {
+            id,
+            file,
+            sidecar,
+            metadata,
+        });
+    }
+    // How many attachments use each file, their own or one they share.
+    let mut users: HashMap<String, usize> = HashMap::new();
+    for entry in &found {
+        if let Some(metadata) = &entry.metadata {
+            *users.entry(metadata.blob_id().to_string()).or_default() += 1;
+        }
+    }
+
+    // Shared files deleted with their last user, before their own id came up.
+    let mut released = HashSet::new();
+    for entry in found {
+        let blob_id = entry
+            .metadata
+            .as_ref()
+            .map_or(entry.id.as_str(), AttachmentMetadata::blob_id)
+            .to_string();
+        let blob_path = dir.join(&blob_id);
+        let blob = if blob_id == entry.id {
+            entry.file.clone()
+        } else {
+            fs::symlink_metadata(&blob_path).await.ok()
+        };
+        // A file whose metadata is gone but that other attachments still
+        // share is not an orphan.
+        if entry.sidecar.is_none()
+            && (users.get(&entry.id).is_some_and(|users| *users > 0)
+                || released.contains(&entry.id))
+        {
+            continue;
+        }
+        // Metadata from before uploads recorded `created_at` goes by the
+        // modification time of whichever file is there.
+        let uploaded_at = entry
+            .metadata
+            .as_ref()
+            .and_then(|metadata| metadata.created_at)
+            .or_else(|| {
+                entry
+                    .file
+                    .as_ref()
+                    .or(entry.sidecar.as_ref())
+                    .and_then(|metadata| metadata.modified().ok())
+                    .map(unix_seconds)
+            });
+        if uploaded_at.is_none_or(|uploaded_at| uploaded_at > cutoff) {
+            continue;
+        }
+        if blob.is_some()
+            && let Ok(canonical_path) = fs::canonicalize(&blob_path).await
+            && in_use.contains(&canonical_path)
+        {
+            continue;
+        }
+
+        let mut files = Vec::new();
+        if let Some(sidecar) = &entry.sidecar {
+            files.push((dir.join(format!("{}.json", entry.id)), sidecar.len()));
+        }
+        if entry.metadata.is_some()
+            && let Some(users) = users.get_mut(&blob_id)
+        {
+            *users = users.saturating_sub(1);
+        }
+        if users.get(&blob_id).is_none_or(|users| *users == 0)
+            && let Some(blob) = &blob
+        {
+            files.push((blob_path.clone(), blob.len()));
+        }
+        if blob_id != entry.id && files.iter().any(|(path, _)| *path == blob_path) {
+            released.insert(blob_id);
+        }
+        planned.push(Removal {
+            complete: blob.is_some() && entry.sidecar.is_some(),
+            has_metadata: entry.metadata.is_some(),
+            id: entry.id,
+            files,
+        });
+    }
+    planned
+}
+
+/// Canonical paths of the attachments of running turns, as recorded when
+/// their input was submitted, and of queued inputs. When the running turn's
+/// id is unknown every attachment recorded for its thread counts.
+async fn attachments_in_use(state: &WebServerState) -> HashSet<PathBuf> {
+    let mut in_use: HashSet<PathBuf> = state.turn_queue.queued_images().into_iter().collect();
+    for (thread_id, turn_id) in state.thread_statuses.running() {
+        let records = match turn_attachments::load(&state.codex_home, thread_id).await {
+            Ok(records) => records,
+            Err(err) => {
+                tracing::warn!("Failed to read attachments of thread {thread_id}: {err}");
+                continue;
+            }
+        };
+        in_use.extend(
+            records
+                .into_iter()
+                .filter(|(recorded_turn, _)| {
+                    turn_id
+                        .as_ref()
+                        .is_none_or(|turn_id| turn_id == recorded_turn)
+                })
+                .flat_map(|(_, records)| records)
+                .map(|record| record.path),
+        );
+    }
+    in_use
+}
+
+/// Canonical paths of the attachments of queued inputs and of every turn
+/// journaled for a thread other than `pruned`.
+async fn attachments_referenced(state: &WebServerState, pruned: &[ThreadId]) -> HashSet<PathBuf> {
+    let mut referenced: HashSet<PathBuf> = state.turn_queue.queued_images().into_iter().collect();
+    for thread_id in turn_attachments::journaled_threads(&state.codex_home).await {
+        if pruned.contains(&thread_id) {
+            continue;
+        }
+        match turn_attachments::load(&state.codex_home, thread_id).await {
+            Ok(records) => {
+                referenced.extend(records.into_values().flatten().map(|record| record.path))
+            }
+            Err(err) => {
+                tracing::warn!("Failed to read attachments of thread {thread_id}: {err}");
+            }
+        }
+    }
+    referenced
+}
+
+fn unix_seconds(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
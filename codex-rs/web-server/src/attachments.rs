@@ -1,16 +1,42 @@
 use axum::Json;
+use axum::extract::FromRequestParts;
 use axum::extract::Multipart;
 use axum::extract::Path;
+use axum::extract::Query;
 use axum::extract::State;
+use axum::extract::multipart::MultipartError;
+use axum::extract::multipart::MultipartRejection;
+use axum::http::HeaderMap;
 use axum::http::HeaderValue;
+use axum::http::StatusCode;
+use axum::http::header::ACCEPT_RANGES;
+use axum::http::header::AUTHORIZATION;
 use axum::http::header::CONTENT_DISPOSITION;
+use axum::http::header::CONTENT_LENGTH;
+use axum::http::header::CONTENT_RANGE;
 use axum::http::header::CONTENT_TYPE;
+use axum::http::header::ETAG;
+use axum::http::header::IF_NONE_MATCH;
+use axum::http::header::RANGE;
+use axum::http::request::Parts;
 use axum::response::Response;
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use codex_protocol::user_input::UserInput;
 use futures::StreamExt;
 use futures::TryStreamExt;
 use serde::Deserialize;
 use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 use tokio::fs;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncSeekExt;
 use tokio::io::AsyncWriteExt;
 use tokio_util::io::ReaderStream;
 use utoipa::ToSchema;
@@ -33,6 +59,30 @@ pub struct AttachmentMetadata {
     /// File size in bytes
     #[schema(example = 1024)]
     pub size: u64,
+    /// Namespace of the client that uploaded it; `None` for attachments
+    /// stored before namespacing, which every client can read
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Unix seconds of the upload; `None` in metadata written before it was
+    /// recorded
+    #[serde(default)]
+    pub created_at: Option<i64>,
+    /// Hex SHA-256 of the file, served as its `ETag`; `None` in metadata
+    /// written before it was recorded
+    #[serde(default)]
+    pub sha256: Option<String>,
+    /// Id of the attachment in the same directory whose file holds these
+    /// bytes, for an upload identical to an earlier one; `None` when the
+    /// attachment has its own file
+    #[serde(default)]
+    pub blob_ref: Option<String>,
+}
+
+impl AttachmentMetadata {
+    /// Name of the file that holds the attachment's bytes.
+    pub fn blob_id(&self) -> &str {
+        self.blob_ref.as_deref().unwrap_or(&self.id)
+    }
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -48,14 +98,459 @@ pub struct UploadResponse {
     pub size: u64,
 }
 
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UploadAttachmentsResponse {
+    /// The stored files, in the order they were sent
+    pub data: Vec<UploadResponse>,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct ListAttachmentsParams {
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// `next_cursor` from the previous page
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ListAttachmentsResponse {
+    /// Most recently uploaded first
+    pub data: Vec<AttachmentMetadata>,
+    /// Pass as `cursor` to get the next page; `None` on the last page
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DeleteAttachmentResponse {
+    pub success: bool,
+}
+
+/// Multipart field the file is expected in. Fields under other names are
+/// also taken as the file when they carry a filename.
+pub const FILE_FIELD: &str = "file";
+/// Largest accepted attachment in bytes. Unset uses
+/// [`DEFAULT_MAX_ATTACHMENT_BYTES`].
+pub const MAX_ATTACHMENT_BYTES_ENV: &str = "CODEX_WEB_MAX_ATTACHMENT_BYTES";
+pub const DEFAULT_MAX_ATTACHMENT_BYTES: u64 = 100 * 1024 * 1024;
+/// Largest total size of the files in one multi-file upload. Unset uses
+/// [`DEFAULT_MAX_UPLOAD_BYTES`].
+pub const MAX_UPLOAD_BYTES_ENV: &str = "CODEX_WEB_MAX_UPLOAD_BYTES";
+pub const DEFAULT_MAX_UPLOAD_BYTES: u64 = 256 * 1024 * 1024;
+/// Largest image accepted inline in turn input, after decoding.
+pub const MAX_INLINE_IMAGE_BYTES: usize = 4 * 1024 * 1024;
+/// Room for the multipart framing and other fields around the file.
+const MULTIPART_OVERHEAD: u64 = 64 * 1024;
+const DEFAULT_LIST_LIMIT: usize = 100;
+const MAX_LIST_LIMIT: usize = 500;
+
+/// Optional header that splits a token's attachments further, e.g. per user
+/// of a shared token.
+pub const NAMESPACE_HEADER: &str = "x-codex-namespace";
+const MAX_NAMESPACE_LEN: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttachmentLimits {
+    /// Largest single file
+    pub max_bytes: u64,
+    /// Largest total of the files in one multi-file upload
+    pub max_upload_bytes: u64,
+}
+
+impl Default for AttachmentLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_MAX_ATTACHMENT_BYTES,
+            max_upload_bytes: DEFAULT_MAX_UPLOAD_BYTES,
+        }
+    }
+}
+
+impl AttachmentLimits {
+    /// Reads the limits from `CODEX_WEB_MAX_ATTACHMENT_BYTES` and
+    /// `CODEX_WEB_MAX_UPLOAD_BYTES`, keeping the default of each when it is
+    /// unset, `0` or invalid.
+    pub fn from_env() -> Self {
+        Self {
+            max_bytes: bytes_from_env(MAX_ATTACHMENT_BYTES_ENV, DEFAULT_MAX_ATTACHMENT_BYTES),
+            max_upload_bytes: bytes_from_env(MAX_UPLOAD_BYTES_ENV, DEFAULT_MAX_UPLOAD_BYTES),
+        }
+    }
+
+    /// Largest request body an upload may have: the file plus its multipart
+    /// framing. The upload route's body limit is set to this.
+    pub fn max_body_bytes(&self) -> usize {
+        body_bytes(self.max_bytes)
+    }
+
+    /// Largest request body a multi-file upload may have. The route's body
+    /// limit is set to this.
+    pub fn max_upload_body_bytes(&self) -> usize {
+        body_bytes(self.max_upload_bytes)
+    }
+}
+
+fn bytes_from_env(name: &str, default: u64) -> u64 {
+    let Ok(value) = std::env::var(name) else {
+        return default;
+    };
+    match value.trim().parse::<u64>() {
+        Ok(0) => default,
+        Ok(bytes) => bytes,
+        Err(err) => {
+            tracing::warn!("Ignoring invalid {name}={value:?}: {err}");
+            default
+        }
+    }
+}
+
+fn body_bytes(max_files_bytes: u64) -> usize {
+    usize::try_from(max_files_bytes.saturating_add(MULTIPART_OVERHEAD)).unwrap_or(usize::MAX)
+}
+
+/// Attachments a request may store and read.
+///
+/// Each bearer token stores its uploads in its own subdirectory of the
+/// attachments directory, named by a hash so tokens never reach the disk;
+/// `X-Codex-Namespace` gives the token a separate subdirectory per declared
+/// name. Attachments of other namespaces are reported as not found. The admin
+/// token (the bearer token when `CODEX_WEB_ADMIN_TOKEN` is unset) reads every
+/// namespace, and files in the flat layout from before namespacing stay
+/// readable by everyone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttachmentScope {
+    /// Subdirectory of the attachments directory, recorded as the owner
+    pub namespace: String,
+    pub admin: bool,
+}
+
+impl AttachmentScope {
+    pub fn new(state: &WebServerState, token: &str, declared: Option<&str>) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(token.as_bytes());
+        if let Some(declared) = declared {
+            hasher.update([0]);
+            hasher.update(declared.as_bytes());
+        }
+        let admin_token = state.admin_token.as_deref().unwrap_or(&state.auth_token);
+        Self {
+            namespace: format!("{:x}", hasher.finalize()),
+            admin: token == admin_token,
+        }
+    }
+
+    /// Scope of a request with `headers`. Requests reach the handlers only
+    /// through the auth middleware; without a bearer token (a router built
+    /// without it) the server's own token is assumed.
+    pub fn from_headers(state: &WebServerState, headers: &HeaderMap) -> Result<Self, ApiError> {
+        let token = headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .unwrap_or(&state.auth_token);
+        let declared = match headers.get(NAMESPACE_HEADER) {
+            None => None,
+            Some(value) => Some(
+                value
+                    .to_str()
+                    .ok()
+                    .filter(|declared| is_valid_namespace(declared))
+                    .ok_or_else(|| {
+                        ApiError::InvalidRequest(format!(
+                            "{NAMESPACE_HEADER} must be 1 to {MAX_NAMESPACE_LEN} letters, digits, '-' or '_'"
+                        ))
+                    })?,
+            ),
+        };
+        Ok(Self::new(state, token, declared))
+    }
+
+    fn can_read(&self, owner: Option<&str>) -> bool {
+        self.admin || owner.is_none_or(|owner| owner == self.namespace)
+    }
+}
+
+fn is_valid_namespace(declared: &str) -> bool {
+    !declared.is_empty()
+        && declared.len() <= MAX_NAMESPACE_LEN
+        && declared
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'))
+}
+
+impl FromRequestParts<WebServerState> for AttachmentScope {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &WebServerState,
+    ) -> Result<Self, Self::Rejection> {
+        Self::from_headers(state, &parts.headers)
+    }
+}
+
+/// An attachment found by [`resolve_attachment`].
+#[derive(Debug)]
+pub struct ResolvedAttachment {
+    /// Canonical path of the file
+    pub path: PathBuf,
+    /// `None` for a file in the flat layout without a metadata file
+    pub metadata: Option<AttachmentMetadata>,
+}
+
+/// Directory `scope` stores its uploads in, under the state's attachments
+/// directory.
+pub fn upload_dir(state: &WebServerState, scope: &AttachmentScope) -> PathBuf {
+    state.attachments_dir.join(&scope.namespace)
+}
+
+/// Finds attachment `id` for `scope` under the state's attachments directory:
+/// in its namespace, in the flat pre-namespace layout, or for the admin in
+/// any namespace. Every handler that reads an attachment goes through here.
+///
+/// `id` must be a UUID, so it cannot name a path. The file, that of the
+/// attachment's `blob_ref` for an upload identical to an earlier one, must
+/// still resolve, symlinks included, to a direct child of the directory it
+/// was found in, so a link cannot reach into another namespace. The
+/// attachments directory itself may be a symlink. Only the flat layout has
+/// attachments without metadata; a namespace's files without it are uploads
+/// in progress or files kept for the attachments that share them.
+pub async fn resolve_attachment(
+    state: &WebServerState,
+    scope: &AttachmentScope,
+    id: &str,
+) -> Result<ResolvedAttachment, ApiError> {
+    Uuid::parse_str(id)
+        .map_err(|_| ApiError::InvalidRequest("Invalid attachment ID format".to_string()))?;
+    let attachments_dir = state.attachments_dir.as_path();
+    for dir in readable_dirs(state, scope).await {
+        let metadata =
+            match fs::read_to_string(dir.join(format!("{id}.json"))).await {
+                Ok(json) => Some(serde_json::from_str::<AttachmentMetadata>(&json).map_err(
+                    |e| ApiError::InternalError(format!("Failed to parse metadata: {e}")),
+                )?),
+                Err(_) => None,
+            };
+        let blob_id = metadata.as_ref().map_or(id, AttachmentMetadata::blob_id);
+        if Uuid::parse_str(blob_id).is_err() {
+            return Err(ApiError::InvalidRequest(
+                "Invalid attachment path".to_string(),
+            ));
+        }
+        let Ok(canonical_path) = fs::canonicalize(dir.join(blob_id)).await else {
+            continue;
+        };
+        let canonical_dir = fs::canonicalize(&dir).await.map_err(|e| {
+            ApiError::InternalError(format!("Failed to resolve attachments directory: {e}"))
+        })?;
+        if canonical_path.parent() != Some(canonical_dir.as_path()) {
+            return Err(ApiError::InvalidRequest(
+                "Invalid attachment path".to_string(),
+            ));
+        }
+        if metadata.is_none() && dir != attachments_dir {
+            continue;
+        }
+        if !scope.can_read(owner_of(metadata.as_ref(), &dir, attachments_dir)) {
+            return Err(ApiError::AttachmentNotFound);
+        }
+        return Ok(ResolvedAttachment {
+            path: canonical_path,
+            metadata,
+        });
+    }
+    Err(ApiError::AttachmentNotFound)
+}
+
+/// Directories `scope` may find attachments in: its namespace, the flat
+/// pre-namespace layout, and for the admin every namespace.
+async fn readable_dirs(state: &WebServerState, scope: &AttachmentScope) -> Vec<PathBuf> {
+    let attachments_dir = state.attachments_dir.as_path();
+    let mut dirs = vec![upload_dir(state, scope), attachments_dir.to_path_buf()];
+    if scope.admin
+        && let Ok(mut entries) = fs::read_dir(attachments_dir).await
+    {
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            if entry
+                .file_type()
+                .await
+                .is_ok_and(|file_type| file_type.is_dir())
+                && !dirs.contains(&path)
+            {
+                dirs.push(path);
+            }
+        }
+    }
+    dirs
+}
+
+/// Owner of an attachment found in `dir`. Without metadata the directory
+/// tells the owner.
+fn owner_of<'a>(
+    metadata: Option<&'a AttachmentMetadata>,
+    dir: &'a std::path::Path,
+    attachments_dir: &std::path::Path,
+) -> Option<&'a str> {
+    match metadata {
+        Some(metadata) => metadata.owner.as_deref(),
+        None if dir == attachments_dir => None,
+        None => dir.file_name().and_then(|name| name.to_str()),
+    }
+}
+
+/// Largest file of a text type inlined into a turn.
+const MAX_INLINE_TEXT_SIZE: u64 = 1024 * 1024;
+/// Largest file of another non-image type inlined when it is valid UTF-8,
+/// e.g. source code uploaded as `application/octet-stream`.
+const MAX_SNIFFED_TEXT_SIZE: u64 = 64 * 1024;
+
+/// Turn input for attachment `id`: an image stays a local image for the
+/// model to view, text is inlined under a header naming the file, and any
+/// other type is rejected. A file without metadata, from before metadata was
+/// stored, is taken as an image as before.
+pub async fn attachment_input(
+    id: &str,
+    resolved: ResolvedAttachment,
+) -> Result<UserInput, ApiError> {
+    let Some(metadata) = resolved.metadata else {
+        return Ok(UserInput::LocalImage {
+            path: resolved.path,
+        });
+    };
+    let mime_type = metadata
+        .mime_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    if mime_type.starts_with("image/") {
+        return Ok(UserInput::LocalImage {
+            path: resolved.path,
+        });
+    }
+    let declared_text = is_text_type(&mime_type);
+    let max_size = if declared_text {
+        MAX_INLINE_TEXT_SIZE
+    } else {
+        MAX_SNIFFED_TEXT_SIZE
+    };
+    let unsupported = || {
+        ApiError::InvalidRequest(format!(
+            "Attachment {id} ({}) has unsupported type {mime_type}; attach images or UTF-8 text",
+            metadata.filename
+        ))
+    };
+    let size = fs::metadata(&resolved.path)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to read attachment: {e}")))?
+        .len();
+    if size > max_size {
+        if declared_text {
+            return Err(ApiError::InvalidRequest(format!(
+                "Attachment {id} ({}) is too large to inline: {size} bytes, at most {max_size}",
+                metadata.filename
+            )));
+        }
+        return Err(unsupported());
+    }
+    let bytes = fs::read(&resolved.path)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to read attachment: {e}")))?;
+    let contents = match String::from_utf8(bytes) {
+        Ok(contents) => contents,
+        Err(_) if declared_text => {
+            return Err(ApiError::InvalidRequest(format!(
+                "Attachment {id} ({}) is declared as {mime_type} but is not UTF-8 text",
+                metadata.filename
+            )));
+        }
+        Err(_) => return Err(unsupported()),
+    };
+    Ok(UserInput::Text {
+        text: format!("Attached file: {}\n\n{contents}", metadata.filename),
+        text_elements: Vec::new(),
+    })
+}
+
+/// Whether `mime_type`, lowercased and without parameters, names text.
+fn is_text_type(mime_type: &str) -> bool {
+    mime_type.starts_with("text/")
+        || mime_type.ends_with("+json")
+        || mime_type.ends_with("+xml")
+        || matches!(
+            mime_type,
+            "application/json"
+                | "application/xml"
+                | "application/javascript"
+                | "application/yaml"
+                | "application/x-yaml"
+                | "application/toml"
+                | "application/x-sh"
+        )
+}
+
+/// Why an upload was rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UploadErrorCode {
+    /// Missing or mismatched boundary, or a body that is not valid multipart (400)
+    MalformedMultipart,
+    /// The file or the request body is over the size limit (413)
+    FileTooLarge,
+    /// No field held a file (400)
+    MissingFile,
+}
+
+/// What was parsed before an upload failed.
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+pub struct UploadErrorDetails {
+    /// Field names in the order they were read
+    pub fields_seen: Vec<String>,
+    /// Filename declared by the file field, if one was reached
+    pub filename: Option<String>,
+    /// Content type declared by the file field
+    pub declared_type: Option<String>,
+}
+
+pub fn upload_error(
+    code: UploadErrorCode,
+    message: String,
+    details: &UploadErrorDetails,
+) -> ApiError {
+    ApiError::UploadFailed {
+        code,
+        message,
+        details: details.clone(),
+    }
+}
+
+fn multipart_error(err: MultipartError, details: &UploadErrorDetails) -> ApiError {
+    let code = if err.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        UploadErrorCode::FileTooLarge
+    } else {
+        UploadErrorCode::MalformedMultipart
+    };
+    upload_error(
+        code,
+        format!("Failed to read multipart body: {}", err.body_text()),
+        details,
+    )
+}
+
 #[utoipa::path(
     post,
     path = "/api/v1/attachments",
+    params(
+        ("x-codex-namespace" = Option<String>, Header, description = "Namespace within the token's attachments")
+    ),
     request_body(content = inline(String), content_type = "multipart/form-data"),
     responses(
         (status = 200, description = "File uploaded successfully", body = UploadResponse),
-        (status = 400, description = "Invalid request or file too large"),
+        (status = 400, description = "Malformed multipart body or no file field (see `code` and `fields_seen`), or invalid `X-Codex-Namespace`"),
         (status = 401, description = "Unauthorized"),
+        (status = 413, description = "File over `CODEX_WEB_MAX_ATTACHMENT_BYTES`, or a `Content-Length` declaring a larger body (`file_too_large`)"),
         (status = 500, description = "Internal server error")
     ),
     security(
@@ -65,103 +560,448 @@ pub struct UploadResponse {
 )]
 pub async fn upload_attachment(
     State(state): State<WebServerState>,
-    mut multipart: Multipart,
+    scope: AttachmentScope,
+    headers: HeaderMap,
+    multipart: Result<Multipart, MultipartRejection>,
 ) -> Result<Json<UploadResponse>, ApiError> {
-    let attachment_id = Uuid::new_v4().to_string();
+    let max_bytes = state.attachment_limits.max_bytes;
+    let uploaded = store_uploads(&state, scope, &headers, multipart, max_bytes, 1).await?;
+    uploaded
+        .into_iter()
+        .next()
+        .map(Json)
+        .ok_or_else(|| ApiError::InternalError("Upload stored no file".to_string()))
+}
 
-    fs::create_dir_all(&state.attachments_dir)
-        .await
-        .map_err(|e| ApiError::InternalError(format!("Failed to create attachments dir: {e}")))?;
+#[utoipa::path(
+    post,
+    path = "/api/v2/attachments",
+    params(
+        ("x-codex-namespace" = Option<String>, Header, description = "Namespace within the token's attachments")
+    ),
+    request_body(content = inline(String), content_type = "multipart/form-data"),
+    responses(
+        (status = 200, description = "Every file uploaded, in request order", body = UploadAttachmentsResponse),
+        (status = 400, description = "Malformed multipart body or no file field (see `code` and `fields_seen`), or invalid `X-Codex-Namespace`; no file is stored"),
+        (status = 401, description = "Unauthorized"),
+        (status = 413, description = "A file over `CODEX_WEB_MAX_ATTACHMENT_BYTES`, or files together over `CODEX_WEB_MAX_UPLOAD_BYTES` (`file_too_large`); no file is stored"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Attachments"
+)]
+pub async fn upload_attachments(
+    State(state): State<WebServerState>,
+    scope: AttachmentScope,
+    headers: HeaderMap,
+    multipart: Result<Multipart, MultipartRejection>,
+) -> Result<Json<UploadAttachmentsResponse>, ApiError> {
+    let max_upload_bytes = state.attachment_limits.max_upload_bytes;
+    let data = store_uploads(
+        &state,
+        scope,
+        &headers,
+        multipart,
+        max_upload_bytes,
+        usize::MAX,
+    )
+    .await?;
+    Ok(Json(UploadAttachmentsResponse { data }))
+}
 
-    let mut filename = String::from("unnamed");
-    let mut mime_type = String::from("application/octet-stream");
-    let mut total_size = 0u64;
-    let mut file_saved = false;
+/// Stores up to `max_files` files of the request, together at most
+/// `max_total` bytes, each under its own id. Metadata is written once every
+/// file is received, and any failure removes all that the request stored.
+async fn store_uploads(
+    state: &WebServerState,
+    scope: AttachmentScope,
+    headers: &HeaderMap,
+    multipart: Result<Multipart, MultipartRejection>,
+    max_total: u64,
+    max_files: usize,
+) -> Result<Vec<UploadResponse>, ApiError> {
+    let mut details = UploadErrorDetails::default();
+    // Refused before any of the body is read; a body that is longer than it
+    // declares still stops at the limit while streaming.
+    if let Some(declared) = headers
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok())
+        && declared > body_bytes(max_total) as u64
+    {
+        return Err(upload_error(
+            UploadErrorCode::FileTooLarge,
+            format!(
+                "Request body of {declared} bytes exceeds maximum allowed upload size of {max_total} bytes"
+            ),
+            &details,
+        ));
+    }
+    let mut multipart = multipart.map_err(|rejection| {
+        upload_error(
+            UploadErrorCode::MalformedMultipart,
+            rejection.body_text(),
+            &details,
+        )
+    })?;
 
-    // Only accept the first file field
-    if let Some(field) = multipart
-        .next_field()
+    let dir = upload_dir(state, &scope);
+    fs::create_dir_all(&dir)
         .await
-        .map_err(|e| ApiError::InvalidRequest(format!("Failed to read multipart: {e}")))?
-    {
-        if let Some(name) = field.file_name() {
-            filename = name.to_string();
-        }
+        .map_err(|e| ApiError::InternalError(format!("Failed to create attachments dir: {e}")))?;
 
-        if let Some(content_type) = field.content_type() {
-            mime_type = content_type.to_string();
+    // Ids are recorded before their file is created, so a partial file is
+    // removed too.
+    let mut ids = Vec::new();
+    let mut stored = Vec::new();
+    let saved: Result<(), ApiError> = async {
+        let mut used = 0u64;
+        while stored.len() < max_files {
+            let attachment_id = Uuid::new_v4().to_string();
+            ids.push(attachment_id.clone());
+            let Some(received) = receive_next_file(
+                &mut multipart,
+                &dir.join(&attachment_id),
+                state.attachment_limits.max_bytes,
+                max_total,
+                used,
+                &mut details,
+            )
+            .await?
+            else {
+                break;
+            };
+            used += received.size;
+            stored.push(AttachmentMetadata {
+                id: attachment_id,
+                filename: details
+                    .filename
+                    .clone()
+                    .unwrap_or_else(|| "unnamed".to_string()),
+                mime_type: details
+                    .declared_type
+                    .clone()
+                    .unwrap_or_else(|| "application/octet-stream".to_string()),
+                size: received.size,
+                owner: Some(scope.namespace.clone()),
+                created_at: unix_seconds(SystemTime::now()),
+                sha256: Some(received.sha256),
+                blob_ref: None,
+            });
+        }
+        if stored.is_empty() {
+            return Err(missing_file(&details));
         }
+        commit_uploads(state, &dir, &mut stored).await
+    }
+    .await;
+    if let Err(err) = saved {
+        discard_uploads(state, &dir, &ids).await;
+        return Err(err);
+    }
+
+    Ok(stored
+        .into_iter()
+        .map(|metadata| UploadResponse {
+            attachment_id: metadata.id,
+            filename: metadata.filename,
+            size: metadata.size,
+        })
+        .collect())
+}
 
-        let file_path = state.attachments_dir.join(&attachment_id);
-        let mut file = fs::File::create(&file_path)
+/// Writes the metadata of `uploads`, whose files are in `dir`, after
+/// pointing duplicates of stored content at the existing file and deleting
+/// their own. The caller discards the uploads on error.
+async fn commit_uploads(
+    state: &WebServerState,
+    dir: &std::path::Path,
+    uploads: &mut [AttachmentMetadata],
+) -> Result<(), ApiError> {
+    let _blobs = state.attachment_blobs.lock().await;
+    let duplicates = share_blobs(dir, uploads).await;
+    for metadata in uploads.iter() {
+        let json = serde_json::to_string(metadata)
+            .map_err(|e| ApiError::InternalError(format!("Failed to serialize metadata: {e}")))?;
+        fs::write(dir.join(format!("{}.json", metadata.id)), json)
             .await
-            .map_err(|e| ApiError::InternalError(format!("Failed to create file: {e}")))?;
-
-        // Stream the file content to disk instead of loading into memory
-        const MAX_FILE_SIZE: u64 = 100 * 1024 * 1024; // 100MB limit
-        let mut stream = field.into_stream();
-
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk
-                .map_err(|e| ApiError::InvalidRequest(format!("Failed to read chunk: {e}")))?;
-
-            if total_size + chunk.len() as u64 > MAX_FILE_SIZE {
-                // Clean up partial file
-                let _ = fs::remove_file(&file_path).await;
-                return Err(ApiError::InvalidRequest(format!(
-                    "File size exceeds maximum allowed size of {MAX_FILE_SIZE} bytes"
-                )));
-            }
+            .map_err(|e| ApiError::InternalError(format!("Failed to write metadata: {e}")))?;
+    }
+    for id in duplicates {
+        if let Err(err) = fs::remove_file(dir.join(&id)).await {
+            tracing::warn!("Failed to delete duplicate of attachment {id}: {err}");
+        }
+    }
+    Ok(())
+}
 
-            file.write_all(&chunk)
+/// Removes the files and metadata of the uploads `ids` in `dir`, complete or
+/// not.
+async fn discard_uploads(state: &WebServerState, dir: &std::path::Path, ids: &[String]) {
+    let _blobs = state.attachment_blobs.lock().await;
+    for id in ids {
+        let _ = fs::remove_file(dir.join(id)).await;
+        let _ = fs::remove_file(dir.join(format!("{id}.json"))).await;
+    }
+}
+
+/// Stores a base64 `image/*` payload sent inline with turn input as an
+/// attachment of `scope`, as if it had been uploaded, and returns its id.
+/// Invalid base64 and other types are rejected with 400, and images over
+/// [`MAX_INLINE_IMAGE_BYTES`] with 413.
+pub async fn store_inline_image(
+    state: &WebServerState,
+    scope: &AttachmentScope,
+    data: &str,
+    mime_type: &str,
+) -> Result<String, ApiError> {
+    let Some(subtype) = mime_type
+        .strip_prefix("image/")
+        .filter(|subtype| !subtype.is_empty())
+    else {
+        return Err(ApiError::InvalidRequest(format!(
+            "Inline image mime_type must be an image type, got {mime_type:?}"
+        )));
+    };
+    let bytes = BASE64_STANDARD
+        .decode(data.trim())
+        .map_err(|e| ApiError::InvalidRequest(format!("Inline image is not valid base64: {e}")))?;
+    if bytes.len() > MAX_INLINE_IMAGE_BYTES {
+        return Err(upload_error(
+            UploadErrorCode::FileTooLarge,
+            format!(
+                "Inline image of {} bytes exceeds maximum allowed size of {MAX_INLINE_IMAGE_BYTES} bytes; upload it to /api/v1/attachments instead",
+                bytes.len()
+            ),
+            &UploadErrorDetails {
+                declared_type: Some(mime_type.to_string()),
+                ..UploadErrorDetails::default()
+            },
+        ));
+    }
+
+    let dir = upload_dir(state, scope);
+    fs::create_dir_all(&dir)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to create attachments dir: {e}")))?;
+    let attachment_id = Uuid::new_v4().to_string();
+    let extension = if subtype.chars().all(|c| c.is_ascii_alphanumeric()) {
+        subtype
+    } else {
+        "img"
+    };
+    let mut stored = [AttachmentMetadata {
+        id: attachment_id.clone(),
+        filename: format!("image.{extension}"),
+        mime_type: mime_type.to_string(),
+        size: bytes.len() as u64,
+        owner: Some(scope.namespace.clone()),
+        created_at: unix_seconds(SystemTime::now()),
+        sha256: Some(format!("{:x}", Sha256::digest(&bytes))),
+        blob_ref: None,
+    }];
+    let saved = match fs::write(dir.join(&attachment_id), &bytes).await {
+        Ok(()) => commit_uploads(state, &dir, &mut stored).await,
+        Err(e) => Err(ApiError::InternalError(format!(
+            "Failed to write file: {e}"
+        ))),
+    };
+    if let Err(err) = saved {
+        discard_uploads(state, &dir, std::slice::from_ref(&attachment_id)).await;
+        return Err(err);
+    }
+    Ok(attachment_id)
+}
+
+/// Points each of `uploads` whose content is already stored in `dir`, or
+/// earlier in `uploads`, at that file instead of its own, and returns the
+/// ids whose files are no longer needed. Only attachments in the same
+/// directory are compared, so uploads never share a file across namespaces.
+async fn share_blobs(dir: &std::path::Path, uploads: &mut [AttachmentMetadata]) -> Vec<String> {
+    let mut blobs = HashMap::new();
+    for metadata in dir_metadata(dir).await {
+        if let Some(sha256) = metadata.sha256.clone()
+            && fs::try_exists(dir.join(metadata.blob_id()))
                 .await
-                .map_err(|e| ApiError::InternalError(format!("Failed to write file: {e}")))?;
+                .unwrap_or(false)
+        {
+            blobs
+                .entry(sha256)
+                .or_insert_with(|| metadata.blob_id().to_string());
+        }
+    }
+    let mut duplicates = Vec::new();
+    for metadata in uploads {
+        let Some(sha256) = metadata.sha256.clone() else {
+            continue;
+        };
+        match blobs.get(&sha256) {
+            Some(blob_id) => {
+                metadata.blob_ref = Some(blob_id.clone());
+                duplicates.push(metadata.id.clone());
+            }
+            None => {
+                blobs.insert(sha256, metadata.id.clone());
+            }
+        }
+    }
+    duplicates
+}
 
-            total_size += chunk.len() as u64;
+/// Every metadata file in `dir` that parses and names its own id.
+async fn dir_metadata(dir: &std::path::Path) -> Vec<AttachmentMetadata> {
+    let mut attachments = Vec::new();
+    let Ok(mut entries) = fs::read_dir(dir).await else {
+        return attachments;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let file_name = entry.file_name();
+        let Some(id) = file_name
+            .to_str()
+            .and_then(|name| name.strip_suffix(".json"))
+            .filter(|id| Uuid::parse_str(id).is_ok())
+        else {
+            continue;
+        };
+        if let Ok(json) = fs::read_to_string(entry.path()).await
+            && let Ok(metadata) = serde_json::from_str::<AttachmentMetadata>(&json)
+            && metadata.id == id
+        {
+            attachments.push(metadata);
         }
+    }
+    attachments
+}
+
+/// A file written by [`receive_file`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceivedFile {
+    pub size: u64,
+    /// Hex SHA-256 of the contents
+    pub sha256: String,
+}
 
-        file_saved = true;
+/// Streams the file field to `file_path` and returns its size and hash,
+/// recording what was parsed in `details` for error responses. Fields before
+/// the file are skipped. The caller removes `file_path` on error.
+pub async fn receive_file(
+    multipart: &mut Multipart,
+    file_path: &std::path::Path,
+    max_size: u64,
+    details: &mut UploadErrorDetails,
+) -> Result<ReceivedFile, ApiError> {
+    receive_next_file(multipart, file_path, max_size, u64::MAX, 0, details)
+        .await?
+        .ok_or_else(|| missing_file(details))
+}
 
-        let metadata = AttachmentMetadata {
-            id: attachment_id.clone(),
-            filename: filename.clone(),
-            mime_type: mime_type.clone(),
-            size: total_size,
+/// Streams the next file field to `file_path`, skipping fields without a
+/// file; `None` once the body has no more. The file may take `max_size`
+/// bytes, and no more than `max_total` together with the `used` bytes of
+/// files before it.
+async fn receive_next_file(
+    multipart: &mut Multipart,
+    file_path: &std::path::Path,
+    max_size: u64,
+    max_total: u64,
+    used: u64,
+    details: &mut UploadErrorDetails,
+) -> Result<Option<ReceivedFile>, ApiError> {
+    let field = loop {
+        let Some(field) = multipart
+            .next_field()
+            .await
+            .map_err(|e| multipart_error(e, details))?
+        else {
+            return Ok(None);
         };
+        details
+            .fields_seen
+            .push(field.name().unwrap_or_default().to_string());
+        if field.name() == Some(FILE_FIELD) || field.file_name().is_some() {
+            break field;
+        }
+    };
+    details.filename = field.file_name().map(str::to_string);
+    details.declared_type = field.content_type().map(str::to_string);
 
-        let metadata_path = state.attachments_dir.join(format!("{attachment_id}.json"));
-        let metadata_json = serde_json::to_string(&metadata)
-            .map_err(|e| ApiError::InternalError(format!("Failed to serialize metadata: {e}")))?;
+    let mut file = fs::File::create(file_path)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to create file: {e}")))?;
+
+    // Stream the file content to disk instead of loading into memory
+    let mut total_size = 0u64;
+    let mut hasher = Sha256::new();
+    let mut stream = field.into_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| multipart_error(e, details))?;
 
-        fs::write(&metadata_path, metadata_json)
+        if total_size + chunk.len() as u64 > max_size {
+            return Err(upload_error(
+                UploadErrorCode::FileTooLarge,
+                format!("File size exceeds maximum allowed size of {max_size} bytes"),
+                details,
+            ));
+        }
+        if used.saturating_add(total_size + chunk.len() as u64) > max_total {
+            return Err(upload_error(
+                UploadErrorCode::FileTooLarge,
+                format!("Files exceed maximum allowed upload size of {max_total} bytes"),
+                details,
+            ));
+        }
+
+        file.write_all(&chunk)
             .await
-            .map_err(|e| ApiError::InternalError(format!("Failed to write metadata: {e}")))?;
-    }
+            .map_err(|e| ApiError::InternalError(format!("Failed to write file: {e}")))?;
+        hasher.update(&chunk);
 
-    if !file_saved {
-        return Err(ApiError::InvalidRequest(
-            "No file provided in multipart request".to_string(),
-        ));
+        total_size += chunk.len() as u64;
     }
+    file.flush()
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to write file: {e}")))?;
 
-    Ok(Json(UploadResponse {
-        attachment_id,
-        filename,
+    Ok(Some(ReceivedFile {
         size: total_size,
+        sha256: format!("{:x}", hasher.finalize()),
     }))
 }
 
+fn missing_file(details: &UploadErrorDetails) -> ApiError {
+    let seen = if details.fields_seen.is_empty() {
+        "none".to_string()
+    } else {
+        details.fields_seen.join(", ")
+    };
+    upload_error(
+        UploadErrorCode::MissingFile,
+        format!(
+            "No file provided in multipart request; expected field `{FILE_FIELD}` with a filename, saw: {seen}"
+        ),
+        details,
+    )
+}
+
 #[utoipa::path(
     get,
     path = "/api/v1/attachments/{id}",
     params(
-        ("id" = String, Path, description = "Attachment ID (UUID)")
+        ("id" = String, Path, description = "Attachment ID (UUID)"),
+        ("x-codex-namespace" = Option<String>, Header, description = "Namespace within the token's attachments"),
+        ("if-none-match" = Option<String>, Header, description = "ETags the client already has"),
+        ("range" = Option<String>, Header, description = "A single byte range, e.g. `bytes=0-1023`")
     ),
     responses(
         (status = 200, description = "File download", content_type = "application/octet-stream"),
+        (status = 206, description = "The requested byte range", content_type = "application/octet-stream"),
+        (status = 304, description = "The file matches `If-None-Match`"),
         (status = 400, description = "Invalid attachment ID"),
         (status = 401, description = "Unauthorized"),
-        (status = 404, description = "Attachment not found"),
+        (status = 404, description = "Attachment not found, or owned by another namespace"),
+        (status = 416, description = "The range starts past the end of the file"),
         (status = 500, description = "Internal server error")
     ),
     security(
@@ -171,48 +1011,78 @@ pub async fn upload_attachment(
 )]
 pub async fn download_attachment(
     State(state): State<WebServerState>,
+    scope: AttachmentScope,
     Path(id): Path<String>,
+    headers: HeaderMap,
 ) -> Result<Response, ApiError> {
-    // Validate ID is a valid UUID to prevent path traversal
-    uuid::Uuid::parse_str(&id).map_err(|_| ApiError::AttachmentNotFound)?;
-
-    let file_path = state.attachments_dir.join(&id);
-    let metadata_path = state.attachments_dir.join(format!("{id}.json"));
-
-    if !file_path.exists() {
+    let resolved = resolve_attachment(&state, &scope, &id).await?;
+    let Some(metadata) = resolved.metadata else {
         return Err(ApiError::AttachmentNotFound);
-    }
-
-    // Canonicalize and verify paths are within attachments_dir
-    let canonical_file_path = file_path
-        .canonicalize()
-        .map_err(|_| ApiError::AttachmentNotFound)?;
-    let canonical_attachments_dir = state.attachments_dir.canonicalize().map_err(|e| {
-        ApiError::InternalError(format!("Failed to resolve attachments directory: {e}"))
-    })?;
+    };
+    let etag = metadata
+        .sha256
+        .as_deref()
+        .and_then(|sha256| HeaderValue::from_str(&format!("\"{sha256}\"")).ok());
 
-    if !canonical_file_path.starts_with(&canonical_attachments_dir) {
-        return Err(ApiError::InvalidRequest(
-            "Invalid attachment path".to_string(),
-        ));
+    if let Some(etag) = &etag
+        && headers
+            .get(IF_NONE_MATCH)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| etag_matches(value, etag))
+    {
+        let mut response = Response::new(axum::body::Body::empty());
+        *response.status_mut() = StatusCode::NOT_MODIFIED;
+        response.headers_mut().insert(ETAG, etag.clone());
+        return Ok(response);
     }
 
-    let metadata_json = fs::read_to_string(&metadata_path)
-        .await
-        .map_err(|_| ApiError::AttachmentNotFound)?;
-
-    let metadata: AttachmentMetadata = serde_json::from_str(&metadata_json)
-        .map_err(|e| ApiError::InternalError(format!("Failed to parse metadata: {e}")))?;
-
     // Stream the file instead of reading it all into memory
-    let file = fs::File::open(&canonical_file_path)
+    let mut file = fs::File::open(&resolved.path)
         .await
         .map_err(|e| ApiError::InternalError(format!("Failed to open file: {e}")))?;
+    let len = file
+        .metadata()
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to read file: {e}")))?
+        .len();
 
-    let stream = ReaderStream::new(file);
-    let body = axum::body::Body::from_stream(stream);
-
-    let mut response = Response::new(body);
+    let range = headers
+        .get(RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| parse_range(value, len));
+    let mut response = match range {
+        None => Response::new(axum::body::Body::from_stream(ReaderStream::new(file))),
+        Some(ByteRange::Unsatisfiable) => {
+            let mut response = Response::new(axum::body::Body::empty());
+            *response.status_mut() = StatusCode::RANGE_NOT_SATISFIABLE;
+            if let Ok(content_range) = HeaderValue::from_str(&format!("bytes */{len}")) {
+                response.headers_mut().insert(CONTENT_RANGE, content_range);
+            }
+            return Ok(response);
+        }
+        Some(ByteRange::Satisfiable { start, end }) => {
+            file.seek(SeekFrom::Start(start))
+                .await
+                .map_err(|e| ApiError::InternalError(format!("Failed to read file: {e}")))?;
+            let stream = ReaderStream::new(file.take(end - start + 1));
+            let mut response = Response::new(axum::body::Body::from_stream(stream));
+            *response.status_mut() = StatusCode::PARTIAL_CONTENT;
+            if let Ok(content_range) = HeaderValue::from_str(&format!("bytes {start}-{end}/{len}"))
+            {
+                response.headers_mut().insert(CONTENT_RANGE, content_range);
+            }
+            response
+                .headers_mut()
+                .insert(CONTENT_LENGTH, HeaderValue::from(end - start + 1));
+            response
+        }
+    };
+    response
+        .headers_mut()
+        .insert(ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    if let Some(etag) = etag {
+        response.headers_mut().insert(ETAG, etag);
+    }
 
     // Safely parse headers with fallbacks
     let content_type = metadata
@@ -243,3 +1113,246 @@ pub async fn download_attachment(
 
     Ok(response)
 }
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/attachments",
+    params(
+        ("limit" = Option<usize>, Query, description = "Maximum number of attachments to return, 1 to 500 (default: 100)"),
+        ("cursor" = Option<String>, Query, description = "`next_cursor` from the previous page"),
+        ("x-codex-namespace" = Option<String>, Header, description = "Namespace within the token's attachments")
+    ),
+    responses(
+        (status = 200, description = "Attachments the caller can read, most recent first", body = ListAttachmentsResponse),
+        (status = 400, description = "Invalid query parameters or `X-Codex-Namespace`"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Attachments"
+)]
+pub async fn list_attachments(
+    State(state): State<WebServerState>,
+    scope: AttachmentScope,
+    Query(params): Query<ListAttachmentsParams>,
+) -> Result<Json<ListAttachmentsResponse>, ApiError> {
+    if params.limit == Some(0) {
+        return Err(ApiError::InvalidRequest(
+            "limit must be at least 1".to_string(),
+        ));
+    }
+    let start = match &params.cursor {
+        Some(cursor) => cursor
+            .parse::<usize>()
+            .map_err(|_| ApiError::InvalidRequest("Invalid cursor".to_string()))?,
+        None => 0,
+    };
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_LIST_LIMIT)
+        .min(MAX_LIST_LIMIT);
+
+    let mut attachments = readable_attachments(&state, &scope).await;
+    attachments.sort_by(|a, b| {
+        b.created_at
+            .cmp(&a.created_at)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    let next_cursor = (attachments.len() > start + limit).then(|| (start + limit).to_string());
+    let data = attachments.into_iter().skip(start).take(limit).collect();
+
+    Ok(Json(ListAttachmentsResponse { data, next_cursor }))
+}
+
+/// Metadata of every attachment `scope` can read. Sidecars whose file is
+/// gone or that do not parse are skipped; metadata without `created_at`
+/// takes the file's modification time.
+async fn readable_attachments(
+    state: &WebServerState,
+    scope: &AttachmentScope,
+) -> Vec<AttachmentMetadata> {
+    let attachments_dir = state.attachments_dir.as_path();
+    let mut attachments = Vec::new();
+    for dir in readable_dirs(state, scope).await {
+        let Ok(mut entries) = fs::read_dir(&dir).await else {
+            continue;
+        };
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let file_name = entry.file_name();
+            let Some(id) = file_name
+                .to_str()
+                .and_then(|name| name.strip_suffix(".json"))
+                .filter(|id| Uuid::parse_str(id).is_ok())
+            else {
+                continue;
+            };
+            let Ok(json) = fs::read_to_string(entry.path()).await else {
+                continue;
+            };
+            let Ok(mut metadata) = serde_json::from_str::<AttachmentMetadata>(&json) else {
+                tracing::warn!(
+                    "Skipping unreadable attachment metadata {}",
+                    entry.path().display()
+                );
+                continue;
+            };
+            let Ok(file) = fs::metadata(dir.join(metadata.blob_id())).await else {
+                continue;
+            };
+            if metadata.id != id
+                || !scope.can_read(owner_of(Some(&metadata), &dir, attachments_dir))
+            {
+                continue;
+            }
+            if metadata.created_at.is_none() {
+                metadata.created_at = file.modified().ok().and_then(unix_seconds);
+            }
+            attachments.push(metadata);
+        }
+    }
+    attachments
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/attachments/{id}",
+    params(
+        ("id" = String, Path, description = "Attachment ID (UUID)"),
+        ("x-codex-namespace" = Option<String>, Header, description = "Namespace within the token's attachments")
+    ),
+    responses(
+        (status = 200, description = "Attachment and its metadata deleted", body = DeleteAttachmentResponse),
+        (status = 400, description = "Invalid attachment ID"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Attachment not found, already deleted, or owned by another namespace"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Attachments"
+)]
+pub async fn delete_attachment(
+    State(state): State<WebServerState>,
+    scope: AttachmentScope,
+    Path(id): Path<String>,
+) -> Result<Json<DeleteAttachmentResponse>, ApiError> {
+    let resolved = resolve_attachment(&state, &scope, &id).await?;
+    let Some(dir) = resolved.path.parent() else {
+        return Err(ApiError::AttachmentNotFound);
+    };
+    match remove_attachment(&state, dir, &id).await {
+        Ok(()) => Ok(Json(DeleteAttachmentResponse { success: true })),
+        // Deleted by a concurrent request.
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Err(ApiError::AttachmentNotFound),
+        Err(err) => Err(ApiError::InternalError(format!(
+            "Failed to delete attachment: {err}"
+        ))),
+    }
+}
+
+/// Deletes attachment `id` from `dir`: its metadata, and its file unless
+/// another attachment in `dir` still shares it. A shared file goes with the
+/// last attachment that uses it.
+pub async fn remove_attachment(
+    state: &WebServerState,
+    dir: &std::path::Path,
+    id: &str,
+) -> std::io::Result<()> {
+    let _blobs = state.attachment_blobs.lock().await;
+    let metadata_path = dir.join(format!("{id}.json"));
+    let metadata = match fs::read_to_string(&metadata_path).await {
+        Ok(json) => {
+            Some(serde_json::from_str::<AttachmentMetadata>(&json).map_err(std::io::Error::other)?)
+        }
+        // Only the flat layout has attachments without metadata.
+        Err(err)
+            if err.kind() == std::io::ErrorKind::NotFound
+                && fs::canonicalize(&state.attachments_dir).await.ok()
+                    == fs::canonicalize(dir).await.ok() =>
+        {
+            None
+        }
+        Err(err) => return Err(err),
+    };
+    let Some(metadata) = metadata else {
+        return fs::remove_file(dir.join(id)).await;
+    };
+    fs::remove_file(&metadata_path).await?;
+    let blob_id = metadata.blob_id();
+    if dir_metadata(dir)
+        .await
+        .iter()
+        .any(|other| other.blob_id() == blob_id)
+    {
+        return Ok(());
+    }
+    match fs::remove_file(dir.join(blob_id)).await {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> Option<i64> {
+    let duration = time.duration_since(UNIX_EPOCH).ok()?;
+    i64::try_from(duration.as_secs()).ok()
+}
+
+/// Whether an `If-None-Match` value names `etag`, comparing weakly as the
+/// header asks.
+fn etag_matches(if_none_match: &str, etag: &HeaderValue) -> bool {
+    let etag = etag.to_str().unwrap_or_default();
+    if_none_match.split(',').map(str::trim).any(|candidate| {
+        candidate == "*" || candidate.strip_prefix("W/").unwrap_or(candidate) == etag
+    })
+}
+
+/// A `Range` header resolved against a file's length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ByteRange {
+    /// Inclusive bounds within the file
+    Satisfiable {
+        start: u64,
+        end: u64,
+    },
+    Unsatisfiable,
+}
+
+/// Parses a single `bytes=start-end`, `bytes=start-` or `bytes=-suffix`
+/// range. `None` for anything else, including several ranges, which is
+/// served as the whole file.
+fn parse_range(value: &str, len: u64) -> Option<ByteRange> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+    let range = if start.is_empty() {
+        let suffix = end.parse::<u64>().ok()?;
+        if suffix == 0 || len == 0 {
+            return Some(ByteRange::Unsatisfiable);
+        }
+        (len.saturating_sub(suffix), len - 1)
+    } else {
+        let start = start.parse::<u64>().ok()?;
+        let end = if end.is_empty() {
+            u64::MAX
+        } else {
+            end.parse::<u64>().ok()?
+        };
+        if end < start {
+            return None;
+        }
+        if start >= len {
+            return Some(ByteRange::Unsatisfiable);
+        }
+        (start, end.min(len - 1))
+    };
+    Some(ByteRange::Satisfiable {
+        start: range.0,
+        end: range.1,
+    })
+}
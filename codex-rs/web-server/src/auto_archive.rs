@@ -0,0 +1,153 @@
+//! Background policy that archives idle in-memory threads.
+//!
+//! A thread is idle when it has no running turn, no attached SSE consumers and
+//! no activity for the configured duration. Archiving shuts the thread down so
+//! its rollout is flushed, then drops it from the `ThreadManager`; the rollout
+//! is kept, so `POST /threads/{id}/resume` brings it back.
+
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::AgentStatus;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::Op;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+use tokio::task::JoinHandle;
+
+use crate::state::WebServerState;
+use crate::thread_status::StatusTransition;
+
+/// Idle duration in seconds after which threads are auto-archived. Unset or
+/// `0` disables the policy.
+pub const AUTO_ARCHIVE_IDLE_SECS_ENV: &str = "CODEX_WEB_AUTO_ARCHIVE_IDLE_SECS";
+
+const MAX_SWEEP_INTERVAL: Duration = Duration::from_secs(300);
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A thread dropped from memory by the policy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutoArchivedThread {
+    /// Unix seconds
+    pub archived_at: i64,
+    pub rollout_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoArchivePolicy {
+    pub idle_after: Duration,
+    pub sweep_interval: Duration,
+}
+
+impl AutoArchivePolicy {
+    pub fn new(idle_after: Duration) -> Self {
+        Self {
+            idle_after,
+            sweep_interval: (idle_after / 4).clamp(Duration::from_secs(1), MAX_SWEEP_INTERVAL),
+        }
+    }
+
+    /// Reads the policy from `CODEX_WEB_AUTO_ARCHIVE_IDLE_SECS`. Returns `None`
+    /// when the policy is disabled.
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var(AUTO_ARCHIVE_IDLE_SECS_ENV).ok()?;
+        match value.trim().parse::<u64>() {
+            Ok(0) => None,
+            Ok(secs) => Some(Self::new(Duration::from_secs(secs))),
+            Err(err) => {
+                tracing::warn!("Ignoring invalid {AUTO_ARCHIVE_IDLE_SECS_ENV}={value:?}: {err}");
+                None
+            }
+        }
+    }
+
+    pub fn spawn(self, state: WebServerState) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(self.sweep_interval);
+            interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                sweep_idle_threads(&state, self.idle_after).await;
+            }
+        })
+    }
+}
+
+/// Archives every loaded thread that has been idle for at least `idle_after`
+/// and returns the archived thread ids.
+pub async fn sweep_idle_threads(state: &WebServerState, idle_after: Duration) -> Vec<ThreadId> {
+    let now = Instant::now();
+    let mut archived = Vec::new();
+
+    for thread_id in state.thread_manager.list_thread_ids().await {
+        let idle = state
+            .sessions
+            .write()
+            .await
+            .idle_for(thread_id, now)
+            .is_some_and(|idle| idle >= idle_after);
+        if !idle {
+            continue;
+        }
+        let op = state.thread_ops.begin(thread_id);
+        let Ok(guard) = op.lock().await else {
+            continue;
+        };
+        let Ok(thread) = state.thread_manager.get_thread(thread_id).await else {
+            continue;
+        };
+        if matches!(thread.agent_status().await, AgentStatus::Running) {
+            continue;
+        }
+        // Requests that looked the thread up before this point get a 409
+        // rather than a thread that is shutting down.
+        guard.bump();
+        // A pump left from a stream that just disconnected would take the
+        // shutdown events drained below.
+        state.sessions.write().await.stop_unwatched_pump(thread_id);
+
+        if let Err(err) = thread.submit(Op::Shutdown).await {
+            tracing::warn!("Failed to shut down idle thread {thread_id}: {err}");
+            continue;
+        }
+        // No SSE stream or pump is attached, so draining events here cannot
+        // steal them from a client.
+        let drained = tokio::time::timeout(SHUTDOWN_TIMEOUT, async {
+            loop {
+                match thread.next_event().await {
+                    Ok(event) if matches!(event.msg, EventMsg::ShutdownComplete) => break,
+                    Ok(_) => {}
+                    Err(_) => break,
+                }
+            }
+        })
+        .await;
+        if drained.is_err() {
+            tracing::warn!("Timed out waiting for idle thread {thread_id} to shut down");
+        }
+
+        state.thread_manager.remove_thread(&thread_id).await;
+        state.sessions.write().await.forget(thread_id);
+        state.turn_queue.clear(thread_id);
+        let archived_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or_default();
+        state.auto_archived.lock().await.insert(
+            thread_id,
+            AutoArchivedThread {
+                archived_at,
+                rollout_path: thread.rollout_path(),
+            },
+        );
+        state
+            .thread_statuses
+            .apply(thread_id, StatusTransition::Archived);
+        state.metrics.record_auto_archive();
+        tracing::info!("Auto-archived idle thread {thread_id}");
+        archived.push(thread_id);
+    }
+
+    archived
+}
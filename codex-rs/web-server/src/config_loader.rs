@@ -0,0 +1,165 @@
+//! Single-flight loading of `config.toml` for request handlers.
+//!
+//! Clients tend to fire several requests at once on startup, and each handler
+//! needs a freshly loaded `Config`. Loads that overlap share one in-progress
+//! parse; nothing is kept once it finishes, so the next request re-reads the
+//! files and a failed load is retried.
+//!
+//! Config writes made through the API are recorded by user-layer version
+//! (the content hash `ConfigService` returns). A write drops the in-flight
+//! load, so a later request cannot join a parse that started before the
+//! write, and [`ConfigLoader::load_at_least`] lets a client that just wrote
+//! wait until the loaded config reflects that write.
+
+use codex_core::config::Config;
+use codex_core::config::ConfigBuilder;
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use futures::future::Shared;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+use crate::error::ApiError;
+
+/// Performs one underlying load; replaced in tests to count parses.
+pub type ConfigLoadFn = Arc<dyn Fn() -> BoxFuture<'static, std::io::Result<Config>> + Send + Sync>;
+
+/// How long [`ConfigLoader::load_at_least`] keeps reloading.
+const MIN_VERSION_WAIT: Duration = Duration::from_secs(2);
+const MIN_VERSION_POLL: Duration = Duration::from_millis(25);
+
+/// Written versions kept for ordering; older ones only match exactly.
+const MAX_TRACKED_WRITES: usize = 256;
+
+type SharedLoad = Shared<BoxFuture<'static, Result<Config, Arc<std::io::Error>>>>;
+
+pub struct ConfigLoader {
+    load_fn: ConfigLoadFn,
+    in_flight: Mutex<Option<SharedLoad>>,
+    /// User-layer versions written through the API, oldest first.
+    writes: Mutex<Vec<String>>,
+}
+
+impl ConfigLoader {
+    pub fn new(load_fn: ConfigLoadFn) -> Self {
+        Self {
+            load_fn,
+            in_flight: Mutex::new(None),
+            writes: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Loader that reads the config under `codex_home`.
+    pub fn for_codex_home(codex_home: PathBuf) -> Self {
+        Self::new(Arc::new(move || {
+            ConfigBuilder::default()
+                .codex_home(codex_home.clone())
+                .build()
+                .boxed()
+        }))
+    }
+
+    /// Loads the config, joining a load that is already running. Every
+    /// waiter of a failed load gets the error.
+    pub async fn load(&self) -> Result<Config, ApiError> {
+        let load = {
+            let mut in_flight = self
+                .in_flight
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            match in_flight.as_ref() {
+                Some(load) => load.clone(),
+                None => {
+                    let load = (self.load_fn)()
+                        .map(|result| result.map_err(Arc::new))
+                        .boxed()
+                        .shared();
+                    *in_flight = Some(load.clone());
+                    load
+                }
+            }
+        };
+
+        let result = load.clone().await;
+
+        let mut in_flight = self
+            .in_flight
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if in_flight
+            .as_ref()
+            .is_some_and(|current| current.ptr_eq(&load))
+        {
+            *in_flight = None;
+        }
+        drop(in_flight);
+
+        result.map_err(|e| ApiError::InternalError(format!("Failed to load config: {e}")))
+    }
+
+    /// Records a config write that produced user-layer `version`.
+    pub fn record_write(&self, version: &str) {
+        let mut writes = self
+            .writes
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        writes.push(version.to_string());
+        if writes.len() > MAX_TRACKED_WRITES {
+            writes.remove(0);
+        }
+        drop(writes);
+
+        // A load already running may have read the file before the write.
+        *self
+            .in_flight
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = None;
+    }
+
+    /// Whether a config whose user layer is at `loaded` includes the write
+    /// that produced `min_version`. Versions are content hashes, so they are
+    /// ordered by when this server wrote them; a version it did not write
+    /// only satisfies itself.
+    pub fn satisfies(&self, loaded: &str, min_version: &str) -> bool {
+        if loaded == min_version {
+            return true;
+        }
+        let writes = self
+            .writes
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let position = |version: &str| writes.iter().rposition(|write| write == version);
+        matches!(
+            (position(loaded), position(min_version)),
+            (Some(loaded), Some(min)) if loaded > min
+        )
+    }
+
+    /// Loads the config, reloading until its user layer is at
+    /// `min_version` or later. Fails with a stale-config error if that does
+    /// not happen within a short wait.
+    pub async fn load_at_least(&self, min_version: &str) -> Result<Config, ApiError> {
+        let deadline = Instant::now() + MIN_VERSION_WAIT;
+        loop {
+            let config = self.load().await?;
+            let loaded = config
+                .config_layer_stack
+                .get_user_layer()
+                .map(|layer| layer.version.clone())
+                .unwrap_or_default();
+            if self.satisfies(&loaded, min_version) {
+                return Ok(config);
+            }
+            if Instant::now() >= deadline {
+                return Err(ApiError::StaleConfig {
+                    min_config_version: min_version.to_string(),
+                    loaded_version: loaded,
+                });
+            }
+            tokio::time::sleep(MIN_VERSION_POLL).await;
+        }
+    }
+}
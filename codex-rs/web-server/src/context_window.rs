@@ -0,0 +1,79 @@
+//! How full a thread's context window is, for a client-side meter.
+//!
+//! `used` is the token count of the last model response, which covers the
+//! whole context the model saw. It is measured against the usable window of
+//! the model the thread runs with, the same window core reports in
+//! `TokenCount` events, and against the token count at which core compacts
+//! the context on its own. A turn that overrides the model changes the
+//! thread's model, so its token counts are measured against the override's
+//! window.
+
+use codex_core::CodexThread;
+use codex_protocol::openai_models::ModelInfo;
+use codex_protocol::protocol::TokenUsageInfo;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::state::WebServerState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ContextWindowUsage {
+    /// Tokens in the context as of the last model response
+    #[schema(example = 96000)]
+    pub used: i64,
+    /// Usable context window of the thread's model
+    #[schema(example = 258400)]
+    pub window: i64,
+    /// `used` as a whole percent of `window`, at most 100
+    #[schema(example = 37)]
+    pub percent: i64,
+    /// Token count at which core compacts the context automatically;
+    /// `None` when the model has no limit
+    #[schema(example = 244800)]
+    pub compaction_at: Option<i64>,
+}
+
+impl ContextWindowUsage {
+    /// `None` when the model does not declare a context window.
+    pub fn new(info: &TokenUsageInfo, model_info: &ModelInfo) -> Option<Self> {
+        let window = model_info
+            .context_window?
+            .saturating_mul(model_info.effective_context_window_percent)
+            / 100;
+        if window <= 0 {
+            return None;
+        }
+        let used = info.last_token_usage.tokens_in_context_window().max(0);
+        let percent = ((used as f64 / window as f64) * 100.0)
+            .clamp(0.0, 100.0)
+            .round() as i64;
+        Some(Self {
+            used,
+            window,
+            percent,
+            compaction_at: model_info.auto_compact_token_limit(),
+        })
+    }
+}
+
+/// The context meter of a loaded thread, once the model has reported usage.
+pub async fn for_thread(thread: &CodexThread) -> Option<ContextWindowUsage> {
+    let info = thread.token_usage_info().await?;
+    ContextWindowUsage::new(&info, &thread.model_info().await)
+}
+
+/// The context meter of a thread that is not loaded, from the last usage in
+/// its rollout and the model of its latest turn.
+pub async fn for_model(
+    state: &WebServerState,
+    model: &str,
+    info: &TokenUsageInfo,
+) -> Option<ContextWindowUsage> {
+    let config = state.config_loader.load().await.ok()?;
+    let model_info = state
+        .thread_manager
+        .get_models_manager()
+        .get_model_info(model, &config)
+        .await;
+    ContextWindowUsage::new(info, &model_info)
+}
@@ -0,0 +1,132 @@
+use axum::http::HeaderValue;
+use axum::http::Method;
+use axum::http::header::ACCESS_CONTROL_REQUEST_METHOD;
+use axum::http::request::Parts;
+use std::sync::Arc;
+use tower_http::cors::AllowOrigin;
+use tower_http::cors::Any;
+use tower_http::cors::CorsLayer;
+
+/// Comma-separated origins allowed to call read-only and event-stream routes.
+pub const CORS_READ_ORIGINS_ENV: &str = "CODEX_WEB_CORS_READ_ORIGINS";
+/// Comma-separated origins allowed to call mutating and admin routes.
+pub const CORS_WRITE_ORIGINS_ENV: &str = "CODEX_WEB_CORS_WRITE_ORIGINS";
+
+/// `POST` routes that only evaluate their body and change nothing.
+const READ_ONLY_POST_SUFFIXES: &[&str] = &["/sandbox/preview"];
+
+/// Admin and configuration routes. Even their `GET`s expose settings,
+/// storage and usage, so they belong to the write group.
+const ADMIN_ROUTES: &[&str] = &[
+    "/api/v2/config",
+    "/api/v2/maintenance",
+    "/api/v2/threads/scan",
+    "/api/v2/usage",
+];
+
+const DEFAULT_ORIGINS: &[&str] = &[
+    "http://localhost:3000",
+    "http://127.0.0.1:3000",
+    "http://localhost:8080",
+    "http://127.0.0.1:8080",
+];
+
+/// Origin allow-lists for the two route groups.
+///
+/// Read routes (`GET`/`HEAD` of listings, SSE streams and downloads, plus
+/// the `POST` routes in [`READ_ONLY_POST_SUFFIXES`]) accept both lists;
+/// everything else, including every method of the [`ADMIN_ROUTES`], only
+/// accepts `write`.
+#[derive(Debug, Clone)]
+pub struct CorsOrigins {
+    pub read: Vec<HeaderValue>,
+    pub write: Vec<HeaderValue>,
+}
+
+impl CorsOrigins {
+    pub fn from_env() -> Self {
+        let read = parse_origins(std::env::var(CORS_READ_ORIGINS_ENV).ok().as_deref());
+        let write = parse_origins(std::env::var(CORS_WRITE_ORIGINS_ENV).ok().as_deref());
+        Self { read, write }
+    }
+
+    /// Builds the CORS layer for the whole router.
+    ///
+    /// Several paths serve both groups (`GET`/`POST /api/v2/threads`,
+    /// `GET`/`PUT /api/v2/config`), so separate layers per merged router would
+    /// leave preflight handling to whichever router axum picks for `OPTIONS`.
+    /// Instead a single layer classifies each request, using
+    /// `Access-Control-Request-Method` for preflights.
+    pub fn into_layer(self) -> CorsLayer {
+        let origins = Arc::new(self);
+        CorsLayer::new()
+            .allow_origin(AllowOrigin::predicate(
+                move |origin: &HeaderValue, parts: &Parts| origins.allows(origin, parts),
+            ))
+            .allow_methods(Any)
+            .allow_headers(Any)
+    }
+
+    fn allows(&self, origin: &HeaderValue, parts: &Parts) -> bool {
+        let method = if parts.method == Method::OPTIONS {
+            parts
+                .headers
+                .get(ACCESS_CONTROL_REQUEST_METHOD)
+                .and_then(|value| Method::from_bytes(value.as_bytes()).ok())
+                .unwrap_or(Method::OPTIONS)
+        } else {
+            parts.method.clone()
+        };
+        let read_only = route_group(&method, parts.uri.path()) == RouteGroup::Read;
+
+        self.write.contains(origin) || (read_only && self.read.contains(origin))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum RouteGroup {
+    Read,
+    Write,
+}
+
+/// Group of the route `method` and `path` name. A path may serve both
+/// groups, e.g. `GET`/`POST /api/v2/threads`, so outside the admin routes
+/// the method decides.
+fn route_group(method: &Method, path: &str) -> RouteGroup {
+    let admin = ADMIN_ROUTES.iter().any(|route| {
+        path.strip_prefix(route)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    });
+    let read = *method == Method::GET
+        || *method == Method::HEAD
+        || (*method == Method::POST
+            && READ_ONLY_POST_SUFFIXES
+                .iter()
+                .any(|suffix| path.ends_with(suffix)));
+    if read && !admin {
+        RouteGroup::Read
+    } else {
+        RouteGroup::Write
+    }
+}
+
+fn parse_origins(value: Option<&str>) -> Vec<HeaderValue> {
+    match value.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(value) => value
+            .split(',')
+            .map(str::trim)
+            .filter(|origin| !origin.is_empty())
+            .filter_map(|origin| match HeaderValue::from_str(origin) {
+                Ok(origin) => Some(origin),
+                Err(err) => {
+                    tracing::warn!("Ignoring invalid CORS origin {origin:?}: {err}");
+                    None
+                }
+            })
+            .collect(),
+        None => DEFAULT_ORIGINS
+            .iter()
+            .map(|origin| HeaderValue::from_static(origin))
+            .collect(),
+    }
+}
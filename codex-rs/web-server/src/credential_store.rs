@@ -0,0 +1,114 @@
+//! Chooses where the server keeps model-provider credentials.
+//!
+//! The default is the OS keyring. Headless Linux hosts often have no secret
+//! service, which used to surface as opaque errors from auth handlers. The
+//! keyring is now probed once at startup: when it is unavailable the server
+//! falls back to `codex_home/auth.json` with a warning, or refuses to start
+//! under `--no-credential-fallback`.
+
+use codex_core::auth::AuthCredentialsStoreMode;
+use codex_keyring_store::KeyringStore;
+use std::fmt;
+
+/// `file`, `keyring`, `auto` or `ephemeral`; overridden by the flag.
+pub const CREDENTIAL_STORE_ENV: &str = "CODEX_WEB_CREDENTIAL_STORE";
+/// `--credential-store=<mode>`
+pub const CREDENTIAL_STORE_FLAG: &str = "--credential-store";
+pub const NO_CREDENTIAL_FALLBACK_FLAG: &str = "--no-credential-fallback";
+
+/// The probe reads an entry that never exists, under the service core uses.
+const PROBE_SERVICE: &str = "Codex Auth";
+const PROBE_ACCOUNT: &str = "codex-web-server-probe";
+
+/// The keyring was requested, is unavailable, and fallback is forbidden.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyringUnavailable {
+    pub reason: String,
+}
+
+impl fmt::Display for KeyringUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "keyring credential storage is unavailable ({}) and {NO_CREDENTIAL_FALLBACK_FLAG} \
+             forbids falling back to file storage; start a secret service or set \
+             {CREDENTIAL_STORE_ENV}=file",
+            self.reason
+        )
+    }
+}
+
+impl std::error::Error for KeyringUnavailable {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CredentialStoreSettings {
+    pub requested: AuthCredentialsStoreMode,
+    /// Use file storage when the keyring is requested but unavailable.
+    pub allow_fallback: bool,
+}
+
+impl Default for CredentialStoreSettings {
+    fn default() -> Self {
+        Self {
+            requested: AuthCredentialsStoreMode::Keyring,
+            allow_fallback: true,
+        }
+    }
+}
+
+impl CredentialStoreSettings {
+    /// Reads the settings from command-line `args` (without the program
+    /// name) and the value of [`CREDENTIAL_STORE_ENV`].
+    pub fn from_args_and_env(args: &[String], env_value: Option<&str>) -> Result<Self, String> {
+        let flag_prefix = format!("{CREDENTIAL_STORE_FLAG}=");
+        let flag_value = args.iter().find_map(|arg| arg.strip_prefix(&flag_prefix));
+        let requested = match flag_value.or(env_value) {
+            Some(value) => parse_mode(value)?,
+            None => AuthCredentialsStoreMode::Keyring,
+        };
+        Ok(Self {
+            requested,
+            allow_fallback: !args.iter().any(|arg| arg == NO_CREDENTIAL_FALLBACK_FLAG),
+        })
+    }
+
+    /// The store mode to hand to `AuthManager`. `keyring` and `auto` resolve
+    /// to `keyring` when `keyring` answers a probe read and to `file`
+    /// otherwise; a failed probe under `keyring` without fallback is an
+    /// error.
+    pub fn resolve(
+        self,
+        keyring: &dyn KeyringStore,
+    ) -> Result<AuthCredentialsStoreMode, KeyringUnavailable> {
+        match self.requested {
+            AuthCredentialsStoreMode::Keyring | AuthCredentialsStoreMode::Auto => {}
+            mode @ (AuthCredentialsStoreMode::File | AuthCredentialsStoreMode::Ephemeral) => {
+                return Ok(mode);
+            }
+        }
+        let Err(err) = keyring.load(PROBE_SERVICE, PROBE_ACCOUNT) else {
+            return Ok(AuthCredentialsStoreMode::Keyring);
+        };
+        let reason = err.message();
+        if self.requested == AuthCredentialsStoreMode::Keyring && !self.allow_fallback {
+            return Err(KeyringUnavailable { reason });
+        }
+        tracing::warn!(
+            "⚠️  Keyring unavailable ({reason}); storing credentials in auth.json instead. \
+             Pass {NO_CREDENTIAL_FALLBACK_FLAG} to refuse to start without a keyring"
+        );
+        Ok(AuthCredentialsStoreMode::File)
+    }
+}
+
+fn parse_mode(value: &str) -> Result<AuthCredentialsStoreMode, String> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "file" => Ok(AuthCredentialsStoreMode::File),
+        "keyring" => Ok(AuthCredentialsStoreMode::Keyring),
+        "auto" => Ok(AuthCredentialsStoreMode::Auto),
+        "ephemeral" => Ok(AuthCredentialsStoreMode::Ephemeral),
+        other => Err(format!(
+            "unknown credential store {other:?}; expected file, keyring, auto or ephemeral"
+        )),
+    }
+}
@@ -1,10 +1,52 @@
 use axum::Json;
+use axum::extract::rejection::JsonRejection;
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::response::Response;
+use codex_core::error::CodexErr;
+use codex_protocol::openai_models::InputModality;
+use serde::Serialize;
+use serde_json::Value;
 use serde_json::json;
 use utoipa::ToSchema;
 
+use crate::approval_manager::ApprovalErrorCode;
+use crate::attachments::FILE_FIELD;
+use crate::attachments::UploadErrorCode;
+use crate::attachments::UploadErrorDetails;
+use crate::instance::instance_id;
+use crate::pairing::PairingErrorCode;
+use crate::patches::PatchFileResult;
+use crate::patches::PatchHunkError;
+use crate::requirements::RequirementViolation;
+use crate::review_registry::ReviewStatus;
+
+/// Actionable category for a thread that failed to start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreadStartErrorCode {
+    /// No usable credentials for the model provider (401)
+    AuthRequired,
+    /// The provider does not know the requested model (400)
+    ModelNotFound,
+    /// A required MCP server failed to start (502)
+    McpStartupFailed,
+    /// The working directory is missing, not a directory, or read-only (400)
+    CwdUnwritable,
+}
+
+impl ThreadStartErrorCode {
+    fn status(self) -> StatusCode {
+        match self {
+            ThreadStartErrorCode::AuthRequired => StatusCode::UNAUTHORIZED,
+            ThreadStartErrorCode::ModelNotFound | ThreadStartErrorCode::CwdUnwritable => {
+                StatusCode::BAD_REQUEST
+            }
+            ThreadStartErrorCode::McpStartupFailed => StatusCode::BAD_GATEWAY,
+        }
+    }
+}
+
 #[derive(Debug, ToSchema)]
 #[schema(example = json!({"error": "Unauthorized", "status": 401}))]
 pub enum ApiError {
@@ -16,10 +58,137 @@ pub enum ApiError {
     ThreadNotFound,
     AttachmentNotFound,
     Timeout(String),
+    /// The client answered after the server stopped waiting for it, e.g. an
+    /// expired approval.
+    RequestTimeout(String),
+    /// The thread is owned by another server instance.
+    MisdirectedRequest {
+        owner_instance_id: String,
+    },
+    /// A requested setting is outside the managed config requirements.
+    #[schema(value_type = Object)]
+    RequirementViolation(RequirementViolation),
+    /// Thread start failed for a reason the client can act on.
+    ThreadStartFailed {
+        code: ThreadStartErrorCode,
+        message: String,
+        /// MCP server that failed, for `mcp_startup_failed`
+        server: Option<String>,
+    },
+    /// The requested model is not in the server's model list.
+    UnknownModel {
+        model: String,
+        suggestions: Vec<String>,
+    },
+    /// The thread's model cannot read an input of the turn, e.g. an image.
+    UnsupportedInputModality {
+        model: String,
+        #[schema(value_type = Vec<String>)]
+        input_modalities: Vec<InputModality>,
+        /// Pickable models that accept images
+        suggestions: Vec<String>,
+    },
+    /// The working directory is outside every trusted root.
+    UntrustedCwd {
+        cwd: String,
+        trusted_roots: Vec<String>,
+    },
+    /// A provider override points at a host outside the allow-list.
+    ProviderHostNotAllowed {
+        host: String,
+        allowed_hosts: Vec<String>,
+    },
+    /// A submitted patch could not be parsed.
+    MalformedPatch {
+        hunk_errors: Vec<PatchHunkError>,
+    },
+    /// A submitted patch does not apply to the workspace.
+    PatchConflict {
+        files: Vec<PatchFileResult>,
+    },
+    /// The loaded config did not reach `min_config_version` in time.
+    StaleConfig {
+        min_config_version: String,
+        loaded_version: String,
+    },
+    /// A config write named an `expected_version` the layer is no longer at.
+    ConfigVersionConflict,
+    /// Another operation archived, resumed or unloaded the thread while this
+    /// request was in progress.
+    ThreadStateChanged,
+    /// The thread has a running turn and the request did not ask to stop it.
+    TurnInProgress,
+    /// A turn is running and the input was neither steered in nor queued.
+    TurnRunning {
+        /// `None` when the server has not seen which turn is running
+        active_turn_id: Option<String>,
+    },
+    /// An interrupt or steer named a turn other than the one running.
+    TurnMismatch {
+        /// `None` when no turn is running
+        active_turn_id: Option<String>,
+    },
+    /// Input to steer into the running turn arrived while no turn was running.
+    NoActiveTurn,
+    /// A ChatGPT login already holds the fixed OAuth callback port.
+    LoginInProgress {
+        login_id: String,
+    },
+    /// The review to cancel already completed or was cancelled.
+    ReviewFinished(ReviewStatus),
+    /// An approval answer could not be delivered.
+    ApprovalFailed(ApprovalErrorCode),
+    /// A pairing code was not accepted.
+    PairingFailed(PairingErrorCode),
+    /// An attachment upload could not be read.
+    UploadFailed {
+        code: UploadErrorCode,
+        message: String,
+        details: UploadErrorDetails,
+    },
+    /// An imported file is not a rollout; `line` is 1-based.
+    InvalidRollout {
+        line: usize,
+        message: String,
+    },
+    /// A mutating request whose `Content-Type` is not JSON.
+    UnsupportedMediaType,
+    /// A JSON request body that does not match the request type; `path`
+    /// names the offending field, or is `.` for the body itself.
+    InvalidBody {
+        path: String,
+        message: String,
+    },
 }
 
 impl IntoResponse for ApiError {
     fn into_response(self) -> Response {
+        let (status, body) = self.into_parts();
+        (status, Json(body)).into_response()
+    }
+}
+
+impl ApiError {
+    /// HTTP status and JSON body of the error response.
+    pub fn into_parts(self) -> (StatusCode, Value) {
+        let mut owner_instance_id = None;
+        let mut violation = None;
+        let mut start_failure = None;
+        let mut model_suggestions = None;
+        let mut modality_details = None;
+        let mut trusted_roots = None;
+        let mut allowed_hosts = None;
+        let mut patch_details = None;
+        let mut config_versions = None;
+        let mut approval_code = None;
+        let mut conflict_code = None;
+        let mut pairing_code = None;
+        let mut upload_failure = None;
+        let mut rollout_line = None;
+        let mut active_login = None;
+        let mut timed_out = false;
+        let mut active_turn = None;
+        let mut body_error = None;
         let (status, message) = match self {
             ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized".to_string()),
             ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
@@ -30,14 +199,341 @@ impl IntoResponse for ApiError {
                 (StatusCode::NOT_FOUND, "Attachment not found".to_string())
             }
             ApiError::Timeout(msg) => (StatusCode::GATEWAY_TIMEOUT, msg),
+            ApiError::RequestTimeout(msg) => {
+                timed_out = true;
+                (StatusCode::REQUEST_TIMEOUT, msg)
+            }
+            ApiError::MisdirectedRequest {
+                owner_instance_id: owner,
+            } => {
+                let message = format!("Thread is owned by instance {owner}");
+                owner_instance_id = Some(owner);
+                (StatusCode::MISDIRECTED_REQUEST, message)
+            }
+            ApiError::RequirementViolation(v) => {
+                let message = format!(
+                    "Requested value {} is not allowed by {}",
+                    v.requested, v.constraint
+                );
+                violation = Some(v);
+                (StatusCode::FORBIDDEN, message)
+            }
+            ApiError::ThreadStartFailed {
+                code,
+                message,
+                server,
+            } => {
+                start_failure = Some((code, server));
+                (code.status(), message)
+            }
+            ApiError::UnknownModel { model, suggestions } => {
+                model_suggestions = Some(suggestions);
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!("Unknown model {model}; set skip_validation for custom providers"),
+                )
+            }
+            ApiError::UnsupportedInputModality {
+                model,
+                input_modalities,
+                suggestions,
+            } => {
+                let supported = input_modalities
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let message =
+                    format!("Model {model} does not accept image input; it supports: {supported}");
+                modality_details = Some((model, input_modalities, suggestions));
+                (StatusCode::UNPROCESSABLE_ENTITY, message)
+            }
+            ApiError::UntrustedCwd {
+                cwd,
+                trusted_roots: roots,
+            } => {
+                trusted_roots = Some(roots);
+                (
+                    StatusCode::FORBIDDEN,
+                    format!("Working directory {cwd} is not under a trusted root"),
+                )
+            }
+            ApiError::ProviderHostNotAllowed {
+                host,
+                allowed_hosts: hosts,
+            } => {
+                allowed_hosts = Some(hosts);
+                (
+                    StatusCode::FORBIDDEN,
+                    format!("Provider host {host} is not allowed"),
+                )
+            }
+            ApiError::MalformedPatch { hunk_errors } => {
+                patch_details = Some(("hunk_errors", json!(hunk_errors)));
+                (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    "Patch is malformed".to_string(),
+                )
+            }
+            ApiError::PatchConflict { files } => {
+                patch_details = Some(("files", json!(files)));
+                (
+                    StatusCode::CONFLICT,
+                    "Patch does not apply to the workspace".to_string(),
+                )
+            }
+            ApiError::StaleConfig {
+                min_config_version,
+                loaded_version,
+            } => {
+                config_versions = Some((min_config_version, loaded_version));
+                (
+                    StatusCode::CONFLICT,
+                    "Config did not reach the requested version in time".to_string(),
+                )
+            }
+            ApiError::ConfigVersionConflict => {
+                conflict_code = Some("config_version_conflict");
+                (
+                    StatusCode::CONFLICT,
+                    "Configuration was modified since last read; fetch the latest version and retry"
+                        .to_string(),
+                )
+            }
+            ApiError::ThreadStateChanged => {
+                conflict_code = Some("thread_state_changed");
+                (
+                    StatusCode::CONFLICT,
+                    "Thread changed while the request was in progress; refresh and retry"
+                        .to_string(),
+                )
+            }
+            ApiError::TurnInProgress => {
+                conflict_code = Some("turn_in_progress");
+                (
+                    StatusCode::CONFLICT,
+                    "A turn is running; wait for it to finish or interrupt it first".to_string(),
+                )
+            }
+            ApiError::TurnRunning { active_turn_id } => {
+                conflict_code = Some("turn_in_progress");
+                active_turn = Some(active_turn_id);
+                (
+                    StatusCode::CONFLICT,
+                    "A turn is running; steer the input into it, queue it with queue=true or interrupt the turn first"
+                        .to_string(),
+                )
+            }
+            ApiError::TurnMismatch { active_turn_id } => {
+                conflict_code = Some("turn_mismatch");
+                active_turn = Some(active_turn_id);
+                (
+                    StatusCode::CONFLICT,
+                    "The requested turn is not the running turn".to_string(),
+                )
+            }
+            ApiError::NoActiveTurn => {
+                conflict_code = Some("no_active_turn");
+                (
+                    StatusCode::CONFLICT,
+                    "No turn is running; send the input as a new turn".to_string(),
+                )
+            }
+            ApiError::LoginInProgress { login_id } => {
+                conflict_code = Some("login_in_progress");
+                active_login = Some(login_id);
+                (
+                    StatusCode::CONFLICT,
+                    "A ChatGPT login is already in progress; finish or cancel it first".to_string(),
+                )
+            }
+            ApiError::ReviewFinished(review_status) => {
+                conflict_code = Some("review_finished");
+                let message = match review_status {
+                    ReviewStatus::Cancelled => "Review was already cancelled",
+                    ReviewStatus::Running | ReviewStatus::Completed => "Review already completed",
+                };
+                (StatusCode::CONFLICT, message.to_string())
+            }
+            ApiError::ApprovalFailed(code) => {
+                approval_code = Some(code);
+                let status = match code {
+                    ApprovalErrorCode::NotFound => StatusCode::NOT_FOUND,
+                    ApprovalErrorCode::Timeout => StatusCode::REQUEST_TIMEOUT,
+                    ApprovalErrorCode::ChannelClosed => StatusCode::GONE,
+                    ApprovalErrorCode::NoAmendmentProposed => StatusCode::BAD_REQUEST,
+                };
+                (status, code.message().to_string())
+            }
+            ApiError::PairingFailed(code) => {
+                pairing_code = Some(code);
+                let status = match code {
+                    PairingErrorCode::InvalidCode => StatusCode::UNAUTHORIZED,
+                    PairingErrorCode::Expired => StatusCode::GONE,
+                };
+                (status, code.message().to_string())
+            }
+            ApiError::UploadFailed {
+                code,
+                message,
+                details,
+            } => {
+                let status = match code {
+                    UploadErrorCode::FileTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+                    UploadErrorCode::MalformedMultipart | UploadErrorCode::MissingFile => {
+                        StatusCode::BAD_REQUEST
+                    }
+                };
+                upload_failure = Some((code, details));
+                (status, message)
+            }
+            ApiError::InvalidRollout { line, message } => {
+                rollout_line = Some(line);
+                (StatusCode::BAD_REQUEST, format!("line {line}: {message}"))
+            }
+            ApiError::UnsupportedMediaType => {
+                body_error = Some(("unsupported_media_type", None));
+                (
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    "Expected a request with `Content-Type: application/json`".to_string(),
+                )
+            }
+            ApiError::InvalidBody { path, message } => {
+                let message = if path == "." {
+                    message
+                } else {
+                    format!("{path}: {message}")
+                };
+                body_error = Some(("invalid_body", Some(path)));
+                (StatusCode::UNPROCESSABLE_ENTITY, message)
+            }
         };
 
-        let body = Json(json!({
+        let mut body = json!({
             "error": message,
             "status": status.as_u16(),
-        }));
+            "instance_id": instance_id(),
+        });
+        if let Some(owner_instance_id) = owner_instance_id {
+            body["owner_instance_id"] = json!(owner_instance_id);
+        }
+        if let Some(violation) = violation {
+            body["constraint"] = json!(violation.constraint);
+            body["requested"] = violation.requested;
+            body["allowed"] = json!(violation.allowed);
+        }
+        if let Some((code, server)) = start_failure {
+            body["code"] = json!(code);
+            if let Some(server) = server {
+                body["server"] = json!(server);
+            }
+        }
+        if let Some(suggestions) = model_suggestions {
+            body["code"] = json!(ThreadStartErrorCode::ModelNotFound);
+            body["suggestions"] = json!(suggestions);
+        }
+        if let Some((model, input_modalities, suggestions)) = modality_details {
+            body["code"] = json!("unsupported_input_modality");
+            body["model"] = json!(model);
+            body["input_modalities"] = json!(input_modalities);
+            body["suggestions"] = json!(suggestions);
+        }
+        if let Some(trusted_roots) = trusted_roots {
+            body["trusted_roots"] = json!(trusted_roots);
+        }
+        if let Some(allowed_hosts) = allowed_hosts {
+            body["allowed_hosts"] = json!(allowed_hosts);
+        }
+        if let Some((key, details)) = patch_details {
+            body[key] = details;
+        }
+        if let Some((min_config_version, loaded_version)) = config_versions {
+            body["min_config_version"] = json!(min_config_version);
+            body["loaded_version"] = json!(loaded_version);
+        }
+        if let Some(code) = conflict_code {
+            body["code"] = json!(code);
+        }
+        if let Some(active_turn_id) = active_turn {
+            body["active_turn_id"] = json!(active_turn_id);
+        }
+        if let Some(login_id) = active_login {
+            body["login_id"] = json!(login_id);
+        }
+        if timed_out {
+            body["code"] = json!("timeout");
+        }
+        if let Some(code) = approval_code {
+            body["code"] = json!(code);
+        }
+        if let Some(code) = pairing_code {
+            body["code"] = json!(code);
+        }
+        if let Some((code, details)) = upload_failure {
+            body["code"] = json!(code);
+            body["expected_field"] = json!(FILE_FIELD);
+            body["fields_seen"] = json!(details.fields_seen);
+            body["filename"] = json!(details.filename);
+            body["declared_type"] = json!(details.declared_type);
+        }
+        if let Some(line) = rollout_line {
+            body["code"] = json!("invalid_rollout");
+            body["line"] = json!(line);
+        }
+        if let Some((code, path)) = body_error {
+            body["code"] = json!(code);
+            if let Some(path) = path {
+                body["path"] = json!(path);
+            }
+        }
+
+        (status, body)
+    }
+
+    /// Maps a `ThreadManager` start failure to a categorized error when the
+    /// cause has an obvious remedy; anything else stays a 500.
+    pub fn from_thread_start_error(err: CodexErr) -> Self {
+        let (code, server) = match &err {
+            CodexErr::EnvVar(_) | CodexErr::RefreshTokenFailed(_) => {
+                (ThreadStartErrorCode::AuthRequired, None)
+            }
+            CodexErr::UnexpectedStatus(response) if response.status.as_u16() == 401 => {
+                (ThreadStartErrorCode::AuthRequired, None)
+            }
+            CodexErr::UnexpectedStatus(response)
+                if response.status.as_u16() == 404 || response.body.contains("model_not_found") =>
+            {
+                (ThreadStartErrorCode::ModelNotFound, None)
+            }
+            CodexErr::McpStartupFailed(mcp_err) => (
+                ThreadStartErrorCode::McpStartupFailed,
+                mcp_err
+                    .failures
+                    .first()
+                    .map(|failure| failure.server.clone()),
+            ),
+            _ => return ApiError::InternalError(format!("Failed to start thread: {err}")),
+        };
+        ApiError::ThreadStartFailed {
+            code,
+            message: err.to_string(),
+            server,
+        }
+    }
+}
+
+impl From<RequirementViolation> for ApiError {
+    fn from(violation: RequirementViolation) -> Self {
+        ApiError::RequirementViolation(violation)
+    }
+}
 
-        (status, body).into_response()
+/// For handlers that take `Result<Json<T>, JsonRejection>`: a body that does
+/// not match the request type is a 400 naming the offending field, instead of
+/// axum's 422.
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        ApiError::InvalidRequest(rejection.body_text())
     }
 }
 
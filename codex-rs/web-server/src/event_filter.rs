@@ -0,0 +1,62 @@
+//! Per-stream filtering of SSE events by type.
+//!
+//! A client that subscribes with `?events=turn,item/completed` only gets the
+//! events whose type matches one of the listed prefixes. A prefix matches
+//! whole path segments: `item` matches `item/started` and
+//! `item/agentMessage/delta`, `item/completed` matches only itself, and
+//! `ite` matches nothing.
+//!
+//! Approval requests block their turn until answered, so they are sent
+//! whatever the filter says. So is `stream/reset`, without which the client
+//! would not know to refetch the events it missed.
+
+use std::str::FromStr;
+
+use crate::event_replay::StreamReset;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventFilter {
+    /// `None` lets every event through.
+    prefixes: Option<Vec<String>>,
+}
+
+impl EventFilter {
+    /// Whether an event of type `event_type` is sent to the stream.
+    pub fn allows(&self, event_type: &str) -> bool {
+        let Some(prefixes) = &self.prefixes else {
+            return true;
+        };
+        is_unfilterable(event_type)
+            || prefixes.iter().any(|prefix| {
+                event_type
+                    .strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+    }
+}
+
+/// Events a filter never drops.
+fn is_unfilterable(event_type: &str) -> bool {
+    event_type.ends_with("/requestApproval") || event_type == StreamReset::EVENT_TYPE
+}
+
+impl FromStr for EventFilter {
+    type Err = String;
+
+    /// Parses the comma-separated prefixes of the `events` query parameter.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut prefixes = Vec::new();
+        for prefix in value.split(',').map(str::trim) {
+            let prefix = prefix.trim_end_matches('/');
+            if prefix.is_empty() {
+                return Err(format!(
+                    "events must be a comma-separated list of event type prefixes, got {value:?}"
+                ));
+            }
+            prefixes.push(prefix.to_string());
+        }
+        Ok(Self {
+            prefixes: Some(prefixes),
+        })
+    }
+}
@@ -0,0 +1,115 @@
+//! Ids of the events on a thread's SSE stream.
+//!
+//! Every event is sent with `id: <epoch>:<seq>`. `seq` counts the thread's
+//! events and keeps counting across streams, so ids do not repeat while the
+//! server runs. Nothing is persisted: a restarted server counts `seq` from
+//! the start again, under epochs seeded from its start time, so its ids are
+//! told apart from those of the previous run by the epoch. `epoch` also goes
+//! up when the thread's event source restarts, e.g. after its event channel
+//! closed and the thread was resumed, so a client comparing ids can tell a
+//! restart (new epoch; events around it may be lost) from a gap within one
+//! epoch.
+
+use codex_protocol::ThreadId;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+/// Header a reconnecting SSE client sends with the last id it saw.
+pub const LAST_EVENT_ID_HEADER: &str = "last-event-id";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EventId {
+    pub epoch: u64,
+    pub seq: u64,
+}
+
+impl fmt::Display for EventId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.epoch, self.seq)
+    }
+}
+
+impl FromStr for EventId {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("event id must be `<epoch>:<seq>`, got {value:?}");
+        let (epoch, seq) = value.trim().split_once(':').ok_or_else(invalid)?;
+        Ok(Self {
+            epoch: epoch.parse().map_err(|_| invalid())?,
+            seq: seq.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ThreadSequence {
+    epoch: u64,
+    seq: u64,
+}
+
+/// Per-thread event counters, kept for the life of the server rather than
+/// of any one stream.
+#[derive(Debug)]
+pub struct EventSequencer {
+    /// Epoch of a thread's first event source in this process
+    start_epoch: u64,
+    threads: Mutex<HashMap<ThreadId, ThreadSequence>>,
+}
+
+impl Default for EventSequencer {
+    /// Epochs start at the current time in milliseconds, above those of any
+    /// earlier run of the server.
+    fn default() -> Self {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(1, |elapsed| elapsed.as_millis() as u64);
+        Self::new(now)
+    }
+}
+
+impl EventSequencer {
+    /// Counters whose threads start at `start_epoch`.
+    pub fn new(start_epoch: u64) -> Self {
+        Self {
+            start_epoch,
+            threads: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Id for the next event of `thread_id`.
+    pub fn next(&self, thread_id: ThreadId) -> EventId {
+        let mut threads = self.threads.lock().unwrap_or_else(PoisonError::into_inner);
+        let sequence = self.sequence(&mut threads, thread_id);
+        sequence.seq += 1;
+        EventId {
+            epoch: sequence.epoch,
+            seq: sequence.seq,
+        }
+    }
+
+    /// Starts a new epoch for `thread_id`. `seq` keeps counting, so ids from
+    /// before and after the restart never collide. Returns the new epoch.
+    pub fn restart(&self, thread_id: ThreadId) -> u64 {
+        let mut threads = self.threads.lock().unwrap_or_else(PoisonError::into_inner);
+        let sequence = self.sequence(&mut threads, thread_id);
+        sequence.epoch += 1;
+        sequence.epoch
+    }
+
+    fn sequence<'a>(
+        &self,
+        threads: &'a mut HashMap<ThreadId, ThreadSequence>,
+        thread_id: ThreadId,
+    ) -> &'a mut ThreadSequence {
+        threads.entry(thread_id).or_insert(ThreadSequence {
+            epoch: self.start_epoch,
+            seq: 0,
+        })
+    }
+}
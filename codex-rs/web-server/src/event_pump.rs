@@ -0,0 +1,344 @@
+//! One consumer of a thread's events, shared by its SSE streams.
+//!
+//! A thread's events can be taken only once, so a background task per thread
+//! takes them, applies each to the server state (approvals, statuses, usage,
+//! the turn queue) and publishes what clients should see on a broadcast
+//! channel, together with the thread's status, queue and progress changes.
+//! Each published event gets the next id of the thread and is kept for
+//! [replay](crate::event_replay). Every SSE stream on the thread subscribes
+//! to that channel, so concurrent streams all receive every event under the
+//! same id. The [`SessionStore`] starts the pump with the first stream or
+//! turn and stops it once the last stream has been gone for
+//! [`PUMP_GRACE_PERIOD`] and no turn runs, so notifications, telemetry and
+//! approvals do not depend on a client watching; events published while
+//! nobody listens can still be replayed.
+//!
+//! [`SessionStore`]: crate::state::SessionStore
+
+use codex_app_server_protocol::ServerNotification;
+use codex_core::CodexThread;
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::TokenUsage;
+use serde_json::Value;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::oneshot;
+
+use crate::approval_manager;
+use crate::approval_manager::ApprovalExpired;
+use crate::context_window::ContextWindowUsage;
+use crate::event_ids::EventId;
+use crate::event_stream::EventStreamProcessor;
+use crate::handlers::turns;
+use crate::state::WebServerState;
+use crate::state::stop_pump_after_grace;
+use crate::thread_progress::RolledBack;
+use crate::thread_status::ThreadStatusChange;
+
+/// How long a pump keeps running after its last stream disconnects, so a
+/// client that reconnects right away finds it still attached.
+pub const PUMP_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Events a slow stream may fall behind before it skips some.
+const EVENTS_CAPACITY: usize = 4096;
+
+/// A payload for the thread's streams, sent as an SSE event of `event_type`.
+#[derive(Debug, Clone)]
+pub struct ThreadEvent {
+    pub event_type: &'static str,
+    pub data: String,
+}
+
+impl ThreadEvent {
+    pub fn new(event_type: &'static str, data: String) -> Self {
+        Self { event_type, data }
+    }
+}
+
+/// An event as every stream of the thread sends it.
+#[derive(Debug, Clone)]
+pub struct PublishedEvent {
+    pub id: EventId,
+    pub event: ThreadEvent,
+}
+
+/// Broadcast side of a thread's pump. Dropping it stops the task after the
+/// event it is handling.
+#[derive(Debug)]
+pub struct EventPump {
+    events: broadcast::Sender<PublishedEvent>,
+    stop: Option<oneshot::Sender<()>>,
+}
+
+impl EventPump {
+    /// A pump no task feeds yet; [`EventPump::start`] also spawns the task.
+    pub fn new() -> Self {
+        let (events, _) = broadcast::channel(EVENTS_CAPACITY);
+        Self { events, stop: None }
+    }
+
+    /// Spawns the task that consumes `thread`'s events.
+    pub fn start(state: WebServerState, thread_id: ThreadId, thread: Arc<CodexThread>) -> Self {
+        let mut pump = Self::new();
+        let (stop, stopped) = oneshot::channel();
+        pump.stop = Some(stop);
+        tokio::spawn(run(state, thread_id, thread, pump.events.clone(), stopped));
+        pump
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<PublishedEvent> {
+        self.events.subscribe()
+    }
+
+    /// Whether `events` publishes into this pump.
+    pub fn feeds(&self, events: &broadcast::Sender<PublishedEvent>) -> bool {
+        self.events.same_channel(events)
+    }
+}
+
+impl Default for EventPump {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for EventPump {
+    fn drop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+    }
+}
+
+async fn run(
+    state: WebServerState,
+    thread_id: ThreadId,
+    thread: Arc<CodexThread>,
+    events: broadcast::Sender<PublishedEvent>,
+    mut stopped: oneshot::Receiver<()>,
+) {
+    let mut pump = ThreadPump {
+        event_processor: EventStreamProcessor::new(thread_id, Arc::new(state.clone())),
+        state,
+        thread_id,
+        thread,
+        events: events.clone(),
+        usage_total: None,
+    };
+    let thread = pump.thread.clone();
+    let mut status_changes = pump.state.thread_statuses.subscribe();
+    let mut queue_changes = pump.state.turn_queue.subscribe();
+    let mut progress_changes = pump.state.progress.subscribe();
+    let mut expired_approvals = pump.state.approval_expiry.subscribe();
+    loop {
+        // Checked first so a stopped pump takes no further event. Status,
+        // queue and progress changes and expired approvals are filtered to
+        // this thread.
+        let published = tokio::select! {
+            biased;
+            _ = &mut stopped => return,
+            event = thread.next_event() => {
+                let Ok(event) = event else {
+                    // Whatever serves this thread next is a new event source.
+                    pump.state.event_ids.restart(thread_id);
+                    pump.state.turn_spans.thread_ended(thread_id);
+                    break;
+                };
+                pump.handle(event).await
+            }
+            change = status_changes.recv() => change_event(thread_id, change, |change| {
+                (change.thread_id == thread_id).then(|| {
+                    ThreadEvent::new(ThreadStatusChange::EVENT_TYPE, change.payload().to_string())
+                })
+            }),
+            change = queue_changes.recv() => change_event(thread_id, change, |change| {
+                (change.thread_id() == thread_id)
+                    .then(|| ThreadEvent::new(change.event_type(), change.payload().to_string()))
+            }),
+            change = progress_changes.recv() => change_event(thread_id, change, |change| {
+                (change.thread_id == thread_id)
+                    .then(|| ThreadEvent::new(change.event_type(), change.payload().to_string()))
+            }),
+            expired = expired_approvals.recv() => change_event(thread_id, expired, |expired| {
+                (expired.thread_id == thread_id).then(|| {
+                    ThreadEvent::new(ApprovalExpired::EVENT_TYPE, expired.payload().to_string())
+                })
+            }),
+        };
+        for event in published {
+            let id = pump.state.event_ids.next(thread_id);
+            pump.state
+                .event_replay
+                .record(&pump.state.thread_memory, thread_id, id, &event);
+            // Nobody may be listening during the grace period.
+            let _ = events.send(PublishedEvent { id, event });
+        }
+    }
+    // Subscribers see the channel close once the store drops its sender too.
+    pump.state
+        .sessions
+        .write()
+        .await
+        .pump_ended(thread_id, &events);
+}
+
+/// The event for a registry change, if it concerns this thread.
+fn change_event<T>(
+    thread_id: ThreadId,
+    change: Result<T, RecvError>,
+    event: impl FnOnce(T) -> Option<ThreadEvent>,
+) -> Vec<ThreadEvent> {
+    match change {
+        Ok(change) => event(change).into_iter().collect(),
+        Err(RecvError::Lagged(skipped)) => {
+            tracing::warn!("Event pump for thread {thread_id} skipped {skipped} changes");
+            Vec::new()
+        }
+        // The registries live as long as the state this pump holds.
+        Err(RecvError::Closed) => Vec::new(),
+    }
+}
+
+struct ThreadPump {
+    state: WebServerState,
+    thread_id: ThreadId,
+    thread: Arc<CodexThread>,
+    event_processor: EventStreamProcessor,
+    events: broadcast::Sender<PublishedEvent>,
+    /// Cumulative usage of the last token count, so each response is recorded once.
+    usage_total: Option<TokenUsage>,
+}
+
+impl ThreadPump {
+    /// Applies `event` to the server state and returns what to publish.
+    async fn handle(&mut self, event: Event) -> Vec<ThreadEvent> {
+        let state = &self.state;
+        let thread_id = self.thread_id;
+        {
+            let mut sessions = state.sessions.write().await;
+            sessions.touch(thread_id);
+            if matches!(event.msg, EventMsg::TurnStarted(_)) {
+                sessions.hold_running_pump(thread_id, &self.events);
+            }
+        }
+        state.turn_spans.observe(thread_id, &event);
+        state.thread_statuses.observe(thread_id, &event);
+        state.progress.observe(thread_id, &event);
+        state.reviews.observe(thread_id, &event);
+        state.notifier.observe(thread_id, &event);
+
+        // Approval requests register a pending approval instead of going
+        // through the EventStreamProcessor.
+        if matches!(
+            event.msg,
+            EventMsg::ExecApprovalRequest(_) | EventMsg::ApplyPatchApprovalRequest(_)
+        ) {
+            return approval_manager::handle_approval_request(
+                state,
+                &self.thread,
+                thread_id,
+                &event,
+            )
+            .await;
+        }
+
+        let mut published = Vec::new();
+        for resolved in approval_manager::resolve_external_approvals(state, thread_id, &event).await
+        {
+            published.push(ThreadEvent::new(
+                approval_manager::ApprovalResolved::EVENT_TYPE,
+                resolved.payload().to_string(),
+            ));
+        }
+        for cancelled in approval_manager::cancel_orphaned_approvals(state, thread_id, &event).await
+        {
+            published.push(ThreadEvent::new(
+                approval_manager::ApprovalCancelled::EVENT_TYPE,
+                cancelled.payload().to_string(),
+            ));
+        }
+
+        if let EventMsg::ThreadRolledBack(rolled_back) = &event.msg {
+            let rolled_back = RolledBack {
+                thread_id,
+                num_turns: rolled_back.num_turns,
+            };
+            published.push(ThreadEvent::new(
+                RolledBack::EVENT_TYPE,
+                rolled_back.payload().to_string(),
+            ));
+        }
+
+        let turn_ended = matches!(
+            event.msg,
+            EventMsg::TurnComplete(_) | EventMsg::TurnAborted(_)
+        );
+        if turn_ended
+            && let Some(store) = &state.state_store
+            && let Err(e) = store.remove_turn(thread_id, &event.id).await
+        {
+            tracing::warn!("Failed to clear persisted turn: {}", e);
+        }
+
+        let mut context = None;
+        if let EventMsg::TokenCount(token_count) = &event.msg
+            && let Some(info) = &token_count.info
+        {
+            context = ContextWindowUsage::new(info, &self.thread.model_info().await);
+            if let Some(store) = &state.state_store
+                && let Some(usage) = crate::usage::usage_delta(self.usage_total.as_ref(), info)
+            {
+                let model = self.thread.config_snapshot().await.model;
+                if let Err(e) = store.record_usage(thread_id, &model, &usage).await {
+                    tracing::warn!("Failed to record token usage: {}", e);
+                }
+            }
+            self.usage_total = Some(info.total_token_usage.clone());
+        }
+
+        // Process all other events through EventStreamProcessor
+        for notification in self.event_processor.process_event(event).await {
+            let event_type = EventStreamProcessor::event_type_name(&notification);
+            let json_data = match (&notification, context) {
+                (ServerNotification::ThreadTokenUsageUpdated(_), Some(context)) => {
+                    with_context(&notification, context)
+                }
+                _ => serde_json::to_string(&notification).unwrap_or_default(),
+            };
+            published.push(ThreadEvent::new(event_type, json_data));
+        }
+
+        if turn_ended {
+            let idle = state
+                .sessions
+                .write()
+                .await
+                .release_pump(thread_id, &self.events);
+            if let Some(registrations) = idle {
+                tokio::spawn(stop_pump_after_grace(
+                    state.sessions.clone(),
+                    thread_id,
+                    registrations,
+                ));
+            }
+            // A queued input starts a turn that holds the pump again.
+            turns::start_next_queued(state, thread_id, &self.thread).await;
+        }
+        published
+    }
+}
+
+/// `notification` with the thread's context meter added to its params, so
+/// clients can update it live.
+fn with_context(notification: &ServerNotification, context: ContextWindowUsage) -> String {
+    let mut value = serde_json::to_value(notification).unwrap_or_default();
+    if let Some(params) = value.get_mut("params").and_then(Value::as_object_mut) {
+        params.insert("context".to_string(), json!(context));
+    }
+    value.to_string()
+}
@@ -0,0 +1,163 @@
+//! Recent events of each thread, replayed to a stream that reconnects.
+//!
+//! The pump records every event it publishes under its id. A client that
+//! reconnects with `Last-Event-ID` gets the events after that id from here
+//! before the live ones. Payloads are kept in the thread's [`ThreadMemory`]
+//! budget, and at most [`REPLAY_CAPACITY`] events per thread. When an event
+//! the client missed is gone, the stream starts with a [`StreamReset`]
+//! instead, telling the client to refetch the thread's items.
+
+use codex_protocol::ThreadId;
+use serde_json::Value;
+use serde_json::json;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+use crate::event_ids::EventId;
+use crate::event_pump::ThreadEvent;
+use crate::sse_envelope::server_notification;
+use crate::thread_memory::CacheKind;
+use crate::thread_memory::Retention;
+use crate::thread_memory::ThreadMemory;
+
+/// Events kept per thread for replay.
+pub const REPLAY_CAPACITY: usize = 1000;
+
+/// Sent first on a reconnect that cannot be replayed: some events after
+/// `last_event_id` were evicted, or the id is not one this server issued.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamReset {
+    pub thread_id: ThreadId,
+    pub last_event_id: EventId,
+}
+
+impl StreamReset {
+    pub const EVENT_TYPE: &str = "stream/reset";
+
+    pub fn payload(&self) -> Value {
+        server_notification(
+            Self::EVENT_TYPE,
+            json!({
+                "threadId": self.thread_id.to_string(),
+                "lastEventId": self.last_event_id.to_string(),
+                "itemsUrl": format!("/api/v2/threads/{}/items", self.thread_id),
+            }),
+        )
+    }
+}
+
+/// What a reconnecting stream sends before live events.
+#[derive(Debug)]
+pub enum Replay {
+    /// The events after the client's last one, oldest first
+    Events(Vec<(EventId, ThreadEvent)>),
+    /// Events the client missed are gone
+    Reset,
+}
+
+/// Ids and event types of each thread's recorded events, oldest first; the
+/// payloads are in [`ThreadMemory`] under the id's `seq`.
+#[derive(Debug)]
+pub struct EventReplay {
+    capacity: usize,
+    threads: Mutex<HashMap<ThreadId, VecDeque<(EventId, &'static str)>>>,
+}
+
+impl Default for EventReplay {
+    fn default() -> Self {
+        Self::new(REPLAY_CAPACITY)
+    }
+}
+
+impl EventReplay {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            threads: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Keeps `event` for replay, dropping the oldest event beyond capacity.
+    pub fn record(
+        &self,
+        memory: &ThreadMemory,
+        thread_id: ThreadId,
+        id: EventId,
+        event: &ThreadEvent,
+    ) {
+        memory.insert(
+            thread_id,
+            CacheKind::Replay,
+            id.seq,
+            event.data.as_str(),
+            retention(event.event_type),
+        );
+        let mut threads = self.threads.lock().unwrap_or_else(PoisonError::into_inner);
+        let recorded = threads.entry(thread_id).or_default();
+        recorded.push_back((id, event.event_type));
+        while recorded.len() > self.capacity
+            && let Some((evicted, _)) = recorded.pop_front()
+        {
+            memory.remove(thread_id, CacheKind::Replay, evicted.seq);
+        }
+    }
+
+    /// The recorded events after `last`. Every one of them must still be
+    /// there, since a stream with a gap would look complete to the client.
+    pub fn since(&self, memory: &ThreadMemory, thread_id: ThreadId, last: EventId) -> Replay {
+        let threads = self.threads.lock().unwrap_or_else(PoisonError::into_inner);
+        let recorded = threads.get(&thread_id);
+        let latest = recorded.and_then(VecDeque::back).map(|(id, _)| *id);
+        if let Some(latest) = latest
+            && latest.epoch != last.epoch
+        {
+            // From a previous run of the server, or from before the thread's
+            // event source restarted, when events may have been lost.
+            return Replay::Reset;
+        }
+        if last.seq > latest.map_or(0, |id| id.seq) {
+            // Not an id this server issued for the thread.
+            return Replay::Reset;
+        }
+        let mut events = Vec::new();
+        let mut expected = last.seq + 1;
+        for (id, event_type) in recorded
+            .into_iter()
+            .flatten()
+            .filter(|(id, _)| id.seq > last.seq)
+        {
+            if id.seq != expected {
+                return Replay::Reset;
+            }
+            let Some(data) = memory.get(thread_id, CacheKind::Replay, id.seq) else {
+                return Replay::Reset;
+            };
+            events.push((*id, ThreadEvent::new(event_type, data.to_string())));
+            expected += 1;
+        }
+        Replay::Events(events)
+    }
+
+    /// Drops the record of a deleted thread; its payloads go with its memory.
+    pub fn forget(&self, thread_id: ThreadId) {
+        self.threads
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&thread_id);
+    }
+}
+
+/// Turn lifecycle, thread and approval events are evicted last.
+fn retention(event_type: &str) -> Retention {
+    if event_type.starts_with("turn/")
+        || event_type.starts_with("thread/")
+        || event_type.starts_with("approval/")
+        || event_type.ends_with("/requestApproval")
+    {
+        Retention::Protected
+    } else {
+        Retention::Bulky
+    }
+}
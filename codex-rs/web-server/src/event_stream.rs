@@ -1,7 +1,10 @@
 use codex_app_server_protocol::*;
+use codex_core::review_format::render_review_output_text;
+use codex_core::review_prompts;
 use codex_protocol::ThreadId;
 use codex_protocol::protocol::Event;
 use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::TurnAbortReason;
 use std::sync::Arc;
 
 use crate::state::WebServerState;
@@ -35,6 +38,28 @@ fn convert_file_change(
     }
 }
 
+/// Error attached to an interrupted turn: a code clients can localize from,
+/// plus an English message for clients that don't.
+fn turn_aborted_error(reason: &TurnAbortReason) -> TurnError {
+    let (reason_code, message) = match reason {
+        TurnAbortReason::Interrupted => (
+            TurnReasonCode::UserInterrupt,
+            "Turn interrupted by the user",
+        ),
+        TurnAbortReason::Replaced => (
+            TurnReasonCode::ReplacedByNewTurn,
+            "Turn replaced by a new turn",
+        ),
+        TurnAbortReason::ReviewEnded => (TurnReasonCode::ReviewEnded, "Review ended"),
+    };
+    TurnError {
+        message: message.to_string(),
+        codex_error_info: None,
+        additional_details: None,
+        reason_code: Some(reason_code),
+    }
+}
+
 pub struct EventStreamProcessor {
     thread_id: ThreadId,
     _state: Arc<WebServerState>,
@@ -48,19 +73,9 @@ impl EventStreamProcessor {
         }
     }
 
-    // TODO: Approval request handling needs special integration in stream_events handler
-    //
-    // EventMsg::ExecApprovalRequest and ApplyPatchApprovalRequest cannot be handled here
-    // because they require:
-    // 1. Registering approval in state.pending_approvals with oneshot channel
-    // 2. Sending custom SSE notification to client
-    // 3. Blocking turn execution until approval received via REST POST
-    // 4. Submitting Op::ExecApproval{id, decision} or Op::PatchApproval{id, decision}
-    //
-    // This must be implemented in handlers/mod.rs::stream_events where we have
-    // access to the thread and can spawn async tasks to wait for approval responses.
-    //
-    // Reference: app-server/src/bespoke_event_handling.rs:195-260
+    // EventMsg::ExecApprovalRequest and ApplyPatchApprovalRequest are not handled here:
+    // they register a pending approval and spawn a task that submits the answer, so
+    // the thread's event pump hands them to approval_manager::handle_approval_request.
 
     pub async fn process_event(&self, event: Event) -> Vec<ServerNotification> {
         let Event { id: turn_id, msg } = event;
@@ -84,6 +99,12 @@ impl EventStreamProcessor {
                 )]
             }
 
+            // Core sends each web search as ItemStarted/ItemCompleted carrying a
+            // WebSearch item, which the arms above turn into `webSearch` items,
+            // and repeats it as these legacy events. Mapping them too would
+            // announce every search twice.
+            EventMsg::WebSearchBegin(_) | EventMsg::WebSearchEnd(_) => vec![],
+
             EventMsg::AgentMessageContentDelta(ev) => {
                 vec![ServerNotification::AgentMessageDelta(
                     AgentMessageDeltaNotification {
@@ -184,6 +205,15 @@ impl EventStreamProcessor {
                         },
                     ));
                 }
+                // The account's quota, not the thread's; clients show what is
+                // left without polling `/api/v2/auth/rate-limits`.
+                if let Some(rate_limits) = ev.rate_limits {
+                    notifications.push(ServerNotification::AccountRateLimitsUpdated(
+                        AccountRateLimitsUpdatedNotification {
+                            rate_limits: RateLimitSnapshot::from(rate_limits),
+                        },
+                    ));
+                }
 
                 notifications
             }
@@ -203,6 +233,7 @@ impl EventStreamProcessor {
                             .codex_error_info
                             .map(codex_app_server_protocol::CodexErrorInfo::from),
                         additional_details: None,
+                        reason_code: None,
                     },
                     will_retry: false,
                     thread_id: self.thread_id.to_string(),
@@ -218,6 +249,7 @@ impl EventStreamProcessor {
                             .codex_error_info
                             .map(codex_app_server_protocol::CodexErrorInfo::from),
                         additional_details: ev.additional_details,
+                        reason_code: None,
                     },
                     will_retry: true,
                     thread_id: self.thread_id.to_string(),
@@ -225,8 +257,19 @@ impl EventStreamProcessor {
                 })]
             }
 
+            // Sent as `thread/rolledBack` by the event stream, which has no
+            // app-server notification to map it to.
             EventMsg::ThreadRolledBack(_) => vec![],
 
+            EventMsg::ThreadNameUpdated(ev) => {
+                vec![ServerNotification::ThreadNameUpdated(
+                    ThreadNameUpdatedNotification {
+                        thread_id: ev.thread_id.to_string(),
+                        thread_name: ev.thread_name,
+                    },
+                )]
+            }
+
             EventMsg::TurnDiff(ev) => {
                 vec![ServerNotification::TurnDiffUpdated(
                     TurnDiffUpdatedNotification {
@@ -269,11 +312,7 @@ impl EventStreamProcessor {
                         turn: Turn {
                             id: turn_id,
                             items: vec![],
-                            error: Some(TurnError {
-                                message: format!("Turn interrupted: {:?}", ev.reason),
-                                codex_error_info: None,
-                                additional_details: None,
-                            }),
+                            error: Some(turn_aborted_error(&ev.reason)),
                             status: TurnStatus::Interrupted,
                         },
                     },
@@ -438,7 +477,7 @@ impl EventStreamProcessor {
                         (McpToolCallStatus::Completed, Some(mcp_result), None)
                     }
                     Err(err_msg) => {
-                        let mcp_error = McpToolCallError { message: err_msg };
+                        let mcp_error = McpToolCallError::new(err_msg, ev.error_details);
                         (McpToolCallStatus::Failed, None, Some(mcp_error))
                     }
                 };
@@ -673,7 +712,7 @@ impl EventStreamProcessor {
             EventMsg::EnteredReviewMode(ev) => {
                 let review = ev
                     .user_facing_hint
-                    .unwrap_or_else(|| format!("Reviewing: {:?}", ev.target));
+                    .unwrap_or_else(|| review_prompts::user_facing_hint(&ev.target));
                 let item = ThreadItem::EnteredReviewMode {
                     id: turn_id.clone(),
                     review,
@@ -694,7 +733,7 @@ impl EventStreamProcessor {
 
             EventMsg::ExitedReviewMode(ev) => {
                 let review = match ev.review_output {
-                    Some(output) => format!("Review completed: {output:?}"),
+                    Some(output) => render_review_output_text(&output),
                     None => "Review completed".to_string(),
                 };
                 let item = ThreadItem::ExitedReviewMode {
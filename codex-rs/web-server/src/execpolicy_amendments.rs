@@ -0,0 +1,128 @@
+//! Execpolicy amendments accepted through web approvals.
+//!
+//! Accepting an amendment makes core append an allow rule to
+//! `codex_home/rules/default.rules`, which every later session loads. That
+//! file says nothing about where a rule came from, so the server records each
+//! accepted amendment in `web_execpolicy_amendments.json` in codex home, with
+//! the workspace root and approval it came from. Revoking a record removes the
+//! rule once no other record still needs it.
+
+use codex_protocol::ThreadId;
+use codex_protocol::approvals::ExecPolicyAmendment;
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+use tokio::sync::Mutex;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+const AMENDMENTS_FILE: &str = "web_execpolicy_amendments.json";
+
+/// Serializes read-modify-write cycles on the amendments file.
+static AMENDMENTS_LOCK: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+/// An accepted amendment and the approval that accepted it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ExecPolicyAmendmentRecord {
+    pub id: String,
+    /// Command prefix the allow rule matches
+    pub command: Vec<String>,
+    /// Working directory of the thread that accepted the amendment
+    #[schema(value_type = String)]
+    pub workspace_root: PathBuf,
+    pub thread_id: String,
+    pub approval_id: String,
+    /// Unix timestamp (seconds)
+    pub accepted_at: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AmendmentsFile {
+    #[serde(default)]
+    amendments: Vec<ExecPolicyAmendmentRecord>,
+}
+
+fn amendments_path(codex_home: &Path) -> PathBuf {
+    codex_home.join(AMENDMENTS_FILE)
+}
+
+async fn read_amendments(path: &Path) -> std::io::Result<AmendmentsFile> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(contents) => serde_json::from_str(&contents).map_err(std::io::Error::other),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(AmendmentsFile::default()),
+        Err(err) => Err(err),
+    }
+}
+
+async fn write_amendments(path: &Path, file: &AmendmentsFile) -> std::io::Result<()> {
+    // Write to a sibling temp file and rename so readers never see a partial file.
+    let tmp_path = path.with_extension("json.tmp");
+    let contents = serde_json::to_vec_pretty(file).map_err(std::io::Error::other)?;
+    tokio::fs::write(&tmp_path, contents).await?;
+    tokio::fs::rename(&tmp_path, path).await
+}
+
+/// Accepted amendments, oldest first.
+pub async fn list_amendments(codex_home: &Path) -> std::io::Result<Vec<ExecPolicyAmendmentRecord>> {
+    Ok(read_amendments(&amendments_path(codex_home))
+        .await?
+        .amendments)
+}
+
+/// Records an amendment accepted by `approval_id` on `thread_id`.
+pub async fn record_amendment(
+    codex_home: &Path,
+    amendment: &ExecPolicyAmendment,
+    workspace_root: PathBuf,
+    thread_id: ThreadId,
+    approval_id: &str,
+) -> std::io::Result<ExecPolicyAmendmentRecord> {
+    let accepted_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default();
+    let record = ExecPolicyAmendmentRecord {
+        id: Uuid::new_v4().to_string(),
+        command: amendment.command.clone(),
+        workspace_root,
+        thread_id: thread_id.to_string(),
+        approval_id: approval_id.to_string(),
+        accepted_at,
+    };
+
+    let _guard = AMENDMENTS_LOCK.lock().await;
+    let path = amendments_path(codex_home);
+    let mut file = read_amendments(&path).await?;
+    file.amendments.push(record.clone());
+    write_amendments(&path, &file).await?;
+    Ok(record)
+}
+
+/// Removes the record `id` and, unless another record has the same command,
+/// its allow rule. Returns the removed record, or `None` if `id` is unknown.
+pub async fn revoke_amendment(
+    codex_home: &Path,
+    id: &str,
+) -> anyhow::Result<Option<ExecPolicyAmendmentRecord>> {
+    let _guard = AMENDMENTS_LOCK.lock().await;
+    let path = amendments_path(codex_home);
+    let mut file = read_amendments(&path).await?;
+    let Some(index) = file.amendments.iter().position(|record| record.id == id) else {
+        return Ok(None);
+    };
+    let record = file.amendments.remove(index);
+    if !file
+        .amendments
+        .iter()
+        .any(|other| other.command == record.command)
+    {
+        let amendment = ExecPolicyAmendment::new(record.command.clone());
+        codex_core::revoke_execpolicy_amendment(codex_home, &amendment).await?;
+    }
+    write_amendments(&path, &file).await?;
+    Ok(Some(record))
+}
@@ -0,0 +1,195 @@
+//! `GET /api/v2/events`: the events of every loaded thread on one stream.
+//!
+//! The stream registers on each thread's [pump](crate::event_pump) the way a
+//! per-thread stream does, and a task per thread forwards what the pump
+//! publishes into one channel. Payloads already name their thread, so events
+//! are sent unchanged, without ids: ids are numbered per thread and a
+//! `Last-Event-ID` could not say which thread it belongs to.
+//!
+//! Threads loaded after the stream connected are picked up as they are
+//! announced: the API announces the threads it starts, resumes and forks on
+//! [`ThreadAnnouncements`], and the thread manager announces sub-threads a
+//! collab agent spawns. Account notifications, such as a finished ChatGPT
+//! login, are sent along with them.
+
+use axum::response::sse::Event;
+use codex_protocol::ThreadId;
+use futures::stream::Stream;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::convert::Infallible;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::approval_manager;
+use crate::event_filter::EventFilter;
+use crate::event_pump::PublishedEvent;
+use crate::event_pump::ThreadEvent;
+use crate::sse_envelope::SseEnvelope;
+use crate::state::StreamRegistration;
+use crate::state::WebServerState;
+
+const ANNOUNCEMENTS_CAPACITY: usize = 64;
+/// Events forwarded but not yet sent before the forwarding tasks wait.
+const FORWARDED_CAPACITY: usize = 256;
+
+/// Threads loaded through the API, announced to open firehose streams.
+#[derive(Debug)]
+pub struct ThreadAnnouncements {
+    loaded: broadcast::Sender<ThreadId>,
+}
+
+impl Default for ThreadAnnouncements {
+    fn default() -> Self {
+        let (loaded, _) = broadcast::channel(ANNOUNCEMENTS_CAPACITY);
+        Self { loaded }
+    }
+}
+
+impl ThreadAnnouncements {
+    /// Announces a thread that was just started, resumed or forked.
+    pub fn announce(&self, thread_id: ThreadId) {
+        let _ = self.loaded.send(thread_id);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ThreadId> {
+        self.loaded.subscribe()
+    }
+}
+
+/// The events of every loaded thread, and of threads loaded later, that
+/// `filter` allows.
+pub async fn stream_all_events(
+    state: WebServerState,
+    filter: EventFilter,
+    envelope: SseEnvelope,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    // Subscribed before listing, so a thread loaded in between is announced;
+    // watching it twice is a no-op.
+    let mut announced = state.thread_announcements.subscribe();
+    let mut spawned = state.thread_manager.subscribe_thread_created();
+    let mut account_events = state.account_logins.subscribe();
+    let (forwarded, mut events) = mpsc::channel(FORWARDED_CAPACITY);
+    let mut watched = WatchedThreads {
+        state,
+        forwarded,
+        forwarders: HashMap::new(),
+    };
+    watched.watch_loaded().await;
+
+    async_stream::stream! {
+        let mut announcements_open = true;
+        let mut spawns_open = true;
+        loop {
+            let event = tokio::select! {
+                Some(event) = events.recv() => event,
+                Ok(event) = account_events.recv() => event,
+                received = announced.recv(), if announcements_open => {
+                    match received {
+                        Ok(thread_id) => watched.watch(thread_id).await,
+                        Err(RecvError::Lagged(_)) => watched.watch_loaded().await,
+                        Err(RecvError::Closed) => announcements_open = false,
+                    }
+                    continue;
+                }
+                received = spawned.recv(), if spawns_open => {
+                    match received {
+                        Ok(thread_id) => watched.watch(thread_id).await,
+                        Err(RecvError::Lagged(_)) => watched.watch_loaded().await,
+                        Err(RecvError::Closed) => spawns_open = false,
+                    }
+                    continue;
+                }
+                else => break,
+            };
+            if filter.allows(event.event_type) {
+                yield Ok(envelope.event(event.event_type, event.data));
+            }
+        }
+    }
+}
+
+/// The forwarding task of each thread the stream watches. The tasks end with
+/// their thread's pump, or once the stream is dropped and `forwarded` closes.
+struct WatchedThreads {
+    state: WebServerState,
+    forwarded: mpsc::Sender<ThreadEvent>,
+    forwarders: HashMap<ThreadId, JoinHandle<()>>,
+}
+
+impl WatchedThreads {
+    /// Watches every thread the thread manager has loaded.
+    async fn watch_loaded(&mut self) {
+        for thread_id in self.state.thread_manager.list_thread_ids().await {
+            self.watch(thread_id).await;
+        }
+    }
+
+    /// Starts forwarding `thread_id`'s events unless they already are. A
+    /// thread whose pump closed is watched again, e.g. after a resume.
+    async fn watch(&mut self, thread_id: ThreadId) {
+        if self
+            .forwarders
+            .get(&thread_id)
+            .is_some_and(|forwarder| !forwarder.is_finished())
+        {
+            return;
+        }
+        let Ok(thread) = self.state.thread_manager.get_thread(thread_id).await else {
+            return;
+        };
+        let (registration, events) = StreamRegistration::new(&self.state, thread_id, thread).await;
+        let pending_approvals =
+            approval_manager::pending_approval_requests(&self.state, thread_id).await;
+        let forwarder = tokio::spawn(forward(
+            registration,
+            events,
+            pending_approvals,
+            self.forwarded.clone(),
+        ));
+        self.forwarders.insert(thread_id, forwarder);
+    }
+}
+
+/// Sends the thread's pending approval requests, then what its pump
+/// publishes, skipping the published copies of the requests already sent.
+async fn forward(
+    registration: StreamRegistration,
+    mut events: broadcast::Receiver<PublishedEvent>,
+    pending_approvals: Vec<(String, ThreadEvent)>,
+    forwarded: mpsc::Sender<ThreadEvent>,
+) {
+    // Dropped with the task, so the pump can stop once nobody listens.
+    let _registration = registration;
+    let mut redelivered_ids = HashSet::new();
+    for (approval_id, event) in pending_approvals {
+        redelivered_ids.insert(approval_id);
+        if forwarded.send(event).await.is_err() {
+            return;
+        }
+    }
+    loop {
+        let received = tokio::select! {
+            received = events.recv() => received,
+            () = forwarded.closed() => return,
+        };
+        match received {
+            Ok(PublishedEvent { event, .. }) => {
+                if approval_manager::requested_approval_id(&event)
+                    .is_some_and(|approval_id| redelivered_ids.contains(&approval_id))
+                {
+                    continue;
+                }
+                if forwarded.send(event).await.is_err() {
+                    return;
+                }
+            }
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!("Firehose skipped {skipped} events");
+            }
+            Err(RecvError::Closed) => return,
+        }
+    }
+}
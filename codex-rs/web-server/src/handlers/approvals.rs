@@ -5,15 +5,24 @@ use serde::Deserialize;
 use serde::Serialize;
 use utoipa::ToSchema;
 
+use crate::approval_manager::ApprovalErrorCode;
+use crate::approval_manager::ApprovalInfo;
 use crate::approval_manager::ApprovalManager;
 use crate::error::ApiError;
+use crate::execpolicy_amendments::record_amendment;
+use crate::json_body::JsonBody;
 use crate::state::ApprovalDecision;
 use crate::state::WebServerState;
 
 #[derive(Debug, Deserialize, ToSchema)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct ApprovalRequest {
+    /// `approve`, `approve_for_session` or `decline`
     pub decision: ApprovalDecision,
+    /// Approve and accept the request's `proposedExecpolicyAmendment`, so the
+    /// command prefix no longer needs approval; requires `approve`
+    #[serde(default)]
+    pub accept_execpolicy_amendment: bool,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -21,6 +30,40 @@ pub struct ApprovalResponse {
     pub success: bool,
 }
 
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ListApprovalsResponse {
+    /// Oldest first
+    pub approvals: Vec<ApprovalInfo>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v2/threads/{thread_id}/approvals",
+    params(
+        ("thread_id" = String, Path, description = "Thread ID")
+    ),
+    responses(
+        (status = 200, description = "Approvals the thread is waiting on", body = ListApprovalsResponse),
+        (status = 400, description = "Invalid thread ID"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Approvals"
+)]
+pub async fn list_approvals(
+    State(state): State<WebServerState>,
+    Path(thread_id): Path<String>,
+) -> Result<Json<ListApprovalsResponse>, ApiError> {
+    let thread_id = codex_protocol::ThreadId::from_string(&thread_id)
+        .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
+    let approvals = ApprovalManager::new(state.pending_approvals.clone())
+        .list_for_thread(thread_id)
+        .await;
+    Ok(Json(ListApprovalsResponse { approvals }))
+}
+
 #[utoipa::path(
     post,
     path = "/api/v2/threads/{thread_id}/approvals/{approval_id}",
@@ -31,10 +74,13 @@ pub struct ApprovalResponse {
     ),
     responses(
         (status = 200, description = "Approval response submitted successfully", body = ApprovalResponse),
-        (status = 400, description = "Invalid request"),
+        (status = 400, description = "Invalid request, or an amendment was accepted for a request that proposed none; `code` is `no_amendment_proposed`"),
         (status = 401, description = "Unauthorized"),
-        (status = 404, description = "Approval request not found"),
-        (status = 408, description = "Approval request timed out"),
+        (status = 404, description = "The thread has no pending approval with this ID; `code` is `not_found`"),
+        (status = 408, description = "Approval request timed out and was denied; `code` is `timeout`"),
+        (status = 410, description = "Approval request is no longer awaited; `code` is `channel_closed`"),
+        (status = 415, description = "Body is not `application/json` (`unsupported_media_type`)"),
+        (status = 422, description = "Body has an unknown or mistyped field; `path` names it (`invalid_body`)"),
         (status = 500, description = "Internal server error")
     ),
     security(
@@ -45,28 +91,57 @@ pub struct ApprovalResponse {
 pub async fn respond_to_approval(
     State(state): State<WebServerState>,
     Path((thread_id, approval_id)): Path<(String, String)>,
-    Json(req): Json<ApprovalRequest>,
+    JsonBody(req): JsonBody<ApprovalRequest>,
 ) -> Result<Json<ApprovalResponse>, ApiError> {
-    // Validate thread_id
-    let _thread_id = codex_protocol::ThreadId::from_string(&thread_id)
+    let thread_id = codex_protocol::ThreadId::from_string(&thread_id)
         .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
 
     // Create approval manager
     let approval_manager = ApprovalManager::new(state.pending_approvals.clone());
+    // An approval the sweeper already denied was answered late, not unknown.
+    let failed = |code| match code {
+        ApprovalErrorCode::NotFound if state.approval_expiry.expired_recently(&approval_id) => {
+            ApiError::RequestTimeout(ApprovalErrorCode::Timeout.message().to_string())
+        }
+        ApprovalErrorCode::Timeout => ApiError::RequestTimeout(code.message().to_string()),
+        code => ApiError::ApprovalFailed(code),
+    };
 
-    // Respond to approval
-    approval_manager
-        .respond_to_approval(&approval_id, req.decision)
+    if !req.accept_execpolicy_amendment {
+        approval_manager
+            .respond_to_approval(thread_id, &approval_id, req.decision)
+            .await
+            .map_err(failed)?;
+        return Ok(Json(ApprovalResponse { success: true }));
+    }
+
+    if !matches!(req.decision, ApprovalDecision::Approve) {
+        return Err(ApiError::InvalidRequest(
+            "acceptExecpolicyAmendment requires decision approve".to_string(),
+        ));
+    }
+    let thread = state
+        .thread_manager
+        .get_thread(thread_id)
+        .await
+        .map_err(|_| ApiError::ThreadNotFound)?;
+    let workspace_root = thread.config_snapshot().await.cwd;
+    let amendment = approval_manager
+        .accept_execpolicy_amendment(thread_id, &approval_id)
         .await
-        .map_err(|e| {
-            if e.contains("not found") {
-                ApiError::InvalidRequest("Approval request not found".to_string())
-            } else if e.contains("timed out") {
-                ApiError::InvalidRequest("Approval request has timed out".to_string())
-            } else {
-                ApiError::InternalError(e)
-            }
-        })?;
+        .map_err(failed)?;
+    // Core persists the rule itself; a lost record only hides its origin.
+    if let Err(e) = record_amendment(
+        &state.codex_home,
+        &amendment,
+        workspace_root,
+        thread_id,
+        &approval_id,
+    )
+    .await
+    {
+        tracing::warn!("Failed to record execpolicy amendment: {e}");
+    }
 
     Ok(Json(ApprovalResponse { success: true }))
 }
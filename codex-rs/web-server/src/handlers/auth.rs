@@ -1,27 +1,21 @@
 use axum::Json;
 use axum::extract::State;
 use codex_app_server_protocol::*;
+use codex_core::auth::AuthCredentialsStoreMode;
 use codex_core::auth::CodexAuth;
+use codex_core::auth::login_with_api_key;
 use codex_protocol::account::PlanType;
 use serde::Deserialize;
 use serde::Serialize;
 use std::result::Result;
 use utoipa::ToSchema;
 
+use crate::account_login;
 use crate::error::ApiError;
+use crate::json_body::JsonBody;
+use crate::pairing::PairingRedeemError;
 use crate::state::WebServerState;
 
-// TODO: Full authentication implementation requires:
-// 1. Integration with codex-login crate for OAuth flow
-// 2. Login server management (spawn/shutdown)
-// 3. Account login state tracking
-// 4. Token refresh mechanism
-//
-// Reference implementations:
-// - app-server/src/codex_message_processor.rs (login/logout handlers)
-// - app-server/src/login_manager.rs (OAuth flow management)
-
-#[allow(dead_code)]
 #[derive(Debug, Deserialize, ToSchema)]
 #[serde(tag = "type")]
 pub enum LoginRequest {
@@ -34,7 +28,6 @@ pub enum LoginRequest {
     Chatgpt,
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Serialize, ToSchema)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum LoginResponse {
@@ -44,7 +37,6 @@ pub enum LoginResponse {
     Chatgpt { login_id: String, auth_url: String },
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct CancelLoginRequest {
     pub login_id: String,
@@ -63,6 +55,8 @@ pub struct GetAccountResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub account: Option<Account>,
     pub requires_openai_auth: bool,
+    /// Where credentials are stored: `keyring`, `file` or `ephemeral`
+    pub credential_store: AuthCredentialsStoreMode,
 }
 
 #[derive(Debug, Serialize)]
@@ -72,15 +66,17 @@ pub struct GetRateLimitsResponse {
 
 /// POST /api/v2/auth/login
 ///
-/// Initiates login flow for API Key or ChatGPT OAuth
+/// Stores an API key as the model-provider credential, or initiates the
+/// ChatGPT OAuth flow
 #[utoipa::path(
     post,
     path = "/api/v2/auth/login",
     request_body = LoginRequest,
     responses(
         (status = 200, description = "Login initiated successfully", body = LoginResponse),
-        (status = 400, description = "Invalid request"),
+        (status = 400, description = "Invalid request or empty API key"),
         (status = 401, description = "Unauthorized"),
+        (status = 409, description = "A ChatGPT login is already in progress"),
         (status = 500, description = "Internal server error")
     ),
     security(
@@ -89,17 +85,37 @@ pub struct GetRateLimitsResponse {
     tag = "Authentication"
 )]
 pub async fn login(
-    State(_state): State<WebServerState>,
-    Json(_req): Json<LoginRequest>,
+    State(state): State<WebServerState>,
+    JsonBody(req): JsonBody<LoginRequest>,
 ) -> Result<Json<LoginResponse>, ApiError> {
-    // TODO: Implement login flow
-    // - For API Key: Store in auth.json via AuthManager
-    // - For ChatGPT: Spawn login server, generate OAuth URL, return login_id
-    //
-    // Reference: app-server/src/codex_message_processor.rs::handle_login_account
-    Err(ApiError::InternalError(
-        "Login endpoint not yet implemented".to_string(),
-    ))
+    match req {
+        LoginRequest::ApiKey { api_key } => {
+            let api_key = api_key.trim().to_string();
+            if api_key.is_empty() {
+                return Err(ApiError::InvalidRequest(
+                    "apiKey must not be empty".to_string(),
+                ));
+            }
+            // Stored where `codex login --api-key` stores it, then reloaded so
+            // the account reflects it right away.
+            let codex_home = state.codex_home.clone();
+            let mode = state.credential_store_mode;
+            let auth_manager = state.auth_manager.clone();
+            tokio::task::spawn_blocking(move || {
+                login_with_api_key(&codex_home, &api_key, mode)?;
+                auth_manager.reload();
+                Ok::<_, std::io::Error>(())
+            })
+            .await
+            .map_err(|err| ApiError::InternalError(err.to_string()))?
+            .map_err(|err| ApiError::InternalError(format!("Failed to save API key: {err}")))?;
+            Ok(Json(LoginResponse::ApiKey {}))
+        }
+        LoginRequest::Chatgpt => {
+            let (login_id, auth_url) = account_login::start_chatgpt_login(&state).await?;
+            Ok(Json(LoginResponse::Chatgpt { login_id, auth_url }))
+        }
+    }
 }
 
 /// POST /api/v2/auth/login/cancel
@@ -122,18 +138,18 @@ pub async fn login(
     tag = "Authentication"
 )]
 pub async fn cancel_login(
-    State(_state): State<WebServerState>,
-    Json(_req): Json<CancelLoginRequest>,
+    State(state): State<WebServerState>,
+    JsonBody(req): JsonBody<CancelLoginRequest>,
 ) -> Result<Json<CancelLoginResponse>, ApiError> {
-    // TODO: Implement cancel login
-    // - Lookup login_id in active login sessions
-    // - Shutdown login server for that session
-    // - Return status (Canceled or NotFound)
-    //
-    // Reference: app-server/src/codex_message_processor.rs::handle_cancel_login_account
-    Err(ApiError::InternalError(
-        "Cancel login endpoint not yet implemented".to_string(),
-    ))
+    if !state.account_logins.cancel(&req.login_id) {
+        return Err(ApiError::NotFound(format!(
+            "Login ID not found: {}",
+            req.login_id
+        )));
+    }
+    Ok(Json(CancelLoginResponse {
+        status: "canceled".to_string(),
+    }))
 }
 
 /// POST /api/v2/auth/logout
@@ -205,6 +221,7 @@ pub async fn get_account(
     Ok(Json(GetAccountResponse {
         account,
         requires_openai_auth,
+        credential_store: state.credential_store_mode,
     }))
 }
 
@@ -236,3 +253,44 @@ pub async fn get_rate_limits(
         "Rate limits endpoint not yet implemented".to_string(),
     ))
 }
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PairRequest {
+    /// Contents of `codex_home/web-pairing-code`
+    pub code: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PairResponse {
+    /// Bearer token for all authenticated routes; only its hash is stored
+    pub token: String,
+}
+
+/// POST /api/v2/auth/pair
+///
+/// Exchanges the one-time pairing code written under `codex_home` for a
+/// long-lived bearer token. Needs no bearer token itself
+#[utoipa::path(
+    post,
+    path = "/api/v2/auth/pair",
+    request_body = PairRequest,
+    responses(
+        (status = 200, description = "Paired", body = PairResponse),
+        (status = 401, description = "Code is invalid or already used"),
+        (status = 410, description = "Code has expired"),
+        (status = 500, description = "Token could not be stored")
+    ),
+    tag = "Authentication"
+)]
+pub async fn pair(
+    State(state): State<WebServerState>,
+    JsonBody(req): JsonBody<PairRequest>,
+) -> Result<Json<PairResponse>, ApiError> {
+    match state.pairing.redeem(req.code.trim()) {
+        Ok(token) => Ok(Json(PairResponse { token })),
+        Err(PairingRedeemError::Rejected(code)) => Err(ApiError::PairingFailed(code)),
+        Err(PairingRedeemError::Store(err)) => Err(ApiError::InternalError(format!(
+            "Failed to store paired token: {err}"
+        ))),
+    }
+}
@@ -0,0 +1,61 @@
+use axum::Json;
+use axum::extract::State;
+use codex_core::windows_sandbox::windows_sandbox_level_from_config;
+use codex_protocol::config_types::WindowsSandboxLevel;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::error::ApiError;
+use crate::sse_envelope::NOTIFICATION_SCHEMA_VERSION;
+use crate::sse_envelope::SseEnvelope;
+use crate::state::WebServerState;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CapabilitiesResponse {
+    /// Directories a thread or command cwd must lie under; empty means any
+    #[schema(example = json!(["/srv/repos"]))]
+    pub trusted_roots: Vec<String>,
+    /// Values accepted by the events endpoint's `envelope` parameter
+    #[schema(example = json!(["v1", "v2"]))]
+    pub sse_envelopes: Vec<SseEnvelope>,
+    /// Notification schema version, sent as `v` in the `v2` envelope
+    #[schema(example = 1)]
+    pub notification_schema_version: u32,
+    /// Windows sandbox `POST /api/v2/commands` runs in: `disabled`,
+    /// `restricted-token` or `elevated`; null on other platforms
+    #[schema(value_type = Option<String>, example = json!("restricted-token"))]
+    pub windows_sandbox_level: Option<WindowsSandboxLevel>,
+}
+
+/// GET /api/v2/capabilities
+///
+/// Describes what this server allows, so clients can adapt their UI
+#[utoipa::path(
+    get,
+    path = "/api/v2/capabilities",
+    responses(
+        (status = 200, description = "Server capabilities", body = CapabilitiesResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Config failed to load")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Capabilities"
+)]
+pub async fn get_capabilities(
+    State(state): State<WebServerState>,
+) -> Result<Json<CapabilitiesResponse>, ApiError> {
+    let windows_sandbox_level = if cfg!(target_os = "windows") {
+        let config = state.config_loader.load().await?;
+        Some(windows_sandbox_level_from_config(&config))
+    } else {
+        None
+    };
+    Ok(Json(CapabilitiesResponse {
+        trusted_roots: state.trusted_roots.roots(),
+        sse_envelopes: SseEnvelope::SUPPORTED.to_vec(),
+        notification_schema_version: NOTIFICATION_SCHEMA_VERSION,
+        windows_sandbox_level,
+    }))
+}
@@ -11,16 +11,20 @@ use codex_core::exec_env::create_env;
 use codex_core::features::Feature;
 use codex_core::get_platform_sandbox;
 use codex_core::sandboxing::SandboxPermissions;
+use codex_core::windows_sandbox::sandbox_setup_is_complete;
+use codex_core::windows_sandbox::windows_sandbox_level_from_config;
 use codex_protocol::config_types::WindowsSandboxLevel;
 use codex_protocol::protocol::SandboxPolicy;
 use serde::Deserialize;
 use serde::Serialize;
 use std::collections::HashMap;
+use std::path::Path;
 use std::path::PathBuf;
 use std::result::Result;
 use utoipa::ToSchema;
 
 use crate::error::ApiError;
+use crate::json_body::JsonBody;
 use crate::state::WebServerState;
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -36,6 +40,39 @@ pub struct ExecuteCommandResponse {
     pub exit_code: i32,
 }
 
+/// Prefix core gives errors from spawning a command in the Windows sandbox.
+const WINDOWS_SANDBOX_ERROR_PREFIX: &str = "windows sandbox";
+
+const ENABLE_WINDOWS_SANDBOX: &str =
+    "set `[windows] sandbox = \"unelevated\"` (or `\"elevated\"`) in config.toml";
+
+/// Picks the sandbox one-off commands run in and returns the Windows sandbox
+/// level to pass to core, `Disabled` when BoxLite runs them.
+pub fn command_sandbox_level(
+    config: &Config,
+    codex_home: &Path,
+) -> Result<WindowsSandboxLevel, ApiError> {
+    let level = windows_sandbox_level_from_config(config);
+    match get_platform_sandbox(level != WindowsSandboxLevel::Disabled) {
+        Some(SandboxType::BoxLite) => Ok(WindowsSandboxLevel::Disabled),
+        Some(SandboxType::WindowsRestrictedToken) => {
+            if level == WindowsSandboxLevel::Elevated && !sandbox_setup_is_complete(codex_home) {
+                return Err(ApiError::InternalError(format!(
+                    "The elevated Windows sandbox is not set up yet; run its setup from the Codex CLI on this machine, or {ENABLE_WINDOWS_SANDBOX} to use the unelevated sandbox"
+                )));
+            }
+            Ok(level)
+        }
+        _ if cfg!(target_os = "windows") => Err(ApiError::InternalError(format!(
+            "The Windows sandbox is required for /api/v2/commands; {ENABLE_WINDOWS_SANDBOX}"
+        ))),
+        _ => Err(ApiError::InternalError(
+            "BoxLite sandbox is required for /api/v2/commands; configure BOXLITE_RUNTIME_DIR so BoxLite can locate boxlite-guest/mke2fs/debugfs"
+                .to_string(),
+        )),
+    }
+}
+
 /// POST /api/v2/commands
 ///
 /// Executes a one-off command outside of thread context (with 10s timeout)
@@ -47,6 +84,7 @@ pub struct ExecuteCommandResponse {
         (status = 200, description = "Command executed successfully", body = ExecuteCommandResponse),
         (status = 400, description = "Invalid request"),
         (status = 401, description = "Unauthorized"),
+        (status = 403, description = "cwd is outside the trusted roots"),
         (status = 504, description = "Command timeout (exceeded 10s)"),
         (status = 500, description = "Internal server error")
     ),
@@ -57,7 +95,7 @@ pub struct ExecuteCommandResponse {
 )]
 pub async fn execute_command(
     State(state): State<WebServerState>,
-    Json(req): Json<ExecuteCommandRequest>,
+    JsonBody(req): JsonBody<ExecuteCommandRequest>,
 ) -> Result<Json<ExecuteCommandResponse>, ApiError> {
     // Validate command
     if req.command.is_empty() {
@@ -68,7 +106,8 @@ pub async fn execute_command(
 
     // Validate and canonicalize CWD (prevent path traversal)
     let cwd = if let Some(cwd_str) = req.cwd {
-        let cwd_path = PathBuf::from(&cwd_str);
+        // Checked first so an untrusted path is rejected whether or not it exists.
+        let cwd_path = state.trusted_roots.check(&PathBuf::from(&cwd_str))?;
 
         // Ensure the path is within codex_home or a safe directory
         let canonical_cwd = cwd_path
@@ -85,12 +124,10 @@ pub async fn execute_command(
         canonical_cwd
     } else {
         // Use codex_home as default
-        state.codex_home.clone()
+        state.trusted_roots.check(&state.codex_home)?
     };
 
-    let config = Config::load_with_cli_overrides(vec![])
-        .await
-        .map_err(|e| ApiError::InternalError(format!("Failed to load config: {e}")))?;
+    let config = state.config_loader.load().await?;
 
     let sandbox_policy = config.permissions.sandbox_policy.get();
     if matches!(
@@ -102,12 +139,7 @@ pub async fn execute_command(
                 .to_string(),
         ));
     }
-    if get_platform_sandbox(false) != Some(SandboxType::BoxLite) {
-        return Err(ApiError::InternalError(
-            "BoxLite sandbox is required for /api/v2/commands; configure BOXLITE_RUNTIME_DIR so BoxLite can locate boxlite-guest/mke2fs/debugfs"
-                .to_string(),
-        ));
-    }
+    let windows_sandbox_level = command_sandbox_level(&config, &state.codex_home)?;
 
     let env: HashMap<String, String> =
         create_env(&config.permissions.shell_environment_policy, None);
@@ -119,7 +151,7 @@ pub async fn execute_command(
         env,
         network: None,
         sandbox_permissions: SandboxPermissions::UseDefault,
-        windows_sandbox_level: WindowsSandboxLevel::Disabled,
+        windows_sandbox_level,
         justification: None,
         arg0: None,
     };
@@ -141,6 +173,11 @@ pub async fn execute_command(
         CodexErr::InvalidRequest(message) | CodexErr::UnsupportedOperation(message) => {
             ApiError::InvalidRequest(message)
         }
+        CodexErr::Io(err) if err.to_string().starts_with(WINDOWS_SANDBOX_ERROR_PREFIX) => {
+            ApiError::InternalError(format!(
+                "{err}; check that the Windows sandbox setup completed for this user, or {ENABLE_WINDOWS_SANDBOX} to use the unelevated sandbox"
+            ))
+        }
         other => ApiError::InternalError(other.to_string()),
     })?;
 
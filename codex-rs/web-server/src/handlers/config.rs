@@ -1,21 +1,43 @@
 use axum::Json;
+use axum::extract::Query;
 use axum::extract::State;
 use codex_app_server_protocol::*;
 use codex_core::config::service::ConfigServiceError;
 use codex_core::config_loader::ConfigRequirementsToml;
+use codex_utils_absolute_path::AbsolutePathBuf;
 use serde::Deserialize;
 use serde::Serialize;
+use std::path::PathBuf;
 use std::result::Result;
 use utoipa::ToSchema;
 
 use crate::error::ApiError;
+use crate::json_body::JsonBody;
+use crate::requirements::check_config_edit;
 use crate::state::WebServerState;
 
+/// Config layer a write goes to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigScope {
+    /// `config.toml` in codex_home
+    #[default]
+    Global,
+    /// `.codex/config.toml` of the project of `cwd`
+    Project,
+}
+
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct WriteConfigValueRequest {
     pub key_path: String,
     pub value: serde_json::Value,
     pub merge_strategy: MergeStrategy,
+    #[serde(default)]
+    pub scope: ConfigScope,
+    /// Working directory whose project layer `project` scope writes to
+    pub cwd: Option<String>,
+    /// Config file of a layer in `scope`: codex_home's `config.toml`, or a
+    /// project `.codex/config.toml` between the project root and `cwd`
     pub file_path: Option<String>,
     pub expected_version: Option<String>,
 }
@@ -23,10 +45,23 @@ pub struct WriteConfigValueRequest {
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct BatchWriteConfigRequest {
     pub edits: Vec<ConfigEdit>,
+    #[serde(default)]
+    pub scope: ConfigScope,
+    /// Working directory whose project layer `project` scope writes to
+    pub cwd: Option<String>,
+    /// As in [`WriteConfigValueRequest::file_path`]
     pub file_path: Option<String>,
     pub expected_version: Option<String>,
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct ReadConfigParams {
+    #[serde(default)]
+    pub include_layers: bool,
+    /// Also load the project layers of this working directory
+    pub cwd: Option<String>,
+}
+
 #[derive(Debug, Serialize, ToSchema)]
 pub struct WriteConfigResponse {
     pub new_version: String,
@@ -34,7 +69,56 @@ pub struct WriteConfigResponse {
 
 impl From<ConfigServiceError> for ApiError {
     fn from(err: ConfigServiceError) -> Self {
-        ApiError::InternalError(format!("Config service error: {err}"))
+        match err.write_error_code() {
+            Some(ConfigWriteErrorCode::ConfigVersionConflict) => ApiError::ConfigVersionConflict,
+            Some(
+                ConfigWriteErrorCode::ConfigLayerReadonly
+                | ConfigWriteErrorCode::ConfigValidationError
+                | ConfigWriteErrorCode::ConfigPathNotFound
+                | ConfigWriteErrorCode::ConfigSchemaUnknownKey,
+            ) => ApiError::InvalidRequest(err.to_string()),
+            Some(ConfigWriteErrorCode::UserLayerNotFound) | None => {
+                ApiError::InternalError(format!("Config service error: {err}"))
+            }
+        }
+    }
+}
+
+/// The working directory of a project scoped request, within the trusted
+/// roots.
+async fn project_cwd(state: &WebServerState, cwd: &str) -> Result<AbsolutePathBuf, ApiError> {
+    let cwd = PathBuf::from(cwd);
+    if !cwd.is_absolute() {
+        return Err(ApiError::InvalidRequest(
+            "cwd must be an absolute path".to_string(),
+        ));
+    }
+    let cwd = state.trusted_roots.check(&cwd)?;
+    if !tokio::fs::metadata(&cwd)
+        .await
+        .is_ok_and(|metadata| metadata.is_dir())
+    {
+        return Err(ApiError::InvalidRequest(format!(
+            "cwd {} is not a directory",
+            cwd.display()
+        )));
+    }
+    AbsolutePathBuf::from_absolute_path(&cwd)
+        .map_err(|err| ApiError::InvalidRequest(format!("Invalid cwd: {err}")))
+}
+
+/// The cwd a write in `scope` targets; `None` for the global config.
+async fn write_cwd(
+    state: &WebServerState,
+    scope: ConfigScope,
+    cwd: Option<&str>,
+) -> Result<Option<AbsolutePathBuf>, ApiError> {
+    match (scope, cwd) {
+        (ConfigScope::Global, _) => Ok(None),
+        (ConfigScope::Project, Some(cwd)) => Ok(Some(project_cwd(state, cwd).await?)),
+        (ConfigScope::Project, None) => Err(ApiError::InvalidRequest(
+            "cwd is required for project scope".to_string(),
+        )),
     }
 }
 
@@ -45,11 +129,14 @@ impl From<ConfigServiceError> for ApiError {
     get,
     path = "/api/v2/config",
     params(
-        ("include_layers" = bool, Query, description = "Include configuration layers in response")
+        ("include_layers" = Option<bool>, Query, description = "Include configuration layers in response (default: false)"),
+        ("cwd" = Option<String>, Query, description = "Working directory whose project layers to include")
     ),
     responses(
         (status = 200, description = "Configuration retrieved successfully"),
+        (status = 400, description = "cwd is not an absolute directory"),
         (status = 401, description = "Unauthorized"),
+        (status = 403, description = "cwd is outside the trusted roots"),
         (status = 500, description = "Internal server error")
     ),
     security(
@@ -59,12 +146,15 @@ impl From<ConfigServiceError> for ApiError {
 )]
 pub async fn read_config(
     State(state): State<WebServerState>,
+    Query(query): Query<ReadConfigParams>,
 ) -> Result<Json<ConfigReadResponse>, ApiError> {
-    // Note: include_layers parameter not currently supported
-    // TODO: Enable axum "query" feature and use Query extractor
+    let cwd = match query.cwd.as_deref() {
+        Some(cwd) => Some(project_cwd(&state, cwd).await?.display().to_string()),
+        None => None,
+    };
     let params = ConfigReadParams {
-        include_layers: false,
-        cwd: None,
+        include_layers: query.include_layers,
+        cwd,
     };
 
     let response = state.config_service.read(params).await?;
@@ -73,15 +163,17 @@ pub async fn read_config(
 
 /// PUT /api/v2/config
 ///
-/// Writes a single configuration value
+/// Writes a single configuration value into the global config, or with
+/// `scope: "project"` into the project config of `cwd`
 #[utoipa::path(
     put,
     path = "/api/v2/config",
     request_body = WriteConfigValueRequest,
     responses(
         (status = 200, description = "Configuration value written successfully", body = WriteConfigResponse),
-        (status = 400, description = "Invalid request"),
+        (status = 400, description = "Invalid request, missing cwd, or file_path outside the scope's layers"),
         (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Value violates config requirements, or cwd is outside the trusted roots"),
         (status = 409, description = "Version conflict"),
         (status = 500, description = "Internal server error")
     ),
@@ -92,8 +184,14 @@ pub async fn read_config(
 )]
 pub async fn write_config_value(
     State(state): State<WebServerState>,
-    Json(req): Json<WriteConfigValueRequest>,
+    JsonBody(req): JsonBody<WriteConfigValueRequest>,
 ) -> Result<Json<ConfigWriteResponse>, ApiError> {
+    if let Some(requirements) = state.config_service.read_requirements().await? {
+        check_config_edit(&requirements, &req.key_path, &req.value)?;
+    }
+
+    let cwd = write_cwd(&state, req.scope, req.cwd.as_deref()).await?;
+
     let params = ConfigValueWriteParams {
         key_path: req.key_path,
         value: req.value,
@@ -102,21 +200,35 @@ pub async fn write_config_value(
         expected_version: req.expected_version,
     };
 
-    let response = state.config_service.write_value(params).await?;
+    let response = match cwd {
+        Some(cwd) => {
+            state
+                .config_service
+                .write_project_value(&cwd, params)
+                .await?
+        }
+        None => {
+            let response = state.config_service.write_value(params).await?;
+            state.config_loader.record_write(&response.version);
+            response
+        }
+    };
     Ok(Json(response))
 }
 
 /// PATCH /api/v2/config
 ///
-/// Writes multiple configuration values in a batch
+/// Writes multiple configuration values in a batch, into the layer chosen
+/// as for a single value
 #[utoipa::path(
     patch,
     path = "/api/v2/config",
     request_body = BatchWriteConfigRequest,
     responses(
         (status = 200, description = "Configuration batch written successfully", body = WriteConfigResponse),
-        (status = 400, description = "Invalid request"),
+        (status = 400, description = "Invalid request, missing cwd, or file_path outside the scope's layers"),
         (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Value violates config requirements, or cwd is outside the trusted roots"),
         (status = 409, description = "Version conflict"),
         (status = 500, description = "Internal server error")
     ),
@@ -127,15 +239,35 @@ pub async fn write_config_value(
 )]
 pub async fn batch_write_config(
     State(state): State<WebServerState>,
-    Json(req): Json<BatchWriteConfigRequest>,
+    JsonBody(req): JsonBody<BatchWriteConfigRequest>,
 ) -> Result<Json<ConfigWriteResponse>, ApiError> {
+    if let Some(requirements) = state.config_service.read_requirements().await? {
+        for edit in &req.edits {
+            check_config_edit(&requirements, &edit.key_path, &edit.value)?;
+        }
+    }
+
+    let cwd = write_cwd(&state, req.scope, req.cwd.as_deref()).await?;
+
     let params = ConfigBatchWriteParams {
         edits: req.edits,
         file_path: req.file_path,
         expected_version: req.expected_version,
     };
 
-    let response = state.config_service.batch_write(params).await?;
+    let response = match cwd {
+        Some(cwd) => {
+            state
+                .config_service
+                .batch_write_project(&cwd, params)
+                .await?
+        }
+        None => {
+            let response = state.config_service.batch_write(params).await?;
+            state.config_loader.record_write(&response.version);
+            response
+        }
+    };
     Ok(Json(response))
 }
 
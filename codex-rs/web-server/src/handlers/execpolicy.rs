@@ -0,0 +1,98 @@
+use axum::Json;
+use axum::extract::Path;
+use axum::extract::Query;
+use axum::extract::State;
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::PathBuf;
+use utoipa::ToSchema;
+
+use crate::error::ApiError;
+use crate::execpolicy_amendments::ExecPolicyAmendmentRecord;
+use crate::execpolicy_amendments::list_amendments;
+use crate::execpolicy_amendments::revoke_amendment;
+use crate::state::WebServerState;
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct ListAmendmentsParams {
+    /// Only amendments accepted in this workspace root
+    #[serde(default)]
+    pub workspace_root: Option<PathBuf>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ListAmendmentsResponse {
+    /// Oldest first
+    pub amendments: Vec<ExecPolicyAmendmentRecord>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RevokeAmendmentResponse {
+    pub revoked: ExecPolicyAmendmentRecord,
+}
+
+/// GET /api/v2/execpolicy
+///
+/// Lists the execpolicy amendments accepted through approvals and still in
+/// effect
+#[utoipa::path(
+    get,
+    path = "/api/v2/execpolicy",
+    params(
+        ("workspace_root" = Option<String>, Query, description = "Only amendments accepted in this workspace root")
+    ),
+    responses(
+        (status = 200, description = "Active amendments", body = ListAmendmentsResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Approvals"
+)]
+pub async fn list_execpolicy_amendments(
+    State(state): State<WebServerState>,
+    Query(params): Query<ListAmendmentsParams>,
+) -> Result<Json<ListAmendmentsResponse>, ApiError> {
+    let mut amendments = list_amendments(&state.codex_home)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to read amendments: {e}")))?;
+    if let Some(root) = params.workspace_root {
+        amendments.retain(|record| record.workspace_root == root);
+    }
+    Ok(Json(ListAmendmentsResponse { amendments }))
+}
+
+/// DELETE /api/v2/execpolicy/{amendment_id}
+///
+/// Revokes an accepted amendment. Its allow rule is removed unless another
+/// amendment has the same command; threads that already loaded the rule keep
+/// it until they are resumed
+#[utoipa::path(
+    delete,
+    path = "/api/v2/execpolicy/{amendment_id}",
+    params(
+        ("amendment_id" = String, Path, description = "Amendment ID")
+    ),
+    responses(
+        (status = 200, description = "Amendment revoked", body = RevokeAmendmentResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Amendment not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Approvals"
+)]
+pub async fn revoke_execpolicy_amendment(
+    State(state): State<WebServerState>,
+    Path(amendment_id): Path<String>,
+) -> Result<Json<RevokeAmendmentResponse>, ApiError> {
+    let revoked = revoke_amendment(&state.codex_home, &amendment_id)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to revoke amendment: {e}")))?
+        .ok_or_else(|| ApiError::NotFound("Amendment not found".to_string()))?;
+    Ok(Json(RevokeAmendmentResponse { revoked }))
+}
@@ -8,6 +8,7 @@ use std::result::Result;
 use utoipa::ToSchema;
 
 use crate::error::ApiError;
+use crate::json_body::JsonBody;
 use crate::state::WebServerState;
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -45,7 +46,7 @@ pub struct UploadFeedbackResponse {
 )]
 pub async fn upload_feedback(
     State(state): State<WebServerState>,
-    Json(req): Json<UploadFeedbackRequest>,
+    JsonBody(req): JsonBody<UploadFeedbackRequest>,
 ) -> Result<(StatusCode, Json<UploadFeedbackResponse>), ApiError> {
     // Validate classification
     if req.classification.is_empty() {
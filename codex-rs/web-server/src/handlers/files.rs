@@ -0,0 +1,186 @@
+use axum::Json;
+use axum::extract::Path;
+use axum::extract::Query;
+use axum::extract::State;
+use codex_core::workspace_ignore::WorkspaceIgnore;
+use codex_protocol::ThreadId;
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::PathBuf;
+use utoipa::ToSchema;
+
+use crate::error::ApiError;
+use crate::state::WebServerState;
+
+const MAX_FILE_ENTRIES: usize = 1000;
+
+fn default_ignore() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ListFilesParams {
+    /// Directory relative to the thread's cwd (default: the cwd itself)
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Apply the workspace ignore rules (default: true)
+    #[serde(default = "default_ignore")]
+    pub ignore: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FileEntryKind {
+    File,
+    Directory,
+    Symlink,
+    Other,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FileEntry {
+    pub name: String,
+    /// Relative to the thread's cwd
+    pub path: String,
+    pub kind: FileEntryKind,
+    /// Bytes; files only
+    pub size: Option<u64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ListFilesResponse {
+    /// Listed directory, relative to the thread's cwd
+    pub path: String,
+    /// Sorted by name
+    pub entries: Vec<FileEntry>,
+    /// More than 1000 entries; the rest were left out
+    pub truncated: bool,
+}
+
+/// GET /api/v2/threads/:id/files
+///
+/// Lists one directory of the thread's workspace. Entries matched by the
+/// workspace ignore rules (`.gitignore`, `.codexignore`, `ignore_globs`, and
+/// `node_modules`, `target`, `.git`) are left out unless `ignore=false`
+#[utoipa::path(
+    get,
+    path = "/api/v2/threads/{id}/files",
+    params(
+        ("id" = String, Path, description = "Thread ID"),
+        ("path" = Option<String>, Query, description = "Directory relative to the thread's cwd"),
+        ("ignore" = Option<bool>, Query, description = "Apply the workspace ignore rules (default: true)")
+    ),
+    responses(
+        (status = 200, description = "Directory entries", body = ListFilesResponse),
+        (status = 400, description = "Path is outside the workspace or not a directory"),
+        (status = 404, description = "Thread or directory not found"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Threads"
+)]
+pub async fn list_files(
+    State(state): State<WebServerState>,
+    Path(thread_id): Path<String>,
+    Query(params): Query<ListFilesParams>,
+) -> Result<Json<ListFilesResponse>, ApiError> {
+    let thread_id = ThreadId::from_string(&thread_id)
+        .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
+    let thread = state
+        .thread_manager
+        .get_thread(thread_id)
+        .await
+        .map_err(|_| ApiError::ThreadNotFound)?;
+    let cwd = thread.config_snapshot().await.cwd;
+    let root = tokio::fs::canonicalize(&cwd)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to resolve workspace: {e}")))?;
+
+    let relative = params.path.unwrap_or_default();
+    let dir = match tokio::fs::canonicalize(root.join(&relative)).await {
+        Ok(dir) => dir,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            return Err(ApiError::NotFound("Directory not found".to_string()));
+        }
+        Err(err) => {
+            return Err(ApiError::InternalError(format!(
+                "Failed to resolve path: {err}"
+            )));
+        }
+    };
+    // Canonical paths also catch `..` and symlinks that leave the workspace.
+    if !dir.starts_with(&root) {
+        return Err(ApiError::InvalidRequest(
+            "path is outside the workspace".to_string(),
+        ));
+    }
+    if !dir.is_dir() {
+        return Err(ApiError::InvalidRequest(
+            "path is not a directory".to_string(),
+        ));
+    }
+
+    let ignore = if params.ignore {
+        let config = state.config_loader.load().await?;
+        WorkspaceIgnore::new(&root, &config.ignore_globs)
+    } else {
+        WorkspaceIgnore::default()
+    };
+
+    let mut read_dir = tokio::fs::read_dir(&dir)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to read directory: {e}")))?;
+    let mut entries = Vec::new();
+    while let Some(entry) = read_dir
+        .next_entry()
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to read directory: {e}")))?
+    {
+        let Ok(file_type) = entry.file_type().await else {
+            continue;
+        };
+        let kind = if file_type.is_symlink() {
+            FileEntryKind::Symlink
+        } else if file_type.is_dir() {
+            FileEntryKind::Directory
+        } else if file_type.is_file() {
+            FileEntryKind::File
+        } else {
+            FileEntryKind::Other
+        };
+        let path = entry.path();
+        if ignore.is_ignored(&path, kind == FileEntryKind::Directory) {
+            continue;
+        }
+        let size = match kind {
+            FileEntryKind::File => entry.metadata().await.ok().map(|metadata| metadata.len()),
+            _ => None,
+        };
+        entries.push(FileEntry {
+            name: entry.file_name().to_string_lossy().into_owned(),
+            path: relative_display(&root, &path),
+            kind,
+            size,
+        });
+    }
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    let truncated = entries.len() > MAX_FILE_ENTRIES;
+    entries.truncate(MAX_FILE_ENTRIES);
+
+    Ok(Json(ListFilesResponse {
+        path: relative_display(&root, &dir),
+        entries,
+        truncated,
+    }))
+}
+
+fn relative_display(root: &std::path::Path, path: &std::path::Path) -> String {
+    path.strip_prefix(root)
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .to_string_lossy()
+        .replace('\\', "/")
+}
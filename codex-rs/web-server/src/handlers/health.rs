@@ -0,0 +1,35 @@
+use axum::Json;
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::response::Response;
+use serde::Serialize;
+
+use crate::middleware::is_authorized;
+use crate::startup_checks::run_readiness;
+use crate::state::WebServerState;
+
+/// What `GET /health/ready` tells callers without a token.
+#[derive(Debug, Serialize)]
+pub struct ReadinessStatus {
+    pub ok: bool,
+}
+
+/// GET /health/ready
+///
+/// Runs the startup checks; 503 when any of them fails. Only authenticated
+/// callers get the checks themselves, since they name paths and servers
+pub async fn ready(State(state): State<WebServerState>, headers: HeaderMap) -> Response {
+    let report = run_readiness(&state).await;
+    let status = if report.ok {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    if is_authorized(&state, &headers) {
+        (status, Json(report)).into_response()
+    } else {
+        (status, Json(ReadinessStatus { ok: report.ok })).into_response()
+    }
+}
@@ -0,0 +1,371 @@
+use axum::Json;
+use axum::extract::Query;
+use axum::extract::State;
+use codex_protocol::ThreadId;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::Duration;
+use std::time::SystemTime;
+use utoipa::ToSchema;
+
+use crate::attachment_gc;
+use crate::error::ApiError;
+use crate::json_body::JsonBody;
+use crate::permissions;
+use crate::permissions::PermissionIssue;
+use crate::state::WebServerState;
+use crate::storage;
+use crate::storage::StorageCategory;
+use crate::storage::StoredFile;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FixPermissionsResponse {
+    /// Paths restricted to the owner by this request
+    pub fixed: Vec<PermissionIssue>,
+    /// Paths still open to other users, e.g. owned by another account
+    pub remaining: Vec<PermissionIssue>,
+}
+
+/// POST /api/v2/maintenance/fix-permissions
+///
+/// Restricts `codex_home`, its sessions and attachments directories and the
+/// stored credentials file to the owner (`0700` directories, `0600` files)
+#[utoipa::path(
+    post,
+    path = "/api/v2/maintenance/fix-permissions",
+    responses(
+        (status = 200, description = "Permissions tightened", body = FixPermissionsResponse),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Maintenance"
+)]
+pub async fn fix_permissions(State(state): State<WebServerState>) -> Json<FixPermissionsResponse> {
+    let fixed = permissions::repair(&state.codex_home, &state.attachments_dir);
+    let remaining = permissions::audit(&state.codex_home, &state.attachments_dir);
+    Json(FixPermissionsResponse { fixed, remaining })
+}
+
+/// Default and maximum number of threads in `largest_threads`.
+const DEFAULT_TOP_THREADS: usize = 10;
+const MAX_TOP_THREADS: usize = 100;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct StorageParams {
+    /// Number of threads to list in `largest_threads` (default 10, max 100)
+    #[serde(default)]
+    pub top: Option<usize>,
+}
+
+#[derive(Debug, Default, Serialize, ToSchema)]
+pub struct StorageCategories {
+    /// Rollout files plus rollout items stored in Postgres
+    pub rollouts: u64,
+    pub attachments: u64,
+    /// Shell snapshots and memories
+    pub artifacts: u64,
+    pub logs: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ThreadStorage {
+    pub thread_id: String,
+    pub bytes: u64,
+    /// Whether the thread is archived
+    pub archived: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StorageReport {
+    pub total_bytes: u64,
+    pub categories: StorageCategories,
+    /// Threads with the largest rollouts, largest first
+    pub largest_threads: Vec<ThreadStorage>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct VacuumRequest {
+    /// Only remove data last written more than this many days ago
+    #[serde(default)]
+    pub older_than_days: Option<u64>,
+    /// Only remove archived threads; attachments are kept
+    #[serde(default)]
+    pub archived_only: bool,
+    /// Report what would be removed without removing it (default true)
+    #[serde(default = "default_dry_run")]
+    pub dry_run: bool,
+}
+
+fn default_dry_run() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VacuumedItem {
+    pub category: StorageCategory,
+    /// File removed, or `postgres` for rollout items stored in Postgres
+    pub path: String,
+    pub bytes: u64,
+    pub thread_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct VacuumResponse {
+    pub dry_run: bool,
+    /// Bytes freed, or that would be freed by a dry run
+    pub reclaimed_bytes: u64,
+    pub deleted: Vec<VacuumedItem>,
+}
+
+/// Per-thread rollout usage from files and, when configured, Postgres.
+#[derive(Default)]
+struct ThreadUsage {
+    bytes: u64,
+    last_write: Option<SystemTime>,
+    archived: bool,
+    files: Vec<StoredFile>,
+    postgres_bytes: u64,
+}
+
+async fn thread_usage(
+    state: &WebServerState,
+    files: &[StoredFile],
+) -> Result<HashMap<ThreadId, ThreadUsage>, ApiError> {
+    let mut usage: HashMap<ThreadId, ThreadUsage> = HashMap::new();
+    for file in files {
+        let Some(thread_id) = file.thread_id else {
+            continue;
+        };
+        let thread = usage.entry(thread_id).or_default();
+        thread.bytes += file.bytes;
+        thread.last_write = thread.last_write.max(file.modified);
+        thread.archived |= file.archived;
+        thread.files.push(file.clone());
+    }
+    if let Some(store) = &state.state_store {
+        let stored = store
+            .rollout_usage()
+            .await
+            .map_err(|err| ApiError::InternalError(err.to_string()))?;
+        for (thread_id, (bytes, updated_at)) in stored {
+            let thread = usage.entry(thread_id).or_default();
+            thread.bytes += bytes;
+            thread.postgres_bytes = bytes;
+            let updated_at = u64::try_from(updated_at)
+                .ok()
+                .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+            thread.last_write = thread.last_write.max(updated_at);
+        }
+    }
+    for thread_id in state.auto_archived.lock().await.keys() {
+        if let Some(thread) = usage.get_mut(thread_id) {
+            thread.archived = true;
+        }
+    }
+    Ok(usage)
+}
+
+/// GET /api/v2/maintenance/storage
+///
+/// Reports disk usage under `codex_home` by category and the threads with
+/// the largest rollouts
+#[utoipa::path(
+    get,
+    path = "/api/v2/maintenance/storage",
+    params(
+        ("top" = Option<usize>, Query, description = "Number of threads to list (default: 10, max: 100)")
+    ),
+    responses(
+        (status = 200, description = "Storage usage", body = StorageReport),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Maintenance"
+)]
+pub async fn get_storage(
+    State(state): State<WebServerState>,
+    Query(params): Query<StorageParams>,
+) -> Result<Json<StorageReport>, ApiError> {
+    let files = storage::scan(&state.codex_home, &state.attachments_dir);
+    let usage = thread_usage(&state, &files).await?;
+
+    let mut categories = StorageCategories::default();
+    for file in &files {
+        match file.category {
+            StorageCategory::Rollouts => categories.rollouts += file.bytes,
+            StorageCategory::Attachments => categories.attachments += file.bytes,
+            StorageCategory::Artifacts => categories.artifacts += file.bytes,
+            StorageCategory::Logs => categories.logs += file.bytes,
+        }
+    }
+    categories.rollouts += usage
+        .values()
+        .map(|thread| thread.postgres_bytes)
+        .sum::<u64>();
+
+    let mut largest_threads: Vec<ThreadStorage> = usage
+        .iter()
+        .map(|(thread_id, thread)| ThreadStorage {
+            thread_id: thread_id.to_string(),
+            bytes: thread.bytes,
+            archived: thread.archived,
+        })
+        .collect();
+    largest_threads.sort_by(|a, b| {
+        b.bytes
+            .cmp(&a.bytes)
+            .then_with(|| a.thread_id.cmp(&b.thread_id))
+    });
+    largest_threads.truncate(
+        params
+            .top
+            .unwrap_or(DEFAULT_TOP_THREADS)
+            .min(MAX_TOP_THREADS),
+    );
+
+    Ok(Json(StorageReport {
+        total_bytes: categories.rollouts
+            + categories.attachments
+            + categories.artifacts
+            + categories.logs,
+        categories,
+        largest_threads,
+    }))
+}
+
+/// POST /api/v2/maintenance/vacuum
+///
+/// Removes rollouts and attachments matching the policy. Threads loaded in
+/// this server are never touched. Defaults to a dry run
+#[utoipa::path(
+    post,
+    path = "/api/v2/maintenance/vacuum",
+    request_body = VacuumRequest,
+    responses(
+        (status = 200, description = "Data removed, or the dry-run plan", body = VacuumResponse),
+        (status = 400, description = "Neither older_than_days nor archived_only is set"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Maintenance"
+)]
+pub async fn vacuum(
+    State(state): State<WebServerState>,
+    JsonBody(request): JsonBody<VacuumRequest>,
+) -> Result<Json<VacuumResponse>, ApiError> {
+    if request.older_than_days.is_none() && !request.archived_only {
+        return Err(ApiError::InvalidRequest(
+            "Set older_than_days, archived_only, or both".to_string(),
+        ));
+    }
+    let cutoff = request.older_than_days.and_then(|days| {
+        SystemTime::now().checked_sub(Duration::from_secs(days * SECONDS_PER_DAY))
+    });
+    let is_old = |modified: Option<SystemTime>| match (cutoff, modified) {
+        (None, _) => true,
+        (Some(cutoff), Some(modified)) => modified < cutoff,
+        // Age unknown: keep it.
+        (Some(_), None) => false,
+    };
+
+    let files = storage::scan(&state.codex_home, &state.attachments_dir);
+    let usage = thread_usage(&state, &files).await?;
+    let loaded: HashSet<ThreadId> = state
+        .thread_manager
+        .list_thread_ids()
+        .await
+        .into_iter()
+        .collect();
+
+    let mut deleted = Vec::new();
+    let mut pruned_threads = Vec::new();
+    for (thread_id, thread) in usage {
+        if loaded.contains(&thread_id)
+            || (request.archived_only && !thread.archived)
+            || !is_old(thread.last_write)
+        {
+            continue;
+        }
+        for file in thread.files {
+            deleted.push(VacuumedItem {
+                category: StorageCategory::Rollouts,
+                path: file.path.display().to_string(),
+                bytes: file.bytes,
+                thread_id: Some(thread_id.to_string()),
+            });
+        }
+        if thread.postgres_bytes > 0 {
+            deleted.push(VacuumedItem {
+                category: StorageCategory::Rollouts,
+                path: "postgres".to_string(),
+                bytes: thread.postgres_bytes,
+                thread_id: Some(thread_id.to_string()),
+            });
+        }
+        pruned_threads.push(thread_id);
+    }
+    // Attachments are removed right away, one by one under the blob lock,
+    // rather than by path below.
+    if !request.archived_only
+        && let Some(cutoff) = cutoff
+    {
+        let attachments =
+            attachment_gc::vacuum_attachments(&state, cutoff, &pruned_threads, request.dry_run)
+                .await;
+        for (path, bytes) in attachments {
+            deleted.push(VacuumedItem {
+                category: StorageCategory::Attachments,
+                path: path.display().to_string(),
+                bytes,
+                thread_id: None,
+            });
+        }
+    }
+    deleted.sort_by(|a, b| a.path.cmp(&b.path));
+
+    if !request.dry_run {
+        let failed = storage::remove_files(
+            &state.codex_home,
+            deleted
+                .iter()
+                .filter(|item| {
+                    item.category == StorageCategory::Rollouts && item.path != "postgres"
+                })
+                .map(|item| Path::new(&item.path)),
+        );
+        for (path, err) in &failed {
+            tracing::warn!("Vacuum failed to remove {}: {err}", path.display());
+        }
+        deleted.retain(|item| !failed.iter().any(|(path, _)| Path::new(&item.path) == path));
+        if let Some(store) = &state.state_store
+            && !pruned_threads.is_empty()
+        {
+            store
+                .prune_threads(&pruned_threads)
+                .await
+                .map_err(|err| ApiError::InternalError(err.to_string()))?;
+        }
+        let mut auto_archived = state.auto_archived.lock().await;
+        for thread_id in &pruned_threads {
+            auto_archived.remove(thread_id);
+        }
+    }
+
+    Ok(Json(VacuumResponse {
+        dry_run: request.dry_run,
+        reclaimed_bytes: deleted.iter().map(|item| item.bytes).sum(),
+        deleted,
+    }))
+}
@@ -1,34 +1,61 @@
 use axum::Json;
 use axum::extract::Path;
+use axum::extract::Query;
 use axum::extract::State;
-use codex_app_server_protocol::McpServerStatus;
+use codex_app_server_protocol::ConfigLayerSource;
+use codex_app_server_protocol::ConfigReadParams;
+use codex_core::config::Config;
+use codex_core::config::edit::ConfigEditsBuilder;
+use codex_protocol::protocol::McpServerRefreshConfig;
 use serde::Deserialize;
 use serde::Serialize;
 use std::result::Result;
-use tokio::sync::oneshot;
 use utoipa::ToSchema;
 
 use crate::error::ApiError;
+use crate::json_body::JsonBody;
+use crate::mcp_snapshot::McpServerListing;
 use crate::state::WebServerState;
 
-#[derive(Debug, Deserialize, ToSchema)]
+#[derive(Debug, Default, Deserialize, ToSchema)]
 pub struct ListMcpServerStatusParams {
     #[serde(default)]
     pub limit: Option<usize>,
     #[serde(default)]
     pub cursor: Option<String>,
+    /// Re-collect instead of serving the cached snapshot
+    #[serde(default)]
+    pub refresh: bool,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
 pub struct ListMcpServerStatusResponse {
     #[schema(value_type = Vec<Object>)]
-    pub data: Vec<McpServerStatus>,
+    pub data: Vec<McpServerListing>,
     pub next_cursor: Option<String>,
+    /// The snapshot is older than the cache TTL; a refresh is running
+    pub stale: bool,
+    /// Unix seconds when the snapshot was collected
+    pub collected_at: i64,
+    /// Servers that did not respond within the per-server timeout
+    pub timed_out_servers: Vec<String>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
 pub struct McpServerRefreshResponse {}
 
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateMcpServerRequest {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UpdateMcpServerResponse {
+    pub name: String,
+    /// Whether the server is enabled in the config reloaded after the write
+    pub enabled: bool,
+}
+
 #[derive(Debug, Serialize, ToSchema)]
 pub struct McpOAuthLoginResponse {
     pub auth_url: Option<String>,
@@ -41,8 +68,9 @@ pub struct McpOAuthLoginResponse {
     get,
     path = "/api/v2/mcp/servers",
     params(
-        ("limit" = Option<usize>, Query, description = "Maximum number of servers to return (default: 20)"),
-        ("cursor" = Option<String>, Query, description = "Pagination cursor (offset as string)")
+        ("limit" = Option<usize>, Query, description = "Maximum number of servers to return (default: 100)"),
+        ("cursor" = Option<String>, Query, description = "Pagination cursor (offset as string)"),
+        ("refresh" = Option<bool>, Query, description = "Re-collect the snapshot instead of serving the cached copy")
     ),
     responses(
         (status = 200, description = "MCP server status list retrieved successfully", body = ListMcpServerStatusResponse),
@@ -55,106 +83,34 @@ pub struct McpOAuthLoginResponse {
     tag = "MCP"
 )]
 pub async fn list_mcp_server_status(
-    State(_state): State<WebServerState>,
+    State(state): State<WebServerState>,
+    Query(params): Query<ListMcpServerStatusParams>,
 ) -> Result<Json<ListMcpServerStatusResponse>, ApiError> {
-    // TODO: Enable axum "query" feature for query parameters
-    let params = ListMcpServerStatusParams {
-        limit: None,
-        cursor: None,
-    };
-    // Spawn async task to avoid blocking
-    let (tx, rx) = oneshot::channel();
-
-    tokio::spawn(async move {
-        let result = list_mcp_server_status_task(params).await;
-        let _ = tx.send(result);
-    });
-
-    let response = rx
-        .await
-        .map_err(|_| ApiError::InternalError("MCP status task failed".to_string()))??;
-
-    Ok(Json(response))
-}
-
-async fn list_mcp_server_status_task(
-    params: ListMcpServerStatusParams,
-) -> Result<ListMcpServerStatusResponse, ApiError> {
-    // Load core config for MCP snapshot collection
-    let config = codex_core::config::Config::load_with_cli_overrides(vec![])
+    let cached = state
+        .mcp_snapshots
+        .get(params.refresh)
         .await
-        .map_err(|e| ApiError::InternalError(format!("Failed to load config: {e}")))?;
-
-    // Collect MCP snapshot (async operation)
-    let snapshot = codex_core::mcp::collect_mcp_snapshot(&config).await;
-
-    // Group tools by server
-    let tools_by_server = codex_core::mcp::group_tools_by_server(&snapshot.tools);
-
-    // Collect all unique server names
-    let mut server_names: Vec<String> = config
-        .mcp_servers
-        .keys()
-        .cloned()
-        .chain(snapshot.auth_statuses.keys().cloned())
-        .chain(snapshot.resources.keys().cloned())
-        .chain(snapshot.resource_templates.keys().cloned())
-        .collect();
-    server_names.sort();
-    server_names.dedup();
+        .map_err(|e| ApiError::InternalError(format!("Failed to collect MCP status: {e}")))?;
+    let servers = cached.snapshot.servers;
 
     // Apply pagination
-    let limit = params.limit.unwrap_or(100);
-    let effective_limit = limit.clamp(1, 100);
-
-    let cursor_offset = params
+    let limit = params.limit.unwrap_or(100).clamp(1, 100);
+    let start = params
         .cursor
         .as_deref()
         .and_then(|s| s.parse::<usize>().ok())
-        .unwrap_or(0);
-
-    let start = cursor_offset;
-    let total = server_names.len();
-
-    // If start offset is beyond total, return empty list
-    if start >= total {
-        return Ok(ListMcpServerStatusResponse {
-            data: Vec::new(),
-            next_cursor: None,
-        });
-    }
-
-    let end = start.saturating_add(effective_limit).min(total);
+        .unwrap_or(0)
+        .min(servers.len());
+    let end = start.saturating_add(limit).min(servers.len());
+    let next_cursor = (end < servers.len()).then(|| end.to_string());
 
-    // Build McpServerStatus list for the current page
-    let data: Vec<McpServerStatus> = server_names[start..end]
-        .iter()
-        .map(|name| McpServerStatus {
-            name: name.clone(),
-            tools: tools_by_server.get(name).cloned().unwrap_or_default(),
-            resources: snapshot.resources.get(name).cloned().unwrap_or_default(),
-            resource_templates: snapshot
-                .resource_templates
-                .get(name)
-                .cloned()
-                .unwrap_or_default(),
-            auth_status: snapshot
-                .auth_statuses
-                .get(name)
-                .cloned()
-                .unwrap_or(codex_protocol::protocol::McpAuthStatus::Unsupported)
-                .into(),
-        })
-        .collect();
-
-    // Compute next cursor
-    let next_cursor = if end < total {
-        Some(end.to_string())
-    } else {
-        None
-    };
-
-    Ok(ListMcpServerStatusResponse { data, next_cursor })
+    Ok(Json(ListMcpServerStatusResponse {
+        data: servers[start..end].to_vec(),
+        next_cursor,
+        stale: cached.stale,
+        collected_at: cached.collected_at,
+        timed_out_servers: cached.snapshot.timed_out_servers,
+    }))
 }
 
 /// POST /api/v2/mcp/servers/refresh
@@ -174,21 +130,118 @@ async fn list_mcp_server_status_task(
     tag = "MCP"
 )]
 pub async fn refresh_mcp_servers(
-    State(_state): State<WebServerState>,
+    State(state): State<WebServerState>,
 ) -> Result<Json<McpServerRefreshResponse>, ApiError> {
-    // TODO: Implement MCP server refresh
-    // This requires:
-    // 1. Loading latest config
-    // 2. Serializing MCP servers
-    // 3. Creating RefreshConfig (need to check if this type exists)
-    // 4. Calling ThreadManager::refresh_mcp_servers()
-    //
-    // Reference: app-server/src/codex_message_processor.rs::mcp_server_refresh
-
-    // For now, return success stub
+    let config = state.config_loader.load().await?;
+    refresh_after_config_change(&state, &config).await?;
     Ok(Json(McpServerRefreshResponse {}))
 }
 
+/// PATCH /api/v2/mcp/servers/:name
+///
+/// Enables or disables an MCP server without removing its config
+#[utoipa::path(
+    patch,
+    path = "/api/v2/mcp/servers/{name}",
+    params(
+        ("name" = String, Path, description = "MCP server name")
+    ),
+    request_body = UpdateMcpServerRequest,
+    responses(
+        (status = 200, description = "MCP server updated", body = UpdateMcpServerResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "MCP server not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "MCP"
+)]
+pub async fn update_mcp_server(
+    State(state): State<WebServerState>,
+    Path(name): Path<String>,
+    JsonBody(req): JsonBody<UpdateMcpServerRequest>,
+) -> Result<Json<UpdateMcpServerResponse>, ApiError> {
+    if !state
+        .config_loader
+        .load()
+        .await?
+        .mcp_servers
+        .get()
+        .contains_key(&name)
+    {
+        return Err(ApiError::NotFound(format!("MCP server not found: {name}")));
+    }
+
+    ConfigEditsBuilder::new(&state.codex_home)
+        .set_mcp_server_enabled(&name, req.enabled)
+        .apply()
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to update MCP server: {e}")))?;
+
+    // A load that read config.toml before the write may still be running;
+    // recording the write keeps the reload from joining it.
+    let version = user_config_version(&state).await?;
+    state.config_loader.record_write(&version);
+    let config = state.config_loader.load_at_least(&version).await?;
+    refresh_after_config_change(&state, &config).await?;
+
+    let enabled = config
+        .mcp_servers
+        .get()
+        .get(&name)
+        .map_or(req.enabled, |server| server.enabled);
+    Ok(Json(UpdateMcpServerResponse { name, enabled }))
+}
+
+/// Version of the user config layer as it is on disk now.
+async fn user_config_version(state: &WebServerState) -> Result<String, ApiError> {
+    let response = state
+        .config_service
+        .read(ConfigReadParams {
+            include_layers: true,
+            cwd: None,
+        })
+        .await?;
+    Ok(response
+        .layers
+        .unwrap_or_default()
+        .into_iter()
+        .find(|layer| matches!(layer.name, ConfigLayerSource::User { .. }))
+        .map(|layer| layer.version)
+        .unwrap_or_default())
+}
+
+/// Queues an MCP reload on every loaded thread (applied on each thread's next
+/// turn) and re-collects the status listing.
+async fn refresh_after_config_change(
+    state: &WebServerState,
+    config: &Config,
+) -> Result<(), ApiError> {
+    let refresh_config = McpServerRefreshConfig {
+        mcp_servers: serde_json::to_value(config.mcp_servers.get()).map_err(|e| {
+            ApiError::InternalError(format!("Failed to serialize MCP servers: {e}"))
+        })?,
+        mcp_oauth_credentials_store_mode: serde_json::to_value(
+            config.mcp_oauth_credentials_store_mode,
+        )
+        .map_err(|e| {
+            ApiError::InternalError(format!(
+                "Failed to serialize MCP OAuth credentials store mode: {e}"
+            ))
+        })?,
+    };
+    state
+        .thread_manager
+        .refresh_mcp_servers(refresh_config)
+        .await;
+
+    state.mcp_snapshots.invalidate().await;
+    state.mcp_snapshots.spawn_refresh().await;
+    Ok(())
+}
+
 /// POST /api/v2/mcp/servers/:name/auth
 ///
 /// Initiates OAuth login for an MCP server
@@ -215,9 +268,7 @@ pub async fn mcp_oauth_login(
     Path(name): Path<String>,
 ) -> Result<Json<McpOAuthLoginResponse>, ApiError> {
     // Load config to get MCP server settings
-    let config = codex_core::config::Config::load_with_cli_overrides(vec![])
-        .await
-        .map_err(|e| ApiError::InternalError(format!("Failed to load config: {e}")))?;
+    let config = state.config_loader.load().await?;
 
     // Get MCP server configuration
     let server = config
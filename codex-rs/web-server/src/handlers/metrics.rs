@@ -0,0 +1,38 @@
+use axum::Json;
+use axum::extract::State;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::metrics::MetricsSnapshot;
+use crate::state::WebServerState;
+use crate::thread_memory::ThreadMemorySnapshot;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MetricsResponse {
+    #[serde(flatten)]
+    pub counters: MetricsSnapshot,
+    /// Occupancy and evictions of the per-thread caches
+    pub thread_memory: ThreadMemorySnapshot,
+}
+
+/// GET /api/v2/metrics
+///
+/// Returns server counters
+#[utoipa::path(
+    get,
+    path = "/api/v2/metrics",
+    responses(
+        (status = 200, description = "Current counters", body = MetricsResponse),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Metrics"
+)]
+pub async fn get_metrics(State(state): State<WebServerState>) -> Json<MetricsResponse> {
+    Json(MetricsResponse {
+        counters: state.metrics.snapshot(),
+        thread_memory: state.thread_memory.snapshot(),
+    })
+}
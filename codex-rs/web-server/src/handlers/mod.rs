@@ -1,32 +1,62 @@
 pub mod approvals;
 pub mod auth;
+pub mod capabilities;
 pub mod commands;
 pub mod config;
+pub mod execpolicy;
 pub mod feedback;
+pub mod files;
+pub mod health;
+pub mod maintenance;
 pub mod mcp;
+pub mod metrics;
 pub mod models;
+pub mod patches;
 pub mod review;
+pub mod rpc;
+pub mod sandbox;
 pub mod skills;
 pub mod threads;
 pub mod turns;
+pub mod usage;
 
 use axum::Json;
 use axum::extract::Path;
+use axum::extract::Query;
 use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::http::Uri;
 use axum::response::sse::Event;
 use axum::response::sse::Sse;
-use codex_core::config::Config;
-use codex_protocol::protocol::Op;
-use codex_protocol::user_input::UserInput;
+use codex_protocol::config_types::SandboxMode;
+use codex_protocol::openai_models::ReasoningEffort;
+use codex_protocol::protocol::AskForApproval;
+use futures::future::Either;
 use futures::stream::Stream;
 use serde::Deserialize;
 use serde::Serialize;
+use std::collections::HashSet;
 use std::convert::Infallible;
-use std::sync::Arc;
 use std::time::Duration;
 use utoipa::ToSchema;
 
+use crate::api_version::ApiVersion;
+use crate::approval_manager;
+use crate::approval_manager::ApprovalCancelReason;
+use crate::approval_manager::ApprovalCancelled;
+use crate::attachments::AttachmentScope;
 use crate::error::ApiError;
+use crate::event_filter::EventFilter;
+use crate::event_ids::EventId;
+use crate::event_ids::LAST_EVENT_ID_HEADER;
+use crate::event_pump::PublishedEvent;
+use crate::event_pump::ThreadEvent;
+use crate::event_replay::Replay;
+use crate::event_replay::StreamReset;
+use crate::firehose;
+use crate::json_body::JsonBody;
+use crate::permissions;
+use crate::sse_envelope::EventStreamParams;
 use crate::state::WebServerState;
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -35,6 +65,24 @@ pub struct CreateThreadRequest {
     pub cwd: Option<String>,
     #[schema(example = "claude-sonnet-4-5")]
     pub model: Option<String>,
+    /// Accept a `model` missing from the model list, e.g. for a custom
+    /// provider. Length and character limits still apply.
+    #[serde(default)]
+    pub skip_validation: bool,
+    /// Must be listed in `allowed_approval_policies` when requirements are set
+    #[serde(default)]
+    #[schema(value_type = Option<String>, example = "on-request")]
+    pub approval_policy: Option<AskForApproval>,
+    /// Must be listed in `allowed_sandbox_modes` when requirements are set
+    #[serde(default)]
+    #[schema(value_type = Option<String>, example = "workspace-write")]
+    pub sandbox_mode: Option<SandboxMode>,
+    #[serde(default)]
+    #[schema(value_type = Option<String>, example = "high")]
+    pub model_reasoning_effort: Option<ReasoningEffort>,
+    /// Replaces the model's built-in instructions for this thread
+    #[serde(default)]
+    pub base_instructions: Option<String>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -43,9 +91,15 @@ pub struct CreateThreadResponse {
     pub thread_id: String,
     #[schema(example = "claude-sonnet-4-5")]
     pub model: String,
+    /// Server instance that owns the thread (echo as `X-Codex-Instance`)
+    #[schema(example = "6f1c2d3e-4b5a-4c6d-8e9f-0a1b2c3d4e5f")]
+    pub instance_id: String,
+    #[serde(flatten)]
+    pub settings: threads::ThreadSettings,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
 pub struct SendTurnRequest {
     pub input: Vec<UserInputItem>,
 }
@@ -63,12 +117,66 @@ pub enum UserInputItem {
         #[schema(example = "019bcfb9-4ea6-72e0-b43d-6b7e26ff0daf")]
         attachment_id: String,
     },
+    /// A small image sent inline, stored as an attachment of the caller
+    #[serde(rename = "image")]
+    Image {
+        /// Base64 of the image, at most 4 MiB once decoded
+        #[schema(
+            example = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg=="
+        )]
+        data: String,
+        #[schema(example = "image/png")]
+        mime_type: String,
+    },
 }
 
 #[derive(Debug, Serialize, ToSchema)]
 pub struct SendTurnResponse {
+    /// Matches `turn.id` on the `turn/started` and `turn/completed` events
+    /// for this input.
     #[schema(example = "turn-12345")]
     pub turn_id: String,
+    /// `true` when the input joined the turn already in progress.
+    pub steered: bool,
+}
+
+// The v1 routes keep their own schemas but delegate to the v2 logic.
+
+impl From<CreateThreadRequest> for threads::CreateThreadRequest {
+    fn from(req: CreateThreadRequest) -> Self {
+        Self {
+            cwd: req.cwd,
+            model: req.model,
+            skip_validation: req.skip_validation,
+            approval_policy: req.approval_policy,
+            sandbox_mode: req.sandbox_mode,
+            model_reasoning_effort: req.model_reasoning_effort,
+            base_instructions: req.base_instructions,
+            min_config_version: None,
+            provider_overrides: Default::default(),
+        }
+    }
+}
+
+impl From<threads::CreateThreadResponse> for CreateThreadResponse {
+    fn from(response: threads::CreateThreadResponse) -> Self {
+        Self {
+            thread_id: response.thread_id,
+            model: response.model,
+            instance_id: response.instance_id,
+            settings: response.settings,
+        }
+    }
+}
+
+impl From<UserInputItem> for turns::UserInputItem {
+    fn from(item: UserInputItem) -> Self {
+        match item {
+            UserInputItem::Text { text } => Self::Text { text },
+            UserInputItem::Attachment { attachment_id } => Self::Attachment { attachment_id },
+            UserInputItem::Image { data, mime_type } => Self::Image { data, mime_type },
+        }
+    }
 }
 
 #[utoipa::path(
@@ -77,8 +185,11 @@ pub struct SendTurnResponse {
     request_body = CreateThreadRequest,
     responses(
         (status = 200, description = "Thread created successfully", body = CreateThreadResponse),
-        (status = 401, description = "Unauthorized"),
-        (status = 500, description = "Internal server error")
+        (status = 400, description = "Invalid override value, unknown model or unusable cwd"),
+        (status = 401, description = "Unauthorized, or provider credentials missing"),
+        (status = 403, description = "Override violates config requirements, or cwd is outside the trusted roots"),
+        (status = 500, description = "Internal server error"),
+        (status = 502, description = "Required MCP server failed to start")
     ),
     security(
         ("bearer_auth" = [])
@@ -87,30 +198,10 @@ pub struct SendTurnResponse {
 )]
 pub async fn create_thread(
     State(state): State<WebServerState>,
-    Json(req): Json<CreateThreadRequest>,
+    JsonBody(req): JsonBody<CreateThreadRequest>,
 ) -> Result<Json<CreateThreadResponse>, ApiError> {
-    let mut config = Config::load_with_cli_overrides(vec![])
-        .await
-        .map_err(|e| ApiError::InternalError(format!("Failed to load config: {e}")))?;
-
-    if let Some(cwd) = req.cwd {
-        config.cwd = std::path::PathBuf::from(cwd);
-    }
-
-    if let Some(model) = req.model {
-        config.model = Some(model);
-    }
-
-    let new_thread = state
-        .thread_manager
-        .start_thread(config.clone())
-        .await
-        .map_err(|e| ApiError::InternalError(format!("Failed to start thread: {e}")))?;
-
-    Ok(Json(CreateThreadResponse {
-        thread_id: new_thread.thread_id.to_string(),
-        model: config.model.unwrap_or_else(|| "default".to_string()),
-    }))
+    let response = threads::create_thread_inner(&state, req.into()).await?;
+    Ok(Json(response.into()))
 }
 
 #[utoipa::path(
@@ -122,9 +213,11 @@ pub async fn create_thread(
     ),
     responses(
         (status = 200, description = "Turn submitted successfully", body = SendTurnResponse),
-        (status = 400, description = "Invalid request"),
+        (status = 400, description = "Invalid request, or an attachment that is neither an image nor text"),
         (status = 401, description = "Unauthorized"),
         (status = 404, description = "Thread not found"),
+        (status = 409, description = "Thread was archived or resumed while the request was in progress (`thread_state_changed`)"),
+        (status = 422, description = "The thread's model does not accept image attachments (`unsupported_input_modality`)"),
         (status = 500, description = "Internal server error")
     ),
     security(
@@ -134,81 +227,38 @@ pub async fn create_thread(
 )]
 pub async fn send_turn(
     State(state): State<WebServerState>,
+    scope: AttachmentScope,
     Path(thread_id): Path<String>,
-    Json(req): Json<SendTurnRequest>,
+    JsonBody(req): JsonBody<SendTurnRequest>,
 ) -> Result<Json<SendTurnResponse>, ApiError> {
-    let thread_id = codex_protocol::ThreadId::from_string(&thread_id)
-        .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
-
-    let thread = state
-        .thread_manager
-        .get_thread(thread_id)
-        .await
-        .map_err(|_| ApiError::ThreadNotFound)?;
-
-    let mut user_inputs = Vec::new();
-
-    for item in req.input {
-        match item {
-            UserInputItem::Text { text } => {
-                user_inputs.push(UserInput::Text {
-                    text,
-                    text_elements: Vec::new(),
-                });
-            }
-            UserInputItem::Attachment { attachment_id } => {
-                uuid::Uuid::parse_str(&attachment_id).map_err(|_| {
-                    ApiError::InvalidRequest("Invalid attachment ID format".to_string())
-                })?;
-
-                let attachment_path = state.attachments_dir.join(&attachment_id);
-                if !attachment_path.exists() {
-                    return Err(ApiError::AttachmentNotFound);
-                }
-
-                let canonical_path = attachment_path
-                    .canonicalize()
-                    .map_err(|_| ApiError::AttachmentNotFound)?;
-                let canonical_attachments_dir =
-                    state.attachments_dir.canonicalize().map_err(|e| {
-                        ApiError::InternalError(format!(
-                            "Failed to resolve attachments directory: {e}"
-                        ))
-                    })?;
-
-                if !canonical_path.starts_with(&canonical_attachments_dir) {
-                    return Err(ApiError::InvalidRequest(
-                        "Invalid attachment path".to_string(),
-                    ));
-                }
-
-                user_inputs.push(UserInput::LocalImage {
-                    path: canonical_path,
-                });
-            }
-        }
-    }
-
-    let turn_id: String = thread
-        .submit(Op::UserInput {
-            items: user_inputs,
-            final_output_json_schema: None,
-        })
-        .await
-        .map_err(|e| ApiError::InternalError(format!("Failed to submit turn: {e}")))?;
-
-    Ok(Json(SendTurnResponse { turn_id }))
+    let request = turns::SendTurnRequest {
+        input: req.input.into_iter().map(Into::into).collect(),
+        ..Default::default()
+    };
+    let response =
+        turns::send_turn_inner(&state, &scope, &thread_id, turns::WhenBusy::Steer, request).await?;
+    // v1 never queues, so the input always starts or joins a turn.
+    let turn_id = response
+        .turn_id
+        .ok_or_else(|| ApiError::InternalError("Turn input was queued".to_string()))?;
+    Ok(Json(SendTurnResponse {
+        turn_id,
+        steered: response.steered,
+    }))
 }
 
 #[utoipa::path(
     get,
     path = "/api/v1/threads/{thread_id}/events",
     params(
-        ("thread_id" = String, Path, description = "Thread ID")
+        ("thread_id" = String, Path, description = "Thread ID"),
+        ("envelope" = Option<String>, Query, description = "Shape of each event's data: `v1` (bare payload, default) or `v2` (`{v, data}`)"),
+        ("events" = Option<String>, Query, description = "Comma-separated event type prefixes to send, e.g. `turn,item/completed`; approval requests are always sent"),
+        ("Last-Event-ID" = Option<String>, Header, description = "Last `<epoch>:<seq>` id the client received")
     ),
     responses(
-        (status = 200, description = "SSE event stream", content_type = "text/event-stream"),
-        (status = 400, description = "Invalid request"),
+        (status = 200, description = "SSE event stream; each event has an `<epoch>:<seq>` id", content_type = "text/event-stream"),
+        (status = 400, description = "Invalid request, malformed Last-Event-ID or empty events filter"),
         (status = 401, description = "Unauthorized"),
         (status = 404, description = "Thread not found")
     ),
@@ -220,231 +270,245 @@ pub async fn send_turn(
 pub async fn stream_events(
     State(state): State<WebServerState>,
     Path(thread_id): Path<String>,
+    Query(params): Query<EventStreamParams>,
+    uri: Uri,
+    headers: HeaderMap,
 ) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
     use crate::event_stream::EventStreamProcessor;
-    use crate::state::ApprovalContext;
-    use codex_app_server_protocol::CommandExecutionRequestApprovalParams;
-    use codex_app_server_protocol::FileChangeRequestApprovalParams;
-    use codex_protocol::protocol::EventMsg;
-    use codex_protocol::protocol::Op;
-    use codex_protocol::protocol::ReviewDecision;
-    use tokio::sync::oneshot;
+    use crate::state::StreamRegistration;
+    use codex_app_server_protocol::ServerNotification;
+    use codex_app_server_protocol::ThreadArchivedNotification;
+    use codex_app_server_protocol::Turn;
+    use codex_app_server_protocol::TurnCompletedNotification;
+    use codex_app_server_protocol::TurnError;
+    use codex_app_server_protocol::TurnReasonCode;
+    use codex_app_server_protocol::TurnStatus;
+    use futures::StreamExt;
+    use tokio::sync::broadcast;
 
     let thread_id = codex_protocol::ThreadId::from_string(&thread_id)
         .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
+    let envelope = params.envelope;
+    let filter = match params.events.as_deref() {
+        Some(events) => events
+            .parse::<EventFilter>()
+            .map_err(ApiError::InvalidRequest)?,
+        None => EventFilter::default(),
+    };
+    let last_event_id = match headers.get(LAST_EVENT_ID_HEADER) {
+        Some(last_event_id) => Some(
+            last_event_id
+                .to_str()
+                .map_err(|_| ApiError::InvalidRequest("Last-Event-ID must be ASCII".to_string()))?
+                .parse::<EventId>()
+                .map_err(ApiError::InvalidRequest)?,
+        ),
+        None => None,
+    };
 
-    let thread = state
-        .thread_manager
-        .get_thread(thread_id)
-        .await
-        .map_err(|_| ApiError::ThreadNotFound)?;
-
-    // Register stream in session store
-    {
-        let mut sessions = state.sessions.write().await;
-        sessions.register_stream(thread_id);
-    }
+    let thread = match state.thread_manager.get_thread(thread_id).await {
+        Ok(thread) => thread,
+        Err(_) if state.auto_archived.lock().await.contains_key(&thread_id) => {
+            // Tell late-connecting clients the thread was archived for idleness;
+            // it can be brought back with the resume endpoint.
+            let notification = ServerNotification::ThreadArchived(ThreadArchivedNotification {
+                thread_id: thread_id.to_string(),
+            });
+            let event_type = EventStreamProcessor::event_type_name(&notification);
+            let json_data = serde_json::to_string(&notification).unwrap_or_default();
+            let event = filter
+                .allows(event_type)
+                .then(|| Ok(envelope.event(event_type, json_data)));
+            return Ok(Sse::new(futures::stream::iter(event).boxed()));
+        }
+        Err(_) => return Err(ApiError::ThreadNotFound),
+    };
 
-    let event_processor = EventStreamProcessor::new(thread_id, Arc::new(state.clone()));
-    let state_for_stream = state.clone();
-    let thread_for_approval = thread.clone();
+    let (registration, mut events) = StreamRegistration::new(&state, thread_id, thread).await;
+    let mut account_events = state.account_logins.subscribe();
+    // Taken after subscribing, so an event published in between is both
+    // replayed and received; the loop below skips the second copy.
+    let replay = last_event_id.map(|last| {
+        (
+            last,
+            state
+                .event_replay
+                .since(&state.thread_memory, thread_id, last),
+        )
+    });
+
+    let restored = state.take_restored(thread_id).await;
+    let pending_approvals = approval_manager::pending_approval_requests(&state, thread_id).await;
+    let deprecation = (ApiVersion::from_path(uri.path()) == Some(ApiVersion::V1)).then(|| {
+        ServerNotification::DeprecationNotice(state.api_version_policy.v1_deprecation_notice())
+    });
+    let permission_warning = permissions::config_warning(&permissions::audit(
+        &state.codex_home,
+        &state.attachments_dir,
+    ))
+    .map(ServerNotification::ConfigWarning);
 
     let stream = async_stream::stream! {
-        loop {
-            match thread.next_event().await {
-                Ok(event) => {
-                    let event_msg = event.msg.clone();
-
-                    // Special handling for approval requests
-                    match &event_msg {
-                        EventMsg::ExecApprovalRequest(ev) => {
-                            // Register approval context
-                            let (tx, rx) = oneshot::channel();
-                            let call_id = ev.call_id.clone();
-                            let approval_id = ev.effective_approval_id();
-                            let approval_ctx = ApprovalContext {
-                                thread_id,
-                                item_id: approval_id.clone(),
-                                approval_type: crate::state::ApprovalType::CommandExecution {
-                                    command: ev.command.clone(),
-                                    cwd: ev.cwd.clone(),
-                                    reason: ev.reason.clone().unwrap_or_default(),
-                                },
-                                response_channel: tx,
-                                created_at: std::time::Instant::now(),
-                                timeout: Duration::from_secs(900), // 15 minutes
-                            };
-
-                            {
-                                let mut approvals = state_for_stream.pending_approvals.lock().await;
-                                approvals.insert(approval_id.clone(), approval_ctx);
-                            }
-
-                            // Send approval request as SSE event
-                            let params = CommandExecutionRequestApprovalParams {
-                                thread_id: thread_id.to_string(),
-                                turn_id: ev.turn_id.clone(),
-                                item_id: call_id,
-                                approval_id: ev.approval_id.clone(),
-                                reason: ev.reason.clone(),
-                                network_approval_context: ev
-                                    .network_approval_context
-                                    .clone()
-                                    .map(std::convert::Into::into),
-                                command: Some(ev.command.join(" ")),
-                                cwd: Some(ev.cwd.clone()),
-                                command_actions: None,
-                                proposed_execpolicy_amendment: ev.proposed_execpolicy_amendment.clone().map(std::convert::Into::into),
-                            };
-
-                            let event_type = "item/commandExecution/requestApproval";
-                            let json_data = serde_json::to_string(&params).unwrap_or_default();
-                            yield Ok(Event::default().event(event_type).data(json_data));
-
-                            // Spawn task to wait for approval response
-                            let thread_clone = thread_for_approval.clone();
-                            let approval_id_clone = approval_id.clone();
-                            let turn_id_clone = ev.turn_id.clone();
-                            tokio::spawn(async move {
-                                match rx.await {
-                                    Ok(response) => {
-                                        let decision = match response.decision {
-                                            crate::state::ApprovalDecision::Approve => {
-                                                ReviewDecision::Approved
-                                            }
-                                            crate::state::ApprovalDecision::Decline => {
-                                                ReviewDecision::Denied
-                                            }
-                                        };
-
-                                        if let Err(e) = thread_clone
-                                            .submit(Op::ExecApproval {
-                                                id: approval_id_clone.clone(),
-                                                turn_id: Some(turn_id_clone.clone()),
-                                                decision,
-                                            })
-                                            .await
-                                        {
-                                            tracing::error!("Failed to submit exec approval: {}", e);
-                                        }
-                                    }
-                                    Err(_) => {
-                                        // Channel closed, submit denial
-                                        if let Err(e) = thread_clone
-                                            .submit(Op::ExecApproval {
-                                                id: approval_id_clone.clone(),
-                                                turn_id: Some(turn_id_clone.clone()),
-                                                decision: ReviewDecision::Denied,
-                                            })
-                                            .await
-                                        {
-                                            tracing::error!("Failed to submit denied exec approval: {}", e);
-                                        }
-                                    }
-                                }
-                            });
-                        }
-
-                        EventMsg::ApplyPatchApprovalRequest(ev) => {
-                            // Register approval context
-                            let (tx, rx) = oneshot::channel();
-                            let approval_id = ev.call_id.clone();
-                            let approval_ctx = ApprovalContext {
-                                thread_id,
-                                item_id: approval_id.clone(),
-                                approval_type: crate::state::ApprovalType::FileChange {
-                                    reason: ev.reason.clone().unwrap_or_default(),
-                                },
-                                response_channel: tx,
-                                created_at: std::time::Instant::now(),
-                                timeout: Duration::from_secs(900), // 15 minutes
-                            };
-
-                            {
-                                let mut approvals = state_for_stream.pending_approvals.lock().await;
-                                approvals.insert(approval_id.clone(), approval_ctx);
-                            }
-
-                            // Send approval request as SSE event
-                            let params = FileChangeRequestApprovalParams {
-                                thread_id: thread_id.to_string(),
-                                turn_id: ev.turn_id.clone(),
-                                item_id: approval_id.clone(),
-                                reason: ev.reason.clone(),
-                                grant_root: ev.grant_root.clone(),
-                            };
-
-                            let event_type = "item/fileChange/requestApproval";
-                            let json_data = serde_json::to_string(&params).unwrap_or_default();
-                            yield Ok(Event::default().event(event_type).data(json_data));
-
-                            // Spawn task to wait for approval response
-                            let thread_clone = thread_for_approval.clone();
-                            let approval_id_clone = approval_id.clone();
-                            tokio::spawn(async move {
-                                match rx.await {
-                                    Ok(response) => {
-                                        let decision = match response.decision {
-                                            crate::state::ApprovalDecision::Approve => {
-                                                ReviewDecision::Approved
-                                            }
-                                            crate::state::ApprovalDecision::Decline => {
-                                                ReviewDecision::Denied
-                                            }
-                                        };
+        // Dropped with the stream, including when the client disconnects.
+        let _registration = registration;
 
-                                        if let Err(e) = thread_clone
-                                            .submit(Op::PatchApproval {
-                                                id: approval_id_clone.clone(),
-                                                decision,
-                                            })
-                                            .await
-                                        {
-                                            tracing::error!("Failed to submit patch approval: {}", e);
-                                        }
-                                    }
-                                    Err(_) => {
-                                        // Channel closed, submit denial
-                                        if let Err(e) = thread_clone
-                                            .submit(Op::PatchApproval {
-                                                id: approval_id_clone.clone(),
-                                                decision: ReviewDecision::Denied,
-                                            })
-                                            .await
-                                        {
-                                            tracing::error!("Failed to submit denied patch approval: {}", e);
-                                        }
-                                    }
-                                }
-                            });
-                        }
+        for notification in deprecation.into_iter().chain(permission_warning) {
+            let event_type = EventStreamProcessor::event_type_name(&notification);
+            if !filter.allows(event_type) {
+                continue;
+            }
+            let json_data = serde_json::to_string(&notification).unwrap_or_default();
+            yield Ok(envelope.event(event_type, json_data));
+        }
 
-                        _ => {
-                            // Process all other events through EventStreamProcessor
-                            let notifications = event_processor.process_event(event).await;
+        // Surface state left behind by a previous server run before live events.
+        if let Some(restored) = restored {
+            // Nothing waits for an answer to these any more, so they are
+            // reported as cancelled rather than asked again.
+            for approval in restored.approvals {
+                let cancelled = ApprovalCancelled {
+                    thread_id,
+                    turn_id: approval.turn_id,
+                    approval_id: approval.approval_id,
+                    reason: ApprovalCancelReason::ServerRestart,
+                };
+                if filter.allows(ApprovalCancelled::EVENT_TYPE) {
+                    yield Ok(envelope.event(
+                        ApprovalCancelled::EVENT_TYPE,
+                        cancelled.payload().to_string(),
+                    ));
+                }
+            }
+            for turn_id in restored.interrupted_turn_ids {
+                let notification = ServerNotification::TurnCompleted(TurnCompletedNotification {
+                    thread_id: thread_id.to_string(),
+                    turn: Turn {
+                        id: turn_id,
+                        items: vec![],
+                        error: Some(TurnError {
+                            message: "Turn interrupted by server restart".to_string(),
+                            codex_error_info: None,
+                            additional_details: None,
+                            reason_code: Some(TurnReasonCode::Shutdown),
+                        }),
+                        status: TurnStatus::Interrupted,
+                    },
+                });
+                let event_type = EventStreamProcessor::event_type_name(&notification);
+                if !filter.allows(event_type) {
+                    continue;
+                }
+                let json_data = serde_json::to_string(&notification).unwrap_or_default();
+                yield Ok(envelope.event(event_type, json_data));
+            }
+        }
 
-                            for notification in notifications {
-                                let event_type = EventStreamProcessor::event_type_name(&notification);
-                                let json_data = serde_json::to_string(&notification).unwrap_or_default();
+        // Requests published before this stream connected are still waiting
+        // for an answer. Their published copies are not sent again.
+        let mut redelivered_ids = HashSet::new();
+        for (approval_id, event) in pending_approvals {
+            redelivered_ids.insert(approval_id);
+            yield Ok(envelope.event(event.event_type, event.data));
+        }
+        let redelivered = |event: &ThreadEvent| {
+            approval_manager::requested_approval_id(event)
+                .is_some_and(|approval_id| redelivered_ids.contains(&approval_id))
+        };
+
+        // Events sent up to here carry no id, so the client's last id stays
+        // the last thread event it saw.
+        let mut seen_through = None;
+        match replay {
+            Some((last, Replay::Events(missed))) => {
+                seen_through = Some(last.seq);
+                for (id, event) in missed {
+                    seen_through = Some(id.seq);
+                    if !filter.allows(event.event_type) || redelivered(&event) {
+                        continue;
+                    }
+                    yield Ok(envelope.event(event.event_type, event.data).id(id.to_string()));
+                }
+            }
+            Some((last, Replay::Reset)) => {
+                let reset = StreamReset { thread_id, last_event_id: last };
+                yield Ok(envelope.event(StreamReset::EVENT_TYPE, reset.payload().to_string()));
+            }
+            None => {}
+        }
 
-                                yield Ok(Event::default()
-                                    .event(event_type)
-                                    .data(json_data));
-                            }
-                        }
+        // Thread events come from the thread's pump, numbered once for every
+        // stream. A closed pump means the thread stopped producing events.
+        // Account events are not the thread's and carry no id.
+        loop {
+            let received = tokio::select! {
+                received = events.recv() => Either::Left(received),
+                Ok(event) = account_events.recv() => Either::Right(event),
+            };
+            let received = match received {
+                Either::Left(received) => received,
+                Either::Right(event) => {
+                    if filter.allows(event.event_type) {
+                        yield Ok(envelope.event(event.event_type, event.data));
                     }
+                    continue;
                 }
-                Err(_) => {
-                    // Unregister stream on error/completion
-                    let mut sessions = state_for_stream.sessions.write().await;
-                    sessions.unregister_stream(thread_id);
-                    break;
+            };
+            match received {
+                Ok(PublishedEvent { id, event }) => {
+                    if seen_through.is_some_and(|seq| id.seq <= seq)
+                        || !filter.allows(event.event_type)
+                        || redelivered(&event)
+                    {
+                        continue;
+                    }
+                    yield Ok(envelope.event(event.event_type, event.data).id(id.to_string()));
                 }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Event stream for thread {thread_id} skipped {skipped} events");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
             }
         }
     };
 
-    Ok(Sse::new(stream).keep_alive(
+    Ok(Sse::new(stream.boxed()).keep_alive(
+        axum::response::sse::KeepAlive::new()
+            .interval(Duration::from_secs(10))
+            .text("keepalive"),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v2/events",
+    params(
+        ("envelope" = Option<String>, Query, description = "Shape of each event's data: `v1` (bare payload, default) or `v2` (`{v, data}`)"),
+        ("events" = Option<String>, Query, description = "Comma-separated event type prefixes to send, e.g. `turn,item/completed`; approval requests are always sent")
+    ),
+    responses(
+        (status = 200, description = "SSE stream of the events of every loaded thread, including threads loaded later; events carry no id", content_type = "text/event-stream"),
+        (status = 400, description = "Empty events filter"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Events"
+)]
+pub async fn stream_all_events(
+    State(state): State<WebServerState>,
+    Query(params): Query<EventStreamParams>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    use futures::StreamExt;
+
+    let filter = match params.events.as_deref() {
+        Some(events) => events
+            .parse::<EventFilter>()
+            .map_err(ApiError::InvalidRequest)?,
+        None => EventFilter::default(),
+    };
+    let stream = firehose::stream_all_events(state, filter, params.envelope).await;
+    Ok(Sse::new(stream.boxed()).keep_alive(
         axum::response::sse::KeepAlive::new()
             .interval(Duration::from_secs(10))
             .text("keepalive"),
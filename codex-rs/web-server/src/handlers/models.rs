@@ -1,8 +1,11 @@
 use axum::Json;
+use axum::extract::Query;
 use axum::extract::State;
+use axum::extract::rejection::QueryRejection;
 use codex_app_server_protocol::Model;
 use codex_app_server_protocol::ReasoningEffortOption;
 use codex_core::models_manager::manager::RefreshStrategy;
+use codex_protocol::openai_models::InputModality;
 use codex_protocol::openai_models::ModelPreset;
 use codex_protocol::openai_models::ReasoningEffortPreset;
 use serde::Deserialize;
@@ -13,6 +16,9 @@ use utoipa::ToSchema;
 use crate::error::ApiError;
 use crate::state::WebServerState;
 
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 100;
+
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct ListModelsParams {
     #[serde(default)]
@@ -41,13 +47,14 @@ pub struct ListModelsResponse {
     get,
     path = "/api/v2/models",
     params(
-        ("limit" = Option<usize>, Query, description = "Maximum number of models to return (default: 50)"),
+        ("limit" = Option<usize>, Query, description = "Maximum number of models to return, 1 to 100 (default: 50)"),
         ("offset" = Option<usize>, Query, description = "Number of models to skip (default: 0)"),
-        ("capability" = Option<String>, Query, description = "Filter by capability (e.g., 'vision')"),
-        ("provider" = Option<String>, Query, description = "Filter by provider (e.g., 'anthropic', 'openai')")
+        ("capability" = Option<String>, Query, description = "Filter by capability: 'vision', 'reasoning' or 'personality'"),
+        ("provider" = Option<String>, Query, description = "Filter by model provider id (e.g., 'openai')")
     ),
     responses(
         (status = 200, description = "Models list retrieved successfully", body = ListModelsResponse),
+        (status = 400, description = "Invalid query parameter"),
         (status = 401, description = "Unauthorized"),
         (status = 500, description = "Internal server error")
     ),
@@ -58,49 +65,67 @@ pub struct ListModelsResponse {
 )]
 pub async fn list_models(
     State(state): State<WebServerState>,
+    params: Result<Query<ListModelsParams>, QueryRejection>,
 ) -> Result<Json<ListModelsResponse>, ApiError> {
-    // TODO: Enable axum "query" feature for query parameters
-    let params = ListModelsParams {
-        limit: None,
-        offset: None,
-        capability: None,
-        provider: None,
-    };
-
-    // List all models
-    let all_models = state
-        .thread_manager
-        .list_models(RefreshStrategy::OnlineIfUncached)
-        .await
-        .into_iter()
-        .filter(|preset| preset.show_in_picker)
-        .map(model_from_preset)
-        .collect::<Vec<Model>>();
-
-    // Apply filters
-    let mut filtered_models = all_models;
-
-    if let Some(capability) = &params.capability {
-        // TODO: Implement capability filtering when ModelPreset includes capability field
-        tracing::warn!("Capability filtering not yet implemented: {}", capability);
+    let Query(params) = params.map_err(|rejection| {
+        ApiError::InvalidRequest(format!("Invalid query: {}", rejection.body_text()))
+    })?;
+    if params.limit == Some(0) {
+        return Err(ApiError::InvalidRequest(
+            "limit must be at least 1".to_string(),
+        ));
     }
+    let capability = params
+        .capability
+        .as_deref()
+        .map(|capability| {
+            ModelCapability::parse(capability).ok_or_else(|| {
+                ApiError::InvalidRequest(format!(
+                    "Unknown capability {capability:?}; expected one of {}",
+                    ModelCapability::NAMES.join(", ")
+                ))
+            })
+        })
+        .transpose()?;
 
-    if let Some(provider) = &params.provider {
-        filtered_models.retain(|model| model.id.to_lowercase().contains(&provider.to_lowercase()));
+    // Every listed model comes from the configured provider, so a provider
+    // filter either keeps the whole list or empties it.
+    let provider_matches = match &params.provider {
+        Some(provider) => state
+            .config_loader
+            .load()
+            .await?
+            .model_provider_id
+            .eq_ignore_ascii_case(provider),
+        None => true,
+    };
+
+    let mut filtered_models = if provider_matches {
+        state
+            .thread_manager
+            .list_models(RefreshStrategy::OnlineIfUncached)
+            .await
+            .into_iter()
+            .filter(|preset| preset.show_in_picker)
+            .map(model_from_preset)
+            .collect::<Vec<Model>>()
+    } else {
+        Vec::new()
+    };
+    if let Some(capability) = capability {
+        filtered_models.retain(|model| capability.supported_by(model));
     }
 
     let total = filtered_models.len();
 
     // Apply pagination
-    let limit = params.limit.unwrap_or(50).min(100); // Max 100 per page
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
     let offset = params.offset.unwrap_or(0);
-
-    let end = (offset + limit).min(total);
-    let data = if offset < total {
-        filtered_models[offset..end].to_vec()
-    } else {
-        Vec::new()
-    };
+    let data = filtered_models
+        .into_iter()
+        .skip(offset)
+        .take(limit)
+        .collect();
 
     Ok(Json(ListModelsResponse {
         data,
@@ -110,6 +135,37 @@ pub async fn list_models(
     }))
 }
 
+/// Values accepted by the `capability` filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModelCapability {
+    /// Accepts image input
+    Vision,
+    /// Offers a choice of reasoning effort
+    Reasoning,
+    Personality,
+}
+
+impl ModelCapability {
+    const NAMES: [&str; 3] = ["vision", "reasoning", "personality"];
+
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "vision" | "image" => Some(Self::Vision),
+            "reasoning" => Some(Self::Reasoning),
+            "personality" => Some(Self::Personality),
+            _ => None,
+        }
+    }
+
+    fn supported_by(self, model: &Model) -> bool {
+        match self {
+            Self::Vision => model.input_modalities.contains(&InputModality::Image),
+            Self::Reasoning => !model.supported_reasoning_efforts.is_empty(),
+            Self::Personality => model.supports_personality,
+        }
+    }
+}
+
 fn model_from_preset(preset: ModelPreset) -> Model {
     let ModelPreset {
         id,
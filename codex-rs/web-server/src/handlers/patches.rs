@@ -0,0 +1,106 @@
+use axum::Json;
+use axum::extract::Path;
+use axum::extract::State;
+use codex_protocol::protocol::Op;
+use serde::Deserialize;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::error::ApiError;
+use crate::json_body::JsonBody;
+use crate::patches;
+use crate::patches::PatchFileResult;
+use crate::patches::PatchFileStatus;
+use crate::state::WebServerState;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ApplyPatchRequest {
+    /// A unified diff, or a patch in the `apply_patch` envelope format
+    /// (`*** Begin Patch` ... `*** End Patch`). Paths are relative to the
+    /// thread's cwd.
+    #[schema(example = "--- a/README.md\n+++ b/README.md\n@@ -1 +1 @@\n-Hello\n+Hello, world\n")]
+    pub patch: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ApplyPatchResponse {
+    /// `item.id` of the `fileChange` item that reports the outcome
+    pub item_id: String,
+    /// Every file section verified against the workspace
+    pub files: Vec<PatchFileResult>,
+}
+
+/// POST /api/v2/threads/{thread_id}/patches
+///
+/// Applies a client-supplied patch in the thread's workspace. The patch goes
+/// through the same safety checks, approval policy and sandbox as patches
+/// from the agent, and its progress streams as a `fileChange` item. A patch
+/// sent while a turn is running is applied within that turn; otherwise it
+/// runs as a turn of its own.
+#[utoipa::path(
+    post,
+    path = "/api/v2/threads/{thread_id}/patches",
+    request_body = ApplyPatchRequest,
+    params(
+        ("thread_id" = String, Path, description = "Thread ID")
+    ),
+    responses(
+        (status = 200, description = "Patch verified and submitted", body = ApplyPatchResponse),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Thread not found"),
+        (status = 409, description = "Patch does not apply to the workspace; `files` has per-file results"),
+        (status = 422, description = "Patch is malformed; `hunk_errors` says where"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Patches"
+)]
+pub async fn apply_patch(
+    State(state): State<WebServerState>,
+    Path(thread_id): Path<String>,
+    JsonBody(req): JsonBody<ApplyPatchRequest>,
+) -> Result<Json<ApplyPatchResponse>, ApiError> {
+    let thread_id = codex_protocol::ThreadId::from_string(&thread_id)
+        .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
+
+    let thread = state
+        .thread_manager
+        .get_thread(thread_id)
+        .await
+        .map_err(|_| ApiError::ThreadNotFound)?;
+    let snapshot = thread.config_snapshot().await;
+
+    let parsed = patches::parse(&req.patch)
+        .map_err(|hunk_errors| ApiError::MalformedPatch { hunk_errors })?;
+    let cwd = snapshot.cwd;
+    let (parsed, verified) = tokio::task::spawn_blocking(move || {
+        let verified = patches::verify(&parsed.hunks, &cwd);
+        (parsed, verified)
+    })
+    .await
+    .map_err(|err| ApiError::InternalError(format!("Failed to verify patch: {err}")))?;
+    let files = verified.map_err(|hunk_errors| ApiError::MalformedPatch { hunk_errors })?;
+    if files
+        .iter()
+        .any(|file| file.status == PatchFileStatus::Conflict)
+    {
+        return Err(ApiError::PatchConflict { files });
+    }
+
+    let call_id = uuid::Uuid::new_v4().to_string();
+    thread
+        .submit(Op::ApplyUserPatch {
+            call_id: call_id.clone(),
+            patch: parsed.envelope,
+        })
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to submit patch: {e}")))?;
+
+    Ok(Json(ApplyPatchResponse {
+        item_id: call_id,
+        files,
+    }))
+}
@@ -1,5 +1,6 @@
 use axum::Json;
 use axum::extract::Path;
+use axum::extract::Query;
 use axum::extract::State;
 use axum::http::StatusCode;
 use codex_protocol::protocol::Op;
@@ -11,6 +12,12 @@ use std::result::Result;
 use utoipa::ToSchema;
 
 use crate::error::ApiError;
+use crate::handlers::threads;
+use crate::handlers::threads::start_configured_thread;
+use crate::json_body::JsonBody;
+use crate::review_registry::CancelReviewError;
+use crate::review_registry::ReviewDeliveryMode;
+use crate::review_registry::ReviewSummary;
 use crate::state::WebServerState;
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -71,7 +78,7 @@ pub struct StartReviewResponse {
 pub async fn start_inline_review(
     State(state): State<WebServerState>,
     Path(thread_id): Path<String>,
-    Json(req): Json<StartReviewRequest>,
+    JsonBody(req): JsonBody<StartReviewRequest>,
 ) -> Result<(StatusCode, Json<StartReviewResponse>), ApiError> {
     let thread_id = codex_protocol::ThreadId::from_string(&thread_id)
         .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
@@ -92,6 +99,9 @@ pub async fn start_inline_review(
         .submit(Op::Review { review_request })
         .await
         .map_err(|e| ApiError::InternalError(format!("Failed to start review: {e}")))?;
+    state
+        .reviews
+        .register(&turn_id, thread_id, ReviewDeliveryMode::Inline);
 
     // Review will stream via SSE
     Ok((
@@ -123,19 +133,13 @@ pub async fn start_inline_review(
 )]
 pub async fn start_detached_review(
     State(state): State<WebServerState>,
-    Json(req): Json<StartReviewRequest>,
+    JsonBody(req): JsonBody<StartReviewRequest>,
 ) -> Result<(StatusCode, Json<StartReviewResponse>), ApiError> {
     // Load config
-    let config = codex_core::config::Config::load_with_cli_overrides(vec![])
-        .await
-        .map_err(|e| ApiError::InternalError(format!("Failed to load config: {e}")))?;
+    let config = state.config_loader.load().await?;
 
     // Start new thread for detached review
-    let new_thread = state
-        .thread_manager
-        .start_thread(config)
-        .await
-        .map_err(|e| ApiError::InternalError(format!("Failed to start review thread: {e}")))?;
+    let new_thread = start_configured_thread(&state, config).await?;
 
     let thread_id = new_thread.thread_id;
 
@@ -158,6 +162,9 @@ pub async fn start_detached_review(
         .map_err(|e| {
             ApiError::InternalError(format!("Failed to start detached review turn: {e}"))
         })?;
+    state
+        .reviews
+        .register(&turn_id, thread_id, ReviewDeliveryMode::Detached);
 
     // Review will stream via SSE
     Ok((
@@ -169,6 +176,118 @@ pub async fn start_detached_review(
     ))
 }
 
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ListReviewsResponse {
+    pub data: Vec<ReviewSummary>,
+}
+
+/// GET /api/v2/reviews
+///
+/// Lists the reviews started since the server started, newest first
+#[utoipa::path(
+    get,
+    path = "/api/v2/reviews",
+    responses(
+        (status = 200, description = "Reviews", body = ListReviewsResponse),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Review"
+)]
+pub async fn list_reviews(State(state): State<WebServerState>) -> Json<ListReviewsResponse> {
+    Json(ListReviewsResponse {
+        data: state.reviews.list(),
+    })
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct CancelReviewParams {
+    /// Also delete the thread of a detached review
+    #[serde(default)]
+    pub delete_thread: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CancelReviewResponse {
+    pub review: ReviewSummary,
+    /// The review's thread was deleted (`delete_thread=true`)
+    pub thread_deleted: bool,
+}
+
+/// DELETE /api/v2/reviews/:review_id
+///
+/// Cancels a running review by interrupting its turn. With
+/// `delete_thread=true`, a detached review's thread is deleted as well
+#[utoipa::path(
+    delete,
+    path = "/api/v2/reviews/{review_id}",
+    params(
+        ("review_id" = String, Path, description = "Review ID"),
+        ("delete_thread" = Option<bool>, Query, description = "Delete the thread of a detached review")
+    ),
+    responses(
+        (status = 200, description = "Review cancelled", body = CancelReviewResponse),
+        (status = 400, description = "`delete_thread` on an inline review"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Review not found"),
+        (status = 409, description = "The review already completed or was cancelled (`review_finished`)"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Review"
+)]
+pub async fn cancel_review(
+    State(state): State<WebServerState>,
+    Path(review_id): Path<String>,
+    Query(params): Query<CancelReviewParams>,
+) -> Result<Json<CancelReviewResponse>, ApiError> {
+    let not_found = || ApiError::NotFound(format!("Review not found: {review_id}"));
+    let review = state.reviews.get(&review_id).ok_or_else(not_found)?;
+    // An inline review shares its thread with the conversation around it.
+    if params.delete_thread && review.delivery == ReviewDeliveryMode::Inline {
+        return Err(ApiError::InvalidRequest(
+            "delete_thread only applies to detached reviews".to_string(),
+        ));
+    }
+    let review = state.reviews.cancel(&review_id).map_err(|err| match err {
+        CancelReviewError::NotFound => not_found(),
+        CancelReviewError::Finished(status) => ApiError::ReviewFinished(status),
+    })?;
+
+    let thread_id = codex_protocol::ThreadId::from_string(&review.thread_id)
+        .map_err(|e| ApiError::InternalError(format!("Invalid review thread ID: {e}")))?;
+    // A thread that is no longer loaded has no turn left to stop.
+    if let Ok(thread) = state.thread_manager.get_thread(thread_id).await {
+        thread
+            .submit(Op::Interrupt)
+            .await
+            .map_err(|e| ApiError::InternalError(format!("Failed to interrupt review: {e}")))?;
+    }
+
+    if params.delete_thread {
+        match threads::delete_thread(
+            State(state.clone()),
+            Path(review.thread_id.clone()),
+            Query(threads::DeleteThreadParams { force: true }),
+        )
+        .await
+        {
+            // Nothing left to delete.
+            Ok(_) | Err(ApiError::ThreadNotFound) => {}
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(Json(CancelReviewResponse {
+        review,
+        thread_deleted: params.delete_thread,
+    }))
+}
+
 // Helper function to convert API ReviewTarget to Core ReviewRequest
 fn build_review_request(target: ReviewTarget) -> Result<CoreReviewRequest, ApiError> {
     let core_target = match target {
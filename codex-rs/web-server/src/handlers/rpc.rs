@@ -0,0 +1,335 @@
+//! JSON-RPC 2.0 bridge for clients written against the app-server protocol.
+//!
+//! `POST /api/v2/rpc` takes one request and runs it through the same handler
+//! as the matching REST endpoint, so results are the REST response bodies.
+//! Params use the app-server's camelCase names. Notifications are not sent
+//! on this connection; clients read them from the thread's event stream,
+//! whose events already carry `method` and `params`.
+
+use axum::Json;
+use axum::body::Bytes;
+use axum::extract::Path;
+use axum::extract::Query;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::response::Response;
+use codex_app_server_protocol::RequestId;
+use codex_protocol::config_types::SandboxMode;
+use codex_protocol::openai_models::ReasoningEffort;
+use codex_protocol::protocol::AskForApproval;
+use serde::Deserialize;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use serde_json::json;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use crate::attachments::AttachmentScope;
+use crate::error::ApiError;
+use crate::handlers::approvals;
+use crate::handlers::threads;
+use crate::handlers::turns;
+use crate::json_body::JsonBody;
+use crate::state::ApprovalDecision;
+use crate::state::WebServerState;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+
+/// Methods the bridge understands.
+pub const RPC_METHODS: [&str; 5] = [
+    "thread/start",
+    "thread/list",
+    "turn/start",
+    "turn/interrupt",
+    "approval/respond",
+];
+
+/// A JSON-RPC request. `jsonrpc` is optional, as on the app-server's stdio
+/// transport; a request without `id` is a notification and gets no body.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RpcRequest {
+    #[serde(default)]
+    #[schema(value_type = Option<String>, example = "2.0")]
+    pub jsonrpc: Option<String>,
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub id: Option<RequestId>,
+    #[schema(example = "thread/start")]
+    pub method: String,
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub params: Option<Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+impl From<ApiError> for RpcError {
+    /// Keeps the REST error body, including any `code`, as `data`.
+    fn from(err: ApiError) -> Self {
+        let (status, body) = err.into_parts();
+        let code = match status {
+            StatusCode::BAD_REQUEST => INVALID_PARAMS,
+            status if status.is_client_error() => INVALID_REQUEST,
+            _ => INTERNAL_ERROR,
+        };
+        Self {
+            code,
+            message: body["error"].as_str().unwrap_or_default().to_string(),
+            data: Some(body),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ThreadStartParams {
+    cwd: Option<String>,
+    model: Option<String>,
+    #[serde(default)]
+    skip_validation: bool,
+    #[serde(default)]
+    approval_policy: Option<AskForApproval>,
+    #[serde(default)]
+    sandbox: Option<SandboxMode>,
+    #[serde(default)]
+    base_instructions: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ThreadListParams {
+    #[serde(default)]
+    sort: Option<threads::ThreadSort>,
+    #[serde(default)]
+    pinned_first: bool,
+    #[serde(default)]
+    limit: Option<usize>,
+    #[serde(default)]
+    cursor: Option<String>,
+    #[serde(default)]
+    include_archived: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TurnStartParams {
+    thread_id: String,
+    input: Vec<turns::UserInputItem>,
+    #[serde(default)]
+    queue: bool,
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    effort: Option<ReasoningEffort>,
+    #[serde(default)]
+    cwd: Option<String>,
+    #[serde(default)]
+    skip_validation: bool,
+    #[serde(default)]
+    output_schema: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TurnInterruptParams {
+    thread_id: String,
+    #[serde(default)]
+    turn_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApprovalRespondParams {
+    thread_id: String,
+    approval_id: String,
+    decision: ApprovalDecision,
+    #[serde(default)]
+    accept_execpolicy_amendment: bool,
+}
+
+/// POST /api/v2/rpc
+///
+/// Runs one app-server style JSON-RPC request against the REST handlers
+#[utoipa::path(
+    post,
+    path = "/api/v2/rpc",
+    request_body = RpcRequest,
+    responses(
+        (status = 200, description = "JSON-RPC response with `result` or `error`"),
+        (status = 204, description = "The request was a notification (no `id`)"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "RPC"
+)]
+pub async fn rpc(
+    State(state): State<WebServerState>,
+    scope: AttachmentScope,
+    body: Bytes,
+) -> Response {
+    let value: Value = match serde_json::from_slice(&body) {
+        Ok(value) => value,
+        Err(err) => {
+            return error_response(
+                None,
+                RpcError::new(PARSE_ERROR, format!("Parse error: {err}")),
+            );
+        }
+    };
+    let request: RpcRequest = match serde_json::from_value(value) {
+        Ok(request) => request,
+        Err(err) => {
+            return error_response(
+                None,
+                RpcError::new(INVALID_REQUEST, format!("Invalid request: {err}")),
+            );
+        }
+    };
+    if let Some(version) = &request.jsonrpc
+        && version != "2.0"
+    {
+        return error_response(
+            request.id,
+            RpcError::new(
+                INVALID_REQUEST,
+                format!("Unsupported jsonrpc version {version:?}"),
+            ),
+        );
+    }
+
+    let outcome = dispatch(state, &scope, &request.method, request.params).await;
+    let Some(id) = request.id else {
+        return StatusCode::NO_CONTENT.into_response();
+    };
+    match outcome {
+        Ok(result) => Json(json!({ "jsonrpc": "2.0", "id": id, "result": result })).into_response(),
+        Err(err) => error_response(Some(id), err),
+    }
+}
+
+fn error_response(id: Option<RequestId>, error: RpcError) -> Response {
+    Json(json!({ "jsonrpc": "2.0", "id": id, "error": error })).into_response()
+}
+
+async fn dispatch(
+    state: WebServerState,
+    scope: &AttachmentScope,
+    method: &str,
+    params: Option<Value>,
+) -> Result<Value, RpcError> {
+    match method {
+        "thread/start" => {
+            let params: ThreadStartParams = parse_params(params)?;
+            let request = threads::CreateThreadRequest {
+                cwd: params.cwd,
+                model: params.model,
+                skip_validation: params.skip_validation,
+                approval_policy: params.approval_policy,
+                sandbox_mode: params.sandbox,
+                model_reasoning_effort: None,
+                base_instructions: params.base_instructions,
+                min_config_version: None,
+                provider_overrides: HashMap::new(),
+            };
+            to_result(threads::create_thread_inner(&state, request).await?)
+        }
+        "thread/list" => {
+            let params: ThreadListParams = parse_params(params)?;
+            let query = threads::ListThreadsParams {
+                sort: params.sort,
+                pinned_first: params.pinned_first,
+                limit: params.limit,
+                cursor: params.cursor,
+                include_archived: params.include_archived,
+            };
+            let Json(response) = threads::list_threads(State(state), Query(query)).await?;
+            to_result(response)
+        }
+        "turn/start" => {
+            let params: TurnStartParams = parse_params(params)?;
+            let request = turns::SendTurnRequest {
+                input: params.input,
+                model: params.model,
+                effort: params.effort,
+                cwd: params.cwd,
+                skip_validation: params.skip_validation,
+                output_schema: params.output_schema,
+            };
+            let when_busy = if params.queue {
+                turns::WhenBusy::Queue
+            } else {
+                turns::WhenBusy::Reject
+            };
+            let response =
+                turns::send_turn_inner(&state, scope, &params.thread_id, when_busy, request)
+                    .await?;
+            to_result(response)
+        }
+        "turn/interrupt" => {
+            let params: TurnInterruptParams = parse_params(params)?;
+            let Json(response) = turns::interrupt_turn(
+                State(state),
+                Path(params.thread_id),
+                JsonBody(turns::InterruptTurnRequest {
+                    turn_id: params.turn_id,
+                }),
+            )
+            .await?;
+            to_result(response)
+        }
+        "approval/respond" => {
+            let params: ApprovalRespondParams = parse_params(params)?;
+            let Json(response) = approvals::respond_to_approval(
+                State(state),
+                Path((params.thread_id, params.approval_id)),
+                JsonBody(approvals::ApprovalRequest {
+                    decision: params.decision,
+                    accept_execpolicy_amendment: params.accept_execpolicy_amendment,
+                }),
+            )
+            .await?;
+            to_result(response)
+        }
+        other => Err(RpcError {
+            code: METHOD_NOT_FOUND,
+            message: format!("Method not found: {other}"),
+            data: Some(json!({ "supported_methods": RPC_METHODS })),
+        }),
+    }
+}
+
+/// Missing params are read as `{}` so methods without required fields
+/// accept them.
+fn parse_params<T: DeserializeOwned>(params: Option<Value>) -> Result<T, RpcError> {
+    serde_json::from_value(params.unwrap_or_else(|| json!({})))
+        .map_err(|err| RpcError::new(INVALID_PARAMS, format!("Invalid params: {err}")))
+}
+
+fn to_result<T: Serialize>(response: T) -> Result<Value, RpcError> {
+    serde_json::to_value(response)
+        .map_err(|err| RpcError::new(INTERNAL_ERROR, format!("Failed to serialize result: {err}")))
+}
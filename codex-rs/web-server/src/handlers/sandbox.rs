@@ -0,0 +1,200 @@
+use axum::Json;
+use axum::extract::Path;
+use axum::extract::State;
+use codex_core::ExecApprovalPreview;
+use codex_core::get_platform_sandbox;
+use codex_core::preview_exec_approval;
+use codex_protocol::protocol::SandboxPolicy;
+use codex_protocol::protocol::WritableRoot;
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::Component;
+use std::path::PathBuf;
+use utoipa::ToSchema;
+
+use crate::error::ApiError;
+use crate::json_body::JsonBody;
+use crate::state::WebServerState;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SandboxPreviewRequest {
+    #[schema(example = json!(["cargo", "test"]))]
+    pub command: Vec<String>,
+    /// Defaults to the thread's working directory; relative paths are
+    /// resolved against it
+    pub cwd: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalPreview {
+    /// Runs without asking
+    NotRequired,
+    /// Would ask for approval under the thread's approval policy
+    Required,
+    /// Would be rejected without asking
+    Forbidden,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WritableRootPreview {
+    pub root: String,
+    /// Paths under `root` that stay read-only
+    pub read_only_subpaths: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SandboxPreviewResponse {
+    /// Whether the command would run inside the platform sandbox
+    pub sandboxed: bool,
+    /// `true` when the sandbox policy allows writing anywhere
+    pub full_disk_write_access: bool,
+    /// Where the command could write, unless `full_disk_write_access`
+    pub writable_roots: Vec<WritableRootPreview>,
+    /// Path arguments of the command that it could not write to
+    pub unwritable_paths: Vec<String>,
+    pub network_access: bool,
+    pub approval: ApprovalPreview,
+    /// Why approval is required or the command is forbidden, when known
+    pub reason: Option<String>,
+    pub cwd: String,
+}
+
+/// POST /api/v2/threads/{thread_id}/sandbox/preview
+///
+/// Assesses a command under the thread's current sandbox and approval
+/// policies the way core does before exec. Nothing is executed.
+#[utoipa::path(
+    post,
+    path = "/api/v2/threads/{thread_id}/sandbox/preview",
+    request_body = SandboxPreviewRequest,
+    params(
+        ("thread_id" = String, Path, description = "Thread ID")
+    ),
+    responses(
+        (status = 200, description = "Sandbox assessment for the command", body = SandboxPreviewResponse),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "cwd is outside the trusted roots"),
+        (status = 404, description = "Thread not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Sandbox"
+)]
+pub async fn preview_sandbox(
+    State(state): State<WebServerState>,
+    Path(thread_id): Path<String>,
+    JsonBody(req): JsonBody<SandboxPreviewRequest>,
+) -> Result<Json<SandboxPreviewResponse>, ApiError> {
+    if req.command.is_empty() {
+        return Err(ApiError::InvalidRequest(
+            "Command cannot be empty".to_string(),
+        ));
+    }
+
+    let thread_id = codex_protocol::ThreadId::from_string(&thread_id)
+        .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
+
+    let thread = state
+        .thread_manager
+        .get_thread(thread_id)
+        .await
+        .map_err(|_| ApiError::ThreadNotFound)?;
+    let snapshot = thread.config_snapshot().await;
+
+    let cwd = match req.cwd {
+        Some(cwd) => state
+            .trusted_roots
+            .check(&normalize(&snapshot.cwd.join(cwd)))?,
+        None => snapshot.cwd.clone(),
+    };
+
+    let config = state.config_loader.load().await?;
+    let approval = preview_exec_approval(
+        &config.config_layer_stack,
+        &req.command,
+        snapshot.approval_policy,
+        &snapshot.sandbox_policy,
+    )
+    .await
+    .map_err(|e| ApiError::InternalError(format!("Failed to load exec policy rules: {e}")))?;
+
+    let (approval, reason, bypass_sandbox) = match approval {
+        ExecApprovalPreview::Allowed { bypass_sandbox } => {
+            (ApprovalPreview::NotRequired, None, bypass_sandbox)
+        }
+        ExecApprovalPreview::NeedsApproval { reason } => (ApprovalPreview::Required, reason, false),
+        ExecApprovalPreview::Forbidden { reason } => {
+            (ApprovalPreview::Forbidden, Some(reason), false)
+        }
+    };
+
+    let policy = &snapshot.sandbox_policy;
+    let restricted = !matches!(
+        policy,
+        SandboxPolicy::DangerFullAccess | SandboxPolicy::ExternalSandbox { .. }
+    );
+    let full_disk_write_access = policy.has_full_disk_write_access();
+    let writable_roots = policy.get_writable_roots_with_cwd(&cwd);
+    let unwritable_paths = if full_disk_write_access {
+        Vec::new()
+    } else {
+        path_arguments(&req.command)
+            .map(|arg| (arg, normalize(&cwd.join(arg))))
+            .filter(|(_, path)| {
+                !writable_roots
+                    .iter()
+                    .any(|root| root.is_path_writable(path))
+            })
+            .map(|(arg, _)| arg.to_string())
+            .collect()
+    };
+
+    Ok(Json(SandboxPreviewResponse {
+        sandboxed: restricted && !bypass_sandbox && get_platform_sandbox(false).is_some(),
+        full_disk_write_access,
+        writable_roots: writable_roots.iter().map(writable_root_preview).collect(),
+        unwritable_paths,
+        network_access: policy.has_full_network_access(),
+        approval,
+        reason,
+        cwd: cwd.display().to_string(),
+    }))
+}
+
+fn writable_root_preview(root: &WritableRoot) -> WritableRootPreview {
+    WritableRootPreview {
+        root: root.root.display().to_string(),
+        read_only_subpaths: root
+            .read_only_subpaths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect(),
+    }
+}
+
+/// Arguments after the program that look like filesystem paths.
+fn path_arguments(command: &[String]) -> impl Iterator<Item = &str> {
+    command.iter().skip(1).map(String::as_str).filter(|arg| {
+        arg.starts_with('/') || arg.starts_with("./") || arg.starts_with("../") || *arg == ".."
+    })
+}
+
+/// Resolves `.` and `..` lexically, so `/work/../etc` is not treated as
+/// being under `/work`.
+fn normalize(path: &std::path::Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
@@ -1,5 +1,6 @@
 use axum::Json;
 use axum::extract::Path;
+use axum::extract::Query;
 use axum::extract::State;
 use codex_app_server_protocol::SkillDependencies;
 use codex_app_server_protocol::SkillErrorInfo;
@@ -14,6 +15,9 @@ use std::result::Result;
 use utoipa::ToSchema;
 
 use crate::error::ApiError;
+use crate::json_body::JsonBody;
+use crate::skills_listing::SkillsLoadStatus;
+use crate::skills_listing::collect_skills;
 use crate::state::WebServerState;
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -27,6 +31,9 @@ pub struct ListSkillsParams {
 #[derive(Debug, Serialize, ToSchema)]
 pub struct SkillsListEntry {
     pub cwd: String, // Changed from PathBuf
+    /// `timed_out` and `failed` entries list no skills and one error for the
+    /// cwd itself
+    pub status: SkillsLoadStatus,
     #[schema(value_type = Vec<Object>)]
     pub skills: Vec<SkillMetadata>,
     #[schema(value_type = Vec<Object>)]
@@ -50,17 +57,20 @@ pub struct UpdateSkillConfigResponse {
 
 /// GET /api/v2/skills
 ///
-/// Lists skills available in the workspace
+/// Lists skills available in the workspace. Each cwd is loaded concurrently
+/// with its own timeout, so one slow or failing cwd does not hide the others
 #[utoipa::path(
     get,
     path = "/api/v2/skills",
     params(
-        ("cwds" = Option<Vec<String>>, Query, description = "Working directories to search for skills (default: current config cwd)"),
+        ("cwds" = Option<Vec<String>>, Query, description = "Working directories to search for skills; repeat for several (default: current config cwd)"),
         ("force_reload" = Option<bool>, Query, description = "Force reload skills from disk (default: false)")
     ),
     responses(
         (status = 200, description = "Skills list retrieved successfully", body = ListSkillsResponse),
+        (status = 400, description = "Invalid query parameter"),
         (status = 401, description = "Unauthorized"),
+        (status = 403, description = "A cwd is outside the trusted roots"),
         (status = 500, description = "Internal server error")
     ),
     security(
@@ -70,43 +80,59 @@ pub struct UpdateSkillConfigResponse {
 )]
 pub async fn list_skills(
     State(state): State<WebServerState>,
+    Query(query): Query<Vec<(String, String)>>,
 ) -> Result<Json<ListSkillsResponse>, ApiError> {
-    // TODO: Enable axum "query" feature for query parameters
-    let params = ListSkillsParams {
-        cwds: Vec::new(),
-        force_reload: false,
-    };
+    let params = parse_list_params(query)?;
     // Get current config to determine default cwd
-    let config = codex_core::config::Config::load_with_cli_overrides(vec![])
-        .await
-        .map_err(|e| ApiError::InternalError(format!("Failed to load config: {e}")))?;
-
     let cwds = if params.cwds.is_empty() {
-        vec![config.cwd.clone()]
+        vec![state.config_loader.load().await?.cwd]
     } else {
-        params.cwds.into_iter().map(PathBuf::from).collect()
+        params
+            .cwds
+            .into_iter()
+            .map(|cwd| state.trusted_roots.check(&PathBuf::from(cwd)))
+            .collect::<Result<_, _>>()?
     };
 
-    let skills_manager = state.thread_manager.skills_manager();
-    let mut data = Vec::new();
-
-    for cwd in cwds {
-        let outcome = skills_manager
-            .skills_for_cwd(&cwd, params.force_reload)
-            .await;
-        let errors = errors_to_info(&outcome.errors);
-        let skills = skills_to_info(&outcome.skills, &outcome.disabled_paths);
-
-        data.push(SkillsListEntry {
-            cwd: cwd.display().to_string(),
-            skills,
-            errors,
-        });
-    }
+    let data = collect_skills(
+        &state.skills_source,
+        cwds,
+        params.force_reload,
+        state.skills_timeout,
+    )
+    .await
+    .into_iter()
+    .map(|collected| SkillsListEntry {
+        cwd: collected.cwd.display().to_string(),
+        status: collected.status,
+        skills: skills_to_info(&collected.outcome.skills, &collected.outcome.disabled_paths),
+        errors: errors_to_info(&collected.outcome.errors),
+    })
+    .collect();
 
     Ok(Json(ListSkillsResponse { data }))
 }
 
+/// Reads `cwds` (repeatable) and `force_reload` from the query string.
+fn parse_list_params(query: Vec<(String, String)>) -> Result<ListSkillsParams, ApiError> {
+    let mut params = ListSkillsParams {
+        cwds: Vec::new(),
+        force_reload: false,
+    };
+    for (key, value) in query {
+        match key.as_str() {
+            "cwds" => params.cwds.push(value),
+            "force_reload" => {
+                params.force_reload = value.parse().map_err(|_| {
+                    ApiError::InvalidRequest("force_reload must be true or false".to_string())
+                })?;
+            }
+            _ => {}
+        }
+    }
+    Ok(params)
+}
+
 /// PATCH /api/v2/skills/:name
 ///
 /// Updates skill configuration (enable/disable)
@@ -132,7 +158,7 @@ pub async fn list_skills(
 pub async fn update_skill_config(
     State(state): State<WebServerState>,
     Path(name): Path<String>,
-    Json(req): Json<UpdateSkillConfigRequest>,
+    JsonBody(req): JsonBody<UpdateSkillConfigRequest>,
 ) -> Result<Json<UpdateSkillConfigResponse>, ApiError> {
     let path = PathBuf::from(&name);
     let edits = vec![ConfigEdit::SetSkillConfig {
@@ -1,16 +1,92 @@
 use axum::Json;
+use axum::body::Body;
+use axum::extract::Multipart;
 use axum::extract::Path;
+use axum::extract::Query;
 use axum::extract::State;
+use axum::extract::multipart::MultipartRejection;
+use axum::extract::rejection::JsonRejection;
+use axum::http::HeaderValue;
+use axum::http::StatusCode;
+use axum::http::header::CONTENT_DISPOSITION;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::Response;
+use codex_app_server_protocol::ThreadItem;
+use codex_app_server_protocol::TokenUsageBreakdown;
+use codex_app_server_protocol::TurnError;
+use codex_app_server_protocol::TurnStatus;
+use codex_app_server_protocol::build_turns_from_rollout_items;
+use codex_core::CodexThread;
+use codex_core::NewThread;
+use codex_core::RolloutRecorder;
 use codex_core::config::Config;
 use codex_core::error::CodexErr;
 use codex_protocol::ThreadId;
+use codex_protocol::config_types::SandboxMode;
+use codex_protocol::openai_models::ReasoningEffort;
+use codex_protocol::protocol::AgentStatus;
+use codex_protocol::protocol::AskForApproval;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::Op;
+use codex_protocol::protocol::ReviewDecision;
+use codex_protocol::protocol::RolloutItem;
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
 use utoipa::ToSchema;
 
+use crate::approval_manager::ApprovalReply;
+use crate::attachments::AttachmentScope;
+use crate::attachments::UploadErrorCode;
+use crate::attachments::UploadErrorDetails;
+use crate::attachments::receive_file;
+use crate::attachments::upload_error;
+use crate::context_window;
+use crate::context_window::ContextWindowUsage;
 use crate::error::ApiError;
+use crate::error::ThreadStartErrorCode;
+use crate::instance::instance_id;
+use crate::json_body::JsonBody;
+use crate::model_validation::validate_model;
+use crate::provider_overrides::ProviderOverride;
+use crate::provider_overrides::apply_provider_overrides;
+use crate::requirements::apply_thread_overrides;
+use crate::requirements::check_thread_overrides;
+use crate::requirements::sandbox_mode_of;
+use crate::rollout_archive::archive_rollout;
+use crate::rollout_archive::restore_rollout;
+use crate::rollout_import::ImportError;
+use crate::rollout_import::import_rollout;
+use crate::rollout_tail::LastTurnStatus;
+use crate::rollout_tail::RolloutTail;
+use crate::rollout_tail::read_rollout_tail;
+use crate::state::ApprovalResponse;
 use crate::state::WebServerState;
+use crate::storage;
+use crate::thread_export;
+use crate::thread_export::DEFAULT_EXPORT_MAX_ITEMS;
+use crate::thread_export::ExportFormat;
+use crate::thread_export::ExportOptions;
+use crate::thread_export::export_stream;
+use crate::thread_progress::LongOperation;
+use crate::thread_progress::LongOperationStatus;
+use crate::thread_scan;
+use crate::thread_scan::ThreadScan;
+use crate::thread_status::StatusTransition;
+use crate::thread_status::ThreadRunStatus;
+use crate::turn_attachments;
+use crate::turn_attachments::HistoryAttachment;
+
+/// How long archive waits for a loaded thread to shut down.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateThreadRequest {
@@ -18,12 +94,84 @@ pub struct CreateThreadRequest {
     pub cwd: Option<String>,
     #[schema(example = "claude-sonnet-4-5")]
     pub model: Option<String>,
+    /// Accept a `model` missing from the model list, e.g. for a custom
+    /// provider. Length and character limits still apply.
+    #[serde(default)]
+    pub skip_validation: bool,
+    /// Must be listed in `allowed_approval_policies` when requirements are set
+    #[serde(default)]
+    #[schema(value_type = Option<String>, example = "on-request")]
+    pub approval_policy: Option<AskForApproval>,
+    /// Must be listed in `allowed_sandbox_modes` when requirements are set
+    #[serde(default)]
+    #[schema(value_type = Option<String>, example = "workspace-write")]
+    pub sandbox_mode: Option<SandboxMode>,
+    #[serde(default)]
+    #[schema(value_type = Option<String>, example = "high")]
+    pub model_reasoning_effort: Option<ReasoningEffort>,
+    /// Replaces the model's built-in instructions for this thread
+    #[serde(default)]
+    pub base_instructions: Option<String>,
+    /// `version` from a config write; the thread starts only once that write
+    /// is in effect
+    #[schema(example = "sha256:3f5a...")]
+    pub min_config_version: Option<String>,
+    /// Base URL and extra headers per provider id, for this thread only.
+    /// Base URL hosts must be listed in `CODEX_WEB_PROVIDER_HOSTS`.
+    #[serde(default)]
+    pub provider_overrides: HashMap<String, ProviderOverride>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
 pub struct CreateThreadResponse {
     pub thread_id: String,
     pub model: String,
+    /// Server instance that owns the thread (echo as `X-Codex-Instance`)
+    pub instance_id: String,
+    #[serde(flatten)]
+    pub settings: ThreadSettings,
+}
+
+/// Settings a new thread actually runs with, after config, requirements and
+/// request overrides.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ThreadSettings {
+    #[schema(value_type = String, example = "on-request")]
+    pub approval_policy: AskForApproval,
+    /// `None` when an external sandbox is configured
+    #[schema(value_type = Option<String>, example = "workspace-write")]
+    pub sandbox_mode: Option<SandboxMode>,
+    /// `None` when the model's default applies
+    #[schema(value_type = Option<String>, example = "high")]
+    pub model_reasoning_effort: Option<ReasoningEffort>,
+    /// Custom base instructions, `None` for the model's built-in ones
+    pub base_instructions: Option<String>,
+}
+
+impl ThreadSettings {
+    pub fn new(new_thread: &NewThread, config: &Config) -> Self {
+        let session = &new_thread.session_configured;
+        Self {
+            approval_policy: session.approval_policy,
+            sandbox_mode: sandbox_mode_of(&session.sandbox_policy),
+            model_reasoning_effort: session.reasoning_effort,
+            base_instructions: config.base_instructions.clone(),
+        }
+    }
+}
+
+/// Applies the request fields that no requirement constrains.
+fn apply_session_overrides(
+    config: &mut Config,
+    model_reasoning_effort: Option<ReasoningEffort>,
+    base_instructions: Option<String>,
+) {
+    if let Some(effort) = model_reasoning_effort {
+        config.model_reasoning_effort = Some(effort);
+    }
+    if let Some(base_instructions) = base_instructions {
+        config.base_instructions = Some(base_instructions);
+    }
 }
 
 #[utoipa::path(
@@ -32,9 +180,12 @@ pub struct CreateThreadResponse {
     request_body = CreateThreadRequest,
     responses(
         (status = 200, description = "Thread created successfully", body = CreateThreadResponse),
-        (status = 400, description = "Invalid request"),
-        (status = 401, description = "Unauthorized"),
-        (status = 500, description = "Internal server error")
+        (status = 400, description = "Invalid request or override value, unknown model (`model_not_found`) or unusable cwd (`cwd_unwritable`)"),
+        (status = 401, description = "Unauthorized, or provider credentials missing (`auth_required`)"),
+        (status = 403, description = "Override violates config requirements, cwd is outside the trusted roots, or a provider override host is not allowed"),
+        (status = 409, description = "Config did not reach `min_config_version` in time"),
+        (status = 500, description = "Internal server error"),
+        (status = 502, description = "Required MCP server failed to start (`mcp_startup_failed`)")
     ),
     security(
         ("bearer_auth" = [])
@@ -43,43 +194,200 @@ pub struct CreateThreadResponse {
 )]
 pub async fn create_thread(
     State(state): State<WebServerState>,
-    Json(req): Json<CreateThreadRequest>,
+    JsonBody(req): JsonBody<CreateThreadRequest>,
 ) -> Result<Json<CreateThreadResponse>, ApiError> {
-    let mut config = Config::load_with_cli_overrides(vec![])
-        .await
-        .map_err(|e| ApiError::InternalError(format!("Failed to load config: {e}")))?;
+    create_thread_inner(&state, req).await.map(Json)
+}
+
+/// Checks `req`, applies it to the loaded config and starts the thread.
+/// Shared by the v1 and v2 routes and JSON-RPC `thread/start`.
+pub async fn create_thread_inner(
+    state: &WebServerState,
+    req: CreateThreadRequest,
+) -> Result<CreateThreadResponse, ApiError> {
+    check_thread_overrides(state, req.approval_policy, req.sandbox_mode).await?;
+
+    let mut config = match &req.min_config_version {
+        Some(min_version) => state.config_loader.load_at_least(min_version).await?,
+        None => state.config_loader.load().await?,
+    };
 
     if let Some(cwd) = req.cwd {
         config.cwd = std::path::PathBuf::from(cwd);
     }
 
     if let Some(model) = req.model {
+        validate_model(state, &model, req.skip_validation).await?;
         config.model = Some(model);
     }
 
+    apply_thread_overrides(&mut config, req.approval_policy, req.sandbox_mode)?;
+    apply_session_overrides(
+        &mut config,
+        req.model_reasoning_effort,
+        req.base_instructions,
+    );
+    apply_provider_overrides(&mut config, &req.provider_overrides, &state.provider_hosts)?;
+
+    let new_thread = start_configured_thread(state, config.clone()).await?;
+
+    Ok(CreateThreadResponse {
+        thread_id: new_thread.thread_id.to_string(),
+        settings: ThreadSettings::new(&new_thread, &config),
+        model: config.model.unwrap_or_else(|| "default".to_string()),
+        instance_id: instance_id().to_string(),
+    })
+}
+
+/// Starts a thread, reporting missing credentials, a bad `cwd` and required
+/// MCP server failures as categorized errors instead of a generic 500.
+pub async fn start_configured_thread(
+    state: &WebServerState,
+    mut config: Config,
+) -> Result<NewThread, ApiError> {
+    config.cwd = check_cwd(state, &config.cwd)?;
+
+    config
+        .model_provider
+        .api_key()
+        .map_err(ApiError::from_thread_start_error)?;
+    if config.model_provider.requires_openai_auth && state.auth_manager.auth().await.is_none() {
+        return Err(ApiError::ThreadStartFailed {
+            code: ThreadStartErrorCode::AuthRequired,
+            message: format!(
+                "Model provider {} requires sign-in; use POST /api/v2/auth/login",
+                config.model_provider.name
+            ),
+            server: None,
+        });
+    }
+
     let new_thread = state
         .thread_manager
-        .start_thread(config.clone())
+        .start_thread(config)
         .await
-        .map_err(|e| ApiError::InternalError(format!("Failed to start thread: {e}")))?;
+        .map_err(ApiError::from_thread_start_error)?;
+    state.thread_announcements.announce(new_thread.thread_id);
+    Ok(new_thread)
+}
 
-    Ok(Json(CreateThreadResponse {
-        thread_id: new_thread.thread_id.to_string(),
-        model: config.model.unwrap_or_else(|| "default".to_string()),
-    }))
+/// Resolves a thread's working directory within the trusted roots and checks
+/// that it is a writable directory.
+fn check_cwd(state: &WebServerState, cwd: &std::path::Path) -> Result<PathBuf, ApiError> {
+    let (cwd, problem) = if cwd.is_absolute() {
+        let cwd = state.trusted_roots.check(cwd)?;
+        let problem = match std::fs::metadata(&cwd) {
+            Ok(metadata) if !metadata.is_dir() => Some("is not a directory".to_string()),
+            Ok(metadata) if metadata.permissions().readonly() => Some("is read-only".to_string()),
+            Ok(_) => None,
+            Err(err) => Some(format!("cannot be accessed: {err}")),
+        };
+        (cwd, problem)
+    } else {
+        (
+            cwd.to_path_buf(),
+            Some("is not an absolute path".to_string()),
+        )
+    };
+    match problem {
+        Some(problem) => Err(ApiError::ThreadStartFailed {
+            code: ThreadStartErrorCode::CwdUnwritable,
+            message: format!("Working directory {} {problem}", cwd.display()),
+            server: None,
+        }),
+        None => Ok(cwd),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ThreadSort {
+    /// Most recently updated first
+    #[default]
+    Updated,
+    /// Most recently created first
+    Created,
+    /// Alphabetical by thread name, unnamed threads last
+    Name,
+}
+
+const DEFAULT_THREAD_LIMIT: usize = 100;
+const MAX_THREAD_LIMIT: usize = 500;
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct ListThreadsParams {
+    #[serde(default)]
+    pub sort: Option<ThreadSort>,
+    #[serde(default)]
+    pub pinned_first: bool,
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// `next_cursor` from the previous page
+    #[serde(default)]
+    pub cursor: Option<String>,
+    /// Also list threads archived with `POST /threads/{id}/archive`
+    #[serde(default)]
+    pub include_archived: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreadListStatus {
+    /// Loaded in memory
+    Active,
+    /// Dropped from memory by the idle policy; resume to load it again
+    AutoArchived,
+    /// Only in a rollout, e.g. from before a restart; resume to load it
+    Persisted,
+    /// Archived explicitly; listed only with `include_archived`
+    Archived,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ThreadSummary {
+    pub thread_id: String,
+    pub name: Option<String>,
+    /// Model the thread runs with; for a thread that is not loaded, the
+    /// model of its latest turn once a thread scan has read it
+    pub model: Option<String>,
+    pub status: ThreadListStatus,
+    /// Loaded in the thread manager
+    pub active: bool,
+    /// Live run status, as last sent in `thread/status/changed`
+    pub run_status: ThreadRunStatus,
+    pub pinned: bool,
+    /// `false` when the thread opted out of notifications
+    pub notifications: bool,
+    /// Unix seconds
+    pub created_at: Option<i64>,
+    /// Unix seconds
+    pub updated_at: Option<i64>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
 pub struct ListThreadsResponse {
+    /// Thread ids in the requested order
     pub thread_ids: Vec<String>,
+    pub data: Vec<ThreadSummary>,
+    /// Pass as `cursor` to get the next page; `None` on the last page
+    pub next_cursor: Option<String>,
 }
 
 #[utoipa::path(
     get,
     path = "/api/v2/threads",
+    params(
+        ("sort" = Option<String>, Query, description = "Sort order: updated (default), created, or name"),
+        ("pinned_first" = Option<bool>, Query, description = "List pinned threads before the rest (default: false)"),
+        ("limit" = Option<usize>, Query, description = "Maximum number of threads to return, 1 to 500 (default: 100)"),
+        ("cursor" = Option<String>, Query, description = "`next_cursor` from the previous page"),
+        ("include_archived" = Option<bool>, Query, description = "Also list explicitly archived threads (default: false)")
+    ),
     responses(
-        (status = 200, description = "List of active threads", body = ListThreadsResponse),
-        (status = 401, description = "Unauthorized")
+        (status = 200, description = "Loaded and persisted threads", body = ListThreadsResponse),
+        (status = 400, description = "Invalid query parameters"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
     ),
     security(
         ("bearer_auth" = [])
@@ -88,68 +396,279 @@ pub struct ListThreadsResponse {
 )]
 pub async fn list_threads(
     State(state): State<WebServerState>,
+    Query(params): Query<ListThreadsParams>,
 ) -> Result<Json<ListThreadsResponse>, ApiError> {
-    let thread_ids = state
-        .thread_manager
-        .list_thread_ids()
+    if params.limit == Some(0) {
+        return Err(ApiError::InvalidRequest(
+            "limit must be at least 1".to_string(),
+        ));
+    }
+
+    // Loaded threads first, then auto-archived ones with their last rollout
+    // path, then threads known only from storage. Each id is listed once.
+    let mut listed: Vec<(ThreadId, ThreadListStatus, Option<PathBuf>)> = Vec::new();
+    // Stored threads have a model once a scan has read it.
+    let mut models = state.thread_scan.models();
+    for thread_id in state.thread_manager.list_thread_ids().await {
+        let rollout_path = match state.thread_manager.get_thread(thread_id).await {
+            Ok(thread) => {
+                models.insert(thread_id.to_string(), thread.config_snapshot().await.model);
+                thread.rollout_path()
+            }
+            Err(_) => None,
+        };
+        listed.push((thread_id, ThreadListStatus::Active, rollout_path));
+    }
+    for (thread_id, archived) in state.auto_archived.lock().await.iter() {
+        if !listed.iter().any(|(id, _, _)| id == thread_id) {
+            listed.push((
+                *thread_id,
+                ThreadListStatus::AutoArchived,
+                archived.rollout_path.clone(),
+            ));
+        }
+    }
+    let mut seen: HashSet<ThreadId> = listed.iter().map(|(thread_id, _, _)| *thread_id).collect();
+    let mut stored_activity = None;
+    match &state.state_store {
+        Some(store) => {
+            let persisted = store
+                .persisted_threads()
+                .await
+                .map_err(|e| ApiError::InternalError(format!("Failed to list threads: {e}")))?;
+            for thread_id in persisted.keys() {
+                // Postgres keeps no archive flag; this server's archives are
+                // known from their status.
+                let archived =
+                    state.thread_statuses.status(*thread_id) == ThreadRunStatus::Archived;
+                if (params.include_archived || !archived) && seen.insert(*thread_id) {
+                    let status = if archived {
+                        ThreadListStatus::Archived
+                    } else {
+                        ThreadListStatus::Persisted
+                    };
+                    listed.push((*thread_id, status, None));
+                }
+            }
+            stored_activity = Some(persisted);
+        }
+        None => {
+            let codex_home = state.codex_home.clone();
+            let include_archived = params.include_archived;
+            let rollouts = tokio::task::spawn_blocking(move || {
+                storage::scan_rollouts(&codex_home, include_archived)
+            })
+            .await
+            .map_err(|e| ApiError::InternalError(format!("Failed to list rollouts: {e}")))?;
+            for rollout in rollouts {
+                if let Some(thread_id) = rollout.thread_id
+                    && seen.insert(thread_id)
+                {
+                    let status = if rollout.archived {
+                        ThreadListStatus::Archived
+                    } else {
+                        ThreadListStatus::Persisted
+                    };
+                    listed.push((thread_id, status, Some(rollout.path)));
+                }
+            }
+        }
+    }
+    let thread_ids: Vec<ThreadId> = listed.iter().map(|(thread_id, _, _)| *thread_id).collect();
+
+    let pinned = state
+        .pinned_threads()
         .await
-        .into_iter()
-        .map(|id| id.to_string())
+        .map_err(|e| ApiError::InternalError(format!("Failed to load thread pins: {e}")))?;
+
+    let names = codex_core::find_thread_names_by_ids(
+        &state.codex_home,
+        &thread_ids.iter().copied().collect::<HashSet<_>>(),
+    )
+    .await
+    .unwrap_or_else(|err| {
+        tracing::warn!("Failed to read thread names: {err}");
+        HashMap::new()
+    });
+
+    // Timestamps come from the Postgres rollout table when configured, and from
+    // rollout file metadata otherwise.
+    let activity = match stored_activity {
+        Some(activity) => activity,
+        None => {
+            let mut activity = HashMap::new();
+            for (thread_id, _, rollout_path) in &listed {
+                let Some(rollout_path) = rollout_path else {
+                    continue;
+                };
+                let Ok(metadata) = tokio::fs::metadata(rollout_path).await else {
+                    continue;
+                };
+                let updated_at = metadata.modified().ok().and_then(unix_seconds);
+                let created_at = metadata
+                    .created()
+                    .ok()
+                    .and_then(unix_seconds)
+                    .or(updated_at);
+                if let (Some(created_at), Some(updated_at)) = (created_at, updated_at) {
+                    activity.insert(*thread_id, (created_at, updated_at));
+                }
+            }
+            activity
+        }
+    };
+
+    let mut data: Vec<ThreadSummary> = listed
+        .iter()
+        .map(|(thread_id, status, _)| {
+            let times = activity.get(thread_id);
+            ThreadSummary {
+                thread_id: thread_id.to_string(),
+                name: names.get(thread_id).cloned(),
+                model: models.get(&thread_id.to_string()).cloned(),
+                status: *status,
+                active: *status == ThreadListStatus::Active,
+                run_status: match status {
+                    ThreadListStatus::AutoArchived | ThreadListStatus::Archived => {
+                        ThreadRunStatus::Archived
+                    }
+                    ThreadListStatus::Active | ThreadListStatus::Persisted => {
+                        state.thread_statuses.status(*thread_id)
+                    }
+                },
+                pinned: pinned.contains(thread_id),
+                notifications: !state.notifier.is_muted(*thread_id),
+                created_at: times.map(|(created_at, _)| *created_at),
+                updated_at: times.map(|(_, updated_at)| *updated_at),
+            }
+        })
         .collect();
 
-    Ok(Json(ListThreadsResponse { thread_ids }))
-}
+    match params.sort.unwrap_or_default() {
+        ThreadSort::Updated => data.sort_by(|a, b| {
+            b.updated_at
+                .cmp(&a.updated_at)
+                .then_with(|| a.thread_id.cmp(&b.thread_id))
+        }),
+        ThreadSort::Created => data.sort_by(|a, b| {
+            b.created_at
+                .cmp(&a.created_at)
+                .then_with(|| a.thread_id.cmp(&b.thread_id))
+        }),
+        ThreadSort::Name => data.sort_by(|a, b| {
+            let by_name = match (&a.name, &b.name) {
+                (Some(a), Some(b)) => a.to_lowercase().cmp(&b.to_lowercase()),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            };
+            by_name.then_with(|| a.thread_id.cmp(&b.thread_id))
+        }),
+    }
+    if params.pinned_first {
+        // Stable sort keeps the requested order within each group.
+        data.sort_by_key(|summary| !summary.pinned);
+    }
 
-#[derive(Debug, Serialize, ToSchema)]
-pub struct ArchiveThreadResponse {
-    pub success: bool,
+    // The cursor is the last id of the previous page, so threads created
+    // since then do not shift the next page.
+    let start = match &params.cursor {
+        Some(cursor) => data
+            .iter()
+            .position(|summary| summary.thread_id == *cursor)
+            .map(|index| index + 1)
+            .ok_or_else(|| {
+                ApiError::InvalidRequest("Unknown cursor; list again without one".to_string())
+            })?,
+        None => 0,
+    };
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_THREAD_LIMIT)
+        .min(MAX_THREAD_LIMIT);
+    let has_more = data.len() > start + limit;
+    let data: Vec<ThreadSummary> = data.into_iter().skip(start).take(limit).collect();
+    let next_cursor = has_more
+        .then(|| data.last().map(|summary| summary.thread_id.clone()))
+        .flatten();
+
+    let thread_ids = data
+        .iter()
+        .map(|summary| summary.thread_id.clone())
+        .collect();
+
+    Ok(Json(ListThreadsResponse {
+        thread_ids,
+        data,
+        next_cursor,
+    }))
 }
 
+/// POST /api/v2/threads/scan
+///
+/// Reads the latest model of the most recently active stored threads, up to
+/// `CODEX_WEB_THREAD_SCAN_LIMIT`, and keeps it for the thread listing
 #[utoipa::path(
     post,
-    path = "/api/v2/threads/{thread_id}/archive",
-    params(
-        ("thread_id" = String, Path, description = "Thread ID to archive")
-    ),
+    path = "/api/v2/threads/scan",
     responses(
-        (status = 200, description = "Thread archived successfully", body = ArchiveThreadResponse),
-        (status = 404, description = "Thread not found"),
-        (status = 401, description = "Unauthorized")
+        (status = 200, description = "Scanned threads, most recently active first", body = ThreadScan),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
     ),
     security(
         ("bearer_auth" = [])
     ),
     tag = "Threads"
 )]
-pub async fn archive_thread(
-    State(_state): State<WebServerState>,
-    Path(thread_id): Path<String>,
-) -> Result<Json<ArchiveThreadResponse>, ApiError> {
-    let _thread_id = ThreadId::from_string(&thread_id)
-        .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
+pub async fn scan_threads(
+    State(state): State<WebServerState>,
+) -> Result<Json<ThreadScan>, ApiError> {
+    let scan = thread_scan::scan_threads(&state)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to scan threads: {e}")))?;
+    Ok(Json(ThreadScan::clone(&scan)))
+}
 
-    Ok(Json(ArchiveThreadResponse { success: true }))
+fn unix_seconds(time: SystemTime) -> Option<i64> {
+    let duration = time.duration_since(UNIX_EPOCH).ok()?;
+    i64::try_from(duration.as_secs()).ok()
 }
 
+/// One thread's configuration and usage.
 #[derive(Debug, Serialize, ToSchema)]
-pub struct ResumeThreadResponse {
-    pub success: bool,
+pub struct ThreadDetails {
     pub thread_id: String,
+    /// Model of the latest turn; `None` for an unloaded thread with no turns
+    pub model: Option<String>,
+    pub cwd: Option<String>,
+    /// Loaded in the thread manager; `false` for threads known only from
+    /// their rollout file
+    pub active: bool,
+    pub rollout_path: Option<String>,
+    /// Cumulative token usage, once the model has reported any
+    #[schema(value_type = Option<Object>)]
+    pub token_usage: Option<TokenUsageBreakdown>,
+    /// How full the context window is, once the model has reported usage;
+    /// `None` for a model without a known context window
+    pub context: Option<ContextWindowUsage>,
 }
 
-/// POST /api/v2/threads/:id/resume
+/// GET /api/v2/threads/:id
 ///
-/// Resumes an archived thread
+/// Returns a thread's model, cwd, rollout path, token usage and how full its
+/// context window is
 #[utoipa::path(
-    post,
-    path = "/api/v2/threads/{id}/resume",
+    get,
+    path = "/api/v2/threads/{id}",
     params(
-        ("id" = String, Path, description = "Thread ID to resume")
+        ("id" = String, Path, description = "Thread ID")
     ),
     responses(
-        (status = 200, description = "Thread resumed successfully", body = ResumeThreadResponse),
-        (status = 404, description = "Thread not found"),
+        (status = 200, description = "Thread details", body = ThreadDetails),
+        (status = 400, description = "Invalid thread ID"),
         (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Thread not found"),
         (status = 500, description = "Internal server error")
     ),
     security(
@@ -157,95 +676,305 @@ pub struct ResumeThreadResponse {
     ),
     tag = "Threads"
 )]
-pub async fn resume_thread(
+pub async fn get_thread(
     State(state): State<WebServerState>,
     Path(thread_id): Path<String>,
-) -> Result<Json<ResumeThreadResponse>, ApiError> {
+) -> Result<Json<ThreadDetails>, ApiError> {
     let thread_id = ThreadId::from_string(&thread_id)
         .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
 
-    // Check if thread is already active
-    if state.thread_manager.get_thread(thread_id).await.is_ok() {
-        // Thread is already active, return success (idempotent)
-        return Ok(Json(ResumeThreadResponse {
-            success: true,
+    if let Ok(thread) = state.thread_manager.get_thread(thread_id).await {
+        let snapshot = thread.config_snapshot().await;
+        return Ok(Json(ThreadDetails {
             thread_id: thread_id.to_string(),
+            model: Some(snapshot.model),
+            cwd: Some(snapshot.cwd.display().to_string()),
+            active: true,
+            rollout_path: thread.rollout_path().map(|path| path.display().to_string()),
+            token_usage: thread.total_token_usage().await.map(Into::into),
+            context: context_window::for_thread(&thread).await,
         }));
     }
 
-    // Load config (could support overrides in future)
-    let config = Config::load_with_cli_overrides(vec![])
-        .await
-        .map_err(|e| ApiError::InternalError(format!("Failed to load config: {e}")))?;
-
-    // Prefer Postgres-backed rollouts when configured.
-    let postgres_enabled = std::env::var("CODEX_ROLLOUT_POSTGRES_URL")
-        .ok()
-        .is_some_and(|value| !value.trim().is_empty());
-
-    let new_thread = if postgres_enabled {
-        state
-            .thread_manager
-            .resume_thread_from_postgres(config, thread_id, state.auth_manager.clone())
-            .await
-            .map_err(|err| match err {
-                CodexErr::Io(io) if io.kind() == ErrorKind::NotFound => {
-                    ApiError::NotFound(format!("Rollout history not found for thread: {thread_id}"))
-                }
-                CodexErr::ThreadNotFound(_) => {
-                    ApiError::NotFound(format!("Rollout history not found for thread: {thread_id}"))
-                }
-                other => ApiError::InternalError(format!("Failed to resume thread: {other}")),
-            })?
-    } else {
-        let Some(rollout_path) =
-            codex_core::find_thread_path_by_id_str(&state.codex_home, &thread_id.to_string())
+    // Not loaded: describe it from its rollout file, live or archived.
+    let thread_id_str = thread_id.to_string();
+    let rollout_path =
+        match codex_core::find_thread_path_by_id_str(&state.codex_home, &thread_id_str).await {
+            Ok(Some(path)) => Some(path),
+            _ => codex_core::find_archived_thread_path_by_id_str(&state.codex_home, &thread_id_str)
                 .await
-                .map_err(|e| ApiError::InternalError(format!("Failed to locate rollout: {e}")))?
-        else {
-            return Err(ApiError::NotFound(format!(
-                "Rollout file not found for thread: {thread_id}"
-            )));
+                .ok()
+                .flatten(),
         };
-        state
-            .thread_manager
-            .resume_thread_from_rollout(config, rollout_path, state.auth_manager.clone())
-            .await
-            .map_err(|e| ApiError::InternalError(format!("Failed to resume thread: {e}")))?
+    let Some(rollout_path) = rollout_path else {
+        return Err(ApiError::ThreadNotFound);
+    };
+    let history = RolloutRecorder::get_rollout_history(&rollout_path)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to read rollout: {e}")))?;
+    let items = history.get_rollout_items();
+    let turn_context = items.iter().rev().find_map(|item| match item {
+        RolloutItem::TurnContext(context) => Some(context),
+        _ => None,
+    });
+    let token_info = items.iter().rev().find_map(|item| match item {
+        RolloutItem::EventMsg(EventMsg::TokenCount(event)) => event.info.as_ref(),
+        _ => None,
+    });
+    let context = match (turn_context, token_info) {
+        (Some(turn_context), Some(info)) => {
+            context_window::for_model(&state, &turn_context.model, info).await
+        }
+        _ => None,
     };
 
-    Ok(Json(ResumeThreadResponse {
-        success: true,
-        thread_id: new_thread.thread_id.to_string(),
+    Ok(Json(ThreadDetails {
+        thread_id: thread_id_str,
+        model: turn_context.map(|context| context.model.clone()),
+        cwd: turn_context
+            .map(|context| context.cwd.clone())
+            .or_else(|| history.session_cwd())
+            .map(|cwd| cwd.display().to_string()),
+        active: false,
+        rollout_path: Some(rollout_path.display().to_string()),
+        token_usage: token_info.map(|info| info.total_token_usage.clone().into()),
+        context,
     }))
 }
 
+/// Most ids accepted by one batch status request.
+const MAX_STATUS_BATCH: usize = 100;
+
 #[derive(Debug, Deserialize, ToSchema)]
-pub struct ForkThreadRequest {
-    pub turn_id: Option<String>,
+pub struct ThreadStatusRequest {
+    /// Up to 100 thread ids
+    pub thread_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreadLookupStatus {
+    /// Loaded in memory
+    Active,
+    /// Dropped from memory by the idle policy; resume to load it again
+    AutoArchived,
+    /// Only in a rollout; resume to load it
+    Persisted,
+    /// Archived explicitly
+    Archived,
+    /// No loaded thread or rollout has this id, or it is not a thread id
+    NotFound,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
-pub struct ForkThreadResponse {
-    pub new_thread_id: String,
-    pub source_thread_id: String,
+pub struct ThreadStatusEntry {
+    /// As requested
+    pub thread_id: String,
+    pub status: ThreadLookupStatus,
+    /// Loaded in the thread manager
+    pub active: bool,
+    /// A turn is running, possibly waiting on an approval
+    pub turn_running: bool,
+    /// `None` when the thread has no turns
+    pub last_turn: Option<LastTurnStatus>,
+    /// Cumulative token usage, once the model has reported any
+    #[schema(value_type = Option<Object>)]
+    pub token_usage: Option<TokenUsageBreakdown>,
+    pub name: Option<String>,
 }
 
-/// POST /api/v2/threads/:id/fork
+impl ThreadStatusEntry {
+    fn not_found(thread_id: String) -> Self {
+        Self {
+            thread_id,
+            status: ThreadLookupStatus::NotFound,
+            active: false,
+            turn_running: false,
+            last_turn: None,
+            token_usage: None,
+            name: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ThreadStatusResponse {
+    /// One entry per requested id, in request order
+    pub data: Vec<ThreadStatusEntry>,
+}
+
+/// POST /api/v2/threads/status
 ///
-/// Forks a thread from a specific turn (or latest turn if not specified)
+/// Returns the run state, last turn, token usage and name of up to 100
+/// threads. Unknown ids are reported as `not_found`
 #[utoipa::path(
     post,
-    path = "/api/v2/threads/{id}/fork",
+    path = "/api/v2/threads/status",
+    request_body = ThreadStatusRequest,
+    responses(
+        (status = 200, description = "One entry per requested id", body = ThreadStatusResponse),
+        (status = 400, description = "Invalid request or more than 100 ids"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Threads"
+)]
+pub async fn batch_thread_status(
+    State(state): State<WebServerState>,
+    JsonBody(req): JsonBody<ThreadStatusRequest>,
+) -> Result<Json<ThreadStatusResponse>, ApiError> {
+    if req.thread_ids.len() > MAX_STATUS_BATCH {
+        return Err(ApiError::InvalidRequest(format!(
+            "At most {MAX_STATUS_BATCH} thread ids per request"
+        )));
+    }
+    let requested: Vec<(String, Option<ThreadId>)> = req
+        .thread_ids
+        .into_iter()
+        .map(|id| {
+            let thread_id = ThreadId::from_string(&id).ok();
+            (id, thread_id)
+        })
+        .collect();
+
+    let names = codex_core::find_thread_names_by_ids(
+        &state.codex_home,
+        &requested
+            .iter()
+            .filter_map(|(_, thread_id)| *thread_id)
+            .collect::<HashSet<_>>(),
+    )
+    .await
+    .unwrap_or_else(|err| {
+        tracing::warn!("Failed to read thread names: {err}");
+        HashMap::new()
+    });
+
+    let mut data = Vec::with_capacity(requested.len());
+    for (id, thread_id) in requested {
+        let entry = match thread_id {
+            Some(thread_id) => {
+                let name = names.get(&thread_id).cloned();
+                thread_status_entry(&state, thread_id, &id, name).await
+            }
+            None => None,
+        };
+        data.push(entry.unwrap_or_else(|| ThreadStatusEntry::not_found(id)));
+    }
+    Ok(Json(ThreadStatusResponse { data }))
+}
+
+/// Describes a loaded thread from the thread manager, and any other thread
+/// from the tail of its rollout. `None` when the thread is unknown.
+async fn thread_status_entry(
+    state: &WebServerState,
+    thread_id: ThreadId,
+    id: &str,
+    name: Option<String>,
+) -> Option<ThreadStatusEntry> {
+    if let Ok(thread) = state.thread_manager.get_thread(thread_id).await {
+        let run_status = state.thread_statuses.status(thread_id);
+        let last_turn = match run_status {
+            ThreadRunStatus::Running | ThreadRunStatus::WaitingApproval => {
+                Some(LastTurnStatus::InProgress)
+            }
+            ThreadRunStatus::Interrupted => Some(LastTurnStatus::Aborted),
+            ThreadRunStatus::Idle | ThreadRunStatus::Archived => match thread.rollout_path() {
+                Some(path) => rollout_tail(&path).await.last_turn,
+                None => None,
+            },
+        };
+        return Some(ThreadStatusEntry {
+            thread_id: id.to_string(),
+            status: ThreadLookupStatus::Active,
+            active: true,
+            turn_running: last_turn == Some(LastTurnStatus::InProgress),
+            last_turn,
+            token_usage: thread.total_token_usage().await.map(Into::into),
+            name,
+        });
+    }
+
+    let thread_id_str = thread_id.to_string();
+    let auto_archived = state.auto_archived.lock().await.contains_key(&thread_id);
+    let (status, rollout_path) =
+        match codex_core::find_thread_path_by_id_str(&state.codex_home, &thread_id_str).await {
+            Ok(Some(path)) => (ThreadLookupStatus::Persisted, Some(path)),
+            _ => (
+                ThreadLookupStatus::Archived,
+                codex_core::find_archived_thread_path_by_id_str(&state.codex_home, &thread_id_str)
+                    .await
+                    .ok()
+                    .flatten(),
+            ),
+        };
+    let status = match (auto_archived, &rollout_path) {
+        (true, _) => ThreadLookupStatus::AutoArchived,
+        (false, Some(_)) => status,
+        (false, None) => return None,
+    };
+    let tail = match &rollout_path {
+        Some(path) => rollout_tail(path).await,
+        None => RolloutTail::default(),
+    };
+    Some(ThreadStatusEntry {
+        thread_id: id.to_string(),
+        status,
+        active: false,
+        turn_running: false,
+        last_turn: tail.last_turn,
+        token_usage: tail.token_usage.map(Into::into),
+        name,
+    })
+}
+
+/// The tail of the rollout at `path`, empty when it cannot be read.
+async fn rollout_tail(path: &std::path::Path) -> RolloutTail {
+    read_rollout_tail(path).await.unwrap_or_else(|err| {
+        tracing::warn!("Failed to read rollout {}: {err}", path.display());
+        RolloutTail::default()
+    })
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateThreadRequest {
+    /// Display name shown in listings; surrounding whitespace is trimmed
+    pub name: Option<String>,
+    pub pinned: Option<bool>,
+    /// `false` opts the thread out of notifications
+    pub notifications: Option<bool>,
+}
+
+/// Echoes the fields that were updated.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UpdateThreadResponse {
+    pub thread_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pinned: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub notifications: Option<bool>,
+}
+
+/// PATCH /api/v2/threads/:id
+///
+/// Renames a thread and updates its listing and notification preferences
+#[utoipa::path(
+    patch,
+    path = "/api/v2/threads/{id}",
     params(
-        ("id" = String, Path, description = "Source thread ID")
+        ("id" = String, Path, description = "Thread ID")
     ),
-    request_body = ForkThreadRequest,
+    request_body = UpdateThreadRequest,
     responses(
-        (status = 200, description = "Thread forked successfully", body = ForkThreadResponse),
+        (status = 200, description = "Thread updated successfully", body = UpdateThreadResponse),
         (status = 400, description = "Invalid request"),
-        (status = 404, description = "Thread not found"),
         (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Thread not found"),
         (status = 500, description = "Internal server error")
     ),
     security(
@@ -253,58 +982,1344 @@ pub struct ForkThreadResponse {
     ),
     tag = "Threads"
 )]
-pub async fn fork_thread(
+pub async fn update_thread(
     State(state): State<WebServerState>,
     Path(thread_id): Path<String>,
-    Json(req): Json<ForkThreadRequest>,
-) -> Result<Json<ForkThreadResponse>, ApiError> {
-    let source_thread_id = ThreadId::from_string(&thread_id)
+    JsonBody(req): JsonBody<UpdateThreadRequest>,
+) -> Result<Json<UpdateThreadResponse>, ApiError> {
+    let thread_id = ThreadId::from_string(&thread_id)
         .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
-    let _turn_id = req.turn_id;
-
-    // Get rollout path for the source thread
-    // Load config (TODO: support config overrides from request)
-    let config = Config::load_with_cli_overrides(vec![])
-        .await
-        .map_err(|e| ApiError::InternalError(format!("Failed to load config: {e}")))?;
+    if req.name.is_none() && req.pinned.is_none() && req.notifications.is_none() {
+        return Err(ApiError::InvalidRequest(
+            "Request must set at least one field".to_string(),
+        ));
+    }
+    let name = match req.name.as_deref() {
+        Some(name) => Some(
+            codex_core::util::normalize_thread_name(name).ok_or_else(|| {
+                ApiError::InvalidRequest("Thread name must not be empty".to_string())
+            })?,
+        ),
+        None => None,
+    };
 
-    // Prefer Postgres-backed rollouts when configured.
-    let postgres_enabled = std::env::var("CODEX_ROLLOUT_POSTGRES_URL")
-        .ok()
-        .is_some_and(|value| !value.trim().is_empty());
+    // Unloaded threads can still be pinned as long as their history exists,
+    // in Postgres or as a rollout file.
+    let thread_id_str = thread_id.to_string();
+    let loaded = state.thread_manager.get_thread(thread_id).await.ok();
+    let persisted = match &state.state_store {
+        Some(store) => store
+            .persisted_threads()
+            .await
+            .map_err(|e| ApiError::InternalError(format!("Failed to list threads: {e}")))?
+            .contains_key(&thread_id),
+        None => false,
+    };
+    let known = loaded.is_some()
+        || persisted
+        || codex_core::find_thread_path_by_id_str(&state.codex_home, &thread_id_str)
+            .await
+            .ok()
+            .flatten()
+            .is_some()
+        || codex_core::find_archived_thread_path_by_id_str(&state.codex_home, &thread_id_str)
+            .await
+            .ok()
+            .flatten()
+            .is_some();
+    if !known {
+        return Err(ApiError::ThreadNotFound);
+    }
 
-    // Fork the thread (usize::MAX keeps full history, matching app-server behavior)
-    // NOTE: turn_id is currently ignored - app-server doesn't support partial forks via JSON-RPC
-    let new_thread = if postgres_enabled {
-        state
-            .thread_manager
-            .fork_thread_from_postgres(usize::MAX, config, source_thread_id)
+    if let Some(name) = &name {
+        // Written here rather than left to the session so that the next
+        // listing sees the name. A loaded thread also records it itself,
+        // which updates its session and emits `thread/name/updated`; the
+        // duplicate index entry is harmless since the latest one wins.
+        codex_core::append_thread_name(&state.codex_home, thread_id, name)
             .await
-            .map_err(|err| match err {
-                CodexErr::Io(io) if io.kind() == ErrorKind::NotFound => ApiError::ThreadNotFound,
-                CodexErr::ThreadNotFound(_) => ApiError::ThreadNotFound,
-                other => ApiError::InternalError(format!("Failed to fork thread: {other}")),
-            })?
-    } else {
-        let source_thread = state
-            .thread_manager
-            .get_thread(source_thread_id)
+            .map_err(|e| ApiError::InternalError(format!("Failed to rename thread: {e}")))?;
+        if let Some(thread) = &loaded
+            && let Err(err) = thread
+                .submit(Op::SetThreadName { name: name.clone() })
+                .await
+        {
+            tracing::warn!("Failed to update name of loaded thread {thread_id}: {err}");
+        }
+    }
+    if let Some(pinned) = req.pinned {
+        state
+            .set_thread_pinned(thread_id, pinned)
             .await
-            .map_err(|_| ApiError::ThreadNotFound)?;
-        let rollout_path = source_thread.rollout_path().ok_or_else(|| {
-            ApiError::InvalidRequest("Source thread has no rollout path".to_string())
-        })?;
+            .map_err(|e| ApiError::InternalError(format!("Failed to update thread pin: {e}")))?;
+    }
+    if let Some(notifications) = req.notifications {
         state
-            .thread_manager
-            .fork_thread(usize::MAX, config, rollout_path, false)
+            .set_thread_notifications_muted(thread_id, !notifications)
             .await
-            .map_err(|e| ApiError::InternalError(format!("Failed to fork thread: {e}")))?
-    };
-
-    let new_thread_id = new_thread.thread_id;
+            .map_err(|e| {
+                ApiError::InternalError(format!("Failed to update thread notifications: {e}"))
+            })?;
+    }
 
-    Ok(Json(ForkThreadResponse {
-        new_thread_id: new_thread_id.to_string(),
-        source_thread_id: source_thread_id.to_string(),
+    Ok(Json(UpdateThreadResponse {
+        thread_id: thread_id_str,
+        name,
+        pinned: req.pinned,
+        notifications: req.notifications,
     }))
 }
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ArchiveThreadResponse {
+    pub success: bool,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v2/threads/{thread_id}/archive",
+    params(
+        ("thread_id" = String, Path, description = "Thread ID to archive")
+    ),
+    responses(
+        (status = 200, description = "Thread archived successfully", body = ArchiveThreadResponse),
+        (status = 404, description = "Thread not found"),
+        (status = 409, description = "Another operation changed the thread first (`thread_state_changed`)"),
+        (status = 401, description = "Unauthorized")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Threads"
+)]
+pub async fn archive_thread(
+    State(state): State<WebServerState>,
+    Path(thread_id): Path<String>,
+) -> Result<Json<ArchiveThreadResponse>, ApiError> {
+    let thread_id = ThreadId::from_string(&thread_id)
+        .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
+
+    let op = state.thread_ops.begin(thread_id);
+    let guard = op.lock().await?;
+    let thread_id_str = thread_id.to_string();
+
+    let live_rollout = codex_core::find_thread_path_by_id_str(&state.codex_home, &thread_id_str)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to locate rollout: {e}")))?;
+    let loaded = state.thread_manager.get_thread(thread_id).await.is_ok();
+    let auto_archived = state.auto_archived.lock().await.contains_key(&thread_id);
+    if live_rollout.is_none() && !loaded && !auto_archived {
+        // Already archived: nothing left to do. A thread archived before its
+        // first turn has no rollout, so its status is the only record.
+        let archived = state.thread_statuses.status(thread_id) == ThreadRunStatus::Archived
+            || matches!(
+                codex_core::find_archived_thread_path_by_id_str(&state.codex_home, &thread_id_str)
+                    .await,
+                Ok(Some(_))
+            );
+        return if archived {
+            Ok(Json(ArchiveThreadResponse { success: true }))
+        } else {
+            Err(ApiError::ThreadNotFound)
+        };
+    }
+    // Requests that looked the thread up before this point get a 409 rather
+    // than a thread that is shutting down.
+    guard.bump();
+
+    let mut state_db = None;
+    if let Some(thread) = state.thread_manager.remove_thread(&thread_id).await {
+        state_db = thread.state_db();
+        shut_down_thread(thread_id, &thread).await;
+    }
+    state.sessions.write().await.forget(thread_id);
+    state.turn_queue.clear(thread_id);
+    state.auto_archived.lock().await.remove(&thread_id);
+
+    // Look again now that the thread is stopped. A thread that never ran a
+    // turn may not have written a rollout at all.
+    let live_rollout = codex_core::find_thread_path_by_id_str(&state.codex_home, &thread_id_str)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to locate rollout: {e}")))?;
+    if let Some(rollout_path) = live_rollout {
+        let archived_path = archive_rollout(&state.codex_home, thread_id, &rollout_path)
+            .await
+            .map_err(|e| ApiError::InternalError(format!("Failed to archive rollout: {e}")))?;
+        if let Some(state_db) = state_db
+            && let Err(err) = state_db
+                .mark_archived(thread_id, &archived_path, chrono::Utc::now())
+                .await
+        {
+            tracing::warn!("Failed to mark thread {thread_id} archived: {err}");
+        }
+    }
+    state
+        .thread_statuses
+        .apply(thread_id, StatusTransition::Archived);
+
+    Ok(Json(ArchiveThreadResponse { success: true }))
+}
+
+/// Shuts a removed thread down and waits for it to stop. Polls the agent
+/// status instead of reading events so attached SSE streams still see the
+/// shutdown.
+async fn shut_down_thread(thread_id: ThreadId, thread: &CodexThread) {
+    if let Err(err) = thread.submit(Op::Shutdown).await {
+        tracing::warn!("Failed to shut down thread {thread_id}: {err}");
+        return;
+    }
+    let stopped = tokio::time::timeout(SHUTDOWN_TIMEOUT, async {
+        while !matches!(thread.agent_status().await, AgentStatus::Shutdown) {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await;
+    if stopped.is_err() {
+        tracing::warn!("Timed out waiting for thread {thread_id} to shut down; archiving anyway");
+    }
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct DeleteThreadParams {
+    /// Delete even while a turn is running; the turn is stopped
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DeleteThreadResponse {
+    pub success: bool,
+}
+
+/// DELETE /api/v2/threads/:id
+///
+/// Permanently deletes a thread: shuts it down if loaded, removes its rollout
+/// (live or archived, or its Postgres rows when configured) and drops its
+/// pending approvals
+#[utoipa::path(
+    delete,
+    path = "/api/v2/threads/{id}",
+    params(
+        ("id" = String, Path, description = "Thread ID to delete"),
+        ("force" = Option<bool>, Query, description = "Delete even while a turn is running")
+    ),
+    responses(
+        (status = 200, description = "Thread deleted", body = DeleteThreadResponse),
+        (status = 404, description = "Thread not found"),
+        (status = 409, description = "A turn is running and `force` is not set (`turn_in_progress`), or another operation changed the thread first (`thread_state_changed`)"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Threads"
+)]
+pub async fn delete_thread(
+    State(state): State<WebServerState>,
+    Path(thread_id): Path<String>,
+    Query(params): Query<DeleteThreadParams>,
+) -> Result<Json<DeleteThreadResponse>, ApiError> {
+    let thread_id = ThreadId::from_string(&thread_id)
+        .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
+
+    let op = state.thread_ops.begin(thread_id);
+    let guard = op.lock().await?;
+    let thread_id_str = thread_id.to_string();
+
+    let live_rollout = codex_core::find_thread_path_by_id_str(&state.codex_home, &thread_id_str)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to locate rollout: {e}")))?;
+    let archived_rollout =
+        codex_core::find_archived_thread_path_by_id_str(&state.codex_home, &thread_id_str)
+            .await
+            .map_err(|e| ApiError::InternalError(format!("Failed to locate rollout: {e}")))?;
+    let persisted = match &state.state_store {
+        Some(store) => store
+            .persisted_threads()
+            .await
+            .map_err(|e| ApiError::InternalError(format!("Failed to list threads: {e}")))?
+            .contains_key(&thread_id),
+        None => false,
+    };
+    let loaded = state.thread_manager.get_thread(thread_id).await.is_ok();
+    let auto_archived = state.auto_archived.lock().await.contains_key(&thread_id);
+    let status = state.thread_statuses.status(thread_id);
+    // A thread archived before its first turn has no rollout; its status is
+    // the only record.
+    if live_rollout.is_none()
+        && archived_rollout.is_none()
+        && !persisted
+        && !loaded
+        && !auto_archived
+        && status != ThreadRunStatus::Archived
+    {
+        return Err(ApiError::ThreadNotFound);
+    }
+    if matches!(
+        status,
+        ThreadRunStatus::Running | ThreadRunStatus::WaitingApproval
+    ) && !params.force
+    {
+        return Err(ApiError::TurnInProgress);
+    }
+    // Requests that looked the thread up before this point get a 409 rather
+    // than a thread that is being deleted.
+    guard.bump();
+
+    let mut state_db = None;
+    if let Some(thread) = state.thread_manager.remove_thread(&thread_id).await {
+        state_db = thread.state_db();
+        shut_down_thread(thread_id, &thread).await;
+    }
+    let pending: Vec<_> = state
+        .pending_approvals
+        .lock()
+        .await
+        .extract_if(|_, ctx| ctx.thread_id == thread_id)
+        .collect();
+    for (_, ctx) in pending {
+        // Nothing is left to submit an answer to.
+        let _ = ctx
+            .response_channel
+            .send(ApprovalResponse::ResolvedExternally);
+    }
+    state.sessions.write().await.forget(thread_id);
+    state.turn_queue.clear(thread_id);
+    state.auto_archived.lock().await.remove(&thread_id);
+
+    // Look again now that the thread is stopped: shutting down may have
+    // flushed a rollout that did not exist before.
+    let live_rollout = codex_core::find_thread_path_by_id_str(&state.codex_home, &thread_id_str)
+        .await
+        .map_err(|e| ApiError::InternalError(format!("Failed to locate rollout: {e}")))?;
+    for rollout_path in live_rollout.iter().chain(archived_rollout.iter()) {
+        match tokio::fs::remove_file(rollout_path).await {
+            Ok(()) => {}
+            Err(err) if err.kind() == ErrorKind::NotFound => {}
+            Err(err) => {
+                return Err(ApiError::InternalError(format!(
+                    "Failed to delete rollout: {err}"
+                )));
+            }
+        }
+    }
+    match &state.state_store {
+        // Also removes the thread's pins and persisted web state.
+        Some(store) => {
+            store
+                .prune_threads(&[thread_id])
+                .await
+                .map_err(|e| ApiError::InternalError(format!("Failed to delete thread: {e}")))?;
+        }
+        None => {
+            if let Err(err) = state.set_thread_pinned(thread_id, false).await {
+                tracing::warn!("Failed to unpin deleted thread {thread_id}: {err}");
+            }
+        }
+    }
+    if let Some(state_db) = state_db
+        && let Err(err) = state_db.delete_thread(thread_id).await
+    {
+        tracing::warn!("Failed to delete thread {thread_id} metadata: {err}");
+    }
+    if let Err(err) = turn_attachments::forget(&state.codex_home, thread_id).await {
+        tracing::warn!("Failed to delete attachment journal of thread {thread_id}: {err}");
+    }
+    state.thread_statuses.forget(thread_id);
+    state.thread_memory.forget(thread_id);
+    state.event_replay.forget(thread_id);
+
+    Ok(Json(DeleteThreadResponse { success: true }))
+}
+
+/// Overrides for a resume. The body is optional; an empty one resumes with
+/// the configured settings.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct ResumeThreadRequest {
+    #[schema(example = "claude-sonnet-4-5")]
+    pub model: Option<String>,
+    #[schema(example = "/path/to/project")]
+    pub cwd: Option<String>,
+    /// Accept a `model` missing from the model list, e.g. for a custom
+    /// provider. Length and character limits still apply.
+    #[serde(default)]
+    pub skip_validation: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ResumeThreadResponse {
+    pub success: bool,
+    pub thread_id: String,
+    /// The thread was already loaded, so nothing was resumed and the
+    /// overrides were not applied
+    pub already_active: bool,
+    /// Model the thread runs with
+    pub model: String,
+    /// Things done to make the thread resumable, e.g. approvals left pending
+    /// by a server restart that were denied
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// POST /api/v2/threads/:id/resume
+///
+/// Resumes an archived thread, applying the optional `model` and `cwd`
+/// overrides. Resuming a thread that is already loaded succeeds without
+/// changing it. Approvals left unanswered when the server stopped are denied
+/// before the response, so the thread accepts new turns
+#[utoipa::path(
+    post,
+    path = "/api/v2/threads/{id}/resume",
+    params(
+        ("id" = String, Path, description = "Thread ID to resume")
+    ),
+    request_body(content = Option<ResumeThreadRequest>, description = "Optional overrides"),
+    responses(
+        (status = 200, description = "Thread resumed, or already active", body = ResumeThreadResponse),
+        (status = 400, description = "Invalid request or unknown model (`model_not_found`)"),
+        (status = 404, description = "Thread not found"),
+        (status = 409, description = "Another operation changed the thread first (`thread_state_changed`)"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "cwd is outside the trusted roots"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Threads"
+)]
+pub async fn resume_thread(
+    State(state): State<WebServerState>,
+    Path(thread_id): Path<String>,
+    payload: Result<Option<Json<ResumeThreadRequest>>, JsonRejection>,
+) -> Result<Json<ResumeThreadResponse>, ApiError> {
+    let req = payload?.map(|Json(req)| req).unwrap_or_default();
+    let thread_id = ThreadId::from_string(&thread_id)
+        .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
+
+    let op = state.thread_ops.begin(thread_id);
+    let guard = op.lock().await?;
+
+    // Check if thread is already active
+    if let Ok(thread) = state.thread_manager.get_thread(thread_id).await {
+        state
+            .thread_statuses
+            .apply(thread_id, StatusTransition::Restored);
+        guard.bump();
+        // Thread is already active, return success (idempotent)
+        return Ok(Json(ResumeThreadResponse {
+            success: true,
+            thread_id: thread_id.to_string(),
+            already_active: true,
+            model: thread.config_snapshot().await.model,
+            warnings: Vec::new(),
+        }));
+    }
+
+    let mut config = state.config_loader.load().await?;
+    if let Some(cwd) = req.cwd {
+        config.cwd = std::path::PathBuf::from(cwd);
+    }
+    if let Some(model) = req.model {
+        validate_model(&state, &model, req.skip_validation).await?;
+        config.model = Some(model);
+    }
+    config.cwd = state.trusted_roots.check(&config.cwd)?;
+
+    // Prefer Postgres-backed rollouts when configured.
+    let postgres_enabled = std::env::var("CODEX_ROLLOUT_POSTGRES_URL")
+        .ok()
+        .is_some_and(|value| !value.trim().is_empty());
+
+    let (new_thread, unanswered_calls) = if postgres_enabled {
+        let new_thread = state
+            .thread_manager
+            .resume_thread_from_postgres(config, thread_id, state.auth_manager.clone())
+            .await
+            .map_err(|err| match err {
+                CodexErr::Io(io) if io.kind() == ErrorKind::NotFound => {
+                    ApiError::NotFound(format!("Rollout history not found for thread: {thread_id}"))
+                }
+                CodexErr::ThreadNotFound(_) => {
+                    ApiError::NotFound(format!("Rollout history not found for thread: {thread_id}"))
+                }
+                other => ApiError::InternalError(format!("Failed to resume thread: {other}")),
+            })?;
+        (new_thread, Vec::new())
+    } else {
+        let thread_id_str = thread_id.to_string();
+        let live_rollout =
+            codex_core::find_thread_path_by_id_str(&state.codex_home, &thread_id_str)
+                .await
+                .map_err(|e| ApiError::InternalError(format!("Failed to locate rollout: {e}")))?;
+        let rollout_path = match live_rollout {
+            Some(rollout_path) => rollout_path,
+            // Explicitly archived: move the rollout back before resuming it.
+            None => {
+                let Some(archived_path) = codex_core::find_archived_thread_path_by_id_str(
+                    &state.codex_home,
+                    &thread_id_str,
+                )
+                .await
+                .map_err(|e| ApiError::InternalError(format!("Failed to locate rollout: {e}")))?
+                else {
+                    return Err(ApiError::NotFound(format!(
+                        "Rollout file not found for thread: {thread_id}"
+                    )));
+                };
+                restore_rollout(&state.codex_home, thread_id, &archived_path)
+                    .await
+                    .map_err(|e| {
+                        ApiError::InternalError(format!("Failed to restore rollout: {e}"))
+                    })?
+            }
+        };
+        let tail = rollout_tail(&rollout_path).await;
+        let new_thread = state
+            .thread_manager
+            .resume_thread_from_rollout(config, rollout_path, state.auth_manager.clone())
+            .await
+            .map_err(|e| ApiError::InternalError(format!("Failed to resume thread: {e}")))?;
+        let unanswered_calls = if tail.last_turn == Some(LastTurnStatus::Unfinished) {
+            tail.unanswered_calls
+        } else {
+            Vec::new()
+        };
+        (new_thread, unanswered_calls)
+    };
+    let warnings = deny_stale_approvals(&state, &new_thread, unanswered_calls).await?;
+    state.thread_announcements.announce(new_thread.thread_id);
+
+    state.auto_archived.lock().await.remove(&thread_id);
+    state
+        .thread_statuses
+        .apply(thread_id, StatusTransition::Restored);
+    guard.bump();
+
+    Ok(Json(ResumeThreadResponse {
+        success: true,
+        thread_id: new_thread.thread_id.to_string(),
+        already_active: false,
+        model: new_thread.session_configured.model.clone(),
+        warnings,
+    }))
+}
+
+/// Denies what a previous run left waiting on the client: approvals recovered
+/// from the state store, and calls of an unfinished last turn that never got
+/// an output. The resumed thread starts idle and fills in missing outputs, so
+/// the denials only tell it explicitly and clear what would otherwise be
+/// re-emitted. Returns the warnings for the resume response.
+async fn deny_stale_approvals(
+    state: &WebServerState,
+    new_thread: &NewThread,
+    unanswered_calls: Vec<String>,
+) -> Result<Vec<String>, ApiError> {
+    let thread_id = new_thread.thread_id;
+    let approvals = state.take_restored_approvals(thread_id).await;
+    for approval in &approvals {
+        if let Some(reply) = ApprovalReply::from_persisted(approval) {
+            new_thread
+                .thread
+                .submit(reply.op(ReviewDecision::Denied))
+                .await
+                .map_err(|e| ApiError::InternalError(format!("Failed to deny approval: {e}")))?;
+        }
+    }
+    if !unanswered_calls.is_empty() {
+        new_thread
+            .thread
+            .submit(Op::Interrupt)
+            .await
+            .map_err(|e| ApiError::InternalError(format!("Failed to abort turn: {e}")))?;
+    }
+
+    let mut denied: Vec<String> = approvals
+        .into_iter()
+        .map(|approval| approval.approval_id)
+        .collect();
+    for call_id in unanswered_calls {
+        if !denied.contains(&call_id) {
+            denied.push(call_id);
+        }
+    }
+    if denied.is_empty() {
+        return Ok(Vec::new());
+    }
+    tracing::warn!(
+        "Denied {} approvals of thread {thread_id} left pending by a previous run",
+        denied.len()
+    );
+    Ok(vec![format!(
+        "Denied approvals left pending when the server stopped: {}",
+        denied.join(", ")
+    )])
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ForkThreadRequest {
+    pub turn_id: Option<String>,
+    /// Working directory of the fork, e.g. a fresh checkout of the same
+    /// repository. Defaults to the source thread's.
+    pub cwd: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ForkThreadResponse {
+    pub new_thread_id: String,
+    pub source_thread_id: String,
+    /// Last turn of the source thread kept in the fork. `None` when the source
+    /// thread has not run a turn yet.
+    pub forked_at_turn_id: Option<String>,
+    /// Working directory of the source thread
+    pub source_cwd: String,
+    /// Working directory of the fork
+    pub cwd: String,
+}
+
+/// POST /api/v2/threads/:id/fork
+///
+/// Forks a thread from a specific turn (or latest turn if not specified).
+/// The fork keeps the given turn and everything before it, and runs in the
+/// source thread's cwd unless `cwd` moves it to another workspace
+#[utoipa::path(
+    post,
+    path = "/api/v2/threads/{id}/fork",
+    params(
+        ("id" = String, Path, description = "Source thread ID")
+    ),
+    request_body = ForkThreadRequest,
+    responses(
+        (status = 200, description = "Thread forked successfully", body = ForkThreadResponse),
+        (status = 400, description = "Invalid request, turn not found in the source thread or unusable cwd (`cwd_unwritable`)"),
+        (status = 404, description = "Thread not found"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "cwd is outside the trusted roots"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Threads"
+)]
+pub async fn fork_thread(
+    State(state): State<WebServerState>,
+    Path(thread_id): Path<String>,
+    JsonBody(req): JsonBody<ForkThreadRequest>,
+) -> Result<Json<ForkThreadResponse>, ApiError> {
+    let source_thread_id = ThreadId::from_string(&thread_id)
+        .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
+
+    let mut config = state.config_loader.load().await?;
+
+    // Prefer Postgres-backed rollouts when configured.
+    let postgres_enabled = std::env::var("CODEX_ROLLOUT_POSTGRES_URL")
+        .ok()
+        .is_some_and(|value| !value.trim().is_empty());
+
+    let rollout_path = if postgres_enabled {
+        None
+    } else {
+        let source_thread = state
+            .thread_manager
+            .get_thread(source_thread_id)
+            .await
+            .map_err(|_| ApiError::ThreadNotFound)?;
+        Some(source_thread.rollout_path().ok_or_else(|| {
+            ApiError::InvalidRequest("Source thread has no rollout path".to_string())
+        })?)
+    };
+    let history = match &rollout_path {
+        Some(path) => RolloutRecorder::get_rollout_history(path)
+            .await
+            .map(|history| history.get_rollout_items()),
+        None => codex_core::load_postgres_rollout_items(source_thread_id).await,
+    }
+    .map_err(|err| match err.kind() {
+        ErrorKind::NotFound => ApiError::ThreadNotFound,
+        _ => ApiError::InternalError(format!("Failed to read source thread history: {err}")),
+    })?;
+
+    // usize::MAX keeps the full history, matching app-server behavior.
+    let (nth_user_message, forked_at_turn_id) = match req.turn_id {
+        Some(turn_id) => {
+            let nth =
+                codex_core::nth_user_message_after_turn(&history, &turn_id).ok_or_else(|| {
+                    ApiError::InvalidRequest(format!("Turn {turn_id} not found in source thread"))
+                })?;
+            (nth, Some(turn_id))
+        }
+        None => (usize::MAX, codex_core::last_turn_id(&history)),
+    };
+
+    let source_cwd = history_cwd(&history).unwrap_or_else(|| config.cwd.clone());
+    let cwd = check_cwd(
+        &state,
+        &req.cwd.map_or_else(|| source_cwd.clone(), PathBuf::from),
+    )?;
+    // Compared resolved, since the check may have canonicalized the new cwd.
+    if std::fs::canonicalize(&source_cwd).ok() != std::fs::canonicalize(&cwd).ok() {
+        // The carried-over history still names files under the old workspace.
+        let note = workspace_moved_note(&source_cwd, &cwd);
+        config.developer_instructions = Some(match config.developer_instructions.take() {
+            Some(instructions) => format!("{instructions}\n\n{note}"),
+            None => note,
+        });
+    }
+    config.cwd = cwd.clone();
+
+    let new_thread = match rollout_path {
+        Some(path) => {
+            state
+                .thread_manager
+                .fork_thread(nth_user_message, config, path, false)
+                .await
+        }
+        None => {
+            state
+                .thread_manager
+                .fork_thread_from_postgres(nth_user_message, config, source_thread_id)
+                .await
+        }
+    }
+    .map_err(|err| match err {
+        CodexErr::Io(io) if io.kind() == ErrorKind::NotFound => ApiError::ThreadNotFound,
+        CodexErr::ThreadNotFound(_) => ApiError::ThreadNotFound,
+        other => ApiError::InternalError(format!("Failed to fork thread: {other}")),
+    })?;
+    state.thread_announcements.announce(new_thread.thread_id);
+
+    Ok(Json(ForkThreadResponse {
+        new_thread_id: new_thread.thread_id.to_string(),
+        source_thread_id: source_thread_id.to_string(),
+        forked_at_turn_id,
+        source_cwd: source_cwd.display().to_string(),
+        cwd: cwd.display().to_string(),
+    }))
+}
+
+/// The cwd the thread last ran a turn in, or else the one it started in.
+fn history_cwd(history: &[RolloutItem]) -> Option<PathBuf> {
+    history.iter().rev().find_map(|item| match item {
+        RolloutItem::TurnContext(context) => Some(context.cwd.clone()),
+        RolloutItem::SessionMeta(meta) => Some(meta.meta.cwd.clone()),
+        _ => None,
+    })
+}
+
+/// Tells the model of a fork that the workspace moved, so paths from the
+/// earlier conversation are read against the new directory.
+fn workspace_moved_note(from: &std::path::Path, to: &std::path::Path) -> String {
+    format!(
+        "This conversation was forked from a session whose working directory was {}. \
+         The workspace is now {}: paths under the old directory refer to the same files \
+         under the new one, and commands run there.",
+        from.display(),
+        to.display()
+    )
+}
+
+const DEFAULT_ITEM_LIMIT: usize = 100;
+const MAX_ITEM_LIMIT: usize = 500;
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct ThreadItemsParams {
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// `next_cursor` from the previous page
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ThreadHistoryItem {
+    pub turn_id: String,
+    /// Same shape as `item` in `item/completed`
+    #[schema(value_type = Object)]
+    pub item: ThreadItem,
+    /// Attachments a user message was sent with, deleted ones included
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<HistoryAttachment>,
+}
+
+/// A turn with items on the page, as last reported in `turn/completed`.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ThreadHistoryTurn {
+    pub id: String,
+    #[schema(value_type = String, example = "completed")]
+    pub status: TurnStatus,
+    #[schema(value_type = Option<Object>)]
+    pub error: Option<TurnError>,
+    /// Items in the whole turn, including those on other pages
+    pub item_count: usize,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ThreadItemsResponse {
+    pub thread_id: String,
+    /// Turns of the items in `data`, oldest first
+    pub turns: Vec<ThreadHistoryTurn>,
+    /// Oldest first
+    pub data: Vec<ThreadHistoryItem>,
+    /// Pass as `cursor` to get the next page; `None` on the last page
+    pub next_cursor: Option<String>,
+}
+
+/// GET /api/v2/threads/:id/items
+///
+/// Lists the items a thread has produced so far, read from its rollout, so a
+/// client can render the conversation before subscribing to live events
+#[utoipa::path(
+    get,
+    path = "/api/v2/threads/{id}/items",
+    params(
+        ("id" = String, Path, description = "Thread ID"),
+        ("limit" = Option<usize>, Query, description = "Maximum number of items to return, 1 to 500 (default: 100)"),
+        ("cursor" = Option<String>, Query, description = "`next_cursor` from the previous page")
+    ),
+    responses(
+        (status = 200, description = "Thread items, oldest first", body = ThreadItemsResponse),
+        (status = 400, description = "Invalid query parameters"),
+        (status = 404, description = "Thread not found"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Threads"
+)]
+pub async fn list_thread_items(
+    State(state): State<WebServerState>,
+    scope: AttachmentScope,
+    Path(thread_id): Path<String>,
+    Query(params): Query<ThreadItemsParams>,
+) -> Result<Json<ThreadItemsResponse>, ApiError> {
+    let thread_id = ThreadId::from_string(&thread_id)
+        .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
+    if params.limit == Some(0) {
+        return Err(ApiError::InvalidRequest(
+            "limit must be at least 1".to_string(),
+        ));
+    }
+    // The cursor is the position of the next item. Rollouts only grow, so
+    // positions stay valid until a rollback drops turns.
+    let start = match &params.cursor {
+        Some(cursor) => cursor
+            .parse::<usize>()
+            .map_err(|_| ApiError::InvalidRequest("Invalid cursor".to_string()))?,
+        None => 0,
+    };
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_ITEM_LIMIT)
+        .min(MAX_ITEM_LIMIT);
+
+    let history = load_thread_history(&state, thread_id).await?;
+    let attachments = turn_attachments::load(&state.codex_home, thread_id)
+        .await
+        .unwrap_or_else(|err| {
+            tracing::warn!("Failed to read attachments of thread {thread_id}: {err}");
+            HashMap::new()
+        });
+    let mut turns = Vec::new();
+    let mut data = Vec::new();
+    let mut position = 0;
+    for turn in build_turns_from_rollout_items(&history) {
+        let item_count = turn.items.len();
+        let page_items: Vec<ThreadItem> = turn
+            .items
+            .into_iter()
+            .skip(start.saturating_sub(position))
+            .take((start + limit).saturating_sub(position.max(start)))
+            .collect();
+        position += item_count;
+        if page_items.is_empty() {
+            continue;
+        }
+        let records = attachments.get(&turn.id).map_or(&[][..], Vec::as_slice);
+        for item in page_items {
+            let attachments = match &item {
+                ThreadItem::UserMessage { content, .. } if !records.is_empty() => {
+                    turn_attachments::history_attachments(&state, &scope, records, content).await
+                }
+                _ => Vec::new(),
+            };
+            data.push(ThreadHistoryItem {
+                turn_id: turn.id.clone(),
+                item,
+                attachments,
+            });
+        }
+        turns.push(ThreadHistoryTurn {
+            id: turn.id,
+            status: turn.status,
+            error: turn.error,
+            item_count,
+        });
+    }
+    let next_cursor = (position > start + limit).then(|| (start + limit).to_string());
+
+    Ok(Json(ThreadItemsResponse {
+        thread_id: thread_id.to_string(),
+        turns,
+        data,
+        next_cursor,
+    }))
+}
+
+/// Path of a thread's rollout, whether the thread is loaded, only stored, or
+/// archived. `None` when rollouts are kept in Postgres.
+async fn locate_rollout(
+    state: &WebServerState,
+    thread_id: ThreadId,
+) -> Result<Option<PathBuf>, ApiError> {
+    let postgres_enabled = std::env::var("CODEX_ROLLOUT_POSTGRES_URL")
+        .ok()
+        .is_some_and(|value| !value.trim().is_empty());
+    match state.thread_manager.get_thread(thread_id).await {
+        Ok(thread) => Ok(thread.rollout_path()),
+        Err(_) if postgres_enabled => Ok(None),
+        Err(_) => {
+            let id = thread_id.to_string();
+            let live = codex_core::find_thread_path_by_id_str(&state.codex_home, &id).await;
+            let path = match live {
+                Ok(None) => {
+                    codex_core::find_archived_thread_path_by_id_str(&state.codex_home, &id).await
+                }
+                found => found,
+            }
+            .map_err(|e| ApiError::InternalError(format!("Failed to locate rollout: {e}")))?;
+            Ok(Some(path.ok_or(ApiError::ThreadNotFound)?))
+        }
+    }
+}
+
+/// Reads a thread's rollout items from wherever [`locate_rollout`] finds them.
+async fn load_thread_history(
+    state: &WebServerState,
+    thread_id: ThreadId,
+) -> Result<Vec<RolloutItem>, ApiError> {
+    match locate_rollout(state, thread_id).await? {
+        Some(path) => RolloutRecorder::get_rollout_history(&path)
+            .await
+            .map(|history| history.get_rollout_items()),
+        None => codex_core::load_postgres_rollout_items(thread_id).await,
+    }
+    .map_err(|err| match err.kind() {
+        ErrorKind::NotFound => ApiError::ThreadNotFound,
+        _ => ApiError::InternalError(format!("Failed to read thread history: {err}")),
+    })
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportItems {
+    /// Every rollout line
+    #[default]
+    All,
+    /// Without raw model response items (`response_item` lines)
+    Compact,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct ExportThreadParams {
+    #[serde(default)]
+    pub items: ExportItems,
+    #[serde(default)]
+    pub format: ExportFormat,
+    /// Items written before the export stops (default: 100000)
+    pub max_items: Option<usize>,
+}
+
+/// GET /api/v2/threads/:id/export
+///
+/// Streams the thread's rollout as JSONL or a Markdown transcript, from the
+/// rollout file or from Postgres rows a page at a time
+#[utoipa::path(
+    get,
+    path = "/api/v2/threads/{id}/export",
+    params(
+        ("id" = String, Path, description = "Thread ID"),
+        ("items" = Option<String>, Query, description = "`all` (default) or `compact` to leave out raw response items"),
+        ("format" = Option<String>, Query, description = "`jsonl` (default) or `markdown`"),
+        ("max_items" = Option<usize>, Query, description = "Items written before the export stops (default: 100000)")
+    ),
+    responses(
+        (status = 200, description = "Rollout lines", content_type = "application/x-ndjson"),
+        (status = 200, description = "Transcript", content_type = "text/markdown"),
+        (status = 400, description = "Invalid thread ID or max_items"),
+        (status = 404, description = "Thread not found"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Threads"
+)]
+pub async fn export_thread(
+    State(state): State<WebServerState>,
+    Path(thread_id): Path<String>,
+    Query(params): Query<ExportThreadParams>,
+) -> Result<Response, ApiError> {
+    let thread_id = ThreadId::from_string(&thread_id)
+        .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
+    let max_items = params.max_items.unwrap_or(DEFAULT_EXPORT_MAX_ITEMS);
+    if max_items == 0 {
+        return Err(ApiError::InvalidRequest(
+            "max_items must be at least 1".to_string(),
+        ));
+    }
+    let options = ExportOptions {
+        format: params.format,
+        compact: params.items == ExportItems::Compact,
+        max_items,
+    };
+    let read_error = |err: std::io::Error| match err.kind() {
+        ErrorKind::NotFound => ApiError::ThreadNotFound,
+        _ => ApiError::InternalError(format!("Failed to read thread history: {err}")),
+    };
+
+    let body = match locate_rollout(&state, thread_id).await? {
+        Some(path) => Body::from_stream(export_stream(
+            thread_export::file_lines(&path).await.map_err(read_error)?,
+            options,
+        )),
+        None => Body::from_stream(export_stream(
+            thread_export::postgres_lines(thread_id)
+                .await
+                .map_err(read_error)?,
+            options,
+        )),
+    };
+
+    let mut response = Response::new(body);
+    response.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static(params.format.content_type()),
+    );
+    // Thread ids are UUIDs, so the filename needs no sanitizing.
+    if let Ok(disposition) = HeaderValue::from_str(&format!(
+        "attachment; filename=\"{thread_id}.{}\"",
+        params.format.extension()
+    )) {
+        response
+            .headers_mut()
+            .insert(CONTENT_DISPOSITION, disposition);
+    }
+    Ok(response)
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct ImportThreadParams {
+    /// Load the imported thread so it can take turns right away
+    #[serde(default)]
+    pub resume: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ImportThreadResponse {
+    /// Id the rollout was imported under
+    pub thread_id: String,
+    /// Id recorded in the uploaded rollout
+    pub source_thread_id: String,
+    /// Rollout lines written
+    pub line_count: usize,
+    pub resumed: bool,
+}
+
+/// POST /api/v2/threads/import
+///
+/// Imports a rollout JSONL (e.g. from the export endpoint) as a new thread.
+/// The file is sent as the `file` field of a multipart body
+#[utoipa::path(
+    post,
+    path = "/api/v2/threads/import",
+    request_body(content = inline(String), content_type = "multipart/form-data"),
+    params(
+        ("resume" = Option<bool>, Query, description = "Load the imported thread right away (default: false)")
+    ),
+    responses(
+        (status = 200, description = "Rollout imported", body = ImportThreadResponse),
+        (status = 400, description = "Malformed multipart body, or a line that is not a rollout item (`invalid_rollout`, with `line`)"),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Configured cwd is outside the trusted roots"),
+        (status = 413, description = "File is over `CODEX_WEB_IMPORT_MAX_BYTES`"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Threads"
+)]
+pub async fn import_thread(
+    State(state): State<WebServerState>,
+    Query(params): Query<ImportThreadParams>,
+    multipart: Result<Multipart, MultipartRejection>,
+) -> Result<Json<ImportThreadResponse>, ApiError> {
+    let mut details = UploadErrorDetails::default();
+    let mut multipart = multipart.map_err(|rejection| {
+        upload_error(
+            UploadErrorCode::MalformedMultipart,
+            rejection.body_text(),
+            &details,
+        )
+    })?;
+
+    // Staged outside the sessions tree so a partial upload is never listed.
+    let upload_path = state
+        .codex_home
+        .join(format!(".import-{}.jsonl", uuid::Uuid::new_v4()));
+    let received = receive_file(
+        &mut multipart,
+        &upload_path,
+        state.import_limits.max_bytes,
+        &mut details,
+    )
+    .await;
+    let imported = match received {
+        Ok(_) => import_rollout(&state.codex_home, &upload_path).await,
+        Err(err) => {
+            let _ = tokio::fs::remove_file(&upload_path).await;
+            return Err(err);
+        }
+    };
+    let _ = tokio::fs::remove_file(&upload_path).await;
+    let imported = imported.map_err(|err| match err {
+        ImportError::Invalid { line, message } => ApiError::InvalidRollout { line, message },
+        ImportError::Io(err) => ApiError::InternalError(format!("Failed to import rollout: {err}")),
+    })?;
+
+    if params.resume {
+        let mut config = state.config_loader.load().await?;
+        config.cwd = state.trusted_roots.check(&config.cwd)?;
+        state
+            .thread_manager
+            .resume_thread_from_rollout(config, imported.path.clone(), state.auth_manager.clone())
+            .await
+            .map_err(|e| ApiError::InternalError(format!("Failed to resume thread: {e}")))?;
+        state
+            .thread_statuses
+            .apply(imported.thread_id, StatusTransition::Restored);
+        state.thread_announcements.announce(imported.thread_id);
+    }
+
+    Ok(Json(ImportThreadResponse {
+        thread_id: imported.thread_id.to_string(),
+        source_thread_id: imported.source_thread_id.to_string(),
+        line_count: imported.line_count,
+        resumed: params.resume,
+    }))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CompactThreadResponse {
+    pub thread_id: String,
+    pub status: LongOperationStatus,
+}
+
+/// POST /api/v2/threads/:id/compact
+///
+/// Starts compacting the thread's context and returns at once. Progress is
+/// streamed as `thread/compacting` and the result as `thread/compacted`
+#[utoipa::path(
+    post,
+    path = "/api/v2/threads/{id}/compact",
+    params(
+        ("id" = String, Path, description = "Thread ID")
+    ),
+    responses(
+        (status = 202, description = "Compaction started", body = CompactThreadResponse),
+        (status = 404, description = "Thread not found"),
+        (status = 409, description = "A turn is running (`turn_in_progress`)"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Threads"
+)]
+pub async fn compact_thread(
+    State(state): State<WebServerState>,
+    Path(thread_id): Path<String>,
+) -> Result<(StatusCode, Json<CompactThreadResponse>), ApiError> {
+    let thread_id = ThreadId::from_string(&thread_id)
+        .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
+    let thread = state
+        .thread_manager
+        .get_thread(thread_id)
+        .await
+        .map_err(|_| ApiError::ThreadNotFound)?;
+    let operation = LongOperation::Compaction;
+    // Compaction runs as a turn, so a running turn would end the progress
+    // tracking early.
+    if !state.progress.is_running(thread_id, operation) {
+        check_no_turn_running(&state, thread_id)?;
+        let _completion = state.progress.begin(thread_id, operation);
+        if let Err(err) = thread.submit(Op::Compact).await {
+            state.progress.abandon(thread_id, operation);
+            return Err(ApiError::InternalError(format!(
+                "Failed to start compaction: {err}"
+            )));
+        }
+    }
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(CompactThreadResponse {
+            thread_id: thread_id.to_string(),
+            status: LongOperationStatus::RunningInBackground,
+        }),
+    ))
+}
+
+/// Names the turns to drop, by `turn_id` or `num_turns`; set exactly one.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RollbackThreadRequest {
+    /// Last turn to keep; every later user turn is dropped from the context
+    #[serde(default)]
+    pub turn_id: Option<String>,
+    /// User turns to drop from the end; at least 1
+    #[serde(default)]
+    #[schema(example = 1)]
+    pub num_turns: Option<u32>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RollbackThreadResponse {
+    pub success: bool,
+    /// The thread's last turn after the rollback
+    pub current_turn_id: String,
+    /// User turns dropped
+    pub num_turns: u32,
+    pub status: LongOperationStatus,
+}
+
+/// POST /api/v2/threads/:id/rollback
+///
+/// Drops the turns after `turn_id`, or the last `num_turns` turns, from the
+/// thread's context. Files changed by those turns are not restored. The
+/// first turn is always kept. Waits for the rollback up to a bound,
+/// then answers 202 while it continues in the background; progress is
+/// streamed as `thread/rollingBack`
+#[utoipa::path(
+    post,
+    path = "/api/v2/threads/{id}/rollback",
+    params(
+        ("id" = String, Path, description = "Thread ID")
+    ),
+    request_body = RollbackThreadRequest,
+    responses(
+        (status = 200, description = "Thread rolled back", body = RollbackThreadResponse),
+        (status = 202, description = "Rollback still running in the background", body = RollbackThreadResponse),
+        (status = 400, description = "Invalid request, turn not found in the thread, or more turns than can be dropped"),
+        (status = 404, description = "Thread not found"),
+        (status = 409, description = "A turn is running (`turn_in_progress`)"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Threads"
+)]
+pub async fn rollback_thread(
+    State(state): State<WebServerState>,
+    Path(thread_id): Path<String>,
+    JsonBody(req): JsonBody<RollbackThreadRequest>,
+) -> Result<(StatusCode, Json<RollbackThreadResponse>), ApiError> {
+    let thread_id = ThreadId::from_string(&thread_id)
+        .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
+    let thread = state
+        .thread_manager
+        .get_thread(thread_id)
+        .await
+        .map_err(|_| ApiError::ThreadNotFound)?;
+    check_no_turn_running(&state, thread_id)?;
+
+    let history = match thread.rollout_path() {
+        Some(path) => RolloutRecorder::get_rollout_history(&path)
+            .await
+            .map(|history| history.get_rollout_items()),
+        None => codex_core::load_postgres_rollout_items(thread_id).await,
+    }
+    .map_err(|err| ApiError::InternalError(format!("Failed to read thread history: {err}")))?;
+    let (current_turn_id, num_turns) = match (req.turn_id, req.num_turns) {
+        (Some(turn_id), None) => {
+            let num_turns = codex_core::user_turns_after_turn(&history, &turn_id)
+                .ok_or_else(|| ApiError::InvalidRequest(format!("Turn {turn_id} not found")))?;
+            (turn_id, num_turns)
+        }
+        (None, Some(0)) => {
+            return Err(ApiError::InvalidRequest(
+                "num_turns must be at least 1".to_string(),
+            ));
+        }
+        (None, Some(num_turns)) => (
+            turn_kept_by_rollback(&history, num_turns)?,
+            num_turns as usize,
+        ),
+        _ => {
+            return Err(ApiError::InvalidRequest(
+                "Set exactly one of turn_id and num_turns".to_string(),
+            ));
+        }
+    };
+    let num_turns = u32::try_from(num_turns)
+        .map_err(|_| ApiError::InvalidRequest("Too many turns to roll back".to_string()))?;
+    let mut response = RollbackThreadResponse {
+        success: true,
+        current_turn_id,
+        num_turns,
+        status: LongOperationStatus::Completed,
+    };
+    if num_turns == 0 {
+        return Ok((StatusCode::OK, Json(response)));
+    }
+
+    let operation = LongOperation::Rollback;
+    let completion = state.progress.begin(thread_id, operation);
+    if let Err(err) = thread.submit(Op::ThreadRollback { num_turns }).await {
+        state.progress.abandon(thread_id, operation);
+        return Err(ApiError::InternalError(format!(
+            "Failed to roll back thread: {err}"
+        )));
+    }
+    response.status = state
+        .progress
+        .wait_in_foreground(thread_id, operation, completion)
+        .await;
+    let status = match response.status {
+        LongOperationStatus::Completed => StatusCode::OK,
+        LongOperationStatus::RunningInBackground => StatusCode::ACCEPTED,
+    };
+    Ok((status, Json(response)))
+}
+
+/// The latest turn that dropping the last `num_turns` user turns keeps.
+fn turn_kept_by_rollback(history: &[RolloutItem], num_turns: u32) -> Result<String, ApiError> {
+    let turns = build_turns_from_rollout_items(history);
+    let mut droppable = 0;
+    for turn in turns.iter().rev() {
+        let after = codex_core::user_turns_after_turn(history, &turn.id).unwrap_or_default();
+        if after == num_turns as usize {
+            return Ok(turn.id.clone());
+        }
+        droppable = droppable.max(after);
+    }
+    Err(ApiError::InvalidRequest(format!(
+        "Cannot roll back {num_turns} turns; the thread has {droppable} after its first turn"
+    )))
+}
+
+fn check_no_turn_running(state: &WebServerState, thread_id: ThreadId) -> Result<(), ApiError> {
+    match state.thread_statuses.status(thread_id) {
+        ThreadRunStatus::Running | ThreadRunStatus::WaitingApproval => {
+            Err(ApiError::TurnInProgress)
+        }
+        _ => Ok(()),
+    }
+}
@@ -1,21 +1,102 @@
 use axum::Json;
 use axum::extract::Path;
+use axum::extract::Query;
 use axum::extract::State;
+use codex_core::CodexThread;
+use codex_core::SteerInputError;
+use codex_protocol::ThreadId;
+use codex_protocol::openai_models::ReasoningEffort;
 use codex_protocol::protocol::Op;
 use codex_protocol::user_input::UserInput;
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::Value;
+use std::path::PathBuf;
 use utoipa::ToSchema;
 
+use crate::attachments::AttachmentMetadata;
+use crate::attachments::AttachmentScope;
+use crate::attachments::attachment_input;
+use crate::attachments::resolve_attachment;
+use crate::attachments::store_inline_image;
 use crate::error::ApiError;
+use crate::json_body::JsonBody;
+use crate::model_validation::check_turn_input;
+use crate::model_validation::validate_model;
 use crate::state::WebServerState;
+use crate::thread_status::StatusTransition;
+use crate::thread_status::ThreadRunStatus;
+use crate::turn_attachments;
+use crate::turn_queue::DequeueReason;
+use crate::turn_queue::QueueEntry;
 
-#[derive(Debug, Deserialize, ToSchema)]
+/// `model`, `effort` and `cwd` change the thread's turn context before the
+/// input starts its turn, so they also apply to the turns after it.
+/// `output_schema` applies to this turn only.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
 pub struct SendTurnRequest {
     pub input: Vec<UserInputItem>,
+    #[serde(default)]
+    #[schema(example = "claude-sonnet-4-5")]
+    pub model: Option<String>,
+    #[serde(default)]
+    #[schema(value_type = Option<String>, example = "high")]
+    pub effort: Option<ReasoningEffort>,
+    /// Absolute path; must lie under a trusted root when any are configured
+    #[serde(default)]
+    #[schema(example = "/path/to/project")]
+    pub cwd: Option<String>,
+    /// Accept a `model` missing from the model list, e.g. for a custom
+    /// provider. Length and character limits still apply.
+    #[serde(default)]
+    pub skip_validation: bool,
+    /// JSON Schema the model's final message must follow; must be an object
+    #[serde(default)]
+    #[schema(value_type = Option<Object>, example = json!({
+        "type": "object",
+        "properties": { "summary": { "type": "string" } },
+        "required": ["summary"],
+        "additionalProperties": false
+    }))]
+    pub output_schema: Option<Value>,
 }
 
-#[derive(Debug, Deserialize, ToSchema)]
+/// Checked per-turn settings from a [`SendTurnRequest`].
+#[derive(Debug, Clone, Default)]
+pub struct TurnOverrides {
+    pub model: Option<String>,
+    pub effort: Option<ReasoningEffort>,
+    pub cwd: Option<PathBuf>,
+    /// Sent with the input rather than changing the turn context
+    pub output_schema: Option<Value>,
+}
+
+impl TurnOverrides {
+    pub fn is_empty(&self) -> bool {
+        self.output_schema.is_none() && !self.changes_context()
+    }
+
+    fn changes_context(&self) -> bool {
+        self.model.is_some() || self.effort.is_some() || self.cwd.is_some()
+    }
+
+    fn context_op(&self) -> Op {
+        Op::OverrideTurnContext {
+            cwd: self.cwd.clone(),
+            approval_policy: None,
+            sandbox_policy: None,
+            windows_sandbox_level: None,
+            model: self.model.clone(),
+            effort: self.effort.map(Some),
+            summary: None,
+            collaboration_mode: None,
+            personality: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
 #[serde(tag = "type")]
 pub enum UserInputItem {
     #[serde(rename = "text")]
@@ -28,20 +109,97 @@ pub enum UserInputItem {
         #[schema(example = "019bcfb9-4ea6-72e0-b43d-6b7e26ff0daf")]
         attachment_id: String,
     },
+    /// A small image sent inline, stored as an attachment of the caller
+    #[serde(rename = "image")]
+    Image {
+        /// Base64 of the image, at most 4 MiB once decoded
+        #[schema(
+            example = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk+M9QDwADhgGAWjR9awAAAABJRU5ErkJggg=="
+        )]
+        data: String,
+        #[schema(example = "image/png")]
+        mime_type: String,
+    },
+}
+
+/// Body limit of the routes that take turn input: room for a few inline
+/// images of up to [`crate::attachments::MAX_INLINE_IMAGE_BYTES`] each,
+/// base64 being a third larger than the image.
+pub const MAX_TURN_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct SendTurnParams {
+    /// Hold the input until the running turn finishes instead of failing
+    /// with 409
+    #[serde(default)]
+    pub queue: bool,
+}
+
+/// What happens to input sent while the thread has a running turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WhenBusy {
+    /// Add it to the running turn (v1)
+    Steer,
+    /// Hold it until the running turn finishes (`?queue=true`)
+    Queue,
+    /// Fail with `409 Conflict` naming the running turn
+    Reject,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
 pub struct SendTurnResponse {
+    /// Matches `turn.id` on the `turn/started` and `turn/completed` events
+    /// for this input. `null` while the input is queued; `turn/dequeued`
+    /// carries the id once it starts.
     #[schema(example = "turn-12345")]
-    pub turn_id: String,
+    pub turn_id: Option<String>,
+    /// `true` when the input joined the turn already in progress instead of
+    /// starting a new one.
+    pub steered: bool,
+    /// Set when the input was queued behind the running turn
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queue_entry_id: Option<String>,
+    /// Model the turn runs with: the `model` override, otherwise the thread's
+    /// current model
+    #[schema(example = "claude-sonnet-4-5")]
+    pub model: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TurnQueueResponse {
+    /// Queued inputs, oldest first
+    pub entries: Vec<QueueEntry>,
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
-pub struct InterruptTurnRequest {}
+#[serde(deny_unknown_fields)]
+pub struct SteerTurnRequest {
+    pub input: Vec<UserInputItem>,
+    /// Steer only if this turn is the one running; otherwise 409
+    #[serde(default)]
+    pub turn_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SteerTurnResponse {
+    /// The running turn the input joined
+    #[schema(example = "turn-12345")]
+    pub turn_id: String,
+}
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct InterruptTurnRequest {
+    /// Interrupt only if this turn is the one running; otherwise 409
+    #[serde(default)]
+    pub turn_id: Option<String>,
+}
 
 #[derive(Debug, Serialize, ToSchema)]
 pub struct InterruptTurnResponse {
     pub success: bool,
+    /// The turn that was running, or `null` when the thread was idle
+    pub interrupted_turn_id: Option<String>,
 }
 
 #[utoipa::path(
@@ -49,13 +207,18 @@ pub struct InterruptTurnResponse {
     path = "/api/v2/threads/{thread_id}/turns",
     request_body = SendTurnRequest,
     params(
-        ("thread_id" = String, Path, description = "Thread ID")
+        ("thread_id" = String, Path, description = "Thread ID"),
+        ("queue" = Option<bool>, Query, description = "Queue the input while a turn is running instead of failing with 409")
     ),
     responses(
         (status = 200, description = "Turn submitted successfully", body = SendTurnResponse),
-        (status = 400, description = "Invalid request"),
+        (status = 400, description = "Invalid request, an attachment that is neither an image nor text, or a `model` missing from the model list (`model_not_found`)"),
         (status = 401, description = "Unauthorized"),
+        (status = 403, description = "`cwd` is outside the trusted roots (`untrusted_cwd`)"),
         (status = 404, description = "Thread not found"),
+        (status = 409, description = "Thread was archived or resumed while the request was in progress (`thread_state_changed`), or a turn is running and `queue` is not set (`turn_in_progress`, with `active_turn_id`)"),
+        (status = 415, description = "Body is not `application/json` (`unsupported_media_type`)"),
+        (status = 422, description = "Body has an unknown or mistyped field (`invalid_body`, with `path`), or the turn's model does not accept image attachments (`unsupported_input_modality`)"),
         (status = 500, description = "Internal server error")
     ),
     security(
@@ -65,70 +228,477 @@ pub struct InterruptTurnResponse {
 )]
 pub async fn send_turn(
     State(state): State<WebServerState>,
+    scope: AttachmentScope,
     Path(thread_id): Path<String>,
-    Json(req): Json<SendTurnRequest>,
+    Query(params): Query<SendTurnParams>,
+    JsonBody(req): JsonBody<SendTurnRequest>,
 ) -> Result<Json<SendTurnResponse>, ApiError> {
-    let thread_id = codex_protocol::ThreadId::from_string(&thread_id)
+    let when_busy = if params.queue {
+        WhenBusy::Queue
+    } else {
+        WhenBusy::Reject
+    };
+    send_turn_inner(&state, &scope, &thread_id, when_busy, req)
+        .await
+        .map(Json)
+}
+
+/// Resolves `input`, then submits it, or handles it as `when_busy` says if a
+/// turn is running. Shared by the v1 and v2 routes and JSON-RPC `turn/start`.
+pub async fn send_turn_inner(
+    state: &WebServerState,
+    scope: &AttachmentScope,
+    thread_id: &str,
+    when_busy: WhenBusy,
+    mut req: SendTurnRequest,
+) -> Result<SendTurnResponse, ApiError> {
+    let thread_id = codex_protocol::ThreadId::from_string(thread_id)
         .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
 
+    let op = state.thread_ops.begin(thread_id);
     let thread = state
         .thread_manager
         .get_thread(thread_id)
         .await
         .map_err(|_| ApiError::ThreadNotFound)?;
 
+    let overrides = check_turn_overrides(state, &req).await?;
+    store_inline_images(state, scope, &mut req.input).await?;
+    let user_inputs = resolve_user_inputs(state, scope, &req.input).await?;
+    let model = match &overrides.model {
+        Some(model) => model.clone(),
+        None => thread.config_snapshot().await.model,
+    };
+    check_turn_input(state, &model, &user_inputs).await?;
+
+    // Held only while the input is queued or submitted, not for the turn.
+    let _guard = op.lock().await?;
+    let running = matches!(
+        state.thread_statuses.status(thread_id),
+        ThreadRunStatus::Running | ThreadRunStatus::WaitingApproval
+    );
+    if running {
+        match when_busy {
+            WhenBusy::Queue => {
+                let entry = state
+                    .turn_queue
+                    .enqueue(thread_id, req.input, user_inputs, overrides);
+                return Ok(SendTurnResponse {
+                    turn_id: None,
+                    steered: false,
+                    queue_entry_id: Some(entry.queue_entry_id),
+                    model,
+                });
+            }
+            WhenBusy::Reject => {
+                return Err(ApiError::TurnRunning {
+                    active_turn_id: state.thread_statuses.active_turn(thread_id),
+                });
+            }
+            // Steered input joins a turn whose context is already fixed.
+            WhenBusy::Steer if !overrides.is_empty() => {
+                return Err(ApiError::TurnInProgress);
+            }
+            WhenBusy::Steer => {}
+        }
+    }
+
+    let (turn_id, steered) =
+        submit_user_input(state, thread_id, &thread, user_inputs, &overrides).await?;
+
+    Ok(SendTurnResponse {
+        turn_id: Some(turn_id),
+        steered,
+        queue_entry_id: None,
+        model,
+    })
+}
+
+/// Validates the `model` and `cwd` overrides of `req` so a bad value fails
+/// the request instead of the turn.
+async fn check_turn_overrides(
+    state: &WebServerState,
+    req: &SendTurnRequest,
+) -> Result<TurnOverrides, ApiError> {
+    if let Some(model) = &req.model {
+        validate_model(state, model, req.skip_validation).await?;
+    }
+    if let Some(schema) = &req.output_schema
+        && !schema.is_object()
+    {
+        return Err(ApiError::InvalidRequest(
+            "output_schema must be a JSON object".to_string(),
+        ));
+    }
+    let cwd = match &req.cwd {
+        Some(cwd) => {
+            let cwd = PathBuf::from(cwd);
+            if !cwd.is_absolute() {
+                return Err(ApiError::InvalidRequest(
+                    "cwd must be an absolute path".to_string(),
+                ));
+            }
+            let cwd = state.trusted_roots.check(&cwd)?;
+            if !tokio::fs::metadata(&cwd)
+                .await
+                .is_ok_and(|metadata| metadata.is_dir())
+            {
+                return Err(ApiError::InvalidRequest(
+                    "cwd is not a directory".to_string(),
+                ));
+            }
+            Some(cwd)
+        }
+        None => None,
+    };
+    Ok(TurnOverrides {
+        model: req.model.clone(),
+        effort: req.effort,
+        cwd,
+        output_schema: req.output_schema.clone(),
+    })
+}
+
+/// Converts request items to core inputs. An attachment must be readable
+/// by `scope`, see [`resolve_attachment`], and an image or text, see
+/// [`attachment_input`]. Inline images are stored first, see
+/// [`store_inline_image`].
+pub async fn resolve_user_inputs(
+    state: &WebServerState,
+    scope: &AttachmentScope,
+    items: &[UserInputItem],
+) -> Result<Vec<UserInput>, ApiError> {
     let mut user_inputs = Vec::new();
 
-    for item in req.input {
+    for item in items {
         match item {
             UserInputItem::Text { text } => {
                 user_inputs.push(UserInput::Text {
-                    text,
+                    text: text.clone(),
                     text_elements: Vec::new(),
                 });
             }
             UserInputItem::Attachment { attachment_id } => {
-                uuid::Uuid::parse_str(&attachment_id).map_err(|_| {
-                    ApiError::InvalidRequest("Invalid attachment ID format".to_string())
-                })?;
-
-                let attachment_path = state.attachments_dir.join(&attachment_id);
-                if !attachment_path.exists() {
-                    return Err(ApiError::AttachmentNotFound);
-                }
-
-                let canonical_path = attachment_path
-                    .canonicalize()
-                    .map_err(|_| ApiError::AttachmentNotFound)?;
-                let canonical_attachments_dir =
-                    state.attachments_dir.canonicalize().map_err(|e| {
-                        ApiError::InternalError(format!(
-                            "Failed to resolve attachments directory: {e}"
-                        ))
-                    })?;
-
-                if !canonical_path.starts_with(&canonical_attachments_dir) {
-                    return Err(ApiError::InvalidRequest(
-                        "Invalid attachment path".to_string(),
-                    ));
-                }
-
-                user_inputs.push(UserInput::LocalImage {
-                    path: canonical_path,
-                });
+                let resolved = resolve_attachment(state, scope, attachment_id).await?;
+                user_inputs.push(attachment_input(attachment_id, resolved).await?);
+            }
+            UserInputItem::Image { data, mime_type } => {
+                let attachment_id = store_inline_image(state, scope, data, mime_type).await?;
+                let resolved = resolve_attachment(state, scope, &attachment_id).await?;
+                user_inputs.push(attachment_input(&attachment_id, resolved).await?);
             }
         }
     }
 
+    Ok(user_inputs)
+}
+
+/// Stores the inline images of `items` as attachments of `scope` and
+/// replaces them with references to those, so queued input lists an id
+/// instead of the image data.
+pub async fn store_inline_images(
+    state: &WebServerState,
+    scope: &AttachmentScope,
+    items: &mut [UserInputItem],
+) -> Result<(), ApiError> {
+    for item in items {
+        if let UserInputItem::Image { data, mime_type } = item {
+            let attachment_id = store_inline_image(state, scope, data, mime_type).await?;
+            *item = UserInputItem::Attachment { attachment_id };
+        }
+    }
+    Ok(())
+}
+
+/// Starts the oldest queued input once the thread's turn has finished.
+/// Called by the event stream that observed the turn end.
+pub(crate) async fn start_next_queued(
+    state: &WebServerState,
+    thread_id: ThreadId,
+    thread: &CodexThread,
+) {
+    if matches!(
+        state.thread_statuses.status(thread_id),
+        ThreadRunStatus::Running | ThreadRunStatus::WaitingApproval
+    ) {
+        return;
+    }
+    let op = state.thread_ops.begin(thread_id);
+    let Ok(_guard) = op.lock().await else {
+        return;
+    };
+    let Some(queued) = state.turn_queue.pop(thread_id) else {
+        return;
+    };
+    match submit_user_input(state, thread_id, thread, queued.items, &queued.overrides).await {
+        Ok((turn_id, _)) => state.turn_queue.finish(
+            thread_id,
+            &queued.entry,
+            DequeueReason::Started,
+            Some(turn_id),
+        ),
+        Err(err) => {
+            tracing::warn!(
+                "Failed to start queued input {} on thread {thread_id}: {err:?}",
+                queued.entry.queue_entry_id
+            );
+            state
+                .turn_queue
+                .finish(thread_id, &queued.entry, DequeueReason::Failed, None);
+        }
+    }
+}
+
+/// GET /api/v2/threads/{thread_id}/queue
+///
+/// Lists inputs waiting for the running turn to finish
+#[utoipa::path(
+    get,
+    path = "/api/v2/threads/{thread_id}/queue",
+    params(
+        ("thread_id" = String, Path, description = "Thread ID")
+    ),
+    responses(
+        (status = 200, description = "Queued inputs", body = TurnQueueResponse),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Thread not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Turns"
+)]
+pub async fn list_queue(
+    State(state): State<WebServerState>,
+    Path(thread_id): Path<String>,
+) -> Result<Json<TurnQueueResponse>, ApiError> {
+    let thread_id = codex_protocol::ThreadId::from_string(&thread_id)
+        .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
+    state
+        .thread_manager
+        .get_thread(thread_id)
+        .await
+        .map_err(|_| ApiError::ThreadNotFound)?;
+
+    Ok(Json(TurnQueueResponse {
+        entries: state.turn_queue.list(thread_id),
+    }))
+}
+
+/// DELETE /api/v2/threads/{thread_id}/queue/{entry_id}
+///
+/// Drops a queued input before it runs
+#[utoipa::path(
+    delete,
+    path = "/api/v2/threads/{thread_id}/queue/{entry_id}",
+    params(
+        ("thread_id" = String, Path, description = "Thread ID"),
+        ("entry_id" = String, Path, description = "Queue entry ID")
+    ),
+    responses(
+        (status = 200, description = "Queued input removed", body = QueueEntry),
+        (status = 400, description = "Invalid request"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Thread or queue entry not found")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Turns"
+)]
+pub async fn cancel_queued(
+    State(state): State<WebServerState>,
+    Path((thread_id, entry_id)): Path<(String, String)>,
+) -> Result<Json<QueueEntry>, ApiError> {
+    let thread_id = codex_protocol::ThreadId::from_string(&thread_id)
+        .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
+
+    state
+        .turn_queue
+        .cancel(thread_id, &entry_id)
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("Queue entry not found: {entry_id}")))
+}
+
+/// Starts a turn for `items`, or adds them to the turn already running.
+///
+/// Core folds input that arrives mid-turn into the active turn, so its events
+/// never carry the id `submit` would return. Steering first yields the id
+/// the events actually use. Returns that id and whether the input was
+/// steered. Input with `overrides`, including an output schema, always starts
+/// a new turn; callers only submit it while the thread is idle.
+pub(crate) async fn submit_user_input(
+    state: &WebServerState,
+    thread_id: ThreadId,
+    thread: &CodexThread,
+    items: Vec<UserInput>,
+    overrides: &TurnOverrides,
+) -> Result<(String, bool), ApiError> {
+    if items.is_empty() {
+        return Err(ApiError::InvalidRequest(
+            "Turn input must not be empty".to_string(),
+        ));
+    }
+    let attachments = turn_attachments::attachments_in(&items).await;
+    let (turn_id, steered) = submit_items(state, thread_id, thread, items, overrides).await?;
+    record_attachments(state, thread_id, &turn_id, attachments).await;
+    Ok((turn_id, steered))
+}
+
+async fn submit_items(
+    state: &WebServerState,
+    thread_id: ThreadId,
+    thread: &CodexThread,
+    items: Vec<UserInput>,
+    overrides: &TurnOverrides,
+) -> Result<(String, bool), ApiError> {
+    if !overrides.is_empty() {
+        // Core applies ops in order, so the new turn starts with the override.
+        if overrides.changes_context() {
+            thread.submit(overrides.context_op()).await.map_err(|e| {
+                ApiError::InternalError(format!("Failed to override turn context: {e}"))
+            })?;
+        }
+        return start_turn(
+            state,
+            thread_id,
+            thread,
+            items,
+            overrides.output_schema.clone(),
+        )
+        .await;
+    }
+    let items = match thread.steer_input(items, None).await {
+        Ok(active_turn_id) => return Ok((active_turn_id, true)),
+        Err(SteerInputError::NoActiveTurn(items)) => items,
+        Err(SteerInputError::EmptyInput) => {
+            return Err(ApiError::InvalidRequest(
+                "Turn input must not be empty".to_string(),
+            ));
+        }
+        Err(SteerInputError::ExpectedTurnMismatch { expected, actual }) => {
+            return Err(ApiError::InternalError(format!(
+                "Active turn changed from {expected} to {actual}"
+            )));
+        }
+    };
+    start_turn(state, thread_id, thread, items, None).await
+}
+
+/// Journals the attachments of input that became part of `turn_id`. A
+/// failure only costs the history its attachment listing.
+async fn record_attachments(
+    state: &WebServerState,
+    thread_id: ThreadId,
+    turn_id: &str,
+    attachments: Vec<(PathBuf, AttachmentMetadata)>,
+) {
+    if let Err(err) =
+        turn_attachments::record(&state.codex_home, thread_id, turn_id, attachments).await
+    {
+        tracing::warn!("Failed to record attachments of turn {turn_id}: {err}");
+    }
+}
+
+async fn start_turn(
+    state: &WebServerState,
+    thread_id: ThreadId,
+    thread: &CodexThread,
+    items: Vec<UserInput>,
+    final_output_json_schema: Option<Value>,
+) -> Result<(String, bool), ApiError> {
     let turn_id: String = thread
         .submit(Op::UserInput {
-            items: user_inputs,
-            final_output_json_schema: None,
+            items,
+            final_output_json_schema,
         })
         .await
         .map_err(|e| ApiError::InternalError(format!("Failed to submit turn: {e}")))?;
 
-    Ok(Json(SendTurnResponse { turn_id }))
+    state.turn_spans.turn_started(thread_id, &turn_id);
+    state.thread_statuses.apply(
+        thread_id,
+        StatusTransition::TurnStarted {
+            turn_id: turn_id.clone(),
+        },
+    );
+
+    if let Some(store) = &state.state_store
+        && let Err(e) = store.save_turn(thread_id, &turn_id).await
+    {
+        tracing::warn!("Failed to persist pending turn: {}", e);
+    }
+    // Started last, so a pump this starts sees the turn's span and status.
+    state.hold_event_pump(thread_id).await;
+
+    Ok((turn_id, false))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v2/threads/{thread_id}/turns/steer",
+    request_body = SteerTurnRequest,
+    params(
+        ("thread_id" = String, Path, description = "Thread ID")
+    ),
+    responses(
+        (status = 200, description = "Input added to the running turn", body = SteerTurnResponse),
+        (status = 400, description = "Invalid request, empty input, or an attachment that is neither an image nor text"),
+        (status = 401, description = "Unauthorized"),
+        (status = 404, description = "Thread not found"),
+        (status = 409, description = "No turn is running (`no_active_turn`), or `turn_id` is not the running turn (`turn_mismatch`)"),
+        (status = 422, description = "The turn's model does not accept image attachments (`unsupported_input_modality`)"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Turns"
+)]
+pub async fn steer_turn(
+    State(state): State<WebServerState>,
+    scope: AttachmentScope,
+    Path(thread_id): Path<String>,
+    JsonBody(mut req): JsonBody<SteerTurnRequest>,
+) -> Result<Json<SteerTurnResponse>, ApiError> {
+    let thread_id = codex_protocol::ThreadId::from_string(&thread_id)
+        .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
+
+    let op = state.thread_ops.begin(thread_id);
+    let thread = state
+        .thread_manager
+        .get_thread(thread_id)
+        .await
+        .map_err(|_| ApiError::ThreadNotFound)?;
+
+    store_inline_images(&state, &scope, &mut req.input).await?;
+    let user_inputs = resolve_user_inputs(&state, &scope, &req.input).await?;
+    let model = thread.config_snapshot().await.model;
+    check_turn_input(&state, &model, &user_inputs).await?;
+
+    let _guard = op.lock().await?;
+    let attachments = turn_attachments::attachments_in(&user_inputs).await;
+    let turn_id = match thread
+        .steer_input(user_inputs, req.turn_id.as_deref())
+        .await
+    {
+        Ok(turn_id) => turn_id,
+        Err(SteerInputError::NoActiveTurn(_)) => return Err(ApiError::NoActiveTurn),
+        Err(SteerInputError::EmptyInput) => {
+            return Err(ApiError::InvalidRequest(
+                "Turn input must not be empty".to_string(),
+            ));
+        }
+        Err(SteerInputError::ExpectedTurnMismatch { actual, .. }) => {
+            return Err(ApiError::TurnMismatch {
+                active_turn_id: Some(actual),
+            });
+        }
+    };
+    record_attachments(&state, thread_id, &turn_id, attachments).await;
+
+    Ok(Json(SteerTurnResponse { turn_id }))
 }
 
 #[utoipa::path(
@@ -143,6 +713,7 @@ pub async fn send_turn(
         (status = 400, description = "Invalid request"),
         (status = 401, description = "Unauthorized"),
         (status = 404, description = "Thread not found"),
+        (status = 409, description = "`turn_id` is not the running turn (`turn_mismatch`)"),
         (status = 500, description = "Internal server error")
     ),
     security(
@@ -153,7 +724,7 @@ pub async fn send_turn(
 pub async fn interrupt_turn(
     State(state): State<WebServerState>,
     Path(thread_id): Path<String>,
-    Json(_req): Json<InterruptTurnRequest>,
+    JsonBody(req): JsonBody<InterruptTurnRequest>,
 ) -> Result<Json<InterruptTurnResponse>, ApiError> {
     let thread_id = codex_protocol::ThreadId::from_string(&thread_id)
         .map_err(|_| ApiError::InvalidRequest("Invalid thread ID".to_string()))?;
@@ -164,10 +735,22 @@ pub async fn interrupt_turn(
         .await
         .map_err(|_| ApiError::ThreadNotFound)?;
 
+    let active_turn_id = state.thread_statuses.active_turn(thread_id);
+    if let Some(turn_id) = &req.turn_id
+        && active_turn_id.as_ref() != Some(turn_id)
+    {
+        return Err(ApiError::TurnMismatch { active_turn_id });
+    }
+
+    // Submitted even when the thread looks idle: a turn started by another
+    // client may not have been seen yet.
     thread
         .submit(Op::Interrupt)
         .await
         .map_err(|e| ApiError::InternalError(format!("Failed to interrupt turn: {e}")))?;
 
-    Ok(Json(InterruptTurnResponse { success: true }))
+    Ok(Json(InterruptTurnResponse {
+        success: true,
+        interrupted_turn_id: active_turn_id,
+    }))
 }
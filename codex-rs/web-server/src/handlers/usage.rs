@@ -0,0 +1,197 @@
+use axum::Json;
+use axum::extract::Query;
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::http::header;
+use axum::response::IntoResponse;
+use axum::response::Response;
+use chrono::Days;
+use chrono::NaiveDate;
+use chrono::Utc;
+use codex_protocol::ThreadId;
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+use std::time::SystemTime;
+use utoipa::ToSchema;
+
+use crate::context_window;
+use crate::error::ApiError;
+use crate::state::WebServerState;
+use crate::storage;
+use crate::usage::UsageGroup;
+use crate::usage::UsageGroupBy;
+use crate::usage::UsageSample;
+use crate::usage::UsageTotals;
+use crate::usage::aggregate;
+use crate::usage::samples_from_rollout;
+use crate::usage::to_csv;
+
+/// Days covered when `from` is omitted, counting `to`.
+const DEFAULT_REPORT_DAYS: u64 = 30;
+
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct UsageParams {
+    /// First UTC day to include, `YYYY-MM-DD` (default: 29 days before `to`)
+    #[serde(default)]
+    pub from: Option<String>,
+    /// Last UTC day to include, `YYYY-MM-DD` (default: today)
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(default)]
+    pub group_by: UsageGroupBy,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UsageReport {
+    pub from: String,
+    pub to: String,
+    pub group_by: UsageGroupBy,
+    /// Ordered by key
+    pub groups: Vec<UsageGroup>,
+    pub total: UsageTotals,
+}
+
+/// GET /api/v2/usage
+///
+/// Aggregates token usage across threads by day, model or thread, with an
+/// estimated cost when pricing is configured, and the context meter of loaded
+/// threads when grouped by thread. Returns CSV when the request accepts
+/// `text/csv`
+#[utoipa::path(
+    get,
+    path = "/api/v2/usage",
+    params(
+        ("from" = Option<String>, Query, description = "First UTC day, YYYY-MM-DD (default: 29 days before `to`)"),
+        ("to" = Option<String>, Query, description = "Last UTC day, YYYY-MM-DD (default: today)"),
+        ("group_by" = Option<UsageGroupBy>, Query, description = "day, model or thread (default: day)")
+    ),
+    responses(
+        (status = 200, description = "Usage report, as JSON or CSV", body = UsageReport),
+        (status = 400, description = "Invalid date range"),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("bearer_auth" = [])
+    ),
+    tag = "Maintenance"
+)]
+pub async fn get_usage(
+    State(state): State<WebServerState>,
+    Query(params): Query<UsageParams>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let to = match params.to.as_deref() {
+        Some(to) => parse_day("to", to)?,
+        None => Utc::now().date_naive(),
+    };
+    let from = match params.from.as_deref() {
+        Some(from) => Some(parse_day("from", from)?),
+        None => to.checked_sub_days(Days::new(DEFAULT_REPORT_DAYS - 1)),
+    };
+    let day_start = |day: NaiveDate| {
+        day.and_hms_opt(0, 0, 0)
+            .map(|time| time.and_utc().timestamp())
+    };
+    let start = from.and_then(day_start);
+    let end = to.checked_add_days(Days::new(1)).and_then(day_start);
+    let (Some(from), Some(start), Some(end)) = (from, start, end) else {
+        return Err(ApiError::InvalidRequest("Date out of range".to_string()));
+    };
+    if from > to {
+        return Err(ApiError::InvalidRequest(
+            "from must not be after to".to_string(),
+        ));
+    }
+
+    let samples = match &state.state_store {
+        Some(store) => store
+            .usage_between(start, end)
+            .await
+            .map_err(|err| ApiError::InternalError(err.to_string()))?
+            .into_iter()
+            .map(|usage| UsageSample {
+                thread_id: usage.thread_id,
+                recorded_at: usage.recorded_at,
+                model: Some(usage.model),
+                usage: usage.usage,
+            })
+            .collect(),
+        None => {
+            let codex_home = state.codex_home.clone();
+            tokio::task::spawn_blocking(move || rollout_samples(&codex_home, start, end))
+                .await
+                .map_err(|err| ApiError::InternalError(err.to_string()))?
+        }
+    };
+    let (mut groups, total) = aggregate(&samples, params.group_by, &state.model_pricing);
+    if params.group_by == UsageGroupBy::Thread {
+        for group in &mut groups {
+            if let Ok(thread_id) = ThreadId::from_string(&group.key)
+                && let Ok(thread) = state.thread_manager.get_thread(thread_id).await
+            {
+                group.context = context_window::for_thread(&thread).await;
+            }
+        }
+    }
+
+    let wants_csv = headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/csv"));
+    if wants_csv {
+        return Ok((
+            [(header::CONTENT_TYPE, "text/csv; charset=utf-8")],
+            to_csv(&groups, params.group_by),
+        )
+            .into_response());
+    }
+    Ok(Json(UsageReport {
+        from: from.to_string(),
+        to: to.to_string(),
+        group_by: params.group_by,
+        groups,
+        total,
+    })
+    .into_response())
+}
+
+fn parse_day(name: &str, value: &str) -> Result<NaiveDate, ApiError> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| ApiError::InvalidRequest(format!("{name} must be a date as YYYY-MM-DD")))
+}
+
+/// Samples in `[start, end)` from live and archived rollout files. Files last
+/// written before `start` are skipped unread.
+fn rollout_samples(codex_home: &Path, start: i64, end: i64) -> Vec<UsageSample> {
+    let cutoff = u64::try_from(start)
+        .ok()
+        .map(|secs| SystemTime::UNIX_EPOCH + Duration::from_secs(secs));
+    let mut samples = Vec::new();
+    for file in storage::scan_rollouts(codex_home, true) {
+        let Some(thread_id) = file.thread_id else {
+            continue;
+        };
+        if let (Some(cutoff), Some(modified)) = (cutoff, file.modified)
+            && modified < cutoff
+        {
+            continue;
+        }
+        let contents = match std::fs::read_to_string(&file.path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                tracing::warn!("Failed to read {}: {err}", file.path.display());
+                continue;
+            }
+        };
+        samples.extend(
+            samples_from_rollout(thread_id, &contents)
+                .into_iter()
+                .filter(|sample| (start..end).contains(&sample.recorded_at)),
+        );
+    }
+    samples.sort_by_key(|sample| sample.recorded_at);
+    samples
+}
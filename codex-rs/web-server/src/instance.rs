@@ -0,0 +1,38 @@
+use std::sync::LazyLock;
+use uuid::Uuid;
+
+/// Response/request header carrying the id of the instance that owns a thread.
+///
+/// Load balancers can key sticky routing on this header: every response from a
+/// protected route carries it, and clients echo it back on follow-up requests.
+pub const INSTANCE_HEADER: &str = "x-codex-instance";
+
+/// Optional stable instance id (e.g. a StatefulSet pod name). When unset the
+/// id is random per boot.
+pub const INSTANCE_ID_ENV: &str = "CODEX_WEB_INSTANCE_ID";
+
+static CONFIGURED_INSTANCE_ID: LazyLock<Option<String>> = LazyLock::new(|| {
+    std::env::var(INSTANCE_ID_ENV)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+});
+
+static INSTANCE_ID: LazyLock<String> = LazyLock::new(|| {
+    CONFIGURED_INSTANCE_ID
+        .clone()
+        .unwrap_or_else(|| Uuid::new_v4().to_string())
+});
+
+/// Identifier for this server process: `CODEX_WEB_INSTANCE_ID` when set,
+/// otherwise regenerated on every boot.
+pub fn instance_id() -> &'static str {
+    INSTANCE_ID.as_str()
+}
+
+/// The instance id configured through `CODEX_WEB_INSTANCE_ID`, if any. Only a
+/// configured id survives restarts, so only it can identify state written by a
+/// previous run of this same instance.
+pub fn configured_instance_id() -> Option<&'static str> {
+    CONFIGURED_INSTANCE_ID.as_deref()
+}
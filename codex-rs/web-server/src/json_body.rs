@@ -0,0 +1,83 @@
+//! `JsonBody<T>`, the request-body extractor for mutating endpoints.
+//!
+//! Unlike axum's `Json`, every rejection is an [`ApiError`] with the usual
+//! JSON error body: a missing or non-JSON `Content-Type` is a 415, and a body
+//! that parses but does not match `T` is a 422 whose `path` names the
+//! offending field, e.g. `input[0].text`. Anything after the JSON value,
+//! other than whitespace, is rejected rather than ignored.
+
+use axum::body::Bytes;
+use axum::extract::FromRequest;
+use axum::extract::Request;
+use axum::http::HeaderMap;
+use axum::http::header::CONTENT_TYPE;
+use serde::de::DeserializeOwned;
+use serde_json::error::Category;
+
+use crate::error::ApiError;
+
+/// A JSON request body deserialized as `T`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonBody<T>(pub T);
+
+impl<T, S> FromRequest<S> for JsonBody<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !has_json_content_type(req.headers()) {
+            return Err(ApiError::UnsupportedMediaType);
+        }
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(|rejection| ApiError::InvalidRequest(rejection.body_text()))?;
+        parse(&bytes).map(JsonBody)
+    }
+}
+
+/// Whether `Content-Type` is `application/json` or an `application/*+json`
+/// type, with any parameters.
+fn has_json_content_type(headers: &HeaderMap) -> bool {
+    let Some(content_type) = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    essence == "application/json"
+        || essence
+            .strip_prefix("application/")
+            .is_some_and(|subtype| subtype.ends_with("+json"))
+}
+
+/// Deserializes all of `bytes` as `T`. Malformed JSON, including trailing
+/// data, is a 400; JSON of the wrong shape is a 422 with the field's path.
+pub fn parse<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, ApiError> {
+    let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+    let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|err| {
+        let path = err.path().to_string();
+        let err = err.into_inner();
+        match err.classify() {
+            Category::Data => ApiError::InvalidBody {
+                path,
+                message: err.to_string(),
+            },
+            Category::Syntax | Category::Eof | Category::Io => {
+                ApiError::InvalidRequest(format!("Malformed JSON body: {err}"))
+            }
+        }
+    })?;
+    deserializer
+        .end()
+        .map_err(|err| ApiError::InvalidRequest(format!("Malformed JSON body: {err}")))?;
+    Ok(value)
+}
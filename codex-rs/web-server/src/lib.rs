@@ -1,10 +1,56 @@
 // Library interface for codex-web-server
 // Exposes types and functions for testing
 
+pub mod account_login;
+pub mod api_version;
 pub mod approval_manager;
+pub mod attachment_gc;
 pub mod attachments;
+pub mod auto_archive;
+pub mod config_loader;
+pub mod context_window;
+pub mod cors;
+pub mod credential_store;
 pub mod error;
+pub mod event_filter;
+pub mod event_ids;
+pub mod event_pump;
+pub mod event_replay;
 pub mod event_stream;
+pub mod execpolicy_amendments;
+pub mod firehose;
 pub mod handlers;
+pub mod instance;
+pub mod json_body;
+pub mod mcp_snapshot;
+pub mod metrics;
 pub mod middleware;
+pub mod mock_model;
+pub mod model_validation;
+pub mod notifications;
+pub mod pairing;
+pub mod patches;
+pub mod permissions;
+pub mod provider_overrides;
+pub mod requirements;
+pub mod review_registry;
+pub mod rollout_archive;
+pub mod rollout_import;
+pub mod rollout_tail;
+pub mod skills_listing;
+pub mod sse_envelope;
+pub mod startup_checks;
 pub mod state;
+pub mod storage;
+pub mod telemetry;
+pub mod thread_export;
+pub mod thread_memory;
+pub mod thread_ops;
+pub mod thread_prefs;
+pub mod thread_progress;
+pub mod thread_scan;
+pub mod thread_status;
+pub mod turn_attachments;
+pub mod turn_queue;
+pub mod usage;
+pub mod workspace_trust;
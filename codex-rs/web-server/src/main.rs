@@ -1,21 +1,69 @@
+mod account_login;
+mod api_version;
 mod approval_manager;
+mod attachment_gc;
 mod attachments;
+mod auto_archive;
+mod config_loader;
+mod context_window;
+mod cors;
+mod credential_store;
 mod error;
+mod event_filter;
+mod event_ids;
+mod event_pump;
+mod event_replay;
 mod event_stream;
+mod execpolicy_amendments;
+mod firehose;
 mod handlers;
+mod instance;
+mod json_body;
+mod mcp_snapshot;
+mod metrics;
 mod middleware;
+mod mock_model;
+mod model_validation;
+mod notifications;
+mod pairing;
+mod patches;
+mod permissions;
+mod provider_overrides;
+mod requirements;
+mod review_registry;
+mod rollout_archive;
+mod rollout_import;
+mod rollout_tail;
+mod skills_listing;
+mod sse_envelope;
+mod startup_checks;
 mod state;
+mod storage;
+mod telemetry;
+mod thread_export;
+mod thread_memory;
+mod thread_ops;
+mod thread_prefs;
+mod thread_progress;
+mod thread_scan;
+mod thread_status;
+mod turn_attachments;
+mod turn_queue;
+mod usage;
+mod workspace_trust;
 
 use anyhow::Context;
 use axum::Json;
 use axum::Router;
-use axum::http::HeaderValue;
+use axum::extract::DefaultBodyLimit;
 use axum::middleware::from_fn_with_state;
+use axum::routing::delete;
 use axum::routing::get;
 use axum::routing::patch;
 use axum::routing::post;
 use axum::routing::put;
 use codex_core::ThreadManager;
+use codex_core::WebStateStore;
 use codex_core::auth::AuthManager;
 use codex_core::config::service::ConfigService;
 use codex_core::config_loader::CloudRequirementsLoader;
@@ -23,15 +71,37 @@ use codex_protocol::protocol::SessionSource;
 use serde_json::Value;
 use serde_json::json;
 use std::sync::Arc;
-use tower_http::cors::Any;
-use tower_http::cors::CorsLayer;
 use utoipa::OpenApi;
 #[cfg(feature = "swagger-ui")]
 use utoipa_swagger_ui::SwaggerUi;
 use uuid::Uuid;
 
+use crate::api_version::ApiVersionPolicy;
+use crate::api_version::api_version_middleware;
+use crate::approval_manager::approval_timeout_from_env;
+use crate::approval_manager::spawn_approval_sweeper;
+use crate::attachment_gc::AttachmentGcPolicy;
+use crate::attachments::AttachmentLimits;
+use crate::auto_archive::AutoArchivePolicy;
+use crate::cors::CorsOrigins;
+use crate::credential_store::CredentialStoreSettings;
+use crate::mcp_snapshot::DEFAULT_PER_SERVER_TIMEOUT;
+use crate::middleware::admin_middleware;
 use crate::middleware::auth_middleware;
+use crate::middleware::instance_affinity_middleware;
+use crate::mock_model::MockModelServer;
+use crate::mock_model::MockScript;
+use crate::notifications::NotificationsConfig;
+use crate::notifications::Notifier;
+use crate::provider_overrides::ProviderHosts;
+use crate::rollout_import::ImportLimits;
+use crate::skills_listing::skills_timeout_from_env;
 use crate::state::WebServerState;
+use crate::thread_memory::thread_memory_budget_from_env;
+use crate::thread_scan::scan_limit_from_env;
+use crate::thread_scan::scan_on_startup_from_env;
+use crate::usage::ModelPricing;
+use crate::workspace_trust::TrustedRoots;
 
 #[derive(OpenApi)]
 #[openapi(
@@ -39,17 +109,38 @@ use crate::state::WebServerState;
         handlers::create_thread,
         handlers::send_turn,
         handlers::stream_events,
+        handlers::stream_all_events,
         handlers::threads::create_thread,
         handlers::threads::list_threads,
+        handlers::threads::get_thread,
+        handlers::threads::update_thread,
         handlers::threads::archive_thread,
+        handlers::threads::delete_thread,
         handlers::threads::resume_thread,
         handlers::threads::fork_thread,
+        handlers::threads::list_thread_items,
+        handlers::threads::export_thread,
+        handlers::threads::import_thread,
+        handlers::threads::batch_thread_status,
+        handlers::threads::scan_threads,
+        handlers::files::list_files,
+        handlers::threads::compact_thread,
+        handlers::threads::rollback_thread,
         handlers::turns::send_turn,
+        handlers::turns::steer_turn,
         handlers::turns::interrupt_turn,
+        handlers::turns::list_queue,
+        handlers::turns::cancel_queued,
+        handlers::sandbox::preview_sandbox,
+        handlers::patches::apply_patch,
         handlers::approvals::respond_to_approval,
+        handlers::approvals::list_approvals,
+        handlers::execpolicy::list_execpolicy_amendments,
+        handlers::execpolicy::revoke_execpolicy_amendment,
         handlers::auth::login,
         handlers::auth::cancel_login,
         handlers::auth::logout,
+        handlers::auth::pair,
         handlers::auth::get_account,
         handlers::auth::get_rate_limits,
         handlers::config::read_config,
@@ -61,13 +152,26 @@ use crate::state::WebServerState;
         handlers::skills::update_skill_config,
         handlers::mcp::list_mcp_server_status,
         handlers::mcp::refresh_mcp_servers,
+        handlers::mcp::update_mcp_server,
         handlers::mcp::mcp_oauth_login,
         handlers::review::start_inline_review,
         handlers::review::start_detached_review,
+        handlers::review::list_reviews,
+        handlers::review::cancel_review,
         handlers::commands::execute_command,
         handlers::feedback::upload_feedback,
+        handlers::metrics::get_metrics,
+        handlers::capabilities::get_capabilities,
+        handlers::maintenance::fix_permissions,
+        handlers::maintenance::get_storage,
+        handlers::maintenance::vacuum,
+        handlers::usage::get_usage,
+        handlers::rpc::rpc,
         attachments::upload_attachment,
+        attachments::upload_attachments,
+        attachments::list_attachments,
         attachments::download_attachment,
+        attachments::delete_attachment,
     ),
     components(
         schemas(
@@ -78,25 +182,118 @@ use crate::state::WebServerState;
             handlers::UserInputItem,
             handlers::threads::CreateThreadRequest,
             handlers::threads::CreateThreadResponse,
+            handlers::threads::ThreadSettings,
             handlers::threads::ListThreadsResponse,
+            handlers::threads::ThreadSummary,
+            handlers::threads::ThreadSort,
+            handlers::threads::ThreadListStatus,
+            provider_overrides::ProviderOverride,
+            thread_scan::ScannedThread,
+            thread_scan::ThreadScan,
+            thread_status::ThreadRunStatus,
+            handlers::threads::ThreadDetails,
+            context_window::ContextWindowUsage,
+            handlers::threads::UpdateThreadRequest,
+            handlers::threads::UpdateThreadResponse,
             handlers::threads::ArchiveThreadResponse,
+            handlers::threads::DeleteThreadParams,
+            handlers::threads::DeleteThreadResponse,
+            handlers::threads::ResumeThreadRequest,
+            handlers::threads::ResumeThreadResponse,
+            handlers::threads::ThreadHistoryItem,
+            turn_attachments::HistoryAttachment,
+            handlers::threads::ThreadHistoryTurn,
+            handlers::threads::ThreadItemsResponse,
+            handlers::threads::ExportItems,
+            thread_export::ExportFormat,
+            handlers::threads::ImportThreadResponse,
+            handlers::threads::ThreadStatusRequest,
+            handlers::threads::ThreadLookupStatus,
+            handlers::threads::ThreadStatusEntry,
+            handlers::threads::ThreadStatusResponse,
+            rollout_tail::LastTurnStatus,
+            handlers::files::FileEntryKind,
+            handlers::files::FileEntry,
+            handlers::files::ListFilesResponse,
+            handlers::threads::CompactThreadResponse,
+            handlers::threads::RollbackThreadRequest,
+            handlers::threads::RollbackThreadResponse,
+            thread_progress::LongOperation,
+            thread_progress::LongOperationStatus,
             handlers::turns::SendTurnRequest,
             handlers::turns::SendTurnResponse,
             handlers::turns::UserInputItem,
+            handlers::turns::SteerTurnRequest,
+            handlers::turns::SteerTurnResponse,
             handlers::turns::InterruptTurnRequest,
             handlers::turns::InterruptTurnResponse,
+            handlers::turns::TurnQueueResponse,
+            turn_queue::QueueEntry,
+            turn_queue::DequeueReason,
+            handlers::sandbox::SandboxPreviewRequest,
+            handlers::sandbox::SandboxPreviewResponse,
+            handlers::sandbox::WritableRootPreview,
+            handlers::sandbox::ApprovalPreview,
+            handlers::review::ListReviewsResponse,
+            handlers::review::CancelReviewParams,
+            handlers::review::CancelReviewResponse,
+            review_registry::ReviewSummary,
+            review_registry::ReviewStatus,
+            review_registry::ReviewDeliveryMode,
+            handlers::patches::ApplyPatchRequest,
+            handlers::patches::ApplyPatchResponse,
+            patches::PatchFileResult,
+            patches::PatchFileAction,
+            patches::PatchFileStatus,
+            patches::PatchHunkError,
             handlers::approvals::ApprovalRequest,
             handlers::approvals::ApprovalResponse,
+            handlers::approvals::ListApprovalsResponse,
+            approval_manager::ApprovalErrorCode,
+            approval_manager::ApprovalInfo,
+            approval_manager::PendingRequest,
+            handlers::execpolicy::ListAmendmentsParams,
+            handlers::execpolicy::ListAmendmentsResponse,
+            handlers::execpolicy::RevokeAmendmentResponse,
+            execpolicy_amendments::ExecPolicyAmendmentRecord,
             handlers::auth::LoginRequest,
             handlers::auth::LoginResponse,
             handlers::auth::CancelLoginRequest,
             handlers::auth::CancelLoginResponse,
             handlers::auth::LogoutResponse,
+            handlers::auth::PairRequest,
+            handlers::auth::PairResponse,
+            pairing::PairingErrorCode,
+            handlers::config::ConfigScope,
             handlers::config::WriteConfigValueRequest,
             handlers::config::BatchWriteConfigRequest,
             handlers::config::WriteConfigResponse,
+            metrics::MetricsSnapshot,
+            handlers::metrics::MetricsResponse,
+            thread_memory::ThreadMemorySnapshot,
+            handlers::capabilities::CapabilitiesResponse,
+            sse_envelope::SseEnvelope,
+            handlers::maintenance::FixPermissionsResponse,
+            handlers::maintenance::StorageReport,
+            handlers::maintenance::StorageCategories,
+            handlers::maintenance::ThreadStorage,
+            handlers::maintenance::VacuumRequest,
+            handlers::rpc::RpcRequest,
+            handlers::maintenance::VacuumResponse,
+            handlers::maintenance::VacuumedItem,
+            handlers::usage::UsageReport,
+            usage::UsageGroup,
+            usage::UsageGroupBy,
+            usage::UsageTotals,
+            storage::StorageCategory,
+            permissions::PermissionIssue,
             attachments::UploadResponse,
+            attachments::UploadAttachmentsResponse,
             attachments::AttachmentMetadata,
+            attachments::UploadErrorCode,
+            attachments::UploadErrorDetails,
+            attachments::ListAttachmentsResponse,
+            attachments::DeleteAttachmentResponse,
         )
     ),
     tags(
@@ -110,8 +307,14 @@ use crate::state::WebServerState;
         (name = "MCP", description = "MCP server management endpoints"),
         (name = "Review", description = "Code review endpoints"),
         (name = "Commands", description = "One-off command execution endpoints"),
+        (name = "Sandbox", description = "Sandbox policy previews"),
+        (name = "Patches", description = "Client-supplied patch application"),
         (name = "Feedback", description = "User feedback endpoints"),
         (name = "Events", description = "Event streaming endpoints"),
+        (name = "Metrics", description = "Server counters"),
+        (name = "Capabilities", description = "Server capability discovery"),
+        (name = "Maintenance", description = "Repairs to the server's own files"),
+        (name = "RPC", description = "JSON-RPC bridge for app-server clients"),
         (name = "Attachments", description = "File attachment endpoints"),
     ),
     info(
@@ -149,7 +352,18 @@ impl utoipa::Modify for SecurityAddon {
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    tracing_subscriber::fmt::init();
+    // Core applies patches by re-running the current executable.
+    if let Some(exit_code) = patches::run_apply_patch_invocation() {
+        std::process::exit(exit_code);
+    }
+
+    let _telemetry = telemetry::init()?;
+    let self_test = std::env::args()
+        .skip(1)
+        .any(|arg| arg == startup_checks::SELF_TEST_FLAG);
+    let strict_permissions = std::env::args()
+        .skip(1)
+        .any(|arg| arg == permissions::STRICT_PERMISSIONS_FLAG);
 
     let codex_home = dirs::home_dir()
         .context("Failed to get home dir")?
@@ -161,14 +375,23 @@ async fn main() -> anyhow::Result<()> {
     let auth_token =
         std::env::var("CODEX_WEB_TOKEN").unwrap_or_else(|_| Uuid::new_v4().to_string());
 
-    tracing::info!("🔐 Auth token: {}", auth_token);
-    tracing::info!("🔗 Use: Authorization: Bearer {}", auth_token);
+    // Probe the keyring up front so a missing secret service fails (or falls
+    // back) here rather than inside auth handlers.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let credential_store = CredentialStoreSettings::from_args_and_env(
+        &args,
+        std::env::var(credential_store::CREDENTIAL_STORE_ENV)
+            .ok()
+            .as_deref(),
+    )
+    .map_err(anyhow::Error::msg)?;
+    let credential_store_mode = tokio::task::spawn_blocking(move || {
+        credential_store.resolve(&codex_keyring_store::DefaultKeyringStore)
+    })
+    .await??;
+    tracing::info!("🔑 Credential store: {credential_store_mode:?}");
 
-    let auth_manager = AuthManager::shared(
-        codex_home.clone(),
-        false,
-        codex_core::auth::AuthCredentialsStoreMode::Keyring,
-    );
+    let auth_manager = AuthManager::shared(codex_home.clone(), false, credential_store_mode);
 
     let config_service = Arc::new(ConfigService::new(
         codex_home.clone(),
@@ -186,6 +409,11 @@ async fn main() -> anyhow::Result<()> {
     // Initialize CodexFeedback for feedback upload functionality
     let feedback = codex_feedback::CodexFeedback::new();
 
+    // Persist approvals and running turns alongside rollouts when Postgres is configured.
+    let state_store = WebStateStore::connect_from_env(instance::instance_id())
+        .await
+        .map(|store| store.map(Arc::new));
+
     let web_state = WebServerState::new(
         thread_manager,
         auth_manager,
@@ -194,38 +422,220 @@ async fn main() -> anyhow::Result<()> {
         attachments_dir,
         auth_token,
         feedback,
-    );
+    )
+    .with_state_store(state_store.as_ref().ok().cloned().flatten())
+    .with_admin_token(std::env::var("CODEX_WEB_ADMIN_TOKEN").ok())
+    .with_credential_store_mode(credential_store_mode)
+    .with_api_version_policy(ApiVersionPolicy::from_env())
+    .with_trusted_roots(TrustedRoots::load(&codex_home)?)
+    .with_provider_hosts(ProviderHosts::from_env())
+    .with_model_pricing(ModelPricing::from_env())
+    .with_import_limits(ImportLimits::from_env())
+    .with_attachment_limits(AttachmentLimits::from_env())
+    .with_skills_timeout(skills_timeout_from_env())
+    .with_approval_timeout(approval_timeout_from_env())
+    .with_thread_memory_budget(thread_memory_budget_from_env())
+    .with_thread_scan_limit(scan_limit_from_env())
+    .with_notifier(load_notifier(&codex_home));
+
+    let web_state = match std::env::var_os(mock_model::MOCK_SCRIPT_ENV) {
+        Some(script_path) => {
+            let script_path = std::path::PathBuf::from(script_path);
+            let script = MockScript::load(&script_path)?;
+            let server = MockModelServer::start(script).await?;
+            tracing::warn!(
+                "🧪 Mock model mode: replaying {} via {}",
+                script_path.display(),
+                server.base_url()
+            );
+            web_state.with_config_loader(server.config_loader(codex_home.clone()))
+        }
+        None => web_state,
+    };
+
+    if self_test {
+        let report = startup_checks::run_self_test(
+            &web_state,
+            state_store.as_ref().map(Option::as_deref),
+            DEFAULT_PER_SERVER_TIMEOUT,
+        )
+        .await;
+        print!("{}", report.render());
+        std::process::exit(report.exit_code());
+    }
+    state_store.context("Failed to connect web state store")?;
+
+    let permission_issues = permissions::audit(&web_state.codex_home, &web_state.attachments_dir);
+    if !permission_issues.is_empty() {
+        let summary = permissions::describe(&permission_issues);
+        if strict_permissions {
+            anyhow::bail!(
+                "Refusing to start with {}: {summary}",
+                permissions::STRICT_PERMISSIONS_FLAG
+            );
+        }
+        tracing::warn!(
+            "⚠️  Codex files are accessible to other users: {summary}. \
+             Fix with POST /api/v2/maintenance/fix-permissions"
+        );
+    }
+
+    tracing::info!("🔐 Auth token: {}", web_state.auth_token);
+    tracing::info!("🔗 Use: Authorization: Bearer {}", web_state.auth_token);
+    // Packaged clients that cannot read our logs pair through a code file instead.
+    if !web_state.pairing.has_paired_tokens() {
+        match web_state.pairing.issue_code() {
+            Ok(_) => tracing::info!(
+                "🤝 Pairing code written to {} (valid for {}s, single use)",
+                web_state.pairing.code_path().display(),
+                pairing::PAIRING_CODE_TTL.as_secs()
+            ),
+            Err(err) => tracing::warn!("Failed to write pairing code: {err}"),
+        }
+    }
+    if web_state.admin_token.is_some() {
+        tracing::info!("🛡️  Maintenance and usage routes require CODEX_WEB_ADMIN_TOKEN");
+    }
+    tracing::info!("🆔 Instance id: {}", instance::instance_id());
+
+    if !web_state.trusted_roots.is_unrestricted() {
+        tracing::info!(
+            "📂 Working directories limited to: {}",
+            web_state.trusted_roots.roots().join(", ")
+        );
+    }
+    if !web_state.provider_hosts.hosts().is_empty() {
+        tracing::info!(
+            "🔀 Provider overrides allowed for: {}",
+            web_state.provider_hosts.hosts().join(", ")
+        );
+    }
+    web_state
+        .restore_from_store()
+        .await
+        .context("Failed to reconcile persisted web state")?;
+    if let Err(err) = web_state.load_notification_opt_outs().await {
+        tracing::warn!("Failed to load notification opt-outs: {err}");
+    }
+
+    // Denies approvals nobody answered in time, so their turns can go on.
+    spawn_approval_sweeper(web_state.clone());
+
+    if let Some(policy) = AttachmentGcPolicy::from_env() {
+        tracing::info!(
+            "🧹 Deleting attachments after {}h",
+            policy.ttl.as_secs() / 3600
+        );
+        policy.spawn(web_state.clone());
+    }
+
+    if let Some(policy) = AutoArchivePolicy::from_env() {
+        tracing::info!(
+            "🗄️  Auto-archiving threads idle for {}s",
+            policy.idle_after.as_secs()
+        );
+        policy.spawn(web_state.clone());
+    }
+
+    if scan_on_startup_from_env() {
+        // In the background: listing works meanwhile, without models.
+        let state = web_state.clone();
+        tokio::spawn(async move {
+            match thread_scan::scan_threads(&state).await {
+                Ok(scan) => tracing::info!("🔎 Scanned {} stored threads", scan.threads.len()),
+                Err(err) => tracing::warn!("Failed to scan stored threads: {err}"),
+            }
+        });
+    }
+
+    // Start MCP servers in the background so the first settings page load is fast.
+    web_state.mcp_snapshots.spawn_refresh().await;
 
+    // Turn input may carry inline images, see MAX_TURN_BODY_BYTES.
+    let turn_body_limit = DefaultBodyLimit::max(handlers::turns::MAX_TURN_BODY_BYTES);
     let protected_routes = Router::new()
         // v1 API (backward compatible)
         .route("/api/v1/threads", post(handlers::create_thread))
-        .route("/api/v1/threads/{id}/turns", post(handlers::send_turn))
+        .route(
+            "/api/v1/threads/{id}/turns",
+            post(handlers::send_turn).layer(turn_body_limit),
+        )
         .route("/api/v1/threads/{id}/events", get(handlers::stream_events))
-        .route("/api/v1/attachments", post(attachments::upload_attachment))
+        // The handler also refuses a larger Content-Length up front and
+        // enforces CODEX_WEB_MAX_ATTACHMENT_BYTES on the file while streaming.
+        .route(
+            "/api/v1/attachments",
+            post(attachments::upload_attachment).layer(DefaultBodyLimit::max(
+                web_state.attachment_limits.max_body_bytes(),
+            )),
+        )
+        .route("/api/v1/attachments", get(attachments::list_attachments))
         .route(
             "/api/v1/attachments/{id}",
             get(attachments::download_attachment),
         )
+        .route(
+            "/api/v1/attachments/{id}",
+            delete(attachments::delete_attachment),
+        )
         // v2 API (new endpoints)
         .route("/api/v2/threads", post(handlers::threads::create_thread))
         .route("/api/v2/threads", get(handlers::threads::list_threads))
+        .route("/api/v2/threads/{id}", get(handlers::threads::get_thread))
+        .route(
+            "/api/v2/threads/{id}",
+            patch(handlers::threads::update_thread).delete(handlers::threads::delete_thread),
+        )
         .route(
             "/api/v2/threads/{id}/archive",
             post(handlers::threads::archive_thread),
         )
         .route(
             "/api/v2/threads/{id}/turns",
-            post(handlers::turns::send_turn),
+            post(handlers::turns::send_turn).layer(turn_body_limit),
+        )
+        .route(
+            "/api/v2/threads/{id}/turns/steer",
+            post(handlers::turns::steer_turn).layer(turn_body_limit),
         )
         .route(
             "/api/v2/threads/{id}/turns/interrupt",
             post(handlers::turns::interrupt_turn),
         )
+        .route(
+            "/api/v2/threads/{id}/queue",
+            get(handlers::turns::list_queue),
+        )
+        .route(
+            "/api/v2/threads/{id}/queue/{entry_id}",
+            delete(handlers::turns::cancel_queued),
+        )
+        .route(
+            "/api/v2/threads/{thread_id}/approvals",
+            get(handlers::approvals::list_approvals),
+        )
         .route(
             "/api/v2/threads/{thread_id}/approvals/{approval_id}",
             post(handlers::approvals::respond_to_approval),
         )
+        .route(
+            "/api/v2/execpolicy",
+            get(handlers::execpolicy::list_execpolicy_amendments),
+        )
+        .route(
+            "/api/v2/execpolicy/{amendment_id}",
+            delete(handlers::execpolicy::revoke_execpolicy_amendment),
+        )
         .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .route("/api/v2/events", get(handlers::stream_all_events))
+        .route(
+            "/api/v2/threads/{id}/sandbox/preview",
+            post(handlers::sandbox::preview_sandbox),
+        )
+        .route(
+            "/api/v2/threads/{id}/patches",
+            post(handlers::patches::apply_patch),
+        )
         // Authentication endpoints
         .route("/api/v2/auth/login", post(handlers::auth::login))
         .route(
@@ -266,6 +676,10 @@ async fn main() -> anyhow::Result<()> {
             "/api/v2/mcp/servers/refresh",
             post(handlers::mcp::refresh_mcp_servers),
         )
+        .route(
+            "/api/v2/mcp/servers/{name}",
+            patch(handlers::mcp::update_mcp_server),
+        )
         .route(
             "/api/v2/mcp/servers/{name}/auth",
             post(handlers::mcp::mcp_oauth_login),
@@ -277,18 +691,39 @@ async fn main() -> anyhow::Result<()> {
         )
         .route(
             "/api/v2/reviews",
-            post(handlers::review::start_detached_review),
+            post(handlers::review::start_detached_review).get(handlers::review::list_reviews),
+        )
+        .route(
+            "/api/v2/reviews/{review_id}",
+            delete(handlers::review::cancel_review),
         )
         // Commands endpoint
         .route(
             "/api/v2/commands",
             post(handlers::commands::execute_command),
         )
+        // Several files per request; the handler enforces both attachment
+        // limits while streaming.
+        .route(
+            "/api/v2/attachments",
+            post(attachments::upload_attachments).layer(DefaultBodyLimit::max(
+                web_state.attachment_limits.max_upload_body_bytes(),
+            )),
+        )
         // Feedback endpoint
         .route(
             "/api/v2/feedback",
             post(handlers::feedback::upload_feedback),
         )
+        .route("/api/v2/metrics", get(handlers::metrics::get_metrics))
+        .route(
+            "/api/v2/capabilities",
+            get(handlers::capabilities::get_capabilities),
+        )
+        .route(
+            "/api/v2/rpc",
+            post(handlers::rpc::rpc).layer(turn_body_limit),
+        )
         // Thread operations
         .route(
             "/api/v2/threads/{id}/resume",
@@ -298,22 +733,78 @@ async fn main() -> anyhow::Result<()> {
             "/api/v2/threads/{id}/fork",
             post(handlers::threads::fork_thread),
         )
+        .route(
+            "/api/v2/threads/{id}/items",
+            get(handlers::threads::list_thread_items),
+        )
+        .route(
+            "/api/v2/threads/{id}/export",
+            get(handlers::threads::export_thread),
+        )
+        // The handler enforces CODEX_WEB_IMPORT_MAX_BYTES while streaming.
+        .route(
+            "/api/v2/threads/import",
+            post(handlers::threads::import_thread).layer(DefaultBodyLimit::disable()),
+        )
+        .route(
+            "/api/v2/threads/status",
+            post(handlers::threads::batch_thread_status),
+        )
+        .route(
+            "/api/v2/threads/{id}/files",
+            get(handlers::files::list_files),
+        )
+        .route(
+            "/api/v2/threads/{id}/compact",
+            post(handlers::threads::compact_thread),
+        )
+        .route(
+            "/api/v2/threads/{id}/rollback",
+            post(handlers::threads::rollback_thread),
+        )
+        .layer(from_fn_with_state(
+            web_state.clone(),
+            instance_affinity_middleware,
+        ))
         .layer(from_fn_with_state(web_state.clone(), auth_middleware));
 
+    // Maintenance, usage and thread scan routes take the admin token when one is configured.
+    let admin_routes = Router::new()
+        .route(
+            "/api/v2/maintenance/fix-permissions",
+            post(handlers::maintenance::fix_permissions),
+        )
+        .route(
+            "/api/v2/maintenance/storage",
+            get(handlers::maintenance::get_storage),
+        )
+        .route(
+            "/api/v2/maintenance/vacuum",
+            post(handlers::maintenance::vacuum),
+        )
+        .route(
+            "/api/v2/threads/scan",
+            post(handlers::threads::scan_threads),
+        )
+        .route("/api/v2/usage", get(handlers::usage::get_usage))
+        .layer(from_fn_with_state(
+            web_state.clone(),
+            instance_affinity_middleware,
+        ))
+        .layer(from_fn_with_state(web_state.clone(), admin_middleware));
+
     let app = Router::new()
         .route("/health", get(health))
+        .route("/health/ready", get(handlers::health::ready))
+        .route("/api/v2/auth/pair", post(handlers::auth::pair))
         .merge(protected_routes)
-        .layer(
-            CorsLayer::new()
-                .allow_origin([
-                    HeaderValue::from_static("http://localhost:3000"),
-                    HeaderValue::from_static("http://127.0.0.1:3000"),
-                    HeaderValue::from_static("http://localhost:8080"),
-                    HeaderValue::from_static("http://127.0.0.1:8080"),
-                ])
-                .allow_methods(Any)
-                .allow_headers(Any),
-        )
+        .merge(admin_routes)
+        .layer(from_fn_with_state(
+            web_state.clone(),
+            api_version_middleware,
+        ))
+        .layer(CorsOrigins::from_env().into_layer())
+        .layer(telemetry::trace_layer())
         .with_state(web_state);
 
     #[cfg(feature = "swagger-ui")]
@@ -326,28 +817,53 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("🚀 Server starting on http://{}", bind_addr);
     #[cfg(feature = "swagger-ui")]
     tracing::info!("📚 Swagger UI: http://{bind_addr}/swagger-ui");
-    tracing::info!("📍 API v1 Endpoints (backward compatible):");
+    tracing::info!("📍 API v1 Endpoints (deprecated):");
     tracing::info!("  GET  /health");
+    tracing::info!("  GET  /health/ready");
     tracing::info!("  POST /api/v1/threads");
     tracing::info!("  POST /api/v1/threads/{{id}}/turns");
     tracing::info!("  GET  /api/v1/threads/{{id}}/events (SSE)");
     tracing::info!("  POST /api/v1/attachments");
+    tracing::info!("  GET  /api/v1/attachments");
     tracing::info!("  GET  /api/v1/attachments/{{id}}");
+    tracing::info!("  DELETE /api/v1/attachments/{{id}}");
     tracing::info!("📍 API v2 Endpoints (enhanced):");
     tracing::info!("  POST /api/v2/threads");
     tracing::info!("  GET  /api/v2/threads");
+    tracing::info!("  GET  /api/v2/threads/{{id}}");
+    tracing::info!("  PATCH /api/v2/threads/{{id}}");
+    tracing::info!("  DELETE /api/v2/threads/{{id}}");
     tracing::info!("  POST /api/v2/threads/{{id}}/archive");
     tracing::info!("  POST /api/v2/threads/{{id}}/resume");
     tracing::info!("  POST /api/v2/threads/{{id}}/fork");
+    tracing::info!("  GET  /api/v2/threads/{{id}}/items");
+    tracing::info!("  GET  /api/v2/threads/{{id}}/files");
+    tracing::info!("  GET  /api/v2/threads/{{id}}/export");
+    tracing::info!("  POST /api/v2/threads/import");
+    tracing::info!("  POST /api/v2/threads/status");
+    tracing::info!("  POST /api/v2/threads/{{id}}/compact");
+    tracing::info!("  POST /api/v2/threads/{{id}}/rollback");
     tracing::info!("  POST /api/v2/threads/{{id}}/turns");
+    tracing::info!("  POST /api/v2/threads/{{id}}/turns/steer");
     tracing::info!("  POST /api/v2/threads/{{id}}/turns/interrupt");
+    tracing::info!("  GET  /api/v2/threads/{{id}}/queue");
+    tracing::info!("  DELETE /api/v2/threads/{{id}}/queue/{{entry_id}}");
+    tracing::info!("  GET  /api/v2/threads/{{thread_id}}/approvals");
     tracing::info!("  POST /api/v2/threads/{{thread_id}}/approvals/{{approval_id}}");
+    tracing::info!("  GET  /api/v2/execpolicy");
+    tracing::info!("  DELETE /api/v2/execpolicy/{{amendment_id}}");
     tracing::info!("  GET  /api/v2/threads/{{id}}/events (SSE)");
+    tracing::info!("  GET  /api/v2/events (SSE, all threads)");
+    tracing::info!("  POST /api/v2/threads/{{id}}/sandbox/preview");
+    tracing::info!("  POST /api/v2/threads/{{id}}/patches");
     tracing::info!("  POST /api/v2/threads/{{id}}/reviews");
     tracing::info!("  POST /api/v2/reviews");
+    tracing::info!("  GET  /api/v2/reviews");
+    tracing::info!("  DELETE /api/v2/reviews/{{review_id}}");
     tracing::info!("  POST /api/v2/auth/login");
     tracing::info!("  POST /api/v2/auth/login/cancel");
     tracing::info!("  POST /api/v2/auth/logout");
+    tracing::info!("  POST /api/v2/auth/pair");
     tracing::info!("  GET  /api/v2/auth/account");
     tracing::info!("  GET  /api/v2/auth/rate-limits");
     tracing::info!("  GET  /api/v2/config");
@@ -359,15 +875,50 @@ async fn main() -> anyhow::Result<()> {
     tracing::info!("  PATCH /api/v2/skills/{{name}}");
     tracing::info!("  GET  /api/v2/mcp/servers");
     tracing::info!("  POST /api/v2/mcp/servers/refresh");
+    tracing::info!("  PATCH /api/v2/mcp/servers/{{name}}");
     tracing::info!("  POST /api/v2/mcp/servers/{{name}}/auth");
     tracing::info!("  POST /api/v2/commands");
+    tracing::info!("  POST /api/v2/attachments");
     tracing::info!("  POST /api/v2/feedback");
+    tracing::info!("  GET  /api/v2/metrics");
+    tracing::info!("  GET  /api/v2/capabilities");
+    tracing::info!("  POST /api/v2/rpc");
+    tracing::info!("  POST /api/v2/maintenance/fix-permissions");
+    tracing::info!("  GET  /api/v2/maintenance/storage");
+    tracing::info!("  POST /api/v2/maintenance/vacuum");
+    tracing::info!("  POST /api/v2/threads/scan");
+    tracing::info!("  GET  /api/v2/usage");
 
     let listener = tokio::net::TcpListener::bind(&bind_addr).await?;
     axum::serve(listener, app).await?;
     Ok(())
 }
 
+/// A misconfigured notifications section disables notifications rather than
+/// keeping the server from starting.
+fn load_notifier(codex_home: &std::path::Path) -> Notifier {
+    let config = match NotificationsConfig::load(codex_home) {
+        Ok(Some(config)) => config,
+        Ok(None) => return Notifier::default(),
+        Err(err) => {
+            tracing::warn!("Notifications disabled: {err:#}");
+            return Notifier::default();
+        }
+    };
+    match Notifier::from_config(&config) {
+        Ok(notifier) => {
+            if notifier.is_enabled() {
+                tracing::info!("🔔 Notifications go to {} sink(s)", config.sinks.len());
+            }
+            notifier
+        }
+        Err(err) => {
+            tracing::warn!("Notifications disabled: {err:#}");
+            Notifier::default()
+        }
+    }
+}
+
 async fn health() -> Json<Value> {
     Json(json!({ "status": "ok" }))
 }
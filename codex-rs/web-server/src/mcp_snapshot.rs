@@ -0,0 +1,255 @@
+//! Cached MCP server status for `GET /api/v2/mcp/servers`.
+//!
+//! Collecting a snapshot starts every configured MCP server, which can take
+//! seconds. The cache serves the last snapshot immediately and refreshes it in
+//! the background once it is older than the TTL.
+
+use codex_app_server_protocol::McpServerStatus;
+use codex_core::config::Config;
+use codex_core::config::ConfigBuilder;
+use codex_core::mcp::McpSnapshotWithTimeout;
+use codex_protocol::protocol::McpAuthStatus;
+use futures::future::BoxFuture;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+use tokio::sync::Mutex;
+
+/// How long a snapshot is served as fresh.
+pub const DEFAULT_SNAPSHOT_TTL: Duration = Duration::from_secs(60);
+/// Upper bound for a single server to start and list its tools.
+pub const DEFAULT_PER_SERVER_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// One row of the MCP server listing.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct McpServerListing {
+    #[serde(flatten)]
+    pub status: McpServerStatus,
+    /// `false` when `enabled = false` is set in config; such servers are not
+    /// started and report no tools
+    pub enabled: bool,
+}
+
+/// Status of every configured MCP server at one point in time.
+#[derive(Debug, Clone, Default)]
+pub struct McpStatusSnapshot {
+    /// Sorted by server name
+    pub servers: Vec<McpServerListing>,
+    /// Servers that did not answer within the per-server timeout
+    pub timed_out_servers: Vec<String>,
+    /// Startup error of each server that failed to start, by name
+    pub failed_servers: HashMap<String, String>,
+}
+
+/// Produces a fresh snapshot; replaced in tests to stub slow servers.
+pub type SnapshotCollector =
+    Arc<dyn Fn() -> BoxFuture<'static, anyhow::Result<McpStatusSnapshot>> + Send + Sync>;
+
+/// A snapshot as handed to the listing handler.
+#[derive(Debug, Clone)]
+pub struct CachedMcpStatus {
+    pub snapshot: McpStatusSnapshot,
+    /// Unix seconds
+    pub collected_at: i64,
+    /// Older than the TTL; a background refresh is in flight
+    pub stale: bool,
+}
+
+struct CacheEntry {
+    snapshot: McpStatusSnapshot,
+    collected_at: i64,
+    collected: Instant,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entry: Option<CacheEntry>,
+    refreshing: bool,
+    /// Bumped on invalidation so refreshes started earlier are discarded.
+    generation: u64,
+}
+
+pub struct McpSnapshotCache {
+    ttl: Duration,
+    collector: SnapshotCollector,
+    state: Mutex<CacheState>,
+}
+
+impl McpSnapshotCache {
+    pub fn new(ttl: Duration, collector: SnapshotCollector) -> Self {
+        Self {
+            ttl,
+            collector,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// Cache backed by [`collect_mcp_status`] with the default TTL and
+    /// per-server timeout.
+    pub fn with_defaults(codex_home: PathBuf) -> Self {
+        Self::new(
+            DEFAULT_SNAPSHOT_TTL,
+            Arc::new(move || {
+                Box::pin(collect_mcp_status(
+                    codex_home.clone(),
+                    DEFAULT_PER_SERVER_TIMEOUT,
+                ))
+            }),
+        )
+    }
+
+    /// Returns the cached snapshot, collecting synchronously only when there
+    /// is none yet or `force_refresh` is set. An expired snapshot is returned
+    /// as `stale` while a background refresh replaces it.
+    pub async fn get(self: &Arc<Self>, force_refresh: bool) -> anyhow::Result<CachedMcpStatus> {
+        if !force_refresh {
+            let cached = self
+                .state
+                .lock()
+                .await
+                .entry
+                .as_ref()
+                .map(|entry| CachedMcpStatus {
+                    snapshot: entry.snapshot.clone(),
+                    collected_at: entry.collected_at,
+                    stale: entry.collected.elapsed() >= self.ttl,
+                });
+            if let Some(cached) = cached {
+                if cached.stale {
+                    self.spawn_refresh().await;
+                }
+                return Ok(cached);
+            }
+        }
+
+        let generation = self.state.lock().await.generation;
+        self.collect(generation).await
+    }
+
+    /// Starts a collection in the background, e.g. to warm the cache at
+    /// startup or after invalidation.
+    pub async fn spawn_refresh(self: &Arc<Self>) {
+        let mut state = self.state.lock().await;
+        if state.refreshing {
+            return;
+        }
+        state.refreshing = true;
+        let generation = state.generation;
+        let cache = Arc::clone(self);
+        tokio::spawn(async move {
+            if let Err(err) = cache.collect(generation).await {
+                tracing::warn!("MCP snapshot refresh failed: {err}");
+            }
+        });
+    }
+
+    /// Drops the cached snapshot; refreshes already in flight are discarded.
+    pub async fn invalidate(&self) {
+        let mut state = self.state.lock().await;
+        state.entry = None;
+        state.refreshing = false;
+        state.generation += 1;
+    }
+
+    async fn collect(&self, generation: u64) -> anyhow::Result<CachedMcpStatus> {
+        let result = (self.collector)().await;
+        let mut state = self.state.lock().await;
+        if state.generation == generation {
+            state.refreshing = false;
+        }
+        let snapshot = result?;
+        let collected_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default();
+        if state.generation == generation {
+            state.entry = Some(CacheEntry {
+                snapshot: snapshot.clone(),
+                collected_at,
+                collected: Instant::now(),
+            });
+        }
+        Ok(CachedMcpStatus {
+            snapshot,
+            collected_at,
+            stale: false,
+        })
+    }
+}
+
+/// Loads the current config and collects status for every configured MCP
+/// server, giving each at most `per_server_timeout`.
+pub async fn collect_mcp_status(
+    codex_home: PathBuf,
+    per_server_timeout: Duration,
+) -> anyhow::Result<McpStatusSnapshot> {
+    let config = ConfigBuilder::default()
+        .codex_home(codex_home)
+        .build()
+        .await?;
+    Ok(collect_mcp_status_for_config(&config, per_server_timeout).await)
+}
+
+pub async fn collect_mcp_status_for_config(
+    config: &Config,
+    per_server_timeout: Duration,
+) -> McpStatusSnapshot {
+    let McpSnapshotWithTimeout {
+        snapshot,
+        timed_out: timed_out_servers,
+        failed,
+    } = codex_core::mcp::collect_mcp_snapshot_with_timeout(config, per_server_timeout).await;
+    let tools_by_server = codex_core::mcp::group_tools_by_server(&snapshot.tools);
+
+    let mut server_names: Vec<String> = config
+        .mcp_servers
+        .keys()
+        .cloned()
+        .chain(snapshot.auth_statuses.keys().cloned())
+        .chain(snapshot.resources.keys().cloned())
+        .chain(snapshot.resource_templates.keys().cloned())
+        .collect();
+    server_names.sort();
+    server_names.dedup();
+
+    let servers = server_names
+        .into_iter()
+        .map(|name| McpServerListing {
+            enabled: config
+                .mcp_servers
+                .get()
+                .get(&name)
+                .is_none_or(|server| server.enabled),
+            status: McpServerStatus {
+                tools: tools_by_server.get(&name).cloned().unwrap_or_default(),
+                resources: snapshot.resources.get(&name).cloned().unwrap_or_default(),
+                resource_templates: snapshot
+                    .resource_templates
+                    .get(&name)
+                    .cloned()
+                    .unwrap_or_default(),
+                auth_status: snapshot
+                    .auth_statuses
+                    .get(&name)
+                    .cloned()
+                    .unwrap_or(McpAuthStatus::Unsupported)
+                    .into(),
+                name,
+            },
+        })
+        .collect();
+
+    McpStatusSnapshot {
+        servers,
+        timed_out_servers,
+        failed_servers: failed
+            .into_iter()
+            .map(|failure| (failure.server, failure.error))
+            .collect(),
+    }
+}
@@ -0,0 +1,50 @@
+//! Process-wide counters exposed at `GET /api/v2/metrics`.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use utoipa::ToSchema;
+
+#[derive(Debug, Default)]
+pub struct ServerMetrics {
+    auto_archived_threads: AtomicU64,
+    v1_requests: Mutex<BTreeMap<String, u64>>,
+}
+
+impl ServerMetrics {
+    pub fn record_auto_archive(&self) {
+        self.auto_archived_threads.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a request to a deprecated v1 route, keyed by `"METHOD /route"`.
+    pub fn record_v1_request(&self, route: &str) {
+        *self
+            .v1_requests
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(route.to_string())
+            .or_default() += 1;
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            auto_archived_threads: self.auto_archived_threads.load(Ordering::Relaxed),
+            v1_requests: self
+                .v1_requests
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct MetricsSnapshot {
+    /// Threads archived by the idle policy since startup
+    pub auto_archived_threads: u64,
+    /// Requests to deprecated v1 routes since startup, by `"METHOD /route"`
+    pub v1_requests: BTreeMap<String, u64>,
+}
@@ -1,10 +1,16 @@
 use axum::body::Body;
 use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::http::HeaderValue;
 use axum::http::Request;
 use axum::middleware::Next;
+use axum::response::IntoResponse;
 use axum::response::Response;
+use codex_protocol::ThreadId;
 
 use crate::error::ApiError;
+use crate::instance::INSTANCE_HEADER;
+use crate::instance::instance_id;
 use crate::state::WebServerState;
 
 pub async fn auth_middleware(
@@ -12,20 +18,93 @@ pub async fn auth_middleware(
     request: Request<Body>,
     next: Next,
 ) -> Result<Response, ApiError> {
-    let auth_header = request
+    if is_authorized(&state, request.headers()) {
+        Ok(next.run(request).await)
+    } else {
+        Err(ApiError::Unauthorized)
+    }
+}
+
+/// Whether `headers` carry the auth token or a paired token as bearer token.
+pub fn is_authorized(state: &WebServerState, headers: &HeaderMap) -> bool {
+    headers
+        .get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|header| header.strip_prefix("Bearer "))
+        .is_some_and(|token| token == state.auth_token || state.pairing.is_paired_token(token))
+}
+
+/// Guards maintenance, usage and thread scan routes. When `CODEX_WEB_ADMIN_TOKEN` is set only that
+/// token is accepted; otherwise the regular bearer token is.
+pub async fn admin_middleware(
+    State(state): State<WebServerState>,
+    request: Request<Body>,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let expected = state.admin_token.as_deref().unwrap_or(&state.auth_token);
+    let token = request
         .headers()
         .get("authorization")
-        .and_then(|h| h.to_str().ok());
-
-    match auth_header {
-        Some(header) if header.starts_with("Bearer ") => {
-            let token = &header[7..];
-            if token == state.auth_token {
-                Ok(next.run(request).await)
-            } else {
-                Err(ApiError::Unauthorized)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|header| header.strip_prefix("Bearer "));
+
+    match token {
+        Some(token) if token == expected => Ok(next.run(request).await),
+        _ => Err(ApiError::Unauthorized),
+    }
+}
+
+/// Tags every response with this instance's id and rejects thread-scoped
+/// requests that a client routed here while naming another instance as owner.
+///
+/// A request is misdirected when it carries `X-Codex-Instance` for a different
+/// instance and the thread in its path is not loaded locally. Requests without
+/// the header pass through untouched so single-instance deployments keep working.
+pub async fn instance_affinity_middleware(
+    State(state): State<WebServerState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let requested_owner = request
+        .headers()
+        .get(INSTANCE_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|owner| *owner != instance_id())
+        .map(str::to_string);
+
+    // Thread-scoped routes look like /api/{version}/threads/{id}/...
+    let thread_id = {
+        let mut segments = request.uri().path().split('/').skip(1);
+        match (
+            segments.next(),
+            segments.next(),
+            segments.next(),
+            segments.next(),
+        ) {
+            (Some("api"), Some(_), Some("threads"), Some(id)) => ThreadId::from_string(id).ok(),
+            _ => None,
+        }
+    };
+
+    let mut response = match (requested_owner, thread_id) {
+        (Some(owner_instance_id), Some(thread_id))
+            if state.thread_manager.get_thread(thread_id).await.is_err() =>
+        {
+            ApiError::MisdirectedRequest { owner_instance_id }.into_response()
+        }
+        _ => {
+            // Any request addressed to a loaded thread resets its idle timer.
+            if let Some(thread_id) = thread_id
+                && state.thread_manager.get_thread(thread_id).await.is_ok()
+            {
+                state.sessions.write().await.touch(thread_id);
             }
+            next.run(request).await
         }
-        _ => Err(ApiError::Unauthorized),
+    };
+
+    if let Ok(value) = HeaderValue::from_str(instance_id()) {
+        response.headers_mut().insert(INSTANCE_HEADER, value);
     }
+    response
 }
@@ -0,0 +1,172 @@
+//! Scripted model backend for hermetic end-to-end tests and demos.
+//!
+//! When `CODEX_WEB_MOCK_SCRIPT` names a script file, the server starts a
+//! local Responses API endpoint that replays the script and points every
+//! thread's config at it. Threads still run in core, so turns, approvals and
+//! events flow through the usual handlers and `EventStreamProcessor`; only
+//! the model's replies are canned and no credentials are needed.
+//!
+//! A script is a list of model responses, each a list of Responses API
+//! stream events. Every turn starts at the first response; each tool output
+//! core sends back advances to the next one, so a reply that calls a tool is
+//! followed by the reply that sees its result.
+
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::http::header::CONTENT_TYPE;
+use axum::response::IntoResponse;
+use axum::response::Response;
+use axum::routing::post;
+use codex_core::ModelProviderInfo;
+use codex_core::WireApi;
+use codex_core::config::Config;
+use codex_core::config::ConfigBuilder;
+use codex_core::create_oss_provider_with_base_url;
+use futures::FutureExt;
+use serde::Deserialize;
+use serde_json::Value;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::config_loader::ConfigLoader;
+
+/// Path of the script to replay; unset runs against the configured provider.
+pub const MOCK_SCRIPT_ENV: &str = "CODEX_WEB_MOCK_SCRIPT";
+
+/// Provider id threads report while the mock is active.
+pub const MOCK_PROVIDER_ID: &str = "mock";
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct MockScript {
+    /// Model responses in the order a turn requests them.
+    pub responses: Vec<Vec<Value>>,
+}
+
+impl MockScript {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("failed to read {}: {e}", path.display()))?;
+        Self::parse(&contents)
+    }
+
+    pub fn parse(contents: &str) -> anyhow::Result<Self> {
+        let script: Self = serde_json::from_str(contents)?;
+        if script.responses.is_empty() {
+            anyhow::bail!("mock script has no responses");
+        }
+        for (index, events) in script.responses.iter().enumerate() {
+            if events
+                .iter()
+                .any(|event| event.get("type").and_then(Value::as_str).is_none())
+            {
+                anyhow::bail!("mock response {index} has an event without a \"type\"");
+            }
+        }
+        Ok(script)
+    }
+
+    /// The response for a model request whose `input` is `input`: the number
+    /// of tool outputs since the latest user message, capped at the last
+    /// response.
+    pub fn response_for(&self, input: &[Value]) -> &[Value] {
+        let turn_start = input
+            .iter()
+            .rposition(|item| item["type"] == "message" && item["role"] == "user")
+            .map_or(0, |index| index + 1);
+        let step = input[turn_start..]
+            .iter()
+            .filter(|item| {
+                matches!(
+                    item["type"].as_str(),
+                    Some("function_call_output" | "custom_tool_call_output")
+                )
+            })
+            .count();
+        let last = self.responses.len().saturating_sub(1);
+        self.responses
+            .get(step.min(last))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+/// A running mock Responses API endpoint.
+pub struct MockModelServer {
+    base_url: String,
+}
+
+impl MockModelServer {
+    /// Serves `script` on an ephemeral loopback port.
+    pub async fn start(script: MockScript) -> std::io::Result<Self> {
+        let app = Router::new()
+            .route("/v1/responses", post(respond))
+            .with_state(Arc::new(script));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(async move {
+            if let Err(err) = axum::serve(listener, app).await {
+                tracing::error!("Mock model server stopped: {err}");
+            }
+        });
+        Ok(Self {
+            base_url: format!("http://{addr}/v1"),
+        })
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn provider(&self) -> ModelProviderInfo {
+        let mut provider = create_oss_provider_with_base_url(&self.base_url, WireApi::Responses);
+        provider.name = "Mock model (scripted)".to_string();
+        provider.request_max_retries = Some(0);
+        provider.stream_max_retries = Some(0);
+        provider
+    }
+
+    /// Loader for `codex_home` whose configs use this server as the model
+    /// provider.
+    pub fn config_loader(&self, codex_home: PathBuf) -> ConfigLoader {
+        let provider = self.provider();
+        ConfigLoader::new(Arc::new(move || {
+            let codex_home = codex_home.clone();
+            let provider = provider.clone();
+            async move {
+                let mut config = ConfigBuilder::default()
+                    .codex_home(codex_home)
+                    .build()
+                    .await?;
+                use_provider(&mut config, provider);
+                Ok(config)
+            }
+            .boxed()
+        }))
+    }
+}
+
+fn use_provider(config: &mut Config, provider: ModelProviderInfo) {
+    config.model_provider_id = MOCK_PROVIDER_ID.to_string();
+    config.model_provider = provider;
+}
+
+async fn respond(State(script): State<Arc<MockScript>>, body: Bytes) -> Response {
+    let input = serde_json::from_slice::<Value>(&body)
+        .ok()
+        .and_then(|request| request.get("input").and_then(Value::as_array).cloned())
+        .unwrap_or_default();
+    let body = render_sse(script.response_for(&input));
+    (StatusCode::OK, [(CONTENT_TYPE, "text/event-stream")], body).into_response()
+}
+
+fn render_sse(events: &[Value]) -> String {
+    let mut out = String::new();
+    for event in events {
+        let kind = event["type"].as_str().unwrap_or_default();
+        out.push_str(&format!("event: {kind}\ndata: {event}\n\n"));
+    }
+    out
+}
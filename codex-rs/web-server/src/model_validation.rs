@@ -0,0 +1,152 @@
+//! Checks the model requested for a new thread or turn before it reaches core.
+//!
+//! Every model id must be short and use a conservative character set, since it
+//! ends up in rollouts and logs. Unless the request sets `skip_validation`,
+//! the id must also be a model the server knows. Only the cached model list is
+//! consulted, so creating a thread never waits on the network.
+//!
+//! Turns with image attachments are checked against the `input_modalities`
+//! of the turn's model, so a text-only model fails fast instead of with a
+//! provider error mid-turn.
+
+use codex_core::models_manager::manager::RefreshStrategy;
+use codex_protocol::openai_models::InputModality;
+use codex_protocol::openai_models::ModelPreset;
+use codex_protocol::user_input::UserInput;
+
+use crate::error::ApiError;
+use crate::state::WebServerState;
+
+/// Longest model or provider id a request may name.
+pub const MAX_IDENTIFIER_LEN: usize = 128;
+
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Rejects ids that are empty, longer than [`MAX_IDENTIFIER_LEN`], or use
+/// characters outside ASCII letters, digits and `-_.:/@`. The message does
+/// not quote the value, so it is safe to log.
+pub fn check_identifier(kind: &str, value: &str) -> Result<(), ApiError> {
+    let allowed = |c: char| c.is_ascii_alphanumeric() || "-_.:/@".contains(c);
+    if value.is_empty() || value.len() > MAX_IDENTIFIER_LEN || !value.chars().all(allowed) {
+        return Err(ApiError::InvalidRequest(format!(
+            "{kind} id must be 1 to {MAX_IDENTIFIER_LEN} characters of ASCII letters, digits, \
+             '-', '_', '.', ':', '/' or '@'"
+        )));
+    }
+    Ok(())
+}
+
+/// Checks `model` against [`check_identifier`] and, unless `skip_validation`
+/// is set, against the cached model list.
+pub async fn validate_model(
+    state: &WebServerState,
+    model: &str,
+    skip_validation: bool,
+) -> Result<(), ApiError> {
+    check_identifier("Model", model)?;
+    if skip_validation {
+        return Ok(());
+    }
+    let known: Vec<String> = state
+        .thread_manager
+        .list_models(RefreshStrategy::Offline)
+        .await
+        .into_iter()
+        .flat_map(|preset| [preset.id, preset.model])
+        .collect();
+    if known.iter().any(|known| known == model) {
+        return Ok(());
+    }
+    Err(ApiError::UnknownModel {
+        model: model.to_string(),
+        suggestions: suggest(model, &known),
+    })
+}
+
+/// Rejects `inputs` that carry images when `model`, the model the turn will
+/// run with, cannot read them.
+pub async fn check_turn_input(
+    state: &WebServerState,
+    model: &str,
+    inputs: &[UserInput],
+) -> Result<(), ApiError> {
+    let has_images = inputs.iter().any(|input| {
+        matches!(
+            input,
+            UserInput::Image { .. } | UserInput::LocalImage { .. }
+        )
+    });
+    if !has_images {
+        return Ok(());
+    }
+    let presets = state
+        .thread_manager
+        .list_models(RefreshStrategy::Offline)
+        .await;
+    check_image_input(model, &presets)
+}
+
+/// Fails when `model` is a preset without the image modality, suggesting
+/// pickable presets that have it. Models missing from `presets`, such as
+/// those of a custom provider, pass.
+pub fn check_image_input(model: &str, presets: &[ModelPreset]) -> Result<(), ApiError> {
+    let accepts_images =
+        |preset: &ModelPreset| preset.input_modalities.contains(&InputModality::Image);
+    let Some(preset) = presets
+        .iter()
+        .find(|preset| preset.model == model || preset.id == model)
+    else {
+        return Ok(());
+    };
+    if accepts_images(preset) {
+        return Ok(());
+    }
+    Err(ApiError::UnsupportedInputModality {
+        model: model.to_string(),
+        input_modalities: preset.input_modalities.clone(),
+        suggestions: presets
+            .iter()
+            .filter(|preset| preset.show_in_picker && accepts_images(preset))
+            .map(|preset| preset.id.clone())
+            .take(MAX_SUGGESTIONS)
+            .collect(),
+    })
+}
+
+/// Known models that extend `model`, are extended by it, or are within a few
+/// edits of it, closest first.
+pub fn suggest(model: &str, known: &[String]) -> Vec<String> {
+    let model = model.to_ascii_lowercase();
+    let max_distance = (model.len() / 3).max(2);
+    let mut candidates: Vec<(usize, &String)> = known
+        .iter()
+        .filter_map(|candidate| {
+            let lower = candidate.to_ascii_lowercase();
+            let distance = edit_distance(&model, &lower);
+            let related = lower.starts_with(&model) || model.starts_with(&lower);
+            (related || distance <= max_distance).then_some((distance, candidate))
+        })
+        .collect();
+    candidates.sort();
+    candidates.dedup_by(|a, b| a.1 == b.1);
+    candidates
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.clone())
+        .collect()
+}
+
+/// Levenshtein distance over chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
@@ -0,0 +1,415 @@
+//! Push notifications for events that need a user who is not watching the tab.
+//!
+//! `[web_server.notifications]` in `config.toml` lists sinks (a webhook that
+//! receives the notification as a JSON POST, or a command that receives it on
+//! stdin) and the events that trigger them. The SSE loop only enqueues; a
+//! background task delivers to every sink, retrying failed attempts with
+//! exponential backoff, so a slow or failing sink never delays the stream.
+
+use anyhow::Context;
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use futures::future::BoxFuture;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+
+/// Upper bound for one delivery attempt to a sink.
+pub const DELIVERY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Longest agent message excerpt included in a notification's `text`.
+const MAX_TEXT_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NotificationEvent {
+    #[serde(rename = "turn/completed")]
+    TurnCompleted,
+    #[serde(rename = "turn/failed")]
+    TurnFailed,
+    #[serde(rename = "approval/requested")]
+    ApprovalRequested,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkConfig {
+    /// POSTs the notification JSON to `url`. The body has a `text` field, so
+    /// a Slack incoming webhook URL works as is.
+    Webhook {
+        url: String,
+        #[serde(default)]
+        headers: BTreeMap<String, String>,
+    },
+    /// Runs `command` (program, then arguments) with the notification JSON on
+    /// stdin, e.g. a script calling `notify-send`.
+    Command { command: Vec<String> },
+}
+
+fn default_events() -> Vec<NotificationEvent> {
+    vec![
+        NotificationEvent::TurnCompleted,
+        NotificationEvent::TurnFailed,
+        NotificationEvent::ApprovalRequested,
+    ]
+}
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+fn default_retry_delay_ms() -> u64 {
+    1_000
+}
+
+/// The `[web_server.notifications]` section of `config.toml`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct NotificationsConfig {
+    /// Events that trigger a notification; all of them by default.
+    #[serde(default = "default_events")]
+    pub events: Vec<NotificationEvent>,
+    #[serde(default)]
+    pub sinks: Vec<SinkConfig>,
+    /// Attempts per sink and notification, including the first.
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled before each further one.
+    #[serde(default = "default_retry_delay_ms")]
+    pub retry_delay_ms: u64,
+}
+
+impl NotificationsConfig {
+    /// Reads the section from `config.toml` under `codex_home`. `None` when
+    /// the file or the section is absent.
+    pub fn load(codex_home: &Path) -> anyhow::Result<Option<Self>> {
+        let path = codex_home.join("config.toml");
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => {
+                Self::parse(&contents).with_context(|| format!("Invalid {}", path.display()))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    pub fn parse(config_toml: &str) -> anyhow::Result<Option<Self>> {
+        let mut root: toml::Table = toml::from_str(config_toml)?;
+        let Some(toml::Value::Table(mut web_server)) = root.remove("web_server") else {
+            return Ok(None);
+        };
+        let Some(section) = web_server.remove("notifications") else {
+            return Ok(None);
+        };
+        let config: Self = section
+            .try_into()
+            .context("Invalid [web_server.notifications] section")?;
+        Ok(Some(config))
+    }
+
+    fn retry_delay(&self) -> Duration {
+        Duration::from_millis(self.retry_delay_ms)
+    }
+}
+
+/// What a sink receives, serialized as JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Notification {
+    pub event: NotificationEvent,
+    pub thread_id: String,
+    pub turn_id: String,
+    /// One-line summary for display
+    pub text: String,
+}
+
+impl Notification {
+    /// The notification an event triggers, if any.
+    pub fn from_event(thread_id: ThreadId, event: &Event) -> Option<Self> {
+        let (kind, text) = match &event.msg {
+            EventMsg::TurnComplete(ev) => (
+                NotificationEvent::TurnCompleted,
+                match ev.last_agent_message.as_deref().map(excerpt) {
+                    Some(message) if !message.is_empty() => format!("Turn finished: {message}"),
+                    _ => "Turn finished".to_string(),
+                },
+            ),
+            EventMsg::Error(ev) if ev.affects_turn_status() => (
+                NotificationEvent::TurnFailed,
+                format!("Turn failed: {}", excerpt(&ev.message)),
+            ),
+            EventMsg::ExecApprovalRequest(ev) => (
+                NotificationEvent::ApprovalRequested,
+                format!("Approval needed to run: {}", excerpt(&ev.command.join(" "))),
+            ),
+            EventMsg::ApplyPatchApprovalRequest(ev) => (
+                NotificationEvent::ApprovalRequested,
+                format!("Approval needed to edit {} file(s)", ev.changes.len()),
+            ),
+            _ => return None,
+        };
+        Some(Self {
+            event: kind,
+            thread_id: thread_id.to_string(),
+            turn_id: event.id.clone(),
+            text,
+        })
+    }
+}
+
+/// First line of `text`, shortened to [`MAX_TEXT_CHARS`].
+fn excerpt(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default().trim();
+    if line.chars().count() <= MAX_TEXT_CHARS {
+        return line.to_string();
+    }
+    let mut shortened: String = line.chars().take(MAX_TEXT_CHARS).collect();
+    shortened.push('…');
+    shortened
+}
+
+/// Destination for notifications; implemented by the built-in sinks and
+/// replaceable in tests.
+pub trait NotificationSink: Send + Sync {
+    /// Names the sink in logs.
+    fn describe(&self) -> String;
+
+    /// Makes one delivery attempt of the serialized notification.
+    fn deliver<'a>(&'a self, body: &'a [u8]) -> BoxFuture<'a, anyhow::Result<()>>;
+}
+
+pub struct WebhookSink {
+    client: reqwest::Client,
+    url: String,
+    headers: BTreeMap<String, String>,
+}
+
+impl WebhookSink {
+    pub fn new(url: String, headers: BTreeMap<String, String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+            headers,
+        }
+    }
+}
+
+impl NotificationSink for WebhookSink {
+    fn describe(&self) -> String {
+        // Webhook URLs often embed a secret, so only the host is logged.
+        let host = reqwest::Url::parse(&self.url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_default();
+        format!("webhook to {host}")
+    }
+
+    fn deliver<'a>(&'a self, body: &'a [u8]) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let mut request = self
+                .client
+                .post(&self.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(body.to_vec());
+            for (name, value) in &self.headers {
+                request = request.header(name, value);
+            }
+            request.send().await?.error_for_status()?;
+            Ok(())
+        })
+    }
+}
+
+pub struct CommandSink {
+    command: Vec<String>,
+}
+
+impl CommandSink {
+    pub fn new(command: Vec<String>) -> anyhow::Result<Self> {
+        if command.is_empty() {
+            anyhow::bail!("command sink needs a program to run");
+        }
+        Ok(Self { command })
+    }
+}
+
+impl NotificationSink for CommandSink {
+    fn describe(&self) -> String {
+        format!("command {}", self.command.join(" "))
+    }
+
+    fn deliver<'a>(&'a self, body: &'a [u8]) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let (program, args) = self
+                .command
+                .split_first()
+                .context("command sink needs a program to run")?;
+            let mut child = tokio::process::Command::new(program)
+                .args(args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true)
+                .spawn()?;
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin.write_all(body).await?;
+            }
+            let output = child.wait_with_output().await?;
+            if !output.status.success() {
+                anyhow::bail!(
+                    "exited with {}: {}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Filters events into notifications and hands them to the delivery task.
+/// The default notifier has no sinks and drops everything.
+#[derive(Default)]
+pub struct Notifier {
+    events: HashSet<NotificationEvent>,
+    sender: Option<mpsc::UnboundedSender<Notification>>,
+    /// Threads whose owner opted out of notifications.
+    muted: Mutex<HashSet<ThreadId>>,
+}
+
+impl Notifier {
+    /// Builds the sinks listed in `config` and starts delivering to them.
+    pub fn from_config(config: &NotificationsConfig) -> anyhow::Result<Self> {
+        let sinks = config
+            .sinks
+            .iter()
+            .map(|sink| -> anyhow::Result<Arc<dyn NotificationSink>> {
+                Ok(match sink {
+                    SinkConfig::Webhook { url, headers } => {
+                        Arc::new(WebhookSink::new(url.clone(), headers.clone()))
+                    }
+                    SinkConfig::Command { command } => Arc::new(CommandSink::new(command.clone())?),
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self::new(config, sinks))
+    }
+
+    /// Starts the delivery task; must be called inside a Tokio runtime.
+    pub fn new(config: &NotificationsConfig, sinks: Vec<Arc<dyn NotificationSink>>) -> Self {
+        if sinks.is_empty() {
+            return Self::default();
+        }
+        let (sender, mut receiver) = mpsc::unbounded_channel::<Notification>();
+        let max_attempts = config.max_attempts.max(1);
+        let retry_delay = config.retry_delay();
+        tokio::spawn(async move {
+            while let Some(notification) = receiver.recv().await {
+                let body = match serde_json::to_vec(&notification) {
+                    Ok(body) => Arc::new(body),
+                    Err(err) => {
+                        tracing::warn!("Failed to serialize notification: {err}");
+                        continue;
+                    }
+                };
+                // One task per sink so a slow sink does not hold up the others.
+                for sink in &sinks {
+                    tokio::spawn(deliver_with_retry(
+                        Arc::clone(sink),
+                        Arc::clone(&body),
+                        max_attempts,
+                        retry_delay,
+                    ));
+                }
+            }
+        });
+        Self {
+            events: config.events.iter().copied().collect(),
+            sender: Some(sender),
+            muted: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.sender.is_some()
+    }
+
+    /// Enqueues the notification `event` triggers, if any. Never blocks.
+    pub fn observe(&self, thread_id: ThreadId, event: &Event) {
+        if !self.is_enabled() {
+            return;
+        }
+        if let Some(notification) = Notification::from_event(thread_id, event) {
+            self.notify(thread_id, notification);
+        }
+    }
+
+    pub fn notify(&self, thread_id: ThreadId, notification: Notification) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+        if !self.events.contains(&notification.event) || self.is_muted(thread_id) {
+            return;
+        }
+        // Only fails once the delivery task is gone, i.e. at shutdown.
+        let _ = sender.send(notification);
+    }
+
+    pub fn is_muted(&self, thread_id: ThreadId) -> bool {
+        self.muted
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains(&thread_id)
+    }
+
+    pub fn set_muted(&self, thread_id: ThreadId, muted: bool) {
+        let mut threads = self.muted.lock().unwrap_or_else(PoisonError::into_inner);
+        if muted {
+            threads.insert(thread_id);
+        } else {
+            threads.remove(&thread_id);
+        }
+    }
+
+    /// Replaces the opt-outs, e.g. with those persisted before a restart.
+    pub fn replace_muted(&self, threads: HashSet<ThreadId>) {
+        *self.muted.lock().unwrap_or_else(PoisonError::into_inner) = threads;
+    }
+}
+
+async fn deliver_with_retry(
+    sink: Arc<dyn NotificationSink>,
+    body: Arc<Vec<u8>>,
+    max_attempts: u32,
+    retry_delay: Duration,
+) {
+    let mut delay = retry_delay;
+    for attempt in 1..=max_attempts {
+        let error = match tokio::time::timeout(DELIVERY_TIMEOUT, sink.deliver(&body)).await {
+            Ok(Ok(())) => return,
+            Ok(Err(err)) => err.to_string(),
+            Err(_) => format!("timed out after {}s", DELIVERY_TIMEOUT.as_secs()),
+        };
+        if attempt == max_attempts {
+            tracing::warn!(
+                "Giving up on notification to {} after {attempt} attempt(s): {error}",
+                sink.describe()
+            );
+            return;
+        }
+        tracing::debug!(
+            "Notification to {} failed (attempt {attempt}): {error}",
+            sink.describe()
+        );
+        tokio::time::sleep(delay).await;
+        delay = delay.saturating_mul(2);
+    }
+}
@@ -0,0 +1,215 @@
+//! First-run pairing for clients that spawn the server themselves.
+//!
+//! While no client is paired the server writes a one-time code to
+//! `codex_home/web-pairing-code`, readable only by the owner. A client running
+//! as the same user reads it and exchanges it at `POST /api/v2/auth/pair` for a
+//! long-lived token. Only the SHA-256 of issued tokens is kept, in
+//! `codex_home/web-tokens.json`. The bearer token printed at startup keeps
+//! working alongside paired tokens.
+
+use rand::RngCore;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
+use std::time::Instant;
+use utoipa::ToSchema;
+
+/// File the pending pairing code is written to.
+pub const PAIRING_CODE_FILE: &str = "web-pairing-code";
+/// File holding the hashes of paired tokens.
+pub const TOKEN_STORE_FILE: &str = "web-tokens.json";
+/// How long a pairing code can be redeemed.
+pub const PAIRING_CODE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Why a pairing code was not accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PairingErrorCode {
+    /// No pending code matches, including one that was already used (401)
+    InvalidCode,
+    /// The code is older than `PAIRING_CODE_TTL` (410)
+    Expired,
+}
+
+impl PairingErrorCode {
+    /// English description; clients localize from the code.
+    pub fn message(self) -> &'static str {
+        match self {
+            PairingErrorCode::InvalidCode => "Pairing code is invalid or already used",
+            PairingErrorCode::Expired => "Pairing code has expired",
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TokenStoreFile {
+    /// Hex SHA-256 of each paired token
+    tokens: Vec<String>,
+}
+
+struct PendingCode {
+    code: String,
+    issued_at: Instant,
+}
+
+pub struct Pairing {
+    codex_home: PathBuf,
+    ttl: Duration,
+    pending: Mutex<Option<PendingCode>>,
+    token_hashes: Mutex<HashSet<String>>,
+}
+
+impl Pairing {
+    /// Loads the paired tokens stored under `codex_home`. A missing or
+    /// unreadable store starts empty.
+    pub fn new(codex_home: PathBuf, ttl: Duration) -> Self {
+        let store_path = codex_home.join(TOKEN_STORE_FILE);
+        let token_hashes = match std::fs::read_to_string(&store_path) {
+            Ok(contents) => match serde_json::from_str::<TokenStoreFile>(&contents) {
+                Ok(store) => store.tokens.into_iter().collect(),
+                Err(err) => {
+                    tracing::warn!("Ignoring malformed {}: {err}", store_path.display());
+                    HashSet::new()
+                }
+            },
+            Err(_) => HashSet::new(),
+        };
+        Self {
+            codex_home,
+            ttl,
+            pending: Mutex::new(None),
+            token_hashes: Mutex::new(token_hashes),
+        }
+    }
+
+    pub fn code_path(&self) -> PathBuf {
+        self.codex_home.join(PAIRING_CODE_FILE)
+    }
+
+    /// Whether any client has paired.
+    pub fn has_paired_tokens(&self) -> bool {
+        !self
+            .token_hashes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_empty()
+    }
+
+    /// Whether `token` was issued by a pairing.
+    pub fn is_paired_token(&self, token: &str) -> bool {
+        self.token_hashes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains(&hash_token(token))
+    }
+
+    /// Replaces any pending code with a new one and writes it to
+    /// [`Self::code_path`].
+    pub fn issue_code(&self) -> std::io::Result<String> {
+        let code = random_hex(16);
+        write_private(&self.code_path(), &code)?;
+        *self.pending.lock().unwrap_or_else(PoisonError::into_inner) = Some(PendingCode {
+            code: code.clone(),
+            issued_at: Instant::now(),
+        });
+        Ok(code)
+    }
+
+    /// Exchanges the pending code for a new token. The code is consumed on
+    /// success and once it expires; a wrong code leaves it pending.
+    pub fn redeem(&self, code: &str) -> Result<String, PairingRedeemError> {
+        let mut pending = self.pending.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(current) = pending.as_ref() else {
+            return Err(PairingErrorCode::InvalidCode.into());
+        };
+        if current.issued_at.elapsed() > self.ttl {
+            *pending = None;
+            self.remove_code_file();
+            return Err(PairingErrorCode::Expired.into());
+        }
+        if current.code != code {
+            return Err(PairingErrorCode::InvalidCode.into());
+        }
+
+        let token = random_hex(32);
+        let mut token_hashes = self
+            .token_hashes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        token_hashes.insert(hash_token(&token));
+        let mut tokens: Vec<String> = token_hashes.iter().cloned().collect();
+        tokens.sort();
+        let contents = serde_json::to_string_pretty(&TokenStoreFile { tokens })
+            .map_err(|err| PairingRedeemError::Store(std::io::Error::other(err)))?;
+        if let Err(err) = write_private(&self.codex_home.join(TOKEN_STORE_FILE), &contents) {
+            token_hashes.remove(&hash_token(&token));
+            return Err(PairingRedeemError::Store(err));
+        }
+
+        *pending = None;
+        self.remove_code_file();
+        Ok(token)
+    }
+
+    fn remove_code_file(&self) {
+        let path = self.code_path();
+        if let Err(err) = std::fs::remove_file(&path)
+            && err.kind() != std::io::ErrorKind::NotFound
+        {
+            tracing::warn!("Failed to remove {}: {err}", path.display());
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PairingRedeemError {
+    Rejected(PairingErrorCode),
+    /// The token could not be persisted; the code stays pending.
+    Store(std::io::Error),
+}
+
+impl From<PairingErrorCode> for PairingRedeemError {
+    fn from(code: PairingErrorCode) -> Self {
+        PairingRedeemError::Rejected(code)
+    }
+}
+
+fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+fn random_hex(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    rand::rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Writes `contents` to `path` with owner-only permissions.
+#[cfg(unix)]
+fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    // `mode` only applies to new files.
+    file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    file.write_all(contents.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn write_private(path: &Path, contents: &str) -> std::io::Result<()> {
+    std::fs::write(path, contents)
+}
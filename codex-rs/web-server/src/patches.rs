@@ -0,0 +1,426 @@
+//! Parsing and verification of client-supplied patches.
+//!
+//! Clients may send either the `apply_patch` envelope the agent uses or a
+//! unified diff (`git diff` output). Unified diffs are rewritten into the
+//! envelope format, so core applies both through the same runtime. Before a
+//! patch is handed to core, every file section is checked against the
+//! workspace so conflicts are reported per file instead of failing the
+//! whole patch on the first mismatch.
+
+use codex_apply_patch::CODEX_CORE_APPLY_PATCH_ARG1;
+use codex_apply_patch::Hunk;
+use codex_apply_patch::ParseError;
+use codex_apply_patch::parse_patch;
+use codex_apply_patch::unified_diff_from_chunks;
+use serde::Serialize;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+use utoipa::ToSchema;
+
+const BEGIN_PATCH: &str = "*** Begin Patch";
+const END_PATCH: &str = "*** End Patch";
+
+/// Where and why a patch could not be parsed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct PatchHunkError {
+    /// 1-based line of the submitted patch, when the error has one
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PatchFileAction {
+    Add,
+    Delete,
+    Update,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PatchFileStatus {
+    /// The file section applies cleanly to the current workspace
+    Verified,
+    /// The file is missing or its contents do not match the hunks
+    Conflict,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct PatchFileResult {
+    /// Path as written in the patch, relative to the thread's cwd
+    pub path: String,
+    /// Destination of a rename
+    pub move_path: Option<String>,
+    pub action: PatchFileAction,
+    pub status: PatchFileStatus,
+    /// Why the file conflicts
+    pub error: Option<String>,
+}
+
+/// A parsed patch, normalized to the `apply_patch` envelope format.
+#[derive(Debug)]
+pub struct ParsedPatch {
+    pub envelope: String,
+    pub hunks: Vec<Hunk>,
+}
+
+/// Parses `patch` in either supported format.
+pub fn parse(patch: &str) -> Result<ParsedPatch, Vec<PatchHunkError>> {
+    let envelope = if patch.trim_start().starts_with(BEGIN_PATCH) {
+        patch.trim().to_string()
+    } else {
+        unified_diff_to_envelope(patch)?
+    };
+    let args = parse_patch(&envelope).map_err(|err| vec![parse_error(err)])?;
+    if args.hunks.is_empty() {
+        return Err(vec![PatchHunkError {
+            line: None,
+            message: "patch does not change any files".to_string(),
+        }]);
+    }
+    Ok(ParsedPatch {
+        envelope,
+        hunks: args.hunks,
+    })
+}
+
+fn parse_error(err: ParseError) -> PatchHunkError {
+    match err {
+        ParseError::InvalidPatchError(message) => PatchHunkError {
+            line: None,
+            message,
+        },
+        ParseError::InvalidHunkError {
+            message,
+            line_number,
+        } => PatchHunkError {
+            line: Some(line_number),
+            message,
+        },
+    }
+}
+
+/// Handles `<exe> --codex-run-as-apply-patch <PATCH>`, which is how core's
+/// patch runtime applies patches inside the sandbox. Returns the exit code
+/// when this process was started for that purpose.
+pub fn run_apply_patch_invocation() -> Option<i32> {
+    let mut args = std::env::args_os().skip(1);
+    if args.next()? != CODEX_CORE_APPLY_PATCH_ARG1 {
+        return None;
+    }
+    let Some(patch) = args.next().and_then(|arg| arg.into_string().ok()) else {
+        eprintln!("Error: {CODEX_CORE_APPLY_PATCH_ARG1} requires a UTF-8 PATCH argument.");
+        return Some(1);
+    };
+    let mut stdout = std::io::stdout();
+    let mut stderr = std::io::stderr();
+    match codex_apply_patch::apply_patch(&patch, &mut stdout, &mut stderr) {
+        Ok(()) => Some(0),
+        Err(_) => Some(1),
+    }
+}
+
+/// Checks each file section of `hunks` against the files under `cwd`. A
+/// patch that names a path outside `cwd` is refused before any file is read.
+/// Blocks on the filesystem.
+pub fn verify(hunks: &[Hunk], cwd: &Path) -> Result<Vec<PatchFileResult>, Vec<PatchHunkError>> {
+    let escaping: Vec<PatchHunkError> = hunks
+        .iter()
+        .flat_map(|hunk| {
+            let move_path = match hunk {
+                Hunk::UpdateFile { move_path, .. } => move_path.as_deref(),
+                Hunk::AddFile { .. } | Hunk::DeleteFile { .. } => None,
+            };
+            std::iter::once(hunk_path(hunk)).chain(move_path)
+        })
+        .filter(|path| !is_within(cwd, path))
+        .map(|path| PatchHunkError {
+            line: None,
+            message: format!(
+                "`{}` is outside the thread's working directory",
+                path.display()
+            ),
+        })
+        .collect();
+    if !escaping.is_empty() {
+        return Err(escaping);
+    }
+
+    Ok(hunks
+        .iter()
+        .map(|hunk| {
+            let path = hunk.resolve_path(cwd);
+            let (relative, move_path, action, error) = match hunk {
+                Hunk::AddFile { path: relative, .. } => {
+                    (relative, None, PatchFileAction::Add, None)
+                }
+                Hunk::DeleteFile { path: relative } => {
+                    let error = (!path.is_file()).then(|| "file does not exist".to_string());
+                    (relative, None, PatchFileAction::Delete, error)
+                }
+                Hunk::UpdateFile {
+                    path: relative,
+                    move_path,
+                    chunks,
+                } => {
+                    let error = unified_diff_from_chunks(&path, chunks)
+                        .err()
+                        .map(|err| err.to_string());
+                    (
+                        relative,
+                        move_path.as_deref(),
+                        PatchFileAction::Update,
+                        error,
+                    )
+                }
+            };
+            PatchFileResult {
+                path: relative.display().to_string(),
+                move_path: move_path.map(|path| path.display().to_string()),
+                action,
+                status: if error.is_some() {
+                    PatchFileStatus::Conflict
+                } else {
+                    PatchFileStatus::Verified
+                },
+                error,
+            }
+        })
+        .collect())
+}
+
+fn hunk_path(hunk: &Hunk) -> &Path {
+    match hunk {
+        Hunk::AddFile { path, .. } | Hunk::DeleteFile { path } | Hunk::UpdateFile { path, .. } => {
+            path
+        }
+    }
+}
+
+/// Whether `path`, relative to `cwd` or absolute, stays under `cwd` once `..`
+/// is resolved and symlinks are followed as far as the path exists.
+fn is_within(cwd: &Path, path: &Path) -> bool {
+    let Ok(root) = cwd.canonicalize() else {
+        return false;
+    };
+    let mut normalized = PathBuf::new();
+    for component in cwd.join(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    // The deepest existing ancestor decides where a symlink leads; the rest
+    // of the path does not exist yet, e.g. a file the patch adds.
+    let mut existing = normalized.as_path();
+    let mut missing = Vec::new();
+    let resolved = loop {
+        if let Ok(resolved) = existing.canonicalize() {
+            break resolved;
+        }
+        let (Some(parent), Some(name)) = (existing.parent(), existing.file_name()) else {
+            return false;
+        };
+        missing.push(name);
+        existing = parent;
+    };
+    missing
+        .into_iter()
+        .rev()
+        .fold(resolved, |path, name| path.join(name))
+        .starts_with(&root)
+}
+
+/// One `---`/`+++` section of a unified diff.
+enum DiffFile {
+    Add {
+        path: String,
+        lines: Vec<String>,
+    },
+    Delete {
+        path: String,
+    },
+    Update {
+        path: String,
+        move_path: Option<String>,
+        lines: Vec<String>,
+    },
+}
+
+/// Rewrites a unified diff as an `apply_patch` envelope. Hunk bodies carry
+/// over unchanged since both formats prefix lines with ` `, `-` and `+`;
+/// line numbers are dropped because `apply_patch` locates hunks by content.
+fn unified_diff_to_envelope(diff: &str) -> Result<String, Vec<PatchHunkError>> {
+    let lines: Vec<&str> = diff.lines().collect();
+    let mut files = Vec::new();
+    let mut errors = Vec::new();
+    let mut index = 0;
+
+    while index < lines.len() {
+        let Some(old) = lines[index].strip_prefix("--- ") else {
+            // `diff --git`, `index`, mode lines and other git metadata.
+            index += 1;
+            continue;
+        };
+        let line = index + 1;
+        let Some(new) = lines.get(index + 1).and_then(|l| l.strip_prefix("+++ ")) else {
+            errors.push(PatchHunkError {
+                line: Some(line + 1),
+                message: "expected a `+++` line after `---`".to_string(),
+            });
+            index += 1;
+            continue;
+        };
+        index += 2;
+
+        let mut body = Vec::new();
+        while let Some(header) = lines.get(index).filter(|l| l.starts_with("@@")) {
+            let header_line = index + 1;
+            index += 1;
+            let Some((mut old_count, mut new_count)) = hunk_counts(header) else {
+                errors.push(PatchHunkError {
+                    line: Some(header_line),
+                    message: format!("invalid hunk header `{header}`"),
+                });
+                break;
+            };
+            body.push("@@".to_string());
+            while old_count > 0 || new_count > 0 {
+                let Some(&text) = lines.get(index) else {
+                    break;
+                };
+                // Editors often strip the space off empty context lines.
+                let (prefix, rest) = match text.chars().next() {
+                    Some(prefix @ (' ' | '-' | '+')) => (prefix, &text[1..]),
+                    None => (' ', ""),
+                    Some('\\') => {
+                        index += 1;
+                        continue;
+                    }
+                    Some(_) => break,
+                };
+                match prefix {
+                    ' ' if old_count > 0 && new_count > 0 => {
+                        old_count -= 1;
+                        new_count -= 1;
+                    }
+                    '-' if old_count > 0 => old_count -= 1,
+                    '+' if new_count > 0 => new_count -= 1,
+                    _ => break,
+                }
+                body.push(format!("{prefix}{rest}"));
+                index += 1;
+            }
+            if old_count > 0 || new_count > 0 {
+                errors.push(PatchHunkError {
+                    line: Some(header_line),
+                    message: format!("hunk is short by {old_count} old and {new_count} new lines"),
+                });
+            }
+            // A `\ No newline at end of file` marker may trail the hunk.
+            while lines.get(index).is_some_and(|l| l.starts_with('\\')) {
+                index += 1;
+            }
+        }
+
+        let old = diff_path(old);
+        let new = diff_path(new);
+        let file = match (old, new) {
+            (None, Some(path)) => DiffFile::Add {
+                path,
+                lines: body
+                    .iter()
+                    .filter(|l| l.starts_with('+'))
+                    .cloned()
+                    .collect(),
+            },
+            (Some(path), None) => DiffFile::Delete { path },
+            (Some(path), Some(new)) => {
+                if body.is_empty() && path == new {
+                    errors.push(PatchHunkError {
+                        line: Some(line),
+                        message: format!("no hunks for `{path}`"),
+                    });
+                    continue;
+                }
+                DiffFile::Update {
+                    move_path: (new != path).then_some(new),
+                    path,
+                    lines: body,
+                }
+            }
+            (None, None) => {
+                errors.push(PatchHunkError {
+                    line: Some(line),
+                    message: "both sides of the file header are /dev/null".to_string(),
+                });
+                continue;
+            }
+        };
+        files.push(file);
+    }
+
+    if files.is_empty() && errors.is_empty() {
+        errors.push(PatchHunkError {
+            line: None,
+            message: format!("expected a unified diff or a patch starting with `{BEGIN_PATCH}`"),
+        });
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut envelope = vec![BEGIN_PATCH.to_string()];
+    for file in files {
+        match file {
+            DiffFile::Add { path, lines } => {
+                envelope.push(format!("*** Add File: {path}"));
+                envelope.extend(lines);
+            }
+            DiffFile::Delete { path } => envelope.push(format!("*** Delete File: {path}")),
+            DiffFile::Update {
+                path,
+                move_path,
+                lines,
+            } => {
+                envelope.push(format!("*** Update File: {path}"));
+                if let Some(move_path) = move_path {
+                    envelope.push(format!("*** Move to: {move_path}"));
+                }
+                envelope.extend(lines);
+            }
+        }
+    }
+    envelope.push(END_PATCH.to_string());
+    Ok(envelope.join("\n"))
+}
+
+/// Old and new line counts from `@@ -a,b +c,d @@`; a missing count is 1.
+fn hunk_counts(header: &str) -> Option<(usize, usize)> {
+    let mut ranges = header.strip_prefix("@@ ")?.split_whitespace();
+    let old = ranges.next()?.strip_prefix('-')?;
+    let new = ranges.next()?.strip_prefix('+')?;
+    let count = |range: &str| match range.split_once(',') {
+        Some((_, count)) => count.parse().ok(),
+        None => range.parse::<usize>().ok().map(|_| 1),
+    };
+    Some((count(old)?, count(new)?))
+}
+
+/// Path of a `---`/`+++` header without git's `a/`/`b/` prefix or a trailing
+/// timestamp; `None` for `/dev/null`.
+fn diff_path(header: &str) -> Option<String> {
+    let path = header.split('\t').next().unwrap_or_default().trim();
+    if path == "/dev/null" {
+        return None;
+    }
+    let path = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path);
+    Some(path.to_string())
+}
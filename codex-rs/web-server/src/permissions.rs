@@ -0,0 +1,137 @@
+//! Audit of file permissions under `codex_home`.
+//!
+//! The bearer token, stored credentials, rollouts and uploaded attachments all
+//! live under `codex_home`, so a directory other users can read or write
+//! undermines the server's auth model. On Unix the audit flags any of them
+//! with group or other permission bits; Windows reports its own
+//! `windows/worldWritableWarning`, so there the audit finds nothing.
+
+use codex_app_server_protocol::ConfigWarningNotification;
+use serde::Serialize;
+use std::path::Path;
+use utoipa::ToSchema;
+
+/// Command-line flag that refuses to start while the audit finds problems.
+pub const STRICT_PERMISSIONS_FLAG: &str = "--strict-permissions";
+
+/// File holding stored credentials when they are not in the keyring.
+#[cfg(unix)]
+const AUTH_FILE: &str = "auth.json";
+#[cfg(unix)]
+const DIR_MODE: u32 = 0o700;
+#[cfg(unix)]
+const FILE_MODE: u32 = 0o600;
+
+/// A path whose permissions are broader than owner-only.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct PermissionIssue {
+    pub path: String,
+    /// Current mode in octal, e.g. `0755`
+    #[schema(example = "0755")]
+    pub mode: String,
+    /// Mode the repair sets, `0700` for directories and `0600` for files
+    #[schema(example = "0700")]
+    pub expected: String,
+}
+
+/// Paths the audit covers and the mode each should have. Missing paths are
+/// skipped, e.g. `auth.json` when credentials live in the keyring.
+#[cfg(unix)]
+fn targets(codex_home: &Path, attachments_dir: &Path) -> [(std::path::PathBuf, u32); 4] {
+    [
+        (codex_home.to_path_buf(), DIR_MODE),
+        (codex_home.join(codex_core::SESSIONS_SUBDIR), DIR_MODE),
+        (attachments_dir.to_path_buf(), DIR_MODE),
+        (codex_home.join(AUTH_FILE), FILE_MODE),
+    ]
+}
+
+/// Lists the audited paths that group or other users can access.
+pub fn audit(codex_home: &Path, attachments_dir: &Path) -> Vec<PermissionIssue> {
+    find_issues(codex_home, attachments_dir)
+        .into_iter()
+        .map(|(issue, _)| issue)
+        .collect()
+}
+
+/// Tightens every path the audit flags and returns the ones it changed. A
+/// path that cannot be changed is logged and left for the next audit.
+pub fn repair(codex_home: &Path, attachments_dir: &Path) -> Vec<PermissionIssue> {
+    find_issues(codex_home, attachments_dir)
+        .into_iter()
+        .filter_map(
+            |(issue, mode)| match set_mode(Path::new(&issue.path), mode) {
+                Ok(()) => Some(issue),
+                Err(err) => {
+                    tracing::warn!("Failed to restrict permissions of {}: {err}", issue.path);
+                    None
+                }
+            },
+        )
+        .collect()
+}
+
+#[cfg(unix)]
+fn find_issues(codex_home: &Path, attachments_dir: &Path) -> Vec<(PermissionIssue, u32)> {
+    use std::os::unix::fs::PermissionsExt;
+
+    targets(codex_home, attachments_dir)
+        .into_iter()
+        .filter_map(|(path, expected)| {
+            let mode = std::fs::metadata(&path).ok()?.permissions().mode() & 0o777;
+            let issue = PermissionIssue {
+                path: path.display().to_string(),
+                mode: format!("{mode:04o}"),
+                expected: format!("{expected:04o}"),
+            };
+            (mode & 0o077 != 0).then_some((issue, expected))
+        })
+        .collect()
+}
+
+#[cfg(not(unix))]
+fn find_issues(_codex_home: &Path, _attachments_dir: &Path) -> Vec<(PermissionIssue, u32)> {
+    Vec::new()
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Summary of `issues` for logs and `/health/ready`.
+pub fn describe(issues: &[PermissionIssue]) -> String {
+    issues
+        .iter()
+        .map(|issue| {
+            format!(
+                "{} is {} (expected {})",
+                issue.path, issue.mode, issue.expected
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// `configWarning` sent to event stream subscribers while issues remain.
+pub fn config_warning(issues: &[PermissionIssue]) -> Option<ConfigWarningNotification> {
+    if issues.is_empty() {
+        return None;
+    }
+    Some(ConfigWarningNotification {
+        summary: "Codex files are accessible to other users".to_string(),
+        details: Some(format!(
+            "{}. Run POST /api/v2/maintenance/fix-permissions to restrict them.",
+            describe(issues)
+        )),
+        path: None,
+        range: None,
+    })
+}
@@ -0,0 +1,172 @@
+//! Per-thread model provider overrides for self-hosted gateways.
+//!
+//! `POST /api/v2/threads` may point a named provider at another base URL and
+//! add request headers for that thread only. Hosts must be listed in
+//! `CODEX_WEB_PROVIDER_HOSTS`; without the list, overrides are refused.
+//! Header values often carry gateway credentials, so they are accepted but
+//! never serialized, logged or returned.
+
+use codex_core::ModelProviderInfo;
+use codex_core::config::Config;
+use http::HeaderName;
+use http::HeaderValue;
+use reqwest::Url;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fmt;
+use utoipa::ToSchema;
+
+use crate::error::ApiError;
+use crate::model_validation::check_identifier;
+
+/// Permitted gateway hosts, comma separated (e.g. `gw.internal,10.0.0.5`).
+pub const PROVIDER_HOSTS_ENV: &str = "CODEX_WEB_PROVIDER_HOSTS";
+
+/// Overrides for one provider, keyed by provider id in the request.
+#[derive(Clone, Default, Deserialize, ToSchema)]
+pub struct ProviderOverride {
+    /// Replaces the provider's `base_url`; the host must be allow-listed
+    #[schema(example = "https://gateway.internal/project-a/v1")]
+    pub base_url: Option<String>,
+    /// Added to the provider's `http_headers`, replacing same-named entries.
+    /// Write-only: values are never echoed.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+impl fmt::Debug for ProviderOverride {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProviderOverride")
+            .field("base_url", &self.base_url)
+            .field("headers", &self.headers.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Hosts that provider overrides may point at.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProviderHosts {
+    hosts: Vec<String>,
+}
+
+impl ProviderHosts {
+    pub fn new(hosts: impl IntoIterator<Item = String>) -> Self {
+        let hosts = hosts
+            .into_iter()
+            .map(|host| host.trim().to_ascii_lowercase())
+            .filter(|host| !host.is_empty())
+            .collect();
+        Self { hosts }
+    }
+
+    pub fn from_env() -> Self {
+        match std::env::var(PROVIDER_HOSTS_ENV) {
+            Ok(value) => Self::new(value.split(',').map(str::to_string)),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn hosts(&self) -> &[String] {
+        &self.hosts
+    }
+
+    fn allows(&self, host: &str) -> bool {
+        self.hosts
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(host))
+    }
+}
+
+/// Applies `overrides` to the thread's config: the active provider when its
+/// id matches, and the entry in `model_providers` that tools resolve by id.
+///
+/// Everything is validated before anything changes, so a rejected request
+/// leaves `config` untouched.
+pub fn apply_provider_overrides(
+    config: &mut Config,
+    overrides: &HashMap<String, ProviderOverride>,
+    allowed: &ProviderHosts,
+) -> Result<(), ApiError> {
+    if overrides.is_empty() {
+        return Ok(());
+    }
+    for (provider_id, provider_override) in overrides {
+        check_identifier("Provider", provider_id)?;
+        if *provider_id != config.model_provider_id
+            && !config.model_providers.contains_key(provider_id)
+        {
+            return Err(ApiError::InvalidRequest(format!(
+                "Unknown model provider {provider_id:?} in provider_overrides"
+            )));
+        }
+        validate(provider_id, provider_override, allowed)?;
+    }
+
+    for (provider_id, provider_override) in overrides {
+        if *provider_id == config.model_provider_id {
+            apply(&mut config.model_provider, provider_override);
+        }
+        if let Some(provider) = config.model_providers.get_mut(provider_id) {
+            apply(provider, provider_override);
+        }
+    }
+    Ok(())
+}
+
+fn validate(
+    provider_id: &str,
+    provider_override: &ProviderOverride,
+    allowed: &ProviderHosts,
+) -> Result<(), ApiError> {
+    if let Some(base_url) = &provider_override.base_url {
+        let url = Url::parse(base_url).map_err(|err| {
+            ApiError::InvalidRequest(format!(
+                "Invalid base_url for provider {provider_id:?}: {err}"
+            ))
+        })?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(ApiError::InvalidRequest(format!(
+                "base_url for provider {provider_id:?} must use http or https"
+            )));
+        }
+        let host = url.host_str().unwrap_or_default();
+        if !allowed.allows(host) {
+            return Err(ApiError::ProviderHostNotAllowed {
+                host: host.to_string(),
+                allowed_hosts: allowed.hosts().to_vec(),
+            });
+        }
+    }
+    for (name, value) in &provider_override.headers {
+        if HeaderName::from_bytes(name.as_bytes()).is_err() {
+            return Err(ApiError::InvalidRequest(format!(
+                "Invalid header name {name:?} for provider {provider_id:?}"
+            )));
+        }
+        // The value is not quoted back; it is likely a secret.
+        if HeaderValue::from_str(value).is_err() {
+            return Err(ApiError::InvalidRequest(format!(
+                "Invalid value for header {name:?} of provider {provider_id:?}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn apply(provider: &mut ModelProviderInfo, provider_override: &ProviderOverride) {
+    if let Some(base_url) = &provider_override.base_url {
+        provider.base_url = Some(base_url.clone());
+    }
+    if !provider_override.headers.is_empty() {
+        provider
+            .http_headers
+            .get_or_insert_with(HashMap::new)
+            .extend(
+                provider_override
+                    .headers
+                    .iter()
+                    .map(|(name, value)| (name.clone(), value.clone())),
+            );
+    }
+}
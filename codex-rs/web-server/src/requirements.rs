@@ -0,0 +1,150 @@
+//! Enforcement of managed config requirements for web clients.
+//!
+//! `ConfigRequirementsToml` only constrains approval policies and sandbox
+//! modes for now; there is no model allowlist, so requested models pass
+//! through unchecked.
+
+use codex_core::config::Config;
+use codex_core::config_loader::ConfigRequirementsToml;
+use codex_core::config_loader::SandboxModeRequirement;
+use codex_protocol::config_types::SandboxMode;
+use codex_protocol::protocol::AskForApproval;
+use codex_protocol::protocol::SandboxPolicy;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::ApiError;
+use crate::state::WebServerState;
+
+/// A requested value that is outside the allowed set of a requirement.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RequirementViolation {
+    /// Requirement key, e.g. `allowed_approval_policies`
+    pub constraint: String,
+    pub requested: Value,
+    pub allowed: Vec<Value>,
+}
+
+fn check_allowed<T: PartialEq + Serialize>(
+    constraint: &str,
+    allowed: Option<&[T]>,
+    requested: &T,
+) -> Result<(), RequirementViolation> {
+    match allowed {
+        Some(allowed) if !allowed.contains(requested) => Err(RequirementViolation {
+            constraint: constraint.to_string(),
+            requested: serde_json::to_value(requested).unwrap_or_default(),
+            allowed: allowed
+                .iter()
+                .map(|value| serde_json::to_value(value).unwrap_or_default())
+                .collect(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+pub fn check_approval_policy(
+    requirements: &ConfigRequirementsToml,
+    approval_policy: AskForApproval,
+) -> Result<(), RequirementViolation> {
+    check_allowed(
+        "allowed_approval_policies",
+        requirements.allowed_approval_policies.as_deref(),
+        &approval_policy,
+    )
+}
+
+pub fn check_sandbox_mode(
+    requirements: &ConfigRequirementsToml,
+    sandbox_mode: SandboxMode,
+) -> Result<(), RequirementViolation> {
+    check_allowed(
+        "allowed_sandbox_modes",
+        requirements.allowed_sandbox_modes.as_deref(),
+        &SandboxModeRequirement::from(sandbox_mode),
+    )
+}
+
+/// Checks a single config write. Only `approval_policy` and `sandbox_mode`
+/// keys (top-level or under `profiles.<name>`) are constrained; values that
+/// do not parse are left for the config service to reject.
+pub fn check_config_edit(
+    requirements: &ConfigRequirementsToml,
+    key_path: &str,
+    value: &Value,
+) -> Result<(), RequirementViolation> {
+    let key = match key_path.split('.').collect::<Vec<_>>().as_slice() {
+        [key] | ["profiles", _, key] => *key,
+        _ => return Ok(()),
+    };
+    match key {
+        "approval_policy" => match serde_json::from_value(value.clone()) {
+            Ok(approval_policy) => check_approval_policy(requirements, approval_policy),
+            Err(_) => Ok(()),
+        },
+        "sandbox_mode" => match serde_json::from_value(value.clone()) {
+            Ok(sandbox_mode) => check_sandbox_mode(requirements, sandbox_mode),
+            Err(_) => Ok(()),
+        },
+        _ => Ok(()),
+    }
+}
+
+/// Validates per-thread overrides against the server's requirements.
+pub async fn check_thread_overrides(
+    state: &WebServerState,
+    approval_policy: Option<AskForApproval>,
+    sandbox_mode: Option<SandboxMode>,
+) -> Result<(), ApiError> {
+    if approval_policy.is_none() && sandbox_mode.is_none() {
+        return Ok(());
+    }
+    let Some(requirements) = state.config_service.read_requirements().await? else {
+        return Ok(());
+    };
+    if let Some(approval_policy) = approval_policy {
+        check_approval_policy(&requirements, approval_policy)?;
+    }
+    if let Some(sandbox_mode) = sandbox_mode {
+        check_sandbox_mode(&requirements, sandbox_mode)?;
+    }
+    Ok(())
+}
+
+/// Applies overrides already accepted by [`check_thread_overrides`].
+pub fn apply_thread_overrides(
+    config: &mut Config,
+    approval_policy: Option<AskForApproval>,
+    sandbox_mode: Option<SandboxMode>,
+) -> Result<(), ApiError> {
+    if let Some(approval_policy) = approval_policy {
+        config
+            .permissions
+            .approval_policy
+            .set(approval_policy)
+            .map_err(|e| ApiError::InvalidRequest(e.to_string()))?;
+    }
+    if let Some(sandbox_mode) = sandbox_mode {
+        let sandbox_policy = match sandbox_mode {
+            SandboxMode::ReadOnly => SandboxPolicy::new_read_only_policy(),
+            SandboxMode::WorkspaceWrite => SandboxPolicy::new_workspace_write_policy(),
+            SandboxMode::DangerFullAccess => SandboxPolicy::DangerFullAccess,
+        };
+        config
+            .permissions
+            .sandbox_policy
+            .set(sandbox_policy)
+            .map_err(|e| ApiError::InvalidRequest(e.to_string()))?;
+    }
+    Ok(())
+}
+
+/// The sandbox mode a policy corresponds to; `None` for an external sandbox.
+pub fn sandbox_mode_of(policy: &SandboxPolicy) -> Option<SandboxMode> {
+    match policy {
+        SandboxPolicy::ReadOnly { .. } => Some(SandboxMode::ReadOnly),
+        SandboxPolicy::WorkspaceWrite { .. } => Some(SandboxMode::WorkspaceWrite),
+        SandboxPolicy::DangerFullAccess => Some(SandboxMode::DangerFullAccess),
+        SandboxPolicy::ExternalSandbox { .. } => None,
+    }
+}
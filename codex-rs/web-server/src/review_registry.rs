@@ -0,0 +1,128 @@
+//! Reviews started through the API, for `GET /api/v2/reviews` and
+//! cancellation.
+//!
+//! A review is identified by the id of the turn that runs it. Its status
+//! follows the thread's event stream: the turn completing finishes the review,
+//! and the turn being aborted cancels it. Cancelling through the API marks the
+//! review first, so the abort that follows does not change it again.
+
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewStatus {
+    Running,
+    Completed,
+    /// Cancelled through the API or interrupted
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewDeliveryMode {
+    /// Runs in the thread it was started on
+    Inline,
+    /// Runs in a thread created for it
+    Detached,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct ReviewSummary {
+    pub review_id: String,
+    pub thread_id: String,
+    pub delivery: ReviewDeliveryMode,
+    pub status: ReviewStatus,
+    /// Unix seconds
+    pub started_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CancelReviewError {
+    NotFound,
+    /// The review already completed or was cancelled
+    Finished(ReviewStatus),
+}
+
+#[derive(Debug, Default)]
+pub struct ReviewRegistry {
+    reviews: Mutex<HashMap<String, ReviewSummary>>,
+}
+
+impl ReviewRegistry {
+    pub fn register(&self, review_id: &str, thread_id: ThreadId, delivery: ReviewDeliveryMode) {
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs() as i64);
+        self.lock().insert(
+            review_id.to_string(),
+            ReviewSummary {
+                review_id: review_id.to_string(),
+                thread_id: thread_id.to_string(),
+                delivery,
+                status: ReviewStatus::Running,
+                started_at,
+            },
+        );
+    }
+
+    pub fn get(&self, review_id: &str) -> Option<ReviewSummary> {
+        self.lock().get(review_id).cloned()
+    }
+
+    /// All reviews, newest first.
+    pub fn list(&self) -> Vec<ReviewSummary> {
+        let mut reviews: Vec<_> = self.lock().values().cloned().collect();
+        reviews.sort_by(|a, b| {
+            b.started_at
+                .cmp(&a.started_at)
+                .then_with(|| b.review_id.cmp(&a.review_id))
+        });
+        reviews
+    }
+
+    /// Marks a running review cancelled and returns it. The caller still has
+    /// to interrupt its turn.
+    pub fn cancel(&self, review_id: &str) -> Result<ReviewSummary, CancelReviewError> {
+        let mut reviews = self.lock();
+        let review = reviews
+            .get_mut(review_id)
+            .ok_or(CancelReviewError::NotFound)?;
+        if review.status != ReviewStatus::Running {
+            return Err(CancelReviewError::Finished(review.status));
+        }
+        review.status = ReviewStatus::Cancelled;
+        Ok(review.clone())
+    }
+
+    /// Finishes the review whose turn `event` ends.
+    pub fn observe(&self, thread_id: ThreadId, event: &Event) {
+        let (turn_id, status) = match &event.msg {
+            EventMsg::TurnComplete(ev) => (ev.turn_id.as_str(), ReviewStatus::Completed),
+            EventMsg::TurnAborted(ev) => (
+                ev.turn_id.as_deref().unwrap_or(event.id.as_str()),
+                ReviewStatus::Cancelled,
+            ),
+            _ => return,
+        };
+        let thread_id = thread_id.to_string();
+        if let Some(review) = self.lock().get_mut(turn_id)
+            && review.thread_id == thread_id
+            && review.status == ReviewStatus::Running
+        {
+            review.status = status;
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, ReviewSummary>> {
+        self.reviews.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,80 @@
+//! Moves rollout files between `sessions` and `archived_sessions`.
+//!
+//! Archived rollouts keep their file name, which carries the date, so resume
+//! can put them back under the `sessions/YYYY/MM/DD` directory they came from.
+
+use codex_protocol::ThreadId;
+use std::ffi::OsStr;
+use std::io;
+use std::io::ErrorKind;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Moves `rollout_path` from the sessions directory into
+/// `archived_sessions` and returns the new path.
+pub async fn archive_rollout(
+    codex_home: &Path,
+    thread_id: ThreadId,
+    rollout_path: &Path,
+) -> io::Result<PathBuf> {
+    let sessions_dir = codex_home.join(codex_core::SESSIONS_SUBDIR);
+    let (rollout_path, file_name) = checked_rollout(&sessions_dir, thread_id, rollout_path).await?;
+
+    let archived_dir = codex_home.join(codex_core::ARCHIVED_SESSIONS_SUBDIR);
+    tokio::fs::create_dir_all(&archived_dir).await?;
+    let archived_path = archived_dir.join(file_name);
+    tokio::fs::rename(&rollout_path, &archived_path).await?;
+    Ok(archived_path)
+}
+
+/// Moves an archived rollout back under the sessions directory and returns
+/// the new path.
+pub async fn restore_rollout(
+    codex_home: &Path,
+    thread_id: ThreadId,
+    archived_path: &Path,
+) -> io::Result<PathBuf> {
+    let archived_dir = codex_home.join(codex_core::ARCHIVED_SESSIONS_SUBDIR);
+    let (archived_path, file_name) =
+        checked_rollout(&archived_dir, thread_id, archived_path).await?;
+
+    let mut restored_dir = codex_home.join(codex_core::SESSIONS_SUBDIR);
+    if let Some((year, month, day)) = codex_core::rollout_date_parts(OsStr::new(&file_name)) {
+        restored_dir = restored_dir.join(year).join(month).join(day);
+    }
+    tokio::fs::create_dir_all(&restored_dir).await?;
+    let restored_path = restored_dir.join(file_name);
+    tokio::fs::rename(&archived_path, &restored_path).await?;
+    Ok(restored_path)
+}
+
+/// Canonicalizes `path` and checks that it is `thread_id`'s rollout inside
+/// `root`.
+async fn checked_rollout(
+    root: &Path,
+    thread_id: ThreadId,
+    path: &Path,
+) -> io::Result<(PathBuf, String)> {
+    let root = tokio::fs::canonicalize(root).await?;
+    let path = tokio::fs::canonicalize(path).await?;
+    if !path.starts_with(&root) {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("rollout {} is not under {}", path.display(), root.display()),
+        ));
+    }
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if !file_name.ends_with(&format!("{thread_id}.jsonl")) {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "rollout {} does not belong to thread {thread_id}",
+                path.display()
+            ),
+        ));
+    }
+    Ok((path, file_name))
+}
@@ -0,0 +1,179 @@
+//! Rollout files uploaded to `POST /api/v2/threads/import`.
+//!
+//! An upload is checked line by line before it lands in the sessions
+//! directory: every line must be a rollout line, and the first must be the
+//! session meta record. That record is rewritten to a freshly generated
+//! thread id, so an import never collides with the thread it was exported
+//! from, even on the same machine.
+
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use tokio::fs;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::io::BufWriter;
+
+/// Largest accepted upload in bytes. Unset uses
+/// [`DEFAULT_IMPORT_MAX_BYTES`].
+pub const IMPORT_MAX_BYTES_ENV: &str = "CODEX_WEB_IMPORT_MAX_BYTES";
+pub const DEFAULT_IMPORT_MAX_BYTES: u64 = 100 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImportLimits {
+    pub max_bytes: u64,
+}
+
+impl Default for ImportLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_IMPORT_MAX_BYTES,
+        }
+    }
+}
+
+impl ImportLimits {
+    /// Reads the limit from `CODEX_WEB_IMPORT_MAX_BYTES`, keeping the default
+    /// when it is unset, `0` or invalid.
+    pub fn from_env() -> Self {
+        let Ok(value) = std::env::var(IMPORT_MAX_BYTES_ENV) else {
+            return Self::default();
+        };
+        match value.trim().parse::<u64>() {
+            Ok(0) => Self::default(),
+            Ok(max_bytes) => Self { max_bytes },
+            Err(err) => {
+                tracing::warn!("Ignoring invalid {IMPORT_MAX_BYTES_ENV}={value:?}: {err}");
+                Self::default()
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ImportError {
+    /// The upload is not an importable rollout; `line` is 1-based
+    Invalid {
+        line: usize,
+        message: String,
+    },
+    Io(io::Error),
+}
+
+impl From<io::Error> for ImportError {
+    fn from(err: io::Error) -> Self {
+        ImportError::Io(err)
+    }
+}
+
+fn invalid(line: usize, message: impl Into<String>) -> ImportError {
+    ImportError::Invalid {
+        line,
+        message: message.into(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportedRollout {
+    pub thread_id: ThreadId,
+    /// Id recorded in the uploaded session meta
+    pub source_thread_id: ThreadId,
+    pub path: PathBuf,
+    /// Rollout lines written, session meta included
+    pub line_count: usize,
+}
+
+/// Checks the rollout at `upload` and writes it into the sessions directory
+/// of `codex_home` under a new thread id. Nothing is left behind on error.
+pub async fn import_rollout(
+    codex_home: &Path,
+    upload: &Path,
+) -> Result<ImportedRollout, ImportError> {
+    let thread_id = ThreadId::new();
+    let now = chrono::Local::now();
+    let dir = codex_home
+        .join(codex_core::SESSIONS_SUBDIR)
+        .join(now.format("%Y").to_string())
+        .join(now.format("%m").to_string())
+        .join(now.format("%d").to_string());
+    fs::create_dir_all(&dir).await?;
+    let path = dir.join(format!(
+        "rollout-{}-{thread_id}.jsonl",
+        now.format("%Y-%m-%dT%H-%M-%S")
+    ));
+
+    match copy_checked(upload, &path, thread_id).await {
+        Ok((source_thread_id, line_count)) => Ok(ImportedRollout {
+            thread_id,
+            source_thread_id,
+            path,
+            line_count,
+        }),
+        Err(err) => {
+            let _ = fs::remove_file(&path).await;
+            Err(err)
+        }
+    }
+}
+
+/// Copies `upload` to `path` line by line, giving the session meta
+/// `thread_id`. Returns the uploaded thread id and the number of lines.
+async fn copy_checked(
+    upload: &Path,
+    path: &Path,
+    thread_id: ThreadId,
+) -> Result<(ThreadId, usize), ImportError> {
+    let mut lines = BufReader::new(fs::File::open(upload).await?).lines();
+    let mut out = BufWriter::new(
+        fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .await?,
+    );
+    let mut number = 0;
+    let mut line_count = 0;
+    let mut source_thread_id = None;
+    loop {
+        number += 1;
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(err) if err.kind() == io::ErrorKind::InvalidData => {
+                return Err(invalid(number, "line is not valid UTF-8"));
+            }
+            Err(err) => return Err(err.into()),
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut rollout_line: RolloutLine = serde_json::from_str(&line)
+            .map_err(|e| invalid(number, format!("not a rollout item: {e}")))?;
+        let line = if source_thread_id.is_none() {
+            let RolloutItem::SessionMeta(session_meta) = &mut rollout_line.item else {
+                return Err(invalid(
+                    number,
+                    "the first item must be a `session_meta` record",
+                ));
+            };
+            source_thread_id = Some(session_meta.meta.id);
+            session_meta.meta.id = thread_id;
+            serde_json::to_string(&rollout_line).map_err(io::Error::other)?
+        } else {
+            line
+        };
+        out.write_all(line.as_bytes()).await?;
+        out.write_all(b"\n").await?;
+        line_count += 1;
+    }
+    out.flush().await?;
+
+    let Some(source_thread_id) = source_thread_id else {
+        return Err(invalid(1, "the file contains no rollout items"));
+    };
+    Ok((source_thread_id, line_count))
+}
@@ -0,0 +1,169 @@
+//! Thread state read from the end of a rollout file.
+//!
+//! Batch status lookups and thread scans describe many unloaded threads at
+//! once, so only the last turn, the latest token count and the model of the
+//! latest turn are needed from each rollout. All sit near the end of the
+//! file: it is read backwards in chunks and parsing stops as soon as all are
+//! found.
+//!
+//! When the rollout ends inside a turn, the tool calls of that turn that never
+//! got an output are collected on the way: a server that stopped while an
+//! approval was pending leaves exactly such a call behind.
+
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use codex_protocol::protocol::TokenUsage;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io;
+use std::io::SeekFrom;
+use std::path::Path;
+use tokio::fs;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncSeekExt;
+use utoipa::ToSchema;
+
+const CHUNK_BYTES: u64 = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LastTurnStatus {
+    /// A turn is running right now
+    InProgress,
+    Completed,
+    Aborted,
+    /// The rollout ends inside a turn, e.g. the server stopped mid-turn
+    Unfinished,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RolloutTail {
+    /// `None` when the rollout has no turns
+    pub last_turn: Option<LastTurnStatus>,
+    /// Cumulative usage from the latest token count
+    pub token_usage: Option<TokenUsage>,
+    /// Model of the latest turn
+    pub model: Option<String>,
+    /// Call ids of an unfinished last turn that have no output, in rollout
+    /// order; empty unless `last_turn` is `Unfinished`
+    pub unanswered_calls: Vec<String>,
+    /// Call ids with an output, seen after the last turn boundary
+    answered_calls: HashSet<String>,
+}
+
+impl RolloutTail {
+    fn is_complete(&self) -> bool {
+        self.last_turn.is_some() && self.token_usage.is_some() && self.model.is_some()
+    }
+
+    /// Takes what `line` says unless a later line already said it. Lines
+    /// that are not rollout items are skipped.
+    fn observe(&mut self, line: &[u8]) {
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return;
+        }
+        let Ok(RolloutLine { item, .. }) = serde_json::from_slice::<RolloutLine>(line) else {
+            return;
+        };
+        match item {
+            RolloutItem::EventMsg(msg) => self.observe_event(msg),
+            RolloutItem::TurnContext(context) if self.model.is_none() => {
+                self.model = Some(context.model);
+            }
+            RolloutItem::ResponseItem(item) if self.last_turn.is_none() => match item {
+                ResponseItem::FunctionCall { call_id, .. }
+                | ResponseItem::CustomToolCall { call_id, .. }
+                | ResponseItem::LocalShellCall {
+                    call_id: Some(call_id),
+                    ..
+                } => self.call_requested(call_id),
+                ResponseItem::FunctionCallOutput { call_id, .. }
+                | ResponseItem::CustomToolCallOutput { call_id, .. } => {
+                    self.answered_calls.insert(call_id);
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn observe_event(&mut self, msg: EventMsg) {
+        match msg {
+            EventMsg::TokenCount(event) if self.token_usage.is_none() => {
+                self.token_usage = event.info.map(|info| info.total_token_usage);
+            }
+            EventMsg::TurnComplete(_) if self.last_turn.is_none() => {
+                self.last_turn = Some(LastTurnStatus::Completed);
+                self.unanswered_calls.clear();
+            }
+            EventMsg::TurnAborted(_) if self.last_turn.is_none() => {
+                self.last_turn = Some(LastTurnStatus::Aborted);
+                self.unanswered_calls.clear();
+            }
+            EventMsg::TurnStarted(_) if self.last_turn.is_none() => {
+                self.last_turn = Some(LastTurnStatus::Unfinished);
+                // Collected newest first.
+                self.unanswered_calls.reverse();
+            }
+            EventMsg::ExecApprovalRequest(event) if self.last_turn.is_none() => {
+                self.call_requested(event.call_id);
+            }
+            EventMsg::ApplyPatchApprovalRequest(event) if self.last_turn.is_none() => {
+                self.call_requested(event.call_id);
+            }
+            EventMsg::ExecCommandEnd(event) if self.last_turn.is_none() => {
+                self.answered_calls.insert(event.call_id);
+            }
+            EventMsg::PatchApplyEnd(event) if self.last_turn.is_none() => {
+                self.answered_calls.insert(event.call_id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Lines are read backwards, so a call's output has already been seen.
+    fn call_requested(&mut self, call_id: String) {
+        if !self.answered_calls.contains(&call_id) && !self.unanswered_calls.contains(&call_id) {
+            self.unanswered_calls.push(call_id);
+        }
+    }
+}
+
+/// Reads the last turn, its unanswered calls, the token usage and the latest
+/// model of the rollout at `path`.
+pub async fn read_rollout_tail(path: &Path) -> io::Result<RolloutTail> {
+    let mut file = fs::File::open(path).await?;
+    let mut end = file.metadata().await?.len();
+    let mut tail = RolloutTail::default();
+    // Start of the line cut off at the beginning of the previous chunk.
+    let mut carry = Vec::new();
+    while end > 0 && !tail.is_complete() {
+        let start = end.saturating_sub(CHUNK_BYTES);
+        let mut chunk = vec![0; usize::try_from(end - start).map_err(io::Error::other)?];
+        file.seek(SeekFrom::Start(start)).await?;
+        file.read_exact(&mut chunk).await?;
+        chunk.append(&mut carry);
+
+        // Unless the chunk starts the file, its first line may be cut off.
+        let split = if start == 0 {
+            0
+        } else {
+            chunk
+                .iter()
+                .position(|byte| *byte == b'\n')
+                .map_or(chunk.len(), |newline| newline + 1)
+        };
+        for line in chunk[split..].split(|byte| *byte == b'\n').rev() {
+            tail.observe(line);
+            if tail.is_complete() {
+                break;
+            }
+        }
+        chunk.truncate(split);
+        carry = chunk;
+        end = start;
+    }
+    Ok(tail)
+}
@@ -0,0 +1,126 @@
+//! Per-cwd skill collection for `GET /api/v2/skills`.
+//!
+//! Every requested cwd is loaded in its own task with a timeout, so a cwd on a
+//! hung network mount costs only its own entry: the other cwds still return
+//! their skills, and the slow one comes back `timed_out` with an error. The
+//! skill loader reads the disk synchronously, so a timed-out task can keep a
+//! worker busy until the filesystem answers; it is detached, not awaited.
+
+use codex_core::skills::SkillError;
+use codex_core::skills::SkillLoadOutcome;
+use codex_core::skills::SkillsManager;
+use futures::future::BoxFuture;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+/// Milliseconds allowed for loading the skills of one cwd. Unset uses
+/// [`DEFAULT_SKILLS_TIMEOUT`].
+pub const SKILLS_TIMEOUT_ENV: &str = "CODEX_WEB_SKILLS_TIMEOUT_MS";
+pub const DEFAULT_SKILLS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Reads the timeout from `CODEX_WEB_SKILLS_TIMEOUT_MS`, keeping the default
+/// when it is unset, `0` or invalid.
+pub fn skills_timeout_from_env() -> Duration {
+    let Ok(value) = std::env::var(SKILLS_TIMEOUT_ENV) else {
+        return DEFAULT_SKILLS_TIMEOUT;
+    };
+    match value.trim().parse::<u64>() {
+        Ok(0) => DEFAULT_SKILLS_TIMEOUT,
+        Ok(millis) => Duration::from_millis(millis),
+        Err(err) => {
+            tracing::warn!("Ignoring invalid {SKILLS_TIMEOUT_ENV}={value:?}: {err}");
+            DEFAULT_SKILLS_TIMEOUT
+        }
+    }
+}
+
+/// Where skills are loaded from; the thread manager's skills manager,
+/// replaceable in tests.
+pub trait SkillsSource: Send + Sync {
+    fn skills_for_cwd(
+        &self,
+        cwd: PathBuf,
+        force_reload: bool,
+    ) -> BoxFuture<'static, SkillLoadOutcome>;
+}
+
+impl SkillsSource for Arc<SkillsManager> {
+    fn skills_for_cwd(
+        &self,
+        cwd: PathBuf,
+        force_reload: bool,
+    ) -> BoxFuture<'static, SkillLoadOutcome> {
+        let manager = Arc::clone(self);
+        Box::pin(async move { manager.skills_for_cwd(&cwd, force_reload).await })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SkillsLoadStatus {
+    /// Loaded; `errors` lists skill files that could not be parsed
+    Loaded,
+    /// Loading took longer than the timeout; no skills are listed
+    TimedOut,
+    /// The loader failed outright; no skills are listed
+    Failed,
+}
+
+#[derive(Debug, Clone)]
+pub struct CwdSkills {
+    pub cwd: PathBuf,
+    pub status: SkillsLoadStatus,
+    pub outcome: SkillLoadOutcome,
+}
+
+/// Loads the skills of every cwd concurrently, each bounded by `timeout`.
+/// Entries come back in the order of `cwds`.
+pub async fn collect_skills(
+    source: &Arc<dyn SkillsSource>,
+    cwds: Vec<PathBuf>,
+    force_reload: bool,
+    timeout: Duration,
+) -> Vec<CwdSkills> {
+    let loads = cwds.into_iter().map(|cwd| {
+        let task = tokio::spawn(source.skills_for_cwd(cwd.clone(), force_reload));
+        async move {
+            let abort = task.abort_handle();
+            let (status, outcome) = match tokio::time::timeout(timeout, task).await {
+                Ok(Ok(outcome)) => (SkillsLoadStatus::Loaded, outcome),
+                Ok(Err(err)) => (
+                    SkillsLoadStatus::Failed,
+                    error_outcome(&cwd, format!("Failed to load skills: {err}")),
+                ),
+                Err(_) => {
+                    abort.abort();
+                    (
+                        SkillsLoadStatus::TimedOut,
+                        error_outcome(
+                            &cwd,
+                            format!("Loading skills timed out after {} ms", timeout.as_millis()),
+                        ),
+                    )
+                }
+            };
+            CwdSkills {
+                cwd,
+                status,
+                outcome,
+            }
+        }
+    });
+    futures::future::join_all(loads).await
+}
+
+fn error_outcome(cwd: &std::path::Path, message: String) -> SkillLoadOutcome {
+    SkillLoadOutcome {
+        errors: vec![SkillError {
+            path: cwd.to_path_buf(),
+            message,
+        }],
+        ..Default::default()
+    }
+}
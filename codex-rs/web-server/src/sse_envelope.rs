@@ -0,0 +1,69 @@
+//! Versioned SSE data.
+//!
+//! By default an event's `data` is the bare notification payload (the `v1`
+//! envelope). A client that subscribes with `?envelope=v2` gets every payload
+//! wrapped as `{"v": <schema version>, "data": <payload>}` instead, so it can
+//! tell when the server sends notification shapes newer than it understands.
+//!
+//! [`NOTIFICATION_SCHEMA_VERSION`] covers the serialized shape of every
+//! notification; `tests/suite/notification_schema.rs` snapshots them, so a
+//! change to any payload fails there until the version is bumped along with
+//! the snapshot.
+
+use axum::response::sse::Event;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use serde_json::json;
+use utoipa::ToSchema;
+
+/// Version of the notification payload shapes, sent as `v` in the `v2`
+/// envelope.
+pub const NOTIFICATION_SCHEMA_VERSION: u32 = 2;
+
+/// Payload of a notification the web server sends itself, serialized like an
+/// app-server `ServerNotification`: `{"method": <event type>, "params": ...}`.
+pub fn server_notification(method: &str, params: Value) -> Value {
+    json!({
+        "method": method,
+        "params": params,
+    })
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SseEnvelope {
+    /// `data` is the payload itself
+    #[default]
+    V1,
+    /// `data` is `{"v": <schema version>, "data": <payload>}`
+    V2,
+}
+
+impl SseEnvelope {
+    /// Every envelope a client can ask for, oldest first.
+    pub const SUPPORTED: [SseEnvelope; 2] = [SseEnvelope::V1, SseEnvelope::V2];
+
+    /// Event data for `json`, an already serialized payload.
+    pub fn data(self, json: String) -> String {
+        match self {
+            SseEnvelope::V1 => json,
+            SseEnvelope::V2 => format!(r#"{{"v":{NOTIFICATION_SCHEMA_VERSION},"data":{json}}}"#),
+        }
+    }
+
+    /// SSE event of type `event_type` carrying `json`, an already serialized
+    /// payload.
+    pub fn event(self, event_type: &str, json: String) -> Event {
+        Event::default().event(event_type).data(self.data(json))
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct EventStreamParams {
+    /// Shape of each event's `data` (default: `v1`)
+    #[serde(default)]
+    pub envelope: SseEnvelope,
+    /// Comma-separated event type prefixes to send (default: every event)
+    pub events: Option<String>,
+}
@@ -0,0 +1,316 @@
+//! Checks that the server can do useful work, shared by `--self-test` and
+//! `GET /health/ready`.
+//!
+//! `--self-test` runs every check once without binding a port, prints the
+//! report and exits non-zero on failure, starting each configured MCP server
+//! with a timeout. Readiness runs the same checks but reads MCP status from
+//! the snapshot cache so probes do not spawn servers, and reuses a recent
+//! `codex_home` write probe so they do not write on every request.
+
+use codex_core::WebStateStore;
+use codex_core::auth::AuthManager;
+use codex_core::config::Config;
+use codex_core::exec::SandboxType;
+use codex_core::get_platform_sandbox;
+use codex_core::windows_sandbox::windows_sandbox_level_from_config;
+use codex_protocol::config_types::WindowsSandboxLevel;
+use serde::Serialize;
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::error::ApiError;
+use crate::mcp_snapshot::McpStatusSnapshot;
+use crate::mcp_snapshot::collect_mcp_status_for_config;
+use crate::permissions;
+use crate::state::WebServerState;
+
+/// Command-line flag that runs the checks and exits instead of serving.
+pub const SELF_TEST_FLAG: &str = "--self-test";
+
+/// How long readiness reuses the result of a `codex_home` write probe.
+pub const WRITABLE_PROBE_TTL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    /// Not applicable here, e.g. Postgres when it is not configured.
+    Skip,
+    /// Works, but needs attention; does not fail the report.
+    Warn,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl CheckResult {
+    fn new(name: impl Into<String>, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+        }
+    }
+
+    fn pass(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Pass, detail)
+    }
+
+    fn fail(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Fail, detail)
+    }
+
+    fn skip(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Skip, detail)
+    }
+
+    fn warn(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self::new(name, CheckStatus::Warn, detail)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StartupReport {
+    /// `false` when any check failed
+    pub ok: bool,
+    pub checks: Vec<CheckResult>,
+}
+
+impl StartupReport {
+    pub fn new(checks: Vec<CheckResult>) -> Self {
+        let ok = checks.iter().all(|check| check.status != CheckStatus::Fail);
+        Self { ok, checks }
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &CheckResult> {
+        self.checks
+            .iter()
+            .filter(|check| check.status == CheckStatus::Fail)
+    }
+
+    /// Process exit code for `--self-test`.
+    pub fn exit_code(&self) -> i32 {
+        if self.ok { 0 } else { 1 }
+    }
+
+    /// One `STATUS name: detail` line per check, then a summary line.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for check in &self.checks {
+            let status = match check.status {
+                CheckStatus::Pass => "PASS",
+                CheckStatus::Fail => "FAIL",
+                CheckStatus::Skip => "SKIP",
+                CheckStatus::Warn => "WARN",
+            };
+            out.push_str(&format!("{status} {}: {}\n", check.name, check.detail));
+        }
+        let failed = self.failures().count();
+        if failed == 0 {
+            out.push_str("self-test passed\n");
+        } else {
+            out.push_str(&format!("self-test failed: {failed} check(s) failed\n"));
+        }
+        out
+    }
+}
+
+/// Runs every check for `--self-test`, starting each enabled MCP server and
+/// giving it at most `mcp_timeout`.
+///
+/// `state_store` is the outcome of connecting to Postgres, so a connection
+/// failure is reported instead of aborting the run.
+pub async fn run_self_test(
+    state: &WebServerState,
+    state_store: Result<Option<&WebStateStore>, &std::io::Error>,
+    mcp_timeout: Duration,
+) -> StartupReport {
+    let (config_check, config) = check_config(state).await;
+    let mut checks = vec![
+        config_check,
+        check_codex_home_writable(&state.codex_home).await,
+        check_permissions(&state.codex_home, &state.attachments_dir),
+        check_auth(&state.auth_manager).await,
+        check_sandbox(config.as_ref()),
+        check_postgres(state_store).await,
+    ];
+    match config {
+        Some(config) => checks.extend(check_mcp_servers(
+            &collect_mcp_status_for_config(&config, mcp_timeout).await,
+            &config,
+        )),
+        None => checks.push(CheckResult::skip("mcp", "config did not load")),
+    }
+    StartupReport::new(checks)
+}
+
+/// Runs the checks for `GET /health/ready`, taking MCP status from the
+/// snapshot cache.
+pub async fn run_readiness(state: &WebServerState) -> StartupReport {
+    let (config_check, config) = check_config(state).await;
+    let mut checks = vec![
+        config_check,
+        state.writable_probe.check(&state.codex_home).await,
+        check_permissions(&state.codex_home, &state.attachments_dir),
+        check_auth(&state.auth_manager).await,
+        check_sandbox(config.as_ref()),
+        check_postgres(Ok(state.state_store.as_deref())).await,
+    ];
+    match (config, state.mcp_snapshots.get(false).await) {
+        (Some(config), Ok(cached)) => checks.extend(check_mcp_servers(&cached.snapshot, &config)),
+        (Some(_), Err(err)) => checks.push(CheckResult::fail(
+            "mcp",
+            format!("failed to collect status: {err}"),
+        )),
+        (None, _) => checks.push(CheckResult::skip("mcp", "config did not load")),
+    }
+    StartupReport::new(checks)
+}
+
+pub async fn check_config(state: &WebServerState) -> (CheckResult, Option<Config>) {
+    match state.config_loader.load().await {
+        Ok(config) => (
+            CheckResult::pass(
+                "config",
+                match config.model.as_deref() {
+                    Some(model) => format!("loaded (model {model})"),
+                    None => "loaded".to_string(),
+                },
+            ),
+            Some(config),
+        ),
+        Err(ApiError::InternalError(message)) => (CheckResult::fail("config", message), None),
+        Err(err) => (CheckResult::fail("config", format!("{err:?}")), None),
+    }
+}
+
+/// Writes and removes a scratch file, since sessions, attachments and auth
+/// are all stored under `codex_home`.
+pub async fn check_codex_home_writable(codex_home: &Path) -> CheckResult {
+    let probe = codex_home.join(format!(".self-test-{}", Uuid::new_v4()));
+    let result = match tokio::fs::write(&probe, b"ok").await {
+        Ok(()) => tokio::fs::remove_file(&probe).await,
+        Err(err) => Err(err),
+    };
+    match result {
+        Ok(()) => CheckResult::pass(
+            "codex_home",
+            format!("{} is writable", codex_home.display()),
+        ),
+        Err(err) => CheckResult::fail(
+            "codex_home",
+            format!("{} is not writable: {err}", codex_home.display()),
+        ),
+    }
+}
+
+/// The latest [`check_codex_home_writable`] result, reused for
+/// [`WRITABLE_PROBE_TTL`]. Probes that arrive together share one write.
+#[derive(Debug, Default)]
+pub struct WritableProbe {
+    latest: Mutex<Option<(Instant, CheckResult)>>,
+}
+
+impl WritableProbe {
+    pub async fn check(&self, codex_home: &Path) -> CheckResult {
+        let mut latest = self.latest.lock().await;
+        if let Some((probed_at, result)) = latest.as_ref()
+            && probed_at.elapsed() < WRITABLE_PROBE_TTL
+        {
+            return result.clone();
+        }
+        let result = check_codex_home_writable(codex_home).await;
+        *latest = Some((Instant::now(), result.clone()));
+        result
+    }
+}
+
+/// Warns when `codex_home` or the files under it are open to other users.
+pub fn check_permissions(codex_home: &Path, attachments_dir: &Path) -> CheckResult {
+    let issues = permissions::audit(codex_home, attachments_dir);
+    if issues.is_empty() {
+        CheckResult::pass("permissions", "owner-only")
+    } else {
+        CheckResult::warn("permissions", permissions::describe(&issues))
+    }
+}
+
+pub async fn check_auth(auth_manager: &AuthManager) -> CheckResult {
+    match auth_manager.auth().await {
+        Some(auth) => CheckResult::pass("auth", format!("signed in ({:?})", auth.auth_mode())),
+        None => CheckResult::fail(
+            "auth",
+            "no credentials; log in via POST /api/v2/auth/login or set an API key",
+        ),
+    }
+}
+
+/// Reports the sandbox `POST /api/v2/commands` runs in. On Windows it depends
+/// on the configured Windows sandbox level.
+pub fn check_sandbox(config: Option<&Config>) -> CheckResult {
+    let level = config.map_or(
+        WindowsSandboxLevel::Disabled,
+        windows_sandbox_level_from_config,
+    );
+    match get_platform_sandbox(level != WindowsSandboxLevel::Disabled) {
+        Some(SandboxType::BoxLite) => CheckResult::pass("sandbox", "BoxLite"),
+        Some(SandboxType::WindowsRestrictedToken) => {
+            CheckResult::pass("sandbox", format!("Windows sandbox ({level})"))
+        }
+        Some(sandbox) => CheckResult::pass(
+            "sandbox",
+            format!("{sandbox:?}; POST /api/v2/commands needs BoxLite"),
+        ),
+        None if cfg!(target_os = "windows") => CheckResult::fail(
+            "sandbox",
+            "the Windows sandbox is disabled; set `[windows] sandbox = \"unelevated\"` in config.toml",
+        ),
+        None => CheckResult::fail("sandbox", "no sandbox is available on this platform"),
+    }
+}
+
+pub async fn check_postgres(
+    state_store: Result<Option<&WebStateStore>, &std::io::Error>,
+) -> CheckResult {
+    match state_store {
+        Ok(None) => CheckResult::skip("postgres", "not configured"),
+        Ok(Some(store)) => match store.ping().await {
+            Ok(()) => CheckResult::pass("postgres", "reachable"),
+            Err(err) => CheckResult::fail("postgres", err.to_string()),
+        },
+        Err(err) => CheckResult::fail("postgres", format!("failed to connect: {err}")),
+    }
+}
+
+/// One result per configured server, named `mcp:<server>`. A server fails
+/// when it failed to start, with its startup error, or did not start and
+/// list its tools within the timeout.
+pub fn check_mcp_servers(snapshot: &McpStatusSnapshot, config: &Config) -> Vec<CheckResult> {
+    if config.mcp_servers.get().is_empty() {
+        return vec![CheckResult::pass("mcp", "no servers configured")];
+    }
+    snapshot
+        .servers
+        .iter()
+        .map(|listing| {
+            let name = format!("mcp:{}", listing.status.name);
+            if !listing.enabled {
+                CheckResult::skip(name, "disabled")
+            } else if let Some(error) = snapshot.failed_servers.get(&listing.status.name) {
+                CheckResult::fail(name, error.clone())
+            } else if snapshot.timed_out_servers.contains(&listing.status.name) {
+                CheckResult::fail(name, "did not start within the timeout")
+            } else {
+                CheckResult::pass(name, format!("{} tool(s)", listing.status.tools.len()))
+            }
+        })
+        .collect()
+}
@@ -1,28 +1,135 @@
+use crate::account_login::AccountLogins;
+use crate::api_version::ApiVersionPolicy;
+use crate::approval_manager::ApprovalExpiry;
+use crate::attachments::AttachmentLimits;
+use crate::auto_archive::AutoArchivedThread;
+use crate::config_loader::ConfigLoader;
+use crate::event_ids::EventSequencer;
+use crate::event_pump::EventPump;
+use crate::event_pump::PUMP_GRACE_PERIOD;
+use crate::event_pump::PublishedEvent;
+use crate::event_replay::EventReplay;
+use crate::firehose::ThreadAnnouncements;
+use crate::mcp_snapshot::McpSnapshotCache;
+use crate::metrics::ServerMetrics;
+use crate::notifications::Notifier;
+use crate::pairing::PAIRING_CODE_TTL;
+use crate::pairing::Pairing;
+use crate::provider_overrides::ProviderHosts;
+use crate::review_registry::ReviewRegistry;
+use crate::rollout_import::ImportLimits;
+use crate::skills_listing::DEFAULT_SKILLS_TIMEOUT;
+use crate::skills_listing::SkillsSource;
+use crate::startup_checks::WritableProbe;
+use crate::telemetry::TurnSpans;
+use crate::thread_memory::ThreadMemory;
+use crate::thread_ops::ThreadOps;
+use crate::thread_progress::ProgressRegistry;
+use crate::thread_scan::ThreadScanCache;
+use crate::thread_status::ThreadStatusRegistry;
+use crate::turn_queue::TurnQueue;
+use crate::usage::ModelPricing;
+use crate::workspace_trust::TrustedRoots;
+use codex_core::CodexThread;
+use codex_core::PersistedApproval;
 use codex_core::ThreadManager;
+use codex_core::WebStateStore;
+use codex_core::auth::AuthCredentialsStoreMode;
 use codex_core::auth::AuthManager;
 use codex_core::config::service::ConfigService;
 use codex_feedback::CodexFeedback;
 use codex_protocol::ThreadId;
+use codex_protocol::approvals::ExecPolicyAmendment;
+use codex_protocol::approvals::NetworkApprovalContext;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 use std::time::Instant;
 use tokio::sync::Mutex;
 use tokio::sync::RwLock;
+use tokio::sync::broadcast;
 use utoipa::ToSchema;
 
 #[derive(Clone)]
 pub struct WebServerState {
     pub thread_manager: Arc<ThreadManager>,
     pub auth_manager: Arc<AuthManager>,
+    /// Where `auth_manager` keeps credentials, after any keyring fallback.
+    pub credential_store_mode: AuthCredentialsStoreMode,
     pub config_service: Arc<ConfigService>,
     pub codex_home: PathBuf,
     pub attachments_dir: PathBuf,
+    /// Held while attachments start or stop sharing a file, so a duplicate
+    /// upload never refers to a file that is being deleted.
+    pub attachment_blobs: Arc<Mutex<()>>,
     pub auth_token: String,
+    /// Token required by maintenance routes instead of `auth_token`, when set.
+    pub admin_token: Option<String>,
+    /// First-run pairing code and the tokens it issued.
+    pub pairing: Arc<Pairing>,
     pub sessions: Arc<RwLock<SessionStore>>,
     pub pending_approvals: Arc<Mutex<HashMap<String, ApprovalContext>>>,
+    /// Approval timeout, and approvals denied for running past it.
+    pub approval_expiry: Arc<ApprovalExpiry>,
     pub feedback: CodexFeedback,
+    /// Postgres persistence for approvals and running turns, when configured.
+    pub state_store: Option<Arc<WebStateStore>>,
+    /// State recovered from `state_store` at startup, delivered to the first
+    /// SSE subscriber of each thread.
+    pub restored_threads: Arc<Mutex<HashMap<ThreadId, RestoredThreadState>>>,
+    /// Threads dropped from memory by the idle policy, until they are resumed.
+    pub auto_archived: Arc<Mutex<HashMap<ThreadId, AutoArchivedThread>>>,
+    pub metrics: Arc<ServerMetrics>,
+    /// MCP server status served by `GET /api/v2/mcp/servers`.
+    pub mcp_snapshots: Arc<McpSnapshotCache>,
+    /// Coalesces concurrent config loads from handlers.
+    pub config_loader: Arc<ConfigLoader>,
+    /// Turn and tool-call spans still open.
+    pub turn_spans: Arc<TurnSpans>,
+    /// Run status per thread, broadcast as `thread/status/changed`.
+    pub thread_statuses: Arc<ThreadStatusRegistry>,
+    /// `<epoch>:<seq>` ids of the SSE events of each thread.
+    pub event_ids: Arc<EventSequencer>,
+    /// Recent events of each thread, replayed to streams that reconnect.
+    pub event_replay: Arc<EventReplay>,
+    /// Compaction and rollback in flight, broadcast as progress notifications.
+    pub progress: Arc<ProgressRegistry>,
+    /// Serializes archive, resume and turn submission per thread.
+    pub thread_ops: Arc<ThreadOps>,
+    /// Inputs waiting for each thread's running turn to finish.
+    pub turn_queue: Arc<TurnQueue>,
+    /// Reviews started through the API and whether they are still running.
+    pub reviews: Arc<ReviewRegistry>,
+    /// How v1 deprecation is announced to clients.
+    pub api_version_policy: Arc<ApiVersionPolicy>,
+    /// Directories threads and commands may run in; empty allows any.
+    pub trusted_roots: Arc<TrustedRoots>,
+    /// Hosts `provider_overrides` may point at; empty refuses overrides.
+    pub provider_hosts: Arc<ProviderHosts>,
+    /// Delivers turn and approval notifications to the configured sinks.
+    pub notifier: Arc<Notifier>,
+    /// Prices behind the cost estimates in `GET /api/v2/usage`.
+    pub model_pricing: Arc<ModelPricing>,
+    /// Size limit for `POST /api/v2/threads/import`.
+    pub import_limits: ImportLimits,
+    /// Size limits for `POST /api/v1/attachments` and `POST /api/v2/attachments`.
+    pub attachment_limits: AttachmentLimits,
+    /// Loads the skills listed by `GET /api/v2/skills`.
+    pub skills_source: Arc<dyn SkillsSource>,
+    /// Time allowed for loading the skills of one cwd.
+    pub skills_timeout: Duration,
+    /// Byte budget shared by each thread's caches.
+    pub thread_memory: Arc<ThreadMemory>,
+    /// Models of stored threads from the latest scan.
+    pub thread_scan: Arc<ThreadScanCache>,
+    /// Threads started, resumed or forked through the API, for `GET /api/v2/events`.
+    pub thread_announcements: Arc<ThreadAnnouncements>,
+    /// ChatGPT logins in progress and the account notifications for SSE streams.
+    pub account_logins: Arc<AccountLogins>,
+    /// Recent `codex_home` write probe reused by `GET /health/ready`.
+    pub writable_probe: Arc<WritableProbe>,
 }
 
 impl WebServerState {
@@ -35,22 +142,337 @@ impl WebServerState {
         auth_token: String,
         feedback: CodexFeedback,
     ) -> Self {
+        let mcp_snapshots = Arc::new(McpSnapshotCache::with_defaults(codex_home.clone()));
+        let config_loader = Arc::new(ConfigLoader::for_codex_home(codex_home.clone()));
+        let pairing = Arc::new(Pairing::new(codex_home.clone(), PAIRING_CODE_TTL));
+        let skills_source: Arc<dyn SkillsSource> = Arc::new(thread_manager.skills_manager());
         Self {
             thread_manager,
             auth_manager,
+            credential_store_mode: AuthCredentialsStoreMode::File,
             config_service,
             codex_home,
             attachments_dir,
+            attachment_blobs: Arc::new(Mutex::new(())),
             auth_token,
+            admin_token: None,
+            pairing,
             sessions: Arc::new(RwLock::new(SessionStore::new())),
             pending_approvals: Arc::new(Mutex::new(HashMap::new())),
+            approval_expiry: Arc::new(ApprovalExpiry::default()),
             feedback,
+            state_store: None,
+            restored_threads: Arc::new(Mutex::new(HashMap::new())),
+            auto_archived: Arc::new(Mutex::new(HashMap::new())),
+            metrics: Arc::new(ServerMetrics::default()),
+            mcp_snapshots,
+            config_loader,
+            turn_spans: Arc::new(TurnSpans::default()),
+            thread_statuses: Arc::new(ThreadStatusRegistry::default()),
+            event_ids: Arc::new(EventSequencer::default()),
+            event_replay: Arc::new(EventReplay::default()),
+            progress: Arc::new(ProgressRegistry::default()),
+            thread_ops: Arc::new(ThreadOps::default()),
+            turn_queue: Arc::new(TurnQueue::default()),
+            reviews: Arc::new(ReviewRegistry::default()),
+            api_version_policy: Arc::new(ApiVersionPolicy::default()),
+            trusted_roots: Arc::new(TrustedRoots::default()),
+            provider_hosts: Arc::new(ProviderHosts::default()),
+            notifier: Arc::new(Notifier::default()),
+            model_pricing: Arc::new(ModelPricing::default()),
+            import_limits: ImportLimits::default(),
+            attachment_limits: AttachmentLimits::default(),
+            skills_source,
+            skills_timeout: DEFAULT_SKILLS_TIMEOUT,
+            thread_memory: Arc::new(ThreadMemory::default()),
+            thread_scan: Arc::new(ThreadScanCache::default()),
+            thread_announcements: Arc::new(ThreadAnnouncements::default()),
+            account_logins: Arc::new(AccountLogins::default()),
+            writable_probe: Arc::new(WritableProbe::default()),
         }
     }
+
+    /// Stores and reads attachments under `attachments_dir` instead of the
+    /// directory passed to [`WebServerState::new`].
+    pub fn with_attachments_dir(mut self, attachments_dir: PathBuf) -> Self {
+        self.attachments_dir = attachments_dir;
+        self
+    }
+
+    pub fn with_state_store(mut self, state_store: Option<Arc<WebStateStore>>) -> Self {
+        self.state_store = state_store;
+        self
+    }
+
+    pub fn with_credential_store_mode(mut self, mode: AuthCredentialsStoreMode) -> Self {
+        self.credential_store_mode = mode;
+        self
+    }
+
+    pub fn with_account_logins(mut self, account_logins: AccountLogins) -> Self {
+        self.account_logins = Arc::new(account_logins);
+        self
+    }
+
+    pub fn with_admin_token(mut self, admin_token: Option<String>) -> Self {
+        self.admin_token = admin_token;
+        self
+    }
+
+    pub fn with_api_version_policy(mut self, policy: ApiVersionPolicy) -> Self {
+        self.api_version_policy = Arc::new(policy);
+        self
+    }
+
+    pub fn with_trusted_roots(mut self, trusted_roots: TrustedRoots) -> Self {
+        self.trusted_roots = Arc::new(trusted_roots);
+        self
+    }
+
+    pub fn with_provider_hosts(mut self, provider_hosts: ProviderHosts) -> Self {
+        self.provider_hosts = Arc::new(provider_hosts);
+        self
+    }
+
+    pub fn with_notifier(mut self, notifier: Notifier) -> Self {
+        self.notifier = Arc::new(notifier);
+        self
+    }
+
+    pub fn with_model_pricing(mut self, model_pricing: ModelPricing) -> Self {
+        self.model_pricing = Arc::new(model_pricing);
+        self
+    }
+
+    pub fn with_import_limits(mut self, import_limits: ImportLimits) -> Self {
+        self.import_limits = import_limits;
+        self
+    }
+
+    pub fn with_attachment_limits(mut self, attachment_limits: AttachmentLimits) -> Self {
+        self.attachment_limits = attachment_limits;
+        self
+    }
+
+    pub fn with_skills_source(mut self, skills_source: Arc<dyn SkillsSource>) -> Self {
+        self.skills_source = skills_source;
+        self
+    }
+
+    pub fn with_skills_timeout(mut self, skills_timeout: Duration) -> Self {
+        self.skills_timeout = skills_timeout;
+        self
+    }
+
+    pub fn with_thread_memory_budget(mut self, budget: usize) -> Self {
+        self.thread_memory = Arc::new(ThreadMemory::new(budget));
+        self
+    }
+
+    /// Gives clients `timeout` to answer approval requests instead of the
+    /// default 15 minutes.
+    pub fn with_approval_timeout(mut self, timeout: Duration) -> Self {
+        self.approval_expiry = Arc::new(ApprovalExpiry::new(timeout));
+        self
+    }
+
+    /// Caps how many stored threads one scan reads.
+    pub fn with_thread_scan_limit(mut self, limit: usize) -> Self {
+        self.thread_scan = Arc::new(ThreadScanCache::new(limit));
+        self
+    }
+
+    pub fn with_config_loader(mut self, config_loader: ConfigLoader) -> Self {
+        self.config_loader = Arc::new(config_loader);
+        self
+    }
+
+    pub fn with_progress(mut self, progress: ProgressRegistry) -> Self {
+        self.progress = Arc::new(progress);
+        self
+    }
+
+    /// Reconciles persisted approvals and turns left behind by a previous run:
+    /// running turns are marked interrupted-by-restart and unanswered approvals
+    /// are kept until the thread is resumed, which denies them.
+    ///
+    /// Only a configured instance id identifies the rows of a previous run of
+    /// this instance; without one, reconciling would claim every peer's
+    /// running turns and approvals, so it is skipped.
+    pub async fn restore_from_store(&self) -> anyhow::Result<()> {
+        let Some(store) = &self.state_store else {
+            return Ok(());
+        };
+        let Some(instance_id) = crate::instance::configured_instance_id() else {
+            tracing::warn!(
+                "Skipping restart recovery: set {} to reconcile state left by a previous run",
+                crate::instance::INSTANCE_ID_ENV
+            );
+            return Ok(());
+        };
+
+        let reconciliation = store.reconcile_after_restart(instance_id).await?;
+        if reconciliation.approvals.is_empty() && reconciliation.interrupted_turns.is_empty() {
+            return Ok(());
+        }
+
+        tracing::info!(
+            "Recovered {} pending approvals and {} interrupted turns from Postgres",
+            reconciliation.approvals.len(),
+            reconciliation.interrupted_turns.len()
+        );
+
+        let mut restored = self.restored_threads.lock().await;
+        for approval in reconciliation.approvals {
+            restored
+                .entry(approval.thread_id)
+                .or_default()
+                .approvals
+                .push(approval);
+        }
+        for (thread_id, turn_id) in reconciliation.interrupted_turns {
+            restored
+                .entry(thread_id)
+                .or_default()
+                .interrupted_turn_ids
+                .push(turn_id);
+        }
+        Ok(())
+    }
+
+    /// Removes and returns the restart state recovered for `thread_id`, dropping
+    /// the corresponding rows so it is only delivered once.
+    pub async fn take_restored(&self, thread_id: ThreadId) -> Option<RestoredThreadState> {
+        let restored = self.restored_threads.lock().await.remove(&thread_id)?;
+        if let Some(store) = &self.state_store {
+            for approval in &restored.approvals {
+                if let Err(err) = store.remove_approval(&approval.approval_id).await {
+                    tracing::warn!("Failed to clear restored approval: {err}");
+                }
+            }
+            for turn_id in &restored.interrupted_turn_ids {
+                if let Err(err) = store.remove_turn(thread_id, turn_id).await {
+                    tracing::warn!("Failed to clear interrupted turn: {err}");
+                }
+            }
+        }
+        Some(restored)
+    }
+
+    /// Removes and returns the approvals recovered for `thread_id`, leaving its
+    /// interrupted turns to be reported. A resumed thread starts idle, so
+    /// nothing would ever receive an answer to them.
+    pub async fn take_restored_approvals(&self, thread_id: ThreadId) -> Vec<PersistedApproval> {
+        let approvals = {
+            let mut restored = self.restored_threads.lock().await;
+            let Some(entry) = restored.get_mut(&thread_id) else {
+                return Vec::new();
+            };
+            let approvals = std::mem::take(&mut entry.approvals);
+            if entry.interrupted_turn_ids.is_empty() {
+                restored.remove(&thread_id);
+            }
+            approvals
+        };
+        if let Some(store) = &self.state_store {
+            for approval in &approvals {
+                if let Err(err) = store.remove_approval(&approval.approval_id).await {
+                    tracing::warn!("Failed to clear restored approval: {err}");
+                }
+            }
+        }
+        approvals
+    }
+
+    /// Pins or unpins a thread in the listing. Pins are keyed by thread id, so
+    /// they survive archive/resume cycles.
+    pub async fn set_thread_pinned(&self, thread_id: ThreadId, pinned: bool) -> anyhow::Result<()> {
+        match &self.state_store {
+            Some(store) => store.set_thread_pinned(thread_id, pinned).await?,
+            None => {
+                crate::thread_prefs::set_thread_pinned(&self.codex_home, thread_id, pinned).await?
+            }
+        }
+        Ok(())
+    }
+
+    /// Keeps the thread's events consumed while its turn runs, whether or not
+    /// an SSE stream is attached.
+    pub async fn hold_event_pump(&self, thread_id: ThreadId) {
+        let Ok(thread) = self.thread_manager.get_thread(thread_id).await else {
+            return;
+        };
+        self.sessions.write().await.hold_pump(thread_id, || {
+            EventPump::start(self.clone(), thread_id, thread)
+        });
+    }
+
+    /// Opts a thread out of (or back into) notifications.
+    pub async fn set_thread_notifications_muted(
+        &self,
+        thread_id: ThreadId,
+        muted: bool,
+    ) -> anyhow::Result<()> {
+        match &self.state_store {
+            Some(store) => {
+                store
+                    .set_thread_notifications_muted(thread_id, muted)
+                    .await?
+            }
+            None => {
+                crate::thread_prefs::set_thread_notifications_muted(
+                    &self.codex_home,
+                    thread_id,
+                    muted,
+                )
+                .await?
+            }
+        }
+        self.notifier.set_muted(thread_id, muted);
+        Ok(())
+    }
+
+    /// Loads the persisted notification opt-outs into the notifier.
+    pub async fn load_notification_opt_outs(&self) -> anyhow::Result<()> {
+        let muted = match &self.state_store {
+            Some(store) => store.notifications_muted_threads().await?,
+            None => crate::thread_prefs::notifications_muted_threads(&self.codex_home).await?,
+        };
+        self.notifier.replace_muted(muted);
+        Ok(())
+    }
+
+    pub async fn pinned_threads(&self) -> anyhow::Result<HashSet<ThreadId>> {
+        let pinned = match &self.state_store {
+            Some(store) => store.pinned_threads().await?,
+            None => crate::thread_prefs::pinned_threads(&self.codex_home).await?,
+        };
+        Ok(pinned)
+    }
+}
+
+/// Approvals and turns of one thread that were pending when the server
+/// previously stopped.
+#[derive(Debug, Default)]
+pub struct RestoredThreadState {
+    pub approvals: Vec<PersistedApproval>,
+    pub interrupted_turn_ids: Vec<String>,
 }
 
 pub struct SessionStore {
     active_streams: HashMap<ThreadId, usize>,
+    last_activity: HashMap<ThreadId, Instant>,
+    pumps: HashMap<ThreadId, PumpEntry>,
+}
+
+struct PumpEntry {
+    pump: EventPump,
+    /// Streams ever registered on the pump, and turns it was held for, so a
+    /// grace period that ends after a client came back does not stop it.
+    registrations: u64,
+    /// Kept running without streams while a turn runs, so notifications,
+    /// spans and approvals of the turn are handled with nobody watching.
+    held: bool,
 }
 
 impl Default for SessionStore {
@@ -63,26 +485,202 @@ impl SessionStore {
     pub fn new() -> Self {
         Self {
             active_streams: HashMap::new(),
+            last_activity: HashMap::new(),
+            pumps: HashMap::new(),
         }
     }
 
-    pub fn register_stream(&mut self, thread_id: ThreadId) {
+    /// Counts a stream on `thread_id` and subscribes it to the thread's
+    /// events, calling `start` for a pump unless one is running.
+    pub fn register_stream(
+        &mut self,
+        thread_id: ThreadId,
+        start: impl FnOnce() -> EventPump,
+    ) -> broadcast::Receiver<PublishedEvent> {
         *self.active_streams.entry(thread_id).or_insert(0) += 1;
+        self.touch(thread_id);
+        let entry = self.pumps.entry(thread_id).or_insert_with(|| PumpEntry {
+            pump: start(),
+            registrations: 0,
+            held: false,
+        });
+        entry.registrations += 1;
+        entry.pump.subscribe()
+    }
+
+    /// Keeps the thread's pump running while a turn runs, calling `start`
+    /// for a pump unless one is running.
+    pub fn hold_pump(&mut self, thread_id: ThreadId, start: impl FnOnce() -> EventPump) {
+        let entry = self.pumps.entry(thread_id).or_insert_with(|| PumpEntry {
+            pump: start(),
+            registrations: 0,
+            held: false,
+        });
+        entry.registrations += 1;
+        entry.held = true;
+    }
+
+    /// Holds the pump publishing into `events`, for a turn it saw start.
+    pub fn hold_running_pump(
+        &mut self,
+        thread_id: ThreadId,
+        events: &broadcast::Sender<PublishedEvent>,
+    ) {
+        if let Some(entry) = self.pumps.get_mut(&thread_id)
+            && entry.pump.feeds(events)
+        {
+            entry.registrations += 1;
+            entry.held = true;
+        }
+    }
+
+    /// Lets the pump publishing into `events` stop once its turn ended. When
+    /// no stream is attached, returns the token to pass to
+    /// [`SessionStore::stop_idle_pump`] after [`PUMP_GRACE_PERIOD`].
+    pub fn release_pump(
+        &mut self,
+        thread_id: ThreadId,
+        events: &broadcast::Sender<PublishedEvent>,
+    ) -> Option<u64> {
+        let entry = self
+            .pumps
+            .get_mut(&thread_id)
+            .filter(|entry| entry.pump.feeds(events))?;
+        entry.held = false;
+        (!self.active_streams.contains_key(&thread_id)).then_some(entry.registrations)
     }
 
-    pub fn unregister_stream(&mut self, thread_id: ThreadId) {
+    /// Uncounts a stream. When it was the thread's last, returns the token to
+    /// pass to [`SessionStore::stop_idle_pump`] after [`PUMP_GRACE_PERIOD`].
+    pub fn unregister_stream(&mut self, thread_id: ThreadId) -> Option<u64> {
         if let Some(count) = self.active_streams.get_mut(&thread_id) {
             *count = count.saturating_sub(1);
             if *count == 0 {
                 self.active_streams.remove(&thread_id);
             }
         }
+        self.touch(thread_id);
+        if self.active_streams.contains_key(&thread_id) {
+            return None;
+        }
+        self.pumps.get(&thread_id).map(|entry| entry.registrations)
+    }
+
+    /// Stops the thread's pump unless a stream registered or a turn held it
+    /// since `registrations` was returned.
+    pub fn stop_idle_pump(&mut self, thread_id: ThreadId, registrations: u64) {
+        if !self.active_streams.contains_key(&thread_id)
+            && self
+                .pumps
+                .get(&thread_id)
+                .is_some_and(|entry| entry.registrations == registrations && !entry.held)
+        {
+            self.pumps.remove(&thread_id);
+        }
+    }
+
+    /// Stops the thread's pump if no stream is attached, e.g. before draining
+    /// its events elsewhere.
+    pub fn stop_unwatched_pump(&mut self, thread_id: ThreadId) {
+        if !self.active_streams.contains_key(&thread_id) {
+            self.pumps.remove(&thread_id);
+        }
     }
+
+    /// Drops the pump publishing into `events` after its thread stopped
+    /// producing events.
+    pub fn pump_ended(&mut self, thread_id: ThreadId, events: &broadcast::Sender<PublishedEvent>) {
+        if self
+            .pumps
+            .get(&thread_id)
+            .is_some_and(|entry| entry.pump.feeds(events))
+        {
+            self.pumps.remove(&thread_id);
+        }
+    }
+
+    /// Records activity on a thread, resetting its idle timer.
+    pub fn touch(&mut self, thread_id: ThreadId) {
+        self.last_activity.insert(thread_id, Instant::now());
+    }
+
+    /// Returns how long `thread_id` has been idle as of `now`, or `None` while
+    /// an SSE stream is attached. Threads seen for the first time start idling
+    /// at `now`.
+    pub fn idle_for(&mut self, thread_id: ThreadId, now: Instant) -> Option<Duration> {
+        if self.active_streams.contains_key(&thread_id) {
+            return None;
+        }
+        let last_activity = *self.last_activity.entry(thread_id).or_insert(now);
+        Some(now.saturating_duration_since(last_activity))
+    }
+
+    pub fn forget(&mut self, thread_id: ThreadId) {
+        self.active_streams.remove(&thread_id);
+        self.last_activity.remove(&thread_id);
+        self.pumps.remove(&thread_id);
+    }
+}
+
+/// Keeps an SSE stream registered in the [`SessionStore`] until dropped, which
+/// also covers clients that disconnect mid-stream. The last stream of a thread
+/// stops its pump after [`PUMP_GRACE_PERIOD`].
+pub struct StreamRegistration {
+    sessions: Arc<RwLock<SessionStore>>,
+    thread_id: ThreadId,
+}
+
+impl StreamRegistration {
+    /// Registers a stream on `thread` and returns it with the receiver of the
+    /// thread's events.
+    pub async fn new(
+        state: &WebServerState,
+        thread_id: ThreadId,
+        thread: Arc<CodexThread>,
+    ) -> (Self, broadcast::Receiver<PublishedEvent>) {
+        let events = state.sessions.write().await.register_stream(thread_id, || {
+            EventPump::start(state.clone(), thread_id, thread)
+        });
+        let registration = Self {
+            sessions: state.sessions.clone(),
+            thread_id,
+        };
+        (registration, events)
+    }
+}
+
+impl Drop for StreamRegistration {
+    fn drop(&mut self) {
+        let sessions = self.sessions.clone();
+        let thread_id = self.thread_id;
+        tokio::spawn(async move {
+            let Some(registrations) = sessions.write().await.unregister_stream(thread_id) else {
+                return;
+            };
+            stop_pump_after_grace(sessions, thread_id, registrations).await;
+        });
+    }
+}
+
+/// Waits [`PUMP_GRACE_PERIOD`], then stops the thread's pump unless it was
+/// registered on or held since `registrations`.
+pub async fn stop_pump_after_grace(
+    sessions: Arc<RwLock<SessionStore>>,
+    thread_id: ThreadId,
+    registrations: u64,
+) {
+    tokio::time::sleep(PUMP_GRACE_PERIOD).await;
+    sessions
+        .write()
+        .await
+        .stop_idle_pump(thread_id, registrations);
 }
 
 #[allow(dead_code)]
 pub struct ApprovalContext {
     pub thread_id: ThreadId,
+    /// Turn that asked for the approval
+    pub turn_id: String,
     pub item_id: String,
     pub approval_type: ApprovalType,
     pub response_channel: tokio::sync::oneshot::Sender<ApprovalResponse>,
@@ -96,21 +694,39 @@ pub enum ApprovalType {
     CommandExecution {
         command: Vec<String>,
         cwd: PathBuf,
-        reason: String,
+        reason: Option<String>,
+        /// Set when one command asks for several approvals; otherwise the
+        /// approval is keyed by the command's item id
+        approval_id: Option<String>,
+        network_approval_context: Option<NetworkApprovalContext>,
+        /// Allow rule the client may accept instead of a one-off approval
+        proposed_execpolicy_amendment: Option<ExecPolicyAmendment>,
     },
     FileChange {
-        reason: String,
+        reason: Option<String>,
+        grant_root: Option<PathBuf>,
     },
 }
 
 #[derive(Debug, Clone)]
-pub struct ApprovalResponse {
-    pub decision: ApprovalDecision,
+pub enum ApprovalResponse {
+    /// Answered by a web client
+    Decided(ApprovalDecision),
+    /// Approved, and the proposed execpolicy amendment accepted
+    AcceptedAmendment(ExecPolicyAmendment),
+    /// Resolved outside the web server; nothing is submitted to the thread
+    ResolvedExternally,
+    /// The turn ended before anyone answered; nothing is submitted
+    Cancelled,
 }
 
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum ApprovalDecision {
     Approve,
+    /// Approve, and let identical requests run without asking for the rest
+    /// of the session
+    #[serde(rename = "approve_for_session")]
+    ApproveForSession,
     Decline,
 }
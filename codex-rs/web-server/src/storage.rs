@@ -0,0 +1,164 @@
+//! Disk usage under `codex_home` and the vacuum that reclaims it.
+//!
+//! Rollouts are grouped by the thread id at the end of their file name
+//! (`rollout-<timestamp>-<thread id>.jsonl`); everything else is counted per
+//! file. Rollouts under `archived_sessions` count as archived.
+
+use codex_protocol::ThreadId;
+use serde::Serialize;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use utoipa::ToSchema;
+
+/// Directory the file log layer writes to.
+const LOG_SUBDIR: &str = "log";
+/// Directories holding files Codex derives from threads, not user data.
+const ARTIFACT_SUBDIRS: [&str; 2] = ["shell_snapshots", "memories"];
+/// Length of a thread id in its hyphenated form.
+const THREAD_ID_LEN: usize = 36;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageCategory {
+    Rollouts,
+    Attachments,
+    Artifacts,
+    Logs,
+}
+
+/// A file found by [`scan`].
+#[derive(Debug, Clone)]
+pub struct StoredFile {
+    pub category: StorageCategory,
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub modified: Option<SystemTime>,
+    /// Thread a rollout belongs to
+    pub thread_id: Option<ThreadId>,
+    /// Whether a rollout lives under `archived_sessions`
+    pub archived: bool,
+}
+
+/// Lists every file the storage report covers. Missing directories are
+/// skipped and unreadable entries are logged.
+pub fn scan(codex_home: &Path, attachments_dir: &Path) -> Vec<StoredFile> {
+    let mut files = Vec::new();
+    let roots = [
+        (
+            codex_home.join(codex_core::SESSIONS_SUBDIR),
+            StorageCategory::Rollouts,
+            false,
+        ),
+        (
+            codex_home.join(codex_core::ARCHIVED_SESSIONS_SUBDIR),
+            StorageCategory::Rollouts,
+            true,
+        ),
+        (
+            attachments_dir.to_path_buf(),
+            StorageCategory::Attachments,
+            false,
+        ),
+        (codex_home.join(LOG_SUBDIR), StorageCategory::Logs, false),
+    ];
+    let artifact_roots = ARTIFACT_SUBDIRS
+        .iter()
+        .map(|subdir| (codex_home.join(subdir), StorageCategory::Artifacts, false));
+
+    for (root, category, archived) in roots.into_iter().chain(artifact_roots) {
+        walk(&root, &mut |path, metadata| {
+            let thread_id = match category {
+                StorageCategory::Rollouts => rollout_thread_id(path),
+                _ => None,
+            };
+            files.push(StoredFile {
+                category,
+                path: path.to_path_buf(),
+                bytes: metadata.len(),
+                modified: metadata.modified().ok(),
+                thread_id,
+                archived,
+            });
+        });
+    }
+    files
+}
+
+/// Lists rollout files under `sessions`, and under `archived_sessions` when
+/// `include_archived` is set.
+pub fn scan_rollouts(codex_home: &Path, include_archived: bool) -> Vec<StoredFile> {
+    let mut files = Vec::new();
+    let mut roots = vec![(codex_home.join(codex_core::SESSIONS_SUBDIR), false)];
+    if include_archived {
+        roots.push((codex_home.join(codex_core::ARCHIVED_SESSIONS_SUBDIR), true));
+    }
+    for (root, archived) in roots {
+        walk(&root, &mut |path, metadata| {
+            files.push(StoredFile {
+                category: StorageCategory::Rollouts,
+                path: path.to_path_buf(),
+                bytes: metadata.len(),
+                modified: metadata.modified().ok(),
+                thread_id: rollout_thread_id(path),
+                archived,
+            });
+        });
+    }
+    files
+}
+
+fn walk(dir: &Path, visit: &mut dyn FnMut(&Path, &std::fs::Metadata)) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return,
+        Err(err) => {
+            tracing::warn!("Failed to read {}: {err}", dir.display());
+            return;
+        }
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // Symlinks are not followed so the scan stays inside `codex_home`.
+        match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => walk(&path, visit),
+            Ok(metadata) if metadata.is_file() => visit(&path, &metadata),
+            Ok(_) => {}
+            Err(err) => tracing::warn!("Failed to stat {}: {err}", path.display()),
+        }
+    }
+}
+
+/// Thread id of a `rollout-<timestamp>-<thread id>.jsonl` file.
+pub fn rollout_thread_id(path: &Path) -> Option<ThreadId> {
+    let stem = path.file_name()?.to_str()?.strip_suffix(".jsonl")?;
+    let start = stem.len().checked_sub(THREAD_ID_LEN)?;
+    ThreadId::from_string(stem.get(start..)?).ok()
+}
+
+/// Removes the files in `paths` and the directories they leave empty below
+/// the top-level directories of `codex_home`. Returns the files that could
+/// not be removed.
+pub fn remove_files<'a>(
+    codex_home: &Path,
+    paths: impl IntoIterator<Item = &'a Path>,
+) -> Vec<(PathBuf, std::io::Error)> {
+    let mut failed = Vec::new();
+    for path in paths {
+        if let Err(err) = std::fs::remove_file(path) {
+            failed.push((path.to_path_buf(), err));
+            continue;
+        }
+        // Rollouts sit in dated directories; drop the ones emptied here.
+        let mut parent = path.parent();
+        while let Some(dir) = parent
+            && dir.parent().is_some_and(|grandparent| {
+                grandparent != codex_home && grandparent.starts_with(codex_home)
+            })
+            && std::fs::remove_dir(dir).is_ok()
+        {
+            parent = dir.parent();
+        }
+    }
+    failed
+}
@@ -0,0 +1,327 @@
+//! Tracing setup and the spans the server opens for requests, turns and tool
+//! calls.
+//!
+//! With the `otel` feature, spans are exported over OTLP when the standard
+//! `OTEL_EXPORTER_OTLP_*` variables name an endpoint, and an incoming
+//! `traceparent` header becomes the parent of the request span. Without the
+//! feature, or without an endpoint, the same spans only feed the log output.
+
+use axum::body::Body;
+use axum::http::Request;
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use tower_http::classify::ServerErrorsAsFailures;
+use tower_http::classify::SharedClassifier;
+use tower_http::trace::TraceLayer;
+use tracing::Span;
+use tracing::field;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+#[cfg(feature = "otel")]
+pub use otel::trace_settings;
+
+pub const SERVICE_NAME: &str = "codex-web-server";
+
+/// Keeps the exporter alive; dropping it flushes pending spans.
+pub struct Telemetry {
+    #[cfg(feature = "otel")]
+    _provider: Option<codex_otel::otel_provider::OtelProvider>,
+}
+
+/// Installs the global subscriber: log output filtered by `RUST_LOG`, plus
+/// OTLP span export when configured.
+pub fn init() -> anyhow::Result<Telemetry> {
+    let fmt_layer = tracing_subscriber::fmt::layer()
+        .with_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")));
+
+    #[cfg(feature = "otel")]
+    {
+        let provider = otel::provider_from_env()?;
+        let otel_layer = provider
+            .as_ref()
+            .and_then(|provider| provider.tracing_layer());
+        tracing_subscriber::registry()
+            .with(fmt_layer)
+            .with(otel_layer)
+            .try_init()?;
+        if provider.is_some() {
+            tracing::info!("📡 Exporting traces over OTLP");
+        }
+        return Ok(Telemetry {
+            _provider: provider,
+        });
+    }
+
+    #[cfg(not(feature = "otel"))]
+    {
+        tracing_subscriber::registry().with(fmt_layer).try_init()?;
+        Ok(Telemetry {})
+    }
+}
+
+pub type RequestTraceLayer =
+    TraceLayer<SharedClassifier<ServerErrorsAsFailures>, fn(&Request<Body>) -> Span>;
+
+/// Wraps every request in [`request_span`].
+pub fn trace_layer() -> RequestTraceLayer {
+    TraceLayer::new_for_http().make_span_with(request_span as fn(&Request<Body>) -> Span)
+}
+
+/// Span for one HTTP request. `thread_id` is filled from the path; handlers
+/// that start a turn record `turn_id`.
+pub fn request_span(request: &Request<Body>) -> Span {
+    let path = request.uri().path();
+    let span = tracing::info_span!(
+        "http.request",
+        otel.name = %format!("{} {path}", request.method()),
+        otel.kind = "server",
+        http.request.method = %request.method(),
+        url.path = %path,
+        thread_id = field::Empty,
+        turn_id = field::Empty,
+    );
+    if let Some(thread_id) = thread_id_from_path(path) {
+        span.record("thread_id", thread_id);
+    }
+
+    #[cfg(feature = "otel")]
+    otel::set_remote_parent(&span, request.headers());
+
+    span
+}
+
+fn thread_id_from_path(path: &str) -> Option<&str> {
+    match path.trim_start_matches('/').split('/').collect::<Vec<_>>()[..] {
+        ["api", _, "threads", thread_id, ..] if !thread_id.is_empty() => Some(thread_id),
+        _ => None,
+    }
+}
+
+/// Open turn and tool-call spans, closed when the thread's event pump
+/// reports them finished or the thread stops producing events. The pump runs
+/// while a turn does, so spans close whether or not a client is streaming.
+#[derive(Default)]
+pub struct TurnSpans {
+    turns: Mutex<HashMap<(ThreadId, String), Span>>,
+    tools: Mutex<HashMap<(ThreadId, String), Span>>,
+}
+
+impl TurnSpans {
+    /// Opens the span for a submitted turn under the current request span.
+    pub fn turn_started(&self, thread_id: ThreadId, turn_id: &str) {
+        Span::current().record("turn_id", turn_id);
+        let span = tracing::info_span!(
+            "turn",
+            otel.name = "turn",
+            thread_id = %thread_id,
+            turn_id = %turn_id,
+        );
+        self.turns
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert((thread_id, turn_id.to_string()), span);
+    }
+
+    /// Opens and closes spans as a thread's events stream by. `event.id` is
+    /// the turn id.
+    pub fn observe(&self, thread_id: ThreadId, event: &Event) {
+        match &event.msg {
+            EventMsg::TurnComplete(_) => self.turn_finished(thread_id, &event.id, "completed"),
+            EventMsg::TurnAborted(_) => self.turn_finished(thread_id, &event.id, "aborted"),
+            EventMsg::ExecCommandBegin(ev) => {
+                self.tool_started(thread_id, &ev.turn_id, &ev.call_id, "exec_command");
+            }
+            EventMsg::McpToolCallBegin(ev) => {
+                let tool = format!("{}/{}", ev.invocation.server, ev.invocation.tool);
+                self.tool_started(thread_id, &event.id, &ev.call_id, &tool);
+            }
+            EventMsg::ExecCommandEnd(ev) => self.tool_finished(thread_id, &ev.call_id),
+            EventMsg::McpToolCallEnd(ev) => self.tool_finished(thread_id, &ev.call_id),
+            _ => {}
+        }
+    }
+
+    /// Ids of the thread's turns whose spans are open.
+    pub fn open_turns(&self, thread_id: ThreadId) -> Vec<String> {
+        self.turns
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .keys()
+            .filter(|(turn_thread_id, _)| *turn_thread_id == thread_id)
+            .map(|(_, turn_id)| turn_id.clone())
+            .collect()
+    }
+
+    /// Closes every span of a thread that will report nothing further.
+    pub fn thread_ended(&self, thread_id: ThreadId) {
+        for turn_id in self.open_turns(thread_id) {
+            self.turn_finished(thread_id, &turn_id, "closed");
+        }
+    }
+
+    /// Closes the turn span along with any tool spans still open under it.
+    fn turn_finished(&self, thread_id: ThreadId, turn_id: &str, status: &str) {
+        let span = self
+            .turns
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&(thread_id, turn_id.to_string()));
+        if let Some(span) = span {
+            span.in_scope(|| tracing::info!(status, "turn finished"));
+        }
+        self.tools
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|(tool_thread_id, _), _| *tool_thread_id != thread_id);
+    }
+
+    /// Opens a tool-call span under the turn it belongs to.
+    fn tool_started(&self, thread_id: ThreadId, turn_id: &str, call_id: &str, tool: &str) {
+        let parent = self
+            .turns
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&(thread_id, turn_id.to_string()))
+            .cloned()
+            .unwrap_or_else(Span::none);
+        let span = tracing::info_span!(
+            parent: &parent,
+            "tool_call",
+            otel.name = %format!("tool {tool}"),
+            thread_id = %thread_id,
+            turn_id = %turn_id,
+            call_id = %call_id,
+            tool = %tool,
+        );
+        self.tools
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert((thread_id, call_id.to_string()), span);
+    }
+
+    fn tool_finished(&self, thread_id: ThreadId, call_id: &str) {
+        self.tools
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&(thread_id, call_id.to_string()));
+    }
+}
+
+#[cfg(feature = "otel")]
+mod otel {
+    use codex_otel::config::OtelExporter;
+    use codex_otel::config::OtelHttpProtocol;
+    use codex_otel::config::OtelSettings;
+    use codex_otel::otel_provider::OtelProvider;
+    use opentelemetry::propagation::Extractor;
+    use std::collections::HashMap;
+    use std::path::PathBuf;
+    use tracing::Span;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    use super::SERVICE_NAME;
+
+    struct HeaderExtractor<'a>(&'a http::HeaderMap);
+
+    impl Extractor for HeaderExtractor<'_> {
+        fn get(&self, key: &str) -> Option<&str> {
+            self.0.get(key).and_then(|value| value.to_str().ok())
+        }
+
+        fn keys(&self) -> Vec<&str> {
+            self.0.keys().map(http::HeaderName::as_str).collect()
+        }
+    }
+
+    pub(super) fn set_remote_parent(span: &Span, headers: &http::HeaderMap) {
+        if !headers.contains_key("traceparent") {
+            return;
+        }
+        let context = opentelemetry::global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(headers))
+        });
+        let _ = span.set_parent(context);
+    }
+
+    pub(super) fn provider_from_env() -> anyhow::Result<Option<OtelProvider>> {
+        let codex_home = dirs::home_dir().unwrap_or_default().join(".codex");
+        let Some(settings) = trace_settings(|key| std::env::var(key).ok(), codex_home) else {
+            return Ok(None);
+        };
+        OtelProvider::from(&settings).map_err(|e| anyhow::anyhow!("error loading otel config: {e}"))
+    }
+
+    /// Trace-only exporter settings from the standard OTLP variables, or
+    /// `None` when no endpoint is set or `OTEL_SDK_DISABLED=true`.
+    pub fn trace_settings(
+        lookup: impl Fn(&str) -> Option<String>,
+        codex_home: PathBuf,
+    ) -> Option<OtelSettings> {
+        if lookup("OTEL_SDK_DISABLED").is_some_and(|value| value.eq_ignore_ascii_case("true")) {
+            return None;
+        }
+        let signal_or_generic = |name: &str| {
+            lookup(&format!("OTEL_EXPORTER_OTLP_TRACES_{name}"))
+                .or_else(|| lookup(&format!("OTEL_EXPORTER_OTLP_{name}")))
+        };
+
+        let protocol = signal_or_generic("PROTOCOL").unwrap_or_else(|| "http/protobuf".into());
+        // The generic endpoint is a base URL; HTTP exporters append the
+        // signal path to it.
+        let endpoint = match lookup("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT") {
+            Some(endpoint) => endpoint,
+            None => {
+                let base = lookup("OTEL_EXPORTER_OTLP_ENDPOINT")?;
+                if protocol == "grpc" {
+                    base
+                } else {
+                    format!("{}/v1/traces", base.trim_end_matches('/'))
+                }
+            }
+        };
+        let headers: HashMap<String, String> = signal_or_generic("HEADERS")
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|pair| pair.split_once('='))
+            .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+            .collect();
+
+        let trace_exporter = match protocol.as_str() {
+            "grpc" => OtelExporter::OtlpGrpc {
+                endpoint,
+                headers,
+                tls: None,
+            },
+            "http/json" => OtelExporter::OtlpHttp {
+                endpoint,
+                headers,
+                protocol: OtelHttpProtocol::Json,
+                tls: None,
+            },
+            _ => OtelExporter::OtlpHttp {
+                endpoint,
+                headers,
+                protocol: OtelHttpProtocol::Binary,
+                tls: None,
+            },
+        };
+
+        Some(OtelSettings {
+            environment: "dev".to_string(),
+            service_name: lookup("OTEL_SERVICE_NAME").unwrap_or_else(|| SERVICE_NAME.to_string()),
+            service_version: env!("CARGO_PKG_VERSION").to_string(),
+            codex_home,
+            exporter: OtelExporter::None,
+            trace_exporter,
+            metrics_exporter: OtelExporter::None,
+            runtime_metrics: false,
+        })
+    }
+}
@@ -0,0 +1,204 @@
+//! Streaming export of a thread's rollout for `GET /api/v2/threads/:id/export`.
+//!
+//! Rollout lines are read one at a time, from the rollout file or a page of
+//! Postgres rows, and each is formatted and handed to the response body on
+//! its own. The body only polls for the next line once the previous chunk
+//! has been taken, so a slow client holds back the reader instead of the
+//! server buffering the transcript, and memory stays flat however long the
+//! thread is.
+
+use codex_core::PostgresRolloutLinePages;
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use futures::Stream;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::Value;
+use std::io;
+use std::io::ErrorKind;
+use std::path::Path;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::BufReader;
+use utoipa::ToSchema;
+
+/// Items exported when the request sets no `max_items`.
+pub const DEFAULT_EXPORT_MAX_ITEMS: usize = 100_000;
+
+/// Postgres rows fetched per query.
+const POSTGRES_PAGE_SIZE: i64 = 500;
+
+/// Lines read between yields to the runtime. A file read from the buffer is
+/// always ready, so a long export would otherwise never give way.
+const YIELD_EVERY: usize = 256;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// One rollout line per line, as stored
+    #[default]
+    Jsonl,
+    /// The conversation as a readable transcript
+    Markdown,
+}
+
+impl ExportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Jsonl => "application/x-ndjson",
+            ExportFormat::Markdown => "text/markdown; charset=utf-8",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Markdown => "md",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Leave out raw model response items
+    pub compact: bool,
+    /// Items written before the export stops
+    pub max_items: usize,
+}
+
+impl ExportOptions {
+    /// The chunk written for one rollout line, or `None` when the line is
+    /// left out of the export.
+    fn chunk(&self, line: &str) -> Option<String> {
+        match self.format {
+            ExportFormat::Jsonl => {
+                (!(self.compact && is_response_item_line(line))).then(|| format!("{line}\n"))
+            }
+            ExportFormat::Markdown => serde_json::from_str::<RolloutLine>(line)
+                .ok()
+                .and_then(|line| markdown(&line.item)),
+        }
+    }
+}
+
+/// The lines of a rollout file, read as the export asks for them.
+pub async fn file_lines(
+    path: &Path,
+) -> io::Result<impl Stream<Item = io::Result<String>> + Send + 'static> {
+    let mut lines = BufReader::new(tokio::fs::File::open(path).await?).lines();
+    Ok(async_stream::stream! {
+        loop {
+            match lines.next_line().await {
+                Ok(Some(line)) => yield Ok(line),
+                Ok(None) => break,
+                Err(err) => {
+                    yield Err(err);
+                    break;
+                }
+            }
+        }
+    })
+}
+
+/// The thread's Postgres rows as rollout-file lines, fetched a page at a
+/// time. Fails with `NotFound` when the thread has no rows.
+pub async fn postgres_lines(
+    thread_id: ThreadId,
+) -> io::Result<impl Stream<Item = io::Result<String>> + Send + 'static> {
+    let mut pages = PostgresRolloutLinePages::open(thread_id).await?;
+    let mut page = pages.next_page(POSTGRES_PAGE_SIZE).await?;
+    if page.is_empty() {
+        return Err(io::Error::new(
+            ErrorKind::NotFound,
+            format!("no rollout history found in Postgres for thread {thread_id}"),
+        ));
+    }
+    Ok(async_stream::stream! {
+        while !page.is_empty() {
+            for line in page {
+                yield serde_json::to_string(&line).map_err(io::Error::other);
+            }
+            page = match pages.next_page(POSTGRES_PAGE_SIZE).await {
+                Ok(page) => page,
+                Err(err) => {
+                    yield Err(err);
+                    break;
+                }
+            };
+        }
+    })
+}
+
+/// Formats `lines` one at a time, stopping after `options.max_items` items.
+/// A Markdown export that stops early ends with a note saying so.
+pub fn export_stream<S>(
+    lines: S,
+    options: ExportOptions,
+) -> impl Stream<Item = io::Result<String>> + Send + 'static
+where
+    S: Stream<Item = io::Result<String>> + Send + 'static,
+{
+    async_stream::stream! {
+        let mut lines = Box::pin(lines);
+        let mut read = 0;
+        let mut written = 0;
+        while let Some(line) = lines.next().await {
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => {
+                    yield Err(err);
+                    break;
+                }
+            };
+            read += 1;
+            if read % YIELD_EVERY == 0 {
+                tokio::task::yield_now().await;
+            }
+            let Some(chunk) = options.chunk(&line) else {
+                continue;
+            };
+            if written == options.max_items {
+                if options.format == ExportFormat::Markdown {
+                    yield Ok(format!(
+                        "_Export stopped after {written} items; raise `max_items` for the rest._\n"
+                    ));
+                }
+                break;
+            }
+            written += 1;
+            yield Ok(chunk);
+        }
+    }
+}
+
+/// Whether a rollout-file line holds a raw model response item. Lines that do
+/// not parse are kept.
+fn is_response_item_line(line: &str) -> bool {
+    serde_json::from_str::<Value>(line)
+        .is_ok_and(|value| value.get("type").and_then(Value::as_str) == Some("response_item"))
+}
+
+/// The transcript section for `item`; only the messages of the conversation
+/// and where its context was compacted are shown.
+fn markdown(item: &RolloutItem) -> Option<String> {
+    match item {
+        RolloutItem::SessionMeta(line) => Some(format!(
+            "# Thread {}\n\n- Started: {}\n- Working directory: `{}`\n\n",
+            line.meta.id,
+            line.meta.timestamp,
+            line.meta.cwd.display()
+        )),
+        RolloutItem::EventMsg(EventMsg::UserMessage(event)) => {
+            Some(format!("## User\n\n{}\n\n", event.message.trim_end()))
+        }
+        RolloutItem::EventMsg(EventMsg::AgentMessage(event)) => {
+            Some(format!("## Assistant\n\n{}\n\n", event.message.trim_end()))
+        }
+        RolloutItem::Compacted(_) => Some("_Earlier context was compacted._\n\n".to_string()),
+        RolloutItem::ResponseItem(_) | RolloutItem::TurnContext(_) | RolloutItem::EventMsg(_) => {
+            None
+        }
+    }
+}
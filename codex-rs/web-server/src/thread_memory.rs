@@ -0,0 +1,291 @@
+//! Memory budget shared by the per-thread caches.
+//!
+//! Caches that hold data per thread (item history, event replay, usage
+//! samples) keep their payloads here instead of in maps of their own, so a
+//! single budget bounds them together and a chatty thread cannot grow without
+//! limit. When a thread goes over budget, bulky payloads are evicted first,
+//! largest first and least recently used among equal sizes. Lifecycle and
+//! approval payloads are evicted only once no bulky payload is left, oldest
+//! first.
+
+use codex_protocol::ThreadId;
+use serde::Serialize;
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use utoipa::ToSchema;
+
+/// Bytes each thread may keep across its caches. Unset uses
+/// [`DEFAULT_THREAD_MEMORY_BUDGET`].
+pub const THREAD_MEMORY_ENV: &str = "CODEX_WEB_THREAD_MEMORY_BYTES";
+pub const DEFAULT_THREAD_MEMORY_BUDGET: usize = 32 * 1024 * 1024;
+
+/// Reads the budget from `CODEX_WEB_THREAD_MEMORY_BYTES`, keeping the default
+/// when it is unset, `0` or invalid.
+pub fn thread_memory_budget_from_env() -> usize {
+    let Ok(value) = std::env::var(THREAD_MEMORY_ENV) else {
+        return DEFAULT_THREAD_MEMORY_BUDGET;
+    };
+    match value.trim().parse::<usize>() {
+        Ok(0) => DEFAULT_THREAD_MEMORY_BUDGET,
+        Ok(bytes) => bytes,
+        Err(err) => {
+            tracing::warn!("Ignoring invalid {THREAD_MEMORY_ENV}={value:?}: {err}");
+            DEFAULT_THREAD_MEMORY_BUDGET
+        }
+    }
+}
+
+/// The cache a payload belongs to; each has its own key space.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CacheKind {
+    Items,
+    Replay,
+    Usage,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Retention {
+    /// Turn lifecycle and approval requests
+    Protected,
+    /// Deltas, command output and raw items
+    Bulky,
+}
+
+type EntryKey = (CacheKind, u64);
+
+#[derive(Debug)]
+struct Entry {
+    data: Arc<str>,
+    retention: Retention,
+    last_used: u64,
+}
+
+/// Payloads of one thread with the eviction order of each retention class.
+#[derive(Debug, Default)]
+struct ThreadEntries {
+    entries: HashMap<EntryKey, Entry>,
+    /// Largest last; among equal sizes the least recently used last.
+    bulky: BTreeSet<(usize, Reverse<u64>, EntryKey)>,
+    /// Least recently used first.
+    protected: BTreeSet<(u64, EntryKey)>,
+    used: usize,
+    clock: u64,
+}
+
+impl ThreadEntries {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn insert(&mut self, key: EntryKey, data: Arc<str>, retention: Retention) {
+        self.remove(key);
+        let last_used = self.tick();
+        self.index(key, data.len(), retention, last_used);
+        self.used += data.len();
+        self.entries.insert(
+            key,
+            Entry {
+                data,
+                retention,
+                last_used,
+            },
+        );
+    }
+
+    fn remove(&mut self, key: EntryKey) -> Option<Entry> {
+        let entry = self.entries.remove(&key)?;
+        self.unindex(key, &entry);
+        self.used -= entry.data.len();
+        Some(entry)
+    }
+
+    fn touch(&mut self, key: EntryKey) -> Option<Arc<str>> {
+        let last_used = self.tick();
+        let entry = self.entries.get(&key)?;
+        let (size, retention, previous) = (entry.data.len(), entry.retention, entry.last_used);
+        let data = Arc::clone(&entry.data);
+        match retention {
+            Retention::Bulky => {
+                self.bulky.remove(&(size, Reverse(previous), key));
+            }
+            Retention::Protected => {
+                self.protected.remove(&(previous, key));
+            }
+        }
+        self.index(key, size, retention, last_used);
+        if let Some(entry) = self.entries.get_mut(&key) {
+            entry.last_used = last_used;
+        }
+        Some(data)
+    }
+
+    fn index(&mut self, key: EntryKey, size: usize, retention: Retention, last_used: u64) {
+        match retention {
+            Retention::Bulky => {
+                self.bulky.insert((size, Reverse(last_used), key));
+            }
+            Retention::Protected => {
+                self.protected.insert((last_used, key));
+            }
+        }
+    }
+
+    fn unindex(&mut self, key: EntryKey, entry: &Entry) {
+        match entry.retention {
+            Retention::Bulky => {
+                self.bulky
+                    .remove(&(entry.data.len(), Reverse(entry.last_used), key));
+            }
+            Retention::Protected => {
+                self.protected.remove(&(entry.last_used, key));
+            }
+        }
+    }
+
+    /// The next payload to evict.
+    fn victim(&self) -> Option<EntryKey> {
+        self.bulky
+            .last()
+            .map(|(_, _, key)| *key)
+            .or_else(|| self.protected.first().map(|(_, key)| *key))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ThreadMemorySnapshot {
+    /// Bytes each thread may keep
+    pub budget_bytes: u64,
+    /// Bytes held across all threads
+    pub used_bytes: u64,
+    /// Threads holding any payload
+    pub threads: u64,
+    /// Payloads evicted to stay within budget since startup
+    pub evicted_entries: u64,
+    pub evicted_bytes: u64,
+    /// Of `evicted_entries`, lifecycle and approval payloads; non-zero means
+    /// the budget is too small for a thread's protected payloads alone
+    pub evicted_protected_entries: u64,
+}
+
+/// Per-thread payload store bounded by a byte budget per thread.
+#[derive(Debug)]
+pub struct ThreadMemory {
+    budget: usize,
+    threads: Mutex<HashMap<ThreadId, ThreadEntries>>,
+    evicted_entries: AtomicU64,
+    evicted_bytes: AtomicU64,
+    evicted_protected_entries: AtomicU64,
+}
+
+impl Default for ThreadMemory {
+    fn default() -> Self {
+        Self::new(DEFAULT_THREAD_MEMORY_BUDGET)
+    }
+}
+
+impl ThreadMemory {
+    pub fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            threads: Mutex::new(HashMap::new()),
+            evicted_entries: AtomicU64::new(0),
+            evicted_bytes: AtomicU64::new(0),
+            evicted_protected_entries: AtomicU64::new(0),
+        }
+    }
+
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    /// Stores `data` under `key`, replacing what was there, then evicts until
+    /// the thread is within budget. A payload larger than the whole budget is
+    /// evicted right away.
+    pub fn insert(
+        &self,
+        thread_id: ThreadId,
+        kind: CacheKind,
+        key: u64,
+        data: impl Into<Arc<str>>,
+        retention: Retention,
+    ) {
+        let mut threads = self.lock();
+        let entries = threads.entry(thread_id).or_default();
+        entries.insert((kind, key), data.into(), retention);
+        while entries.used > self.budget
+            && let Some(victim) = entries.victim()
+            && let Some(evicted) = entries.remove(victim)
+        {
+            self.evicted_entries.fetch_add(1, Ordering::Relaxed);
+            self.evicted_bytes
+                .fetch_add(evicted.data.len() as u64, Ordering::Relaxed);
+            if evicted.retention == Retention::Protected {
+                self.evicted_protected_entries
+                    .fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// The payload under `key`, marking it recently used.
+    pub fn get(&self, thread_id: ThreadId, kind: CacheKind, key: u64) -> Option<Arc<str>> {
+        self.lock().get_mut(&thread_id)?.touch((kind, key))
+    }
+
+    /// Keys the thread holds in `kind`, ascending.
+    pub fn keys(&self, thread_id: ThreadId, kind: CacheKind) -> Vec<u64> {
+        let threads = self.lock();
+        let mut keys: Vec<u64> = threads
+            .get(&thread_id)
+            .into_iter()
+            .flat_map(|entries| entries.entries.keys())
+            .filter(|(entry_kind, _)| *entry_kind == kind)
+            .map(|(_, key)| *key)
+            .collect();
+        keys.sort_unstable();
+        keys
+    }
+
+    pub fn remove(&self, thread_id: ThreadId, kind: CacheKind, key: u64) {
+        if let Some(entries) = self.lock().get_mut(&thread_id) {
+            entries.remove((kind, key));
+        }
+    }
+
+    /// Bytes the thread holds across its caches.
+    pub fn used(&self, thread_id: ThreadId) -> usize {
+        self.lock()
+            .get(&thread_id)
+            .map_or(0, |entries| entries.used)
+    }
+
+    /// Drops every payload of a deleted thread.
+    pub fn forget(&self, thread_id: ThreadId) {
+        self.lock().remove(&thread_id);
+    }
+
+    pub fn snapshot(&self) -> ThreadMemorySnapshot {
+        let threads = self.lock();
+        ThreadMemorySnapshot {
+            budget_bytes: self.budget as u64,
+            used_bytes: threads.values().map(|entries| entries.used as u64).sum(),
+            threads: threads
+                .values()
+                .filter(|entries| !entries.entries.is_empty())
+                .count() as u64,
+            evicted_entries: self.evicted_entries.load(Ordering::Relaxed),
+            evicted_bytes: self.evicted_bytes.load(Ordering::Relaxed),
+            evicted_protected_entries: self.evicted_protected_entries.load(Ordering::Relaxed),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<ThreadId, ThreadEntries>> {
+        self.threads.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
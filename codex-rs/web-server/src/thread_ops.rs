@@ -0,0 +1,84 @@
+//! Serializes operations that change a thread's lifecycle.
+//!
+//! A handler calls [`ThreadOps::begin`] before it looks the thread up and
+//! takes the lock with [`ThreadOp::lock`] only around its mutation: the
+//! submit, not the turn it starts. Archive, auto-archive and resume bump the
+//! thread's generation while holding the lock. An operation that began before
+//! one of them then fails with `409 thread_state_changed` instead of acting
+//! on a thread that changed underneath it.
+
+use codex_protocol::ThreadId;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use tokio::sync::MutexGuard;
+
+use crate::error::ApiError;
+
+#[derive(Default)]
+struct ThreadSlot {
+    lock: tokio::sync::Mutex<()>,
+    generation: AtomicU64,
+}
+
+/// Per-thread operation locks and generations.
+#[derive(Default)]
+pub struct ThreadOps {
+    // Slots are kept after a thread is unloaded so that operations that
+    // began before the unload still see the bumped generation.
+    slots: Mutex<HashMap<ThreadId, Arc<ThreadSlot>>>,
+}
+
+impl ThreadOps {
+    /// Records the thread's current generation for a later [`ThreadOp::lock`].
+    pub fn begin(&self, thread_id: ThreadId) -> ThreadOp {
+        let slot = self
+            .slots
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(thread_id)
+            .or_default()
+            .clone();
+        let generation = slot.generation.load(Ordering::SeqCst);
+        ThreadOp { slot, generation }
+    }
+}
+
+/// An operation on one thread, started with [`ThreadOps::begin`].
+pub struct ThreadOp {
+    slot: Arc<ThreadSlot>,
+    generation: u64,
+}
+
+impl ThreadOp {
+    /// Waits for conflicting operations to finish, then fails with
+    /// [`ApiError::ThreadStateChanged`] if one of them changed the thread
+    /// since [`ThreadOps::begin`].
+    pub async fn lock(&self) -> Result<ThreadOpGuard<'_>, ApiError> {
+        let guard = self.slot.lock.lock().await;
+        if self.slot.generation.load(Ordering::SeqCst) != self.generation {
+            return Err(ApiError::ThreadStateChanged);
+        }
+        Ok(ThreadOpGuard {
+            _guard: guard,
+            slot: &self.slot,
+        })
+    }
+}
+
+/// Held for the critical section of a thread operation.
+pub struct ThreadOpGuard<'a> {
+    _guard: MutexGuard<'a, ()>,
+    slot: &'a ThreadSlot,
+}
+
+impl ThreadOpGuard<'_> {
+    /// Marks the thread as changed; operations that began earlier get a 409
+    /// when they lock.
+    pub fn bump(&self) {
+        self.slot.generation.fetch_add(1, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,99 @@
+//! File-backed per-thread preferences (pins, notification opt-outs) used when
+//! Postgres persistence is not configured. Stored next to `session_index.jsonl` in codex home.
+
+use codex_protocol::ThreadId;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+use tokio::sync::Mutex;
+
+const THREAD_PREFS_FILE: &str = "web_thread_prefs.json";
+
+/// Serializes read-modify-write cycles on the prefs file.
+static THREAD_PREFS_LOCK: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ThreadPrefsFile {
+    #[serde(default)]
+    pinned: BTreeSet<String>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    notifications_muted: BTreeSet<String>,
+}
+
+fn thread_prefs_path(codex_home: &Path) -> PathBuf {
+    codex_home.join(THREAD_PREFS_FILE)
+}
+
+async fn read_prefs(path: &Path) -> std::io::Result<ThreadPrefsFile> {
+    match tokio::fs::read_to_string(path).await {
+        Ok(contents) => serde_json::from_str(&contents).map_err(std::io::Error::other),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(ThreadPrefsFile::default()),
+        Err(err) => Err(err),
+    }
+}
+
+fn thread_ids(ids: &BTreeSet<String>) -> HashSet<ThreadId> {
+    ids.iter()
+        .filter_map(|id| ThreadId::from_string(id).ok())
+        .collect()
+}
+
+pub async fn pinned_threads(codex_home: &Path) -> std::io::Result<HashSet<ThreadId>> {
+    let prefs = read_prefs(&thread_prefs_path(codex_home)).await?;
+    Ok(thread_ids(&prefs.pinned))
+}
+
+pub async fn notifications_muted_threads(codex_home: &Path) -> std::io::Result<HashSet<ThreadId>> {
+    let prefs = read_prefs(&thread_prefs_path(codex_home)).await?;
+    Ok(thread_ids(&prefs.notifications_muted))
+}
+
+pub async fn set_thread_pinned(
+    codex_home: &Path,
+    thread_id: ThreadId,
+    pinned: bool,
+) -> std::io::Result<()> {
+    update_prefs(codex_home, |prefs| {
+        set_membership(&mut prefs.pinned, thread_id, pinned)
+    })
+    .await
+}
+
+pub async fn set_thread_notifications_muted(
+    codex_home: &Path,
+    thread_id: ThreadId,
+    muted: bool,
+) -> std::io::Result<()> {
+    update_prefs(codex_home, |prefs| {
+        set_membership(&mut prefs.notifications_muted, thread_id, muted)
+    })
+    .await
+}
+
+fn set_membership(ids: &mut BTreeSet<String>, thread_id: ThreadId, member: bool) {
+    if member {
+        ids.insert(thread_id.to_string());
+    } else {
+        ids.remove(&thread_id.to_string());
+    }
+}
+
+async fn update_prefs(
+    codex_home: &Path,
+    update: impl FnOnce(&mut ThreadPrefsFile),
+) -> std::io::Result<()> {
+    let _guard = THREAD_PREFS_LOCK.lock().await;
+    let path = thread_prefs_path(codex_home);
+    let mut prefs = read_prefs(&path).await?;
+    update(&mut prefs);
+
+    // Write to a sibling temp file and rename so readers never see a partial file.
+    let tmp_path = path.with_extension("json.tmp");
+    let contents = serde_json::to_vec_pretty(&prefs).map_err(std::io::Error::other)?;
+    tokio::fs::write(&tmp_path, contents).await?;
+    tokio::fs::rename(&tmp_path, &path).await
+}
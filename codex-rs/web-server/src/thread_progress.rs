@@ -0,0 +1,290 @@
+//! Progress notifications for compaction and rollback.
+//!
+//! Core reports these operations only when they end (`thread/compacted`, a
+//! rolled-back thread), which on a large thread can be tens of seconds after
+//! the request. Core exposes no intermediate progress, so the server
+//! synthesizes it: while an operation runs, `thread/compacting` or
+//! `thread/rollingBack` is broadcast with a percentage that climbs towards 99
+//! on a fixed checkpoint schedule, and with 100 once the thread's event
+//! stream shows the operation ended. Endpoints wait for the end only up to a
+//! bound; past it the operation is reported as running in the background.
+
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::CodexErrorInfo;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use serde::Serialize;
+use serde_json::Value;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::sync::atomic::AtomicU8;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use utoipa::ToSchema;
+
+use crate::sse_envelope::server_notification;
+
+const CHANGES_CAPACITY: usize = 256;
+/// Percentages reported at successive checkpoints. The first is sent as soon
+/// as the operation starts; the last holds until it ends.
+const CHECKPOINTS: [u8; 10] = [5, 15, 30, 45, 60, 70, 80, 88, 94, 99];
+pub const DEFAULT_CHECKPOINT_INTERVAL: Duration = Duration::from_secs(2);
+/// How long an endpoint waits for its operation before answering that it
+/// continues in the background.
+pub const DEFAULT_FOREGROUND_LIMIT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LongOperation {
+    Compaction,
+    Rollback,
+}
+
+impl LongOperation {
+    pub fn event_type(self) -> &'static str {
+        match self {
+            LongOperation::Compaction => "thread/compacting",
+            LongOperation::Rollback => "thread/rollingBack",
+        }
+    }
+
+    /// Whether `msg` shows the operation ended, successfully or not.
+    /// Compaction runs as a turn of its own, so the end of that turn counts
+    /// even when no `ContextCompacted` is emitted.
+    fn ended_by(self, msg: &EventMsg) -> bool {
+        match self {
+            LongOperation::Compaction => matches!(
+                msg,
+                EventMsg::ContextCompacted(_)
+                    | EventMsg::TurnComplete(_)
+                    | EventMsg::TurnAborted(_)
+            ),
+            LongOperation::Rollback => match msg {
+                EventMsg::ThreadRolledBack(_) => true,
+                EventMsg::Error(ev) => matches!(
+                    ev.codex_error_info,
+                    Some(CodexErrorInfo::ThreadRollbackFailed)
+                ),
+                _ => false,
+            },
+        }
+    }
+}
+
+/// Whether an operation finished before its endpoint answered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LongOperationStatus {
+    Completed,
+    /// Still running after the foreground limit; watch the event stream
+    RunningInBackground,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgressUpdate {
+    pub thread_id: ThreadId,
+    pub operation: LongOperation,
+    /// Estimated from checkpoints until the operation ends at 100
+    pub percent: u8,
+    /// The triggering endpoint stopped waiting for the operation
+    pub background: bool,
+}
+
+impl ProgressUpdate {
+    pub fn event_type(&self) -> &'static str {
+        self.operation.event_type()
+    }
+
+    pub fn payload(&self) -> Value {
+        server_notification(
+            self.event_type(),
+            json!({
+                "threadId": self.thread_id.to_string(),
+                "percent": self.percent,
+                "background": self.background,
+            }),
+        )
+    }
+}
+
+/// A rollback core applied, so clients can drop the turns from their local
+/// transcript. Sent by the thread's event stream when core reports it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RolledBack {
+    pub thread_id: ThreadId,
+    /// User turns dropped from the end of the thread
+    pub num_turns: u32,
+}
+
+impl RolledBack {
+    pub const EVENT_TYPE: &str = "thread/rolledBack";
+
+    pub fn payload(&self) -> Value {
+        server_notification(
+            Self::EVENT_TYPE,
+            json!({
+                "threadId": self.thread_id.to_string(),
+                "numTurns": self.num_turns,
+            }),
+        )
+    }
+}
+
+struct RunningOperation {
+    percent: Arc<AtomicU8>,
+    ended: watch::Sender<bool>,
+    ticker: JoinHandle<()>,
+}
+
+/// Resolves when an operation started with [`ProgressRegistry::begin`] ends.
+pub struct Completion(watch::Receiver<bool>);
+
+/// Compaction and rollback in flight, with their progress broadcast to all
+/// event streams.
+pub struct ProgressRegistry {
+    running: Mutex<HashMap<(ThreadId, LongOperation), RunningOperation>>,
+    changes: broadcast::Sender<ProgressUpdate>,
+    checkpoint_interval: Duration,
+    foreground_limit: Duration,
+}
+
+impl Default for ProgressRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_CHECKPOINT_INTERVAL, DEFAULT_FOREGROUND_LIMIT)
+    }
+}
+
+impl ProgressRegistry {
+    pub fn new(checkpoint_interval: Duration, foreground_limit: Duration) -> Self {
+        let (changes, _) = broadcast::channel(CHANGES_CAPACITY);
+        Self {
+            running: Mutex::new(HashMap::new()),
+            changes,
+            checkpoint_interval,
+            foreground_limit,
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ProgressUpdate> {
+        self.changes.subscribe()
+    }
+
+    pub fn is_running(&self, thread_id: ThreadId, operation: LongOperation) -> bool {
+        self.running
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains_key(&(thread_id, operation))
+    }
+
+    /// Starts reporting progress for `operation` on `thread_id`. Starting an
+    /// operation that is already running joins it.
+    pub fn begin(&self, thread_id: ThreadId, operation: LongOperation) -> Completion {
+        let mut running = self.running.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(existing) = running.get(&(thread_id, operation)) {
+            return Completion(existing.ended.subscribe());
+        }
+        let percent = Arc::new(AtomicU8::new(0));
+        let (ended, completion) = watch::channel(false);
+        let ticker = tokio::spawn({
+            let percent = Arc::clone(&percent);
+            let changes = self.changes.clone();
+            let interval = self.checkpoint_interval;
+            async move {
+                for (index, checkpoint) in CHECKPOINTS.into_iter().enumerate() {
+                    if index > 0 {
+                        tokio::time::sleep(interval).await;
+                    }
+                    percent.store(checkpoint, Ordering::SeqCst);
+                    // No receivers just means no stream is attached.
+                    let _ = changes.send(ProgressUpdate {
+                        thread_id,
+                        operation,
+                        percent: checkpoint,
+                        background: false,
+                    });
+                }
+            }
+        });
+        running.insert(
+            (thread_id, operation),
+            RunningOperation {
+                percent,
+                ended,
+                ticker,
+            },
+        );
+        Completion(completion)
+    }
+
+    /// Stops tracking an operation that failed to start, without reporting
+    /// it as ended.
+    pub fn abandon(&self, thread_id: ThreadId, operation: LongOperation) {
+        let removed = self
+            .running
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&(thread_id, operation));
+        if let Some(removed) = removed {
+            removed.ticker.abort();
+        }
+    }
+
+    /// Ends the thread's operations that `event` shows are over.
+    pub fn observe(&self, thread_id: ThreadId, event: &Event) {
+        let ended: Vec<_> = self
+            .running
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .extract_if(|(id, operation), _| *id == thread_id && operation.ended_by(&event.msg))
+            .collect();
+        for ((thread_id, operation), running) in ended {
+            running.ticker.abort();
+            let _ = self.changes.send(ProgressUpdate {
+                thread_id,
+                operation,
+                percent: 100,
+                background: false,
+            });
+            let _ = running.ended.send(true);
+        }
+    }
+
+    /// Waits up to the foreground limit for the operation behind `completion`
+    /// to end. When it does not, streams are told it continues in the
+    /// background.
+    pub async fn wait_in_foreground(
+        &self,
+        thread_id: ThreadId,
+        operation: LongOperation,
+        completion: Completion,
+    ) -> LongOperationStatus {
+        let Completion(mut ended) = completion;
+        let finished = tokio::time::timeout(self.foreground_limit, ended.wait_for(|ended| *ended))
+            .await
+            .is_ok_and(|result| result.is_ok());
+        if finished {
+            return LongOperationStatus::Completed;
+        }
+        let percent = self
+            .running
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&(thread_id, operation))
+            .map(|running| running.percent.load(Ordering::SeqCst));
+        if let Some(percent) = percent {
+            let _ = self.changes.send(ProgressUpdate {
+                thread_id,
+                operation,
+                percent,
+                background: true,
+            });
+        }
+        LongOperationStatus::RunningInBackground
+    }
+}
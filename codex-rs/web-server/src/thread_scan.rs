@@ -0,0 +1,202 @@
+//! Metadata of stored threads, collected without resuming them.
+//!
+//! The thread listing shows threads known only from storage as `persisted`,
+//! with the names and timestamps it can read cheaply. A scan adds the model
+//! of each thread's latest turn, read from the end of its rollout file or
+//! from the Postgres rollout store, so a client can offer to resume it after
+//! a restart. Reading rollouts is the expensive part: a scan covers only the
+//! most recently active threads, and its result is kept until the next scan.
+//!
+//! The scan runs at startup when `CODEX_WEB_SCAN_THREADS_ON_STARTUP` is set,
+//! and on `POST /api/v2/threads/scan`.
+
+use codex_protocol::ThreadId;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+use utoipa::ToSchema;
+
+use crate::rollout_tail::read_rollout_tail;
+use crate::state::WebServerState;
+use crate::storage;
+
+/// Scan stored threads once the server has started when set to `1` or `true`.
+pub const SCAN_ON_STARTUP_ENV: &str = "CODEX_WEB_SCAN_THREADS_ON_STARTUP";
+/// Most threads one scan reads. Unset uses [`DEFAULT_SCAN_LIMIT`].
+pub const SCAN_LIMIT_ENV: &str = "CODEX_WEB_THREAD_SCAN_LIMIT";
+pub const DEFAULT_SCAN_LIMIT: usize = 200;
+
+/// Reads `CODEX_WEB_SCAN_THREADS_ON_STARTUP`.
+pub fn scan_on_startup_from_env() -> bool {
+    std::env::var(SCAN_ON_STARTUP_ENV).is_ok_and(|value| matches!(value.trim(), "1" | "true"))
+}
+
+/// Reads the scan limit from `CODEX_WEB_THREAD_SCAN_LIMIT`, keeping the
+/// default when it is unset, `0` or invalid.
+pub fn scan_limit_from_env() -> usize {
+    let Ok(value) = std::env::var(SCAN_LIMIT_ENV) else {
+        return DEFAULT_SCAN_LIMIT;
+    };
+    match value.trim().parse::<usize>() {
+        Ok(0) => DEFAULT_SCAN_LIMIT,
+        Ok(limit) => limit,
+        Err(err) => {
+            tracing::warn!("Ignoring invalid {SCAN_LIMIT_ENV}={value:?}: {err}");
+            DEFAULT_SCAN_LIMIT
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ScannedThread {
+    pub thread_id: String,
+    /// Model of the latest turn; `None` for a thread without turns
+    pub model: Option<String>,
+    /// Unix seconds
+    pub updated_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct ThreadScan {
+    /// Unix seconds
+    pub scanned_at: i64,
+    /// Most recently active first
+    pub threads: Vec<ScannedThread>,
+    /// Stored threads left out because the scan reached its limit
+    pub skipped: usize,
+}
+
+/// The latest scan, served to the thread listing.
+#[derive(Debug)]
+pub struct ThreadScanCache {
+    limit: usize,
+    scan: RwLock<Option<Arc<ThreadScan>>>,
+}
+
+impl Default for ThreadScanCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_SCAN_LIMIT)
+    }
+}
+
+impl ThreadScanCache {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit: limit.max(1),
+            scan: RwLock::new(None),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// The latest scan; `None` until the first one finishes.
+    pub fn latest(&self) -> Option<Arc<ThreadScan>> {
+        self.scan
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Models found by the latest scan, by thread id.
+    pub fn models(&self) -> HashMap<String, String> {
+        self.latest()
+            .map(|scan| {
+                scan.threads
+                    .iter()
+                    .filter_map(|thread| Some((thread.thread_id.clone(), thread.model.clone()?)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn store(&self, scan: Arc<ThreadScan>) {
+        *self.scan.write().unwrap_or_else(PoisonError::into_inner) = Some(scan);
+    }
+}
+
+/// Scans the most recently active stored threads, up to the cache's limit,
+/// and replaces the cached scan with the result.
+pub async fn scan_threads(state: &WebServerState) -> io::Result<Arc<ThreadScan>> {
+    let limit = state.thread_scan.limit();
+    let mut threads = Vec::new();
+    let skipped;
+    match &state.state_store {
+        Some(store) => {
+            let mut stored: Vec<(ThreadId, i64)> = store
+                .persisted_threads()
+                .await?
+                .into_iter()
+                .map(|(thread_id, (_, updated_at))| (thread_id, updated_at))
+                .collect();
+            stored.sort_by(|a, b| {
+                b.1.cmp(&a.1)
+                    .then_with(|| a.0.to_string().cmp(&b.0.to_string()))
+            });
+            skipped = stored.len().saturating_sub(limit);
+            stored.truncate(limit);
+            let thread_ids: Vec<ThreadId> =
+                stored.iter().map(|(thread_id, _)| *thread_id).collect();
+            let mut models = store.latest_turn_models(&thread_ids).await?;
+            for (thread_id, updated_at) in stored {
+                threads.push(ScannedThread {
+                    thread_id: thread_id.to_string(),
+                    model: models.remove(&thread_id),
+                    updated_at: Some(updated_at),
+                });
+            }
+        }
+        None => {
+            let codex_home = state.codex_home.clone();
+            let mut rollouts: Vec<_> =
+                tokio::task::spawn_blocking(move || storage::scan_rollouts(&codex_home, false))
+                    .await
+                    .map_err(io::Error::other)?
+                    .into_iter()
+                    .filter_map(|rollout| {
+                        let updated_at = rollout.modified.and_then(unix_seconds);
+                        Some((rollout.thread_id?, updated_at, rollout.path))
+                    })
+                    .collect();
+            rollouts.sort_by(|a, b| {
+                b.1.cmp(&a.1)
+                    .then_with(|| a.0.to_string().cmp(&b.0.to_string()))
+            });
+            skipped = rollouts.len().saturating_sub(limit);
+            rollouts.truncate(limit);
+            for (thread_id, updated_at, path) in rollouts {
+                let model = match read_rollout_tail(&path).await {
+                    Ok(tail) => tail.model,
+                    Err(err) => {
+                        tracing::warn!("Failed to read rollout {}: {err}", path.display());
+                        None
+                    }
+                };
+                threads.push(ScannedThread {
+                    thread_id: thread_id.to_string(),
+                    model,
+                    updated_at,
+                });
+            }
+        }
+    }
+
+    let scan = Arc::new(ThreadScan {
+        scanned_at: unix_seconds(SystemTime::now()).unwrap_or_default(),
+        threads,
+        skipped,
+    });
+    state.thread_scan.store(Arc::clone(&scan));
+    Ok(scan)
+}
+
+fn unix_seconds(time: SystemTime) -> Option<i64> {
+    let duration = time.duration_since(UNIX_EPOCH).ok()?;
+    i64::try_from(duration.as_secs()).ok()
+}
@@ -0,0 +1,297 @@
+//! Thread run status for `thread/status/changed` notifications and the
+//! thread listing.
+//!
+//! Status is derived from what is outstanding on the thread (an active turn,
+//! pending approvals) rather than from the last event seen, so events that
+//! arrive out of order cannot leave a thread stuck: a stale `TurnComplete`
+//! does not end a newer turn, and an approval answered before its request was
+//! observed never counts as pending.
+
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::TurnAbortReason;
+use serde::Serialize;
+use serde_json::Value;
+use serde_json::json;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use tokio::sync::broadcast;
+use utoipa::ToSchema;
+
+use crate::sse_envelope::server_notification;
+
+/// Finished turns and early approval answers remembered per thread to discard
+/// late events about them.
+const RECENT_IDS: usize = 16;
+const CHANGES_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreadRunStatus {
+    /// No turn is running
+    #[default]
+    Idle,
+    Running,
+    /// A running turn is blocked on at least one approval
+    WaitingApproval,
+    /// The last turn was interrupted; no turn is running
+    Interrupted,
+    Archived,
+}
+
+/// Something that can move a thread to another status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatusTransition {
+    TurnStarted {
+        turn_id: String,
+    },
+    ApprovalRequested {
+        approval_id: String,
+    },
+    ApprovalResolved {
+        approval_id: String,
+    },
+    TurnFinished {
+        turn_id: String,
+        interrupted: bool,
+    },
+    Archived,
+    /// Resumed after being archived
+    Restored,
+}
+
+impl StatusTransition {
+    /// The transition an event stream event implies, if any. Approval
+    /// resolution is not an event; it is applied where the answer is
+    /// submitted.
+    pub fn from_event(event: &Event) -> Option<Self> {
+        match &event.msg {
+            EventMsg::TurnStarted(ev) => Some(Self::TurnStarted {
+                turn_id: ev.turn_id.clone(),
+            }),
+            EventMsg::ExecApprovalRequest(ev) => Some(Self::ApprovalRequested {
+                approval_id: ev.effective_approval_id(),
+            }),
+            EventMsg::ApplyPatchApprovalRequest(ev) => Some(Self::ApprovalRequested {
+                approval_id: ev.call_id.clone(),
+            }),
+            EventMsg::TurnComplete(ev) => Some(Self::TurnFinished {
+                turn_id: ev.turn_id.clone(),
+                interrupted: false,
+            }),
+            EventMsg::TurnAborted(ev) => Some(Self::TurnFinished {
+                turn_id: ev.turn_id.clone().unwrap_or_else(|| event.id.clone()),
+                interrupted: ev.reason == TurnAbortReason::Interrupted,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Run status bookkeeping for one thread.
+#[derive(Debug, Default)]
+pub struct ThreadStatusTracker {
+    active_turn: Option<String>,
+    pending_approvals: HashSet<String>,
+    finished_turns: VecDeque<String>,
+    early_resolutions: VecDeque<String>,
+    interrupted: bool,
+    archived: bool,
+}
+
+impl ThreadStatusTracker {
+    pub fn status(&self) -> ThreadRunStatus {
+        if self.archived {
+            ThreadRunStatus::Archived
+        } else if !self.pending_approvals.is_empty() {
+            ThreadRunStatus::WaitingApproval
+        } else if self.active_turn.is_some() {
+            ThreadRunStatus::Running
+        } else if self.interrupted {
+            ThreadRunStatus::Interrupted
+        } else {
+            ThreadRunStatus::Idle
+        }
+    }
+
+    /// Id of the running turn, if any.
+    pub fn active_turn(&self) -> Option<&str> {
+        self.active_turn.as_deref()
+    }
+
+    /// Applies a transition and returns the new status when it changed.
+    pub fn apply(&mut self, transition: StatusTransition) -> Option<ThreadRunStatus> {
+        let before = self.status();
+        match transition {
+            StatusTransition::TurnStarted { turn_id } => {
+                if !self.finished_turns.contains(&turn_id) {
+                    self.active_turn = Some(turn_id);
+                    self.interrupted = false;
+                }
+            }
+            StatusTransition::ApprovalRequested { approval_id } => {
+                match self
+                    .early_resolutions
+                    .iter()
+                    .position(|id| *id == approval_id)
+                {
+                    Some(index) => {
+                        self.early_resolutions.remove(index);
+                    }
+                    None => {
+                        self.pending_approvals.insert(approval_id);
+                    }
+                }
+            }
+            StatusTransition::ApprovalResolved { approval_id } => {
+                if !self.pending_approvals.remove(&approval_id) {
+                    remember(&mut self.early_resolutions, approval_id);
+                }
+            }
+            StatusTransition::TurnFinished {
+                turn_id,
+                interrupted,
+            } => {
+                // A newer turn already started; this completion is stale.
+                let stale = self
+                    .active_turn
+                    .as_ref()
+                    .is_some_and(|active| *active != turn_id);
+                if !stale && !self.finished_turns.contains(&turn_id) {
+                    self.active_turn = None;
+                    self.pending_approvals.clear();
+                    self.interrupted = interrupted;
+                }
+                remember(&mut self.finished_turns, turn_id);
+            }
+            StatusTransition::Archived => self.archived = true,
+            StatusTransition::Restored => self.archived = false,
+        }
+        let after = self.status();
+        (before != after).then_some(after)
+    }
+}
+
+fn remember(ids: &mut VecDeque<String>, id: String) {
+    if ids.contains(&id) {
+        return;
+    }
+    if ids.len() == RECENT_IDS {
+        ids.pop_front();
+    }
+    ids.push_back(id);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThreadStatusChange {
+    pub thread_id: ThreadId,
+    pub status: ThreadRunStatus,
+}
+
+impl ThreadStatusChange {
+    pub const EVENT_TYPE: &str = "thread/status/changed";
+
+    pub fn payload(&self) -> Value {
+        server_notification(
+            Self::EVENT_TYPE,
+            json!({
+                "threadId": self.thread_id.to_string(),
+                "status": self.status,
+            }),
+        )
+    }
+}
+
+/// Status of every thread the server has seen, with changes broadcast to all
+/// event streams.
+pub struct ThreadStatusRegistry {
+    threads: Mutex<HashMap<ThreadId, ThreadStatusTracker>>,
+    changes: broadcast::Sender<ThreadStatusChange>,
+}
+
+impl Default for ThreadStatusRegistry {
+    fn default() -> Self {
+        let (changes, _) = broadcast::channel(CHANGES_CAPACITY);
+        Self {
+            threads: Mutex::new(HashMap::new()),
+            changes,
+        }
+    }
+}
+
+impl ThreadStatusRegistry {
+    pub fn status(&self, thread_id: ThreadId) -> ThreadRunStatus {
+        self.threads
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&thread_id)
+            .map(ThreadStatusTracker::status)
+            .unwrap_or_default()
+    }
+
+    /// Id of the thread's running turn, as far as the server has seen.
+    pub fn active_turn(&self, thread_id: ThreadId) -> Option<String> {
+        self.threads
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&thread_id)
+            .and_then(|tracker| tracker.active_turn().map(str::to_string))
+    }
+
+    /// Threads with a running turn, each with the turn's id as far as the
+    /// server has seen.
+    pub fn running(&self) -> Vec<(ThreadId, Option<String>)> {
+        self.threads
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .filter(|(_, tracker)| {
+                matches!(
+                    tracker.status(),
+                    ThreadRunStatus::Running | ThreadRunStatus::WaitingApproval
+                )
+            })
+            .map(|(thread_id, tracker)| (*thread_id, tracker.active_turn().map(str::to_string)))
+            .collect()
+    }
+
+    /// Applies a transition and broadcasts the new status when it changed.
+    pub fn apply(
+        &self,
+        thread_id: ThreadId,
+        transition: StatusTransition,
+    ) -> Option<ThreadRunStatus> {
+        let status = self
+            .threads
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(thread_id)
+            .or_default()
+            .apply(transition)?;
+        // No receivers just means no stream is attached.
+        let _ = self.changes.send(ThreadStatusChange { thread_id, status });
+        Some(status)
+    }
+
+    /// Drops everything known about a deleted thread.
+    pub fn forget(&self, thread_id: ThreadId) {
+        self.threads
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&thread_id);
+    }
+
+    pub fn observe(&self, thread_id: ThreadId, event: &Event) {
+        if let Some(transition) = StatusTransition::from_event(event) {
+            self.apply(thread_id, transition);
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ThreadStatusChange> {
+        self.changes.subscribe()
+    }
+}
@@ -0,0 +1,200 @@
+//! Attachments each turn was sent with, for the item history.
+//!
+//! A rollout keeps only the local path of an attached image, which says
+//! nothing once the file is gone. When input is submitted, the metadata of
+//! each attachment in it is appended to a journal per thread in
+//! `codex_home/web_turn_attachments`, keyed by turn id, so the history can
+//! list the attachments of a user message and show deleted ones as
+//! tombstones.
+
+use codex_app_server_protocol::UserInput as HistoryInput;
+use codex_protocol::ThreadId;
+use codex_protocol::user_input::UserInput;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use utoipa::ToSchema;
+
+use crate::attachments::AttachmentMetadata;
+use crate::attachments::AttachmentScope;
+use crate::attachments::resolve_attachment;
+use crate::state::WebServerState;
+
+const JOURNAL_DIR: &str = "web_turn_attachments";
+
+/// One attachment of a turn, as appended to the journal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TurnAttachmentRecord {
+    pub turn_id: String,
+    pub attachment_id: String,
+    pub filename: String,
+    pub size: u64,
+    /// Where the file was stored when the turn was submitted
+    pub path: PathBuf,
+}
+
+/// An attachment of a user message in the item history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct HistoryAttachment {
+    #[schema(example = "019bcfb9-4ea6-72e0-b43d-6b7e26ff0daf")]
+    pub attachment_id: String,
+    #[schema(example = "image.png")]
+    pub filename: String,
+    pub size: u64,
+    /// Download path; `None` once the file is deleted, or when the caller's
+    /// namespace may not read it
+    #[schema(example = "/api/v1/attachments/019bcfb9-4ea6-72e0-b43d-6b7e26ff0daf")]
+    pub download_url: Option<String>,
+    /// The file no longer exists; only the recorded metadata is left
+    pub deleted: bool,
+}
+
+fn journal_path(codex_home: &Path, thread_id: ThreadId) -> PathBuf {
+    codex_home
+        .join(JOURNAL_DIR)
+        .join(format!("{thread_id}.jsonl"))
+}
+
+/// Attachments among `items`: local images that have an upload's metadata
+/// file next to them.
+pub async fn attachments_in(items: &[UserInput]) -> Vec<(PathBuf, AttachmentMetadata)> {
+    let mut attachments = Vec::new();
+    for item in items {
+        let UserInput::LocalImage { path } = item else {
+            continue;
+        };
+        let Some(id) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if let Ok(json) = fs::read_to_string(path.with_file_name(format!("{id}.json"))).await
+            && let Ok(metadata) = serde_json::from_str::<AttachmentMetadata>(&json)
+            && metadata.id == id
+        {
+            attachments.push((path.clone(), metadata));
+        }
+    }
+    attachments
+}
+
+/// Appends the attachments of input that became part of `turn_id`.
+pub async fn record(
+    codex_home: &Path,
+    thread_id: ThreadId,
+    turn_id: &str,
+    attachments: Vec<(PathBuf, AttachmentMetadata)>,
+) -> std::io::Result<()> {
+    if attachments.is_empty() {
+        return Ok(());
+    }
+    let mut lines = String::new();
+    for (path, metadata) in attachments {
+        let record = TurnAttachmentRecord {
+            turn_id: turn_id.to_string(),
+            attachment_id: metadata.id,
+            filename: metadata.filename,
+            size: metadata.size,
+            path,
+        };
+        lines.push_str(&serde_json::to_string(&record).map_err(std::io::Error::other)?);
+        lines.push('\n');
+    }
+    let path = journal_path(codex_home, thread_id);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).await?;
+    }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .await?;
+    file.write_all(lines.as_bytes()).await?;
+    file.flush().await
+}
+
+/// Recorded attachments of a thread by turn id. Lines that do not parse are
+/// skipped.
+pub async fn load(
+    codex_home: &Path,
+    thread_id: ThreadId,
+) -> std::io::Result<HashMap<String, Vec<TurnAttachmentRecord>>> {
+    let contents = match fs::read_to_string(journal_path(codex_home, thread_id)).await {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+    let mut by_turn: HashMap<String, Vec<TurnAttachmentRecord>> = HashMap::new();
+    for record in contents
+        .lines()
+        .filter_map(|line| serde_json::from_str::<TurnAttachmentRecord>(line).ok())
+    {
+        by_turn
+            .entry(record.turn_id.clone())
+            .or_default()
+            .push(record);
+    }
+    Ok(by_turn)
+}
+
+/// Threads that have a journal.
+pub async fn journaled_threads(codex_home: &Path) -> Vec<ThreadId> {
+    let mut threads = Vec::new();
+    let Ok(mut entries) = fs::read_dir(codex_home.join(JOURNAL_DIR)).await else {
+        return threads;
+    };
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if let Some(thread_id) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.strip_suffix(".jsonl"))
+            .and_then(|id| ThreadId::from_string(id).ok())
+        {
+            threads.push(thread_id);
+        }
+    }
+    threads
+}
+
+/// Drops the journal of a deleted thread.
+pub async fn forget(codex_home: &Path, thread_id: ThreadId) -> std::io::Result<()> {
+    match fs::remove_file(journal_path(codex_home, thread_id)).await {
+        Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+/// The attachments of a user message with `content`, among those recorded
+/// for its turn. Download links are offered only where `scope` passes the
+/// same ownership checks as the download endpoint.
+pub async fn history_attachments(
+    state: &WebServerState,
+    scope: &AttachmentScope,
+    records: &[TurnAttachmentRecord],
+    content: &[HistoryInput],
+) -> Vec<HistoryAttachment> {
+    let mut attachments = Vec::new();
+    for input in content {
+        let HistoryInput::LocalImage { path } = input else {
+            continue;
+        };
+        let Some(record) = records.iter().find(|record| &record.path == path) else {
+            continue;
+        };
+        let deleted = !fs::try_exists(&record.path).await.unwrap_or(false);
+        let readable = !deleted
+            && resolve_attachment(state, scope, &record.attachment_id)
+                .await
+                .is_ok();
+        attachments.push(HistoryAttachment {
+            attachment_id: record.attachment_id.clone(),
+            filename: record.filename.clone(),
+            size: record.size,
+            download_url: readable.then(|| format!("/api/v1/attachments/{}", record.attachment_id)),
+            deleted,
+        });
+    }
+    attachments
+}
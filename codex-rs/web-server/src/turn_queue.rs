@@ -0,0 +1,250 @@
+//! Inputs held until a thread's running turn finishes.
+//!
+//! `POST /api/v2/threads/{id}/turns?queue=true` on a busy thread parks the
+//! input here instead of steering it into the running turn. The event stream
+//! that sees the turn finish submits the oldest entry. Every change is
+//! broadcast as `turn/queued` or `turn/dequeued` so all clients of a thread
+//! see the same queue.
+
+use codex_protocol::ThreadId;
+use codex_protocol::user_input::UserInput;
+use serde::Serialize;
+use serde_json::Value;
+use serde_json::json;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+use tokio::sync::broadcast;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::handlers::turns::TurnOverrides;
+use crate::handlers::turns::UserInputItem;
+use crate::sse_envelope::server_notification;
+
+const CHANGES_CAPACITY: usize = 256;
+
+/// A queued input as clients see it.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct QueueEntry {
+    pub queue_entry_id: String,
+    /// Unix seconds
+    pub enqueued_at: i64,
+    /// The input as it was submitted
+    pub input: Vec<UserInputItem>,
+}
+
+/// A queued input with the items core will receive.
+#[derive(Debug, Clone)]
+pub struct QueuedInput {
+    pub entry: QueueEntry,
+    pub items: Vec<UserInput>,
+    /// Applied when the input starts its turn
+    pub overrides: TurnOverrides,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DequeueReason {
+    /// Submitted as a new turn
+    Started,
+    /// Removed with `DELETE /api/v2/threads/{id}/queue/{entry_id}`
+    Cancelled,
+    /// Submitting it failed; the input was dropped
+    Failed,
+}
+
+#[derive(Debug, Clone)]
+pub enum QueueChange {
+    Queued {
+        thread_id: ThreadId,
+        entry: QueueEntry,
+    },
+    Dequeued {
+        thread_id: ThreadId,
+        queue_entry_id: String,
+        reason: DequeueReason,
+        /// Turn the input started, for `started`
+        turn_id: Option<String>,
+    },
+}
+
+impl QueueChange {
+    pub const QUEUED_EVENT_TYPE: &str = "turn/queued";
+    pub const DEQUEUED_EVENT_TYPE: &str = "turn/dequeued";
+
+    pub fn thread_id(&self) -> ThreadId {
+        match self {
+            QueueChange::Queued { thread_id, .. } | QueueChange::Dequeued { thread_id, .. } => {
+                *thread_id
+            }
+        }
+    }
+
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            QueueChange::Queued { .. } => Self::QUEUED_EVENT_TYPE,
+            QueueChange::Dequeued { .. } => Self::DEQUEUED_EVENT_TYPE,
+        }
+    }
+
+    pub fn payload(&self) -> Value {
+        let params = match self {
+            QueueChange::Queued { thread_id, entry } => json!({
+                "threadId": thread_id.to_string(),
+                "entry": entry,
+            }),
+            QueueChange::Dequeued {
+                thread_id,
+                queue_entry_id,
+                reason,
+                turn_id,
+            } => json!({
+                "threadId": thread_id.to_string(),
+                "queueEntryId": queue_entry_id,
+                "reason": reason,
+                "turnId": turn_id,
+            }),
+        };
+        server_notification(self.event_type(), params)
+    }
+}
+
+/// Per-thread FIFO of queued inputs, with changes broadcast to all event
+/// streams.
+pub struct TurnQueue {
+    queues: Mutex<HashMap<ThreadId, VecDeque<QueuedInput>>>,
+    changes: broadcast::Sender<QueueChange>,
+}
+
+impl Default for TurnQueue {
+    fn default() -> Self {
+        let (changes, _) = broadcast::channel(CHANGES_CAPACITY);
+        Self {
+            queues: Mutex::new(HashMap::new()),
+            changes,
+        }
+    }
+}
+
+impl TurnQueue {
+    /// Appends an input to the thread's queue.
+    pub fn enqueue(
+        &self,
+        thread_id: ThreadId,
+        input: Vec<UserInputItem>,
+        items: Vec<UserInput>,
+        overrides: TurnOverrides,
+    ) -> QueueEntry {
+        let enqueued_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or_default();
+        let entry = QueueEntry {
+            queue_entry_id: Uuid::new_v4().to_string(),
+            enqueued_at,
+            input,
+        };
+        self.queues
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(thread_id)
+            .or_default()
+            .push_back(QueuedInput {
+                entry: entry.clone(),
+                items,
+                overrides,
+            });
+        self.broadcast(QueueChange::Queued {
+            thread_id,
+            entry: entry.clone(),
+        });
+        entry
+    }
+
+    /// Queued inputs, oldest first.
+    pub fn list(&self, thread_id: ThreadId) -> Vec<QueueEntry> {
+        self.queues
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&thread_id)
+            .map(|queue| queue.iter().map(|queued| queued.entry.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Local images of every queued input, of all threads.
+    pub fn queued_images(&self) -> Vec<PathBuf> {
+        self.queues
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .flatten()
+            .flat_map(|queued| &queued.items)
+            .filter_map(|item| match item {
+                UserInput::LocalImage { path } => Some(path.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Drops an input before it runs.
+    pub fn cancel(&self, thread_id: ThreadId, queue_entry_id: &str) -> Option<QueueEntry> {
+        let removed = {
+            let mut queues = self.queues.lock().unwrap_or_else(PoisonError::into_inner);
+            let queue = queues.get_mut(&thread_id)?;
+            let index = queue
+                .iter()
+                .position(|queued| queued.entry.queue_entry_id == queue_entry_id)?;
+            queue.remove(index)?
+        };
+        self.finish(thread_id, &removed.entry, DequeueReason::Cancelled, None);
+        Some(removed.entry)
+    }
+
+    /// Takes the oldest input. The caller reports the outcome with
+    /// [`Self::finish`].
+    pub fn pop(&self, thread_id: ThreadId) -> Option<QueuedInput> {
+        self.queues
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get_mut(&thread_id)?
+            .pop_front()
+    }
+
+    /// Announces that `entry` left the queue.
+    pub fn finish(
+        &self,
+        thread_id: ThreadId,
+        entry: &QueueEntry,
+        reason: DequeueReason,
+        turn_id: Option<String>,
+    ) {
+        self.broadcast(QueueChange::Dequeued {
+            thread_id,
+            queue_entry_id: entry.queue_entry_id.clone(),
+            reason,
+            turn_id,
+        });
+    }
+
+    /// Forgets a thread's queue, e.g. when the thread is unloaded.
+    pub fn clear(&self, thread_id: ThreadId) {
+        self.queues
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&thread_id);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<QueueChange> {
+        self.changes.subscribe()
+    }
+
+    fn broadcast(&self, change: QueueChange) {
+        // No receivers just means no stream is attached.
+        let _ = self.changes.send(change);
+    }
+}
@@ -0,0 +1,273 @@
+//! Token usage reporting across threads.
+//!
+//! Each model response reports cumulative usage in a `TokenCount` event. A
+//! response counts once: repeated events with an unchanged total, such as
+//! rate-limit refreshes, are skipped. Samples come from rollout files, or from
+//! the `codex_web_usage` table that the event stream fills when Postgres is
+//! configured. Costs are estimated from `CODEX_WEB_MODEL_PRICING`, a JSON
+//! object of USD prices per million tokens keyed by model, e.g.
+//! `{"gpt-5.1-codex": {"input": 1.25, "cached_input": 0.125, "output": 10.0}}`.
+
+use chrono::DateTime;
+use chrono::Utc;
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use codex_protocol::protocol::TokenUsage;
+use codex_protocol::protocol::TokenUsageInfo;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use utoipa::ToSchema;
+
+use crate::context_window::ContextWindowUsage;
+
+pub const MODEL_PRICING_ENV: &str = "CODEX_WEB_MODEL_PRICING";
+
+const TOKENS_PER_PRICE_UNIT: f64 = 1_000_000.0;
+
+/// USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    /// Defaults to `input`
+    #[serde(default)]
+    pub cached_input: Option<f64>,
+    pub output: f64,
+}
+
+/// Prices by model id; empty when pricing is not configured.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ModelPricing {
+    prices: HashMap<String, ModelPrice>,
+}
+
+impl ModelPricing {
+    pub fn new(prices: HashMap<String, ModelPrice>) -> Self {
+        Self { prices }
+    }
+
+    pub fn from_env() -> Self {
+        let Ok(value) = std::env::var(MODEL_PRICING_ENV) else {
+            return Self::default();
+        };
+        match serde_json::from_str(&value) {
+            Ok(prices) => Self::new(prices),
+            Err(err) => {
+                tracing::warn!("Ignoring invalid {MODEL_PRICING_ENV}: {err}");
+                Self::default()
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prices.is_empty()
+    }
+
+    /// Estimated USD cost, or `None` when `model` has no price.
+    pub fn estimate(&self, model: &str, usage: &TokenUsage) -> Option<f64> {
+        let price = self.prices.get(model)?;
+        let cached_price = price.cached_input.unwrap_or(price.input);
+        Some(
+            (usage.non_cached_input() as f64 * price.input
+                + usage.cached_input() as f64 * cached_price
+                + usage.output_tokens.max(0) as f64 * price.output)
+                / TOKENS_PER_PRICE_UNIT,
+        )
+    }
+}
+
+/// Usage of one model response.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageSample {
+    pub thread_id: ThreadId,
+    /// Unix seconds
+    pub recorded_at: i64,
+    /// `None` when the rollout recorded no turn context before the response
+    pub model: Option<String>,
+    pub usage: TokenUsage,
+}
+
+/// The usage to record for a `TokenCount` event, given the cumulative total
+/// of the thread's previous one. `None` when the total has not moved.
+pub fn usage_delta(
+    previous_total: Option<&TokenUsage>,
+    info: &TokenUsageInfo,
+) -> Option<TokenUsage> {
+    if previous_total == Some(&info.total_token_usage) || info.last_token_usage.is_zero() {
+        return None;
+    }
+    Some(info.last_token_usage.clone())
+}
+
+/// Samples from the JSONL contents of a rollout file. Lines that do not
+/// parse are skipped.
+pub fn samples_from_rollout(thread_id: ThreadId, contents: &str) -> Vec<UsageSample> {
+    let mut samples = Vec::new();
+    let mut model = None;
+    let mut previous_total = None;
+    for line in contents.lines() {
+        let Ok(line) = serde_json::from_str::<RolloutLine>(line) else {
+            continue;
+        };
+        match line.item {
+            RolloutItem::TurnContext(context) => model = Some(context.model),
+            RolloutItem::EventMsg(EventMsg::TokenCount(event)) => {
+                let Some(info) = event.info else {
+                    continue;
+                };
+                if let Some(usage) = usage_delta(previous_total.as_ref(), &info)
+                    && let Ok(recorded_at) = DateTime::parse_from_rfc3339(&line.timestamp)
+                {
+                    samples.push(UsageSample {
+                        thread_id,
+                        recorded_at: recorded_at.timestamp(),
+                        model: model.clone(),
+                        usage,
+                    });
+                }
+                previous_total = Some(info.total_token_usage);
+            }
+            _ => {}
+        }
+    }
+    samples
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageGroupBy {
+    /// UTC calendar day, `YYYY-MM-DD`
+    #[default]
+    Day,
+    Model,
+    Thread,
+}
+
+impl UsageGroupBy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Day => "day",
+            Self::Model => "model",
+            Self::Thread => "thread",
+        }
+    }
+
+    fn key(self, sample: &UsageSample) -> String {
+        match self {
+            Self::Day => DateTime::<Utc>::from_timestamp(sample.recorded_at, 0)
+                .map(|time| time.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+            Self::Model => sample.model.clone().unwrap_or_default(),
+            Self::Thread => sample.thread_id.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ToSchema)]
+pub struct UsageTotals {
+    pub input_tokens: i64,
+    pub cached_input_tokens: i64,
+    pub output_tokens: i64,
+    pub reasoning_output_tokens: i64,
+    pub total_tokens: i64,
+    /// USD; `None` when pricing is not configured or a response used a model
+    /// without a price
+    pub estimated_cost_usd: Option<f64>,
+}
+
+impl UsageTotals {
+    fn new(pricing: &ModelPricing) -> Self {
+        Self {
+            estimated_cost_usd: (!pricing.is_empty()).then_some(0.0),
+            ..Self::default()
+        }
+    }
+
+    fn add(&mut self, sample: &UsageSample, pricing: &ModelPricing) {
+        self.input_tokens += sample.usage.input_tokens;
+        self.cached_input_tokens += sample.usage.cached_input_tokens;
+        self.output_tokens += sample.usage.output_tokens;
+        self.reasoning_output_tokens += sample.usage.reasoning_output_tokens;
+        self.total_tokens += sample.usage.total_tokens;
+        let cost = sample
+            .model
+            .as_deref()
+            .and_then(|model| pricing.estimate(model, &sample.usage));
+        self.estimated_cost_usd = self.estimated_cost_usd.zip(cost).map(|(a, b)| a + b);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct UsageGroup {
+    /// Day, model or thread id, per `group_by`. Empty for responses whose
+    /// model is unknown
+    pub key: String,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+    /// With `group_by=thread`, how full a loaded thread's context window is
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<ContextWindowUsage>,
+}
+
+/// Groups `samples` by `group_by`, ordered by key, plus the overall totals.
+pub fn aggregate(
+    samples: &[UsageSample],
+    group_by: UsageGroupBy,
+    pricing: &ModelPricing,
+) -> (Vec<UsageGroup>, UsageTotals) {
+    let mut groups: BTreeMap<String, UsageTotals> = BTreeMap::new();
+    let mut total = UsageTotals::new(pricing);
+    for sample in samples {
+        total.add(sample, pricing);
+        groups
+            .entry(group_by.key(sample))
+            .or_insert_with(|| UsageTotals::new(pricing))
+            .add(sample, pricing);
+    }
+    let groups = groups
+        .into_iter()
+        .map(|(key, totals)| UsageGroup {
+            key,
+            totals,
+            context: None,
+        })
+        .collect();
+    (groups, total)
+}
+
+/// Renders `groups` as CSV with a header row; the first column is named
+/// after `group_by`.
+pub fn to_csv(groups: &[UsageGroup], group_by: UsageGroupBy) -> String {
+    let mut csv = format!(
+        "{},input_tokens,cached_input_tokens,output_tokens,reasoning_output_tokens,total_tokens,estimated_cost_usd\n",
+        group_by.as_str()
+    );
+    for group in groups {
+        let totals = &group.totals;
+        let cost = totals
+            .estimated_cost_usd
+            .map(|cost| format!("{cost:.6}"))
+            .unwrap_or_default();
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{cost}\n",
+            csv_field(&group.key),
+            totals.input_tokens,
+            totals.cached_input_tokens,
+            totals.output_tokens,
+            totals.reasoning_output_tokens,
+            totals.total_tokens,
+        ));
+    }
+    csv
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
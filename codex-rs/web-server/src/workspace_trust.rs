@@ -0,0 +1,99 @@
+//! Directories threads and commands may use as their working directory.
+//!
+//! Hosted deployments list trusted roots in `[web_server] trusted_roots` in
+//! `config.toml`; a cwd is accepted only when its canonical path (symlinks
+//! resolved) lies under one of them. Without roots every cwd is accepted, as
+//! before.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+use crate::error::ApiError;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrustedRoots {
+    roots: Vec<PathBuf>,
+}
+
+impl TrustedRoots {
+    /// Canonicalizes `roots` so symlinked roots compare against canonical
+    /// cwds. A root that cannot be resolved is kept as written; it only
+    /// matches once it exists.
+    pub fn new(roots: impl IntoIterator<Item = PathBuf>) -> Self {
+        let roots = roots
+            .into_iter()
+            .filter(|root| !root.as_os_str().is_empty())
+            .map(|root| match root.canonicalize() {
+                Ok(canonical) => canonical,
+                Err(err) => {
+                    tracing::warn!("Trusted root {} cannot be resolved: {err}", root.display());
+                    root
+                }
+            })
+            .collect();
+        Self { roots }
+    }
+
+    /// Reads the roots from `config.toml` under `codex_home`. Unrestricted
+    /// when the file or the key is absent.
+    pub fn load(codex_home: &Path) -> anyhow::Result<Self> {
+        let path = codex_home.join("config.toml");
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => {
+                Self::parse(&contents).with_context(|| format!("Invalid {}", path.display()))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err).with_context(|| format!("Failed to read {}", path.display())),
+        }
+    }
+
+    pub fn parse(config_toml: &str) -> anyhow::Result<Self> {
+        let mut root: toml::Table = toml::from_str(config_toml)?;
+        let Some(toml::Value::Table(mut web_server)) = root.remove("web_server") else {
+            return Ok(Self::default());
+        };
+        let Some(roots) = web_server.remove("trusted_roots") else {
+            return Ok(Self::default());
+        };
+        let roots: Vec<PathBuf> = roots
+            .try_into()
+            .context("Invalid [web_server] trusted_roots")?;
+        Ok(Self::new(roots))
+    }
+
+    /// `true` when no roots are configured and every cwd is accepted.
+    pub fn is_unrestricted(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    pub fn roots(&self) -> Vec<String> {
+        self.roots
+            .iter()
+            .map(|root| root.display().to_string())
+            .collect()
+    }
+
+    /// Returns the cwd to use: `cwd` unchanged when unrestricted, otherwise
+    /// its canonical form if that lies under a trusted root.
+    ///
+    /// A cwd that cannot be resolved is rejected the same way as one outside
+    /// the roots, so the response does not reveal which paths exist.
+    pub fn check(&self, cwd: &Path) -> Result<PathBuf, ApiError> {
+        if self.is_unrestricted() {
+            return Ok(cwd.to_path_buf());
+        }
+        // `Path::starts_with` compares whole components, so `/repo-evil` is
+        // not under `/repo`.
+        match cwd.canonicalize() {
+            Ok(canonical) if self.roots.iter().any(|root| canonical.starts_with(root)) => {
+                Ok(canonical)
+            }
+            _ => Err(ApiError::UntrustedCwd {
+                cwd: cwd.display().to_string(),
+                trusted_roots: self.roots(),
+            }),
+        }
+    }
+}
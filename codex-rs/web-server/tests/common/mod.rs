@@ -1,6 +1,28 @@
+use anyhow::Context;
 use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::BodyDataStream;
+use axum::http::Request;
+use axum::http::StatusCode;
+use codex_core::ThreadManager;
+use codex_core::auth::AuthCredentialsStoreMode;
+use codex_core::auth::AuthManager;
+use codex_core::config::ConfigBuilder;
+use codex_core::config::service::ConfigService;
+use codex_core::config_loader::CloudRequirementsLoader;
+use codex_feedback::CodexFeedback;
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::SessionSource;
+use codex_web_server::handlers::threads::start_configured_thread;
+use codex_web_server::state::WebServerState;
+use futures::StreamExt;
+use serde_json::Value;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 use tempfile::TempDir;
+use tower::ServiceExt;
 
 /// Test fixture containing temporary directories
 pub struct TestFixture {
@@ -56,3 +78,183 @@ model = "test-model"
 approval_policy = "never"
 sandbox_mode = "read-only"
 "#;
+
+/// Build a [`WebServerState`] rooted at the fixture's temp directories.
+pub fn create_test_state(fixture: &TestFixture, auth_token: &str) -> WebServerState {
+    let codex_home = fixture.codex_home_path();
+    let auth_manager =
+        AuthManager::shared(codex_home.clone(), false, AuthCredentialsStoreMode::File);
+    let config_service = Arc::new(ConfigService::new(
+        codex_home.clone(),
+        vec![],
+        Default::default(),
+        CloudRequirementsLoader::default(),
+    ));
+    let thread_manager = Arc::new(ThreadManager::new(
+        codex_home.clone(),
+        auth_manager.clone(),
+        SessionSource::VSCode,
+    ));
+
+    WebServerState::new(
+        thread_manager,
+        auth_manager,
+        config_service,
+        codex_home,
+        fixture.attachments_path(),
+        auth_token.to_string(),
+        CodexFeedback::new(),
+    )
+}
+
+/// Starts a thread on `state` whose model is the mock Responses API at
+/// `server_uri`.
+pub async fn start_mock_provider_thread(
+    fixture: &TestFixture,
+    state: &WebServerState,
+    server_uri: &str,
+) -> Result<ThreadId> {
+    start_mock_provider_thread_with(fixture, state, server_uri, "").await
+}
+
+/// [`start_mock_provider_thread`] with `extra_config` added to the top level
+/// of `config.toml`.
+pub async fn start_mock_provider_thread_with(
+    fixture: &TestFixture,
+    state: &WebServerState,
+    server_uri: &str,
+    extra_config: &str,
+) -> Result<ThreadId> {
+    fixture.create_test_config(&format!(
+        r#"
+{extra_config}
+model = "test-model"
+approval_policy = "never"
+sandbox_mode = "read-only"
+model_provider = "mock"
+
+[model_providers.mock]
+name = "Mock provider"
+base_url = "{server_uri}/v1"
+wire_api = "responses"
+request_max_retries = 0
+stream_max_retries = 0
+"#
+    ))?;
+    let mut config = ConfigBuilder::default()
+        .codex_home(fixture.codex_home_path())
+        .build()
+        .await?;
+    config.cwd = fixture.codex_home_path();
+    Ok(start_configured_thread(state, config).await?.thread_id)
+}
+
+/// One SSE frame of a thread's event stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub event_type: String,
+    /// `None` for events sent to one stream only
+    pub id: Option<String>,
+    pub data: Value,
+}
+
+/// Reads SSE frames from a thread's event stream or from `GET /api/v2/events`.
+pub struct EventReader {
+    stream: BodyDataStream,
+    buffer: String,
+}
+
+impl EventReader {
+    pub async fn open(app: &Router, thread_id: ThreadId) -> Result<Self> {
+        Self::connect(app, thread_id, "", None).await
+    }
+
+    /// Opens the stream with `query` (e.g. `?events=turn`) appended to its
+    /// uri.
+    pub async fn open_with_query(app: &Router, thread_id: ThreadId, query: &str) -> Result<Self> {
+        Self::connect(app, thread_id, query, None).await
+    }
+
+    /// Opens the stream the way a client reconnecting after `last_event_id`
+    /// does.
+    pub async fn resume(app: &Router, thread_id: ThreadId, last_event_id: &str) -> Result<Self> {
+        Self::connect(app, thread_id, "", Some(last_event_id)).await
+    }
+
+    /// Opens the stream of every thread's events, `GET /api/v2/events`.
+    pub async fn open_all(app: &Router, query: &str) -> Result<Self> {
+        Self::connect_uri(app, format!("/api/v2/events{query}"), None).await
+    }
+
+    async fn connect(
+        app: &Router,
+        thread_id: ThreadId,
+        query: &str,
+        last_event_id: Option<&str>,
+    ) -> Result<Self> {
+        Self::connect_uri(
+            app,
+            format!("/api/v2/threads/{thread_id}/events{query}"),
+            last_event_id,
+        )
+        .await
+    }
+
+    async fn connect_uri(app: &Router, uri: String, last_event_id: Option<&str>) -> Result<Self> {
+        let mut request = Request::builder().uri(uri);
+        if let Some(last_event_id) = last_event_id {
+            request = request.header("last-event-id", last_event_id);
+        }
+        let response = app.clone().oneshot(request.body(Body::empty())?).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        Ok(Self {
+            stream: response.into_body().into_data_stream(),
+            buffer: String::new(),
+        })
+    }
+
+    /// Returns the next frame that carries data, skipping keepalives.
+    pub async fn next_frame(&mut self) -> Result<Frame> {
+        tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                while let Some(end) = self.buffer.find("\n\n") {
+                    let frame: String = self.buffer.drain(..end + 2).collect();
+                    let field = |name: &str| {
+                        frame
+                            .lines()
+                            .find_map(|line| line.strip_prefix(name))
+                            .map(str::to_string)
+                    };
+                    if let Some(data) = field("data: ") {
+                        return Ok(Frame {
+                            event_type: field("event: ").unwrap_or_default(),
+                            id: field("id: "),
+                            data: serde_json::from_str(&data)?,
+                        });
+                    }
+                }
+                match self.stream.next().await {
+                    Some(chunk) => self.buffer.push_str(&String::from_utf8_lossy(&chunk?)),
+                    None => anyhow::bail!("event stream ended"),
+                }
+            }
+        })
+        .await?
+    }
+
+    /// Returns the data of the next frame whose event type is `event_type`.
+    pub async fn next_of(&mut self, event_type: &str) -> Result<Value> {
+        tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                let frame = self
+                    .next_frame()
+                    .await
+                    .with_context(|| format!("waiting for {event_type}"))?;
+                if frame.event_type == event_type {
+                    return Ok(frame.data);
+                }
+            }
+        })
+        .await?
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::http::response::Parts;
+use axum::middleware::from_fn_with_state;
+use axum::routing::get;
+use axum::routing::post;
+use chrono::NaiveDate;
+use codex_web_server::api_version::ApiVersionPolicy;
+use codex_web_server::api_version::api_version_middleware;
+use codex_web_server::state::WebServerState;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+async fn call(app: &Router, method: &str, uri: &str, pinned: Option<&str>) -> Result<Parts> {
+    let mut request = Request::builder().method(method).uri(uri);
+    if let Some(version) = pinned {
+        request = request.header("x-codex-api-version", version);
+    }
+    let response = app.clone().oneshot(request.body(Body::empty())?).await?;
+    Ok(response.into_parts().0)
+}
+
+fn header<'a>(parts: &'a Parts, name: &str) -> Option<&'a str> {
+    parts
+        .headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+}
+
+fn versioned_router(state: WebServerState) -> Router {
+    Router::new()
+        .route("/health", get(|| async { StatusCode::OK }))
+        .route("/api/v1/threads", post(|| async { StatusCode::OK }))
+        .route(
+            "/api/v1/threads/{id}/turns",
+            post(|| async { StatusCode::OK }),
+        )
+        .route("/api/v2/threads", get(|| async { StatusCode::OK }))
+        .layer(from_fn_with_state(state.clone(), api_version_middleware))
+        .with_state(state)
+}
+
+#[tokio::test]
+async fn test_v1_responses_are_deprecated_and_v2_are_not() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let mut state = create_test_state(&fixture, "test-token");
+    state.api_version_policy = Arc::new(ApiVersionPolicy {
+        v1_sunset: NaiveDate::from_ymd_opt(2026, 12, 31),
+    });
+    let app = versioned_router(state);
+
+    let v1 = call(&app, "POST", "/api/v1/threads", None).await?;
+    assert_eq!(v1.status, StatusCode::OK);
+    assert_eq!(header(&v1, "x-codex-api-version"), Some("1"));
+    assert_eq!(header(&v1, "deprecation"), Some("true"));
+    assert_eq!(header(&v1, "sunset"), Some("Thu, 31 Dec 2026 00:00:00 GMT"));
+
+    let v2 = call(&app, "GET", "/api/v2/threads", None).await?;
+    assert_eq!(v2.status, StatusCode::OK);
+    assert_eq!(header(&v2, "x-codex-api-version"), Some("2"));
+    assert_eq!(header(&v2, "deprecation"), None);
+    assert_eq!(header(&v2, "sunset"), None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sunset_is_omitted_until_configured() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let app = versioned_router(create_test_state(&fixture, "test-token"));
+
+    let v1 = call(&app, "POST", "/api/v1/threads", None).await?;
+    assert_eq!(header(&v1, "deprecation"), Some("true"));
+    assert_eq!(header(&v1, "sunset"), None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pinned_version_applies_only_where_the_path_is_silent() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let app = versioned_router(create_test_state(&fixture, "test-token"));
+
+    let health = call(&app, "GET", "/health", None).await?;
+    assert_eq!(header(&health, "x-codex-api-version"), Some("2"));
+    let health = call(&app, "GET", "/health", Some("1")).await?;
+    assert_eq!(header(&health, "x-codex-api-version"), Some("1"));
+
+    let v2 = call(&app, "GET", "/api/v2/threads", Some("v2")).await?;
+    assert_eq!(v2.status, StatusCode::OK);
+    assert_eq!(header(&v2, "x-codex-api-version"), Some("2"));
+
+    let mismatched = call(&app, "GET", "/api/v2/threads", Some("1")).await?;
+    assert_eq!(mismatched.status, StatusCode::BAD_REQUEST);
+    let unknown = call(&app, "GET", "/health", Some("3")).await?;
+    assert_eq!(unknown.status, StatusCode::BAD_REQUEST);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_v1_usage_is_counted_per_route() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let metrics = state.metrics.clone();
+    let app = versioned_router(state);
+
+    call(&app, "POST", "/api/v1/threads", None).await?;
+    call(&app, "POST", "/api/v1/threads/a/turns", None).await?;
+    call(&app, "POST", "/api/v1/threads/b/turns", None).await?;
+    call(&app, "GET", "/api/v2/threads", None).await?;
+
+    assert_eq!(
+        metrics.snapshot().v1_requests,
+        BTreeMap::from([
+            ("POST /api/v1/threads".to_string(), 1),
+            ("POST /api/v1/threads/{id}/turns".to_string(), 2),
+        ])
+    );
+    Ok(())
+}
+
+#[test]
+fn test_v1_deprecation_notice_names_the_sunset() {
+    let notice = ApiVersionPolicy {
+        v1_sunset: NaiveDate::from_ymd_opt(2026, 12, 31),
+    }
+    .v1_deprecation_notice();
+    assert_eq!(notice.summary, "The v1 API is deprecated");
+    assert!(
+        notice.details.is_some_and(
+            |details| details.starts_with("v1 routes may be removed after 2026-12-31.")
+        )
+    );
+}
@@ -0,0 +1,775 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::AgentMessageEvent;
+use codex_protocol::protocol::ApplyPatchApprovalRequestEvent;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::ExecApprovalRequestEvent;
+use codex_protocol::protocol::ExecCommandBeginEvent;
+use codex_protocol::protocol::ExecCommandSource;
+use codex_protocol::protocol::Op;
+use codex_protocol::protocol::ReviewDecision;
+use codex_protocol::protocol::TurnAbortReason;
+use codex_protocol::protocol::TurnAbortedEvent;
+use codex_protocol::protocol::TurnCompleteEvent;
+use codex_web_server::approval_manager::ApprovalCancelReason;
+use codex_web_server::approval_manager::ApprovalCancelled;
+use codex_web_server::approval_manager::ApprovalErrorCode;
+use codex_web_server::approval_manager::ApprovalExpired;
+use codex_web_server::approval_manager::ApprovalManager;
+use codex_web_server::approval_manager::ApprovalReply;
+use codex_web_server::approval_manager::ApprovalResolutionSource;
+use codex_web_server::approval_manager::ApprovalResolved;
+use codex_web_server::approval_manager::RegisteredApproval;
+use codex_web_server::approval_manager::cancel_orphaned_approvals;
+use codex_web_server::approval_manager::expire_approvals;
+use codex_web_server::approval_manager::register_approval_request;
+use codex_web_server::approval_manager::resolve_external_approvals;
+use codex_web_server::approval_manager::spawn_approval_waiter;
+use codex_web_server::handlers;
+use codex_web_server::state::ApprovalDecision;
+use codex_web_server::state::WebServerState;
+use codex_web_server::thread_status::StatusTransition;
+use codex_web_server::thread_status::ThreadRunStatus;
+use core_test_support::responses::start_mock_server;
+use serde_json::Value;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
+use tower::ServiceExt;
+
+use crate::common::EventReader;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+use crate::common::start_mock_provider_thread;
+
+fn exec_request(call_id: &str) -> EventMsg {
+    EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
+        call_id: call_id.to_string(),
+        approval_id: None,
+        turn_id: "turn-1".to_string(),
+        command: vec!["rm".to_string(), "-rf".to_string(), "build".to_string()],
+        cwd: PathBuf::from("/work"),
+        reason: Some("clean build output".to_string()),
+        network_approval_context: None,
+        proposed_execpolicy_amendment: None,
+        parsed_cmd: vec![],
+    })
+}
+
+fn patch_request() -> EventMsg {
+    EventMsg::ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent {
+        call_id: "patch-1".to_string(),
+        turn_id: "turn-1".to_string(),
+        changes: HashMap::new(),
+        reason: None,
+        grant_root: Some(PathBuf::from("/work")),
+    })
+}
+
+async fn register(
+    state: &WebServerState,
+    thread_id: ThreadId,
+    msg: &EventMsg,
+) -> Result<RegisteredApproval> {
+    register_approval_request(state, thread_id, msg)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("not an approval request"))
+}
+
+/// Runs the waiter until `answer` settles the approval and returns what
+/// `answer` produced along with the ops the waiter submitted.
+async fn wait_for_answer<T>(
+    state: &WebServerState,
+    thread_id: ThreadId,
+    request: RegisteredApproval,
+    answer: impl Future<Output = T>,
+) -> Result<(T, Vec<Op>)> {
+    let submitted = Arc::new(Mutex::new(Vec::new()));
+    let recorder = Arc::clone(&submitted);
+    let waiter = spawn_approval_waiter(
+        request.response,
+        request.reply,
+        move |op| async move {
+            recorder
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(op);
+            Ok(String::new())
+        },
+        thread_id,
+        state.thread_statuses.clone(),
+        None,
+    );
+    let answered = answer.await;
+    waiter.await?;
+    let ops = submitted
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    Ok((answered, ops))
+}
+
+async fn respond(
+    state: &WebServerState,
+    thread_id: ThreadId,
+    approval_id: &str,
+    decision: ApprovalDecision,
+) -> Result<(), ApprovalErrorCode> {
+    ApprovalManager::new(state.pending_approvals.clone())
+        .respond_to_approval(thread_id, approval_id, decision)
+        .await
+}
+
+fn exec_begin(call_id: &str) -> Event {
+    Event {
+        id: "turn-1".to_string(),
+        msg: EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
+            call_id: call_id.to_string(),
+            process_id: None,
+            turn_id: "turn-1".to_string(),
+            command: vec!["rm".to_string(), "-rf".to_string(), "build".to_string()],
+            cwd: PathBuf::from("/work"),
+            parsed_cmd: vec![],
+            source: ExecCommandSource::default(),
+            interaction_input: None,
+        }),
+    }
+}
+
+fn turn_complete(turn_id: &str) -> Event {
+    Event {
+        id: turn_id.to_string(),
+        msg: EventMsg::TurnComplete(TurnCompleteEvent {
+            turn_id: turn_id.to_string(),
+            last_agent_message: None,
+        }),
+    }
+}
+
+fn turn_aborted(turn_id: &str) -> Event {
+    Event {
+        id: turn_id.to_string(),
+        msg: EventMsg::TurnAborted(TurnAbortedEvent {
+            turn_id: Some(turn_id.to_string()),
+            reason: TurnAbortReason::Interrupted,
+        }),
+    }
+}
+
+#[tokio::test]
+async fn test_exec_approval_is_registered_and_approved() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = ThreadId::new();
+    state.thread_statuses.apply(
+        thread_id,
+        StatusTransition::ApprovalRequested {
+            approval_id: "call-1".to_string(),
+        },
+    );
+
+    let request = register(&state, thread_id, &exec_request("call-1")).await?;
+    assert_eq!(request.event_type, "item/commandExecution/requestApproval");
+    assert_eq!(
+        request.reply,
+        ApprovalReply::Exec {
+            approval_id: "call-1".to_string(),
+            turn_id: "turn-1".to_string(),
+        }
+    );
+    let data: Value = serde_json::from_str(&request.data)?;
+    assert_eq!(data["threadId"], json!(thread_id.to_string()));
+    assert_eq!(data["itemId"], json!("call-1"));
+    assert_eq!(data["command"], json!("rm -rf build"));
+    assert_eq!(data["reason"], json!("clean build output"));
+    // Clients get 15 minutes to answer.
+    assert_eq!(
+        state
+            .pending_approvals
+            .lock()
+            .await
+            .get("call-1")
+            .map(|ctx| ctx.timeout),
+        Some(Duration::from_secs(15 * 60))
+    );
+
+    let (answered, ops) = wait_for_answer(
+        &state,
+        thread_id,
+        request,
+        respond(&state, thread_id, "call-1", ApprovalDecision::Approve),
+    )
+    .await?;
+
+    assert_eq!(answered, Ok(()));
+    assert_eq!(
+        ops,
+        vec![Op::ExecApproval {
+            id: "call-1".to_string(),
+            turn_id: Some("turn-1".to_string()),
+            decision: ReviewDecision::Approved,
+        }]
+    );
+    assert!(state.pending_approvals.lock().await.is_empty());
+    assert_eq!(
+        state.thread_statuses.status(thread_id),
+        ThreadRunStatus::Idle
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_approval_for_session_is_submitted_as_such() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = ThreadId::new();
+    let decision: ApprovalDecision = serde_json::from_value(json!("approve_for_session"))?;
+
+    let request = register(&state, thread_id, &exec_request("call-1")).await?;
+    let (answered, ops) = wait_for_answer(
+        &state,
+        thread_id,
+        request,
+        respond(&state, thread_id, "call-1", decision),
+    )
+    .await?;
+
+    assert_eq!(answered, Ok(()));
+    assert_eq!(
+        ops,
+        vec![Op::ExecApproval {
+            id: "call-1".to_string(),
+            turn_id: Some("turn-1".to_string()),
+            decision: ReviewDecision::ApprovedForSession,
+        }]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_patch_approval_is_registered_and_declined() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = ThreadId::new();
+
+    let request = register(&state, thread_id, &patch_request()).await?;
+    assert_eq!(request.event_type, "item/fileChange/requestApproval");
+    let data: Value = serde_json::from_str(&request.data)?;
+    assert_eq!(data["itemId"], json!("patch-1"));
+    assert_eq!(data["turnId"], json!("turn-1"));
+    assert_eq!(data["grantRoot"], json!("/work"));
+    assert_eq!(
+        state
+            .pending_approvals
+            .lock()
+            .await
+            .get("patch-1")
+            .map(|ctx| ctx.timeout),
+        Some(Duration::from_secs(15 * 60))
+    );
+
+    let (answered, ops) = wait_for_answer(
+        &state,
+        thread_id,
+        request,
+        respond(&state, thread_id, "patch-1", ApprovalDecision::Decline),
+    )
+    .await?;
+
+    assert_eq!(answered, Ok(()));
+    assert_eq!(
+        ops,
+        vec![Op::PatchApproval {
+            id: "patch-1".to_string(),
+            decision: ReviewDecision::Denied,
+        }]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_dropped_response_channel_is_denied() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = ThreadId::new();
+
+    let request = register(&state, thread_id, &exec_request("call-1")).await?;
+    // Discarding the context drops the sender without an answer.
+    let discard = async {
+        state.pending_approvals.lock().await.remove("call-1");
+    };
+    let (_, ops) = wait_for_answer(&state, thread_id, request, discard).await?;
+
+    assert_eq!(
+        ops,
+        vec![Op::ExecApproval {
+            id: "call-1".to_string(),
+            turn_id: Some("turn-1".to_string()),
+            decision: ReviewDecision::Denied,
+        }]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_late_and_unawaited_answers_report_codes() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    // Shares the pending approvals, but registers them with no time to answer.
+    let expiring = state.clone().with_approval_timeout(Duration::ZERO);
+    let thread_id = ThreadId::new();
+
+    let _expired = register(&expiring, thread_id, &exec_request("expired")).await?;
+    assert_eq!(
+        respond(&state, thread_id, "expired", ApprovalDecision::Approve).await,
+        Err(ApprovalErrorCode::Timeout)
+    );
+
+    let closed = register(&state, thread_id, &exec_request("closed")).await?;
+    drop(closed.response);
+    assert_eq!(
+        respond(&state, thread_id, "closed", ApprovalDecision::Approve).await,
+        Err(ApprovalErrorCode::ChannelClosed)
+    );
+
+    assert_eq!(
+        respond(&state, thread_id, "unknown", ApprovalDecision::Approve).await,
+        Err(ApprovalErrorCode::NotFound)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_expired_approval_is_denied_and_announced() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token").with_approval_timeout(Duration::ZERO);
+    let thread_id = ThreadId::new();
+    let mut announced = state.approval_expiry.subscribe();
+
+    let request = register(&state, thread_id, &exec_request("call-1")).await?;
+    let (expired, ops) =
+        wait_for_answer(&state, thread_id, request, expire_approvals(&state)).await?;
+
+    let expected = ApprovalExpired {
+        thread_id,
+        turn_id: "turn-1".to_string(),
+        approval_id: "call-1".to_string(),
+    };
+    assert_eq!(expired, vec![expected.clone()]);
+    assert_eq!(announced.try_recv()?, expected);
+    assert_eq!(
+        ops,
+        vec![Op::ExecApproval {
+            id: "call-1".to_string(),
+            turn_id: Some("turn-1".to_string()),
+            decision: ReviewDecision::Denied,
+        }]
+    );
+    assert!(state.pending_approvals.lock().await.is_empty());
+
+    // A late answer is told it was late, not that the approval is unknown.
+    let app = Router::new()
+        .route(
+            "/api/v2/threads/{thread_id}/approvals/{approval_id}",
+            post(handlers::approvals::respond_to_approval),
+        )
+        .with_state(state);
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/v2/threads/{thread_id}/approvals/call-1"))
+                .header("content-type", "application/json")
+                .body(Body::from(json!({"decision": "approve"}).to_string()))?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::REQUEST_TIMEOUT);
+    let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+    assert_eq!(body["code"], json!("timeout"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sweep_keeps_approvals_within_their_timeout() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+
+    let _request = register(&state, ThreadId::new(), &exec_request("call-1")).await?;
+
+    assert_eq!(expire_approvals(&state).await, Vec::new());
+    assert!(state.pending_approvals.lock().await.contains_key("call-1"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pending_approvals_are_listed_per_thread() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = ThreadId::new();
+    let _exec = register(&state, thread_id, &exec_request("call-1")).await?;
+    let _patch = register(&state, thread_id, &patch_request()).await?;
+    let _elsewhere = register(&state, ThreadId::new(), &exec_request("call-2")).await?;
+    let app = Router::new()
+        .route(
+            "/api/v2/threads/{thread_id}/approvals",
+            get(handlers::approvals::list_approvals),
+        )
+        .with_state(state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/v2/threads/{thread_id}/approvals"))
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+    let approvals = body["approvals"].as_array().cloned().unwrap_or_default();
+    assert_eq!(approvals.len(), 2, "{body}");
+
+    // Oldest first.
+    let exec = &approvals[0];
+    assert_eq!(exec["approval_id"], json!("call-1"));
+    assert_eq!(exec["thread_id"], json!(thread_id.to_string()));
+    assert_eq!(exec["turn_id"], json!("turn-1"));
+    assert_eq!(exec["type"], json!("command_execution"));
+    assert_eq!(exec["command"], json!(["rm", "-rf", "build"]));
+    assert_eq!(exec["cwd"], json!("/work"));
+    assert_eq!(exec["reason"], json!("clean build output"));
+    assert_eq!(exec["elapsed_secs"], json!(0));
+    let expires_in = exec["expires_in_secs"].as_u64().unwrap_or_default();
+    assert!((899..=900).contains(&expires_in), "{exec}");
+
+    let patch = &approvals[1];
+    assert_eq!(patch["approval_id"], json!("patch-1"));
+    assert_eq!(patch["type"], json!("file_change"));
+    assert_eq!(patch["grant_root"], json!("/work"));
+    Ok(())
+}
+
+/// Answers `approval_id` of `thread_id` through the REST handler and returns
+/// the status with the error code, if any.
+async fn post_answer(
+    app: &Router,
+    thread_id: ThreadId,
+    approval_id: &str,
+) -> Result<(StatusCode, Value)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!(
+                    "/api/v2/threads/{thread_id}/approvals/{approval_id}"
+                ))
+                .header("content-type", "application/json")
+                .body(Body::from(json!({"decision": "approve"}).to_string()))?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    let body: Value = if body.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_slice(&body)?
+    };
+    Ok((status, body["code"].clone()))
+}
+
+#[tokio::test]
+async fn test_answer_statuses() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let expiring = state.clone().with_approval_timeout(Duration::ZERO);
+    let thread_id = ThreadId::new();
+    let other_thread_id = ThreadId::new();
+    let app = Router::new()
+        .route(
+            "/api/v2/threads/{thread_id}/approvals/{approval_id}",
+            post(handlers::approvals::respond_to_approval),
+        )
+        .with_state(state.clone());
+    let _live = register(&state, thread_id, &exec_request("live")).await?;
+    let _expired = register(&expiring, thread_id, &exec_request("expired")).await?;
+    let closed = register(&state, thread_id, &exec_request("closed")).await?;
+    drop(closed.response);
+
+    // Another thread cannot answer the approval, and it stays pending.
+    assert_eq!(
+        post_answer(&app, other_thread_id, "live").await?,
+        (StatusCode::NOT_FOUND, json!("not_found"))
+    );
+    assert_eq!(
+        post_answer(&app, thread_id, "live").await?,
+        (StatusCode::OK, Value::Null)
+    );
+    assert_eq!(
+        post_answer(&app, thread_id, "unknown").await?,
+        (StatusCode::NOT_FOUND, json!("not_found"))
+    );
+    assert_eq!(
+        post_answer(&app, thread_id, "expired").await?,
+        (StatusCode::REQUEST_TIMEOUT, json!("timeout"))
+    );
+    assert_eq!(
+        post_answer(&app, thread_id, "closed").await?,
+        (StatusCode::GONE, json!("channel_closed"))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_other_events_are_not_approvals() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let msg = EventMsg::AgentMessage(AgentMessageEvent {
+        message: "hello".to_string(),
+        phase: None,
+    });
+
+    assert!(
+        register_approval_request(&state, ThreadId::new(), &msg)
+            .await
+            .is_none()
+    );
+    assert!(state.pending_approvals.lock().await.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_externally_started_command_resolves_its_approval() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = ThreadId::new();
+    state.thread_statuses.apply(
+        thread_id,
+        StatusTransition::ApprovalRequested {
+            approval_id: "call-1".to_string(),
+        },
+    );
+
+    let request = register(&state, thread_id, &exec_request("call-1")).await?;
+    let (resolved, ops) = wait_for_answer(
+        &state,
+        thread_id,
+        request,
+        resolve_external_approvals(&state, thread_id, &exec_begin("call-1")),
+    )
+    .await?;
+
+    let expected = ApprovalResolved {
+        thread_id,
+        approval_id: "call-1".to_string(),
+        source: ApprovalResolutionSource::External,
+    };
+    assert_eq!(resolved, vec![expected.clone()]);
+    assert_eq!(
+        expected.payload(),
+        json!({
+            "method": "approval/resolved",
+            "params": {
+                "threadId": thread_id.to_string(),
+                "approvalId": "call-1",
+                "source": "external",
+            },
+        })
+    );
+    // Nothing is submitted on the web client's behalf, and a late answer
+    // from it is rejected.
+    assert!(ops.is_empty());
+    assert!(state.pending_approvals.lock().await.is_empty());
+    assert_eq!(
+        state.thread_statuses.status(thread_id),
+        ThreadRunStatus::Idle
+    );
+    assert_eq!(
+        respond(&state, thread_id, "call-1", ApprovalDecision::Approve).await,
+        Err(ApprovalErrorCode::NotFound)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_started_command_resolves_an_approval_with_its_own_id() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = ThreadId::new();
+    let EventMsg::ExecApprovalRequest(mut ev) = exec_request("call-1") else {
+        anyhow::bail!("not an exec approval request");
+    };
+    ev.approval_id = Some("approval-1".to_string());
+
+    let request = register(&state, thread_id, &EventMsg::ExecApprovalRequest(ev)).await?;
+    let (resolved, ops) = wait_for_answer(
+        &state,
+        thread_id,
+        request,
+        resolve_external_approvals(&state, thread_id, &exec_begin("call-1")),
+    )
+    .await?;
+
+    assert_eq!(
+        resolved,
+        vec![ApprovalResolved {
+            thread_id,
+            approval_id: "approval-1".to_string(),
+            source: ApprovalResolutionSource::External,
+        }]
+    );
+    assert!(ops.is_empty());
+    assert!(state.pending_approvals.lock().await.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_aborted_turn_cancels_only_its_approvals() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = ThreadId::new();
+    let other_thread_id = ThreadId::new();
+    for approval_id in ["call-1", "patch-1"] {
+        state.thread_statuses.apply(
+            thread_id,
+            StatusTransition::ApprovalRequested {
+                approval_id: approval_id.to_string(),
+            },
+        );
+    }
+
+    let exec = register(&state, thread_id, &exec_request("call-1")).await?;
+    let patch = register(&state, thread_id, &patch_request()).await?;
+    let _other = register(&state, other_thread_id, &exec_request("call-2")).await?;
+
+    // An unrelated command starting, or another turn ending, leaves the
+    // approvals pending.
+    assert!(
+        resolve_external_approvals(&state, thread_id, &exec_begin("call-9"))
+            .await
+            .is_empty()
+    );
+    assert!(
+        cancel_orphaned_approvals(&state, thread_id, &turn_complete("turn-0"))
+            .await
+            .is_empty()
+    );
+
+    let mut cancelled = cancel_orphaned_approvals(&state, thread_id, &turn_aborted("turn-1")).await;
+    cancelled.sort_by(|a, b| a.approval_id.cmp(&b.approval_id));
+    assert_eq!(
+        cancelled,
+        ["call-1", "patch-1"].map(|approval_id| ApprovalCancelled {
+            thread_id,
+            turn_id: "turn-1".to_string(),
+            approval_id: approval_id.to_string(),
+            reason: ApprovalCancelReason::TurnAborted,
+        })
+    );
+    assert_eq!(
+        cancelled[0].payload(),
+        json!({
+            "method": "approval/cancelled",
+            "params": {
+                "threadId": thread_id.to_string(),
+                "turnId": "turn-1",
+                "approvalId": "call-1",
+                "reason": "turn_aborted",
+            },
+        })
+    );
+    let pending: Vec<String> = state
+        .pending_approvals
+        .lock()
+        .await
+        .keys()
+        .cloned()
+        .collect();
+    assert_eq!(pending, vec!["call-2".to_string()]);
+
+    // The waiters submit nothing, and a late answer is rejected.
+    for request in [exec, patch] {
+        let (_, ops) = wait_for_answer(&state, thread_id, request, async {}).await?;
+        assert!(ops.is_empty());
+    }
+    assert_eq!(
+        state.thread_statuses.status(thread_id),
+        ThreadRunStatus::Idle
+    );
+    assert_eq!(
+        respond(&state, thread_id, "call-1", ApprovalDecision::Approve).await,
+        Err(ApprovalErrorCode::NotFound)
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_completed_turn_cancels_its_approvals() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = ThreadId::new();
+
+    let exec = register(&state, thread_id, &exec_request("call-1")).await?;
+    let (cancelled, ops) = wait_for_answer(
+        &state,
+        thread_id,
+        exec,
+        cancel_orphaned_approvals(&state, thread_id, &turn_complete("turn-1")),
+    )
+    .await?;
+
+    assert_eq!(
+        cancelled,
+        vec![ApprovalCancelled {
+            thread_id,
+            turn_id: "turn-1".to_string(),
+            approval_id: "call-1".to_string(),
+            reason: ApprovalCancelReason::TurnCompleted,
+        }]
+    );
+    assert!(ops.is_empty());
+    assert!(state.pending_approvals.lock().await.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pending_approvals_are_sent_to_a_stream_that_connects_later() -> Result<()> {
+    let server = start_mock_server().await;
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = start_mock_provider_thread(&fixture, &state, &server.uri()).await?;
+    let exec = register(&state, thread_id, &exec_request("call-1")).await?;
+    let patch = register(&state, thread_id, &patch_request()).await?;
+    // Another thread's approval is not this stream's business.
+    register(&state, ThreadId::new(), &exec_request("call-2")).await?;
+    let app = Router::new()
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .with_state(state);
+
+    // No stream was open when the requests were published; a new one gets
+    // them, oldest first, without event ids.
+    let mut events = EventReader::open(&app, thread_id).await?;
+    for request in [exec, patch] {
+        let frame = loop {
+            let frame = events.next_frame().await?;
+            if frame.event_type.ends_with("/requestApproval") {
+                break frame;
+            }
+        };
+        assert_eq!(frame.event_type, request.event_type);
+        assert_eq!(frame.id, None);
+        assert_eq!(frame.data, serde_json::from_str::<Value>(&request.data)?);
+    }
+    Ok(())
+}
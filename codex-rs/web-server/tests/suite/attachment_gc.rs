@@ -0,0 +1,265 @@
+//! Deleting attachments once their TTL has passed.
+
+use anyhow::Result;
+use codex_protocol::ThreadId;
+use codex_protocol::user_input::UserInput;
+use codex_web_server::attachment_gc::AttachmentGcPolicy;
+use codex_web_server::attachment_gc::AttachmentSweep;
+use codex_web_server::attachment_gc::sweep_attachments;
+use codex_web_server::attachments::AttachmentMetadata;
+use codex_web_server::handlers::turns::TurnOverrides;
+use codex_web_server::thread_status::StatusTransition;
+use codex_web_server::turn_attachments;
+use serde_json::json;
+use std::path::Path;
+use std::time::Duration;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+const TTL: Duration = Duration::from_secs(72 * 3600);
+const OLD: Duration = Duration::from_secs(100 * 3600);
+
+/// Writes attachment `id` into `dir`: its file unless `file` is `None`, and
+/// its metadata unless `created_at` is `None`, recording `created_at` when
+/// that is set. The files were last modified `age` ago.
+fn write_attachment(
+    dir: &Path,
+    id: &str,
+    file: Option<&str>,
+    created_at: Option<Option<i64>>,
+    age: Duration,
+) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let modified = SystemTime::now() - age;
+    if let Some(contents) = file {
+        std::fs::write(dir.join(id), contents)?;
+        std::fs::File::options()
+            .write(true)
+            .open(dir.join(id))?
+            .set_modified(modified)?;
+    }
+    if let Some(created_at) = created_at {
+        let path = dir.join(format!("{id}.json"));
+        std::fs::write(
+            &path,
+            json!({
+                "id": id,
+                "filename": "notes.txt",
+                "mime_type": "text/plain",
+                "size": file.map_or(0, str::len),
+                "created_at": created_at,
+            })
+            .to_string(),
+        )?;
+        std::fs::File::options()
+            .write(true)
+            .open(&path)?
+            .set_modified(modified)?;
+    }
+    Ok(())
+}
+
+fn ago(age: Duration) -> Result<i64> {
+    Ok((SystemTime::now() - age)
+        .duration_since(UNIX_EPOCH)?
+        .as_secs() as i64)
+}
+
+#[tokio::test]
+async fn test_sweep_deletes_expired_attachments_and_orphans() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let root = fixture.attachments_dir.path().canonicalize()?;
+    let namespace = root.join("namespace");
+    let id = || uuid::Uuid::new_v4().to_string();
+    let (expired, fresh, legacy, orphan, fresh_orphan, sidecar_only) =
+        (id(), id(), id(), id(), id(), id());
+    write_attachment(
+        &namespace,
+        &expired,
+        Some("expired"),
+        Some(Some(ago(OLD)?)),
+        Duration::ZERO,
+    )?;
+    write_attachment(
+        &namespace,
+        &fresh,
+        Some("fresh"),
+        Some(Some(ago(Duration::ZERO)?)),
+        OLD,
+    )?;
+    // Metadata without `created_at` goes by the file's modification time.
+    write_attachment(&root, &legacy, Some("legacy"), Some(None), OLD)?;
+    write_attachment(&namespace, &orphan, Some("orphan"), None, OLD)?;
+    write_attachment(
+        &namespace,
+        &fresh_orphan,
+        Some("uploading"),
+        None,
+        Duration::ZERO,
+    )?;
+    write_attachment(&namespace, &sidecar_only, None, Some(Some(ago(OLD)?)), OLD)?;
+    std::fs::write(root.join("README"), "not an attachment")?;
+
+    let sweep = sweep_attachments(&state, TTL).await;
+    let mut deleted = sweep.deleted.clone();
+    deleted.sort();
+    let mut expected = vec![expired.clone(), legacy.clone()];
+    expected.sort();
+    assert_eq!(deleted, expected);
+    assert_eq!(sweep.orphans_deleted, 2);
+    assert!(sweep.reclaimed_bytes > "expired".len() as u64, "{sweep:?}");
+
+    for (dir, id) in [
+        (&namespace, &expired),
+        (&root, &legacy),
+        (&namespace, &orphan),
+        (&namespace, &sidecar_only),
+    ] {
+        assert!(!dir.join(id).exists(), "{id}");
+        assert!(!dir.join(format!("{id}.json")).exists(), "{id}");
+    }
+    assert!(namespace.join(&fresh).exists());
+    assert!(namespace.join(format!("{fresh}.json")).exists());
+    assert!(namespace.join(&fresh_orphan).exists());
+    assert!(root.join("README").exists());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sweep_keeps_attachments_of_running_and_queued_turns() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let namespace = fixture
+        .attachments_dir
+        .path()
+        .canonicalize()?
+        .join("namespace");
+    let running = uuid::Uuid::new_v4().to_string();
+    let queued = uuid::Uuid::new_v4().to_string();
+    for id in [&running, &queued] {
+        write_attachment(&namespace, id, Some("image"), Some(Some(ago(OLD)?)), OLD)?;
+    }
+
+    let thread_id = ThreadId::new();
+    state.thread_statuses.apply(
+        thread_id,
+        StatusTransition::TurnStarted {
+            turn_id: "turn-1".to_string(),
+        },
+    );
+    turn_attachments::record(
+        &state.codex_home,
+        thread_id,
+        "turn-1",
+        vec![(
+            namespace.join(&running),
+            AttachmentMetadata {
+                id: running.clone(),
+                filename: "notes.txt".to_string(),
+                mime_type: "text/plain".to_string(),
+                size: 5,
+                owner: None,
+                created_at: None,
+                sha256: None,
+                blob_ref: None,
+            },
+        )],
+    )
+    .await?;
+    state.turn_queue.enqueue(
+        ThreadId::new(),
+        Vec::new(),
+        vec![UserInput::LocalImage {
+            path: namespace.join(&queued),
+        }],
+        TurnOverrides::default(),
+    );
+
+    let sweep = sweep_attachments(&state, TTL).await;
+    assert_eq!(sweep.deleted, Vec::<String>::new());
+    assert!(namespace.join(&running).exists());
+    assert!(namespace.join(&queued).exists());
+
+    // Once the turn is over, the next sweep takes its attachment.
+    state.thread_statuses.apply(
+        thread_id,
+        StatusTransition::TurnFinished {
+            turn_id: "turn-1".to_string(),
+            interrupted: false,
+        },
+    );
+    let sweep = sweep_attachments(&state, TTL).await;
+    assert_eq!(sweep.deleted, vec![running.clone()]);
+    assert!(!namespace.join(&running).exists());
+    assert!(namespace.join(&queued).exists());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sweep_keeps_a_shared_file_until_its_last_user_expires() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let namespace = fixture
+        .attachments_dir
+        .path()
+        .canonicalize()?
+        .join("namespace");
+    let owner = uuid::Uuid::new_v4().to_string();
+    let alias = uuid::Uuid::new_v4().to_string();
+    write_attachment(
+        &namespace,
+        &owner,
+        Some("shared"),
+        Some(Some(ago(OLD)?)),
+        OLD,
+    )?;
+    let write_alias = |created_at: i64| {
+        std::fs::write(
+            namespace.join(format!("{alias}.json")),
+            json!({
+                "id": alias,
+                "filename": "copy.txt",
+                "mime_type": "text/plain",
+                "size": 6,
+                "created_at": created_at,
+                "blob_ref": owner,
+            })
+            .to_string(),
+        )
+    };
+    write_alias(ago(Duration::ZERO)?)?;
+
+    let sweep = sweep_attachments(&state, TTL).await;
+    assert_eq!(sweep.deleted, vec![owner.clone()]);
+    assert!(!namespace.join(format!("{owner}.json")).exists());
+    assert!(namespace.join(&owner).exists());
+
+    // The file left without its metadata is not an orphan while shared.
+    let sweep = sweep_attachments(&state, TTL).await;
+    assert_eq!(sweep, AttachmentSweep::default());
+    assert!(namespace.join(&owner).exists());
+
+    write_alias(ago(OLD)?)?;
+    let sweep = sweep_attachments(&state, TTL).await;
+    assert_eq!(sweep.deleted, vec![alias.clone()]);
+    assert_eq!(sweep.orphans_deleted, 0);
+    assert!(!namespace.join(&owner).exists());
+    assert!(!namespace.join(format!("{alias}.json")).exists());
+    Ok(())
+}
+
+#[test]
+fn test_sweep_interval_follows_the_ttl() {
+    assert_eq!(
+        AttachmentGcPolicy::new(TTL).sweep_interval,
+        Duration::from_secs(3600)
+    );
+    assert_eq!(
+        AttachmentGcPolicy::new(Duration::from_secs(240)).sweep_interval,
+        Duration::from_secs(10)
+    );
+}
@@ -0,0 +1,988 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::extract::DefaultBodyLimit;
+use axum::http::HeaderMap;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use base64::Engine;
+use base64::prelude::BASE64_STANDARD;
+use codex_protocol::user_input::UserInput;
+use codex_web_server::attachments;
+use codex_web_server::attachments::AttachmentLimits;
+use codex_web_server::attachments::AttachmentScope;
+use codex_web_server::attachments::MAX_INLINE_IMAGE_BYTES;
+use codex_web_server::attachments::UploadErrorCode;
+use codex_web_server::attachments::resolve_attachment;
+use codex_web_server::attachments::store_inline_image;
+use codex_web_server::attachments::upload_dir;
+use codex_web_server::error::ApiError;
+use codex_web_server::handlers::turns::UserInputItem;
+use codex_web_server::handlers::turns::resolve_user_inputs;
+use codex_web_server::handlers::turns::store_inline_images;
+use codex_web_server::state::WebServerState;
+use serde_json::Value;
+use serde_json::json;
+use sha2::Digest;
+use sha2::Sha256;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+const BOUNDARY: &str = "codex-test-boundary";
+
+async fn upload(
+    fixture: &TestFixture,
+    boundary: &str,
+    body: String,
+) -> Result<(StatusCode, Value)> {
+    let app = Router::new()
+        .route("/api/v1/attachments", post(attachments::upload_attachment))
+        .with_state(create_test_state(fixture, "test-token"));
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/attachments")
+                .header(
+                    "content-type",
+                    format!("multipart/form-data; boundary={boundary}"),
+                )
+                .body(Body::from(body))?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+fn part(name: &str, filename: Option<&str>, content_type: Option<&str>, data: &str) -> String {
+    let mut part = format!("--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"");
+    if let Some(filename) = filename {
+        part.push_str(&format!("; filename=\"{filename}\""));
+    }
+    part.push_str("\r\n");
+    if let Some(content_type) = content_type {
+        part.push_str(&format!("Content-Type: {content_type}\r\n"));
+    }
+    part.push_str(&format!("\r\n{data}\r\n"));
+    part
+}
+
+/// Files under the attachments directory, namespace subdirectories included.
+fn stored_files(fixture: &TestFixture) -> Result<usize> {
+    fn count(dir: &std::path::Path) -> Result<usize> {
+        let mut files = 0;
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            files += if entry.file_type()?.is_dir() {
+                count(&entry.path())?
+            } else {
+                1
+            };
+        }
+        Ok(files)
+    }
+    count(fixture.attachments_dir.path())
+}
+
+#[tokio::test]
+async fn test_upload_without_file_field_lists_fields_seen() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let body = format!(
+        "{}{}--{BOUNDARY}--\r\n",
+        part("note", None, None, "hello"),
+        part("upload", None, Some("image/png"), "not marked as a file"),
+    );
+
+    let (status, error) = upload(&fixture, BOUNDARY, body).await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{error}");
+    assert_eq!(error["code"], json!("missing_file"));
+    assert_eq!(error["expected_field"], json!("file"));
+    assert_eq!(error["fields_seen"], json!(["note", "upload"]));
+    assert_eq!(error["filename"], Value::Null);
+    assert_eq!(stored_files(&fixture)?, 0);
+
+    // A field with a filename is taken as the file whatever its name.
+    let body = format!(
+        "{}{}--{BOUNDARY}--\r\n",
+        part("note", None, None, "hello"),
+        part(
+            "upload",
+            Some("diagram.png"),
+            Some("image/png"),
+            "png bytes"
+        ),
+    );
+    let (status, uploaded) = upload(&fixture, BOUNDARY, body).await?;
+    assert_eq!(status, StatusCode::OK, "{uploaded}");
+    assert_eq!(uploaded["filename"], json!("diagram.png"));
+    assert_eq!(uploaded["size"], json!(9));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_upload_with_corrupted_boundary_is_malformed() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    // The file part never reaches its closing boundary.
+    let body = format!(
+        "{}--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\nContent-Type: text/plain\r\n\r\ntruncated content",
+        part("note", None, None, "hello"),
+    );
+    let (status, error) = upload(&fixture, BOUNDARY, body).await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{error}");
+    assert_eq!(error["code"], json!("malformed_multipart"));
+    assert_eq!(error["fields_seen"], json!(["note", "file"]));
+    assert_eq!(error["filename"], json!("a.txt"));
+    assert_eq!(error["declared_type"], json!("text/plain"));
+    assert_eq!(stored_files(&fixture)?, 0);
+
+    // A boundary the body never uses fails before any field is read.
+    let body = format!(
+        "{}--{BOUNDARY}--\r\n",
+        part("file", Some("a.txt"), None, "x")
+    );
+    let (status, error) = upload(&fixture, "some-other-boundary", body).await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{error}");
+    assert_eq!(error["code"], json!("malformed_multipart"));
+    assert_eq!(error["fields_seen"], json!([]));
+    assert_eq!(stored_files(&fixture)?, 0);
+    Ok(())
+}
+
+/// Uploads `data` as one file to an app whose attachment limit is
+/// `max_bytes`, declaring `content_length` if given.
+async fn upload_limited(
+    fixture: &TestFixture,
+    max_bytes: u64,
+    content_length: Option<u64>,
+    data: &str,
+) -> Result<(StatusCode, Value)> {
+    let limits = AttachmentLimits {
+        max_bytes,
+        ..AttachmentLimits::default()
+    };
+    let state = create_test_state(fixture, "test-token").with_attachment_limits(limits);
+    let app = Router::new()
+        .route(
+            "/api/v1/attachments",
+            post(attachments::upload_attachment)
+                .layer(DefaultBodyLimit::max(limits.max_body_bytes())),
+        )
+        .with_state(state);
+    let mut request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/attachments")
+        .header(
+            "content-type",
+            format!("multipart/form-data; boundary={BOUNDARY}"),
+        );
+    if let Some(content_length) = content_length {
+        request = request.header("content-length", content_length);
+    }
+    let body = format!(
+        "{}--{BOUNDARY}--\r\n",
+        part(
+            "file",
+            Some("big.bin"),
+            Some("application/octet-stream"),
+            data
+        )
+    );
+    let response = app.oneshot(request.body(Body::from(body))?).await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+#[tokio::test]
+async fn test_upload_over_the_limit_is_refused() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+
+    // A declared length over the limit is refused before the body is read.
+    let (status, error) = upload_limited(&fixture, 16, Some(10 * 1024 * 1024), "small").await?;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE, "{error}");
+    assert_eq!(error["code"], json!("file_too_large"));
+    assert_eq!(error["fields_seen"], json!([]));
+    assert_eq!(stored_files(&fixture)?, 0);
+
+    // Without one, the file stops at the limit while streaming.
+    let (status, error) = upload_limited(&fixture, 16, None, &"x".repeat(17)).await?;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE, "{error}");
+    assert_eq!(error["code"], json!("file_too_large"));
+    assert_eq!(error["filename"], json!("big.bin"));
+    assert_eq!(stored_files(&fixture)?, 0);
+
+    let (status, uploaded) = upload_limited(&fixture, 16, None, &"x".repeat(16)).await?;
+    assert_eq!(status, StatusCode::OK, "{uploaded}");
+    assert_eq!(uploaded["size"], json!(16));
+    Ok(())
+}
+
+/// Sends `body` to `POST /api/v2/attachments` under `limits`.
+async fn upload_many(
+    fixture: &TestFixture,
+    limits: AttachmentLimits,
+    body: String,
+) -> Result<(StatusCode, Value)> {
+    let state = create_test_state(fixture, "test-token").with_attachment_limits(limits);
+    let app = Router::new()
+        .route(
+            "/api/v2/attachments",
+            post(attachments::upload_attachments)
+                .layer(DefaultBodyLimit::max(limits.max_upload_body_bytes())),
+        )
+        .with_state(state);
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v2/attachments")
+                .header(
+                    "content-type",
+                    format!("multipart/form-data; boundary={BOUNDARY}"),
+                )
+                .body(Body::from(body))?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+#[tokio::test]
+async fn test_upload_stores_every_file_of_a_request() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let body = format!(
+        "{}{}{}--{BOUNDARY}--\r\n",
+        part("file", Some("a.png"), Some("image/png"), "first"),
+        part("note", None, None, "not a file"),
+        part("file", Some("b.txt"), Some("text/plain"), "second file"),
+    );
+    let (status, uploaded) = upload_many(&fixture, AttachmentLimits::default(), body).await?;
+    assert_eq!(status, StatusCode::OK, "{uploaded}");
+    let data = uploaded["data"].as_array().cloned().unwrap_or_default();
+    let files: Vec<_> = data
+        .iter()
+        .map(|file| (file["filename"].clone(), file["size"].clone()))
+        .collect();
+    assert_eq!(
+        files,
+        vec![(json!("a.png"), json!(5)), (json!("b.txt"), json!(11))]
+    );
+    assert_ne!(data[0]["attachment_id"], data[1]["attachment_id"]);
+    // Each file has its own metadata.
+    assert_eq!(stored_files(&fixture)?, 4);
+
+    // v1 keeps its single-object response and stores only the first file.
+    let body = format!(
+        "{}{}--{BOUNDARY}--\r\n",
+        part("file", Some("c.txt"), None, "third"),
+        part("file", Some("d.txt"), None, "fourth"),
+    );
+    let (status, uploaded) = upload(&fixture, BOUNDARY, body).await?;
+    assert_eq!(status, StatusCode::OK, "{uploaded}");
+    assert_eq!(uploaded["filename"], json!("c.txt"));
+    assert_eq!(stored_files(&fixture)?, 6);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_failed_multi_file_upload_stores_nothing() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let limits = AttachmentLimits {
+        max_bytes: 16,
+        max_upload_bytes: 24,
+    };
+
+    // The second file is over the per-file limit.
+    let body = format!(
+        "{}{}--{BOUNDARY}--\r\n",
+        part("file", Some("a.txt"), None, "small"),
+        part("file", Some("b.txt"), None, &"x".repeat(17)),
+    );
+    let (status, error) = upload_many(&fixture, limits, body).await?;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE, "{error}");
+    assert_eq!(error["code"], json!("file_too_large"));
+    assert_eq!(error["filename"], json!("b.txt"));
+    assert_eq!(stored_files(&fixture)?, 0);
+
+    // Each file fits, but not together.
+    let body = format!(
+        "{}{}--{BOUNDARY}--\r\n",
+        part("file", Some("a.txt"), None, &"x".repeat(12)),
+        part("file", Some("b.txt"), None, &"x".repeat(13)),
+    );
+    let (status, error) = upload_many(&fixture, limits, body).await?;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE, "{error}");
+    assert_eq!(error["code"], json!("file_too_large"));
+    assert_eq!(stored_files(&fixture)?, 0);
+
+    // The body ends in the middle of the second file.
+    let body = format!(
+        "{}--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"b.txt\"\r\n\r\ncut off",
+        part("file", Some("a.txt"), None, "complete"),
+    );
+    let (status, error) = upload_many(&fixture, limits, body).await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{error}");
+    assert_eq!(error["code"], json!("malformed_multipart"));
+    assert_eq!(stored_files(&fixture)?, 0);
+
+    let body = format!("{}--{BOUNDARY}--\r\n", part("note", None, None, "text"));
+    let (status, error) = upload_many(&fixture, limits, body).await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{error}");
+    assert_eq!(error["code"], json!("missing_file"));
+    Ok(())
+}
+
+fn attachments_app(state: WebServerState) -> Router {
+    Router::new()
+        .route(
+            "/api/v1/attachments",
+            post(attachments::upload_attachment).get(attachments::list_attachments),
+        )
+        .route(
+            "/api/v1/attachments/{id}",
+            get(attachments::download_attachment).delete(attachments::delete_attachment),
+        )
+        .with_state(state)
+}
+
+async fn upload_as(app: &Router, token: &str, data: &str) -> Result<String> {
+    upload_file(app, token, "notes.txt", "text/plain", data.as_bytes()).await
+}
+
+/// Uploads `data` as `filename` of `content_type` and returns the
+/// attachment id.
+async fn upload_file(
+    app: &Router,
+    token: &str,
+    filename: &str,
+    content_type: &str,
+    data: &[u8],
+) -> Result<String> {
+    let mut body = format!(
+        "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{filename}\"\r\nContent-Type: {content_type}\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(data);
+    body.extend_from_slice(format!("\r\n--{BOUNDARY}--\r\n").as_bytes());
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/attachments")
+                .header("authorization", format!("Bearer {token}"))
+                .header(
+                    "content-type",
+                    format!("multipart/form-data; boundary={BOUNDARY}"),
+                )
+                .body(Body::from(body))?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+    Ok(body["attachment_id"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string())
+}
+
+async fn download_as(
+    app: &Router,
+    token: &str,
+    namespace: Option<&str>,
+    id: &str,
+) -> Result<(StatusCode, String)> {
+    let mut request = Request::builder()
+        .uri(format!("/api/v1/attachments/{id}"))
+        .header("authorization", format!("Bearer {token}"));
+    if let Some(namespace) = namespace {
+        request = request.header(attachments::NAMESPACE_HEADER, namespace);
+    }
+    let response = app.clone().oneshot(request.body(Body::empty())?).await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, String::from_utf8(body.to_vec())?))
+}
+
+#[tokio::test]
+async fn test_attachments_are_only_readable_by_their_token() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    // "test-token" is the server token and, with no admin token set, admin.
+    let state = create_test_state(&fixture, "test-token");
+    let app = attachments_app(state.clone());
+
+    let id = upload_as(&app, "token-a", "from a").await?;
+    assert_eq!(
+        download_as(&app, "token-a", None, &id).await?,
+        (StatusCode::OK, "from a".to_string())
+    );
+    assert_eq!(
+        download_as(&app, "token-b", None, &id).await?.0,
+        StatusCode::NOT_FOUND
+    );
+    // A declared namespace is separate from the token's own.
+    assert_eq!(
+        download_as(&app, "token-a", Some("alice"), &id).await?.0,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        download_as(&app, "token-a", Some("../other"), &id).await?.0,
+        StatusCode::BAD_REQUEST
+    );
+    assert_eq!(
+        download_as(&app, "test-token", None, &id).await?,
+        (StatusCode::OK, "from a".to_string())
+    );
+
+    // Turns cannot reference another token's attachment either.
+    let items = [UserInputItem::Attachment {
+        attachment_id: id.clone(),
+    }];
+    let scope_b = AttachmentScope::new(&state, "token-b", None);
+    assert!(matches!(
+        resolve_user_inputs(&state, &scope_b, &items).await,
+        Err(ApiError::AttachmentNotFound)
+    ));
+    let scope_a = AttachmentScope::new(&state, "token-a", None);
+    let inputs = resolve_user_inputs(&state, &scope_a, &items)
+        .await
+        .map_err(|err| anyhow::anyhow!("{err:?}"))?;
+    let [UserInput::Text { text, .. }] = inputs.as_slice() else {
+        panic!("expected one text input, got {inputs:?}");
+    };
+    assert_eq!(text, "Attached file: notes.txt\n\nfrom a");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_attachments_become_turn_input_by_type() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let app = attachments_app(state.clone());
+    let scope = AttachmentScope::new(&state, "test-token", None);
+    let resolve = |attachment_id: String| {
+        let state = state.clone();
+        let scope = scope.clone();
+        async move {
+            resolve_user_inputs(
+                &state,
+                &scope,
+                &[UserInputItem::Attachment { attachment_id }],
+            )
+            .await
+        }
+    };
+
+    // Images stay images, whatever their bytes.
+    let image = upload_file(&app, "test-token", "diagram.png", "image/png", b"\x89PNG").await?;
+    let inputs = resolve(image.clone())
+        .await
+        .map_err(|err| anyhow::anyhow!("{err:?}"))?;
+    let [UserInput::LocalImage { path }] = inputs.as_slice() else {
+        panic!("expected one local image, got {inputs:?}");
+    };
+    assert_eq!(path.file_name(), Some(std::ffi::OsStr::new(&image)));
+
+    // Text is inlined under its filename, also when declared as a
+    // parameterized or structured type.
+    for (filename, content_type, data) in [
+        (
+            "notes.txt",
+            "text/plain; charset=utf-8",
+            "first line\nsecond",
+        ),
+        ("config.json", "application/json", "{\"key\": 1}"),
+        ("main.rs", "application/octet-stream", "fn main() {}"),
+    ] {
+        let id = upload_file(&app, "test-token", filename, content_type, data.as_bytes()).await?;
+        let inputs = resolve(id)
+            .await
+            .map_err(|err| anyhow::anyhow!("{err:?}"))?;
+        assert_eq!(
+            inputs,
+            vec![UserInput::Text {
+                text: format!("Attached file: {filename}\n\n{data}"),
+                text_elements: Vec::new(),
+            }],
+            "{content_type}"
+        );
+    }
+
+    // Binary data is refused, as is text that is not UTF-8.
+    let garbage = [0xff, 0xfe, 0x00, 0x9c, 0x01, 0x80];
+    for (filename, content_type) in [
+        ("blob.bin", "application/octet-stream"),
+        ("archive.zip", "application/zip"),
+        ("latin1.txt", "text/plain"),
+    ] {
+        let id = upload_file(&app, "test-token", filename, content_type, &garbage).await?;
+        let result = resolve(id.clone()).await;
+        let Err(ApiError::InvalidRequest(message)) = &result else {
+            panic!("expected a bad request for {content_type}, got {result:?}");
+        };
+        assert!(
+            message.contains(&id) && message.contains(filename),
+            "{message}"
+        );
+    }
+    Ok(())
+}
+
+async fn send_as(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    token: &str,
+) -> Result<(StatusCode, Value)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("authorization", format!("Bearer {token}"))
+                .body(Body::empty())?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+/// Ids of the attachments `token` lists, following every page of `limit`.
+async fn list_ids(app: &Router, token: &str, limit: usize) -> Result<Vec<String>> {
+    let mut ids = Vec::new();
+    let mut uri = format!("/api/v1/attachments?limit={limit}");
+    loop {
+        let (status, page) = send_as(app, "GET", &uri, token).await?;
+        assert_eq!(status, StatusCode::OK, "{page}");
+        ids.extend(
+            page["data"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|attachment| attachment["id"].as_str().map(str::to_string)),
+        );
+        let Some(cursor) = page["next_cursor"].as_str() else {
+            return Ok(ids);
+        };
+        uri = format!("/api/v1/attachments?limit={limit}&cursor={cursor}");
+    }
+}
+
+#[tokio::test]
+async fn test_attachments_are_listed_and_deleted_per_namespace() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let app = attachments_app(create_test_state(&fixture, "test-token"));
+    let first = upload_as(&app, "token-a", "first").await?;
+    let second = upload_as(&app, "token-a", "second").await?;
+    let other = upload_as(&app, "token-b", "other").await?;
+    // Shared, and from before uploads recorded `created_at`.
+    let legacy = uuid::Uuid::new_v4().to_string();
+    let dir = fixture.attachments_dir.path();
+    std::fs::write(dir.join(&legacy), "legacy")?;
+    std::fs::write(
+        dir.join(format!("{legacy}.json")),
+        json!({ "id": legacy, "filename": "old.txt", "mime_type": "text/plain", "size": 6 })
+            .to_string(),
+    )?;
+
+    let (status, page) = send_as(&app, "GET", "/api/v1/attachments", "token-a").await?;
+    assert_eq!(status, StatusCode::OK, "{page}");
+    let listed = page["data"].as_array().cloned().unwrap_or_default();
+    assert_eq!(listed.len(), 3, "{page}");
+    assert_eq!(page["next_cursor"], Value::Null);
+    for attachment in &listed {
+        assert!(attachment["created_at"].is_i64(), "{attachment}");
+    }
+    let mut ids = list_ids(&app, "token-a", 1).await?;
+    ids.sort();
+    let mut expected = vec![first.clone(), second.clone(), legacy.clone()];
+    expected.sort();
+    assert_eq!(ids, expected);
+    assert_eq!(list_ids(&app, "token-b", 100).await?.len(), 2);
+    assert_eq!(list_ids(&app, "test-token", 100).await?.len(), 4);
+    assert_eq!(
+        send_as(&app, "GET", "/api/v1/attachments?limit=0", "token-a")
+            .await?
+            .0,
+        StatusCode::BAD_REQUEST
+    );
+
+    // Another token's attachment is not found, and stays.
+    let uri = format!("/api/v1/attachments/{first}");
+    assert_eq!(
+        send_as(&app, "DELETE", &uri, "token-b").await?.0,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        send_as(&app, "DELETE", &uri, "token-a").await?,
+        (StatusCode::OK, json!({"success": true}))
+    );
+    assert_eq!(
+        send_as(&app, "DELETE", &uri, "token-a").await?.0,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        download_as(&app, "token-a", None, &first).await?.0,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        send_as(&app, "DELETE", "/api/v1/attachments/not-a-uuid", "token-a")
+            .await?
+            .0,
+        StatusCode::BAD_REQUEST
+    );
+    // The blob and its metadata are both gone.
+    assert_eq!(stored_files(&fixture)?, 6);
+    assert_eq!(list_ids(&app, "test-token", 100).await?.len(), 3);
+    assert_eq!(
+        download_as(&app, "token-b", None, &other).await?,
+        (StatusCode::OK, "other".to_string())
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_flat_layout_attachments_stay_shared() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let app = attachments_app(create_test_state(&fixture, "test-token"));
+    let id = uuid::Uuid::new_v4().to_string();
+    let dir = fixture.attachments_dir.path();
+    std::fs::write(dir.join(&id), "legacy")?;
+    std::fs::write(
+        dir.join(format!("{id}.json")),
+        json!({ "id": id, "filename": "old.txt", "mime_type": "text/plain", "size": 6 })
+            .to_string(),
+    )?;
+
+    for token in ["token-a", "token-b"] {
+        assert_eq!(
+            download_as(&app, token, None, &id).await?,
+            (StatusCode::OK, "legacy".to_string())
+        );
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_identical_uploads_share_one_file() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let app = attachments_app(state.clone());
+    let dir = upload_dir(&state, &AttachmentScope::new(&state, "token-a", None));
+    let blob_ref = |id: &str| -> Result<Value> {
+        let metadata: Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join(format!("{id}.json")))?)?;
+        Ok(metadata["blob_ref"].clone())
+    };
+
+    let first = upload_as(&app, "token-a", "same bytes").await?;
+    let second = upload_as(&app, "token-a", "same bytes").await?;
+    assert_ne!(first, second);
+    // One file and the metadata of each.
+    assert_eq!(stored_files(&fixture)?, 3);
+    assert_eq!(blob_ref(&second)?, json!(first));
+    assert!(!dir.join(&second).exists());
+    for id in [&first, &second] {
+        assert_eq!(
+            download_as(&app, "token-a", None, id).await?,
+            (StatusCode::OK, "same bytes".to_string())
+        );
+    }
+    // Another namespace keeps its own copy.
+    upload_as(&app, "token-b", "same bytes").await?;
+    assert_eq!(stored_files(&fixture)?, 5);
+
+    // Deleting the first upload keeps the file for the second.
+    let (status, _) = send_as(
+        &app,
+        "DELETE",
+        &format!("/api/v1/attachments/{first}"),
+        "token-a",
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        download_as(&app, "token-a", None, &first).await?.0,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        download_as(&app, "token-a", None, &second).await?,
+        (StatusCode::OK, "same bytes".to_string())
+    );
+    assert_eq!(stored_files(&fixture)?, 4);
+
+    // Uploaded again, the content still goes to the same file.
+    let third = upload_as(&app, "token-a", "same bytes").await?;
+    assert_eq!(blob_ref(&third)?, json!(first));
+    assert_eq!(stored_files(&fixture)?, 5);
+
+    // The file goes with the last attachment using it.
+    for id in [&second, &third] {
+        let (status, _) = send_as(
+            &app,
+            "DELETE",
+            &format!("/api/v1/attachments/{id}"),
+            "token-a",
+        )
+        .await?;
+        assert_eq!(status, StatusCode::OK);
+    }
+    assert!(!dir.join(&first).exists());
+    assert_eq!(stored_files(&fixture)?, 2);
+
+    // Identical files in one request share a file too.
+    let body = format!(
+        "{}{}--{BOUNDARY}--\r\n",
+        part("file", Some("a.png"), None, "twice"),
+        part("file", Some("b.png"), None, "twice"),
+    );
+    let (status, uploaded) = upload_many(&fixture, AttachmentLimits::default(), body).await?;
+    assert_eq!(status, StatusCode::OK, "{uploaded}");
+    assert_eq!(stored_files(&fixture)?, 5);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_inline_images_are_stored_as_attachments() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let scope = AttachmentScope::new(&state, "token-a", None);
+    let png = b"\x89PNG\r\n\x1a\n";
+    let data = BASE64_STANDARD.encode(png);
+
+    let mut items = vec![
+        UserInputItem::Text {
+            text: "look".to_string(),
+        },
+        UserInputItem::Image {
+            data: data.clone(),
+            mime_type: "image/png".to_string(),
+        },
+    ];
+    store_inline_images(&state, &scope, &mut items)
+        .await
+        .map_err(|err| anyhow::anyhow!("{err:?}"))?;
+    let [_, UserInputItem::Attachment { attachment_id }] = items.as_slice() else {
+        panic!("expected the image to become an attachment, got {items:?}");
+    };
+    let resolved = resolve_attachment(&state, &scope, attachment_id)
+        .await
+        .map_err(|err| anyhow::anyhow!("{err:?}"))?;
+    assert_eq!(std::fs::read(&resolved.path)?, png);
+    let Some(metadata) = resolved.metadata else {
+        panic!("expected metadata for {attachment_id}");
+    };
+    assert_eq!(
+        (metadata.filename.as_str(), metadata.mime_type.as_str()),
+        ("image.png", "image/png")
+    );
+
+    // Resolved directly, an inline image becomes a local image too.
+    let inputs = resolve_user_inputs(
+        &state,
+        &scope,
+        &[UserInputItem::Image {
+            data,
+            mime_type: "image/png".to_string(),
+        }],
+    )
+    .await
+    .map_err(|err| anyhow::anyhow!("{err:?}"))?;
+    assert!(
+        matches!(inputs.as_slice(), [UserInput::LocalImage { path }] if *path == resolved.path),
+        "{inputs:?}"
+    );
+
+    for (data, mime_type) in [
+        ("not base64!".to_string(), "image/png"),
+        (BASE64_STANDARD.encode("text"), "text/plain"),
+        (BASE64_STANDARD.encode("text"), "image/"),
+    ] {
+        let result = store_inline_image(&state, &scope, &data, mime_type).await;
+        assert!(
+            matches!(result, Err(ApiError::InvalidRequest(_))),
+            "{mime_type}: {result:?}"
+        );
+    }
+    let oversized = BASE64_STANDARD.encode(vec![0u8; MAX_INLINE_IMAGE_BYTES + 1]);
+    let result = store_inline_image(&state, &scope, &oversized, "image/png").await;
+    assert!(
+        matches!(
+            result,
+            Err(ApiError::UploadFailed {
+                code: UploadErrorCode::FileTooLarge,
+                ..
+            })
+        ),
+        "{result:?}"
+    );
+    // Only the image, shared by both inputs, and the metadata of each.
+    assert_eq!(stored_files(&fixture)?, 3);
+    Ok(())
+}
+
+/// Downloads `id` as `test-token` with the extra `headers`.
+async fn download_with(
+    app: &Router,
+    id: &str,
+    headers: &[(&str, &str)],
+) -> Result<(StatusCode, HeaderMap, String)> {
+    let mut request = Request::builder()
+        .uri(format!("/api/v1/attachments/{id}"))
+        .header("authorization", "Bearer test-token");
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let response = app.clone().oneshot(request.body(Body::empty())?).await?;
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, headers, String::from_utf8(body.to_vec())?))
+}
+
+#[tokio::test]
+async fn test_downloads_honor_etags_and_ranges() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let app = attachments_app(create_test_state(&fixture, "test-token"));
+    let id = upload_as(&app, "test-token", "0123456789").await?;
+    let etag = format!("\"{:x}\"", Sha256::digest(b"0123456789"));
+
+    let (status, headers, body) = download_with(&app, &id, &[]).await?;
+    assert_eq!((status, body.as_str()), (StatusCode::OK, "0123456789"));
+    assert_eq!(headers["etag"], etag.as_str());
+    assert_eq!(headers["accept-ranges"], "bytes");
+
+    for if_none_match in [
+        etag.clone(),
+        format!("\"other\", W/{etag}"),
+        "*".to_string(),
+    ] {
+        let (status, headers, body) =
+            download_with(&app, &id, &[("if-none-match", &if_none_match)]).await?;
+        assert_eq!(status, StatusCode::NOT_MODIFIED, "{if_none_match}");
+        assert_eq!(headers["etag"], etag.as_str());
+        assert_eq!(body, "");
+    }
+    let (status, _, _) = download_with(&app, &id, &[("if-none-match", "\"other\"")]).await?;
+    assert_eq!(status, StatusCode::OK);
+
+    for (range, content_range, expected) in [
+        ("bytes=2-5", "bytes 2-5/10", "2345"),
+        ("bytes=7-", "bytes 7-9/10", "789"),
+        ("bytes=-3", "bytes 7-9/10", "789"),
+        ("bytes=8-100", "bytes 8-9/10", "89"),
+        ("bytes=-20", "bytes 0-9/10", "0123456789"),
+    ] {
+        let (status, headers, body) = download_with(&app, &id, &[("range", range)]).await?;
+        assert_eq!(status, StatusCode::PARTIAL_CONTENT, "{range}");
+        assert_eq!(headers["content-range"], content_range, "{range}");
+        assert_eq!(
+            headers["content-length"],
+            expected.len().to_string().as_str()
+        );
+        assert_eq!(body, expected, "{range}");
+    }
+
+    for range in ["bytes=10-", "bytes=20-30", "bytes=-0"] {
+        let (status, headers, body) = download_with(&app, &id, &[("range", range)]).await?;
+        assert_eq!(status, StatusCode::RANGE_NOT_SATISFIABLE, "{range}");
+        assert_eq!(headers["content-range"], "bytes */10");
+        assert_eq!(body, "");
+    }
+
+    // Several ranges, or ones that do not parse, get the whole file.
+    for range in ["bytes=0-1,4-5", "bytes=5-2", "items=0-1"] {
+        let (status, _, body) = download_with(&app, &id, &[("range", range)]).await?;
+        assert_eq!(
+            (status, body.as_str()),
+            (StatusCode::OK, "0123456789"),
+            "{range}"
+        );
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_downloads_without_a_recorded_hash_have_no_etag() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let app = attachments_app(create_test_state(&fixture, "test-token"));
+    let id = uuid::Uuid::new_v4().to_string();
+    let dir = fixture.attachments_dir.path();
+    std::fs::write(dir.join(&id), "legacy")?;
+    std::fs::write(
+        dir.join(format!("{id}.json")),
+        json!({ "id": id, "filename": "old.txt", "mime_type": "text/plain", "size": 6 })
+            .to_string(),
+    )?;
+
+    let (status, headers, body) =
+        download_with(&app, &id, &[("if-none-match", "\"legacy\"")]).await?;
+    assert_eq!((status, body.as_str()), (StatusCode::OK, "legacy"));
+    assert!(headers.get("etag").is_none());
+    let (status, _, body) = download_with(&app, &id, &[("range", "bytes=1-2")]).await?;
+    assert_eq!((status, body.as_str()), (StatusCode::PARTIAL_CONTENT, "eg"));
+    Ok(())
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_resolve_attachment_rejects_traversal_and_follows_a_symlinked_root() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    // The state's root is a symlink to the real attachments directory.
+    let real_root = fixture.attachments_dir.path().canonicalize()?;
+    let link_root = fixture.codex_home.path().join("attachments-link");
+    std::os::unix::fs::symlink(&real_root, &link_root)?;
+    let state = create_test_state(&fixture, "test-token").with_attachments_dir(link_root);
+    let app = attachments_app(state.clone());
+    let scope = AttachmentScope::new(&state, "token-a", None);
+
+    let id = upload_as(&app, "token-a", "from a").await?;
+    let resolved = resolve_attachment(&state, &scope, &id)
+        .await
+        .map_err(|err| anyhow::anyhow!("{err:?}"))?;
+    assert_eq!(resolved.path, real_root.join(&scope.namespace).join(&id));
+    assert_eq!(
+        resolved.metadata.map(|metadata| metadata.filename),
+        Some("notes.txt".to_string())
+    );
+
+    let nested = format!("{id}/..");
+    for id in ["../secret", "../../etc/passwd", nested.as_str(), ""] {
+        assert!(
+            matches!(
+                resolve_attachment(&state, &scope, id).await,
+                Err(ApiError::InvalidRequest(_))
+            ),
+            "{id}"
+        );
+    }
+    assert_eq!(
+        download_as(&app, "token-a", None, "..%2Fsecret").await?.0,
+        StatusCode::BAD_REQUEST
+    );
+
+    // A link in the caller's namespace may not reach another namespace.
+    let other = uuid::Uuid::new_v4().to_string();
+    let other_dir = real_root.join("other-namespace");
+    std::fs::create_dir_all(&other_dir)?;
+    std::fs::write(other_dir.join(&other), "secret")?;
+    let linked = uuid::Uuid::new_v4().to_string();
+    std::os::unix::fs::symlink(
+        other_dir.join(&other),
+        real_root.join(&scope.namespace).join(&linked),
+    )?;
+    assert!(matches!(
+        resolve_attachment(&state, &scope, &linked).await,
+        Err(ApiError::InvalidRequest(_))
+    ));
+    Ok(())
+}
@@ -0,0 +1,198 @@
+//! Model-provider login through `POST /api/v2/auth/login`.
+
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use codex_core::auth::AuthCredentialsStoreMode;
+use codex_core::auth::load_auth_dot_json;
+use codex_web_server::account_login::AccountLogins;
+use codex_web_server::handlers;
+use codex_web_server::state::WebServerState;
+use serde_json::Value;
+use serde_json::json;
+use std::time::Duration;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+fn auth_app(fixture: &TestFixture) -> Router {
+    auth_routes(create_test_state(fixture, "test-token"))
+}
+
+fn auth_routes(state: WebServerState) -> Router {
+    Router::new()
+        .route("/api/v2/auth/login", post(handlers::auth::login))
+        .route(
+            "/api/v2/auth/login/cancel",
+            post(handlers::auth::cancel_login),
+        )
+        .route("/api/v2/auth/account", get(handlers::auth::get_account))
+        .with_state(state)
+}
+
+async fn send(app: &Router, request: Request<Body>) -> Result<(StatusCode, Value)> {
+    let response = app.clone().oneshot(request).await?;
+    let status = response.status();
+    let bytes = to_bytes(response.into_body(), usize::MAX).await?;
+    let body = if bytes.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_slice(&bytes)?
+    };
+    Ok((status, body))
+}
+
+async fn account(app: &Router) -> Result<Value> {
+    let (status, body) = send(
+        app,
+        Request::builder()
+            .uri("/api/v2/auth/account")
+            .body(Body::empty())?,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    Ok(body)
+}
+
+async fn cancel(app: &Router, login_id: &Value) -> Result<(StatusCode, Value)> {
+    send(
+        app,
+        Request::builder()
+            .method("POST")
+            .uri("/api/v2/auth/login/cancel")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({"login_id": login_id}).to_string()))?,
+    )
+    .await
+}
+
+async fn login(app: &Router, body: Value) -> Result<(StatusCode, Value)> {
+    send(
+        app,
+        Request::builder()
+            .method("POST")
+            .uri("/api/v2/auth/login")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))?,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_api_key_login_persists_the_key_and_updates_the_account() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let app = auth_app(&fixture);
+    assert_eq!(account(&app).await?["requires_openai_auth"], json!(true));
+
+    let (status, body) = login(&app, json!({"type": "apiKey", "apiKey": "sk-test-key"})).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body, json!({"type": "apiKey"}));
+
+    let stored = load_auth_dot_json(&fixture.codex_home_path(), AuthCredentialsStoreMode::File)?;
+    assert_eq!(
+        stored.and_then(|auth| auth.openai_api_key).as_deref(),
+        Some("sk-test-key")
+    );
+    let account = account(&app).await?;
+    assert_eq!(account["requires_openai_auth"], json!(false));
+    assert_eq!(account["account"], json!({"type": "apiKey"}));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_blank_api_key_is_rejected() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let app = auth_app(&fixture);
+    for api_key in ["", "   "] {
+        let (status, body) = login(&app, json!({"type": "apiKey", "apiKey": api_key})).await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    }
+    let (status, _) = login(&app, json!({"type": "apiKey"})).await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let stored = load_auth_dot_json(&fixture.codex_home_path(), AuthCredentialsStoreMode::File)?;
+    assert!(stored.is_none());
+    assert_eq!(account(&app).await?["requires_openai_auth"], json!(true));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_chatgpt_logins_are_isolated_by_login_id() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token")
+        .with_account_logins(AccountLogins::with_callback_port(0));
+    let mut completions = state.account_logins.subscribe();
+    let app = auth_routes(state.clone());
+
+    let mut logins = Vec::new();
+    for _ in 0..2 {
+        let (status, body) = login(&app, json!({"type": "chatgpt"})).await?;
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(body["type"], json!("chatgpt"));
+        let auth_url = body["auth_url"].as_str().unwrap_or_default();
+        assert!(auth_url.contains("redirect_uri=http"), "{auth_url}");
+        logins.push(body);
+    }
+    let (first, second) = (&logins[0]["login_id"], &logins[1]["login_id"]);
+    assert_ne!(first, second);
+    assert_ne!(logins[0]["auth_url"], logins[1]["auth_url"]);
+
+    // Cancelling one login leaves the other running.
+    let (status, body) = cancel(&app, first).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body, json!({"status": "canceled"}));
+    let first_id = first.as_str().unwrap_or_default();
+    let second_id = second.as_str().unwrap_or_default();
+    assert!(!state.account_logins.is_active(first_id));
+    assert!(state.account_logins.is_active(second_id));
+
+    let completed = tokio::time::timeout(Duration::from_secs(5), completions.recv()).await??;
+    assert_eq!(completed.event_type, "account/login/completed");
+    let completed: Value = serde_json::from_str(&completed.data)?;
+    assert_eq!(completed["params"]["loginId"], *first);
+    assert_eq!(completed["params"]["success"], json!(false));
+
+    let (status, _) = cancel(&app, first).await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = cancel(&app, second).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(account(&app).await?["requires_openai_auth"], json!(true));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_second_chatgpt_login_on_the_oauth_port_is_refused() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    // The production configuration: every login shares the OAuth client's
+    // fixed callback port.
+    let state =
+        create_test_state(&fixture, "test-token").with_account_logins(AccountLogins::default());
+    let app = auth_routes(state.clone());
+
+    let (status, first) = login(&app, json!({"type": "chatgpt"})).await?;
+    assert_eq!(status, StatusCode::OK, "{first}");
+    let first_id = first["login_id"].as_str().unwrap_or_default();
+
+    let (status, body) = login(&app, json!({"type": "chatgpt"})).await?;
+    assert_eq!(status, StatusCode::CONFLICT, "{body}");
+    assert_eq!(body["code"], json!("login_in_progress"));
+    assert_eq!(body["login_id"], first["login_id"]);
+    // The refused login did not take the port over.
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(state.account_logins.is_active(first_id));
+
+    let (status, _) = cancel(&app, &first["login_id"]).await?;
+    assert_eq!(status, StatusCode::OK);
+    let (status, second) = login(&app, json!({"type": "chatgpt"})).await?;
+    assert_eq!(status, StatusCode::OK, "{second}");
+    assert_ne!(second["login_id"], first["login_id"]);
+    let (status, _) = cancel(&app, &second["login_id"]).await?;
+    assert_eq!(status, StatusCode::OK);
+    Ok(())
+}
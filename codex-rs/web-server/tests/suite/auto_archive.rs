@@ -0,0 +1,146 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use codex_protocol::ThreadId;
+use codex_web_server::auto_archive::AutoArchivePolicy;
+use codex_web_server::auto_archive::AutoArchivedThread;
+use codex_web_server::auto_archive::sweep_idle_threads;
+use codex_web_server::event_pump::EventPump;
+use codex_web_server::handlers;
+use codex_web_server::metrics::MetricsSnapshot;
+use codex_web_server::state::SessionStore;
+use codex_web_server::state::WebServerState;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use std::time::Instant;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+fn threads_router(state: WebServerState) -> Router {
+    Router::new()
+        .route("/api/v2/threads", get(handlers::threads::list_threads))
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .with_state(state)
+}
+
+async fn mark_auto_archived(state: &WebServerState, thread_id: ThreadId) {
+    state.auto_archived.lock().await.insert(
+        thread_id,
+        AutoArchivedThread {
+            archived_at: 1_768_737_600,
+            rollout_path: None,
+        },
+    );
+}
+
+#[tokio::test]
+async fn test_idle_timer_resets_on_activity_and_pauses_while_streaming() -> Result<()> {
+    let mut sessions = SessionStore::new();
+    let thread_id = ThreadId::new();
+
+    sessions.touch(thread_id);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let idle = sessions.idle_for(thread_id, Instant::now()).expect("idle");
+    assert!(idle >= Duration::from_millis(20));
+
+    let _events = sessions.register_stream(thread_id, EventPump::new);
+    assert_eq!(sessions.idle_for(thread_id, Instant::now()), None);
+
+    sessions.unregister_stream(thread_id);
+    let idle = sessions.idle_for(thread_id, Instant::now()).expect("idle");
+    assert!(idle < Duration::from_millis(20));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sweep_leaves_state_untouched_without_idle_threads() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+
+    let archived = sweep_idle_threads(&state, Duration::ZERO).await;
+
+    assert_eq!(archived, Vec::<ThreadId>::new());
+    assert_eq!(
+        state.metrics.snapshot(),
+        MetricsSnapshot {
+            auto_archived_threads: 0,
+            v1_requests: BTreeMap::new(),
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn test_policy_sweep_interval_follows_idle_duration() {
+    assert_eq!(
+        AutoArchivePolicy::new(Duration::from_secs(2)).sweep_interval,
+        Duration::from_secs(1)
+    );
+    assert_eq!(
+        AutoArchivePolicy::new(Duration::from_secs(8 * 3600)).sweep_interval,
+        Duration::from_secs(300)
+    );
+}
+
+#[tokio::test]
+async fn test_auto_archived_thread_emits_archived_event() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = ThreadId::new();
+    mark_auto_archived(&state, thread_id).await;
+
+    let request = Request::builder()
+        .uri(format!("/api/v2/threads/{thread_id}/events"))
+        .body(Body::empty())?;
+    let response = threads_router(state).oneshot(request).await?;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+    let body = String::from_utf8(body.to_vec())?;
+    assert!(body.contains("event: thread/archived"));
+    assert!(body.contains(&thread_id.to_string()));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_listing_marks_auto_archived_threads() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = ThreadId::new();
+    mark_auto_archived(&state, thread_id).await;
+
+    let request = Request::builder()
+        .uri("/api/v2/threads")
+        .body(Body::empty())?;
+    let response = threads_router(state).oneshot(request).await?;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+    let body: serde_json::Value = serde_json::from_slice(&body)?;
+    assert_eq!(
+        body,
+        json!({
+            "thread_ids": [thread_id.to_string()],
+            "data": [{
+                "thread_id": thread_id.to_string(),
+                "name": null,
+                "status": "auto_archived",
+                "active": false,
+                "run_status": "archived",
+                "pinned": false,
+                "notifications": true,
+                "created_at": null,
+                "updated_at": null,
+            }],
+            "next_cursor": null,
+        })
+    );
+    Ok(())
+}
@@ -0,0 +1,122 @@
+//! `POST /api/v2/commands` on Windows, where commands run in the Windows
+//! sandbox instead of BoxLite.
+
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use codex_web_server::handlers::capabilities::get_capabilities;
+use codex_web_server::handlers::commands::execute_command;
+use serde_json::Value;
+use serde_json::json;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+fn windows_sandbox_tests_enabled() -> bool {
+    match std::env::var("CODEX_WINDOWS_SANDBOX_TESTS") {
+        Ok(value) => value == "1" || value.eq_ignore_ascii_case("true"),
+        Err(_) => false,
+    }
+}
+
+/// Writes a config with the given `[windows] sandbox` mode, or none.
+fn commands_app(fixture: &TestFixture, sandbox: Option<&str>) -> Result<Router> {
+    let windows = sandbox
+        .map(|mode| format!("\n[windows]\nsandbox = \"{mode}\"\n"))
+        .unwrap_or_default();
+    fixture.create_test_config(&format!(
+        r#"
+model = "test-model"
+approval_policy = "never"
+sandbox_mode = "read-only"
+{windows}"#
+    ))?;
+    Ok(Router::new()
+        .route("/api/v2/commands", post(execute_command))
+        .route("/api/v2/capabilities", get(get_capabilities))
+        .with_state(create_test_state(fixture, "test-token")))
+}
+
+async fn call(app: &Router, request: Request<Body>) -> Result<(StatusCode, Value)> {
+    let response = app.clone().oneshot(request).await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body).unwrap_or(Value::Null)))
+}
+
+async fn run_command(app: &Router, command: &[&str]) -> Result<(StatusCode, Value)> {
+    call(
+        app,
+        Request::builder()
+            .method("POST")
+            .uri("/api/v2/commands")
+            .header("content-type", "application/json")
+            .body(Body::from(json!({ "command": command }).to_string()))?,
+    )
+    .await
+}
+
+async fn capabilities(app: &Router) -> Result<Value> {
+    let (status, body) = call(
+        app,
+        Request::builder()
+            .uri("/api/v2/capabilities")
+            .body(Body::empty())?,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    Ok(body)
+}
+
+#[tokio::test]
+async fn test_capabilities_report_the_configured_windows_sandbox_level() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    for (sandbox, expected) in [
+        (None, "disabled"),
+        (Some("unelevated"), "restricted-token"),
+        (Some("elevated"), "elevated"),
+    ] {
+        let app = commands_app(&fixture, sandbox)?;
+        assert_eq!(
+            capabilities(&app).await?["windows_sandbox_level"],
+            json!(expected)
+        );
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_commands_explain_how_to_enable_a_disabled_windows_sandbox() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let app = commands_app(&fixture, None)?;
+
+    let (status, body) = run_command(&app, &["cmd", "/c", "echo", "hello"]).await?;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    let error = body["error"].as_str().unwrap_or_default();
+    assert!(error.contains("[windows] sandbox"), "{error}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_commands_run_in_the_unelevated_windows_sandbox() -> Result<()> {
+    if !windows_sandbox_tests_enabled() {
+        eprintln!(
+            "Skipping Windows sandbox integration tests; set CODEX_WINDOWS_SANDBOX_TESTS=1 to enable."
+        );
+        return Ok(());
+    }
+    let fixture = TestFixture::new().await?;
+    let app = commands_app(&fixture, Some("unelevated"))?;
+
+    let (status, body) = run_command(&app, &["cmd", "/c", "echo", "hello"]).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["exit_code"], json!(0));
+    assert_eq!(body["stdout"].as_str().map(str::trim), Some("hello"));
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::patch;
+use axum::routing::post;
+use codex_web_server::handlers::config::batch_write_config;
+use codex_web_server::handlers::threads::create_thread;
+use serde_json::Value;
+use serde_json::json;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+/// Custom provider that does not need auth; starting a thread never calls
+/// the model.
+const OFFLINE_CONFIG: &str = r#"
+model = "test-model"
+approval_policy = "never"
+sandbox_mode = "read-only"
+model_provider = "offline"
+
+[model_providers.offline]
+name = "Offline provider"
+base_url = "http://127.0.0.1:9/v1"
+wire_api = "responses"
+"#;
+
+async fn send(app: &Router, method: &str, uri: &str, body: Value) -> Result<(StatusCode, Value)> {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))?;
+    let response = app.clone().oneshot(request).await?;
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+fn app(fixture: &TestFixture) -> Router {
+    Router::new()
+        .route("/api/v2/threads", post(create_thread))
+        .route("/api/v2/config", patch(batch_write_config))
+        .with_state(create_test_state(fixture, "test-token"))
+}
+
+#[tokio::test]
+async fn test_thread_created_after_write_uses_written_value() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(OFFLINE_CONFIG)?;
+    let app = app(&fixture);
+
+    for i in 0..10 {
+        let model = format!("written-model-{i}");
+        let (status, written) = send(
+            &app,
+            "PATCH",
+            "/api/v2/config",
+            json!({
+                "edits": [{ "keyPath": "model", "value": model, "mergeStrategy": "replace" }]
+            }),
+        )
+        .await?;
+        assert_eq!(status, StatusCode::OK, "{written}");
+
+        let (status, created) = send(
+            &app,
+            "POST",
+            "/api/v2/threads",
+            json!({
+                "cwd": fixture.codex_home.path(),
+                "min_config_version": written["version"],
+            }),
+        )
+        .await?;
+        assert_eq!(status, StatusCode::OK, "{created}");
+        assert_eq!(created["model"], json!(model));
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unreached_config_version_returns_conflict() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(OFFLINE_CONFIG)?;
+    let app = app(&fixture);
+
+    let (status, body) = send(
+        &app,
+        "POST",
+        "/api/v2/threads",
+        json!({ "min_config_version": "sha256:never-written" }),
+    )
+    .await?;
+
+    assert_eq!(status, StatusCode::CONFLICT, "{body}");
+    assert_eq!(body["min_config_version"], json!("sha256:never-written"));
+    assert!(
+        body["loaded_version"]
+            .as_str()
+            .is_some_and(|version| version.starts_with("sha256:")),
+        "{body}"
+    );
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::patch;
+use codex_core::config::Config;
+use codex_core::config::ConfigBuilder;
+use codex_web_server::config_loader::ConfigLoader;
+use codex_web_server::handlers::mcp::update_mcp_server;
+use serde_json::Value;
+use serde_json::json;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tower::ServiceExt;
+
+use crate::common::TEST_CONFIG;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+/// Loader that counts underlying parses, each taking `delay`, and fails while
+/// `fail` is set.
+fn counting_loader(
+    config: Config,
+    delay: Duration,
+    fail: Arc<AtomicBool>,
+) -> (Arc<ConfigLoader>, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&calls);
+    let loader = ConfigLoader::new(Arc::new(move || {
+        let counter = Arc::clone(&counter);
+        let config = config.clone();
+        let fail = Arc::clone(&fail);
+        Box::pin(async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(delay).await;
+            if fail.load(Ordering::SeqCst) {
+                return Err(std::io::Error::other("config.toml is invalid"));
+            }
+            Ok(config)
+        })
+    }));
+    (Arc::new(loader), calls)
+}
+
+async fn fixture_config(fixture: &TestFixture) -> Result<Config> {
+    fixture.create_test_config(TEST_CONFIG)?;
+    Ok(ConfigBuilder::default()
+        .codex_home(fixture.codex_home_path())
+        .build()
+        .await?)
+}
+
+async fn load_concurrently(loader: &Arc<ConfigLoader>, count: usize) -> Result<Vec<String>> {
+    let handles: Vec<_> = (0..count)
+        .map(|_| {
+            let loader = Arc::clone(loader);
+            tokio::spawn(async move {
+                loader
+                    .load()
+                    .await
+                    .map(|config| config.model.unwrap_or_default())
+                    .map_err(|err| format!("{err:?}"))
+            })
+        })
+        .collect();
+    let mut results = Vec::new();
+    for handle in handles {
+        results.push(handle.await?.unwrap_or_else(|err| err));
+    }
+    Ok(results)
+}
+
+#[tokio::test]
+async fn test_concurrent_loads_share_one_parse() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let config = fixture_config(&fixture).await?;
+    let (loader, calls) = counting_loader(
+        config,
+        Duration::from_millis(200),
+        Arc::new(AtomicBool::new(false)),
+    );
+
+    let results = load_concurrently(&loader, 10).await?;
+
+    assert_eq!(results, vec!["test-model".to_string(); 10]);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // Finished loads are not cached.
+    loader
+        .load()
+        .await
+        .map_err(|err| anyhow::anyhow!("{err:?}"))?;
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_failed_load_reaches_every_waiter_and_is_retried() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let config = fixture_config(&fixture).await?;
+    let fail = Arc::new(AtomicBool::new(true));
+    let (loader, calls) = counting_loader(config, Duration::from_millis(200), Arc::clone(&fail));
+
+    let results = load_concurrently(&loader, 10).await?;
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert!(
+        results
+            .iter()
+            .all(|result| result.contains("Failed to load config: config.toml is invalid"))
+    );
+
+    fail.store(false, Ordering::SeqCst);
+    let config = loader
+        .load()
+        .await
+        .map_err(|err| anyhow::anyhow!("{err:?}"))?;
+    assert_eq!(config.model.as_deref(), Some("test-model"));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_mcp_update_does_not_reuse_a_load_started_before_the_write() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(&format!(
+        "{TEST_CONFIG}\n[mcp_servers.docs]\ncommand = \"docs-server\"\n"
+    ))?;
+    // Each parse reads config.toml right away and returns 200ms later.
+    let codex_home = fixture.codex_home_path();
+    let loader = ConfigLoader::new(Arc::new(move || {
+        let codex_home = codex_home.clone();
+        Box::pin(async move {
+            let config = ConfigBuilder::default()
+                .codex_home(codex_home)
+                .build()
+                .await;
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            config
+        })
+    }));
+    let state = create_test_state(&fixture, "test-token").with_config_loader(loader);
+
+    // Keep a load in flight throughout, so one that read the file before the
+    // write is still running when the handler reloads.
+    let background = {
+        let loader = Arc::clone(&state.config_loader);
+        tokio::spawn(async move {
+            loop {
+                let loader = Arc::clone(&loader);
+                tokio::spawn(async move {
+                    let _ = loader.load().await;
+                });
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+    };
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let app = Router::new()
+        .route("/api/v2/mcp/servers/{name}", patch(update_mcp_server))
+        .with_state(state);
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("PATCH")
+                .uri("/api/v2/mcp/servers/docs")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({"enabled": false}).to_string()))?,
+        )
+        .await?;
+    background.abort();
+
+    let status = response.status();
+    let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body, json!({"name": "docs", "enabled": false}));
+    Ok(())
+}
@@ -0,0 +1,223 @@
+//! Config writes to the global and project layers, and reads that attribute
+//! each value to its layer.
+
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use codex_web_server::handlers::config::batch_write_config;
+use codex_web_server::handlers::config::read_config;
+use codex_web_server::handlers::config::write_config_value;
+use serde_json::Value;
+use serde_json::json;
+use std::path::Path;
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+/// A project directory the user config trusts, so its layer is loaded.
+fn trusted_project(fixture: &TestFixture) -> Result<TempDir> {
+    let project = TempDir::new()?;
+    let root = project.path().canonicalize()?;
+    fixture.create_test_config(&format!(
+        r#"
+model = "test-model"
+approval_policy = "never"
+sandbox_mode = "read-only"
+
+[projects."{}"]
+trust_level = "trusted"
+"#,
+        root.display()
+    ))?;
+    Ok(project)
+}
+
+fn config_app(fixture: &TestFixture) -> Router {
+    Router::new()
+        .route(
+            "/api/v2/config",
+            get(read_config)
+                .put(write_config_value)
+                .patch(batch_write_config),
+        )
+        .with_state(create_test_state(fixture, "test-token"))
+}
+
+async fn send(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> Result<(StatusCode, Value)> {
+    let request = Request::builder().method(method).uri(uri);
+    let request = match body {
+        Some(body) => request
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))?,
+        None => request.body(Body::empty())?,
+    };
+    let response = app.clone().oneshot(request).await?;
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body).unwrap_or(Value::Null)))
+}
+
+fn project_config_path(project: &Path) -> Result<String> {
+    Ok(project
+        .canonicalize()?
+        .join(".codex")
+        .join("config.toml")
+        .display()
+        .to_string())
+}
+
+#[tokio::test]
+async fn test_writes_land_in_their_scope_and_reads_attribute_each_value() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let project = trusted_project(&fixture)?;
+    let cwd = project.path().canonicalize()?.display().to_string();
+    let app = config_app(&fixture);
+
+    let (status, global) = send(
+        &app,
+        "PUT",
+        "/api/v2/config",
+        Some(json!({
+            "key_path": "model",
+            "value": "global-model",
+            "merge_strategy": "replace",
+            "scope": "global"
+        })),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{global}");
+    let (status, project_write) = send(
+        &app,
+        "PATCH",
+        "/api/v2/config",
+        Some(json!({
+            "edits": [
+                {"keyPath": "model_reasoning_effort", "value": "high", "mergeStrategy": "replace"}
+            ],
+            "scope": "project",
+            "cwd": cwd
+        })),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{project_write}");
+    let project_file = project_config_path(project.path())?;
+    assert_eq!(project_write["filePath"], json!(project_file));
+    let contents = std::fs::read_to_string(&project_file)?;
+    assert!(
+        contents.contains("model_reasoning_effort = \"high\""),
+        "{contents}"
+    );
+    let user_config = std::fs::read_to_string(fixture.codex_home_path().join("config.toml"))?;
+    assert!(
+        !user_config.contains("model_reasoning_effort"),
+        "{user_config}"
+    );
+
+    let query = format!("/api/v2/config?include_layers=true&cwd={cwd}");
+    let (status, read) = send(&app, "GET", &query, None).await?;
+    assert_eq!(status, StatusCode::OK, "{read}");
+    assert_eq!(read["origins"]["model"]["name"]["type"], json!("user"));
+    assert_eq!(
+        read["origins"]["model_reasoning_effort"]["name"],
+        json!({
+            "type": "project",
+            "dotCodexFolder": project.path().canonicalize()?.join(".codex")
+        })
+    );
+    assert_eq!(
+        read["origins"]["model_reasoning_effort"]["version"],
+        project_write["version"]
+    );
+    let layers = read["layers"].as_array().cloned().unwrap_or_default();
+    assert!(
+        layers
+            .iter()
+            .any(|layer| layer["name"]["type"] == json!("project")
+                && layer["config"]["model_reasoning_effort"] == json!("high")),
+        "{layers:?}"
+    );
+
+    // Without a cwd, only the global layers are read.
+    let (status, global_read) = send(&app, "GET", "/api/v2/config", None).await?;
+    assert_eq!(status, StatusCode::OK, "{global_read}");
+    assert!(global_read["origins"]["model_reasoning_effort"].is_null());
+    assert!(global_read.get("layers").is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_writes_reject_paths_outside_the_scope() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let project = trusted_project(&fixture)?;
+    let cwd = project.path().canonicalize()?.display().to_string();
+    let elsewhere = TempDir::new()?;
+    let app = config_app(&fixture);
+    let write = |extra: Value| {
+        let mut body = json!({
+            "key_path": "model",
+            "value": "other-model",
+            "merge_strategy": "replace"
+        });
+        if let (Some(body), Some(extra)) = (body.as_object_mut(), extra.as_object()) {
+            body.extend(extra.clone());
+        }
+        body
+    };
+
+    for (request, reason) in [
+        (json!({"scope": "project"}), "project scope without a cwd"),
+        (
+            json!({"file_path": elsewhere.path().join("config.toml")}),
+            "global file outside codex_home",
+        ),
+        (
+            json!({
+                "scope": "project",
+                "cwd": cwd,
+                "file_path": fixture.codex_home_path().join("config.toml")
+            }),
+            "project scope naming the user config",
+        ),
+        (
+            json!({
+                "scope": "project",
+                "cwd": cwd,
+                "file_path": elsewhere.path().join(".codex").join("config.toml")
+            }),
+            "project file outside the project",
+        ),
+        (
+            json!({"scope": "project", "cwd": "relative/dir"}),
+            "relative cwd",
+        ),
+    ] {
+        let (status, body) = send(&app, "PUT", "/api/v2/config", Some(write(request))).await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{reason}: {body}");
+    }
+    assert!(!elsewhere.path().join(".codex").exists());
+
+    let (status, body) = send(
+        &app,
+        "PUT",
+        "/api/v2/config",
+        Some(write(json!({
+            "scope": "project",
+            "cwd": cwd,
+            "expected_version": "sha256:stale"
+        }))),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CONFLICT, "{body}");
+    assert_eq!(body["code"], json!("config_version_conflict"));
+    Ok(())
+}
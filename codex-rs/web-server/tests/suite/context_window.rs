@@ -0,0 +1,174 @@
+//! The context meter in thread details and `thread/tokenUsage/updated`.
+
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use codex_protocol::openai_models::ModelInfo;
+use codex_protocol::protocol::TokenUsage;
+use codex_protocol::protocol::TokenUsageInfo;
+use codex_web_server::context_window::ContextWindowUsage;
+use codex_web_server::handlers;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed_with_tokens;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use serde_json::Value;
+use serde_json::json;
+use std::path::PathBuf;
+use tower::ServiceExt;
+
+use crate::common::EventReader;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+use crate::common::start_mock_provider_thread_with;
+
+/// `large-window-model` and `small-window-model`.
+fn fixture_models() -> Result<(ModelInfo, ModelInfo)> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/model_info.json");
+    let [large, small]: [ModelInfo; 2] = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    Ok((large, small))
+}
+
+/// Usage whose last response saw `used` tokens.
+fn usage(used: i64) -> TokenUsageInfo {
+    let last = TokenUsage {
+        input_tokens: used,
+        total_tokens: used,
+        ..TokenUsage::default()
+    };
+    TokenUsageInfo {
+        total_token_usage: last.clone(),
+        last_token_usage: last,
+        model_context_window: None,
+    }
+}
+
+fn meter(used: i64, window: i64, percent: i64, compaction_at: i64) -> Option<ContextWindowUsage> {
+    Some(ContextWindowUsage {
+        used,
+        window,
+        percent,
+        compaction_at: Some(compaction_at),
+    })
+}
+
+#[test]
+fn test_meter_tracks_usage_across_the_compaction_threshold() -> Result<()> {
+    let (large, small) = fixture_models()?;
+
+    // 95% of 200k is usable; compaction defaults to 90% of the full window.
+    assert_eq!(
+        ContextWindowUsage::new(&usage(95_000), &large),
+        meter(95_000, 190_000, 50, 180_000)
+    );
+    assert_eq!(
+        ContextWindowUsage::new(&usage(180_000), &large),
+        meter(180_000, 190_000, 95, 180_000)
+    );
+    assert_eq!(
+        ContextWindowUsage::new(&usage(200_000), &large),
+        meter(200_000, 190_000, 100, 180_000)
+    );
+
+    // A turn that switches to the small model is measured against its own
+    // window and lower compaction limit: the same usage is past it.
+    assert_eq!(
+        ContextWindowUsage::new(&usage(24_000), &large),
+        meter(24_000, 190_000, 13, 180_000)
+    );
+    assert_eq!(
+        ContextWindowUsage::new(&usage(24_000), &small),
+        meter(24_000, 32_000, 75, 20_000)
+    );
+
+    let no_window = ModelInfo {
+        context_window: None,
+        ..small
+    };
+    assert_eq!(ContextWindowUsage::new(&usage(24_000), &no_window), None);
+    Ok(())
+}
+
+async fn get_json(app: &Router, uri: &str) -> Result<(StatusCode, Value)> {
+    let response = app
+        .clone()
+        .oneshot(Request::builder().uri(uri).body(Body::empty())?)
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+#[tokio::test]
+async fn test_meter_is_streamed_with_token_usage_and_in_thread_details() -> Result<()> {
+    let server = start_mock_server().await;
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_assistant_message("msg-1", "done"),
+            ev_completed_with_tokens("resp-1", 9_000),
+        ]),
+    )
+    .await;
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = start_mock_provider_thread_with(
+        &fixture,
+        &state,
+        &server.uri(),
+        "model_context_window = 10000\nmodel_auto_compact_token_limit = 8000",
+    )
+    .await?;
+    let app = Router::new()
+        .route("/api/v2/threads/{id}", get(handlers::threads::get_thread))
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(handlers::turns::send_turn),
+        )
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .with_state(state);
+
+    let mut events = EventReader::open(&app, thread_id).await?;
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/v2/threads/{thread_id}/turns"))
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({"input": [{"type": "text", "text": "hello"}]}).to_string(),
+                ))?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // 95% of the configured 10k window is usable, and the usage is past the
+    // configured compaction limit.
+    let expected = json!({
+        "used": 9000,
+        "window": 9500,
+        "percent": 95,
+        "compaction_at": 8000
+    });
+    let updated = events.next_of("thread/tokenUsage/updated").await?;
+    assert_eq!(updated["params"]["context"], expected, "{updated}");
+    assert_eq!(
+        updated["params"]["tokenUsage"]["last"]["totalTokens"],
+        json!(9000)
+    );
+    events.next_of("turn/completed").await?;
+
+    let (status, details) = get_json(&app, &format!("/api/v2/threads/{thread_id}")).await?;
+    assert_eq!(status, StatusCode::OK, "{details}");
+    assert_eq!(details["context"], expected);
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::http::HeaderValue;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::http::header::ACCESS_CONTROL_ALLOW_ORIGIN;
+use axum::routing::get;
+use codex_web_server::cors::CorsOrigins;
+use tower::ServiceExt;
+
+const READ_ORIGIN: &str = "https://dashboard.example.com";
+const WRITE_ORIGIN: &str = "https://admin.example.com";
+
+fn cors_router() -> Router {
+    let origins = CorsOrigins {
+        read: vec![HeaderValue::from_static(READ_ORIGIN)],
+        write: vec![HeaderValue::from_static(WRITE_ORIGIN)],
+    };
+    Router::new()
+        .route(
+            "/api/v2/threads",
+            get(|| async { StatusCode::OK }).post(|| async { StatusCode::OK }),
+        )
+        .route(
+            "/api/v2/config",
+            get(|| async { StatusCode::OK }).put(|| async { StatusCode::OK }),
+        )
+        .route("/api/v2/usage", get(|| async { StatusCode::OK }))
+        .route(
+            "/api/v2/maintenance/storage",
+            get(|| async { StatusCode::OK }),
+        )
+        .layer(origins.into_layer())
+}
+
+async fn preflight(origin: &str, method: &str) -> Result<Option<HeaderValue>> {
+    let request = Request::builder()
+        .method("OPTIONS")
+        .uri("/api/v2/threads")
+        .header("origin", origin)
+        .header("access-control-request-method", method)
+        .body(Body::empty())?;
+    let response = cors_router().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    Ok(response.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).cloned())
+}
+
+#[tokio::test]
+async fn test_read_origin_preflight_allows_only_read_methods() -> Result<()> {
+    assert_eq!(
+        preflight(READ_ORIGIN, "GET").await?,
+        Some(HeaderValue::from_static(READ_ORIGIN))
+    );
+    assert_eq!(preflight(READ_ORIGIN, "POST").await?, None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_write_origin_preflight_allows_all_methods() -> Result<()> {
+    assert_eq!(
+        preflight(WRITE_ORIGIN, "GET").await?,
+        Some(HeaderValue::from_static(WRITE_ORIGIN))
+    );
+    assert_eq!(
+        preflight(WRITE_ORIGIN, "POST").await?,
+        Some(HeaderValue::from_static(WRITE_ORIGIN))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unknown_origin_is_rejected() -> Result<()> {
+    assert_eq!(preflight("https://evil.example.com", "GET").await?, None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_simple_request_uses_actual_method() -> Result<()> {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v2/threads")
+        .header("origin", READ_ORIGIN)
+        .body(Body::empty())?;
+    let response = cors_router().oneshot(request).await?;
+    assert_eq!(response.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN), None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_read_origin_may_post_sandbox_preview() -> Result<()> {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v2/threads/019bcfb9-4ea6-72e0-b43d-6b7e26ff0daf/sandbox/preview")
+        .header("origin", READ_ORIGIN)
+        .body(Body::empty())?;
+    let response = cors_router().oneshot(request).await?;
+    assert_eq!(
+        response.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN),
+        Some(&HeaderValue::from_static(READ_ORIGIN))
+    );
+    Ok(())
+}
+
+async fn allowed_origin(origin: &str, uri: &str) -> Result<Option<HeaderValue>> {
+    let request = Request::builder()
+        .uri(uri)
+        .header("origin", origin)
+        .body(Body::empty())?;
+    let response = cors_router().oneshot(request).await?;
+    Ok(response.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).cloned())
+}
+
+#[tokio::test]
+async fn test_admin_gets_are_write_only() -> Result<()> {
+    for uri in [
+        "/api/v2/config",
+        "/api/v2/usage",
+        "/api/v2/maintenance/storage",
+    ] {
+        assert_eq!(allowed_origin(READ_ORIGIN, uri).await?, None, "{uri}");
+        assert_eq!(
+            allowed_origin(WRITE_ORIGIN, uri).await?,
+            Some(HeaderValue::from_static(WRITE_ORIGIN)),
+            "{uri}"
+        );
+    }
+    // Other listings stay readable.
+    assert_eq!(
+        allowed_origin(READ_ORIGIN, "/api/v2/threads").await?,
+        Some(HeaderValue::from_static(READ_ORIGIN))
+    );
+    Ok(())
+}
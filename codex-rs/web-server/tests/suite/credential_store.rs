@@ -0,0 +1,140 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use codex_core::auth::AuthCredentialsStoreMode;
+use codex_keyring_store::CredentialStoreError;
+use codex_keyring_store::KeyringStore;
+use codex_keyring_store::tests::MockKeyringStore;
+use codex_web_server::credential_store::CredentialStoreSettings;
+use codex_web_server::credential_store::NO_CREDENTIAL_FALLBACK_FLAG;
+use codex_web_server::handlers;
+use serde_json::Value;
+use serde_json::json;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+/// A keyring whose backend is missing, as on a headless host.
+#[derive(Debug)]
+struct UnavailableKeyring;
+
+impl UnavailableKeyring {
+    fn error() -> CredentialStoreError {
+        CredentialStoreError::new(keyring::Error::PlatformFailure("no secret service".into()))
+    }
+}
+
+impl KeyringStore for UnavailableKeyring {
+    fn load(&self, _service: &str, _account: &str) -> Result<Option<String>, CredentialStoreError> {
+        Err(Self::error())
+    }
+
+    fn save(
+        &self,
+        _service: &str,
+        _account: &str,
+        _value: &str,
+    ) -> Result<(), CredentialStoreError> {
+        Err(Self::error())
+    }
+
+    fn delete(&self, _service: &str, _account: &str) -> Result<bool, CredentialStoreError> {
+        Err(Self::error())
+    }
+}
+
+fn settings(args: &[&str], env_value: Option<&str>) -> CredentialStoreSettings {
+    let args: Vec<String> = args.iter().map(ToString::to_string).collect();
+    CredentialStoreSettings::from_args_and_env(&args, env_value).unwrap_or_default()
+}
+
+#[test]
+fn test_store_mode_comes_from_flag_then_env() {
+    assert_eq!(
+        settings(&[], None).requested,
+        AuthCredentialsStoreMode::Keyring
+    );
+    assert_eq!(
+        settings(&[], Some("file")).requested,
+        AuthCredentialsStoreMode::File
+    );
+    assert_eq!(
+        settings(&["--credential-store=auto"], Some("file")).requested,
+        AuthCredentialsStoreMode::Auto
+    );
+    assert!(settings(&[], None).allow_fallback);
+    assert!(!settings(&[NO_CREDENTIAL_FALLBACK_FLAG], None).allow_fallback);
+    assert!(CredentialStoreSettings::from_args_and_env(&[], Some("vault")).is_err());
+}
+
+#[test]
+fn test_unavailable_keyring_falls_back_to_file() {
+    let keyring = settings(&[], None);
+    assert_eq!(
+        keyring.resolve(&MockKeyringStore::default()),
+        Ok(AuthCredentialsStoreMode::Keyring)
+    );
+    assert_eq!(
+        keyring.resolve(&UnavailableKeyring),
+        Ok(AuthCredentialsStoreMode::File)
+    );
+    assert_eq!(
+        settings(&["--credential-store=auto"], None).resolve(&UnavailableKeyring),
+        Ok(AuthCredentialsStoreMode::File)
+    );
+    // Explicit non-keyring modes never touch the keyring.
+    assert_eq!(
+        settings(&[], Some("ephemeral")).resolve(&UnavailableKeyring),
+        Ok(AuthCredentialsStoreMode::Ephemeral)
+    );
+}
+
+#[test]
+fn test_forbidden_fallback_names_the_problem() {
+    let err = settings(&[NO_CREDENTIAL_FALLBACK_FLAG], None)
+        .resolve(&UnavailableKeyring)
+        .err()
+        .map(|err| err.to_string())
+        .unwrap_or_default();
+    assert!(
+        err.contains("keyring credential storage is unavailable"),
+        "{err}"
+    );
+    assert!(err.contains("no secret service"), "{err}");
+    assert!(err.contains("CODEX_WEB_CREDENTIAL_STORE=file"), "{err}");
+
+    // A working keyring is fine without fallback.
+    assert_eq!(
+        settings(&[NO_CREDENTIAL_FALLBACK_FLAG], None).resolve(&MockKeyringStore::default()),
+        Ok(AuthCredentialsStoreMode::Keyring)
+    );
+}
+
+#[tokio::test]
+async fn test_account_reports_the_active_store() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let mode = settings(&[], None)
+        .resolve(&MockKeyringStore::default())
+        .unwrap_or(AuthCredentialsStoreMode::File);
+    let state = create_test_state(&fixture, "test-token").with_credential_store_mode(mode);
+    let app = Router::new()
+        .route("/api/v2/auth/account", get(handlers::auth::get_account))
+        .with_state(state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v2/auth/account")
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+    assert_eq!(body["credential_store"], json!("keyring"));
+    Ok(())
+}
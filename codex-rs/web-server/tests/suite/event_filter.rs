@@ -0,0 +1,153 @@
+//! `?events=` filtering of a thread's event stream.
+
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use codex_protocol::ThreadId;
+use codex_web_server::event_filter::EventFilter;
+use codex_web_server::handlers;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_message_item_added;
+use core_test_support::responses::ev_output_text_delta;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use serde_json::json;
+use tower::ServiceExt;
+
+use crate::common::EventReader;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+use crate::common::start_mock_provider_thread;
+
+#[test]
+fn test_filter_matches_whole_segments_and_keeps_approvals() -> Result<()> {
+    let filter: EventFilter = "turn, item/completed".parse()?;
+    for (event_type, allowed) in [
+        ("turn/started", true),
+        ("turn/completed", true),
+        ("item/completed", true),
+        ("item/started", false),
+        ("item/agentMessage/delta", false),
+        ("item/commandExecution/outputDelta", false),
+        ("thread/tokenUsage/updated", false),
+        // Approval requests block the turn, and a reset tells the client to
+        // refetch, so neither can be filtered out.
+        ("item/commandExecution/requestApproval", true),
+        ("item/fileChange/requestApproval", true),
+        ("stream/reset", true),
+    ] {
+        assert_eq!(filter.allows(event_type), allowed, "{event_type}");
+    }
+
+    let filter: EventFilter = "ite,item/".parse()?;
+    assert!(filter.allows("item/started"));
+    assert!(!"it".parse::<EventFilter>()?.allows("item/started"));
+
+    assert!(EventFilter::default().allows("item/agentMessage/delta"));
+    assert!("".parse::<EventFilter>().is_err());
+    assert!("turn,,item".parse::<EventFilter>().is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_filtered_stream_skips_deltas_but_sends_completions() -> Result<()> {
+    let server = start_mock_server().await;
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_message_item_added("msg-1", ""),
+            ev_output_text_delta("hel"),
+            ev_output_text_delta("lo"),
+            ev_assistant_message("msg-1", "hello"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = start_mock_provider_thread(&fixture, &state, &server.uri()).await?;
+    let app = Router::new()
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(handlers::turns::send_turn),
+        )
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .with_state(state);
+
+    let mut everything = EventReader::open(&app, thread_id).await?;
+    let mut filtered =
+        EventReader::open_with_query(&app, thread_id, "?events=turn,item/completed").await?;
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/v2/threads/{thread_id}/turns"))
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({"input": [{"type": "text", "text": "hello"}]}).to_string(),
+                ))?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // The unfiltered stream shows the deltas were sent.
+    everything.next_of("item/agentMessage/delta").await?;
+    everything.next_of("turn/completed").await?;
+
+    let mut event_types = Vec::new();
+    loop {
+        let frame = filtered.next_frame().await?;
+        let done = frame.event_type == "turn/completed";
+        event_types.push(frame.event_type);
+        if done {
+            break;
+        }
+    }
+    assert!(
+        event_types
+            .iter()
+            .all(|event_type| event_type.starts_with("turn/") || event_type == "item/completed"),
+        "{event_types:?}"
+    );
+    assert_eq!(
+        event_types.first().map(String::as_str),
+        Some("turn/started")
+    );
+    assert!(
+        event_types
+            .iter()
+            .any(|event_type| event_type == "item/completed"),
+        "{event_types:?}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_empty_events_filter_is_rejected() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let app = Router::new()
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .with_state(create_test_state(&fixture, "test-token"));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!(
+                    "/api/v2/threads/{}/events?events=",
+                    ThreadId::new()
+                ))
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    Ok(())
+}
@@ -0,0 +1,211 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use codex_protocol::ThreadId;
+use codex_web_server::event_ids::EventId;
+use codex_web_server::event_ids::EventSequencer;
+use codex_web_server::handlers;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use futures::StreamExt;
+use serde_json::json;
+use std::time::Duration;
+use tower::ServiceExt;
+
+use crate::common::EventReader;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+use crate::common::start_mock_provider_thread;
+
+#[test]
+fn test_event_id_uses_the_composite_form() -> Result<()> {
+    let id: EventId = "3:17".parse().map_err(anyhow::Error::msg)?;
+    assert_eq!(id, EventId { epoch: 3, seq: 17 });
+    assert_eq!(id.to_string(), "3:17");
+
+    for invalid in ["17", "", "a:1", "1:", "1:2:3", "-1:2"] {
+        assert!(invalid.parse::<EventId>().is_err(), "{invalid}");
+    }
+    Ok(())
+}
+
+#[test]
+fn test_restart_starts_a_new_epoch_without_reusing_seq() {
+    let sequencer = EventSequencer::new(1);
+    let thread_id = ThreadId::new();
+    let other = ThreadId::new();
+
+    assert_eq!(sequencer.next(thread_id), EventId { epoch: 1, seq: 1 });
+    assert_eq!(sequencer.next(thread_id), EventId { epoch: 1, seq: 2 });
+    assert_eq!(sequencer.restart(thread_id), 2);
+    assert_eq!(sequencer.next(thread_id), EventId { epoch: 2, seq: 3 });
+    // Threads count separately.
+    assert_eq!(sequencer.next(other), EventId { epoch: 1, seq: 1 });
+}
+
+#[test]
+fn test_a_new_server_run_starts_at_a_later_epoch() {
+    let thread_id = ThreadId::new();
+    let earlier = EventSequencer::default();
+    let first = earlier.next(thread_id);
+    std::thread::sleep(Duration::from_millis(5));
+
+    // The same thread on the next run counts seq from the start again, so
+    // only the epoch tells its ids apart.
+    let later = EventSequencer::default().next(thread_id);
+    assert_eq!(later.seq, first.seq);
+    assert!(later.epoch > first.epoch, "{first} then {later}");
+}
+
+/// Sends a v2 turn with `text`.
+async fn send_turn(app: &Router, thread_id: ThreadId, text: &str) -> Result<()> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/v2/threads/{thread_id}/turns"))
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({"input": [{"type": "text", "text": text}]}).to_string(),
+                ))?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    Ok(())
+}
+
+/// Ids of the thread's events up to the turn's completion.
+async fn ids_through_turn(events: &mut EventReader) -> Result<Vec<EventId>> {
+    let mut ids = Vec::new();
+    loop {
+        let frame = events.next_frame().await?;
+        let Some(id) = frame.id else {
+            // Only the per-stream preamble goes without an id.
+            anyhow::ensure!(
+                frame.event_type == "configWarning",
+                "{} has no id",
+                frame.event_type
+            );
+            continue;
+        };
+        ids.push(id.parse().map_err(anyhow::Error::msg)?);
+        if frame.event_type == "turn/completed" {
+            return Ok(ids);
+        }
+    }
+}
+
+/// The first frame of the v1 stream, which is the deprecation notice.
+async fn first_v1_frame(app: &Router, thread_id: ThreadId) -> Result<String> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/v1/threads/{thread_id}/events"))
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let mut stream = response.into_body().into_data_stream();
+    let mut buffer = String::new();
+    tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            if let Some(end) = buffer.find("\n\n") {
+                return Ok(buffer[..end].to_string());
+            }
+            match stream.next().await {
+                Some(chunk) => buffer.push_str(&String::from_utf8_lossy(&chunk?)),
+                None => anyhow::bail!("event stream ended before the first event"),
+            }
+        }
+    })
+    .await?
+}
+
+#[tokio::test]
+async fn test_ids_continue_across_reconnects_and_mark_restarts() -> Result<()> {
+    let server = start_mock_server().await;
+    for (response_id, text) in [("resp-1", "first"), ("resp-2", "second")] {
+        mount_sse_once(
+            &server,
+            sse(vec![
+                ev_response_created(response_id),
+                ev_assistant_message(&format!("msg-{response_id}"), text),
+                ev_completed(response_id),
+            ]),
+        )
+        .await;
+    }
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let event_ids = state.event_ids.clone();
+    let thread_id = start_mock_provider_thread(&fixture, &state, &server.uri()).await?;
+    let app = Router::new()
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(handlers::turns::send_turn),
+        )
+        .route("/api/v1/threads/{id}/events", get(handlers::stream_events))
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .with_state(state);
+
+    // A notice sent to one stream only leaves the client's last id alone.
+    let notice = first_v1_frame(&app, thread_id).await?;
+    assert!(notice.contains("deprecationNotice"), "{notice}");
+    assert!(
+        !notice.lines().any(|line| line.starts_with("id: ")),
+        "{notice}"
+    );
+
+    let mut first = EventReader::open(&app, thread_id).await?;
+    send_turn(&app, thread_id, "one").await?;
+    let ids = ids_through_turn(&mut first).await?;
+    let epoch = ids[0].epoch;
+    assert!(ids.iter().all(|id| id.epoch == epoch), "{ids:?}");
+    // Every event of the thread is numbered, so one stream sees no gaps.
+    assert!(
+        ids.windows(2).all(|pair| pair[1].seq == pair[0].seq + 1),
+        "{ids:?}"
+    );
+    drop(first);
+
+    // Simulate the event source restarting between connections.
+    event_ids.restart(thread_id);
+    let mut reconnected = EventReader::open(&app, thread_id).await?;
+    send_turn(&app, thread_id, "two").await?;
+    let restarted = ids_through_turn(&mut reconnected).await?;
+    assert_eq!(restarted[0].epoch, epoch + 1);
+    assert!(restarted[0].seq > ids[ids.len() - 1].seq);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/v2/threads/{thread_id}/events"))
+                .header("last-event-id", "42")
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/v2/threads/{thread_id}/events"))
+                .header("last-event-id", restarted[0].to_string())
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    Ok(())
+}
@@ -0,0 +1,182 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use codex_core::config::ConfigBuilder;
+use codex_protocol::ThreadId;
+use codex_web_server::event_pump::EventPump;
+use codex_web_server::handlers;
+use codex_web_server::handlers::threads::start_configured_thread;
+use codex_web_server::state::SessionStore;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use serde_json::Value;
+use serde_json::json;
+use std::time::Duration;
+use tower::ServiceExt;
+
+use crate::common::EventReader;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+use crate::common::start_mock_provider_thread;
+
+#[test]
+fn test_pump_outlives_its_streams_only_until_the_grace_period_ends() {
+    let mut sessions = SessionStore::new();
+    let thread_id = ThreadId::new();
+    let mut started = 0;
+
+    let _first = sessions.register_stream(thread_id, || {
+        started += 1;
+        EventPump::new()
+    });
+    let _second = sessions.register_stream(thread_id, || {
+        started += 1;
+        EventPump::new()
+    });
+    assert_eq!(started, 1);
+    assert_eq!(sessions.unregister_stream(thread_id), None);
+    let Some(registrations) = sessions.unregister_stream(thread_id) else {
+        panic!("the last stream should start the grace period");
+    };
+
+    // A client that came back during the grace period keeps the pump.
+    let _third = sessions.register_stream(thread_id, EventPump::new);
+    sessions.stop_idle_pump(thread_id, registrations);
+    let Some(registrations) = sessions.unregister_stream(thread_id) else {
+        panic!("the pump should still be running");
+    };
+
+    sessions.stop_idle_pump(thread_id, registrations);
+    assert_eq!(sessions.unregister_stream(thread_id), None);
+}
+
+/// The ids of the `item/completed` events up to the agent message.
+async fn completed_items(events: &mut EventReader) -> Result<Vec<Value>> {
+    let mut ids = Vec::new();
+    loop {
+        let completed = events.next_of("item/completed").await?;
+        ids.push(completed["item"]["id"].clone());
+        if completed["item"]["type"] == json!("agentMessage") {
+            return Ok(ids);
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_concurrent_streams_each_receive_every_event() -> Result<()> {
+    let server = start_mock_server().await;
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_assistant_message("msg-1", "hello to both"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(&format!(
+        r#"
+model = "test-model"
+approval_policy = "never"
+sandbox_mode = "read-only"
+model_provider = "mock"
+
+[model_providers.mock]
+name = "Mock provider"
+base_url = "{}/v1"
+wire_api = "responses"
+request_max_retries = 0
+stream_max_retries = 0
+"#,
+        server.uri()
+    ))?;
+    let mut config = ConfigBuilder::default()
+        .codex_home(fixture.codex_home_path())
+        .build()
+        .await?;
+    config.cwd = fixture.codex_home_path();
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = start_configured_thread(&state, config).await?.thread_id;
+    let app = Router::new()
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(handlers::turns::send_turn),
+        )
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .with_state(state);
+
+    let mut first = EventReader::open(&app, thread_id).await?;
+    let mut second = EventReader::open(&app, thread_id).await?;
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/v2/threads/{thread_id}/turns"))
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({"input": [{"type": "text", "text": "hi"}]}).to_string(),
+                ))?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let first_items = completed_items(&mut first).await?;
+    assert_eq!(first_items, completed_items(&mut second).await?);
+    let completed = first.next_of("turn/completed").await?;
+    assert_eq!(second.next_of("turn/completed").await?, completed);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_turn_span_closes_without_an_event_stream() -> Result<()> {
+    let server = start_mock_server().await;
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_assistant_message("msg-1", "nobody is watching"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = start_mock_provider_thread(&fixture, &state, &server.uri()).await?;
+    let app = Router::new()
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(handlers::turns::send_turn),
+        )
+        .with_state(state.clone());
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/v2/threads/{thread_id}/turns"))
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({"input": [{"type": "text", "text": "hi"}]}).to_string(),
+                ))?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while !state.turn_spans.open_turns(thread_id).is_empty() {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    })
+    .await?;
+    Ok(())
+}
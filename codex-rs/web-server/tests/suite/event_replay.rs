@@ -0,0 +1,238 @@
+//! Replay of missed events to a stream that reconnects with `Last-Event-ID`.
+
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use codex_protocol::ThreadId;
+use codex_web_server::event_ids::EventId;
+use codex_web_server::event_pump::ThreadEvent;
+use codex_web_server::event_replay::EventReplay;
+use codex_web_server::event_replay::Replay;
+use codex_web_server::event_replay::StreamReset;
+use codex_web_server::handlers;
+use codex_web_server::state::WebServerState;
+use codex_web_server::thread_memory::ThreadMemory;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use serde_json::json;
+use tower::ServiceExt;
+
+use crate::common::EventReader;
+use crate::common::Frame;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+use crate::common::start_mock_provider_thread;
+
+fn event_id(seq: u64) -> EventId {
+    EventId { epoch: 1, seq }
+}
+
+/// The seqs and payloads of a replay, or `None` for a reset.
+fn replayed(replay: Replay) -> Option<Vec<(u64, String)>> {
+    match replay {
+        Replay::Events(events) => Some(
+            events
+                .into_iter()
+                .map(|(id, event)| (id.seq, event.data))
+                .collect(),
+        ),
+        Replay::Reset => None,
+    }
+}
+
+#[test]
+fn test_replay_covers_recorded_events_and_resets_past_eviction() {
+    let memory = ThreadMemory::default();
+    let replay = EventReplay::new(2);
+    let thread_id = ThreadId::new();
+    for seq in 1..=3 {
+        let event = ThreadEvent::new("item/agentMessage/delta", format!("delta {seq}"));
+        replay.record(&memory, thread_id, event_id(seq), &event);
+    }
+
+    assert_eq!(
+        replayed(replay.since(&memory, thread_id, event_id(1))),
+        Some(vec![(2, "delta 2".to_string()), (3, "delta 3".to_string())])
+    );
+    assert_eq!(
+        replayed(replay.since(&memory, thread_id, event_id(3))),
+        Some(vec![])
+    );
+    // Event 1 was evicted, so a client that last saw nothing cannot catch up.
+    assert_eq!(
+        replayed(replay.since(&memory, thread_id, event_id(0))),
+        None
+    );
+    // Ahead of anything recorded, e.g. an id from before a server restart.
+    assert_eq!(
+        replayed(replay.since(&memory, thread_id, event_id(9))),
+        None
+    );
+
+    replay.forget(thread_id);
+    assert_eq!(
+        replayed(replay.since(&memory, thread_id, event_id(1))),
+        None
+    );
+}
+
+#[test]
+fn test_replay_resets_an_id_from_another_epoch() {
+    let memory = ThreadMemory::default();
+    let replay = EventReplay::default();
+    let thread_id = ThreadId::new();
+    for seq in 1..=3 {
+        let event = ThreadEvent::new("item/agentMessage/delta", format!("delta {seq}"));
+        replay.record(&memory, thread_id, EventId { epoch: 7, seq }, &event);
+    }
+
+    // Same seq, but issued by a previous run of the server.
+    assert_eq!(
+        replayed(replay.since(&memory, thread_id, EventId { epoch: 6, seq: 1 })),
+        None
+    );
+    assert_eq!(
+        replayed(replay.since(&memory, thread_id, EventId { epoch: 7, seq: 1 })),
+        Some(vec![(2, "delta 2".to_string()), (3, "delta 3".to_string())])
+    );
+}
+
+/// Frames of the thread's events, up to and including the turn's completion.
+async fn frames_through_turn(events: &mut EventReader) -> Result<Vec<Frame>> {
+    let mut frames = Vec::new();
+    loop {
+        let frame = events.next_frame().await?;
+        if frame.id.is_none() {
+            continue;
+        }
+        let completed = frame.event_type == "turn/completed";
+        frames.push(frame);
+        if completed {
+            return Ok(frames);
+        }
+    }
+}
+
+fn replay_app(state: WebServerState) -> Router {
+    Router::new()
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(handlers::turns::send_turn),
+        )
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .with_state(state)
+}
+
+#[tokio::test]
+async fn test_reconnect_mid_turn_replays_missed_events_without_duplicates() -> Result<()> {
+    let server = start_mock_server().await;
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_assistant_message("msg-1", "replayed"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = start_mock_provider_thread(&fixture, &state, &server.uri()).await?;
+    let app = replay_app(state);
+
+    // The observer stays connected for the whole turn.
+    let mut observer = EventReader::open(&app, thread_id).await?;
+    let mut killed = EventReader::open(&app, thread_id).await?;
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/v2/threads/{thread_id}/turns"))
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({"input": [{"type": "text", "text": "hello"}]}).to_string(),
+                ))?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let last_seen = loop {
+        let frame = killed.next_frame().await?;
+        if frame.event_type == "turn/started"
+            && let Some(id) = frame.id
+        {
+            break id;
+        }
+    };
+    drop(killed);
+
+    let observed = frames_through_turn(&mut observer).await?;
+    let missed: Vec<(Option<String>, String)> = observed
+        .iter()
+        .skip_while(|frame| frame.id.as_deref() != Some(last_seen.as_str()))
+        .skip(1)
+        .map(|frame| (frame.id.clone(), frame.event_type.clone()))
+        .collect();
+    assert!(
+        missed
+            .iter()
+            .any(|(_, event_type)| event_type == "turn/completed"),
+        "{missed:?}"
+    );
+
+    // The turn is over, so everything the reconnect sees is replayed.
+    let mut reopened = EventReader::resume(&app, thread_id, &last_seen).await?;
+    let replayed: Vec<(Option<String>, String)> = frames_through_turn(&mut reopened)
+        .await?
+        .into_iter()
+        .map(|frame| (frame.id, frame.event_type))
+        .collect();
+    assert_eq!(replayed, missed);
+
+    let last: EventId = last_seen.parse().map_err(anyhow::Error::msg)?;
+    let seqs: Vec<u64> = replayed
+        .iter()
+        .filter_map(|(id, _)| id.as_deref()?.parse::<EventId>().ok())
+        .map(|id| id.seq)
+        .collect();
+    let expected: Vec<u64> = (last.seq + 1..).take(replayed.len()).collect();
+    assert_eq!(seqs, expected);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reconnect_after_an_unknown_id_starts_with_a_reset() -> Result<()> {
+    let server = start_mock_server().await;
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = start_mock_provider_thread(&fixture, &state, &server.uri()).await?;
+    let app = replay_app(state);
+
+    let mut events = EventReader::resume(&app, thread_id, "1:999").await?;
+    let frame = loop {
+        let frame = events.next_frame().await?;
+        if frame.event_type != "configWarning" {
+            break frame;
+        }
+    };
+    assert_eq!(frame.event_type, StreamReset::EVENT_TYPE);
+    assert_eq!(frame.id, None);
+    assert_eq!(
+        frame.data["params"],
+        json!({
+            "threadId": thread_id.to_string(),
+            "lastEventId": "1:999",
+            "itemsUrl": format!("/api/v2/threads/{thread_id}/items"),
+        })
+    );
+    Ok(())
+}
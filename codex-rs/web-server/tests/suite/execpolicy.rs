@@ -0,0 +1,185 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::delete;
+use axum::routing::get;
+use axum::routing::post;
+use codex_protocol::ThreadId;
+use codex_web_server::handlers;
+use codex_web_server::mock_model::MockModelServer;
+use codex_web_server::mock_model::MockScript;
+use codex_web_server::state::WebServerState;
+use serde_json::Value;
+use serde_json::json;
+use std::path::PathBuf;
+use tower::ServiceExt;
+
+use crate::common::EventReader;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+const CONFIG: &str = r#"
+model = "test-model"
+approval_policy = "on-request"
+sandbox_mode = "workspace-write"
+"#;
+
+fn app(state: WebServerState) -> Router {
+    Router::new()
+        .route("/api/v2/threads", post(handlers::threads::create_thread))
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(handlers::turns::send_turn),
+        )
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .route(
+            "/api/v2/threads/{thread_id}/approvals/{approval_id}",
+            post(handlers::approvals::respond_to_approval),
+        )
+        .route(
+            "/api/v2/execpolicy",
+            get(handlers::execpolicy::list_execpolicy_amendments),
+        )
+        .route(
+            "/api/v2/execpolicy/{amendment_id}",
+            delete(handlers::execpolicy::revoke_execpolicy_amendment),
+        )
+        .with_state(state)
+}
+
+async fn request(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> Result<(StatusCode, Value)> {
+    let body = body.map_or_else(Body::empty, |body| Body::from(body.to_string()));
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(body)?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+/// Starts a thread in the fixture's codex home and sends the scripted turn,
+/// which runs `printf mock-command-ran` with escalated permissions.
+async fn start_scripted_turn(app: &Router, cwd: &str) -> Result<(ThreadId, EventReader)> {
+    let (status, created) =
+        request(app, "POST", "/api/v2/threads", Some(json!({ "cwd": cwd }))).await?;
+    assert_eq!(status, StatusCode::OK, "{created}");
+    let thread_id = ThreadId::from_string(created["thread_id"].as_str().unwrap_or_default())?;
+    let events = EventReader::open(app, thread_id).await?;
+    let (status, sent) = request(
+        app,
+        "POST",
+        &format!("/api/v2/threads/{thread_id}/turns"),
+        Some(json!({ "input": [{ "type": "text", "text": "Check the workspace" }] })),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{sent}");
+    Ok((thread_id, events))
+}
+
+#[tokio::test]
+async fn test_accepted_amendment_skips_later_approvals() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(CONFIG)?;
+    let codex_home = fixture.codex_home_path();
+    let cwd = fixture.codex_home.path().to_string_lossy().to_string();
+    let script = MockScript::load(
+        &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mock_thread.json"),
+    )?;
+    let server = MockModelServer::start(script).await?;
+
+    let first = app(create_test_state(&fixture, "test-token")
+        .with_config_loader(server.config_loader(codex_home.clone())));
+    let (thread_id, mut events) = start_scripted_turn(&first, &cwd).await?;
+    let approval = events
+        .next_of("item/commandExecution/requestApproval")
+        .await?;
+    let amendment = approval["proposedExecpolicyAmendment"].clone();
+    assert!(
+        amendment
+            .as_array()
+            .is_some_and(|command| command.last() == Some(&json!("mock-command-ran"))),
+        "{approval}"
+    );
+    let approval_id = approval["approvalId"]
+        .as_str()
+        .or(approval["itemId"].as_str())
+        .unwrap_or_default()
+        .to_string();
+    let approval_uri = format!("/api/v2/threads/{thread_id}/approvals/{approval_id}");
+
+    // Declining cannot accept an amendment; the approval stays pending.
+    let (status, _) = request(
+        &first,
+        "POST",
+        &approval_uri,
+        Some(json!({ "decision": "decline", "acceptExecpolicyAmendment": true })),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, body) = request(
+        &first,
+        "POST",
+        &approval_uri,
+        Some(json!({ "decision": "approve", "acceptExecpolicyAmendment": true })),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    events.next_of("turn/completed").await?;
+
+    let (status, listed) = request(&first, "GET", "/api/v2/execpolicy", None).await?;
+    assert_eq!(status, StatusCode::OK, "{listed}");
+    let record = listed["amendments"][0].clone();
+    assert_eq!(record["command"], amendment);
+    assert_eq!(record["thread_id"], json!(thread_id.to_string()));
+    assert_eq!(record["approval_id"], json!(approval_id));
+    assert_eq!(record["workspace_root"], json!(cwd));
+
+    // A fresh server on the same codex home runs the same command without
+    // asking: the turn completes with nothing left to answer.
+    let state = create_test_state(&fixture, "test-token")
+        .with_config_loader(server.config_loader(codex_home.clone()));
+    let restarted = app(state.clone());
+    let (_, mut events) = start_scripted_turn(&restarted, &cwd).await?;
+    let completed = events.next_of("turn/completed").await?;
+    assert_eq!(completed["turn"]["status"], json!("completed"));
+    assert!(state.pending_approvals.lock().await.is_empty());
+
+    let id = record["id"].as_str().unwrap_or_default();
+    let (status, revoked) = request(
+        &restarted,
+        "DELETE",
+        &format!("/api/v2/execpolicy/{id}"),
+        None,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{revoked}");
+    assert_eq!(revoked["revoked"]["id"], json!(id));
+    let rules = std::fs::read_to_string(codex_home.join("rules/default.rules"))?;
+    assert!(!rules.contains("mock-command-ran"), "{rules}");
+    let (_, listed) = request(&restarted, "GET", "/api/v2/execpolicy", None).await?;
+    assert_eq!(listed["amendments"], json!([]));
+    let (status, _) = request(
+        &restarted,
+        "DELETE",
+        &format!("/api/v2/execpolicy/{id}"),
+        None,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use codex_web_server::handlers;
+use codex_web_server::mock_model::MockModelServer;
+use codex_web_server::mock_model::MockScript;
+use serde_json::Value;
+use serde_json::json;
+use std::path::PathBuf;
+use tower::ServiceExt;
+
+use crate::common::TEST_CONFIG;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+async fn request(app: &Router, method: &str, uri: &str, body: Body) -> Result<(StatusCode, Value)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(body)?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body).unwrap_or(Value::Null)))
+}
+
+fn names(listing: &Value) -> Vec<String> {
+    listing["entries"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry["name"].as_str().map(str::to_string))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_list_files_skips_ignored_entries_unless_disabled() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(&format!("{TEST_CONFIG}\nignore_globs = [\"*.log\"]\n"))?;
+    let workspace = fixture.codex_home_path().join("workspace");
+    std::fs::create_dir_all(workspace.join("node_modules/react"))?;
+    std::fs::create_dir_all(workspace.join("src"))?;
+    std::fs::write(workspace.join("src/main.rs"), "fn main() {}")?;
+    std::fs::write(workspace.join("build.log"), "noise")?;
+    std::fs::write(workspace.join(".codexignore"), "dist/\n")?;
+    std::fs::create_dir_all(workspace.join("dist"))?;
+
+    let script = MockScript::load(
+        &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mock_thread.json"),
+    )?;
+    let server = MockModelServer::start(script).await?;
+    let state = create_test_state(&fixture, "test-token")
+        .with_config_loader(server.config_loader(fixture.codex_home_path()));
+    let app = Router::new()
+        .route("/api/v2/threads", post(handlers::threads::create_thread))
+        .route(
+            "/api/v2/threads/{id}/files",
+            get(handlers::files::list_files),
+        )
+        .with_state(state);
+
+    let cwd = workspace.to_string_lossy().to_string();
+    let (status, created) = request(
+        &app,
+        "POST",
+        "/api/v2/threads",
+        Body::from(json!({ "cwd": cwd }).to_string()),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{created}");
+    let thread_id = created["thread_id"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let files_uri = format!("/api/v2/threads/{thread_id}/files");
+
+    let (status, listing) = request(&app, "GET", &files_uri, Body::empty()).await?;
+    assert_eq!(status, StatusCode::OK, "{listing}");
+    assert_eq!(names(&listing), vec![".codexignore", "src"]);
+    assert_eq!(listing["path"], json!(""));
+    assert_eq!(listing["entries"][1]["kind"], json!("directory"));
+
+    let (status, listing) = request(
+        &app,
+        "GET",
+        &format!("{files_uri}?ignore=false"),
+        Body::empty(),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{listing}");
+    assert_eq!(
+        names(&listing),
+        vec![".codexignore", "build.log", "dist", "node_modules", "src"]
+    );
+
+    let (status, listing) =
+        request(&app, "GET", &format!("{files_uri}?path=src"), Body::empty()).await?;
+    assert_eq!(status, StatusCode::OK, "{listing}");
+    assert_eq!(listing["entries"][0]["path"], json!("src/main.rs"));
+    assert_eq!(listing["entries"][0]["size"], json!(12));
+
+    let (status, _) = request(&app, "GET", &format!("{files_uri}?path=.."), Body::empty()).await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = request(
+        &app,
+        "GET",
+        &format!("{files_uri}?path=missing"),
+        Body::empty(),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    Ok(())
+}
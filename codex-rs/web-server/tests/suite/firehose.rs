@@ -0,0 +1,119 @@
+//! `GET /api/v2/events`: every thread's events on one stream.
+
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use codex_protocol::ThreadId;
+use codex_web_server::handlers;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_message_item_added;
+use core_test_support::responses::ev_output_text_delta;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use serde_json::json;
+use std::collections::HashSet;
+use tower::ServiceExt;
+
+use crate::common::EventReader;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+use crate::common::start_mock_provider_thread;
+
+fn reply(response_id: &str) -> String {
+    sse(vec![
+        ev_response_created(response_id),
+        ev_message_item_added("msg-1", ""),
+        ev_output_text_delta("hello"),
+        ev_assistant_message("msg-1", "hello"),
+        ev_completed(response_id),
+    ])
+}
+
+async fn send_turn(app: &Router, thread_id: ThreadId) -> Result<()> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/v2/threads/{thread_id}/turns"))
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({"input": [{"type": "text", "text": "hello"}]}).to_string(),
+                ))?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_stream_carries_threads_started_before_and_after_it_connected() -> Result<()> {
+    let server = start_mock_server().await;
+    mount_sse_sequence(&server, vec![reply("resp-1"), reply("resp-2")]).await;
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let app = Router::new()
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(handlers::turns::send_turn),
+        )
+        .route("/api/v2/events", get(handlers::stream_all_events))
+        .with_state(state.clone());
+
+    let earlier = start_mock_provider_thread(&fixture, &state, &server.uri()).await?;
+    let mut events = EventReader::open_all(&app, "?events=turn").await?;
+    let later = start_mock_provider_thread(&fixture, &state, &server.uri()).await?;
+
+    send_turn(&app, earlier).await?;
+    send_turn(&app, later).await?;
+
+    let mut completed = HashSet::new();
+    while completed.len() < 2 {
+        let frame = events.next_frame().await?;
+        assert!(
+            frame.event_type.starts_with("turn/"),
+            "{}",
+            frame.event_type
+        );
+        // Firehose ids would be ambiguous across threads.
+        assert_eq!(frame.id, None);
+        if frame.event_type == "turn/completed" {
+            completed.insert(
+                frame.data["params"]["threadId"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            );
+        }
+    }
+    assert_eq!(
+        completed,
+        HashSet::from([earlier.to_string(), later.to_string()])
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_empty_events_filter_is_rejected() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let app = Router::new()
+        .route("/api/v2/events", get(handlers::stream_all_events))
+        .with_state(create_test_state(&fixture, "test-token"));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v2/events?events=")
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::middleware::from_fn_with_state;
+use axum::routing::post;
+use codex_protocol::ThreadId;
+use codex_web_server::instance::INSTANCE_HEADER;
+use codex_web_server::instance::instance_id;
+use codex_web_server::middleware::instance_affinity_middleware;
+use codex_web_server::state::WebServerState;
+use serde_json::json;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+fn affinity_router(state: WebServerState) -> Router {
+    Router::new()
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(|| async { StatusCode::OK }),
+        )
+        .layer(from_fn_with_state(
+            state.clone(),
+            instance_affinity_middleware,
+        ))
+        .with_state(state)
+}
+
+#[tokio::test]
+async fn test_foreign_thread_returns_misdirected_request() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let app = affinity_router(create_test_state(&fixture, "test-token"));
+
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("/api/v2/threads/{}/turns", ThreadId::new()))
+        .header(INSTANCE_HEADER, "other-instance")
+        .body(Body::empty())?;
+    let response = app.oneshot(request).await?;
+
+    assert_eq!(response.status(), StatusCode::MISDIRECTED_REQUEST);
+    assert_eq!(
+        response
+            .headers()
+            .get(INSTANCE_HEADER)
+            .map(|v| v.as_bytes()),
+        Some(instance_id().as_bytes())
+    );
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+    let body: serde_json::Value = serde_json::from_slice(&body)?;
+    assert_eq!(
+        body,
+        json!({
+            "error": "Thread is owned by instance other-instance",
+            "status": 421,
+            "instance_id": instance_id(),
+            "owner_instance_id": "other-instance",
+        })
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_requests_without_foreign_owner_pass_through() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let app = affinity_router(create_test_state(&fixture, "test-token"));
+
+    for owner in [None, Some(instance_id())] {
+        let mut request = Request::builder()
+            .method("POST")
+            .uri(format!("/api/v2/threads/{}/turns", ThreadId::new()));
+        if let Some(owner) = owner {
+            request = request.header(INSTANCE_HEADER, owner);
+        }
+        let response = app.clone().oneshot(request.body(Body::empty())?).await?;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response
+                .headers()
+                .get(INSTANCE_HEADER)
+                .map(|v| v.as_bytes()),
+            Some(instance_id().as_bytes())
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,158 @@
+//! Content-type and shape checks on JSON request bodies.
+
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::post;
+use codex_protocol::ThreadId;
+use codex_web_server::handlers;
+use serde_json::Value;
+use serde_json::json;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+async fn app() -> Result<(TestFixture, Router)> {
+    let fixture = TestFixture::new().await?;
+    let app = Router::new()
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(handlers::turns::send_turn),
+        )
+        .route(
+            "/api/v2/threads/{thread_id}/approvals/{approval_id}",
+            post(handlers::approvals::respond_to_approval),
+        )
+        .with_state(create_test_state(&fixture, "test-token"));
+    Ok((fixture, app))
+}
+
+async fn post_raw(
+    app: &Router,
+    uri: &str,
+    content_type: Option<&str>,
+    body: &str,
+) -> Result<(StatusCode, Value)> {
+    let mut request = Request::builder().method("POST").uri(uri);
+    if let Some(content_type) = content_type {
+        request = request.header("content-type", content_type);
+    }
+    let response = app
+        .clone()
+        .oneshot(request.body(Body::from(body.to_string()))?)
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+fn approval_uri() -> String {
+    format!("/api/v2/threads/{}/approvals/item-1", ThreadId::new())
+}
+
+#[tokio::test]
+async fn test_body_without_json_content_type_is_415() -> Result<()> {
+    let (_fixture, app) = app().await?;
+    let body = json!({ "decision": "approve" }).to_string();
+
+    for content_type in [
+        None,
+        Some("text/plain"),
+        Some("application/x-www-form-urlencoded"),
+    ] {
+        let (status, error) = post_raw(&app, &approval_uri(), content_type, &body).await?;
+        assert_eq!(
+            status,
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "{content_type:?}: {error}"
+        );
+        assert_eq!(error["status"], json!(415));
+        assert_eq!(error["code"], json!("unsupported_media_type"));
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unknown_or_mistyped_field_is_422_naming_the_field() -> Result<()> {
+    let (_fixture, app) = app().await?;
+    let json = Some("application/json");
+
+    let (status, error) =
+        post_raw(&app, &approval_uri(), json, r#"{ "decison": "approve" }"#).await?;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{error}");
+    assert_eq!(error["code"], json!("invalid_body"));
+    assert_eq!(error["path"], json!("."));
+    assert!(
+        error["error"]
+            .as_str()
+            .is_some_and(|message| message.contains("unknown field `decison`")),
+        "{error}"
+    );
+
+    let turn_uri = format!("/api/v2/threads/{}/turns", ThreadId::new());
+    let (status, error) = post_raw(
+        &app,
+        &turn_uri,
+        json,
+        r#"{ "input": [], "modle": "gpt-5" }"#,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{error}");
+    assert!(
+        error["error"]
+            .as_str()
+            .is_some_and(|message| message.contains("unknown field `modle`")),
+        "{error}"
+    );
+
+    let (status, error) = post_raw(&app, &turn_uri, json, r#"{ "input": "hello" }"#).await?;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{error}");
+    assert_eq!(error["code"], json!("invalid_body"));
+    assert_eq!(error["path"], json!("input"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_trailing_data_after_the_body_is_400() -> Result<()> {
+    let (_fixture, app) = app().await?;
+
+    let (status, error) = post_raw(
+        &app,
+        &approval_uri(),
+        Some("application/json"),
+        r#"{ "decision": "approve" } {"decision": "decline"}"#,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{error}");
+    assert!(
+        error["error"]
+            .as_str()
+            .is_some_and(|message| message.contains("trailing characters")),
+        "{error}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_well_formed_body_reaches_the_handler() -> Result<()> {
+    let (_fixture, app) = app().await?;
+
+    // Parameters and trailing whitespace are fine; the request gets as far as
+    // looking up the approval, which does not exist.
+    for content_type in ["application/json", "application/json; charset=utf-8"] {
+        let (status, error) = post_raw(
+            &app,
+            &approval_uri(),
+            Some(content_type),
+            "{ \"decision\": \"approve\", \"acceptExecpolicyAmendment\": false }\n",
+        )
+        .await?;
+        assert_eq!(status, StatusCode::NOT_FOUND, "{content_type}: {error}");
+        assert_eq!(error["code"], json!("not_found"));
+    }
+    Ok(())
+}
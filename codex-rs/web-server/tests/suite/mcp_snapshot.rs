@@ -0,0 +1,179 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use codex_app_server_protocol::McpAuthStatus;
+use codex_app_server_protocol::McpServerStatus;
+use codex_core::config::ConfigBuilder;
+use codex_web_server::handlers::mcp::list_mcp_server_status;
+use codex_web_server::handlers::mcp::refresh_mcp_servers;
+use codex_web_server::mcp_snapshot::McpServerListing;
+use codex_web_server::mcp_snapshot::McpSnapshotCache;
+use codex_web_server::mcp_snapshot::McpStatusSnapshot;
+use codex_web_server::mcp_snapshot::collect_mcp_status_for_config;
+use codex_web_server::state::WebServerState;
+use serde_json::Value;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+/// Cache whose collector takes `delay` and reports a single server named
+/// after the collection count (`server-1`, `server-2`, ...).
+fn slow_cache(ttl: Duration, delay: Duration) -> (Arc<McpSnapshotCache>, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&calls);
+    let cache = McpSnapshotCache::new(
+        ttl,
+        Arc::new(move || {
+            let counter = Arc::clone(&counter);
+            Box::pin(async move {
+                tokio::time::sleep(delay).await;
+                let call = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                Ok(McpStatusSnapshot {
+                    servers: vec![McpServerListing {
+                        status: McpServerStatus {
+                            name: format!("server-{call}"),
+                            tools: HashMap::new(),
+                            resources: Vec::new(),
+                            resource_templates: Vec::new(),
+                            auth_status: McpAuthStatus::Unsupported,
+                        },
+                        enabled: true,
+                    }],
+                    timed_out_servers: Vec::new(),
+                    failed_servers: HashMap::new(),
+                })
+            })
+        }),
+    );
+    (Arc::new(cache), calls)
+}
+
+fn mcp_router(state: WebServerState) -> Router {
+    Router::new()
+        .route("/api/v2/mcp/servers", get(list_mcp_server_status))
+        .route("/api/v2/mcp/servers/refresh", post(refresh_mcp_servers))
+        .with_state(state)
+}
+
+async fn send(app: &Router, method: &str, uri: &str) -> Result<Value> {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .body(Body::empty())?;
+    let response = app.clone().oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+    Ok(serde_json::from_slice(&body)?)
+}
+
+#[tokio::test]
+async fn test_expired_snapshot_is_served_stale_while_refreshing() -> Result<()> {
+    let (cache, calls) = slow_cache(Duration::from_millis(200), Duration::from_millis(300));
+
+    let first = cache.get(false).await?;
+    assert_eq!(first.snapshot.servers[0].status.name, "server-1");
+    assert!(!first.stale);
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    let started = Instant::now();
+    let stale = cache.get(false).await?;
+    assert!(started.elapsed() < Duration::from_millis(200));
+    assert_eq!(stale.snapshot.servers[0].status.name, "server-1");
+    assert!(stale.stale);
+
+    // A second read while the refresh runs does not start another one.
+    cache.get(false).await?;
+    tokio::time::sleep(Duration::from_millis(600)).await;
+
+    let refreshed = cache.get(false).await?;
+    assert_eq!(refreshed.snapshot.servers[0].status.name, "server-2");
+    assert!(!refreshed.stale);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_refresh_query_forces_recollection() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let (cache, calls) = slow_cache(Duration::from_secs(60), Duration::from_millis(10));
+    let mut state = create_test_state(&fixture, "test-token");
+    state.mcp_snapshots = cache;
+    let app = mcp_router(state);
+
+    let first = send(&app, "GET", "/api/v2/mcp/servers").await?;
+    let cached = send(&app, "GET", "/api/v2/mcp/servers").await?;
+    assert_eq!(first["data"][0]["name"], json!("server-1"));
+    assert_eq!(cached["data"][0]["name"], json!("server-1"));
+    assert_eq!(cached["stale"], json!(false));
+    assert_eq!(cached["collected_at"], first["collected_at"]);
+
+    let forced = send(&app, "GET", "/api/v2/mcp/servers?refresh=true").await?;
+    assert_eq!(forced["data"][0]["name"], json!("server-2"));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_refresh_endpoint_invalidates_cache() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let (cache, _calls) = slow_cache(Duration::from_secs(60), Duration::from_millis(10));
+    let mut state = create_test_state(&fixture, "test-token");
+    state.mcp_snapshots = cache;
+    let app = mcp_router(state);
+
+    let first = send(&app, "GET", "/api/v2/mcp/servers").await?;
+    assert_eq!(first["data"][0]["name"], json!("server-1"));
+
+    send(&app, "POST", "/api/v2/mcp/servers/refresh").await?;
+    let after = send(&app, "GET", "/api/v2/mcp/servers").await?;
+    assert_ne!(after["data"][0]["name"], json!("server-1"));
+    assert_eq!(after["stale"], json!(false));
+    Ok(())
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_hung_server_times_out_without_blocking_collection() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(
+        r#"
+model = "test-model"
+
+[mcp_servers.hung]
+command = "sleep"
+args = ["30"]
+startup_timeout_sec = 60
+"#,
+    )?;
+    let config = ConfigBuilder::default()
+        .codex_home(fixture.codex_home_path())
+        .build()
+        .await?;
+
+    let started = Instant::now();
+    let status = collect_mcp_status_for_config(&config, Duration::from_millis(500)).await;
+
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert_eq!(status.timed_out_servers, vec!["hung".to_string()]);
+    assert_eq!(
+        status
+            .servers
+            .iter()
+            .map(|server| server.status.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["hung"]
+    );
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::patch;
+use codex_web_server::handlers::mcp::list_mcp_server_status;
+use codex_web_server::handlers::mcp::update_mcp_server;
+use serde_json::Value;
+use serde_json::json;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+/// The command does not exist, so an enabled server reports no tools either;
+/// only the `enabled` flag and the on-disk config distinguish the two states.
+const TOGGLE_CONFIG: &str = r#"
+model = "test-model"
+
+[mcp_servers.docs]
+command = "codex-definitely-not-a-real-binary"
+args = ["--stdio"]
+"#;
+
+fn toggle_router(fixture: &TestFixture) -> Router {
+    Router::new()
+        .route("/api/v2/mcp/servers", get(list_mcp_server_status))
+        .route("/api/v2/mcp/servers/{name}", patch(update_mcp_server))
+        .with_state(create_test_state(fixture, "test-token"))
+}
+
+async fn send(app: &Router, method: &str, uri: &str, body: Value) -> Result<(StatusCode, Value)> {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))?;
+    let response = app.clone().oneshot(request).await?;
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+async fn docs_listing(app: &Router) -> Result<Value> {
+    let (status, body) = send(app, "GET", "/api/v2/mcp/servers?refresh=true", Value::Null).await?;
+    assert_eq!(status, StatusCode::OK);
+    Ok(body["data"][0].clone())
+}
+
+#[tokio::test]
+async fn test_disable_keeps_server_config() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TOGGLE_CONFIG)?;
+    let app = toggle_router(&fixture);
+
+    let before = docs_listing(&app).await?;
+    assert_eq!(before["name"], json!("docs"));
+    assert_eq!(before["enabled"], json!(true));
+
+    let (status, body) = send(
+        &app,
+        "PATCH",
+        "/api/v2/mcp/servers/docs",
+        json!({ "enabled": false }),
+    )
+    .await?;
+    assert_eq!(
+        (status, body),
+        (StatusCode::OK, json!({ "name": "docs", "enabled": false }))
+    );
+
+    let config = std::fs::read_to_string(fixture.codex_home_path().join("config.toml"))?;
+    assert!(config.contains("enabled = false"));
+    assert!(config.contains(r#"command = "codex-definitely-not-a-real-binary""#));
+    assert!(config.contains(r#"args = ["--stdio"]"#));
+
+    let disabled = docs_listing(&app).await?;
+    assert_eq!(disabled["name"], json!("docs"));
+    assert_eq!(disabled["enabled"], json!(false));
+    assert_eq!(disabled["tools"], json!({}));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reenable_server() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(&format!("{TOGGLE_CONFIG}enabled = false\n"))?;
+    let app = toggle_router(&fixture);
+
+    assert_eq!(docs_listing(&app).await?["enabled"], json!(false));
+
+    let (status, _body) = send(
+        &app,
+        "PATCH",
+        "/api/v2/mcp/servers/docs",
+        json!({ "enabled": true }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+
+    let config = std::fs::read_to_string(fixture.codex_home_path().join("config.toml"))?;
+    assert!(config.contains("enabled = true"));
+    assert_eq!(docs_listing(&app).await?["enabled"], json!(true));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_toggle_unknown_server_is_not_found() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TOGGLE_CONFIG)?;
+    let app = toggle_router(&fixture);
+
+    let (status, body) = send(
+        &app,
+        "PATCH",
+        "/api/v2/mcp/servers/missing",
+        json!({ "enabled": false }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"], json!("MCP server not found: missing"));
+
+    let config = std::fs::read_to_string(fixture.codex_home_path().join("config.toml"))?;
+    assert_eq!(config, TOGGLE_CONFIG);
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use anyhow::Result;
+use codex_app_server_protocol::ItemCompletedNotification;
+use codex_app_server_protocol::McpAuthStatus;
+use codex_app_server_protocol::McpToolCallError;
+use codex_app_server_protocol::McpToolCallStatus;
+use codex_app_server_protocol::ServerNotification;
+use codex_app_server_protocol::ThreadItem;
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::McpAuthStatus as CoreMcpAuthStatus;
+use codex_protocol::protocol::McpInvocation;
+use codex_protocol::protocol::McpToolCallEndEvent;
+use codex_protocol::protocol::McpToolCallErrorDetails;
+use codex_web_server::event_stream::EventStreamProcessor;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+async fn failed_tool_call_notification(
+    message: &str,
+    error_details: Option<McpToolCallErrorDetails>,
+) -> Result<(ThreadId, ItemCompletedNotification)> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = ThreadId::new();
+    let processor = EventStreamProcessor::new(thread_id, Arc::new(state));
+
+    let notifications = processor
+        .process_event(Event {
+            id: "turn-1".to_string(),
+            msg: EventMsg::McpToolCallEnd(McpToolCallEndEvent {
+                call_id: "call-1".to_string(),
+                invocation: McpInvocation {
+                    server: "github".to_string(),
+                    tool: "list_issues".to_string(),
+                    arguments: None,
+                },
+                duration: Duration::from_millis(5),
+                result: Err(message.to_string()),
+                error_details,
+            }),
+        })
+        .await;
+    let [ServerNotification::ItemCompleted(completed)] = notifications.as_slice() else {
+        panic!("expected a single item/completed notification, got {notifications:?}");
+    };
+    Ok((thread_id, completed.clone()))
+}
+
+fn expected_notification(
+    thread_id: ThreadId,
+    error: McpToolCallError,
+) -> ItemCompletedNotification {
+    ItemCompletedNotification {
+        thread_id: thread_id.to_string(),
+        turn_id: "turn-1".to_string(),
+        item: ThreadItem::McpToolCall {
+            id: "call-1".to_string(),
+            server: "github".to_string(),
+            tool: "list_issues".to_string(),
+            status: McpToolCallStatus::Failed,
+            arguments: serde_json::Value::Null,
+            result: None,
+            error: Some(error),
+            duration_ms: Some(5),
+        },
+    }
+}
+
+#[tokio::test]
+async fn test_auth_failure_carries_code_data_and_auth_status() -> Result<()> {
+    let (thread_id, completed) = failed_tool_call_notification(
+        "tool call error: unauthorized",
+        Some(McpToolCallErrorDetails {
+            code: Some(-32001),
+            data: Some(json!({"reason": "token_expired"})),
+            auth_status: Some(CoreMcpAuthStatus::NotLoggedIn),
+        }),
+    )
+    .await?;
+
+    assert_eq!(
+        completed,
+        expected_notification(
+            thread_id,
+            McpToolCallError {
+                message: "tool call error: unauthorized".to_string(),
+                code: Some(-32001),
+                data: Some(json!({"reason": "token_expired"})),
+                auth_status: Some(McpAuthStatus::NotLoggedIn),
+            },
+        )
+    );
+
+    let payload = serde_json::to_value(&completed)?;
+    assert_eq!(
+        payload["item"]["error"],
+        json!({
+            "message": "tool call error: unauthorized",
+            "code": -32001,
+            "data": {"reason": "token_expired"},
+            "authStatus": "notLoggedIn",
+        })
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_generic_failure_has_message_only() -> Result<()> {
+    let (thread_id, completed) =
+        failed_tool_call_notification("tool call error: boom", None).await?;
+
+    assert_eq!(
+        completed,
+        expected_notification(
+            thread_id,
+            McpToolCallError {
+                message: "tool call error: boom".to_string(),
+                code: None,
+                data: None,
+                auth_status: None,
+            },
+        )
+    );
+    Ok(())
+}
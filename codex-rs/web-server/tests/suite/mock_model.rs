@@ -0,0 +1,160 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use codex_protocol::ThreadId;
+use codex_web_server::handlers;
+use codex_web_server::mock_model::MockModelServer;
+use codex_web_server::mock_model::MockScript;
+use codex_web_server::state::WebServerState;
+use serde_json::Value;
+use serde_json::json;
+use std::path::PathBuf;
+use tower::ServiceExt;
+
+use crate::common::EventReader;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+fn fixture_script() -> Result<MockScript> {
+    MockScript::load(
+        &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mock_thread.json"),
+    )
+}
+
+fn app(state: WebServerState) -> Router {
+    Router::new()
+        .route("/api/v2/threads", post(handlers::threads::create_thread))
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(handlers::turns::send_turn),
+        )
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .route(
+            "/api/v2/threads/{thread_id}/approvals/{approval_id}",
+            post(handlers::approvals::respond_to_approval),
+        )
+        .with_state(state)
+}
+
+async fn post_json(app: &Router, uri: &str, body: Value) -> Result<Value> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+    Ok(serde_json::from_slice(&body)?)
+}
+
+#[tokio::test]
+async fn test_scripted_turn_runs_through_approval_to_completion() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    // No credentials and no provider config: the mock supplies the model.
+    fixture.create_test_config(
+        r#"
+model = "test-model"
+approval_policy = "on-request"
+sandbox_mode = "workspace-write"
+"#,
+    )?;
+    let server = MockModelServer::start(fixture_script()?).await?;
+    let state = create_test_state(&fixture, "test-token")
+        .with_config_loader(server.config_loader(fixture.codex_home_path()));
+    let app = app(state);
+
+    let created = post_json(
+        &app,
+        "/api/v2/threads",
+        json!({ "cwd": fixture.codex_home.path() }),
+    )
+    .await?;
+    let thread_id = ThreadId::from_string(created["thread_id"].as_str().unwrap_or_default())?;
+    let mut events = EventReader::open(&app, thread_id).await?;
+
+    let sent = post_json(
+        &app,
+        &format!("/api/v2/threads/{thread_id}/turns"),
+        json!({ "input": [{ "type": "text", "text": "Check the workspace" }] }),
+    )
+    .await?;
+    let turn_id = sent["turn_id"].clone();
+
+    assert_eq!(events.next_of("turn/started").await?["turn"]["id"], turn_id);
+    assert_eq!(
+        events.next_of("item/agentMessage/delta").await?["delta"],
+        json!("Let me run ")
+    );
+
+    let approval = events
+        .next_of("item/commandExecution/requestApproval")
+        .await?;
+    assert_eq!(approval["turnId"], turn_id);
+    // shell_command runs through the user's shell, e.g. `bash -lc ...`.
+    assert!(
+        approval["command"]
+            .as_str()
+            .is_some_and(|command| command.ends_with("printf mock-command-ran")),
+        "{approval}"
+    );
+    let approval_id = approval["approvalId"]
+        .as_str()
+        .or(approval["itemId"].as_str())
+        .unwrap_or_default()
+        .to_string();
+    post_json(
+        &app,
+        &format!("/api/v2/threads/{thread_id}/approvals/{approval_id}"),
+        json!({ "decision": "approve" }),
+    )
+    .await?;
+
+    let completed = events.next_of("turn/completed").await?;
+    assert_eq!(completed["turn"]["id"], turn_id);
+    assert_eq!(completed["turn"]["status"], json!("completed"));
+    Ok(())
+}
+
+#[test]
+fn test_script_advances_with_tool_outputs_since_the_last_user_message() -> Result<()> {
+    let script = fixture_script()?;
+    let user = json!({ "type": "message", "role": "user", "content": [] });
+    let output = json!({ "type": "function_call_output", "call_id": "mock-call-1" });
+    let first = &script.responses[0];
+    let second = &script.responses[1];
+
+    assert_eq!(script.response_for(&[]), first.as_slice());
+    assert_eq!(script.response_for(&[user.clone()]), first.as_slice());
+    assert_eq!(
+        script.response_for(&[user.clone(), output.clone()]),
+        second.as_slice()
+    );
+    // A new user message starts the script over.
+    assert_eq!(
+        script.response_for(&[user.clone(), output.clone(), user.clone()]),
+        first.as_slice()
+    );
+    // Extra tool outputs repeat the final response.
+    assert_eq!(
+        script.response_for(&[user, output.clone(), output]),
+        second.as_slice()
+    );
+    Ok(())
+}
+
+#[test]
+fn test_script_rejects_events_without_type() {
+    assert!(MockScript::parse(r#"{"responses": []}"#).is_err());
+    assert!(MockScript::parse(r#"{"responses": [[{"delta": "hi"}]]}"#).is_err());
+}
@@ -1,5 +1,66 @@
 // Test suite modules
+pub mod api_version;
+pub mod approval_manager;
+pub mod attachment_gc;
+pub mod attachments;
+pub mod auth;
+pub mod auto_archive;
+#[cfg(windows)]
+pub mod commands;
+pub mod config_consistency;
+pub mod config_loader;
+pub mod config_scopes;
+pub mod context_window;
+pub mod cors;
+pub mod credential_store;
+pub mod event_filter;
+pub mod event_ids;
+pub mod event_pump;
+pub mod event_replay;
+pub mod execpolicy;
 pub mod feedback;
+pub mod files;
+pub mod firehose;
+pub mod instance;
+pub mod json_body;
 pub mod mcp;
+pub mod mcp_snapshot;
+pub mod mcp_toggle;
+pub mod mcp_tool_call;
+pub mod mock_model;
+pub mod model_validation;
+pub mod models;
+pub mod notification_schema;
+pub mod notifications;
+pub mod pairing;
+pub mod patches;
+#[cfg(unix)]
+pub mod permissions;
+pub mod provider_overrides;
+pub mod rate_limits;
+pub mod requirements;
+pub mod reviews;
+pub mod rollout_archive;
+pub mod rpc;
+pub mod sandbox_preview;
+pub mod skills;
 pub mod sse;
+pub mod startup_checks;
+pub mod state_store;
+pub mod storage;
+#[cfg(feature = "otel")]
+pub mod telemetry;
+pub mod thread_export;
+pub mod thread_memory;
+pub mod thread_ops;
+pub mod thread_progress;
+pub mod thread_scan;
+pub mod thread_start_errors;
+pub mod thread_status;
 pub mod threads;
+pub mod turn_ids;
+pub mod turn_queue;
+pub mod turn_reasons;
+pub mod usage;
+pub mod web_search;
+pub mod workspace_trust;
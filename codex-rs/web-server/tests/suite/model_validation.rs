@@ -0,0 +1,176 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::post;
+use codex_core::models_manager::manager::RefreshStrategy;
+use codex_protocol::openai_models::ModelPreset;
+use codex_web_server::handlers;
+use codex_web_server::mock_model::MockModelServer;
+use codex_web_server::mock_model::MockScript;
+use codex_web_server::model_validation::MAX_IDENTIFIER_LEN;
+use codex_web_server::model_validation::check_identifier;
+use codex_web_server::model_validation::check_image_input;
+use codex_web_server::model_validation::suggest;
+use serde_json::Value;
+use serde_json::json;
+use std::path::PathBuf;
+use tower::ServiceExt;
+
+use crate::common::TEST_CONFIG;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+async fn post_json(app: &Router, uri: &str, body: Value) -> Result<(StatusCode, Value)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+#[test]
+fn test_identifier_limits() {
+    assert!(check_identifier("Model", "gpt-5.1-codex").is_ok());
+    assert!(check_identifier("Model", "org/model:2025-01@latest").is_ok());
+    assert!(check_identifier("Model", "").is_err());
+    assert!(check_identifier("Model", "gpt 5").is_err());
+    assert!(check_identifier("Model", "gpt-5\n").is_err());
+    assert!(check_identifier("Model", &"a".repeat(MAX_IDENTIFIER_LEN)).is_ok());
+    assert!(check_identifier("Model", &"a".repeat(MAX_IDENTIFIER_LEN + 1)).is_err());
+}
+
+#[test]
+fn test_suggestions_are_close_matches_closest_first() {
+    let known = vec![
+        "gpt-5.1-codex".to_string(),
+        "gpt-5.1-codex-mini".to_string(),
+        "gpt-5.1".to_string(),
+        "o3".to_string(),
+    ];
+    assert_eq!(
+        suggest("gpt-5.1-codx", &known),
+        vec!["gpt-5.1-codex", "gpt-5.1"]
+    );
+    assert_eq!(
+        suggest("GPT-5.1-CODEX-MIN", &known)[0],
+        "gpt-5.1-codex-mini"
+    );
+    assert!(suggest("claude-opus", &known).is_empty());
+}
+
+#[tokio::test]
+async fn test_create_thread_validates_the_model() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let script = MockScript::load(
+        &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mock_thread.json"),
+    )?;
+    let server = MockModelServer::start(script).await?;
+    let state = create_test_state(&fixture, "test-token")
+        .with_config_loader(server.config_loader(fixture.codex_home_path()));
+    let known = state
+        .thread_manager
+        .list_models(RefreshStrategy::Offline)
+        .await
+        .into_iter()
+        .next()
+        .map(|preset| preset.model)
+        .unwrap_or_default();
+    let app = Router::new()
+        .route("/api/v2/threads", post(handlers::threads::create_thread))
+        .with_state(state);
+    let cwd = fixture.codex_home.path();
+
+    let (status, body) = post_json(
+        &app,
+        "/api/v2/threads",
+        json!({ "cwd": cwd, "model": known }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["model"], json!(known));
+
+    // Dropping the last character leaves a prefix of the real id.
+    let typo = &known[..known.len() - 1];
+    let (status, body) = post_json(
+        &app,
+        "/api/v2/threads",
+        json!({ "cwd": cwd, "model": typo }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert_eq!(body["code"], json!("model_not_found"));
+    assert!(
+        body["suggestions"]
+            .as_array()
+            .is_some_and(|suggestions| suggestions.contains(&json!(known))),
+        "{body}"
+    );
+
+    // Custom providers can name models the list does not know.
+    let (status, body) = post_json(
+        &app,
+        "/api/v2/threads",
+        json!({ "cwd": cwd, "model": "my-gateway/custom-model", "skip_validation": true }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["model"], json!("my-gateway/custom-model"));
+
+    // The escape hatch does not lift the hard limits.
+    let absurd = format!("model {}", "x".repeat(500));
+    let (status, body) = post_json(
+        &app,
+        "/api/v2/threads",
+        json!({ "cwd": cwd, "model": absurd, "skip_validation": true }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert!(!body.to_string().contains(&absurd), "{body}");
+    Ok(())
+}
+
+fn fixture_presets() -> Result<Vec<ModelPreset>> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/model_presets.json");
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+#[test]
+fn test_text_only_models_refuse_images() -> Result<()> {
+    let presets = fixture_presets()?;
+
+    let err = check_image_input("text-only-model", &presets)
+        .err()
+        .ok_or_else(|| anyhow::anyhow!("text-only model accepted an image"))?;
+    let (status, body) = err.into_parts();
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["code"], json!("unsupported_input_modality"));
+    assert_eq!(body["model"], json!("text-only-model"));
+    assert_eq!(body["input_modalities"], json!(["text"]));
+    // Hidden models are not suggested.
+    assert_eq!(body["suggestions"], json!(["vision-model"]));
+    let message = body["error"].as_str().unwrap_or_default();
+    assert!(message.contains("text-only-model"), "{message}");
+    Ok(())
+}
+
+#[test]
+fn test_vision_and_unlisted_models_accept_images() -> Result<()> {
+    let presets = fixture_presets()?;
+    assert!(check_image_input("vision-model", &presets).is_ok());
+    assert!(check_image_input("hidden-vision-model", &presets).is_ok());
+    // A custom provider's model is not in the list and cannot be checked.
+    assert!(check_image_input("my-gateway/custom-model", &presets).is_ok());
+    Ok(())
+}
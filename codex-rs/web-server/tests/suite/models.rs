@@ -0,0 +1,106 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use codex_web_server::handlers::models::list_models;
+use serde_json::Value;
+use serde_json::json;
+use tower::ServiceExt;
+
+use crate::common::TEST_CONFIG;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+async fn get_models(app: &Router, query: &str) -> Result<(StatusCode, Value)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/v2/models{query}"))
+                .body(Body::empty())?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+fn ids(body: &Value) -> Vec<Value> {
+    body["data"]
+        .as_array()
+        .map(|models| models.iter().map(|model| model["id"].clone()).collect())
+        .unwrap_or_default()
+}
+
+async fn app(fixture: &TestFixture) -> Result<Router> {
+    fixture.create_test_config(TEST_CONFIG)?;
+    Ok(Router::new()
+        .route("/api/v2/models", get(list_models))
+        .with_state(create_test_state(fixture, "test-token")))
+}
+
+#[tokio::test]
+async fn test_limit_and_offset_page_through_models() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let app = app(&fixture).await?;
+
+    let (status, all) = get_models(&app, "").await?;
+    assert_eq!(status, StatusCode::OK, "{all}");
+    let all_ids = ids(&all);
+    assert!(all_ids.len() >= 2, "{all}");
+    assert_eq!(all["total"], json!(all_ids.len()));
+
+    let (status, page) = get_models(&app, "?provider=openai&limit=1&offset=1").await?;
+    assert_eq!(status, StatusCode::OK, "{page}");
+    assert_eq!(ids(&page), all_ids[1..2].to_vec());
+    assert_eq!(page["total"], all["total"]);
+    assert_eq!(page["limit"], json!(1));
+    assert_eq!(page["offset"], json!(1));
+
+    let (_, past_end) = get_models(&app, &format!("?offset={}", all_ids.len())).await?;
+    assert_eq!(past_end["data"], json!([]));
+    assert_eq!(past_end["total"], all["total"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_filters_by_provider_and_capability() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let app = app(&fixture).await?;
+
+    let (status, other) = get_models(&app, "?provider=anthropic").await?;
+    assert_eq!(status, StatusCode::OK, "{other}");
+    assert_eq!(other["total"], json!(0));
+
+    let (status, vision) = get_models(&app, "?capability=vision").await?;
+    assert_eq!(status, StatusCode::OK, "{vision}");
+    let models = vision["data"].as_array().cloned().unwrap_or_default();
+    assert!(
+        models.iter().all(|model| model["inputModalities"]
+            .as_array()
+            .is_some_and(|modalities| modalities.contains(&json!("image")))),
+        "{vision}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_invalid_query_values_are_rejected() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let app = app(&fixture).await?;
+
+    for query in [
+        "?limit=0",
+        "?offset=abc",
+        "?limit=-1",
+        "?capability=telepathy",
+    ] {
+        let (status, body) = get_models(&app, query).await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{query}: {body}");
+        assert!(body["error"].is_string(), "{query}: {body}");
+    }
+    Ok(())
+}
@@ -0,0 +1,314 @@
+//! Snapshots of every SSE notification payload. A failure here means clients
+//! would see a different shape: bump `NOTIFICATION_SCHEMA_VERSION` and update
+//! the snapshot together.
+
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use codex_app_server_protocol::ServerNotification;
+use codex_protocol::ThreadId;
+use codex_web_server::approval_manager::ApprovalCancelReason;
+use codex_web_server::approval_manager::ApprovalCancelled;
+use codex_web_server::approval_manager::ApprovalExpired;
+use codex_web_server::approval_manager::ApprovalResolutionSource;
+use codex_web_server::approval_manager::ApprovalResolved;
+use codex_web_server::auto_archive::AutoArchivedThread;
+use codex_web_server::event_stream::EventStreamProcessor;
+use codex_web_server::handlers;
+use codex_web_server::sse_envelope::NOTIFICATION_SCHEMA_VERSION;
+use codex_web_server::sse_envelope::SseEnvelope;
+use codex_web_server::thread_progress::LongOperation;
+use codex_web_server::thread_progress::ProgressUpdate;
+use codex_web_server::thread_status::ThreadRunStatus;
+use codex_web_server::thread_status::ThreadStatusChange;
+use codex_web_server::turn_queue::DequeueReason;
+use codex_web_server::turn_queue::QueueChange;
+use codex_web_server::turn_queue::QueueEntry;
+use serde::Deserialize;
+use serde_json::Value;
+use serde_json::json;
+use std::collections::BTreeSet;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+const THREAD_ID: &str = "019bcfb9-4ea6-72e0-b43d-6b7e26ff0daf";
+
+#[derive(Deserialize)]
+struct Snapshot {
+    event: String,
+    data: Value,
+}
+
+fn snapshots() -> Result<Vec<Snapshot>> {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join("sse_notifications.json");
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+/// Position of each variant in the snapshot. Deliberately exhaustive: a new
+/// variant does not compile here until it has a snapshot.
+fn variant_index(notification: &ServerNotification) -> usize {
+    match notification {
+        ServerNotification::Error(_) => 0,
+        ServerNotification::ThreadStarted(_) => 1,
+        ServerNotification::ThreadStatusChanged(_) => 2,
+        ServerNotification::ThreadArchived(_) => 3,
+        ServerNotification::ThreadUnarchived(_) => 4,
+        ServerNotification::ThreadNameUpdated(_) => 5,
+        ServerNotification::ThreadTokenUsageUpdated(_) => 6,
+        ServerNotification::TurnStarted(_) => 7,
+        ServerNotification::TurnCompleted(_) => 8,
+        ServerNotification::TurnDiffUpdated(_) => 9,
+        ServerNotification::TurnPlanUpdated(_) => 10,
+        ServerNotification::ItemStarted(_) => 11,
+        ServerNotification::ItemCompleted(_) => 12,
+        ServerNotification::RawResponseItemCompleted(_) => 13,
+        ServerNotification::AgentMessageDelta(_) => 14,
+        ServerNotification::PlanDelta(_) => 15,
+        ServerNotification::CommandExecutionOutputDelta(_) => 16,
+        ServerNotification::TerminalInteraction(_) => 17,
+        ServerNotification::FileChangeOutputDelta(_) => 18,
+        ServerNotification::McpToolCallProgress(_) => 19,
+        ServerNotification::McpServerOauthLoginCompleted(_) => 20,
+        ServerNotification::AccountUpdated(_) => 21,
+        ServerNotification::AccountRateLimitsUpdated(_) => 22,
+        ServerNotification::AppListUpdated(_) => 23,
+        ServerNotification::ReasoningSummaryTextDelta(_) => 24,
+        ServerNotification::ReasoningSummaryPartAdded(_) => 25,
+        ServerNotification::ReasoningTextDelta(_) => 26,
+        ServerNotification::ContextCompacted(_) => 27,
+        ServerNotification::ModelRerouted(_) => 28,
+        ServerNotification::DeprecationNotice(_) => 29,
+        ServerNotification::ConfigWarning(_) => 30,
+        ServerNotification::FuzzyFileSearchSessionUpdated(_) => 31,
+        ServerNotification::FuzzyFileSearchSessionCompleted(_) => 32,
+        ServerNotification::WindowsWorldWritableWarning(_) => 33,
+        ServerNotification::WindowsSandboxSetupCompleted(_) => 34,
+        ServerNotification::AccountLoginCompleted(_) => 35,
+        ServerNotification::AuthStatusChange(_) => 36,
+        ServerNotification::LoginChatGptComplete(_) => 37,
+        ServerNotification::SessionConfigured(_) => 38,
+    }
+}
+const VARIANT_COUNT: usize = 39;
+
+#[test]
+fn test_every_notification_variant_matches_snapshot() -> Result<()> {
+    let mut covered = BTreeSet::new();
+    for snapshot in snapshots()? {
+        let notification: ServerNotification = serde_json::from_value(snapshot.data.clone())
+            .map_err(|e| anyhow::anyhow!("{}: {e}", snapshot.event))?;
+        assert_eq!(
+            EventStreamProcessor::event_type_name(&notification),
+            snapshot.event
+        );
+        assert_eq!(
+            serde_json::to_value(&notification)?,
+            snapshot.data,
+            "{} serializes differently",
+            snapshot.event
+        );
+        assert!(
+            covered.insert(variant_index(&notification)),
+            "{} has two snapshots",
+            snapshot.event
+        );
+    }
+    assert_eq!(covered, (0..VARIANT_COUNT).collect::<BTreeSet<_>>());
+    Ok(())
+}
+
+#[test]
+fn test_server_payloads_match_snapshot() -> Result<()> {
+    let thread_id = ThreadId::from_string(THREAD_ID)?;
+
+    let status = ThreadStatusChange {
+        thread_id,
+        status: ThreadRunStatus::WaitingApproval,
+    };
+    assert_eq!(
+        status.payload(),
+        json!({
+            "method": "thread/status/changed",
+            "params": { "threadId": THREAD_ID, "status": "waiting_approval" },
+        })
+    );
+
+    let queued = QueueChange::Queued {
+        thread_id,
+        entry: QueueEntry {
+            queue_entry_id: "q-1".to_string(),
+            enqueued_at: 1_760_000_000,
+            input: vec![],
+        },
+    };
+    assert_eq!(
+        queued.payload(),
+        json!({
+            "method": "turn/queued",
+            "params": {
+                "threadId": THREAD_ID,
+                "entry": { "queue_entry_id": "q-1", "enqueued_at": 1_760_000_000, "input": [] },
+            },
+        })
+    );
+    let dequeued = QueueChange::Dequeued {
+        thread_id,
+        queue_entry_id: "q-1".to_string(),
+        reason: DequeueReason::Started,
+        turn_id: Some("2".to_string()),
+    };
+    assert_eq!(
+        dequeued.payload(),
+        json!({
+            "method": "turn/dequeued",
+            "params": {
+                "threadId": THREAD_ID,
+                "queueEntryId": "q-1",
+                "reason": "started",
+                "turnId": "2",
+            },
+        })
+    );
+
+    let progress = ProgressUpdate {
+        thread_id,
+        operation: LongOperation::Rollback,
+        percent: 45,
+        background: true,
+    };
+    assert_eq!(
+        progress.payload(),
+        json!({
+            "method": "thread/rollingBack",
+            "params": { "threadId": THREAD_ID, "percent": 45, "background": true },
+        })
+    );
+
+    let resolved = ApprovalResolved {
+        thread_id,
+        approval_id: "call_1".to_string(),
+        source: ApprovalResolutionSource::External,
+    };
+    assert_eq!(
+        resolved.payload(),
+        json!({
+            "method": "approval/resolved",
+            "params": { "threadId": THREAD_ID, "approvalId": "call_1", "source": "external" },
+        })
+    );
+
+    let cancelled = ApprovalCancelled {
+        thread_id,
+        turn_id: "turn-1".to_string(),
+        approval_id: "call_1".to_string(),
+        reason: ApprovalCancelReason::TurnCompleted,
+    };
+    assert_eq!(
+        cancelled.payload(),
+        json!({
+            "method": "approval/cancelled",
+            "params": {
+                "threadId": THREAD_ID,
+                "turnId": "turn-1",
+                "approvalId": "call_1",
+                "reason": "turn_completed",
+            },
+        })
+    );
+
+    let expired = ApprovalExpired {
+        thread_id,
+        turn_id: "turn-1".to_string(),
+        approval_id: "call_1".to_string(),
+    };
+    assert_eq!(
+        expired.payload(),
+        json!({
+            "method": "approval/expired",
+            "params": { "threadId": THREAD_ID, "turnId": "turn-1", "approvalId": "call_1" },
+        })
+    );
+    Ok(())
+}
+
+#[test]
+fn test_v2_envelope_wraps_payload_with_schema_version() -> Result<()> {
+    let payload = json!({ "method": "thread/archived", "params": { "threadId": THREAD_ID } });
+
+    assert_eq!(
+        SseEnvelope::V1.data(payload.to_string()),
+        payload.to_string()
+    );
+    let wrapped: Value = serde_json::from_str(&SseEnvelope::V2.data(payload.to_string()))?;
+    assert_eq!(
+        wrapped,
+        json!({ "v": NOTIFICATION_SCHEMA_VERSION, "data": payload })
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_events_endpoint_negotiates_envelope() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = ThreadId::new();
+    // An auto-archived thread answers with a single `thread/archived` event.
+    state.auto_archived.lock().await.insert(
+        thread_id,
+        AutoArchivedThread {
+            archived_at: 1_768_737_600,
+            rollout_path: None,
+        },
+    );
+    let app = Router::new()
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .with_state(state);
+
+    let read = |query: &'static str| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .uri(format!("/api/v2/threads/{thread_id}/events{query}"))
+                        .body(Body::empty())?,
+                )
+                .await?;
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+            let data = String::from_utf8(body.to_vec())?
+                .lines()
+                .find_map(|line| line.strip_prefix("data: ").map(str::to_string));
+            anyhow::Ok((status, data))
+        }
+    };
+
+    let expected =
+        json!({ "method": "thread/archived", "params": { "threadId": thread_id.to_string() } });
+    for query in ["", "?envelope=v1"] {
+        let (status, data) = read(query).await?;
+        assert_eq!(status, StatusCode::OK);
+        let data: Value = serde_json::from_str(&data.unwrap_or_default())?;
+        assert_eq!(data, expected);
+    }
+
+    let (status, data) = read("?envelope=v2").await?;
+    assert_eq!(status, StatusCode::OK);
+    let data: Value = serde_json::from_str(&data.unwrap_or_default())?;
+    assert_eq!(
+        data,
+        json!({ "v": NOTIFICATION_SCHEMA_VERSION, "data": expected })
+    );
+
+    let (status, _) = read("?envelope=v9").await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    Ok(())
+}
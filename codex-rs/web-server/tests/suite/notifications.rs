@@ -0,0 +1,413 @@
+use anyhow::Result;
+use axum::Json;
+use axum::Router;
+use axum::body::Body;
+use axum::extract::State;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::post;
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::ErrorEvent;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::ExecApprovalRequestEvent;
+use codex_protocol::protocol::TurnCompleteEvent;
+use codex_web_server::handlers;
+use codex_web_server::notifications::Notification;
+use codex_web_server::notifications::NotificationEvent;
+use codex_web_server::notifications::NotificationSink;
+use codex_web_server::notifications::NotificationsConfig;
+use codex_web_server::notifications::Notifier;
+use codex_web_server::notifications::SinkConfig;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use futures::future::BoxFuture;
+use serde_json::Value;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+use crate::common::start_mock_provider_thread;
+
+/// Records every POST and answers with the queued statuses, then 200.
+#[derive(Clone, Default)]
+struct MockWebhook {
+    received: Arc<Mutex<Vec<Value>>>,
+    statuses: Arc<Mutex<VecDeque<StatusCode>>>,
+}
+
+impl MockWebhook {
+    /// Serves the mock on an ephemeral local port and returns its URL.
+    async fn start(&self) -> Result<String> {
+        let app = Router::new()
+            .route("/hook", post(record))
+            .with_state(self.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        Ok(format!("http://{addr}/hook"))
+    }
+
+    fn fail_next(&self, statuses: impl IntoIterator<Item = StatusCode>) {
+        self.statuses
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .extend(statuses);
+    }
+
+    fn received(&self) -> Vec<Value> {
+        self.received
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Waits until `count` requests have arrived.
+    async fn wait_for(&self, count: usize) -> Result<Vec<Value>> {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let received = self.received();
+                if received.len() >= count {
+                    return received;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .map_err(|_| anyhow::anyhow!("expected {count} webhook request(s)"))
+    }
+}
+
+async fn record(State(mock): State<MockWebhook>, Json(body): Json<Value>) -> StatusCode {
+    mock.received
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .push(body);
+    mock.statuses
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .pop_front()
+        .unwrap_or(StatusCode::OK)
+}
+
+fn config(sinks: Vec<SinkConfig>, max_attempts: u32) -> NotificationsConfig {
+    NotificationsConfig {
+        events: vec![
+            NotificationEvent::TurnCompleted,
+            NotificationEvent::TurnFailed,
+            NotificationEvent::ApprovalRequested,
+        ],
+        sinks,
+        max_attempts,
+        retry_delay_ms: 10,
+    }
+}
+
+fn webhook(url: String) -> SinkConfig {
+    SinkConfig::Webhook {
+        url,
+        headers: BTreeMap::new(),
+    }
+}
+
+fn turn_complete(message: &str) -> Event {
+    Event {
+        id: "turn-1".to_string(),
+        msg: EventMsg::TurnComplete(TurnCompleteEvent {
+            turn_id: "turn-1".to_string(),
+            last_agent_message: Some(message.to_string()),
+        }),
+    }
+}
+
+fn exec_approval() -> Event {
+    Event {
+        id: "turn-1".to_string(),
+        msg: EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
+            call_id: "call-1".to_string(),
+            approval_id: None,
+            turn_id: "turn-1".to_string(),
+            command: vec!["cargo".to_string(), "publish".to_string()],
+            cwd: PathBuf::from("/work"),
+            reason: None,
+            network_approval_context: None,
+            proposed_execpolicy_amendment: None,
+            parsed_cmd: vec![],
+        }),
+    }
+}
+
+/// Forwards each delivered notification to a channel.
+struct ChannelSink(mpsc::UnboundedSender<Value>);
+
+impl NotificationSink for ChannelSink {
+    fn describe(&self) -> String {
+        "channel".to_string()
+    }
+
+    fn deliver<'a>(&'a self, body: &'a [u8]) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            self.0.send(serde_json::from_slice(body)?)?;
+            Ok(())
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_webhook_receives_turn_completion() -> Result<()> {
+    let mock = MockWebhook::default();
+    let url = mock.start().await?;
+    let notifier = Notifier::from_config(&config(vec![webhook(url)], 3))?;
+    let thread_id = ThreadId::new();
+
+    notifier.observe(
+        thread_id,
+        &turn_complete("All tests pass.\nDetails follow."),
+    );
+
+    assert_eq!(
+        mock.wait_for(1).await?,
+        vec![json!({
+            "event": "turn/completed",
+            "threadId": thread_id.to_string(),
+            "turnId": "turn-1",
+            "text": "Turn finished: All tests pass.",
+        })]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_failed_delivery_is_retried() -> Result<()> {
+    let mock = MockWebhook::default();
+    mock.fail_next([StatusCode::INTERNAL_SERVER_ERROR, StatusCode::BAD_GATEWAY]);
+    let url = mock.start().await?;
+    let notifier = Notifier::from_config(&config(vec![webhook(url)], 3))?;
+
+    notifier.observe(ThreadId::new(), &exec_approval());
+
+    let received = mock.wait_for(3).await?;
+    assert!(
+        received
+            .iter()
+            .all(|body| body["event"] == "approval/requested"
+                && body["text"] == "Approval needed to run: cargo publish")
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delivery_gives_up_after_max_attempts() -> Result<()> {
+    let mock = MockWebhook::default();
+    mock.fail_next([StatusCode::INTERNAL_SERVER_ERROR; 5]);
+    let url = mock.start().await?;
+    let notifier = Notifier::from_config(&config(vec![webhook(url)], 2))?;
+
+    notifier.observe(ThreadId::new(), &turn_complete("done"));
+
+    mock.wait_for(2).await?;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(mock.received().len(), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unselected_events_and_muted_threads_are_skipped() -> Result<()> {
+    let (sender, mut delivered) = mpsc::unbounded_channel();
+    let mut approvals_only = config(Vec::new(), 1);
+    approvals_only.events = vec![NotificationEvent::ApprovalRequested];
+    let notifier = Notifier::new(&approvals_only, vec![Arc::new(ChannelSink(sender))]);
+    let muted = ThreadId::new();
+    let watched = ThreadId::new();
+    notifier.set_muted(muted, true);
+
+    notifier.observe(watched, &turn_complete("done"));
+    notifier.observe(muted, &exec_approval());
+    notifier.observe(watched, &exec_approval());
+
+    let first = tokio::time::timeout(Duration::from_secs(5), delivered.recv()).await?;
+    assert_eq!(
+        first.map(|body| body["threadId"].clone()),
+        Some(json!(watched.to_string()))
+    );
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(delivered.try_recv().is_err());
+
+    // Opting back in restores delivery.
+    notifier.set_muted(muted, false);
+    notifier.observe(muted, &exec_approval());
+    let next = tokio::time::timeout(Duration::from_secs(5), delivered.recv()).await?;
+    assert_eq!(
+        next.map(|body| body["threadId"].clone()),
+        Some(json!(muted.to_string()))
+    );
+    Ok(())
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_command_sink_gets_notification_on_stdin() -> Result<()> {
+    let dir = tempfile::TempDir::new()?;
+    let out = dir.path().join("notification.json");
+    let command = SinkConfig::Command {
+        command: vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            format!("cat > '{}'", out.display()),
+        ],
+    };
+    let notifier = Notifier::from_config(&config(vec![command], 1))?;
+    let thread_id = ThreadId::new();
+
+    notifier.observe(thread_id, &turn_complete("done"));
+
+    let written = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            if let Ok(contents) = tokio::fs::read_to_string(&out).await
+                && let Ok(body) = serde_json::from_str::<Value>(&contents)
+            {
+                return body;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await?;
+    assert_eq!(written["event"], "turn/completed");
+    assert_eq!(written["threadId"], json!(thread_id.to_string()));
+    Ok(())
+}
+
+#[test]
+fn test_failed_turns_notify_but_retryable_errors_do_not() {
+    let thread_id = ThreadId::new();
+    let failed = Event {
+        id: "turn-2".to_string(),
+        msg: EventMsg::Error(ErrorEvent {
+            message: "context window exceeded".to_string(),
+            codex_error_info: None,
+        }),
+    };
+
+    assert_eq!(
+        Notification::from_event(thread_id, &failed),
+        Some(Notification {
+            event: NotificationEvent::TurnFailed,
+            thread_id: thread_id.to_string(),
+            turn_id: "turn-2".to_string(),
+            text: "Turn failed: context window exceeded".to_string(),
+        })
+    );
+    let message = Event {
+        id: "turn-2".to_string(),
+        msg: EventMsg::Warning(codex_protocol::protocol::WarningEvent {
+            message: "slow down".to_string(),
+        }),
+    };
+    assert_eq!(Notification::from_event(thread_id, &message), None);
+}
+
+#[test]
+fn test_config_section_is_read_from_config_toml() -> Result<()> {
+    let parsed = NotificationsConfig::parse(
+        r#"
+model = "test-model"
+
+[web_server.notifications]
+events = ["turn/completed"]
+
+[[web_server.notifications.sinks]]
+type = "webhook"
+url = "https://hooks.example.com/codex"
+headers = { Authorization = "Bearer secret" }
+
+[[web_server.notifications.sinks]]
+type = "command"
+command = ["notify-send", "Codex"]
+"#,
+    )?;
+
+    assert_eq!(
+        parsed,
+        Some(NotificationsConfig {
+            events: vec![NotificationEvent::TurnCompleted],
+            sinks: vec![
+                SinkConfig::Webhook {
+                    url: "https://hooks.example.com/codex".to_string(),
+                    headers: BTreeMap::from([(
+                        "Authorization".to_string(),
+                        "Bearer secret".to_string()
+                    )]),
+                },
+                SinkConfig::Command {
+                    command: vec!["notify-send".to_string(), "Codex".to_string()],
+                },
+            ],
+            max_attempts: 3,
+            retry_delay_ms: 1_000,
+        })
+    );
+    assert_eq!(NotificationsConfig::parse("model = \"test-model\"")?, None);
+    assert!(NotificationsConfig::parse("[web_server.notifications]\nevents = [\"nope\"]").is_err());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_turn_completion_notifies_without_an_event_stream() -> Result<()> {
+    let server = start_mock_server().await;
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_response_created("resp-1"),
+            ev_assistant_message("msg-1", "done while nobody watched"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    let fixture = TestFixture::new().await?;
+    let (sender, mut delivered) = mpsc::unbounded_channel();
+    let state = create_test_state(&fixture, "test-token").with_notifier(Notifier::new(
+        &config(Vec::new(), 1),
+        vec![Arc::new(ChannelSink(sender))],
+    ));
+    let thread_id = start_mock_provider_thread(&fixture, &state, &server.uri()).await?;
+    let app = Router::new()
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(handlers::turns::send_turn),
+        )
+        .with_state(state);
+
+    // No SSE stream is ever opened on the thread.
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/v2/threads/{thread_id}/turns"))
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({"input": [{"type": "text", "text": "hi"}]}).to_string(),
+                ))?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let notification = tokio::time::timeout(Duration::from_secs(10), delivered.recv())
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("notifier dropped its sink"))?;
+    assert_eq!(notification["event"], "turn/completed");
+    assert_eq!(notification["threadId"], json!(thread_id.to_string()));
+    Ok(())
+}
@@ -0,0 +1,135 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::middleware::from_fn_with_state;
+use axum::routing::get;
+use axum::routing::post;
+use codex_web_server::handlers::auth::pair;
+use codex_web_server::handlers::metrics::get_metrics;
+use codex_web_server::middleware::auth_middleware;
+use codex_web_server::pairing::PAIRING_CODE_TTL;
+use codex_web_server::pairing::Pairing;
+use codex_web_server::pairing::TOKEN_STORE_FILE;
+use codex_web_server::state::WebServerState;
+use serde_json::Value;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+fn app(state: WebServerState) -> Router {
+    let protected = Router::new()
+        .route("/api/v2/metrics", get(get_metrics))
+        .layer(from_fn_with_state(state.clone(), auth_middleware));
+    Router::new()
+        .route("/api/v2/auth/pair", post(pair))
+        .merge(protected)
+        .with_state(state)
+}
+
+async fn redeem(app: &Router, code: &str) -> Result<(StatusCode, Value)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v2/auth/pair")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "code": code }).to_string()))?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+async fn metrics_status(app: &Router, token: &str) -> Result<StatusCode> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/v2/metrics")
+                .header("authorization", format!("Bearer {token}"))
+                .body(Body::empty())?,
+        )
+        .await?;
+    Ok(response.status())
+}
+
+#[tokio::test]
+async fn test_pairing_code_is_exchanged_for_a_stored_token() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "printed-token");
+    let code = state.pairing.issue_code()?;
+    let code_file = std::fs::read_to_string(state.pairing.code_path())?;
+    assert_eq!(code_file, code);
+    let app = app(state);
+
+    let (status, body) = redeem(&app, &code_file).await?;
+
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let token = body["token"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("no token in {body}"))?;
+    assert_eq!(metrics_status(&app, token).await?, StatusCode::OK);
+    assert_eq!(metrics_status(&app, "printed-token").await?, StatusCode::OK);
+    assert_eq!(
+        metrics_status(&app, "guess").await?,
+        StatusCode::UNAUTHORIZED
+    );
+    assert!(!fixture.codex_home.path().join("web-pairing-code").exists());
+
+    // Only the hash is stored, and a restarted server still accepts the token.
+    let stored = std::fs::read_to_string(fixture.codex_home.path().join(TOKEN_STORE_FILE))?;
+    assert!(!stored.contains(token), "{stored}");
+    let restarted = Pairing::new(fixture.codex_home_path(), PAIRING_CODE_TTL);
+    assert!(restarted.is_paired_token(token));
+    assert!(restarted.has_paired_tokens());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_pairing_code_cannot_be_reused() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "printed-token");
+    let code = state.pairing.issue_code()?;
+    let app = app(state);
+
+    let (status, _) = redeem(&app, "not-the-code").await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, _) = redeem(&app, &code).await?;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = redeem(&app, &code).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["code"], json!("invalid_code"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_expired_pairing_code_is_rejected() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let mut state = create_test_state(&fixture, "printed-token");
+    state.pairing = Arc::new(Pairing::new(
+        fixture.codex_home_path(),
+        Duration::from_millis(10),
+    ));
+    let code = state.pairing.issue_code()?;
+    let app = app(state);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let (status, body) = redeem(&app, &code).await?;
+
+    assert_eq!(status, StatusCode::GONE, "{body}");
+    assert_eq!(body["code"], json!("expired"));
+    assert!(!fixture.codex_home.path().join("web-pairing-code").exists());
+    let (status, _) = redeem(&app, &code).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    Ok(())
+}
@@ -0,0 +1,251 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use codex_core::config::ConfigBuilder;
+use codex_protocol::ThreadId;
+use codex_web_server::handlers;
+use codex_web_server::handlers::threads::start_configured_thread;
+use serde_json::Value;
+use serde_json::json;
+use std::path::PathBuf;
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+use crate::common::EventReader;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+/// Custom provider that does not need auth; applying a patch never calls the
+/// model. Full access keeps the test independent of the platform sandbox.
+const PATCH_CONFIG: &str = r#"
+model = "test-model"
+approval_policy = "never"
+sandbox_mode = "danger-full-access"
+model_provider = "offline"
+
+[model_providers.offline]
+name = "Offline provider"
+base_url = "http://127.0.0.1:9/v1"
+wire_api = "responses"
+"#;
+
+struct PatchHarness {
+    app: Router,
+    thread_id: ThreadId,
+    workspace: TempDir,
+    _fixture: TestFixture,
+}
+
+impl PatchHarness {
+    async fn start() -> Result<Self> {
+        let fixture = TestFixture::new().await?;
+        fixture.create_test_config(PATCH_CONFIG)?;
+        let workspace = TempDir::new()?;
+        std::fs::write(workspace.path().join("notes.txt"), "alpha\nbeta\ngamma\n")?;
+
+        let mut config = ConfigBuilder::default()
+            .codex_home(fixture.codex_home_path())
+            .build()
+            .await?;
+        config.cwd = workspace.path().canonicalize()?;
+        // Core applies patches by re-running this executable.
+        config.codex_linux_sandbox_exe =
+            Some(PathBuf::from(env!("CARGO_BIN_EXE_codex-web-server")));
+        let state = create_test_state(&fixture, "test-token");
+        let thread_id = start_configured_thread(&state, config).await?.thread_id;
+        let app = Router::new()
+            .route(
+                "/api/v2/threads/{id}/patches",
+                post(handlers::patches::apply_patch),
+            )
+            .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+            .with_state(state);
+
+        Ok(Self {
+            app,
+            thread_id,
+            workspace,
+            _fixture: fixture,
+        })
+    }
+
+    async fn submit(&self, patch: &str) -> Result<(StatusCode, Value)> {
+        let response = self
+            .app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/v2/threads/{}/patches", self.thread_id))
+                    .header("content-type", "application/json")
+                    .body(Body::from(json!({ "patch": patch }).to_string()))?,
+            )
+            .await?;
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await?;
+        Ok((status, serde_json::from_slice(&body)?))
+    }
+
+    fn read(&self, name: &str) -> std::io::Result<String> {
+        std::fs::read_to_string(self.workspace.path().join(name))
+    }
+}
+
+#[tokio::test]
+async fn test_unified_diff_is_applied_and_reported_as_file_change() -> Result<()> {
+    let harness = PatchHarness::start().await?;
+    let mut events = EventReader::open(&harness.app, harness.thread_id).await?;
+
+    let (status, body) = harness
+        .submit(
+            "diff --git a/notes.txt b/notes.txt
+--- a/notes.txt
++++ b/notes.txt
+@@ -1,3 +1,3 @@
+ alpha
+-beta
++BETA
+ gamma
+--- /dev/null
++++ b/added.txt
+@@ -0,0 +1 @@
++new file
+",
+        )
+        .await?;
+
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(
+        body["files"],
+        json!([
+            {"path": "notes.txt", "move_path": null, "action": "update", "status": "verified", "error": null},
+            {"path": "added.txt", "move_path": null, "action": "add", "status": "verified", "error": null},
+        ])
+    );
+
+    let started = events.next_of("item/started").await?;
+    assert_eq!(started["item"]["type"], json!("fileChange"));
+    assert_eq!(started["item"]["id"], body["item_id"]);
+    let completed = events.next_of("item/completed").await?;
+    assert_eq!(completed["item"]["id"], body["item_id"]);
+    assert_eq!(completed["item"]["status"], json!("completed"));
+
+    assert_eq!(harness.read("notes.txt")?, "alpha\nBETA\ngamma\n");
+    assert_eq!(harness.read("added.txt")?, "new file\n");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_conflicting_patch_reports_per_file_results_and_changes_nothing() -> Result<()> {
+    let harness = PatchHarness::start().await?;
+
+    let (status, body) = harness
+        .submit(
+            "*** Begin Patch
+*** Add File: other.txt
++unused
+*** Update File: notes.txt
+@@
+ alpha
+-delta
++DELTA
+*** Delete File: missing.txt
+*** End Patch",
+        )
+        .await?;
+
+    assert_eq!(status, StatusCode::CONFLICT, "{body}");
+    let files = body["files"].as_array().cloned().unwrap_or_default();
+    let statuses: Vec<_> = files
+        .iter()
+        .map(|file| (file["path"].clone(), file["status"].clone()))
+        .collect();
+    assert_eq!(
+        statuses,
+        vec![
+            (json!("other.txt"), json!("verified")),
+            (json!("notes.txt"), json!("conflict")),
+            (json!("missing.txt"), json!("conflict")),
+        ]
+    );
+    assert!(files[1]["error"].is_string(), "{body}");
+
+    assert_eq!(harness.read("notes.txt")?, "alpha\nbeta\ngamma\n");
+    assert!(!harness.workspace.path().join("other.txt").exists());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_malformed_patch_returns_hunk_errors() -> Result<()> {
+    let harness = PatchHarness::start().await?;
+
+    let (status, body) = harness
+        .submit("*** Begin Patch\n*** Frobnicate File: notes.txt\n*** End Patch")
+        .await?;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+    assert_eq!(body["hunk_errors"][0]["line"], json!(2));
+
+    // The hunk promises three old lines but only has two.
+    let (status, body) = harness
+        .submit("--- a/notes.txt\n+++ b/notes.txt\n@@ -1,3 +1,3 @@\n alpha\n-beta\n")
+        .await?;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+    assert_eq!(body["hunk_errors"][0]["line"], json!(3));
+
+    let (status, _) = harness.submit("just some text").await?;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_paths_outside_the_cwd_are_refused_unread() -> Result<()> {
+    let harness = PatchHarness::start().await?;
+    let outside = TempDir::new()?;
+    let secret = outside.path().join("secret.txt");
+    std::fs::write(&secret, "top secret\n")?;
+    let relative = format!(
+        "../{}/secret.txt",
+        outside
+            .path()
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+    );
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(outside.path(), harness.workspace.path().join("link"))?;
+
+    let mut escaping = vec![relative, secret.display().to_string()];
+    if cfg!(unix) {
+        escaping.push("link/secret.txt".to_string());
+    }
+    for path in escaping {
+        let (status, body) = harness
+            .submit(&format!(
+                "*** Begin Patch\n*** Update File: {path}\n@@\n-wrong guess\n+x\n*** End Patch"
+            ))
+            .await?;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+        let message = body["hunk_errors"][0]["message"]
+            .as_str()
+            .unwrap_or_default();
+        assert!(
+            message.contains("outside the thread's working directory"),
+            "{body}"
+        );
+        assert!(!body.to_string().contains("top secret"), "{body}");
+    }
+    assert_eq!(std::fs::read_to_string(&secret)?, "top secret\n");
+
+    // A rename out of the cwd is refused too.
+    let (status, body) = harness
+        .submit("*** Begin Patch\n*** Update File: notes.txt\n*** Move to: ../moved.txt\n@@\n alpha\n-beta\n+BETA\n*** End Patch")
+        .await?;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY, "{body}");
+    assert_eq!(harness.read("notes.txt")?, "alpha\nbeta\ngamma\n");
+    Ok(())
+}
@@ -0,0 +1,140 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use codex_web_server::handlers::health::ready;
+use codex_web_server::handlers::maintenance::fix_permissions;
+use codex_web_server::permissions;
+use serde_json::Value;
+use serde_json::json;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+fn chmod(path: &Path, mode: u32) -> Result<()> {
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    Ok(())
+}
+
+fn mode_of(path: &Path) -> Result<u32> {
+    Ok(std::fs::metadata(path)?.permissions().mode() & 0o777)
+}
+
+/// A fixture whose codex_home, sessions, attachments and auth file are all
+/// open to other users.
+async fn loose_fixture() -> Result<TestFixture> {
+    let fixture = TestFixture::new().await?;
+    let home = fixture.codex_home.path();
+    std::fs::write(home.join("auth.json"), "{}")?;
+    chmod(home, 0o777)?;
+    chmod(&home.join("sessions"), 0o750)?;
+    chmod(fixture.attachments_dir.path(), 0o755)?;
+    chmod(&home.join("auth.json"), 0o644)?;
+    Ok(fixture)
+}
+
+async fn request(app: &Router, method: &str, uri: &str) -> Result<(StatusCode, Value)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(Body::empty())?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+#[tokio::test]
+async fn test_audit_flags_paths_open_to_other_users() -> Result<()> {
+    let fixture = loose_fixture().await?;
+    let home = fixture.codex_home.path();
+
+    let issues = permissions::audit(home, fixture.attachments_dir.path());
+    let found: Vec<_> = issues
+        .iter()
+        .map(|issue| {
+            (
+                issue.path.clone(),
+                issue.mode.as_str(),
+                issue.expected.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (home.display().to_string(), "0777", "0700"),
+            (home.join("sessions").display().to_string(), "0750", "0700"),
+            (
+                fixture.attachments_dir.path().display().to_string(),
+                "0755",
+                "0700"
+            ),
+            (home.join("auth.json").display().to_string(), "0644", "0600"),
+        ]
+    );
+
+    let warning = permissions::config_warning(&issues)
+        .ok_or_else(|| anyhow::anyhow!("expected a configWarning"))?;
+    assert!(
+        warning
+            .details
+            .is_some_and(|details| details.contains("fix-permissions"))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_readiness_warns_without_failing() -> Result<()> {
+    let fixture = loose_fixture().await?;
+    let app = Router::new()
+        .route("/health/ready", get(ready))
+        .with_state(create_test_state(&fixture, "test-token"));
+
+    let (_, body) = request(&app, "GET", "/health/ready").await?;
+    let check = body["checks"]
+        .as_array()
+        .and_then(|checks| checks.iter().find(|check| check["name"] == "permissions"))
+        .cloned()
+        .ok_or_else(|| anyhow::anyhow!("no permissions check in {body}"))?;
+    assert_eq!(check["status"], json!("warn"));
+    assert!(
+        check["detail"]
+            .as_str()
+            .is_some_and(|detail| detail.contains("0777")),
+        "{check}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fix_permissions_restricts_to_owner() -> Result<()> {
+    let fixture = loose_fixture().await?;
+    let home = fixture.codex_home.path();
+    let app = Router::new()
+        .route("/api/v2/maintenance/fix-permissions", post(fix_permissions))
+        .with_state(create_test_state(&fixture, "test-token"));
+
+    let (status, body) = request(&app, "POST", "/api/v2/maintenance/fix-permissions").await?;
+
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["fixed"].as_array().map(Vec::len), Some(4), "{body}");
+    assert_eq!(body["remaining"], json!([]));
+    assert_eq!(mode_of(home)?, 0o700);
+    assert_eq!(mode_of(&home.join("sessions"))?, 0o700);
+    assert_eq!(mode_of(fixture.attachments_dir.path())?, 0o700);
+    assert_eq!(mode_of(&home.join("auth.json"))?, 0o600);
+    assert!(permissions::audit(home, fixture.attachments_dir.path()).is_empty());
+    Ok(())
+}
@@ -0,0 +1,287 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use codex_core::config::ConfigBuilder;
+use codex_protocol::ThreadId;
+use codex_web_server::config_loader::ConfigLoader;
+use codex_web_server::handlers;
+use codex_web_server::mock_model::MOCK_PROVIDER_ID;
+use codex_web_server::mock_model::MockModelServer;
+use codex_web_server::mock_model::MockScript;
+use codex_web_server::provider_overrides::ProviderHosts;
+use codex_web_server::provider_overrides::ProviderOverride;
+use codex_web_server::provider_overrides::apply_provider_overrides;
+use codex_web_server::state::WebServerState;
+use futures::FutureExt;
+use serde_json::Value;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+use crate::common::EventReader;
+use crate::common::TEST_CONFIG;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+const APPROVAL_CONFIG: &str = r#"
+model = "test-model"
+approval_policy = "on-request"
+sandbox_mode = "workspace-write"
+"#;
+
+/// Nothing listens here, so a thread only reaches the model through an
+/// override.
+const UNREACHABLE_BASE_URL: &str = "http://127.0.0.1:9/v1";
+
+const GATEWAY_SECRET: &str = "gw-secret-value";
+
+async fn post_json(app: &Router, uri: &str, body: Value) -> Result<(StatusCode, Value)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+fn app(state: WebServerState) -> Router {
+    Router::new()
+        .route("/api/v2/threads", post(handlers::threads::create_thread))
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(handlers::turns::send_turn),
+        )
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .with_state(state)
+}
+
+fn gateway_override(base_url: &str) -> ProviderOverride {
+    ProviderOverride {
+        base_url: Some(base_url.to_string()),
+        headers: BTreeMap::from([("x-gateway-key".to_string(), GATEWAY_SECRET.to_string())]),
+    }
+}
+
+#[tokio::test]
+async fn test_override_reaches_the_thread_provider_map() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let mut config = ConfigBuilder::default()
+        .codex_home(fixture.codex_home_path())
+        .build()
+        .await?;
+    let untouched = config.model_providers.clone();
+    let global_base_url = config.model_provider.base_url.clone();
+    let active_id = config.model_provider_id.clone();
+
+    let overrides = HashMap::from([(
+        "openai".to_string(),
+        gateway_override("https://gateway.internal/project-a/v1"),
+    )]);
+    apply_provider_overrides(
+        &mut config,
+        &overrides,
+        &ProviderHosts::new(["gateway.internal".to_string()]),
+    )
+    .map_err(|err| anyhow::anyhow!("{err:?}"))?;
+
+    // Tools look providers up by id, so the map entry carries the override.
+    let provider = &config.model_providers["openai"];
+    assert_eq!(
+        provider.base_url.as_deref(),
+        Some("https://gateway.internal/project-a/v1")
+    );
+    assert_eq!(
+        provider
+            .http_headers
+            .as_ref()
+            .and_then(|headers| headers.get("x-gateway-key"))
+            .map(String::as_str),
+        Some(GATEWAY_SECRET)
+    );
+    if active_id == "openai" {
+        assert_eq!(config.model_provider.base_url, provider.base_url);
+    } else {
+        assert_eq!(config.model_provider.base_url, global_base_url);
+    }
+    for (id, provider) in &untouched {
+        if id != "openai" {
+            assert_eq!(
+                config.model_providers[id].base_url, provider.base_url,
+                "{id}"
+            );
+        }
+    }
+
+    // A second thread's config is loaded fresh and keeps the global provider.
+    let other = ConfigBuilder::default()
+        .codex_home(fixture.codex_home_path())
+        .build()
+        .await?;
+    assert_eq!(
+        other.model_providers["openai"].base_url,
+        untouched["openai"].base_url
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rejected_override_leaves_config_untouched() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let mut config = ConfigBuilder::default()
+        .codex_home(fixture.codex_home_path())
+        .build()
+        .await?;
+    let before = config.model_providers["openai"].clone();
+
+    let overrides = HashMap::from([(
+        "openai".to_string(),
+        gateway_override("https://evil.example/v1"),
+    )]);
+    let rejected = apply_provider_overrides(
+        &mut config,
+        &overrides,
+        &ProviderHosts::new(["gateway.internal".to_string()]),
+    );
+    assert!(rejected.is_err());
+    assert_eq!(config.model_providers["openai"], before);
+
+    let unknown = HashMap::from([(
+        "no-such-provider".to_string(),
+        gateway_override("https://gateway.internal/v1"),
+    )]);
+    assert!(
+        apply_provider_overrides(
+            &mut config,
+            &unknown,
+            &ProviderHosts::new(["gateway.internal".to_string()]),
+        )
+        .is_err()
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_thread_refuses_hosts_outside_the_allow_list() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let unrestricted = app(create_test_state(&fixture, "test-token"));
+    let allow_listed = app(create_test_state(&fixture, "test-token")
+        .with_provider_hosts(ProviderHosts::new(["gateway.internal".to_string()])));
+    let request = json!({
+        "cwd": fixture.codex_home.path(),
+        "provider_overrides": {
+            "openai": {
+                "base_url": "https://evil.example/v1",
+                "headers": { "x-gateway-key": GATEWAY_SECRET },
+            },
+        },
+    });
+
+    // Without an allow-list no host is permitted.
+    let (status, body) = post_json(&unrestricted, "/api/v2/threads", request.clone()).await?;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+    assert_eq!(body["allowed_hosts"], json!([]));
+
+    let (status, body) = post_json(&allow_listed, "/api/v2/threads", request).await?;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+    assert_eq!(body["allowed_hosts"], json!(["gateway.internal"]));
+    assert!(!body.to_string().contains(GATEWAY_SECRET), "{body}");
+
+    let (status, body) = post_json(
+        &allow_listed,
+        "/api/v2/threads",
+        json!({
+            "cwd": fixture.codex_home.path(),
+            "provider_overrides": {
+                "openai": { "headers": { "bad header": GATEWAY_SECRET } },
+            },
+        }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    assert!(!body.to_string().contains(GATEWAY_SECRET), "{body}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_thread_reaches_the_model_through_the_override() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(APPROVAL_CONFIG)?;
+    let script = MockScript::load(
+        &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mock_thread.json"),
+    )?;
+    let server = MockModelServer::start(script).await?;
+
+    // The global config points the mock provider nowhere.
+    let mut unreachable = server.provider();
+    unreachable.base_url = Some(UNREACHABLE_BASE_URL.to_string());
+    let codex_home = fixture.codex_home_path();
+    let loader = ConfigLoader::new(Arc::new(move || {
+        let codex_home = codex_home.clone();
+        let provider = unreachable.clone();
+        async move {
+            let mut config = ConfigBuilder::default()
+                .codex_home(codex_home)
+                .build()
+                .await?;
+            config.model_provider_id = MOCK_PROVIDER_ID.to_string();
+            config.model_provider = provider;
+            Ok(config)
+        }
+        .boxed()
+    }));
+    let state = create_test_state(&fixture, "test-token")
+        .with_config_loader(loader)
+        .with_provider_hosts(ProviderHosts::new(["127.0.0.1".to_string()]));
+    let app = app(state);
+
+    let (status, created) = post_json(
+        &app,
+        "/api/v2/threads",
+        json!({
+            "cwd": fixture.codex_home.path(),
+            "provider_overrides": {
+                MOCK_PROVIDER_ID: {
+                    "base_url": server.base_url(),
+                    "headers": { "x-gateway-key": GATEWAY_SECRET },
+                },
+            },
+        }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{created}");
+    assert!(!created.to_string().contains(GATEWAY_SECRET), "{created}");
+    let thread_id = ThreadId::from_string(created["thread_id"].as_str().unwrap_or_default())?;
+    let mut events = EventReader::open(&app, thread_id).await?;
+
+    let (status, body) = post_json(
+        &app,
+        &format!("/api/v2/threads/{thread_id}/turns"),
+        json!({ "input": [{ "type": "text", "text": "Check the workspace" }] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    // The scripted response only arrives through the overridden base URL.
+    events
+        .next_of("item/commandExecution/requestApproval")
+        .await?;
+    Ok(())
+}
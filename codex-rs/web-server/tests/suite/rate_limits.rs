@@ -0,0 +1,111 @@
+use anyhow::Result;
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::CreditsSnapshot;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::RateLimitSnapshot;
+use codex_protocol::protocol::RateLimitWindow;
+use codex_protocol::protocol::TokenCountEvent;
+use codex_protocol::protocol::TokenUsage;
+use codex_protocol::protocol::TokenUsageInfo;
+use codex_web_server::event_stream::EventStreamProcessor;
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+fn usage(total_tokens: i64) -> TokenUsage {
+    TokenUsage {
+        input_tokens: total_tokens - 20,
+        cached_input_tokens: 0,
+        output_tokens: 20,
+        reasoning_output_tokens: 0,
+        total_tokens,
+    }
+}
+
+#[tokio::test]
+async fn test_token_count_with_rate_limits_streams_usage_and_rate_limits() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = ThreadId::new();
+    let processor = EventStreamProcessor::new(thread_id, Arc::new(state));
+    let token_count = |info, rate_limits| Event {
+        id: "turn-1".to_string(),
+        msg: EventMsg::TokenCount(TokenCountEvent { info, rate_limits }),
+    };
+    let rate_limits = RateLimitSnapshot {
+        limit_id: Some("codex".to_string()),
+        limit_name: None,
+        primary: Some(RateLimitWindow {
+            used_percent: 42.0,
+            window_minutes: Some(300),
+            resets_at: Some(1_760_000_000),
+        }),
+        secondary: None,
+        credits: Some(CreditsSnapshot {
+            has_credits: true,
+            unlimited: false,
+            balance: Some("12.50".to_string()),
+        }),
+        plan_type: None,
+    };
+
+    let notifications = processor
+        .process_event(token_count(
+            Some(TokenUsageInfo {
+                total_token_usage: usage(120),
+                last_token_usage: usage(120),
+                model_context_window: Some(200_000),
+            }),
+            Some(rate_limits.clone()),
+        ))
+        .await;
+
+    let [usage_updated, rate_limits_updated] = notifications.as_slice() else {
+        panic!("expected two notifications, got {notifications:?}");
+    };
+    assert_eq!(
+        EventStreamProcessor::event_type_name(usage_updated),
+        "thread/tokenUsage/updated"
+    );
+    assert_eq!(
+        serde_json::to_value(usage_updated)?["params"]["tokenUsage"]["total"]["totalTokens"],
+        json!(120)
+    );
+    assert_eq!(
+        EventStreamProcessor::event_type_name(rate_limits_updated),
+        "account/rateLimits/updated"
+    );
+    assert_eq!(
+        serde_json::to_value(rate_limits_updated)?["params"]["rateLimits"],
+        json!({
+            "limitId": "codex",
+            "limitName": null,
+            "primary": { "usedPercent": 42, "windowDurationMins": 300, "resetsAt": 1_760_000_000 },
+            "secondary": null,
+            "credits": { "hasCredits": true, "unlimited": false, "balance": "12.50" },
+            "planType": null
+        })
+    );
+
+    // Either half arrives on its own when the other is missing.
+    let notifications = processor
+        .process_event(token_count(None, Some(rate_limits)))
+        .await;
+    let [only] = notifications.as_slice() else {
+        panic!("expected a single notification, got {notifications:?}");
+    };
+    assert_eq!(
+        EventStreamProcessor::event_type_name(only),
+        "account/rateLimits/updated"
+    );
+    assert!(
+        processor
+            .process_event(token_count(None, None))
+            .await
+            .is_empty()
+    );
+    Ok(())
+}
@@ -0,0 +1,222 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::patch;
+use axum::routing::post;
+use codex_core::config::service::ConfigService;
+use codex_core::config_loader::CloudRequirementsLoader;
+use codex_core::config_loader::LoaderOverrides;
+use codex_protocol::config_types::SandboxMode;
+use codex_protocol::protocol::AskForApproval;
+use codex_web_server::handlers::config::batch_write_config;
+use codex_web_server::handlers::config::write_config_value;
+use codex_web_server::handlers::threads::create_thread;
+use codex_web_server::instance::instance_id;
+use codex_web_server::requirements::check_thread_overrides;
+use codex_web_server::state::WebServerState;
+use serde_json::Value;
+use serde_json::json;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+use crate::common::TEST_CONFIG;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+/// Managed config that requires `on-request` approvals and allows
+/// `read-only` or `workspace-write` sandboxes.
+const MANAGED_CONFIG: &str = r#"
+approval_policy = "on-request"
+sandbox_mode = "workspace-write"
+"#;
+
+fn requirements_state(fixture: &TestFixture) -> Result<WebServerState> {
+    let managed_config_path = fixture.codex_home_path().join("managed_config.toml");
+    std::fs::write(&managed_config_path, MANAGED_CONFIG)?;
+
+    let mut state = create_test_state(fixture, "test-token");
+    state.config_service = Arc::new(ConfigService::new(
+        fixture.codex_home_path(),
+        vec![],
+        LoaderOverrides {
+            managed_config_path: Some(managed_config_path),
+            ..Default::default()
+        },
+        CloudRequirementsLoader::default(),
+    ));
+    Ok(state)
+}
+
+fn requirements_router(state: WebServerState) -> Router {
+    Router::new()
+        .route("/api/v2/threads", post(create_thread))
+        .route(
+            "/api/v2/config",
+            patch(batch_write_config).put(write_config_value),
+        )
+        .with_state(state)
+}
+
+async fn send(app: Router, method: &str, uri: &str, body: Value) -> Result<(StatusCode, Value)> {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))?;
+    let response = app.oneshot(request).await?;
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+#[tokio::test]
+async fn test_create_thread_rejects_disallowed_approval_policy() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let app = requirements_router(requirements_state(&fixture)?);
+
+    let (status, body) = send(
+        app,
+        "POST",
+        "/api/v2/threads",
+        json!({ "approval_policy": "never" }),
+    )
+    .await?;
+
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(
+        body,
+        json!({
+            "error": "Requested value \"never\" is not allowed by allowed_approval_policies",
+            "status": 403,
+            "instance_id": instance_id(),
+            "constraint": "allowed_approval_policies",
+            "requested": "never",
+            "allowed": ["on-request"],
+        })
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_create_thread_rejects_disallowed_sandbox_mode() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let app = requirements_router(requirements_state(&fixture)?);
+
+    let (status, body) = send(
+        app,
+        "POST",
+        "/api/v2/threads",
+        json!({ "approval_policy": "on-request", "sandbox_mode": "danger-full-access" }),
+    )
+    .await?;
+
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["constraint"], json!("allowed_sandbox_modes"));
+    assert_eq!(body["requested"], json!("danger-full-access"));
+    assert_eq!(body["allowed"], json!(["read-only", "workspace-write"]));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_compliant_thread_overrides_pass() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let state = requirements_state(&fixture)?;
+
+    check_thread_overrides(
+        &state,
+        Some(AskForApproval::OnRequest),
+        Some(SandboxMode::WorkspaceWrite),
+    )
+    .await
+    .map_err(|err| anyhow::anyhow!("{err:?}"))?;
+    check_thread_overrides(&state, None, Some(SandboxMode::ReadOnly))
+        .await
+        .map_err(|err| anyhow::anyhow!("{err:?}"))?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_thread_overrides_unrestricted_without_requirements() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let state = create_test_state(&fixture, "test-token");
+
+    check_thread_overrides(
+        &state,
+        Some(AskForApproval::Never),
+        Some(SandboxMode::DangerFullAccess),
+    )
+    .await
+    .map_err(|err| anyhow::anyhow!("{err:?}"))?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_config_write_rejects_violating_value() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let app = requirements_router(requirements_state(&fixture)?);
+
+    let (status, body) = send(
+        app.clone(),
+        "PUT",
+        "/api/v2/config",
+        json!({
+            "key_path": "profiles.ci.sandbox_mode",
+            "value": "danger-full-access",
+            "merge_strategy": "replace",
+        }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["constraint"], json!("allowed_sandbox_modes"));
+
+    let (status, body) = send(
+        app,
+        "PATCH",
+        "/api/v2/config",
+        json!({
+            "edits": [
+                { "keyPath": "model", "value": "gpt-5", "mergeStrategy": "replace" },
+                { "keyPath": "approval_policy", "value": "never", "mergeStrategy": "replace" },
+            ],
+        }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    assert_eq!(body["constraint"], json!("allowed_approval_policies"));
+
+    // Nothing from the rejected batch reached disk.
+    let config = std::fs::read_to_string(fixture.codex_home_path().join("config.toml"))?;
+    assert_eq!(config, TEST_CONFIG);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_config_write_accepts_compliant_value() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let app = requirements_router(requirements_state(&fixture)?);
+
+    let (status, _body) = send(
+        app,
+        "PUT",
+        "/api/v2/config",
+        json!({
+            "key_path": "sandbox_mode",
+            "value": "workspace-write",
+            "merge_strategy": "replace",
+        }),
+    )
+    .await?;
+
+    assert_eq!(status, StatusCode::OK);
+    let config = std::fs::read_to_string(fixture.codex_home_path().join("config.toml"))?;
+    assert!(config.contains(r#"sandbox_mode = "workspace-write""#));
+    Ok(())
+}
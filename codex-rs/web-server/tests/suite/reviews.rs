@@ -0,0 +1,190 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::delete;
+use axum::routing::get;
+use axum::routing::post;
+use codex_protocol::ThreadId;
+use codex_web_server::handlers;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_response_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::sse_response;
+use core_test_support::responses::start_mock_server;
+use serde_json::Value;
+use serde_json::json;
+use std::time::Duration;
+use tower::ServiceExt;
+use wiremock::MockServer;
+
+use crate::common::EventReader;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+/// Points the configs of detached reviews at the mock Responses API.
+fn use_mock_provider(fixture: &TestFixture, server: &MockServer) -> Result<()> {
+    fixture.create_test_config(&format!(
+        r#"
+model = "test-model"
+review_model = "test-model"
+approval_policy = "never"
+sandbox_mode = "read-only"
+model_provider = "mock"
+
+[model_providers.mock]
+name = "Mock provider"
+base_url = "{}/v1"
+wire_api = "responses"
+request_max_retries = 0
+stream_max_retries = 0
+"#,
+        server.uri()
+    ))?;
+    Ok(())
+}
+
+/// Mounts the review reply, held back by `delay`.
+async fn mount_review_reply(server: &MockServer, delay: Duration) {
+    let body = sse(vec![
+        ev_response_created("resp-review"),
+        ev_assistant_message("msg-review", "No issues found."),
+        ev_completed("resp-review"),
+    ]);
+    mount_response_once(server, sse_response(body).set_delay(delay)).await;
+}
+
+fn reviews_router(fixture: &TestFixture) -> Router {
+    Router::new()
+        .route(
+            "/api/v2/reviews",
+            post(handlers::review::start_detached_review).get(handlers::review::list_reviews),
+        )
+        .route(
+            "/api/v2/reviews/{review_id}",
+            delete(handlers::review::cancel_review),
+        )
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .route("/api/v2/threads/{id}", get(handlers::threads::get_thread))
+        .with_state(create_test_state(fixture, "test-token"))
+}
+
+async fn call(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> Result<(StatusCode, Value)> {
+    let mut request = Request::builder().method(method).uri(uri);
+    let body = match body {
+        Some(body) => {
+            request = request.header("content-type", "application/json");
+            Body::from(body.to_string())
+        }
+        None => Body::empty(),
+    };
+    let response = app.clone().oneshot(request.body(body)?).await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body).unwrap_or(Value::Null)))
+}
+
+async fn start_review(app: &Router) -> Result<(String, ThreadId)> {
+    let (status, body) = call(
+        app,
+        "POST",
+        "/api/v2/reviews",
+        Some(json!({"target": {"type": "git", "base": "main"}})),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::ACCEPTED, "{body}");
+    let review_id = body["review_id"].as_str().unwrap_or_default().to_string();
+    let thread_id = ThreadId::from_string(body["thread_id"].as_str().unwrap_or_default())?;
+    Ok((review_id, thread_id))
+}
+
+async fn review_status(app: &Router, review_id: &str) -> Result<Value> {
+    let (status, body) = call(app, "GET", "/api/v2/reviews", None).await?;
+    assert_eq!(status, StatusCode::OK);
+    Ok(body["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|review| review["review_id"] == json!(review_id))
+        .map(|review| review["status"].clone())
+        .unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn test_cancel_running_review_deletes_its_thread() -> Result<()> {
+    let server = start_mock_server().await;
+    // Long enough that the review is still waiting for the model.
+    mount_review_reply(&server, Duration::from_secs(30)).await;
+    let fixture = TestFixture::new().await?;
+    use_mock_provider(&fixture, &server)?;
+    let app = reviews_router(&fixture);
+
+    let (review_id, thread_id) = start_review(&app).await?;
+    assert_eq!(review_status(&app, &review_id).await?, json!("running"));
+
+    let (status, body) = call(
+        &app,
+        "DELETE",
+        &format!("/api/v2/reviews/{review_id}?delete_thread=true"),
+        None,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["review"]["status"], json!("cancelled"));
+    assert_eq!(body["review"]["delivery"], json!("detached"));
+    assert_eq!(body["thread_deleted"], json!(true));
+    assert_eq!(review_status(&app, &review_id).await?, json!("cancelled"));
+
+    let (status, _) = call(&app, "GET", &format!("/api/v2/threads/{thread_id}"), None).await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, body) = call(
+        &app,
+        "DELETE",
+        &format!("/api/v2/reviews/{review_id}"),
+        None,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], json!("review_finished"));
+
+    let (status, _) = call(&app, "DELETE", "/api/v2/reviews/no-such-review", None).await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_cancel_after_complete_conflicts() -> Result<()> {
+    let server = start_mock_server().await;
+    // Leaves time to subscribe before the review finishes.
+    mount_review_reply(&server, Duration::from_millis(500)).await;
+    let fixture = TestFixture::new().await?;
+    use_mock_provider(&fixture, &server)?;
+    let app = reviews_router(&fixture);
+
+    let (review_id, thread_id) = start_review(&app).await?;
+    let mut events = EventReader::open(&app, thread_id).await?;
+    events.next_of("turn/completed").await?;
+    assert_eq!(review_status(&app, &review_id).await?, json!("completed"));
+
+    let (status, body) = call(
+        &app,
+        "DELETE",
+        &format!("/api/v2/reviews/{review_id}"),
+        None,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], json!("review_finished"));
+    assert_eq!(review_status(&app, &review_id).await?, json!("completed"));
+    Ok(())
+}
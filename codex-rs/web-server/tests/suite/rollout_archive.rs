@@ -0,0 +1,184 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use codex_protocol::ThreadId;
+use codex_web_server::handlers;
+use codex_web_server::mock_model::MockModelServer;
+use codex_web_server::mock_model::MockScript;
+use serde_json::Value;
+use serde_json::json;
+use std::path::PathBuf;
+use tower::ServiceExt;
+
+use crate::common::EventReader;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+const APPROVAL_CONFIG: &str = r#"
+model = "test-model"
+approval_policy = "on-request"
+sandbox_mode = "workspace-write"
+"#;
+
+async fn request(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    body: Value,
+) -> Result<(StatusCode, Value)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+async fn listed_ids(app: &Router) -> Result<Vec<Value>> {
+    let (status, body) = request(app, "GET", "/api/v2/threads", Value::Null).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    Ok(body["thread_ids"].as_array().cloned().unwrap_or_default())
+}
+
+/// Runs one scripted turn to completion so the thread has a rollout on disk.
+async fn thread_with_rollout(app: &Router, fixture: &TestFixture) -> Result<ThreadId> {
+    let (status, created) = request(
+        app,
+        "POST",
+        "/api/v2/threads",
+        json!({ "cwd": fixture.codex_home.path() }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{created}");
+    let thread_id = ThreadId::from_string(created["thread_id"].as_str().unwrap_or_default())?;
+    let mut events = EventReader::open(app, thread_id).await?;
+
+    request(
+        app,
+        "POST",
+        &format!("/api/v2/threads/{thread_id}/turns"),
+        json!({ "input": [{ "type": "text", "text": "Check the workspace" }] }),
+    )
+    .await?;
+    let approval = events
+        .next_of("item/commandExecution/requestApproval")
+        .await?;
+    let approval_id = approval["approvalId"]
+        .as_str()
+        .or(approval["itemId"].as_str())
+        .unwrap_or_default()
+        .to_string();
+    request(
+        app,
+        "POST",
+        &format!("/api/v2/threads/{thread_id}/approvals/{approval_id}"),
+        json!({ "decision": "approve" }),
+    )
+    .await?;
+    events.next_of("turn/completed").await?;
+    Ok(thread_id)
+}
+
+#[tokio::test]
+async fn test_archive_moves_the_rollout_and_resume_brings_it_back() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(APPROVAL_CONFIG)?;
+    let script = MockScript::load(
+        &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mock_thread.json"),
+    )?;
+    let server = MockModelServer::start(script).await?;
+    let state = create_test_state(&fixture, "test-token")
+        .with_config_loader(server.config_loader(fixture.codex_home_path()));
+    let app = Router::new()
+        .route("/api/v2/threads", post(handlers::threads::create_thread))
+        .route("/api/v2/threads", get(handlers::threads::list_threads))
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(handlers::turns::send_turn),
+        )
+        .route(
+            "/api/v2/threads/{id}/approvals/{approval_id}",
+            post(handlers::approvals::respond_to_approval),
+        )
+        .route(
+            "/api/v2/threads/{id}/archive",
+            post(handlers::threads::archive_thread),
+        )
+        .route(
+            "/api/v2/threads/{id}/resume",
+            post(handlers::threads::resume_thread),
+        )
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .with_state(state);
+    let codex_home = fixture.codex_home_path();
+
+    let thread_id = thread_with_rollout(&app, &fixture).await?;
+    let id = thread_id.to_string();
+    // Loaded and on disk, but listed once.
+    let (_, listing) = request(&app, "GET", "/api/v2/threads", Value::Null).await?;
+    let entries: Vec<&Value> = listing["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|summary| summary["thread_id"] == json!(id))
+        .collect();
+    assert_eq!(entries.len(), 1, "{listing}");
+    assert_eq!(entries[0]["active"], json!(true));
+    assert_eq!(entries[0]["status"], json!("active"));
+
+    let archive_uri = format!("/api/v2/threads/{id}/archive");
+    let (status, body) = request(&app, "POST", &archive_uri, json!({})).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert!(!listed_ids(&app).await?.contains(&json!(id)));
+    assert!(
+        codex_core::find_thread_path_by_id_str(&codex_home, &id)
+            .await?
+            .is_none()
+    );
+    assert!(
+        codex_core::find_archived_thread_path_by_id_str(&codex_home, &id)
+            .await?
+            .is_some()
+    );
+
+    // Archiving again is a no-op.
+    let (status, body) = request(&app, "POST", &archive_uri, json!({})).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+
+    let (status, body) = request(
+        &app,
+        "POST",
+        &format!("/api/v2/threads/{}/archive", ThreadId::new()),
+        json!({}),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND, "{body}");
+
+    let (status, body) = request(
+        &app,
+        "POST",
+        &format!("/api/v2/threads/{id}/resume"),
+        json!({}),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert!(listed_ids(&app).await?.contains(&json!(id)));
+    assert!(
+        codex_core::find_thread_path_by_id_str(&codex_home, &id)
+            .await?
+            .is_some()
+    );
+    Ok(())
+}
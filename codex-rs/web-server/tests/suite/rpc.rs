@@ -0,0 +1,201 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use codex_protocol::ThreadId;
+use codex_web_server::handlers;
+use codex_web_server::mock_model::MockModelServer;
+use codex_web_server::mock_model::MockScript;
+use serde_json::Value;
+use serde_json::json;
+use std::path::PathBuf;
+use tower::ServiceExt;
+
+use crate::common::EventReader;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+const APPROVAL_CONFIG: &str = r#"
+model = "test-model"
+approval_policy = "on-request"
+sandbox_mode = "workspace-write"
+"#;
+
+async fn send(app: &Router, method: &str, uri: &str, body: &str) -> Result<(StatusCode, Value)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    if body.is_empty() {
+        return Ok((status, Value::Null));
+    }
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+async fn call(app: &Router, id: i64, method: &str, params: Value) -> Result<Value> {
+    let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+    let (status, body) = send(app, "POST", "/api/v2/rpc", &request.to_string()).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["jsonrpc"], json!("2.0"));
+    assert_eq!(body["id"], json!(id));
+    Ok(body)
+}
+
+async fn app(fixture: &TestFixture) -> Result<Router> {
+    fixture.create_test_config(APPROVAL_CONFIG)?;
+    let script = MockScript::load(
+        &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mock_thread.json"),
+    )?;
+    let server = MockModelServer::start(script).await?;
+    let state = create_test_state(fixture, "test-token")
+        .with_config_loader(server.config_loader(fixture.codex_home_path()));
+    Ok(Router::new()
+        .route("/api/v2/rpc", post(handlers::rpc::rpc))
+        .route("/api/v2/threads", post(handlers::threads::create_thread))
+        .route("/api/v2/threads", get(handlers::threads::list_threads))
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .with_state(state))
+}
+
+#[tokio::test]
+async fn test_bridge_methods_match_native_endpoints() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let app = app(&fixture).await?;
+    let cwd = fixture.codex_home.path();
+
+    let started = call(&app, 1, "thread/start", json!({ "cwd": cwd })).await?;
+    let started = &started["result"];
+    let (_, native) = send(
+        &app,
+        "POST",
+        "/api/v2/threads",
+        &json!({ "cwd": cwd }).to_string(),
+    )
+    .await?;
+    assert_eq!(started["model"], native["model"]);
+    assert_eq!(started["instance_id"], native["instance_id"]);
+    let thread_id = ThreadId::from_string(started["thread_id"].as_str().unwrap_or_default())?;
+
+    let listed = call(&app, 2, "thread/list", json!({})).await?;
+    let (_, native_list) = send(&app, "GET", "/api/v2/threads", "").await?;
+    assert_eq!(listed["result"], native_list);
+    assert!(
+        listed["result"]["thread_ids"]
+            .as_array()
+            .is_some_and(|ids| ids.contains(&json!(thread_id.to_string()))),
+        "{listed}"
+    );
+
+    let mut events = EventReader::open(&app, thread_id).await?;
+    let turn = call(
+        &app,
+        3,
+        "turn/start",
+        json!({
+            "threadId": thread_id.to_string(),
+            "input": [{ "type": "text", "text": "Check the workspace" }],
+        }),
+    )
+    .await?;
+    let turn_id = turn["result"]["turn_id"].clone();
+    assert!(turn_id.is_string(), "{turn}");
+    assert_eq!(turn["result"]["steered"], json!(false));
+
+    let approval = events
+        .next_of("item/commandExecution/requestApproval")
+        .await?;
+    assert_eq!(approval["turnId"], turn_id);
+    let approval_id = approval["approvalId"]
+        .as_str()
+        .or(approval["itemId"].as_str())
+        .unwrap_or_default();
+    let approved = call(
+        &app,
+        4,
+        "approval/respond",
+        json!({
+            "threadId": thread_id.to_string(),
+            "approvalId": approval_id,
+            "decision": "approve",
+        }),
+    )
+    .await?;
+    assert_eq!(approved["result"], json!({ "success": true }));
+    let completed = events.next_of("turn/completed").await?;
+    assert_eq!(completed["turn"]["id"], turn_id);
+
+    let interrupted = call(
+        &app,
+        5,
+        "turn/interrupt",
+        json!({ "threadId": thread_id.to_string() }),
+    )
+    .await?;
+    assert_eq!(interrupted["result"], json!({ "success": true }));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_bridge_reports_json_rpc_errors() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let app = app(&fixture).await?;
+
+    let unknown = call(&app, 1, "thread/teleport", json!({})).await?;
+    assert_eq!(unknown["error"]["code"], json!(-32601));
+    assert!(
+        unknown["error"]["data"]["supported_methods"]
+            .as_array()
+            .is_some_and(|methods| methods.contains(&json!("turn/start"))),
+        "{unknown}"
+    );
+
+    let missing_params = call(&app, 2, "turn/start", json!({ "input": [] })).await?;
+    assert_eq!(missing_params["error"]["code"], json!(-32602));
+
+    // REST errors keep their body, status and code in `data`.
+    let no_thread = call(
+        &app,
+        3,
+        "turn/start",
+        json!({
+            "threadId": ThreadId::new().to_string(),
+            "input": [{ "type": "text", "text": "hi" }],
+        }),
+    )
+    .await?;
+    assert_eq!(no_thread["error"]["code"], json!(-32600));
+    assert_eq!(no_thread["error"]["data"]["status"], json!(404));
+    assert_eq!(no_thread["error"]["message"], json!("Thread not found"));
+
+    let (status, parse_error) = send(&app, "POST", "/api/v2/rpc", "{not json").await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(parse_error["error"]["code"], json!(-32700));
+    assert_eq!(parse_error["id"], Value::Null);
+
+    let (_, invalid) = send(&app, "POST", "/api/v2/rpc", r#"{"id": 4}"#).await?;
+    assert_eq!(invalid["error"]["code"], json!(-32600));
+
+    // A notification runs but gets no response body.
+    let (status, body) = send(
+        &app,
+        "POST",
+        "/api/v2/rpc",
+        &json!({ "jsonrpc": "2.0", "method": "thread/list" }).to_string(),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+    assert_eq!(body, Value::Null);
+    Ok(())
+}
@@ -0,0 +1,119 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::post;
+use codex_core::config::ConfigBuilder;
+use codex_web_server::handlers::sandbox::preview_sandbox;
+use codex_web_server::handlers::threads::start_configured_thread;
+use serde_json::Value;
+use serde_json::json;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+/// Custom provider that does not need auth; previews never call the model.
+fn thread_config(approval_policy: &str, sandbox_mode: &str) -> String {
+    format!(
+        r#"
+model = "test-model"
+approval_policy = "{approval_policy}"
+sandbox_mode = "{sandbox_mode}"
+model_provider = "offline"
+
+[model_providers.offline]
+name = "Offline provider"
+base_url = "http://127.0.0.1:9/v1"
+wire_api = "responses"
+"#
+    )
+}
+
+/// Starts a thread under the given policies and previews `command` in it.
+async fn preview(approval_policy: &str, sandbox_mode: &str, command: &[&str]) -> Result<Value> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(&thread_config(approval_policy, sandbox_mode))?;
+    let mut config = ConfigBuilder::default()
+        .codex_home(fixture.codex_home_path())
+        .build()
+        .await?;
+    config.cwd = fixture.codex_home.path().canonicalize()?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = start_configured_thread(&state, config).await?.thread_id;
+    let app = Router::new()
+        .route(
+            "/api/v2/threads/{id}/sandbox/preview",
+            post(preview_sandbox),
+        )
+        .with_state(state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/v2/threads/{thread_id}/sandbox/preview"))
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "command": command }).to_string()))?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    Ok(serde_json::from_slice(
+        &to_bytes(response.into_body(), usize::MAX).await?,
+    )?)
+}
+
+#[tokio::test]
+async fn test_benign_command_runs_without_approval() -> Result<()> {
+    let body = preview("on-request", "workspace-write", &["ls", "-la"]).await?;
+
+    assert_eq!(body["approval"], json!("not_required"));
+    assert_eq!(body["reason"], Value::Null);
+    assert_eq!(body["full_disk_write_access"], json!(false));
+    assert_eq!(body["network_access"], json!(false));
+    assert_eq!(body["unwritable_paths"], json!([]));
+    let cwd = body["cwd"].clone();
+    assert!(
+        body["writable_roots"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .any(|root| root["root"] == cwd),
+        "{body}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_write_outside_workspace_is_reported() -> Result<()> {
+    let body = preview(
+        "on-request",
+        "workspace-write",
+        &["cp", "./notes.txt", "/etc/codex-preview.txt"],
+    )
+    .await?;
+
+    // The sandbox blocks the write rather than prompting for it.
+    assert_eq!(body["approval"], json!("not_required"));
+    assert_eq!(body["unwritable_paths"], json!(["/etc/codex-preview.txt"]));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_network_command_under_read_only_policy() -> Result<()> {
+    let body = preview("on-request", "read-only", &["curl", "https://example.com"]).await?;
+
+    assert_eq!(body["network_access"], json!(false));
+    assert_eq!(body["writable_roots"], json!([]));
+    assert_eq!(body["approval"], json!("not_required"));
+
+    // Untrusted commands always ask under `untrusted`, and `never` turns the
+    // prompt into a rejection for commands flagged as dangerous.
+    let body = preview("untrusted", "read-only", &["curl", "https://example.com"]).await?;
+    assert_eq!(body["approval"], json!("required"));
+    let body = preview("never", "read-only", &["rm", "-rf", "/"]).await?;
+    assert_eq!(body["approval"], json!("forbidden"));
+    Ok(())
+}
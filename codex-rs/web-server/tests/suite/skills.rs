@@ -0,0 +1,171 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use codex_core::skills::SkillLoadOutcome;
+use codex_web_server::handlers;
+use codex_web_server::skills_listing::SkillsSource;
+use codex_web_server::workspace_trust::TrustedRoots;
+use futures::future::BoxFuture;
+use serde_json::Value;
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+const HUNG_CWD: &str = "/mnt/hung-share";
+const PANICKING_CWD: &str = "/mnt/broken-share";
+
+/// Never answers for [`HUNG_CWD`], panics for [`PANICKING_CWD`] and loads
+/// every other cwd from `inner`.
+struct SlowSkills {
+    inner: Arc<dyn SkillsSource>,
+}
+
+impl SkillsSource for SlowSkills {
+    fn skills_for_cwd(
+        &self,
+        cwd: PathBuf,
+        force_reload: bool,
+    ) -> BoxFuture<'static, SkillLoadOutcome> {
+        if cwd == PathBuf::from(HUNG_CWD) {
+            return Box::pin(async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                SkillLoadOutcome::default()
+            });
+        }
+        if cwd == PathBuf::from(PANICKING_CWD) {
+            return Box::pin(fail_loading());
+        }
+        self.inner.skills_for_cwd(cwd, force_reload)
+    }
+}
+
+async fn fail_loading() -> SkillLoadOutcome {
+    panic!("skills source failed")
+}
+
+fn fixture_workspace() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/skills_workspace")
+}
+
+async fn list_skills(app: &Router, query: &str) -> Result<(StatusCode, Value)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/v2/skills{query}"))
+                .body(Body::empty())?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body).unwrap_or(Value::Null)))
+}
+
+#[tokio::test]
+async fn test_slow_cwd_times_out_without_hiding_the_others() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let inner = Arc::clone(&state.skills_source);
+    let state = state
+        .with_skills_source(Arc::new(SlowSkills { inner }))
+        .with_skills_timeout(Duration::from_millis(200));
+    let app = Router::new()
+        .route("/api/v2/skills", get(handlers::skills::list_skills))
+        .with_state(state);
+    let workspace = fixture_workspace();
+
+    let started = Instant::now();
+    let (status, body) = list_skills(
+        &app,
+        &format!(
+            "?cwds={HUNG_CWD}&cwds={}&cwds={PANICKING_CWD}",
+            workspace.display()
+        ),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert!(started.elapsed() < Duration::from_secs(10));
+
+    let data = body["data"].as_array().cloned().unwrap_or_default();
+    assert_eq!(data.len(), 3, "{body}");
+
+    // Entries keep the order of the request.
+    assert_eq!(data[0]["cwd"], json!(HUNG_CWD));
+    assert_eq!(data[0]["status"], json!("timed_out"));
+    assert_eq!(data[0]["skills"], json!([]));
+    assert_eq!(data[0]["errors"][0]["path"], json!(HUNG_CWD));
+
+    // Parse errors still count as loaded.
+    assert_eq!(data[1]["status"], json!("loaded"));
+    let names: Vec<&str> = data[1]["skills"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|skill| skill["name"].as_str())
+        .collect();
+    assert!(names.contains(&"demo"), "{body}");
+    assert!(
+        data[1]["errors"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .any(|error| error["path"]
+                .as_str()
+                .is_some_and(|path| path.ends_with("broken/SKILL.md"))),
+        "{body}"
+    );
+
+    assert_eq!(data[2]["status"], json!("failed"));
+    assert_eq!(data[2]["skills"], json!([]));
+    assert_eq!(data[2]["errors"][0]["path"], json!(PANICKING_CWD));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_skills_rejects_invalid_force_reload() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let app = Router::new()
+        .route("/api/v2/skills", get(handlers::skills::list_skills))
+        .with_state(create_test_state(&fixture, "test-token"));
+
+    let (status, _) = list_skills(&app, "?force_reload=maybe").await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_skills_rejects_an_untrusted_cwd() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let workspace = fixture_workspace();
+    let state = create_test_state(&fixture, "test-token")
+        .with_trusted_roots(TrustedRoots::new([workspace.clone()]));
+    let app = Router::new()
+        .route("/api/v2/skills", get(handlers::skills::list_skills))
+        .with_state(state);
+
+    let outside = fixture.codex_home.path().display().to_string();
+    let (status, body) = list_skills(
+        &app,
+        &format!("?cwds={}&cwds={outside}", workspace.display()),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::FORBIDDEN, "{body}");
+
+    let (status, body) = list_skills(&app, &format!("?cwds={}", workspace.display())).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(
+        body["data"][0]["cwd"],
+        json!(workspace.canonicalize()?.display().to_string())
+    );
+    Ok(())
+}
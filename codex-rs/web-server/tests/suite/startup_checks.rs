@@ -0,0 +1,230 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use codex_app_server_protocol::McpAuthStatus;
+use codex_app_server_protocol::McpServerStatus;
+use codex_core::auth::AuthCredentialsStoreMode;
+use codex_core::auth::login_with_api_key;
+use codex_web_server::handlers::health::ready;
+use codex_web_server::mcp_snapshot::McpServerListing;
+use codex_web_server::mcp_snapshot::McpSnapshotCache;
+use codex_web_server::mcp_snapshot::McpStatusSnapshot;
+use codex_web_server::startup_checks::CheckResult;
+use codex_web_server::startup_checks::CheckStatus;
+use codex_web_server::startup_checks::WritableProbe;
+use codex_web_server::startup_checks::check_postgres;
+use codex_web_server::startup_checks::run_self_test;
+use serde_json::Value;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+
+use crate::common::TEST_CONFIG;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+/// An MCP server that starts but never answers the handshake.
+const HANGING_MCP_SERVER: &str = r#"
+[mcp_servers.hang]
+command = "sleep"
+args = ["30"]
+"#;
+
+/// A signed-in fixture home whose only MCP server hangs.
+fn fixture_with_hanging_server(fixture: &TestFixture) -> Result<()> {
+    fixture.create_test_config(&format!("{TEST_CONFIG}{HANGING_MCP_SERVER}"))?;
+    login_with_api_key(
+        fixture.codex_home.path(),
+        "sk-test",
+        AuthCredentialsStoreMode::File,
+    )?;
+    Ok(())
+}
+
+fn status_of(checks: &[CheckResult], name: &str) -> Option<CheckStatus> {
+    checks
+        .iter()
+        .find(|check| check.name == name)
+        .map(|check| check.status)
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_self_test_reports_mcp_server_that_never_starts() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture_with_hanging_server(&fixture)?;
+    let state = create_test_state(&fixture, "test-token");
+
+    let report = run_self_test(&state, Ok(None), Duration::from_millis(300)).await;
+
+    assert_eq!(report.exit_code(), 1);
+    let failed: Vec<&str> = report.failures().map(|check| check.name.as_str()).collect();
+    assert_eq!(failed, vec!["mcp:hang"]);
+    for (name, status) in [
+        ("config", CheckStatus::Pass),
+        ("codex_home", CheckStatus::Pass),
+        ("auth", CheckStatus::Pass),
+        ("postgres", CheckStatus::Skip),
+    ] {
+        assert_eq!(status_of(&report.checks, name), Some(status), "{name}");
+    }
+
+    let rendered = report.render();
+    assert!(rendered.contains("PASS config: loaded (model test-model)\n"));
+    assert!(rendered.contains("SKIP postgres: not configured\n"));
+    assert!(rendered.contains("FAIL mcp:hang: did not start within the timeout\n"));
+    assert!(rendered.ends_with("self-test failed: 1 check(s) failed\n"));
+    Ok(())
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_self_test_reports_the_startup_error_of_a_crashing_server() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(&format!(
+        r#"{TEST_CONFIG}
+[mcp_servers.crash]
+command = "sh"
+args = ["-c", "exit 1"]
+"#
+    ))?;
+    let state = create_test_state(&fixture, "test-token");
+
+    let report = run_self_test(&state, Ok(None), Duration::from_secs(10)).await;
+
+    let check = report
+        .checks
+        .iter()
+        .find(|check| check.name == "mcp:crash")
+        .ok_or_else(|| anyhow::anyhow!("no mcp:crash check in {:?}", report.checks))?;
+    assert_eq!(check.status, CheckStatus::Fail);
+    assert!(
+        check
+            .detail
+            .contains("MCP client for `crash` failed to start"),
+        "{}",
+        check.detail
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_self_test_reports_missing_auth_and_broken_config() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config("model = [")?;
+    let state = create_test_state(&fixture, "test-token");
+
+    let report = run_self_test(&state, Ok(None), Duration::from_millis(300)).await;
+
+    assert_eq!(report.exit_code(), 1);
+    assert_eq!(status_of(&report.checks, "config"), Some(CheckStatus::Fail));
+    assert_eq!(status_of(&report.checks, "auth"), Some(CheckStatus::Fail));
+    // MCP servers cannot be listed without a config.
+    assert_eq!(status_of(&report.checks, "mcp"), Some(CheckStatus::Skip));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_postgres_connection_failure_fails_the_check() {
+    let err = std::io::Error::other("connection refused");
+    assert_eq!(
+        check_postgres(Err(&err)).await,
+        CheckResult {
+            name: "postgres".to_string(),
+            status: CheckStatus::Fail,
+            detail: "failed to connect: connection refused".to_string(),
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_readiness_uses_cached_mcp_status() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture_with_hanging_server(&fixture)?;
+    let mut state = create_test_state(&fixture, "test-token");
+    // The cached snapshot already records the timeout, so the probe does not
+    // start the server itself.
+    state.mcp_snapshots = Arc::new(McpSnapshotCache::new(
+        Duration::from_secs(60),
+        Arc::new(|| {
+            Box::pin(async {
+                Ok(McpStatusSnapshot {
+                    servers: vec![McpServerListing {
+                        status: McpServerStatus {
+                            name: "hang".to_string(),
+                            tools: HashMap::new(),
+                            resources: Vec::new(),
+                            resource_templates: Vec::new(),
+                            auth_status: McpAuthStatus::Unsupported,
+                        },
+                        enabled: true,
+                    }],
+                    timed_out_servers: vec!["hang".to_string()],
+                    failed_servers: HashMap::new(),
+                })
+            })
+        }),
+    ));
+    let app = Router::new()
+        .route("/health/ready", get(ready))
+        .with_state(state);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/health/ready")
+                .header("authorization", "Bearer test-token")
+                .body(Body::empty())?,
+        )
+        .await?;
+
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+    assert_eq!(body["ok"], Value::Bool(false));
+
+    // Without a token the probe only learns the outcome.
+    for authorization in [None, Some("Bearer wrong-token")] {
+        let mut request = Request::builder().uri("/health/ready");
+        if let Some(authorization) = authorization {
+            request = request.header("authorization", authorization);
+        }
+        let response = app.clone().oneshot(request.body(Body::empty())?).await?;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let status: Value =
+            serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+        assert_eq!(status, json!({"ok": false}));
+    }
+
+    let failed: Vec<&str> = body["checks"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|check| check["status"] == "fail")
+        .filter_map(|check| check["name"].as_str())
+        .collect();
+    assert_eq!(failed, vec!["mcp:hang"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_writable_probe_reuses_a_recent_result() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let home = fixture.codex_home.path().join("home");
+    std::fs::create_dir(&home)?;
+    let probe = WritableProbe::default();
+
+    let first = probe.check(&home).await;
+    assert_eq!(first.status, CheckStatus::Pass);
+
+    // A probe within the TTL does not write again, so it cannot notice.
+    std::fs::remove_dir(&home)?;
+    assert_eq!(probe.check(&home).await, first);
+    Ok(())
+}
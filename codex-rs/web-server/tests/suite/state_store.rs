@@ -0,0 +1,86 @@
+use anyhow::Result;
+use codex_core::PersistedApproval;
+use codex_core::WebStateReconciliation;
+use codex_core::WebStateStore;
+use codex_protocol::ThreadId;
+use serde_json::json;
+
+fn postgres_configured() -> bool {
+    std::env::var("CODEX_ROLLOUT_POSTGRES_URL").is_ok_and(|value| !value.trim().is_empty())
+}
+
+#[tokio::test]
+async fn test_restart_reconciles_pending_approvals_and_turns() -> Result<()> {
+    if !postgres_configured() {
+        eprintln!("skipping: CODEX_ROLLOUT_POSTGRES_URL is not set");
+        return Ok(());
+    }
+
+    let instance_id = format!("test-{}", uuid::Uuid::new_v4());
+    let thread_id = ThreadId::new();
+    let approval = PersistedApproval {
+        approval_id: format!("approval-{}", uuid::Uuid::new_v4()),
+        thread_id,
+        turn_id: "turn-1".to_string(),
+        event_type: "item/commandExecution/requestApproval".to_string(),
+        payload: json!({"threadId": thread_id.to_string(), "turnId": "turn-1"}),
+    };
+
+    {
+        let store = WebStateStore::connect_from_env(&instance_id)
+            .await?
+            .expect("postgres store");
+        store.save_approval(&approval).await?;
+        store.save_turn(thread_id, "turn-1").await?;
+        store.save_turn(thread_id, "turn-2").await?;
+        store.remove_turn(thread_id, "turn-2").await?;
+    }
+
+    // Simulate a restart of the same instance with a fresh pool.
+    let store = WebStateStore::connect_from_env(&instance_id)
+        .await?
+        .expect("postgres store");
+    let reconciliation = store.reconcile_after_restart(&instance_id).await?;
+    assert_eq!(
+        reconciliation,
+        WebStateReconciliation {
+            approvals: vec![approval.clone()],
+            interrupted_turns: vec![(thread_id, "turn-1".to_string())],
+        }
+    );
+
+    // Another instance sharing the database must not claim this state.
+    let other = store.reconcile_after_restart("some-other-instance").await?;
+    assert_eq!(
+        other.approvals.iter().find(|a| a.thread_id == thread_id),
+        None
+    );
+
+    store.remove_approval(&approval.approval_id).await?;
+    store.remove_turn(thread_id, "turn-1").await?;
+    let reconciliation = store.reconcile_after_restart(&instance_id).await?;
+    assert_eq!(reconciliation, WebStateReconciliation::default());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_thread_pins_persist_in_postgres() -> Result<()> {
+    if !postgres_configured() {
+        eprintln!("skipping: CODEX_ROLLOUT_POSTGRES_URL is not set");
+        return Ok(());
+    }
+
+    let store = WebStateStore::connect_from_env("test-pins")
+        .await?
+        .expect("postgres store");
+    let thread_id = ThreadId::new();
+
+    store.set_thread_pinned(thread_id, true).await?;
+    assert!(store.pinned_threads().await?.contains(&thread_id));
+
+    store.set_thread_pinned(thread_id, false).await?;
+    assert!(!store.pinned_threads().await?.contains(&thread_id));
+
+    Ok(())
+}
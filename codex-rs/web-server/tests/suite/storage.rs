@@ -0,0 +1,393 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::middleware::from_fn_with_state;
+use axum::routing::get;
+use axum::routing::post;
+use codex_protocol::ThreadId;
+use codex_protocol::user_input::UserInput;
+use codex_web_server::attachments::AttachmentMetadata;
+use codex_web_server::handlers::maintenance::get_storage;
+use codex_web_server::handlers::maintenance::vacuum;
+use codex_web_server::handlers::turns::TurnOverrides;
+use codex_web_server::middleware::admin_middleware;
+use codex_web_server::state::WebServerState;
+use codex_web_server::turn_attachments;
+use serde_json::Value;
+use serde_json::json;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::SystemTime;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Writes `bytes` bytes to `path` and backdates it by `age`.
+fn write_aged(path: &Path, bytes: usize, age: Duration) -> Result<PathBuf> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, vec![b'x'; bytes])?;
+    std::fs::File::options()
+        .write(true)
+        .open(path)?
+        .set_modified(SystemTime::now() - age)?;
+    Ok(path.to_path_buf())
+}
+
+/// Writes the metadata of attachment `id` in `dir`, uploaded `age` ago and
+/// sharing the file of `blob_ref` when set.
+fn write_metadata(
+    dir: &Path,
+    id: &str,
+    blob_ref: Option<&str>,
+    age: Duration,
+) -> Result<AttachmentMetadata> {
+    let created_at = (SystemTime::now() - age)
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs();
+    let metadata = AttachmentMetadata {
+        id: id.to_string(),
+        filename: "image.png".to_string(),
+        mime_type: "image/png".to_string(),
+        size: 50,
+        owner: None,
+        created_at: Some(i64::try_from(created_at)?),
+        sha256: None,
+        blob_ref: blob_ref.map(str::to_string),
+    };
+    let path = dir.join(format!("{id}.json"));
+    std::fs::write(&path, serde_json::to_string(&metadata)?)?;
+    std::fs::File::options()
+        .write(true)
+        .open(&path)?
+        .set_modified(SystemTime::now() - age)?;
+    Ok(metadata)
+}
+
+fn rollout_path(home: &Path, subdir: &str, thread_id: ThreadId) -> PathBuf {
+    home.join(subdir)
+        .join("2025/01/02")
+        .join(format!("rollout-2025-01-02T03-04-05-{thread_id}.jsonl"))
+}
+
+/// Rollouts and attachments with known sizes and ages.
+struct AgedTree {
+    fixture: TestFixture,
+    old_thread: ThreadId,
+    recent_thread: ThreadId,
+    archived_thread: ThreadId,
+    old_rollout: PathBuf,
+    recent_rollout: PathBuf,
+    archived_rollout: PathBuf,
+    old_attachment: PathBuf,
+    recent_attachment: PathBuf,
+}
+
+impl AgedTree {
+    async fn build() -> Result<Self> {
+        let fixture = TestFixture::new().await?;
+        let home = fixture.codex_home.path();
+        let attachments = fixture.attachments_dir.path();
+        let old_thread = ThreadId::new();
+        let recent_thread = ThreadId::new();
+        let archived_thread = ThreadId::new();
+
+        let old_rollout = write_aged(&rollout_path(home, "sessions", old_thread), 400, DAY * 60)?;
+        let recent_rollout = write_aged(&rollout_path(home, "sessions", recent_thread), 300, DAY)?;
+        let archived_rollout = write_aged(
+            &rollout_path(home, "archived_sessions", archived_thread),
+            200,
+            DAY * 2,
+        )?;
+        let old_attachment =
+            write_aged(&attachments.join(Uuid::new_v4().to_string()), 50, DAY * 45)?;
+        let recent_attachment = write_aged(&attachments.join(Uuid::new_v4().to_string()), 20, DAY)?;
+        write_aged(&home.join("log/codex-tui.log"), 10, DAY)?;
+        write_aged(&home.join("shell_snapshots/snapshot.sh"), 5, DAY)?;
+
+        Ok(Self {
+            fixture,
+            old_thread,
+            recent_thread,
+            archived_thread,
+            old_rollout,
+            recent_rollout,
+            archived_rollout,
+            old_attachment,
+            recent_attachment,
+        })
+    }
+
+    fn app(&self) -> Router {
+        Self::routes(create_test_state(&self.fixture, "test-token"))
+    }
+
+    fn routes(state: WebServerState) -> Router {
+        Router::new()
+            .route("/api/v2/maintenance/storage", get(get_storage))
+            .route("/api/v2/maintenance/vacuum", post(vacuum))
+            .with_state(state)
+    }
+}
+
+async fn send(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> Result<(StatusCode, Value)> {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json")
+        .header("authorization", "Bearer test-token");
+    let request = match body {
+        Some(body) => request.body(Body::from(body.to_string()))?,
+        None => request.body(Body::empty())?,
+    };
+    let response = app.clone().oneshot(request).await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+fn deleted_paths(body: &Value) -> Vec<String> {
+    body["deleted"]
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item["path"].as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn display(path: &Path) -> String {
+    path.display().to_string()
+}
+
+#[tokio::test]
+async fn test_storage_report_groups_usage_by_category_and_thread() -> Result<()> {
+    let tree = AgedTree::build().await?;
+
+    let (status, body) = send(
+        &tree.app(),
+        "GET",
+        "/api/v2/maintenance/storage?top=2",
+        None,
+    )
+    .await?;
+
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(
+        body["categories"],
+        json!({"rollouts": 900, "attachments": 70, "artifacts": 5, "logs": 10})
+    );
+    assert_eq!(body["total_bytes"], json!(985));
+    assert_eq!(
+        body["largest_threads"],
+        json!([
+            {"thread_id": tree.old_thread.to_string(), "bytes": 400, "archived": false},
+            {"thread_id": tree.recent_thread.to_string(), "bytes": 300, "archived": false},
+        ])
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_vacuum_dry_run_reports_without_deleting() -> Result<()> {
+    let tree = AgedTree::build().await?;
+    let app = tree.app();
+    let policy = json!({ "older_than_days": 30 });
+
+    let (status, planned) = send(
+        &app,
+        "POST",
+        "/api/v2/maintenance/vacuum",
+        Some(policy.clone()),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{planned}");
+    assert_eq!(planned["dry_run"], json!(true));
+    assert_eq!(planned["reclaimed_bytes"], json!(450));
+    let mut expected = vec![display(&tree.old_attachment), display(&tree.old_rollout)];
+    expected.sort();
+    assert_eq!(deleted_paths(&planned), expected);
+    assert!(tree.old_rollout.exists());
+    assert!(tree.old_attachment.exists());
+
+    let (status, vacuumed) = send(
+        &app,
+        "POST",
+        "/api/v2/maintenance/vacuum",
+        Some(json!({ "older_than_days": 30, "dry_run": false })),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{vacuumed}");
+    assert_eq!(vacuumed["dry_run"], json!(false));
+    assert_eq!(vacuumed["deleted"], planned["deleted"]);
+    assert_eq!(vacuumed["reclaimed_bytes"], json!(450));
+    assert!(!tree.old_rollout.exists());
+    assert!(!tree.old_attachment.exists());
+    assert!(tree.recent_rollout.exists());
+    assert!(tree.recent_attachment.exists());
+    assert!(tree.archived_rollout.exists());
+    assert!(tree.fixture.codex_home.path().join("sessions").is_dir());
+
+    let (_, again) = send(&app, "POST", "/api/v2/maintenance/vacuum", Some(policy)).await?;
+    assert_eq!(again["reclaimed_bytes"], json!(0));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_vacuum_keeps_an_old_file_that_a_recent_upload_shares() -> Result<()> {
+    let tree = AgedTree::build().await?;
+    let attachments = tree.fixture.attachments_dir.path();
+    let original = Uuid::new_v4().to_string();
+    let duplicate = Uuid::new_v4().to_string();
+    write_aged(&attachments.join(&original), 50, DAY * 45)?;
+    write_metadata(attachments, &original, None, DAY * 45)?;
+    write_metadata(attachments, &duplicate, Some(&original), DAY)?;
+
+    let (status, body) = send(
+        &tree.app(),
+        "POST",
+        "/api/v2/maintenance/vacuum",
+        Some(json!({ "older_than_days": 30, "dry_run": false })),
+    )
+    .await?;
+
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let deleted = deleted_paths(&body);
+    assert!(deleted.contains(&display(&attachments.join(format!("{original}.json")))));
+    assert!(!deleted.contains(&display(&attachments.join(&original))));
+    assert!(attachments.join(&original).exists());
+    assert!(attachments.join(format!("{duplicate}.json")).exists());
+    assert!(!tree.old_attachment.exists());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_vacuum_keeps_attachments_of_kept_threads_and_queued_input() -> Result<()> {
+    let tree = AgedTree::build().await?;
+    let state = create_test_state(&tree.fixture, "test-token");
+    let attachments = tree.fixture.attachments_dir.path();
+    let [journaled, queued, pruned] = [(); 3].map(|()| Uuid::new_v4().to_string());
+    for id in [&journaled, &queued, &pruned] {
+        write_aged(&attachments.join(id), 50, DAY * 45)?;
+    }
+    for (thread_id, id) in [(tree.recent_thread, &journaled), (tree.old_thread, &pruned)] {
+        let metadata = write_metadata(attachments, id, None, DAY * 45)?;
+        turn_attachments::record(
+            &state.codex_home,
+            thread_id,
+            "turn-1",
+            vec![(attachments.join(id), metadata)],
+        )
+        .await?;
+    }
+    state.turn_queue.enqueue(
+        ThreadId::new(),
+        Vec::new(),
+        vec![UserInput::LocalImage {
+            path: attachments.join(&queued),
+        }],
+        TurnOverrides::default(),
+    );
+
+    let (status, body) = send(
+        &AgedTree::routes(state),
+        "POST",
+        "/api/v2/maintenance/vacuum",
+        Some(json!({ "older_than_days": 30, "dry_run": false })),
+    )
+    .await?;
+
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert!(!tree.old_rollout.exists());
+    assert!(!attachments.join(&pruned).exists());
+    assert!(attachments.join(&journaled).exists());
+    assert!(attachments.join(format!("{journaled}.json")).exists());
+    assert!(attachments.join(&queued).exists());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_vacuum_archived_only_keeps_live_threads_and_attachments() -> Result<()> {
+    let tree = AgedTree::build().await?;
+
+    let (status, body) = send(
+        &tree.app(),
+        "POST",
+        "/api/v2/maintenance/vacuum",
+        Some(json!({ "archived_only": true, "dry_run": false })),
+    )
+    .await?;
+
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(
+        body["deleted"],
+        json!([{
+            "category": "rollouts",
+            "path": display(&tree.archived_rollout),
+            "bytes": 200,
+            "thread_id": tree.archived_thread.to_string(),
+        }])
+    );
+    assert!(!tree.archived_rollout.exists());
+    assert!(tree.old_rollout.exists());
+    assert!(tree.old_attachment.exists());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_vacuum_requires_a_policy() -> Result<()> {
+    let tree = AgedTree::build().await?;
+
+    let (status, _) = send(
+        &tree.app(),
+        "POST",
+        "/api/v2/maintenance/vacuum",
+        Some(json!({})),
+    )
+    .await?;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(tree.old_rollout.exists());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_admin_token_replaces_regular_token_for_maintenance() -> Result<()> {
+    let tree = AgedTree::build().await?;
+    let state = create_test_state(&tree.fixture, "test-token")
+        .with_admin_token(Some("admin-token".to_string()));
+    let app = Router::new()
+        .route("/api/v2/maintenance/storage", get(get_storage))
+        .layer(from_fn_with_state(state.clone(), admin_middleware))
+        .with_state(state);
+
+    let (status, _) = send(&app, "GET", "/api/v2/maintenance/storage", None).await?;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/v2/maintenance/storage")
+                .header("authorization", "Bearer admin-token")
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    Ok(())
+}
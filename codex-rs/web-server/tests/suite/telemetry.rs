@@ -0,0 +1,193 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::extract::Path;
+use axum::extract::State;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::post;
+use codex_otel::config::OtelExporter;
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::McpInvocation;
+use codex_protocol::protocol::McpToolCallBeginEvent;
+use codex_protocol::protocol::TurnCompleteEvent;
+use codex_web_server::state::WebServerState;
+use codex_web_server::telemetry::trace_layer;
+use codex_web_server::telemetry::trace_settings;
+use opentelemetry::KeyValue;
+use opentelemetry::trace::SpanId;
+use opentelemetry::trace::TraceId;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::InMemorySpanExporter;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::trace::SpanData;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tower::ServiceExt;
+use tracing_subscriber::layer::SubscriberExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+const REMOTE_SPAN_ID: &str = "00f067aa0ba902b7";
+
+/// Stands in for `send_turn`: opens a turn span from inside the request.
+async fn start_turn(
+    State(state): State<WebServerState>,
+    Path(thread_id): Path<String>,
+) -> StatusCode {
+    match ThreadId::from_string(&thread_id) {
+        Ok(thread_id) => {
+            state.turn_spans.turn_started(thread_id, "turn-1");
+            StatusCode::OK
+        }
+        Err(_) => StatusCode::BAD_REQUEST,
+    }
+}
+
+fn span_named<'a>(spans: &'a [SpanData], name: &str) -> Result<&'a SpanData> {
+    spans
+        .iter()
+        .find(|span| span.name == name)
+        .ok_or_else(|| anyhow::anyhow!("no span named {name}"))
+}
+
+#[tokio::test]
+async fn test_request_turn_and_tool_spans_nest_under_traceparent() -> Result<()> {
+    let exporter = InMemorySpanExporter::default();
+    let provider = SdkTracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+    let subscriber = tracing_subscriber::registry()
+        .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let turn_spans = state.turn_spans.clone();
+    let app = Router::new()
+        .route("/api/v2/threads/{id}/turns", post(start_turn))
+        .layer(trace_layer())
+        .with_state(state);
+
+    let thread_id = ThreadId::new();
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("/api/v2/threads/{thread_id}/turns"))
+        .header("traceparent", format!("00-{TRACE_ID}-{REMOTE_SPAN_ID}-01"))
+        .body(Body::empty())?;
+    let response = app.oneshot(request).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+
+    // Later, the event stream reports a tool call and the end of the turn.
+    turn_spans.observe(
+        thread_id,
+        &Event {
+            id: "turn-1".to_string(),
+            msg: EventMsg::McpToolCallBegin(McpToolCallBeginEvent {
+                call_id: "call-1".to_string(),
+                invocation: McpInvocation {
+                    server: "docs".to_string(),
+                    tool: "search".to_string(),
+                    arguments: None,
+                },
+            }),
+        },
+    );
+    turn_spans.observe(
+        thread_id,
+        &Event {
+            id: "turn-1".to_string(),
+            msg: EventMsg::TurnComplete(TurnCompleteEvent {
+                turn_id: "turn-1".to_string(),
+                last_agent_message: None,
+            }),
+        },
+    );
+
+    provider.force_flush()?;
+    let spans = exporter.get_finished_spans()?;
+    let request_span = span_named(&spans, &format!("POST /api/v2/threads/{thread_id}/turns"))?;
+    let turn_span = span_named(&spans, "turn")?;
+    let tool_span = span_named(&spans, "tool docs/search")?;
+
+    let trace_id = TraceId::from_hex(TRACE_ID)?;
+    assert_eq!(
+        [
+            request_span.span_context.trace_id(),
+            turn_span.span_context.trace_id(),
+            tool_span.span_context.trace_id(),
+        ],
+        [trace_id; 3]
+    );
+    assert_eq!(
+        request_span.parent_span_id,
+        SpanId::from_hex(REMOTE_SPAN_ID)?
+    );
+    assert_eq!(
+        turn_span.parent_span_id,
+        request_span.span_context.span_id()
+    );
+    assert_eq!(tool_span.parent_span_id, turn_span.span_context.span_id());
+
+    let thread_id = thread_id.to_string();
+    for attribute in [
+        KeyValue::new("thread_id", thread_id.clone()),
+        KeyValue::new("turn_id", "turn-1"),
+    ] {
+        assert!(request_span.attributes.contains(&attribute));
+        assert!(turn_span.attributes.contains(&attribute));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_trace_settings_from_standard_env_vars() {
+    let env = HashMap::from([
+        ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://collector:4318/"),
+        ("OTEL_EXPORTER_OTLP_HEADERS", "x-api-key=secret, x-team=web"),
+    ]);
+    let settings = trace_settings(
+        |key| env.get(key).map(ToString::to_string),
+        PathBuf::from("/tmp"),
+    );
+
+    let Some(settings) = settings else {
+        panic!("an endpoint should enable export");
+    };
+    assert_eq!(settings.service_name, "codex-web-server");
+    match settings.trace_exporter {
+        OtelExporter::OtlpHttp {
+            endpoint, headers, ..
+        } => {
+            assert_eq!(endpoint, "http://collector:4318/v1/traces");
+            assert_eq!(
+                headers,
+                HashMap::from([
+                    ("x-api-key".to_string(), "secret".to_string()),
+                    ("x-team".to_string(), "web".to_string()),
+                ])
+            );
+        }
+        other => panic!("unexpected exporter: {other:?}"),
+    }
+    assert!(matches!(settings.exporter, OtelExporter::None));
+}
+
+#[test]
+fn test_trace_settings_disabled_without_endpoint() {
+    let env = HashMap::from([("OTEL_SERVICE_NAME", "web")]);
+    assert!(trace_settings(|key| env.get(key).map(ToString::to_string), PathBuf::new()).is_none());
+
+    let env = HashMap::from([
+        ("OTEL_EXPORTER_OTLP_ENDPOINT", "http://collector:4318"),
+        ("OTEL_SDK_DISABLED", "true"),
+    ]);
+    assert!(trace_settings(|key| env.get(key).map(ToString::to_string), PathBuf::new()).is_none());
+}
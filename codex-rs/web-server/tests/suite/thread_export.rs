@@ -0,0 +1,176 @@
+//! Streaming export of long threads.
+
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use codex_protocol::protocol::SessionMeta;
+use codex_protocol::protocol::SessionMetaLine;
+use codex_protocol::protocol::UserMessageEvent;
+use codex_web_server::handlers;
+use codex_web_server::thread_export::DEFAULT_EXPORT_MAX_ITEMS;
+use codex_web_server::thread_export::ExportFormat;
+use codex_web_server::thread_export::ExportOptions;
+use codex_web_server::thread_export::export_stream;
+use futures::StreamExt;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use tower::ServiceExt;
+
+use crate::common::TEST_CONFIG;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+const ITEMS: usize = 10_000;
+
+fn user_line(index: usize) -> Result<String> {
+    let line = RolloutLine {
+        timestamp: "2025-01-02T03:04:06.000Z".to_string(),
+        item: RolloutItem::EventMsg(EventMsg::UserMessage(UserMessageEvent {
+            message: format!("message {index}"),
+            images: None,
+            local_images: Vec::new(),
+            text_elements: Vec::new(),
+        })),
+    };
+    Ok(serde_json::to_string(&line)?)
+}
+
+/// A rollout of a session meta line and `ITEMS` user messages.
+fn write_long_rollout(codex_home: &Path, thread_id: ThreadId) -> Result<()> {
+    let dir = codex_home.join("sessions/2025/01/02");
+    std::fs::create_dir_all(&dir)?;
+    let meta = RolloutLine {
+        timestamp: "2025-01-02T03:04:05.000Z".to_string(),
+        item: RolloutItem::SessionMeta(SessionMetaLine {
+            meta: SessionMeta {
+                id: thread_id,
+                cwd: codex_home.to_path_buf(),
+                ..Default::default()
+            },
+            git: None,
+        }),
+    };
+    let mut contents = serde_json::to_string(&meta)?;
+    contents.push('\n');
+    for index in 0..ITEMS {
+        contents.push_str(&user_line(index)?);
+        contents.push('\n');
+    }
+    std::fs::write(
+        dir.join(format!("rollout-2025-01-02T03-04-05-{thread_id}.jsonl")),
+        contents,
+    )?;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_export_writes_each_item_before_reading_the_next() -> Result<()> {
+    for format in [ExportFormat::Jsonl, ExportFormat::Markdown] {
+        let read = Arc::new(AtomicUsize::new(0));
+        let counter = read.clone();
+        let lines = futures::stream::iter(0..ITEMS).map(move |index| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            user_line(index).map_err(std::io::Error::other)
+        });
+        let mut export = Box::pin(export_stream(
+            lines,
+            ExportOptions {
+                format,
+                compact: false,
+                max_items: DEFAULT_EXPORT_MAX_ITEMS,
+            },
+        ));
+        let mut written = 0;
+        while let Some(chunk) = export.next().await {
+            let chunk = chunk?;
+            written += 1;
+            // Only the line behind this chunk has been read, so at most one
+            // item is held at a time.
+            assert_eq!(read.load(Ordering::SeqCst), written, "{format:?}");
+            assert!(
+                chunk.contains(&format!("message {}", written - 1)),
+                "{chunk}"
+            );
+        }
+        assert_eq!(written, ITEMS, "{format:?}");
+    }
+    Ok(())
+}
+
+async fn export(
+    app: &Router,
+    thread_id: ThreadId,
+    query: &str,
+) -> Result<(StatusCode, String, String)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/v2/threads/{thread_id}/export{query}"))
+                .body(Body::empty())?,
+        )
+        .await?;
+    let status = response.status();
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, content_type, String::from_utf8(body.to_vec())?))
+}
+
+#[tokio::test]
+async fn test_export_of_a_long_rollout_streams_every_item_up_to_the_cap() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let thread_id = ThreadId::new();
+    write_long_rollout(&fixture.codex_home_path(), thread_id)?;
+    let app = Router::new()
+        .route(
+            "/api/v2/threads/{id}/export",
+            get(handlers::threads::export_thread),
+        )
+        .with_state(create_test_state(&fixture, "test-token"));
+
+    let (status, content_type, body) = export(&app, thread_id, "").await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "application/x-ndjson");
+    assert_eq!(body.lines().count(), ITEMS + 1);
+    assert!(body.ends_with(&format!("{}\n", user_line(ITEMS - 1)?)));
+
+    let (status, _, body) = export(&app, thread_id, "?max_items=100").await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.lines().count(), 100);
+
+    let (status, content_type, body) =
+        export(&app, thread_id, "?format=markdown&max_items=3").await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type, "text/markdown; charset=utf-8");
+    assert!(
+        body.starts_with(&format!("# Thread {thread_id}\n")),
+        "{body}"
+    );
+    assert_eq!(body.matches("## User\n").count(), 2, "{body}");
+    assert!(body.contains("message 1"), "{body}");
+    assert!(!body.contains("message 2"), "{body}");
+    assert!(
+        body.ends_with("raise `max_items` for the rest._\n"),
+        "{body}"
+    );
+
+    let (status, _, _) = export(&app, thread_id, "?max_items=0").await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use codex_protocol::ThreadId;
+use codex_web_server::thread_memory::CacheKind;
+use codex_web_server::thread_memory::Retention;
+use codex_web_server::thread_memory::ThreadMemory;
+
+const BUDGET: usize = 64 * 1024;
+
+/// A payload of `len` bytes, like a serialized event.
+fn payload(len: usize) -> String {
+    "x".repeat(len)
+}
+
+#[test]
+fn test_oversized_items_stay_within_budget_and_keep_lifecycle_events() {
+    let memory = ThreadMemory::new(BUDGET);
+    let thread_id = ThreadId::new();
+
+    // A turn: lifecycle and approval events between bulky deltas and outputs.
+    memory.insert(
+        thread_id,
+        CacheKind::Replay,
+        1,
+        payload(200),
+        Retention::Protected,
+    );
+    memory.insert(
+        thread_id,
+        CacheKind::Replay,
+        2,
+        payload(300),
+        Retention::Protected,
+    );
+    for key in 3..200 {
+        let len = if key % 10 == 0 { 30 * 1024 } else { 512 };
+        memory.insert(
+            thread_id,
+            CacheKind::Items,
+            key,
+            payload(len),
+            Retention::Bulky,
+        );
+        assert!(memory.used(thread_id) <= BUDGET, "over budget at {key}");
+    }
+    memory.insert(
+        thread_id,
+        CacheKind::Replay,
+        200,
+        payload(200),
+        Retention::Protected,
+    );
+    // Larger than the whole budget: evicted right away.
+    memory.insert(
+        thread_id,
+        CacheKind::Items,
+        201,
+        payload(BUDGET + 1),
+        Retention::Bulky,
+    );
+
+    assert!(memory.used(thread_id) <= BUDGET);
+    assert_eq!(memory.keys(thread_id, CacheKind::Replay), vec![1, 2, 200]);
+    assert!(memory.get(thread_id, CacheKind::Items, 201).is_none());
+    // The largest payloads went first, so recent small items survive.
+    assert!(memory.get(thread_id, CacheKind::Items, 190).is_none());
+    assert!(memory.get(thread_id, CacheKind::Items, 199).is_some());
+
+    let snapshot = memory.snapshot();
+    assert_eq!(snapshot.budget_bytes, BUDGET as u64);
+    assert_eq!(snapshot.used_bytes, memory.used(thread_id) as u64);
+    assert_eq!(snapshot.threads, 1);
+    assert!(snapshot.evicted_entries > 0);
+    assert!(snapshot.evicted_bytes >= (BUDGET + 1) as u64);
+    assert_eq!(snapshot.evicted_protected_entries, 0);
+}
+
+#[test]
+fn test_least_recently_used_goes_first_among_equal_sizes() {
+    let memory = ThreadMemory::new(3 * 1024);
+    let thread_id = ThreadId::new();
+    for key in 1..=3 {
+        memory.insert(
+            thread_id,
+            CacheKind::Items,
+            key,
+            payload(1024),
+            Retention::Bulky,
+        );
+    }
+    // Reading 1 makes 2 the least recently used.
+    assert!(memory.get(thread_id, CacheKind::Items, 1).is_some());
+    memory.insert(
+        thread_id,
+        CacheKind::Items,
+        4,
+        payload(1024),
+        Retention::Bulky,
+    );
+
+    assert_eq!(memory.keys(thread_id, CacheKind::Items), vec![1, 3, 4]);
+    // Threads have separate budgets.
+    assert_eq!(memory.used(ThreadId::new()), 0);
+    memory.forget(thread_id);
+    assert_eq!(memory.snapshot().used_bytes, 0);
+}
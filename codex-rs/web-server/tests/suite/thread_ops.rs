@@ -0,0 +1,148 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::post;
+use codex_protocol::ThreadId;
+use codex_web_server::error::ApiError;
+use codex_web_server::handlers;
+use codex_web_server::mock_model::MockModelServer;
+use codex_web_server::mock_model::MockScript;
+use codex_web_server::thread_ops::ThreadOps;
+use serde_json::Value;
+use serde_json::json;
+use std::path::PathBuf;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+/// Turns stop on a command approval, so later inputs find a running turn
+/// instead of an idle thread.
+const APPROVAL_CONFIG: &str = r#"
+model = "test-model"
+approval_policy = "on-request"
+sandbox_mode = "workspace-write"
+"#;
+
+async fn post_json(app: &Router, uri: &str, body: Value) -> Result<(StatusCode, Value)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+#[tokio::test]
+async fn test_operation_that_began_before_a_change_is_rejected() -> Result<()> {
+    let ops = ThreadOps::default();
+    let thread_id = ThreadId::new();
+
+    let stale = ops.begin(thread_id);
+    let archive = ops.begin(thread_id);
+    let other_thread = ops.begin(ThreadId::new());
+    {
+        let guard = archive
+            .lock()
+            .await
+            .map_err(|err| anyhow::anyhow!("{err:?}"))?;
+        guard.bump();
+    }
+
+    assert!(matches!(
+        stale.lock().await,
+        Err(ApiError::ThreadStateChanged)
+    ));
+    assert!(ops.begin(thread_id).lock().await.is_ok());
+    assert!(other_thread.lock().await.is_ok());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_interleaved_archive_and_send_turn_never_fail_uncleanly() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(APPROVAL_CONFIG)?;
+    let script = MockScript::load(
+        &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mock_thread.json"),
+    )?;
+    let server = MockModelServer::start(script).await?;
+    let state = create_test_state(&fixture, "test-token")
+        .with_config_loader(server.config_loader(fixture.codex_home_path()));
+    let app = Router::new()
+        .route("/api/v2/threads", post(handlers::threads::create_thread))
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(handlers::turns::send_turn),
+        )
+        .route(
+            "/api/v2/threads/{id}/archive",
+            post(handlers::threads::archive_thread),
+        )
+        .with_state(state);
+
+    let (status, created) = post_json(
+        &app,
+        "/api/v2/threads",
+        json!({ "cwd": fixture.codex_home.path() }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{created}");
+    let thread_id = created["thread_id"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+
+    let mut tasks = Vec::new();
+    for i in 0..24 {
+        let app = app.clone();
+        let thread_id = thread_id.clone();
+        tasks.push(tokio::spawn(async move {
+            if i % 2 == 0 {
+                post_json(
+                    &app,
+                    &format!("/api/v2/threads/{thread_id}/archive"),
+                    json!({}),
+                )
+                .await
+            } else {
+                post_json(
+                    &app,
+                    &format!("/api/v2/threads/{thread_id}/turns"),
+                    json!({ "input": [{ "type": "text", "text": format!("input {i}") }] }),
+                )
+                .await
+            }
+        }));
+    }
+
+    for task in tasks {
+        let (status, body) = task.await??;
+        match status {
+            StatusCode::OK => {}
+            // Changed by an archive, or sent while an earlier input's turn runs.
+            StatusCode::CONFLICT => {
+                assert!(
+                    body["code"] == json!("thread_state_changed")
+                        || body["code"] == json!("turn_in_progress"),
+                    "{body}"
+                );
+            }
+            // Sent after an archive unloaded the thread.
+            StatusCode::NOT_FOUND => {
+                assert_eq!(body["error"], json!("Thread not found"), "{body}");
+            }
+            other => panic!("unexpected {other}: {body}"),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,208 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::ContextCompactedEvent;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::ThreadRolledBackEvent;
+use codex_web_server::handlers;
+use codex_web_server::mock_model::MockModelServer;
+use codex_web_server::mock_model::MockScript;
+use codex_web_server::thread_progress::LongOperation;
+use codex_web_server::thread_progress::LongOperationStatus;
+use codex_web_server::thread_progress::ProgressRegistry;
+use codex_web_server::thread_progress::ProgressUpdate;
+use serde_json::Value;
+use serde_json::json;
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tower::ServiceExt;
+
+use crate::common::EventReader;
+use crate::common::TEST_CONFIG;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+fn event(msg: EventMsg) -> Event {
+    Event {
+        id: "sub-1".to_string(),
+        msg,
+    }
+}
+
+async fn next_update(updates: &mut broadcast::Receiver<ProgressUpdate>) -> Result<ProgressUpdate> {
+    Ok(tokio::time::timeout(Duration::from_secs(5), updates.recv()).await??)
+}
+
+#[tokio::test]
+async fn test_slow_compaction_reports_checkpoints_then_completion() -> Result<()> {
+    let registry = ProgressRegistry::new(Duration::from_millis(20), Duration::from_secs(5));
+    let mut updates = registry.subscribe();
+    let thread_id = ThreadId::new();
+
+    let _completion = registry.begin(thread_id, LongOperation::Compaction);
+    assert!(registry.is_running(thread_id, LongOperation::Compaction));
+    let mut percents = Vec::new();
+    for _ in 0..3 {
+        let update = next_update(&mut updates).await?;
+        assert_eq!(update.event_type(), "thread/compacting");
+        assert!(!update.background);
+        percents.push(update.percent);
+    }
+    assert!(
+        percents.windows(2).all(|pair| pair[0] < pair[1]),
+        "{percents:?}"
+    );
+    assert!(
+        percents.iter().all(|percent| *percent < 100),
+        "{percents:?}"
+    );
+
+    // Events about other threads or unrelated to compaction change nothing.
+    registry.observe(
+        ThreadId::new(),
+        &event(EventMsg::ContextCompacted(ContextCompactedEvent)),
+    );
+    registry.observe(
+        thread_id,
+        &event(EventMsg::ThreadRolledBack(ThreadRolledBackEvent {
+            num_turns: 1,
+        })),
+    );
+    assert!(registry.is_running(thread_id, LongOperation::Compaction));
+
+    registry.observe(
+        thread_id,
+        &event(EventMsg::ContextCompacted(ContextCompactedEvent)),
+    );
+    assert!(!registry.is_running(thread_id, LongOperation::Compaction));
+    let last = loop {
+        let update = next_update(&mut updates).await?;
+        if update.percent == 100 {
+            break update;
+        }
+    };
+    assert_eq!(
+        last.payload(),
+        json!({
+            "method": "thread/compacting",
+            "params": {
+                "threadId": thread_id.to_string(),
+                "percent": 100,
+                "background": false,
+            },
+        })
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rollback_past_foreground_limit_continues_in_background() -> Result<()> {
+    let registry = ProgressRegistry::new(Duration::from_millis(10), Duration::from_millis(50));
+    let mut updates = registry.subscribe();
+    let thread_id = ThreadId::new();
+
+    let completion = registry.begin(thread_id, LongOperation::Rollback);
+    let status = registry
+        .wait_in_foreground(thread_id, LongOperation::Rollback, completion)
+        .await;
+    assert_eq!(status, LongOperationStatus::RunningInBackground);
+    let background = loop {
+        let update = next_update(&mut updates).await?;
+        assert_eq!(update.event_type(), "thread/rollingBack");
+        if update.background {
+            break update;
+        }
+    };
+    assert!(background.percent < 100);
+
+    // A second waiter joins the running rollback and sees it end.
+    let joined = registry.begin(thread_id, LongOperation::Rollback);
+    registry.observe(
+        thread_id,
+        &event(EventMsg::ThreadRolledBack(ThreadRolledBackEvent {
+            num_turns: 2,
+        })),
+    );
+    let status = registry
+        .wait_in_foreground(thread_id, LongOperation::Rollback, joined)
+        .await;
+    assert_eq!(status, LongOperationStatus::Completed);
+    let last = loop {
+        let update = next_update(&mut updates).await?;
+        if update.percent == 100 {
+            break update;
+        }
+    };
+    assert!(!last.background);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_compact_endpoint_accepts_and_streams_progress() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let script = MockScript::load(
+        &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mock_thread.json"),
+    )?;
+    let server = MockModelServer::start(script).await?;
+    let state = create_test_state(&fixture, "test-token")
+        .with_config_loader(server.config_loader(fixture.codex_home_path()))
+        .with_progress(ProgressRegistry::new(
+            Duration::from_secs(60),
+            Duration::from_secs(60),
+        ));
+    let app = Router::new()
+        .route("/api/v2/threads", post(handlers::threads::create_thread))
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .route(
+            "/api/v2/threads/{id}/compact",
+            post(handlers::threads::compact_thread),
+        )
+        .with_state(state);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v2/threads")
+                .header("content-type", "application/json")
+                .body(Body::from("{}"))?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let created: Value =
+        serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+    let thread_id = ThreadId::from_string(created["thread_id"].as_str().unwrap_or_default())?;
+    let mut events = EventReader::open(&app, thread_id).await?;
+
+    let compact = |thread_id: String| {
+        app.clone().oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/v2/threads/{thread_id}/compact"))
+                .body(Body::empty()),
+        )
+    };
+    let response = compact(thread_id.to_string()).await?;
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+    assert_eq!(body["status"], json!("running_in_background"));
+
+    // The first checkpoint is sent as soon as compaction starts.
+    let progress = events.next_of("thread/compacting").await?;
+    assert_eq!(progress["params"]["threadId"], json!(thread_id.to_string()));
+    assert_eq!(progress["params"]["percent"], json!(5));
+
+    let response = compact(ThreadId::new().to_string()).await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    Ok(())
+}
@@ -0,0 +1,157 @@
+//! Scanning stored threads for the listing without resuming them.
+
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use codex_protocol::ThreadId;
+use codex_protocol::config_types::ReasoningSummary;
+use codex_protocol::protocol::AskForApproval;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use codex_protocol::protocol::SandboxPolicy;
+use codex_protocol::protocol::TurnContextItem;
+use codex_web_server::handlers;
+use serde_json::Value;
+use serde_json::json;
+use std::path::Path;
+use std::time::Duration;
+use std::time::SystemTime;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+/// Writes a rollout whose turns ran on `models`, in order, last modified
+/// `age` ago.
+fn write_rollout(
+    codex_home: &Path,
+    thread_id: ThreadId,
+    models: &[&str],
+    age: Duration,
+) -> Result<()> {
+    let dir = codex_home.join("sessions/2025/04/01");
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("rollout-2025-04-01T00-00-00-{thread_id}.jsonl"));
+    let mut contents = String::new();
+    for (turn, model) in models.iter().enumerate() {
+        let line = RolloutLine {
+            timestamp: "2025-04-01T00:00:00.000Z".to_string(),
+            item: RolloutItem::TurnContext(TurnContextItem {
+                turn_id: Some(format!("turn-{turn}")),
+                cwd: codex_home.to_path_buf(),
+                approval_policy: AskForApproval::Never,
+                sandbox_policy: SandboxPolicy::new_read_only_policy(),
+                network: None,
+                model: model.to_string(),
+                personality: None,
+                collaboration_mode: None,
+                effort: None,
+                summary: ReasoningSummary::Auto,
+                user_instructions: None,
+                developer_instructions: None,
+                final_output_json_schema: None,
+                truncation_policy: None,
+            }),
+        };
+        contents.push_str(&serde_json::to_string(&line)?);
+        contents.push('\n');
+    }
+    std::fs::write(&path, contents)?;
+    std::fs::File::options()
+        .write(true)
+        .open(&path)?
+        .set_modified(SystemTime::now() - age)?;
+    Ok(())
+}
+
+async fn send(app: &Router, method: &str, uri: &str) -> Result<(StatusCode, Value)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method(method)
+                .uri(uri)
+                .body(Body::empty())?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+#[tokio::test]
+async fn test_scan_reads_the_most_recent_threads_into_the_listing() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let recent = ThreadId::new();
+    let older = ThreadId::new();
+    let oldest = ThreadId::new();
+    let hour = Duration::from_secs(3600);
+    write_rollout(
+        fixture.codex_home.path(),
+        recent,
+        &["model-a", "model-b"],
+        hour,
+    )?;
+    write_rollout(fixture.codex_home.path(), older, &[], 2 * hour)?;
+    write_rollout(fixture.codex_home.path(), oldest, &["model-c"], 3 * hour)?;
+    let state = create_test_state(&fixture, "test-token").with_thread_scan_limit(2);
+    let app = Router::new()
+        .route("/api/v2/threads", get(handlers::threads::list_threads))
+        .route(
+            "/api/v2/threads/scan",
+            post(handlers::threads::scan_threads),
+        )
+        .with_state(state.clone());
+
+    // Listed before any scan, just without models.
+    let (status, listing) = send(&app, "GET", "/api/v2/threads").await?;
+    assert_eq!(status, StatusCode::OK, "{listing}");
+    assert_eq!(
+        listing["thread_ids"],
+        json!([recent.to_string(), older.to_string(), oldest.to_string()])
+    );
+    assert!(
+        listing["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .all(|thread| thread["status"] == json!("persisted") && thread["model"].is_null()),
+        "{listing}"
+    );
+
+    // The scan stops at its limit; the oldest thread is left out.
+    let (status, scan) = send(&app, "POST", "/api/v2/threads/scan").await?;
+    assert_eq!(status, StatusCode::OK, "{scan}");
+    let scanned: Vec<(Value, Value)> = scan["threads"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|thread| (thread["thread_id"].clone(), thread["model"].clone()))
+        .collect();
+    assert_eq!(
+        scanned,
+        vec![
+            (json!(recent.to_string()), json!("model-b")),
+            (json!(older.to_string()), Value::Null),
+        ]
+    );
+    assert_eq!(scan["skipped"], json!(1));
+
+    let (_, listing) = send(&app, "GET", "/api/v2/threads").await?;
+    let models: Vec<Value> = listing["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|thread| thread["model"].clone())
+        .collect();
+    assert_eq!(models, vec![json!("model-b"), Value::Null, Value::Null]);
+
+    // Nothing was resumed to get there.
+    assert!(state.thread_manager.list_thread_ids().await.is_empty());
+    Ok(())
+}
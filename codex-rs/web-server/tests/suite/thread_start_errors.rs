@@ -0,0 +1,153 @@
+use anyhow::Result;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use codex_core::config::Config;
+use codex_core::config::ConfigBuilder;
+use codex_core::error::CodexErr;
+use codex_core::error::UnexpectedResponseError;
+use codex_web_server::error::ApiError;
+use codex_web_server::handlers::threads::start_configured_thread;
+use codex_web_server::instance::instance_id;
+use serde_json::Value;
+use serde_json::json;
+
+use crate::common::TEST_CONFIG;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+/// Custom provider that does not need ChatGPT auth. Nothing listens on the
+/// base URL; none of these tests get as far as a model request.
+const OFFLINE_PROVIDER: &str = r#"
+model = "test-model"
+approval_policy = "never"
+sandbox_mode = "read-only"
+model_provider = "offline"
+
+[model_providers.offline]
+name = "Offline provider"
+base_url = "http://127.0.0.1:9/v1"
+wire_api = "responses"
+request_max_retries = 0
+stream_max_retries = 0
+"#;
+
+async fn load_fixture_config(fixture: &TestFixture, contents: &str) -> Result<Config> {
+    fixture.create_test_config(contents)?;
+    let mut config = ConfigBuilder::default()
+        .codex_home(fixture.codex_home_path())
+        .build()
+        .await?;
+    config.cwd = fixture.codex_home_path();
+    Ok(config)
+}
+
+async fn error_body(err: ApiError) -> Result<(StatusCode, Value)> {
+    let response = err.into_response();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+#[tokio::test]
+async fn test_missing_cwd_is_cwd_unwritable() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let mut config = load_fixture_config(&fixture, TEST_CONFIG).await?;
+    let missing = fixture.codex_home_path().join("does-not-exist");
+    config.cwd = missing.clone();
+
+    let err = start_configured_thread(&create_test_state(&fixture, "test-token"), config)
+        .await
+        .err()
+        .ok_or_else(|| anyhow::anyhow!("thread start should fail"))?;
+    let (status, body) = error_body(err).await?;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], json!("cwd_unwritable"));
+    let error = body["error"].as_str().unwrap_or_default();
+    assert!(error.starts_with(&format!("Working directory {}", missing.display())));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_missing_provider_key_is_auth_required() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let contents = format!("{OFFLINE_PROVIDER}env_key = \"CODEX_WEB_SERVER_TEST_UNSET_API_KEY\"\n");
+    let config = load_fixture_config(&fixture, &contents).await?;
+
+    let err = start_configured_thread(&create_test_state(&fixture, "test-token"), config)
+        .await
+        .err()
+        .ok_or_else(|| anyhow::anyhow!("thread start should fail"))?;
+    let (status, body) = error_body(err).await?;
+
+    assert_eq!(
+        (status, body),
+        (
+            StatusCode::UNAUTHORIZED,
+            json!({
+                "error": "Missing environment variable: `CODEX_WEB_SERVER_TEST_UNSET_API_KEY`.",
+                "status": 401,
+                "instance_id": instance_id(),
+                "code": "auth_required",
+            })
+        )
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_required_mcp_failure_is_mcp_startup_failed() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let contents = format!(
+        r#"{OFFLINE_PROVIDER}
+[mcp_servers.required_broken]
+command = "codex-definitely-not-a-real-binary"
+required = true
+"#
+    );
+    let config = load_fixture_config(&fixture, &contents).await?;
+
+    let err = start_configured_thread(&create_test_state(&fixture, "test-token"), config)
+        .await
+        .err()
+        .ok_or_else(|| anyhow::anyhow!("thread start should fail"))?;
+    let (status, body) = error_body(err).await?;
+
+    assert_eq!(status, StatusCode::BAD_GATEWAY);
+    assert_eq!(body["code"], json!("mcp_startup_failed"));
+    assert_eq!(body["server"], json!("required_broken"));
+    let error = body["error"].as_str().unwrap_or_default();
+    assert!(error.starts_with("required MCP servers failed to initialize: required_broken"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unknown_model_response_is_model_not_found() -> Result<()> {
+    let err =
+        ApiError::from_thread_start_error(CodexErr::UnexpectedStatus(UnexpectedResponseError {
+            status: StatusCode::NOT_FOUND,
+            body: r#"{"error":{"code":"model_not_found"}}"#.to_string(),
+            url: None,
+            cf_ray: None,
+            request_id: None,
+        }));
+    let (status, body) = error_body(err).await?;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], json!("model_not_found"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_uncategorized_start_failure_stays_internal() -> Result<()> {
+    let err = ApiError::from_thread_start_error(CodexErr::Fatal("boom".to_string()));
+    let (status, body) = error_body(err).await?;
+
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        body["error"],
+        json!("Failed to start thread: Fatal error: boom")
+    );
+    assert_eq!(body.get("code"), None);
+    Ok(())
+}
@@ -0,0 +1,247 @@
+use anyhow::Result;
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::TurnAbortReason;
+use codex_protocol::protocol::TurnAbortedEvent;
+use codex_protocol::protocol::TurnCompleteEvent;
+use codex_web_server::thread_status::StatusTransition;
+use codex_web_server::thread_status::ThreadRunStatus;
+use codex_web_server::thread_status::ThreadStatusChange;
+use codex_web_server::thread_status::ThreadStatusRegistry;
+use codex_web_server::thread_status::ThreadStatusTracker;
+use serde_json::json;
+
+use ThreadRunStatus::Archived;
+use ThreadRunStatus::Idle;
+use ThreadRunStatus::Interrupted;
+use ThreadRunStatus::Running;
+use ThreadRunStatus::WaitingApproval;
+
+fn started(turn_id: &str) -> StatusTransition {
+    StatusTransition::TurnStarted {
+        turn_id: turn_id.to_string(),
+    }
+}
+
+fn requested(approval_id: &str) -> StatusTransition {
+    StatusTransition::ApprovalRequested {
+        approval_id: approval_id.to_string(),
+    }
+}
+
+fn resolved(approval_id: &str) -> StatusTransition {
+    StatusTransition::ApprovalResolved {
+        approval_id: approval_id.to_string(),
+    }
+}
+
+fn completed(turn_id: &str) -> StatusTransition {
+    StatusTransition::TurnFinished {
+        turn_id: turn_id.to_string(),
+        interrupted: false,
+    }
+}
+
+fn interrupted(turn_id: &str) -> StatusTransition {
+    StatusTransition::TurnFinished {
+        turn_id: turn_id.to_string(),
+        interrupted: true,
+    }
+}
+
+/// Applies `transitions` to a fresh tracker and returns what each one emitted.
+fn run(transitions: Vec<StatusTransition>) -> Vec<Option<ThreadRunStatus>> {
+    let mut tracker = ThreadStatusTracker::default();
+    transitions
+        .into_iter()
+        .map(|transition| tracker.apply(transition))
+        .collect()
+}
+
+#[test]
+fn test_turn_with_approval_lifecycle() {
+    assert_eq!(
+        run(vec![
+            started("t1"),
+            requested("a1"),
+            resolved("a1"),
+            completed("t1"),
+        ]),
+        vec![
+            Some(Running),
+            Some(WaitingApproval),
+            Some(Running),
+            Some(Idle)
+        ]
+    );
+}
+
+#[test]
+fn test_interrupted_turn_then_new_turn() {
+    assert_eq!(
+        run(vec![started("t1"), interrupted("t1"), started("t2")]),
+        vec![Some(Running), Some(Interrupted), Some(Running)]
+    );
+}
+
+#[test]
+fn test_waiting_until_every_approval_resolves() {
+    assert_eq!(
+        run(vec![
+            started("t1"),
+            requested("a1"),
+            requested("a2"),
+            resolved("a1"),
+            resolved("a2"),
+        ]),
+        vec![
+            Some(Running),
+            Some(WaitingApproval),
+            None,
+            None,
+            Some(Running)
+        ]
+    );
+}
+
+#[test]
+fn test_turn_end_clears_unanswered_approvals() {
+    assert_eq!(
+        run(vec![started("t1"), requested("a1"), interrupted("t1")]),
+        vec![Some(Running), Some(WaitingApproval), Some(Interrupted)]
+    );
+}
+
+#[test]
+fn test_archive_and_restore() {
+    assert_eq!(
+        run(vec![
+            StatusTransition::Archived,
+            StatusTransition::Archived,
+            StatusTransition::Restored,
+        ]),
+        vec![Some(Archived), None, Some(Idle)]
+    );
+    // Restoring does not forget an interruption from before the archive.
+    assert_eq!(
+        run(vec![
+            started("t1"),
+            interrupted("t1"),
+            StatusTransition::Archived,
+            StatusTransition::Restored,
+        ]),
+        vec![
+            Some(Running),
+            Some(Interrupted),
+            Some(Archived),
+            Some(Interrupted)
+        ]
+    );
+}
+
+#[test]
+fn test_repeated_transitions_emit_nothing() {
+    assert_eq!(
+        run(vec![
+            started("t1"),
+            started("t1"),
+            completed("t1"),
+            completed("t1")
+        ]),
+        vec![Some(Running), None, Some(Idle), None]
+    );
+}
+
+#[test]
+fn test_stale_completion_does_not_end_newer_turn() {
+    assert_eq!(
+        run(vec![started("t1"), started("t2"), completed("t1")]),
+        vec![Some(Running), None, None]
+    );
+}
+
+#[test]
+fn test_late_start_after_completion_is_ignored() {
+    // The turn finished before the submitting request recorded its start.
+    assert_eq!(run(vec![completed("t1"), started("t1")]), vec![None, None]);
+}
+
+#[test]
+fn test_resolution_before_request_never_waits() {
+    assert_eq!(
+        run(vec![started("t1"), resolved("a1"), requested("a1")]),
+        vec![Some(Running), None, None]
+    );
+}
+
+#[test]
+fn test_events_map_to_transitions() {
+    let complete = Event {
+        id: "t1".to_string(),
+        msg: EventMsg::TurnComplete(TurnCompleteEvent {
+            turn_id: "t1".to_string(),
+            last_agent_message: None,
+        }),
+    };
+    let aborted = Event {
+        id: "t2".to_string(),
+        msg: EventMsg::TurnAborted(TurnAbortedEvent {
+            turn_id: None,
+            reason: TurnAbortReason::Interrupted,
+        }),
+    };
+    let replaced = Event {
+        id: "t3".to_string(),
+        msg: EventMsg::TurnAborted(TurnAbortedEvent {
+            turn_id: Some("t3".to_string()),
+            reason: TurnAbortReason::Replaced,
+        }),
+    };
+
+    assert_eq!(
+        [&complete, &aborted, &replaced].map(StatusTransition::from_event),
+        [
+            Some(completed("t1")),
+            Some(interrupted("t2")),
+            Some(completed("t3")),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_registry_broadcasts_changes() -> Result<()> {
+    let registry = ThreadStatusRegistry::default();
+    let mut changes = registry.subscribe();
+    let thread_id = ThreadId::new();
+
+    assert_eq!(registry.status(thread_id), Idle);
+    registry.apply(thread_id, started("t1"));
+    registry.apply(thread_id, started("t1"));
+    registry.apply(thread_id, completed("t1"));
+
+    let first = changes.recv().await?;
+    let second = changes.recv().await?;
+    assert_eq!(
+        [first.clone(), second],
+        [
+            ThreadStatusChange {
+                thread_id,
+                status: Running,
+            },
+            ThreadStatusChange {
+                thread_id,
+                status: Idle,
+            },
+        ]
+    );
+    assert!(changes.try_recv().is_err());
+    assert_eq!(
+        first.payload(),
+        json!({
+            "method": "thread/status/changed",
+            "params": { "threadId": thread_id.to_string(), "status": "running" },
+        })
+    );
+    Ok(())
+}
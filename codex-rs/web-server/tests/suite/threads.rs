@@ -1,8 +1,61 @@
 use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::patch;
+use axum::routing::post;
+use codex_core::WebStateStore;
 use codex_protocol::ThreadId;
+use codex_protocol::config_types::ReasoningSummary;
+use codex_protocol::models::ResponseItem;
+use codex_protocol::protocol::AgentMessageEvent;
+use codex_protocol::protocol::AskForApproval;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::ExecApprovalRequestEvent;
+use codex_protocol::protocol::Op;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use codex_protocol::protocol::SandboxPolicy;
+use codex_protocol::protocol::SessionMeta;
+use codex_protocol::protocol::SessionMetaLine;
+use codex_protocol::protocol::TokenCountEvent;
+use codex_protocol::protocol::TokenUsage;
+use codex_protocol::protocol::TokenUsageInfo;
+use codex_protocol::protocol::TurnCompleteEvent;
+use codex_protocol::protocol::TurnContextItem;
+use codex_protocol::protocol::TurnStartedEvent;
+use codex_protocol::protocol::UserMessageEvent;
+use codex_web_server::approval_manager::register_approval_request;
+use codex_web_server::handlers;
+use codex_web_server::mock_model::MockModelServer;
+use codex_web_server::mock_model::MockScript;
+use codex_web_server::rollout_import::ImportLimits;
+use codex_web_server::state::WebServerState;
+use codex_web_server::thread_status::StatusTransition;
+use codex_web_server::thread_status::ThreadRunStatus;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use serde_json::Value;
+use serde_json::json;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+use tower::ServiceExt;
 
+use crate::common::EventReader;
 use crate::common::TEST_CONFIG;
 use crate::common::TestFixture;
+use crate::common::create_test_state;
+use crate::common::start_mock_provider_thread;
 
 #[tokio::test]
 async fn test_thread_resume_rollout_file_validation() -> Result<()> {
@@ -120,3 +173,1420 @@ async fn test_thread_resume_rollout_content_format() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_thread_pins_persist_in_file_backend() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let codex_home = fixture.codex_home_path();
+
+    let pinned = ThreadId::new();
+    let unpinned = ThreadId::new();
+    codex_web_server::thread_prefs::set_thread_pinned(&codex_home, pinned, true).await?;
+    codex_web_server::thread_prefs::set_thread_pinned(&codex_home, unpinned, true).await?;
+    codex_web_server::thread_prefs::set_thread_pinned(&codex_home, unpinned, false).await?;
+
+    let pins = codex_web_server::thread_prefs::pinned_threads(&codex_home).await?;
+    assert_eq!(pins, std::collections::HashSet::from([pinned]));
+
+    Ok(())
+}
+
+fn details_app(state: WebServerState) -> Router {
+    Router::new()
+        .route("/api/v2/threads", post(handlers::threads::create_thread))
+        .route("/api/v2/threads/{id}", get(handlers::threads::get_thread))
+        .with_state(state)
+}
+
+async fn get_details(app: &Router, thread_id: &str) -> Result<(StatusCode, Value)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/v2/threads/{thread_id}"))
+                .body(Body::empty())?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+/// Writes a rollout with one turn and one token count, laid out the way the
+/// recorder stores it.
+fn write_rollout(codex_home: &Path, thread_id: ThreadId, cwd: &Path) -> Result<PathBuf> {
+    let dir = codex_home.join("sessions/2025/01/02");
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("rollout-2025-01-02T03-04-05-{thread_id}.jsonl"));
+    let usage = TokenUsage {
+        input_tokens: 1200,
+        cached_input_tokens: 200,
+        output_tokens: 300,
+        reasoning_output_tokens: 50,
+        total_tokens: 1500,
+    };
+    let items = [
+        RolloutItem::SessionMeta(SessionMetaLine {
+            meta: SessionMeta {
+                id: thread_id,
+                cwd: cwd.to_path_buf(),
+                ..Default::default()
+            },
+            git: None,
+        }),
+        RolloutItem::TurnContext(TurnContextItem {
+            turn_id: Some("turn-1".to_string()),
+            cwd: cwd.to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            network: None,
+            model: "disk-model".to_string(),
+            personality: None,
+            collaboration_mode: None,
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            user_instructions: None,
+            developer_instructions: None,
+            final_output_json_schema: None,
+            truncation_policy: None,
+        }),
+        RolloutItem::EventMsg(EventMsg::TokenCount(TokenCountEvent {
+            info: Some(TokenUsageInfo {
+                total_token_usage: usage.clone(),
+                last_token_usage: usage,
+                model_context_window: None,
+            }),
+            rate_limits: None,
+        })),
+    ];
+    let mut contents = String::new();
+    for item in items {
+        let line = RolloutLine {
+            timestamp: "2025-01-02T03:04:05.000Z".to_string(),
+            item,
+        };
+        contents.push_str(&serde_json::to_string(&line)?);
+        contents.push('\n');
+    }
+    std::fs::write(&path, contents)?;
+    Ok(path)
+}
+
+#[tokio::test]
+async fn test_get_thread_reads_unloaded_threads_from_their_rollout() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let thread_id = ThreadId::new();
+    let cwd = fixture.codex_home_path();
+    let rollout_path = write_rollout(&cwd, thread_id, &cwd)?;
+    let app = details_app(create_test_state(&fixture, "test-token"));
+
+    let (status, body) = get_details(&app, &thread_id.to_string()).await?;
+
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["thread_id"], json!(thread_id.to_string()));
+    assert_eq!(body["active"], json!(false));
+    assert_eq!(body["model"], json!("disk-model"));
+    assert_eq!(body["cwd"], json!(cwd.display().to_string()));
+    assert_eq!(
+        body["rollout_path"],
+        json!(rollout_path.display().to_string())
+    );
+    assert_eq!(body["token_usage"]["totalTokens"], json!(1500));
+    assert_eq!(body["token_usage"]["cachedInputTokens"], json!(200));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_thread_reports_loaded_threads_as_active() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let script = MockScript::load(
+        &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mock_thread.json"),
+    )?;
+    let server = MockModelServer::start(script).await?;
+    let state = create_test_state(&fixture, "test-token")
+        .with_config_loader(server.config_loader(fixture.codex_home_path()));
+    let app = details_app(state);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v2/threads")
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({ "cwd": fixture.codex_home.path() }).to_string(),
+                ))?,
+        )
+        .await?;
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    let created: Value = serde_json::from_slice(&body)?;
+    let thread_id = created["thread_id"].as_str().unwrap_or_default();
+
+    let (status, body) = get_details(&app, thread_id).await?;
+
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["active"], json!(true));
+    assert_eq!(body["model"], json!("test-model"));
+    assert_eq!(
+        body["cwd"],
+        json!(fixture.codex_home.path().display().to_string())
+    );
+    // No turn has run, so the model has not reported usage yet.
+    assert_eq!(body["token_usage"], Value::Null);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_get_thread_rejects_unknown_and_malformed_ids() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let app = details_app(create_test_state(&fixture, "test-token"));
+
+    let (status, _) = get_details(&app, &ThreadId::new().to_string()).await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = get_details(&app, "not-a-thread").await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    Ok(())
+}
+
+async fn list(app: &Router, query: &str) -> Result<(StatusCode, Value)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/v2/threads{query}"))
+                .body(Body::empty())?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+#[tokio::test]
+async fn test_list_threads_includes_persisted_rollouts_page_by_page() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let codex_home = fixture.codex_home_path();
+    let mut persisted = Vec::new();
+    for _ in 0..3 {
+        let thread_id = ThreadId::new();
+        write_rollout(&codex_home, thread_id, &codex_home)?;
+        persisted.push(thread_id.to_string());
+    }
+    let archived = ThreadId::new();
+    let archived_dir = codex_home.join("archived_sessions");
+    std::fs::create_dir_all(&archived_dir)?;
+    std::fs::write(
+        archived_dir.join(format!("rollout-2025-01-02T03-04-05-{archived}.jsonl")),
+        "",
+    )?;
+    let app = Router::new()
+        .route("/api/v2/threads", get(handlers::threads::list_threads))
+        .with_state(create_test_state(&fixture, "test-token"));
+
+    // A restarted server has nothing loaded but still lists the rollouts.
+    let mut listed = Vec::new();
+    let mut query = "?limit=2".to_string();
+    loop {
+        let (status, page) = list(&app, &query).await?;
+        assert_eq!(status, StatusCode::OK, "{page}");
+        for summary in page["data"].as_array().into_iter().flatten() {
+            assert_eq!(summary["status"], json!("persisted"), "{summary}");
+            assert_eq!(summary["active"], json!(false), "{summary}");
+            listed.push(
+                summary["thread_id"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            );
+        }
+        match page["next_cursor"].as_str() {
+            Some(cursor) => query = format!("?limit=2&cursor={cursor}"),
+            None => break,
+        }
+    }
+    listed.sort();
+    persisted.sort();
+    assert_eq!(listed, persisted);
+
+    let (_, with_archived) = list(&app, "?include_archived=true").await?;
+    let archived_entry = with_archived["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|summary| summary["thread_id"] == json!(archived.to_string()));
+    assert_eq!(
+        archived_entry.map(|summary| summary["status"].clone()),
+        Some(json!("archived"))
+    );
+
+    let (status, _) = list(&app, "?limit=0").await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = list(&app, &format!("?cursor={}", ThreadId::new())).await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    Ok(())
+}
+
+async fn rename(app: &Router, thread_id: &str, name: &str) -> Result<(StatusCode, Value)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("PATCH")
+                .uri(format!("/api/v2/threads/{thread_id}"))
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "name": name }).to_string()))?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body).unwrap_or(Value::Null)))
+}
+
+#[tokio::test]
+async fn test_renamed_threads_are_listed_by_name() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let codex_home = fixture.codex_home_path();
+    let thread_id = ThreadId::new();
+    write_rollout(&codex_home, thread_id, &codex_home)?;
+    let app = Router::new()
+        .route("/api/v2/threads", get(handlers::threads::list_threads))
+        .route(
+            "/api/v2/threads/{id}",
+            patch(handlers::threads::update_thread),
+        )
+        .with_state(create_test_state(&fixture, "test-token"));
+
+    let (status, body) = rename(&app, &thread_id.to_string(), "  my feature work ").await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["name"], json!("my feature work"));
+
+    let (status, page) = list(&app, "").await?;
+    assert_eq!(status, StatusCode::OK, "{page}");
+    let names: Vec<Value> = page["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|summary| summary["name"].clone())
+        .collect();
+    assert_eq!(names, vec![json!("my feature work")]);
+
+    let (status, _) = rename(&app, &thread_id.to_string(), "   ").await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = rename(&app, &ThreadId::new().to_string(), "elsewhere").await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    Ok(())
+}
+
+async fn update(app: &Router, thread_id: ThreadId, body: Value) -> Result<StatusCode> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("PATCH")
+                .uri(format!("/api/v2/threads/{thread_id}"))
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))?,
+        )
+        .await?;
+    Ok(response.status())
+}
+
+/// The threads of `ours` in the order `query` lists them, across every page;
+/// other threads in a shared store are skipped.
+async fn listed_order(app: &Router, query: &str, ours: &[ThreadId]) -> Result<Vec<ThreadId>> {
+    let mut order = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page_query = match &cursor {
+            Some(cursor) => format!("?limit=500&{query}&cursor={cursor}"),
+            None => format!("?limit=500&{query}"),
+        };
+        let (status, page) = list(app, &page_query).await?;
+        assert_eq!(status, StatusCode::OK, "{page}");
+        for summary in page["data"].as_array().into_iter().flatten() {
+            let thread_id =
+                ThreadId::from_string(summary["thread_id"].as_str().unwrap_or_default())?;
+            if ours.contains(&thread_id) {
+                order.push(thread_id);
+            }
+        }
+        match page["next_cursor"].as_str() {
+            Some(next) => cursor = Some(next.to_string()),
+            None => return Ok(order),
+        }
+    }
+}
+
+fn listing_app(state: WebServerState) -> Router {
+    Router::new()
+        .route("/api/v2/threads", get(handlers::threads::list_threads))
+        .route(
+            "/api/v2/threads/{id}",
+            patch(handlers::threads::update_thread),
+        )
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(handlers::turns::send_turn),
+        )
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .with_state(state)
+}
+
+#[tokio::test]
+async fn test_list_threads_sorts_file_backed_threads() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let codex_home = fixture.codex_home_path();
+    let app = listing_app(create_test_state(&fixture, "test-token"));
+
+    // Created in order, updated in the order second, third, first.
+    let threads = [ThreadId::new(), ThreadId::new(), ThreadId::new()];
+    let mut paths = Vec::new();
+    for thread_id in threads {
+        paths.push(write_rollout(&codex_home, thread_id, &codex_home)?);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let base = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    for (path, offset) in paths.iter().zip([0, 300, 200]) {
+        std::fs::File::options()
+            .write(true)
+            .open(path)?
+            .set_modified(base + Duration::from_secs(offset))?;
+    }
+    let [first, second, third] = threads;
+    assert_eq!(
+        update(&app, first, json!({"name": "beta"})).await?,
+        StatusCode::OK
+    );
+    assert_eq!(
+        update(&app, second, json!({"name": "Alpha"})).await?,
+        StatusCode::OK
+    );
+    assert_eq!(
+        update(&app, third, json!({"pinned": true})).await?,
+        StatusCode::OK
+    );
+
+    assert_eq!(
+        listed_order(&app, "", &threads).await?,
+        vec![second, third, first]
+    );
+    // Names sort case-insensitively, unnamed threads last.
+    assert_eq!(
+        listed_order(&app, "sort=name", &threads).await?,
+        vec![second, first, third]
+    );
+    assert_eq!(
+        listed_order(&app, "sort=name&pinned_first=true", &threads).await?,
+        vec![third, second, first]
+    );
+    assert_eq!(
+        listed_order(&app, "sort=updated&pinned_first=true", &threads).await?,
+        vec![third, second, first]
+    );
+    // Creation times come from the file system, where it records them.
+    if std::fs::metadata(&paths[0])?.created().is_ok() {
+        assert_eq!(
+            listed_order(&app, "sort=created", &threads).await?,
+            vec![third, second, first]
+        );
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_list_threads_sorts_postgres_threads() -> Result<()> {
+    if !std::env::var("CODEX_ROLLOUT_POSTGRES_URL").is_ok_and(|value| !value.trim().is_empty()) {
+        eprintln!("skipping: CODEX_ROLLOUT_POSTGRES_URL is not set");
+        return Ok(());
+    }
+
+    let server = start_mock_server().await;
+    for n in 0..4 {
+        let response_id = format!("resp-{n}");
+        mount_sse_once(
+            &server,
+            sse(vec![
+                ev_response_created(&response_id),
+                ev_assistant_message(&format!("msg-{n}"), "done"),
+                ev_completed(&response_id),
+            ]),
+        )
+        .await;
+    }
+    let fixture = TestFixture::new().await?;
+    let store = WebStateStore::connect_from_env("test-sort")
+        .await?
+        .map(Arc::new);
+    let state = create_test_state(&fixture, "test-token").with_state_store(store);
+    let app = listing_app(state.clone());
+
+    // Postgres keeps whole seconds, so the turns are a second apart. The
+    // first thread is created first and updated last.
+    let mut threads = Vec::new();
+    for _ in 0..3 {
+        let thread_id = start_mock_provider_thread(&fixture, &state, &server.uri()).await?;
+        run_turn(&app, thread_id).await?;
+        threads.push(thread_id);
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+    }
+    run_turn(&app, threads[0]).await?;
+    let [first, second, third] = [threads[0], threads[1], threads[2]];
+    assert_eq!(
+        update(&app, first, json!({"name": "beta"})).await?,
+        StatusCode::OK
+    );
+    assert_eq!(
+        update(&app, second, json!({"name": "Alpha"})).await?,
+        StatusCode::OK
+    );
+    assert_eq!(
+        update(&app, third, json!({"pinned": true})).await?,
+        StatusCode::OK
+    );
+    assert_eq!(
+        update(&app, ThreadId::new(), json!({"pinned": true})).await?,
+        StatusCode::NOT_FOUND
+    );
+
+    assert_eq!(
+        listed_order(&app, "", &threads).await?,
+        vec![first, third, second]
+    );
+    assert_eq!(
+        listed_order(&app, "sort=created", &threads).await?,
+        vec![third, second, first]
+    );
+    assert_eq!(
+        listed_order(&app, "sort=name", &threads).await?,
+        vec![second, first, third]
+    );
+    assert_eq!(
+        listed_order(&app, "sort=created&pinned_first=true", &threads).await?,
+        vec![third, second, first]
+    );
+    assert_eq!(
+        listed_order(&app, "sort=updated&pinned_first=true", &threads).await?,
+        vec![third, first, second]
+    );
+    Ok(())
+}
+
+/// Sends a turn to `thread_id` and waits for it to complete.
+async fn run_turn(app: &Router, thread_id: ThreadId) -> Result<()> {
+    let mut events = EventReader::open(app, thread_id).await?;
+    let (status, sent) = create(
+        app,
+        &format!("/api/v2/threads/{thread_id}/turns"),
+        json!({ "input": [{ "type": "text", "text": "hello" }] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{sent}");
+    events.next_of("turn/completed").await?;
+    Ok(())
+}
+
+async fn create(app: &Router, uri: &str, body: Value) -> Result<(StatusCode, Value)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+#[tokio::test]
+async fn test_create_thread_applies_and_echoes_overrides() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let script = MockScript::load(
+        &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mock_thread.json"),
+    )?;
+    let server = MockModelServer::start(script).await?;
+    let state = create_test_state(&fixture, "test-token")
+        .with_config_loader(server.config_loader(fixture.codex_home_path()));
+    let app = Router::new()
+        .route("/api/v1/threads", post(handlers::create_thread))
+        .route("/api/v2/threads", post(handlers::threads::create_thread))
+        .with_state(state);
+    let request = json!({
+        "cwd": fixture.codex_home.path(),
+        "approval_policy": "never",
+        "sandbox_mode": "read-only",
+        "model_reasoning_effort": "high",
+        "base_instructions": "Answer in one sentence.",
+    });
+
+    // The v1 and v2 handlers are separate copies; both must apply overrides.
+    for uri in ["/api/v1/threads", "/api/v2/threads"] {
+        let (status, body) = create(&app, uri, request.clone()).await?;
+        assert_eq!(status, StatusCode::OK, "{uri}: {body}");
+        assert_eq!(body["approval_policy"], json!("never"), "{uri}");
+        assert_eq!(body["sandbox_mode"], json!("read-only"), "{uri}");
+        assert_eq!(body["model_reasoning_effort"], json!("high"), "{uri}");
+        assert_eq!(
+            body["base_instructions"],
+            json!("Answer in one sentence."),
+            "{uri}"
+        );
+    }
+
+    for (field, value) in [
+        ("approval_policy", "sometimes"),
+        ("sandbox_mode", "no-sandbox"),
+        ("model_reasoning_effort", "extreme"),
+    ] {
+        for uri in ["/api/v1/threads", "/api/v2/threads"] {
+            let (status, body) = create(&app, uri, json!({ field: value })).await?;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{uri}: {body}");
+            let error = body["error"].as_str().unwrap_or_default();
+            assert!(error.contains(field), "{uri}: {error}");
+        }
+    }
+    Ok(())
+}
+
+async fn delete_thread(app: &Router, thread_id: &str, query: &str) -> Result<StatusCode> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("DELETE")
+                .uri(format!("/api/v2/threads/{thread_id}{query}"))
+                .body(Body::empty())?,
+        )
+        .await?;
+    Ok(response.status())
+}
+
+fn delete_app(state: WebServerState) -> Router {
+    Router::new()
+        .route("/api/v2/threads", post(handlers::threads::create_thread))
+        .route(
+            "/api/v2/threads/{id}",
+            get(handlers::threads::get_thread).delete(handlers::threads::delete_thread),
+        )
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(handlers::turns::send_turn),
+        )
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .route(
+            "/api/v2/threads/{thread_id}/approvals/{approval_id}",
+            post(handlers::approvals::respond_to_approval),
+        )
+        .with_state(state)
+}
+
+#[tokio::test]
+async fn test_delete_thread_removes_rollout_and_pending_approvals() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let codex_home = fixture.codex_home_path();
+    let thread_id = ThreadId::new();
+    let rollout_path = write_rollout(&codex_home, thread_id, &codex_home)?;
+    let state = create_test_state(&fixture, "test-token");
+    let app = delete_app(state.clone());
+
+    state.thread_statuses.apply(
+        thread_id,
+        StatusTransition::TurnStarted {
+            turn_id: "turn-1".to_string(),
+        },
+    );
+    let request = EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
+        call_id: "call-1".to_string(),
+        approval_id: None,
+        turn_id: "turn-1".to_string(),
+        command: vec!["ls".to_string()],
+        cwd: codex_home.clone(),
+        reason: None,
+        network_approval_context: None,
+        proposed_execpolicy_amendment: None,
+        parsed_cmd: vec![],
+    });
+    let registered = register_approval_request(&state, thread_id, &request).await;
+    assert!(registered.is_some());
+
+    // A running turn is only stopped on request.
+    let id = thread_id.to_string();
+    assert_eq!(delete_thread(&app, &id, "").await?, StatusCode::CONFLICT);
+    assert!(rollout_path.exists());
+
+    assert_eq!(
+        delete_thread(&app, &id, "?force=true").await?,
+        StatusCode::OK
+    );
+    assert!(!rollout_path.exists());
+    assert!(state.pending_approvals.lock().await.is_empty());
+    assert_eq!(
+        state.thread_statuses.status(thread_id),
+        ThreadRunStatus::Idle
+    );
+    let (status, _) = get_details(&app, &id).await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    assert_eq!(delete_thread(&app, &id, "").await?, StatusCode::NOT_FOUND);
+    assert_eq!(
+        delete_thread(&app, "not-a-thread", "").await?,
+        StatusCode::BAD_REQUEST
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_delete_thread_removes_postgres_rows() -> Result<()> {
+    if !std::env::var("CODEX_ROLLOUT_POSTGRES_URL").is_ok_and(|value| !value.trim().is_empty()) {
+        eprintln!("skipping: CODEX_ROLLOUT_POSTGRES_URL is not set");
+        return Ok(());
+    }
+
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(
+        r#"
+model = "test-model"
+approval_policy = "on-request"
+sandbox_mode = "workspace-write"
+"#,
+    )?;
+    let script = MockScript::load(
+        &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mock_thread.json"),
+    )?;
+    let server = MockModelServer::start(script).await?;
+    let store = WebStateStore::connect_from_env("test-delete")
+        .await?
+        .map(Arc::new);
+    let state = create_test_state(&fixture, "test-token")
+        .with_config_loader(server.config_loader(fixture.codex_home_path()))
+        .with_state_store(store.clone());
+    let app = delete_app(state);
+
+    let (status, created) = create(
+        &app,
+        "/api/v2/threads",
+        json!({ "cwd": fixture.codex_home.path() }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{created}");
+    let id = created["thread_id"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let thread_id = ThreadId::from_string(&id)?;
+    let mut events = EventReader::open(&app, thread_id).await?;
+    let (status, sent) = create(
+        &app,
+        &format!("/api/v2/threads/{id}/turns"),
+        json!({ "input": [{ "type": "text", "text": "Check the workspace" }] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{sent}");
+    let approval = events
+        .next_of("item/commandExecution/requestApproval")
+        .await?;
+    let approval_id = approval["approvalId"]
+        .as_str()
+        .or(approval["itemId"].as_str())
+        .unwrap_or_default()
+        .to_string();
+    create(
+        &app,
+        &format!("/api/v2/threads/{id}/approvals/{approval_id}"),
+        json!({ "decision": "approve" }),
+    )
+    .await?;
+    events.next_of("turn/completed").await?;
+    assert!(
+        !codex_core::load_postgres_rollout_items(thread_id)
+            .await?
+            .is_empty()
+    );
+
+    assert_eq!(delete_thread(&app, &id, "").await?, StatusCode::OK);
+    assert!(
+        codex_core::load_postgres_rollout_items(thread_id)
+            .await?
+            .is_empty()
+    );
+    let persisted = match &store {
+        Some(store) => store.persisted_threads().await?,
+        None => Default::default(),
+    };
+    assert!(!persisted.contains_key(&thread_id));
+    assert_eq!(delete_thread(&app, &id, "").await?, StatusCode::NOT_FOUND);
+    Ok(())
+}
+
+fn append_rollout_events(path: &Path, events: Vec<EventMsg>) -> Result<()> {
+    append_rollout_items(
+        path,
+        events.into_iter().map(RolloutItem::EventMsg).collect(),
+    )
+}
+
+fn append_rollout_items(path: &Path, items: Vec<RolloutItem>) -> Result<()> {
+    let mut contents = std::fs::read_to_string(path)?;
+    for item in items {
+        let line = RolloutLine {
+            timestamp: "2025-01-02T03:04:06.000Z".to_string(),
+            item,
+        };
+        contents.push_str(&serde_json::to_string(&line)?);
+        contents.push('\n');
+    }
+    std::fs::write(path, contents)?;
+    Ok(())
+}
+
+fn user_message(message: &str) -> EventMsg {
+    EventMsg::UserMessage(UserMessageEvent {
+        message: message.to_string(),
+        images: None,
+        local_images: Vec::new(),
+        text_elements: Vec::new(),
+    })
+}
+
+fn turn_started(turn_id: &str) -> EventMsg {
+    EventMsg::TurnStarted(TurnStartedEvent {
+        turn_id: turn_id.to_string(),
+        model_context_window: None,
+        collaboration_mode_kind: Default::default(),
+    })
+}
+
+async fn get_items(app: &Router, thread_id: &str, query: &str) -> Result<(StatusCode, Value)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/v2/threads/{thread_id}/items{query}"))
+                .body(Body::empty())?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body).unwrap_or(Value::Null)))
+}
+
+#[tokio::test]
+async fn test_thread_items_pages_through_rollout_history_by_turn() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let codex_home = fixture.codex_home_path();
+    let thread_id = ThreadId::new();
+    let rollout_path = write_rollout(&codex_home, thread_id, &codex_home)?;
+    append_rollout_events(
+        &rollout_path,
+        vec![
+            turn_started("turn-1"),
+            user_message("first"),
+            EventMsg::AgentMessage(AgentMessageEvent {
+                message: "one".to_string(),
+                phase: None,
+            }),
+            EventMsg::TurnComplete(TurnCompleteEvent {
+                turn_id: "turn-1".to_string(),
+                last_agent_message: Some("one".to_string()),
+            }),
+            turn_started("turn-2"),
+            user_message("second"),
+        ],
+    )?;
+    let app = Router::new()
+        .route(
+            "/api/v2/threads/{id}/items",
+            get(handlers::threads::list_thread_items),
+        )
+        .with_state(create_test_state(&fixture, "test-token"));
+    let id = thread_id.to_string();
+
+    let (status, page) = get_items(&app, &id, "?limit=2").await?;
+    assert_eq!(status, StatusCode::OK, "{page}");
+    assert_eq!(page["thread_id"], json!(id));
+    let items = page["data"].as_array().cloned().unwrap_or_default();
+    assert_eq!(items.len(), 2, "{page}");
+    assert_eq!(items[0]["turn_id"], json!("turn-1"));
+    assert_eq!(items[0]["item"]["type"], json!("userMessage"));
+    assert_eq!(items[1]["item"]["type"], json!("agentMessage"));
+    assert_eq!(items[1]["item"]["text"], json!("one"));
+    assert_eq!(
+        page["turns"],
+        json!([{ "id": "turn-1", "status": "completed", "error": null, "item_count": 2 }])
+    );
+    assert_eq!(page["next_cursor"], json!("2"));
+
+    // The turn still running when the rollout was written is the last one.
+    let (status, page) = get_items(&app, &id, "?limit=2&cursor=2").await?;
+    assert_eq!(status, StatusCode::OK, "{page}");
+    assert_eq!(page["data"][0]["turn_id"], json!("turn-2"));
+    assert_eq!(page["data"][0]["item"]["type"], json!("userMessage"));
+    assert_eq!(page["turns"][0]["status"], json!("inProgress"));
+    assert_eq!(page["next_cursor"], Value::Null);
+
+    let (status, _) = get_items(&app, &id, "?cursor=next").await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = get_items(&app, &id, "?limit=0").await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = get_items(&app, &ThreadId::new().to_string(), "").await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    Ok(())
+}
+
+async fn export(
+    app: &Router,
+    thread_id: &str,
+    query: &str,
+) -> Result<(StatusCode, String, String)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/v2/threads/{thread_id}/export{query}"))
+                .body(Body::empty())?,
+        )
+        .await?;
+    let status = response.status();
+    let disposition = response
+        .headers()
+        .get("content-disposition")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, disposition, String::from_utf8(body.to_vec())?))
+}
+
+#[tokio::test]
+async fn test_export_streams_rollout_and_compact_drops_response_items() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let codex_home = fixture.codex_home_path();
+    let thread_id = ThreadId::new();
+    let rollout_path = write_rollout(&codex_home, thread_id, &codex_home)?;
+    append_rollout_events(
+        &rollout_path,
+        vec![turn_started("turn-1"), user_message("hi")],
+    )?;
+    let mut contents = std::fs::read_to_string(&rollout_path)?;
+    contents.push_str(
+        r#"{"timestamp":"2025-01-02T03:04:07.000Z","type":"response_item","payload":{"type":"message","role":"assistant","content":[{"type":"output_text","text":"hello"}]}}"#,
+    );
+    contents.push('\n');
+    std::fs::write(&rollout_path, &contents)?;
+    let app = Router::new()
+        .route(
+            "/api/v2/threads/{id}/export",
+            get(handlers::threads::export_thread),
+        )
+        .with_state(create_test_state(&fixture, "test-token"));
+    let id = thread_id.to_string();
+
+    let (status, disposition, body) = export(&app, &id, "").await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(disposition, format!("attachment; filename=\"{id}.jsonl\""));
+    assert_eq!(body, contents);
+
+    let (status, _, body) = export(&app, &id, "?items=compact").await?;
+    assert_eq!(status, StatusCode::OK);
+    let kept: Vec<&str> = contents
+        .lines()
+        .filter(|line| !line.contains(r#""type":"response_item""#))
+        .collect();
+    assert_eq!(body.lines().collect::<Vec<_>>(), kept);
+    assert_eq!(kept.len(), contents.lines().count() - 1);
+
+    let (status, _, _) = export(&app, &ThreadId::new().to_string(), "").await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    Ok(())
+}
+
+async fn import(app: &Router, query: &str, jsonl: &str) -> Result<(StatusCode, Value)> {
+    let boundary = "codex-import-boundary";
+    let body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"thread.jsonl\"\r\nContent-Type: application/x-ndjson\r\n\r\n{jsonl}\r\n--{boundary}--\r\n"
+    );
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/v2/threads/import{query}"))
+                .header(
+                    "content-type",
+                    format!("multipart/form-data; boundary={boundary}"),
+                )
+                .body(Body::from(body))?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+#[tokio::test]
+async fn test_import_writes_exported_rollout_under_a_new_thread_id() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let codex_home = fixture.codex_home_path();
+    let source_id = ThreadId::new();
+    let rollout_path = write_rollout(&codex_home, source_id, &codex_home)?;
+    append_rollout_events(
+        &rollout_path,
+        vec![turn_started("turn-1"), user_message("hi")],
+    )?;
+    let contents = std::fs::read_to_string(&rollout_path)?;
+    let state = create_test_state(&fixture, "test-token");
+    let app = Router::new()
+        .route(
+            "/api/v2/threads/import",
+            post(handlers::threads::import_thread),
+        )
+        .with_state(state.clone());
+
+    let (status, imported) = import(&app, "?resume=true", &contents).await?;
+    assert_eq!(status, StatusCode::OK, "{imported}");
+    assert_eq!(imported["source_thread_id"], json!(source_id.to_string()));
+    assert_eq!(imported["line_count"], json!(contents.lines().count()));
+    assert_eq!(imported["resumed"], json!(true));
+    let new_id = imported["thread_id"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    assert_ne!(new_id, source_id.to_string());
+    assert!(
+        state
+            .thread_manager
+            .get_thread(ThreadId::from_string(&new_id)?)
+            .await
+            .is_ok()
+    );
+
+    let new_path = codex_core::find_thread_path_by_id_str(&codex_home, &new_id)
+        .await?
+        .expect("imported rollout");
+    let written = std::fs::read_to_string(new_path)?;
+    let first: RolloutLine = serde_json::from_str(written.lines().next().unwrap_or_default())?;
+    let RolloutItem::SessionMeta(session_meta) = first.item else {
+        panic!("first line is not session meta");
+    };
+    assert_eq!(session_meta.meta.id.to_string(), new_id);
+    assert_eq!(
+        written.lines().skip(1).collect::<Vec<_>>(),
+        contents.lines().skip(1).collect::<Vec<_>>()
+    );
+    // The staged upload is gone.
+    assert!(
+        std::fs::read_dir(&codex_home)?
+            .filter_map(|entry| entry.ok())
+            .all(|entry| !entry.file_name().to_string_lossy().starts_with(".import-"))
+    );
+    Ok(())
+}
+
+fn count_rollouts(dir: &Path) -> Result<usize> {
+    let mut count = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            count += count_rollouts(&path)?;
+        } else if path.extension().is_some_and(|ext| ext == "jsonl") {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+#[tokio::test]
+async fn test_import_rejects_invalid_rollouts_naming_the_line() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let codex_home = fixture.codex_home_path();
+    let rollout_path = write_rollout(&codex_home, ThreadId::new(), &codex_home)?;
+    let contents = std::fs::read_to_string(&rollout_path)?;
+    let lines: Vec<&str> = contents.lines().collect();
+    let sessions_before = count_rollouts(&codex_home.join("sessions"))?;
+    let state = create_test_state(&fixture, "test-token");
+    let app = |state: WebServerState| {
+        Router::new()
+            .route(
+                "/api/v2/threads/import",
+                post(handlers::threads::import_thread),
+            )
+            .with_state(state)
+    };
+
+    // The first item is a turn context, not the session meta.
+    let (status, error) = import(&app(state.clone()), "", &lines[1..].join("\n")).await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{error}");
+    assert_eq!(error["code"], json!("invalid_rollout"));
+    assert_eq!(error["line"], json!(1));
+
+    let garbage = format!("{}\n{}\nnot json\n", lines[0], lines[1]);
+    let (status, error) = import(&app(state.clone()), "", &garbage).await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{error}");
+    assert_eq!(error["line"], json!(3));
+
+    let small = state.with_import_limits(ImportLimits { max_bytes: 16 });
+    let (status, error) = import(&app(small), "", &contents).await?;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE, "{error}");
+    assert_eq!(error["code"], json!("file_too_large"));
+
+    let sessions_after = count_rollouts(&codex_home.join("sessions"))?;
+    assert_eq!(sessions_after, sessions_before);
+    Ok(())
+}
+
+fn resume_app(state: WebServerState) -> Router {
+    Router::new()
+        .route("/api/v2/threads", post(handlers::threads::create_thread))
+        .route("/api/v2/threads/{id}", get(handlers::threads::get_thread))
+        .route(
+            "/api/v2/threads/{id}/resume",
+            post(handlers::threads::resume_thread),
+        )
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(handlers::turns::send_turn),
+        )
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .route(
+            "/api/v2/threads/{thread_id}/approvals/{approval_id}",
+            post(handlers::approvals::respond_to_approval),
+        )
+        .with_state(state)
+}
+
+#[tokio::test]
+async fn test_resume_applies_overrides_and_reports_already_active() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let codex_home = fixture.codex_home_path();
+    let thread_id = ThreadId::new();
+    write_rollout(&codex_home, thread_id, &codex_home)?;
+    let app = resume_app(create_test_state(&fixture, "test-token"));
+    let uri = format!("/api/v2/threads/{thread_id}/resume");
+
+    let (status, resumed) = create(
+        &app,
+        &uri,
+        json!({ "model": "resume-model", "skip_validation": true }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{resumed}");
+    assert_eq!(resumed["thread_id"], json!(thread_id.to_string()));
+    assert_eq!(resumed["already_active"], json!(false));
+    assert_eq!(resumed["model"], json!("resume-model"));
+    let (status, details) = get_details(&app, &thread_id.to_string()).await?;
+    assert_eq!(status, StatusCode::OK, "{details}");
+    assert_eq!(details["active"], json!(true));
+    assert_eq!(details["model"], json!("resume-model"));
+
+    // Resuming again is a no-op: the overrides are ignored, and the body may
+    // be left out entirely.
+    let (status, again) = create(
+        &app,
+        &uri,
+        json!({ "model": "other-model", "skip_validation": true }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{again}");
+    assert_eq!(again["already_active"], json!(true));
+    assert_eq!(again["model"], json!("resume-model"));
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(&uri)
+                .body(Body::empty())?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+    assert_eq!(body["already_active"], json!(true));
+
+    // Unknown models are rejected before anything is loaded.
+    let (status, error) = create(
+        &app,
+        &format!("/api/v2/threads/{}/resume", ThreadId::new()),
+        json!({ "model": "no-such-model" }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{error}");
+    assert_eq!(error["code"], json!("model_not_found"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_resume_denies_an_approval_left_pending_by_a_restart() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(
+        r#"
+model = "test-model"
+approval_policy = "on-request"
+sandbox_mode = "workspace-write"
+"#,
+    )?;
+    let codex_home = fixture.codex_home_path();
+    let script = MockScript::load(
+        &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mock_thread.json"),
+    )?;
+    let server = MockModelServer::start(script).await?;
+    let state = create_test_state(&fixture, "test-token")
+        .with_config_loader(server.config_loader(codex_home.clone()));
+    let app = resume_app(state);
+
+    // The server stopped while the shell call of turn-2 waited for approval.
+    let thread_id = ThreadId::new();
+    let path = write_rollout(&codex_home, thread_id, &codex_home)?;
+    append_rollout_items(
+        &path,
+        vec![
+            RolloutItem::EventMsg(turn_started("turn-2")),
+            RolloutItem::EventMsg(user_message("Clean the workspace")),
+            RolloutItem::ResponseItem(ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: json!({ "command": ["rm", "-rf", "build"] }).to_string(),
+                call_id: "call-pending".to_string(),
+            }),
+            RolloutItem::EventMsg(EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
+                call_id: "call-pending".to_string(),
+                approval_id: None,
+                turn_id: "turn-2".to_string(),
+                command: vec!["rm".to_string(), "-rf".to_string(), "build".to_string()],
+                cwd: codex_home.clone(),
+                reason: None,
+                network_approval_context: None,
+                proposed_execpolicy_amendment: None,
+                parsed_cmd: vec![],
+            })),
+        ],
+    )?;
+
+    let id = thread_id.to_string();
+    let (status, resumed) =
+        create(&app, &format!("/api/v2/threads/{id}/resume"), json!({})).await?;
+    assert_eq!(status, StatusCode::OK, "{resumed}");
+    let warnings = resumed["warnings"].as_array().cloned().unwrap_or_default();
+    assert_eq!(warnings.len(), 1, "{resumed}");
+    assert!(
+        warnings[0]
+            .as_str()
+            .unwrap_or_default()
+            .contains("call-pending"),
+        "{resumed}"
+    );
+
+    // The thread takes a new turn and runs it to completion.
+    let mut events = EventReader::open(&app, thread_id).await?;
+    let (status, sent) = create(
+        &app,
+        &format!("/api/v2/threads/{id}/turns"),
+        json!({ "input": [{ "type": "text", "text": "Check the workspace" }] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{sent}");
+    let approval = events
+        .next_of("item/commandExecution/requestApproval")
+        .await?;
+    let approval_id = approval["approvalId"]
+        .as_str()
+        .or(approval["itemId"].as_str())
+        .unwrap_or_default()
+        .to_string();
+    create(
+        &app,
+        &format!("/api/v2/threads/{id}/approvals/{approval_id}"),
+        json!({ "decision": "approve" }),
+    )
+    .await?;
+    let completed = events.next_of("turn/completed").await?;
+    assert_eq!(completed["turn"]["status"], json!("completed"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_resume_from_postgres_applies_overrides() -> Result<()> {
+    if !std::env::var("CODEX_ROLLOUT_POSTGRES_URL").is_ok_and(|value| !value.trim().is_empty()) {
+        eprintln!("skipping: CODEX_ROLLOUT_POSTGRES_URL is not set");
+        return Ok(());
+    }
+
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(
+        r#"
+model = "test-model"
+approval_policy = "on-request"
+sandbox_mode = "workspace-write"
+"#,
+    )?;
+    let script = MockScript::load(
+        &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mock_thread.json"),
+    )?;
+    let server = MockModelServer::start(script).await?;
+    let state = create_test_state(&fixture, "test-token")
+        .with_config_loader(server.config_loader(fixture.codex_home_path()));
+    let app = resume_app(state.clone());
+
+    let (status, created) = create(
+        &app,
+        "/api/v2/threads",
+        json!({ "cwd": fixture.codex_home.path() }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{created}");
+    let id = created["thread_id"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let thread_id = ThreadId::from_string(&id)?;
+    let mut events = EventReader::open(&app, thread_id).await?;
+    let (status, sent) = create(
+        &app,
+        &format!("/api/v2/threads/{id}/turns"),
+        json!({ "input": [{ "type": "text", "text": "Check the workspace" }] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{sent}");
+    let approval = events
+        .next_of("item/commandExecution/requestApproval")
+        .await?;
+    let approval_id = approval["approvalId"]
+        .as_str()
+        .or(approval["itemId"].as_str())
+        .unwrap_or_default()
+        .to_string();
+    create(
+        &app,
+        &format!("/api/v2/threads/{id}/approvals/{approval_id}"),
+        json!({ "decision": "approve" }),
+    )
+    .await?;
+    events.next_of("turn/completed").await?;
+
+    // Unload the thread so the resume has to read its history back.
+    if let Some(thread) = state.thread_manager.remove_thread(&thread_id).await {
+        thread.submit(Op::Shutdown).await?;
+    }
+    let (status, resumed) = create(
+        &app,
+        &format!("/api/v2/threads/{id}/resume"),
+        json!({ "model": "resume-model", "skip_validation": true }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{resumed}");
+    assert_eq!(resumed["thread_id"], json!(id));
+    assert_eq!(resumed["already_active"], json!(false));
+    assert_eq!(resumed["model"], json!("resume-model"));
+    assert!(state.thread_manager.get_thread(thread_id).await.is_ok());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_batch_status_reports_each_thread_and_unknown_ids() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let codex_home = fixture.codex_home_path();
+    let state = create_test_state(&fixture, "test-token");
+    let app = Router::new()
+        .route(
+            "/api/v2/threads/{id}/resume",
+            post(handlers::threads::resume_thread),
+        )
+        .route(
+            "/api/v2/threads/status",
+            post(handlers::threads::batch_thread_status),
+        )
+        .with_state(state.clone());
+
+    // Enough turn output after the token count that the tail is read in
+    // several chunks.
+    let completed = ThreadId::new();
+    let path = write_rollout(&codex_home, completed, &codex_home)?;
+    let mut events = vec![turn_started("turn-2")];
+    events.extend((0..1000).map(|i| user_message(&format!("{i:0>200}"))));
+    events.push(EventMsg::TurnComplete(TurnCompleteEvent {
+        turn_id: "turn-2".to_string(),
+        last_agent_message: None,
+    }));
+    append_rollout_events(&path, events)?;
+    codex_core::append_thread_name(&codex_home, completed, "Big turn").await?;
+
+    let unfinished = ThreadId::new();
+    let path = write_rollout(&codex_home, unfinished, &codex_home)?;
+    append_rollout_events(&path, vec![turn_started("turn-2"), user_message("hi")])?;
+
+    let running = ThreadId::new();
+    write_rollout(&codex_home, running, &codex_home)?;
+    let (status, resumed) = create(
+        &app,
+        &format!("/api/v2/threads/{running}/resume"),
+        json!({}),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{resumed}");
+    state.thread_statuses.apply(
+        running,
+        StatusTransition::TurnStarted {
+            turn_id: "turn-2".to_string(),
+        },
+    );
+
+    let unknown = ThreadId::new().to_string();
+    let (status, body) = create(
+        &app,
+        "/api/v2/threads/status",
+        json!({ "thread_ids": [completed, unfinished, running, unknown, "not-a-thread"] }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    let data = body["data"].as_array().cloned().unwrap_or_default();
+    assert_eq!(data.len(), 5, "{body}");
+
+    assert_eq!(data[0]["thread_id"], json!(completed.to_string()));
+    assert_eq!(data[0]["status"], json!("persisted"));
+    assert_eq!(data[0]["active"], json!(false));
+    assert_eq!(data[0]["turn_running"], json!(false));
+    assert_eq!(data[0]["last_turn"], json!("completed"));
+    assert_eq!(data[0]["token_usage"]["totalTokens"], json!(1500));
+    assert_eq!(data[0]["name"], json!("Big turn"));
+
+    assert_eq!(data[1]["status"], json!("persisted"));
+    assert_eq!(data[1]["last_turn"], json!("unfinished"));
+    assert_eq!(data[1]["token_usage"]["totalTokens"], json!(1500));
+    assert_eq!(data[1]["name"], Value::Null);
+
+    assert_eq!(data[2]["status"], json!("active"));
+    assert_eq!(data[2]["active"], json!(true));
+    assert_eq!(data[2]["turn_running"], json!(true));
+    assert_eq!(data[2]["last_turn"], json!("in_progress"));
+
+    for (entry, id) in data[3..].iter().zip([unknown.as_str(), "not-a-thread"]) {
+        assert_eq!(entry["thread_id"], json!(id));
+        assert_eq!(entry["status"], json!("not_found"));
+        assert_eq!(entry["last_turn"], Value::Null);
+    }
+
+    let too_many: Vec<String> = (0..101).map(|_| ThreadId::new().to_string()).collect();
+    let (status, body) = create(
+        &app,
+        "/api/v2/threads/status",
+        json!({ "thread_ids": too_many }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    Ok(())
+}
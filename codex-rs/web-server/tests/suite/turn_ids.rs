@@ -0,0 +1,766 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::routing::post;
+use codex_core::config::Config;
+use codex_core::config::ConfigBuilder;
+use codex_protocol::ThreadId;
+use codex_web_server::attachments;
+use codex_web_server::handlers;
+use codex_web_server::handlers::threads::start_configured_thread;
+use codex_web_server::state::WebServerState;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_response_created;
+use core_test_support::responses::mount_response_once;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::sse_response;
+use core_test_support::responses::start_mock_server;
+use serde_json::Value;
+use serde_json::json;
+use std::time::Duration;
+use tower::ServiceExt;
+use wiremock::MockServer;
+
+use crate::common::EventReader;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+/// Config pointing the thread at the mock Responses API.
+async fn mock_provider_config(fixture: &TestFixture, server: &MockServer) -> Result<Config> {
+    fixture.create_test_config(&format!(
+        r#"
+model = "test-model"
+approval_policy = "never"
+sandbox_mode = "read-only"
+model_provider = "mock"
+
+[model_providers.mock]
+name = "Mock provider"
+base_url = "{}/v1"
+wire_api = "responses"
+request_max_retries = 0
+stream_max_retries = 0
+"#,
+        server.uri()
+    ))?;
+    let mut config = ConfigBuilder::default()
+        .codex_home(fixture.codex_home_path())
+        .build()
+        .await?;
+    config.cwd = fixture.codex_home_path();
+    Ok(config)
+}
+
+fn turns_router(state: WebServerState) -> Router {
+    Router::new()
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(handlers::turns::send_turn),
+        )
+        .route("/api/v1/threads/{id}/turns", post(handlers::send_turn))
+        .route(
+            "/api/v2/threads/{id}/turns/steer",
+            post(handlers::turns::steer_turn),
+        )
+        .route(
+            "/api/v2/threads/{id}/turns/interrupt",
+            post(handlers::turns::interrupt_turn),
+        )
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .with_state(state)
+}
+
+fn reply(response_id: &str, text: &str) -> String {
+    sse(vec![
+        ev_response_created(response_id),
+        ev_assistant_message(&format!("{response_id}-msg"), text),
+        ev_completed(response_id),
+    ])
+}
+
+async fn send_turn(app: &Router, uri: &str, text: &str) -> Result<Value> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(
+                    json!({"input": [{"type": "text", "text": text}]}).to_string(),
+                ))?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    Ok(serde_json::from_slice(
+        &to_bytes(response.into_body(), usize::MAX).await?,
+    )?)
+}
+
+#[tokio::test]
+async fn test_send_turn_returns_id_carried_by_turn_events() -> Result<()> {
+    let server = start_mock_server().await;
+    mount_sse_once(&server, reply("resp-1", "first")).await;
+    mount_sse_once(&server, reply("resp-2", "second")).await;
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let config = mock_provider_config(&fixture, &server).await?;
+    let thread_id = start_configured_thread(&state, config).await?.thread_id;
+    let app = turns_router(state);
+    let mut events = EventReader::open(&app, thread_id).await?;
+
+    for uri in [
+        format!("/api/v2/threads/{thread_id}/turns"),
+        format!("/api/v1/threads/{thread_id}/turns"),
+    ] {
+        let sent = send_turn(&app, &uri, "hello").await?;
+        assert_eq!(sent["steered"], json!(false));
+
+        let started = events.next_of("turn/started").await?;
+        let completed = events.next_of("turn/completed").await?;
+        assert_eq!(started["turn"]["id"], sent["turn_id"], "{uri}");
+        assert_eq!(completed["turn"]["id"], sent["turn_id"], "{uri}");
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_input_during_active_turn_reports_the_active_turn_id() -> Result<()> {
+    let server = start_mock_server().await;
+    // Hold the first model response open so the second input arrives
+    // mid-turn.
+    mount_response_once(
+        &server,
+        sse_response(reply("resp-1", "first")).set_delay(Duration::from_millis(500)),
+    )
+    .await;
+    mount_sse_once(&server, reply("resp-2", "second")).await;
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let config = mock_provider_config(&fixture, &server).await?;
+    let thread_id = start_configured_thread(&state, config).await?.thread_id;
+    let app = turns_router(state);
+    let mut events = EventReader::open(&app, thread_id).await?;
+    let uri = format!("/api/v2/threads/{thread_id}/turns");
+
+    let first = send_turn(&app, &uri, "start").await?;
+    let started = events.next_of("turn/started").await?;
+    // v2 refuses input for a busy thread and names the running turn.
+    let (status, rejected) = post_turn(
+        &app,
+        &uri,
+        json!({"input": [{"type": "text", "text": "and also this"}]}),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(rejected["code"], json!("turn_in_progress"));
+    assert_eq!(rejected["active_turn_id"], first["turn_id"]);
+    // v1 still steers it into the running turn.
+    let second = send_turn(
+        &app,
+        &format!("/api/v1/threads/{thread_id}/turns"),
+        "and also this",
+    )
+    .await?;
+    let completed = events.next_of("turn/completed").await?;
+
+    assert_eq!(first["steered"], json!(false));
+    assert_eq!(second["steered"], json!(true));
+    assert_eq!(second["turn_id"], first["turn_id"]);
+    assert_eq!(started["turn"]["id"], first["turn_id"]);
+    assert_eq!(completed["turn"]["id"], second["turn_id"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fork_keeps_history_up_to_the_requested_turn() -> Result<()> {
+    let server = start_mock_server().await;
+    mount_sse_once(&server, reply("resp-1", "first")).await;
+    mount_sse_once(&server, reply("resp-2", "second")).await;
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let config = mock_provider_config(&fixture, &server).await?;
+    let thread_id = start_configured_thread(&state, config).await?.thread_id;
+    let app = turns_router(state.clone()).merge(
+        Router::new()
+            .route(
+                "/api/v2/threads/{id}/fork",
+                post(handlers::threads::fork_thread),
+            )
+            .with_state(state),
+    );
+    let mut events = EventReader::open(&app, thread_id).await?;
+    let uri = format!("/api/v2/threads/{thread_id}/turns");
+
+    let first = send_turn(&app, &uri, "one").await?;
+    events.next_of("turn/completed").await?;
+    let second = send_turn(&app, &uri, "two").await?;
+    events.next_of("turn/completed").await?;
+
+    let fork = |body: Value| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri(format!("/api/v2/threads/{thread_id}/fork"))
+                        .header("content-type", "application/json")
+                        .body(Body::from(body.to_string()))?,
+                )
+                .await?;
+            let status = response.status();
+            let body: Value =
+                serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+            anyhow::Ok((status, body))
+        }
+    };
+
+    let (status, body) = fork(json!({ "turn_id": first["turn_id"] })).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["forked_at_turn_id"], first["turn_id"]);
+    assert_ne!(body["new_thread_id"], json!(thread_id.to_string()));
+
+    let (status, body) = fork(json!({})).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["forked_at_turn_id"], second["turn_id"]);
+
+    let (status, body) = fork(json!({ "turn_id": "no-such-turn" })).await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_fork_into_another_cwd_runs_turns_there() -> Result<()> {
+    let server = start_mock_server().await;
+    mount_sse_once(&server, reply("resp-1", "first")).await;
+    let forked_turn = mount_sse_once(&server, reply("resp-2", "second")).await;
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let config = mock_provider_config(&fixture, &server).await?;
+    let source_cwd = config.cwd.clone();
+    let thread_id = start_configured_thread(&state, config).await?.thread_id;
+    let app = turns_router(state.clone()).merge(
+        Router::new()
+            .route(
+                "/api/v2/threads/{id}/fork",
+                post(handlers::threads::fork_thread),
+            )
+            .with_state(state.clone()),
+    );
+    let mut events = EventReader::open(&app, thread_id).await?;
+    send_turn(&app, &format!("/api/v2/threads/{thread_id}/turns"), "one").await?;
+    events.next_of("turn/completed").await?;
+
+    let checkout = tempfile::TempDir::new()?;
+    let new_cwd = checkout.path().canonicalize()?;
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(format!("/api/v2/threads/{thread_id}/fork"))
+                .header("content-type", "application/json")
+                .body(Body::from(json!({ "cwd": new_cwd }).to_string()))?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+    assert_eq!(body["source_cwd"], json!(source_cwd));
+    assert_eq!(body["cwd"], json!(new_cwd));
+    let fork_id = ThreadId::from_string(body["new_thread_id"].as_str().unwrap_or_default())?;
+    let fork = state.thread_manager.get_thread(fork_id).await?;
+    assert_eq!(fork.config_snapshot().await.cwd, new_cwd);
+
+    let mut fork_events = EventReader::open(&app, fork_id).await?;
+    send_turn(&app, &format!("/api/v2/threads/{fork_id}/turns"), "two").await?;
+    fork_events.next_of("turn/completed").await?;
+    let request = forked_turn.single_request().body_json().to_string();
+    assert!(
+        request.contains(&format!("<cwd>{}</cwd>", new_cwd.display())),
+        "{request}"
+    );
+    // The model is told where the earlier conversation's files went.
+    assert!(
+        request.contains(&format!(
+            "whose working directory was {}",
+            source_cwd.display()
+        )),
+        "{request}"
+    );
+
+    for (cwd, expected) in [
+        (json!("relative/dir"), StatusCode::BAD_REQUEST),
+        (
+            json!(checkout.path().join("missing")),
+            StatusCode::BAD_REQUEST,
+        ),
+    ] {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri(format!("/api/v2/threads/{thread_id}/fork"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(json!({ "cwd": cwd }).to_string()))?,
+            )
+            .await?;
+        assert_eq!(response.status(), expected, "{cwd}");
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_v1_and_v2_turns_share_attachment_checks() -> Result<()> {
+    let server = start_mock_server().await;
+    mount_sse_once(&server, reply("resp-1", "first")).await;
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let config = mock_provider_config(&fixture, &server).await?;
+    let thread_id = start_configured_thread(&state, config).await?.thread_id;
+    let app = turns_router(state);
+    // Binary data is neither an image nor text.
+    let binary = uuid::Uuid::new_v4().to_string();
+    let dir = fixture.attachments_dir.path();
+    std::fs::write(dir.join(&binary), [0xff, 0xfe, 0x00, 0x9c])?;
+    std::fs::write(
+        dir.join(format!("{binary}.json")),
+        json!({"id": binary, "filename": "blob.bin", "mime_type": "application/octet-stream", "size": 4})
+            .to_string(),
+    )?;
+
+    for version in ["v1", "v2"] {
+        let uri = format!("/api/{version}/threads/{thread_id}/turns");
+        for (attachment_id, expected) in [
+            ("not-a-uuid", StatusCode::BAD_REQUEST),
+            (
+                "019bcfb9-4ea6-72e0-b43d-6b7e26ff0daf",
+                StatusCode::NOT_FOUND,
+            ),
+            (binary.as_str(), StatusCode::BAD_REQUEST),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri(&uri)
+                        .header("content-type", "application/json")
+                        .body(Body::from(
+                            json!({"input": [{"type": "attachment", "attachment_id": attachment_id}]})
+                                .to_string(),
+                        ))?,
+                )
+                .await?;
+            assert_eq!(response.status(), expected, "{uri} {attachment_id}");
+        }
+        // Inline images must be an image type and valid base64.
+        for (data, mime_type) in [("not base64!", "image/png"), ("aGVsbG8=", "text/plain")] {
+            let image = json!({"type": "image", "data": data, "mime_type": mime_type});
+            let (status, body) = post_turn(&app, &uri, json!({"input": [image]})).await?;
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{uri} {mime_type} {body}");
+        }
+    }
+
+    // The v1 response keeps its own schema: no queue fields.
+    let mut events = EventReader::open(&app, thread_id).await?;
+    let sent = send_turn(&app, &format!("/api/v1/threads/{thread_id}/turns"), "hi").await?;
+    assert!(sent["turn_id"].is_string(), "{sent}");
+    assert!(sent.get("queue_entry_id").is_none(), "{sent}");
+    events.next_of("turn/completed").await?;
+    Ok(())
+}
+
+async fn post_turn(app: &Router, uri: &str, body: Value) -> Result<(StatusCode, Value)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri(uri)
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+#[tokio::test]
+async fn test_turn_overrides_reach_the_model_request() -> Result<()> {
+    let server = start_mock_server().await;
+    // Hold the first response open so the third request arrives mid-turn.
+    let first = mount_response_once(
+        &server,
+        sse_response(reply("resp-1", "first")).set_delay(Duration::from_millis(500)),
+    )
+    .await;
+    let second = mount_sse_once(&server, reply("resp-2", "second")).await;
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let config = mock_provider_config(&fixture, &server).await?;
+    let thread_id = start_configured_thread(&state, config).await?.thread_id;
+    let app = turns_router(state);
+    let mut events = EventReader::open(&app, thread_id).await?;
+    let uri = format!("/api/v2/threads/{thread_id}/turns");
+    let input = json!([{"type": "text", "text": "hello"}]);
+
+    let (status, body) = post_turn(
+        &app,
+        &uri,
+        json!({"input": input, "model": "no-such-model"}),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["code"], json!("model_not_found"));
+
+    let (status, body) =
+        post_turn(&app, &uri, json!({"input": input, "cwd": "relative/dir"})).await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+    let (status, sent) = post_turn(&app, &uri, json!({"input": input})).await?;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(sent["model"], json!("test-model"));
+    events.next_of("turn/started").await?;
+
+    // Overrides cannot steer into the running turn.
+    let (status, body) = post_turn(
+        &app,
+        &uri,
+        json!({"input": input, "model": "other-model", "skip_validation": true}),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], json!("turn_in_progress"));
+    events.next_of("turn/completed").await?;
+
+    let (status, sent) = post_turn(
+        &app,
+        &uri,
+        json!({
+            "input": input,
+            "model": "other-model",
+            "effort": "high",
+            "skip_validation": true,
+        }),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{sent}");
+    assert_eq!(sent["model"], json!("other-model"));
+    assert_eq!(sent["steered"], json!(false));
+    events.next_of("turn/completed").await?;
+
+    assert_eq!(
+        first.single_request().body_json()["model"],
+        json!("test-model")
+    );
+    assert_eq!(
+        second.single_request().body_json()["model"],
+        json!("other-model")
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_output_schema_reaches_the_model_request() -> Result<()> {
+    let server = start_mock_server().await;
+    let response = mount_sse_once(&server, reply("resp-1", "{\"summary\":\"done\"}")).await;
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let config = mock_provider_config(&fixture, &server).await?;
+    let thread_id = start_configured_thread(&state, config).await?.thread_id;
+    let app = turns_router(state);
+    let mut events = EventReader::open(&app, thread_id).await?;
+    let uri = format!("/api/v2/threads/{thread_id}/turns");
+    let input = json!([{"type": "text", "text": "summarize"}]);
+
+    let (status, body) = post_turn(
+        &app,
+        &uri,
+        json!({"input": input, "output_schema": ["not", "an", "object"]}),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{body}");
+
+    let schema = json!({
+        "type": "object",
+        "properties": { "summary": { "type": "string" } },
+        "required": ["summary"],
+        "additionalProperties": false,
+    });
+    let (status, sent) =
+        post_turn(&app, &uri, json!({"input": input, "output_schema": schema})).await?;
+    assert_eq!(status, StatusCode::OK, "{sent}");
+    events.next_of("turn/completed").await?;
+
+    let request = response.single_request().body_json();
+    assert_eq!(request["text"]["format"]["type"], json!("json_schema"));
+    assert_eq!(request["text"]["format"]["schema"], schema);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_interrupt_reports_the_turn_it_stopped() -> Result<()> {
+    let server = start_mock_server().await;
+    // Hold the response open so the turn is still running when interrupted.
+    mount_response_once(
+        &server,
+        sse_response(reply("resp-1", "first")).set_delay(Duration::from_secs(30)),
+    )
+    .await;
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let config = mock_provider_config(&fixture, &server).await?;
+    let thread_id = start_configured_thread(&state, config).await?.thread_id;
+    let app = turns_router(state);
+    let mut events = EventReader::open(&app, thread_id).await?;
+    let interrupt_uri = format!("/api/v2/threads/{thread_id}/turns/interrupt");
+
+    // Nothing is running yet.
+    let (status, body) = post_turn(&app, &interrupt_uri, json!({})).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["interrupted_turn_id"], Value::Null);
+
+    let sent = send_turn(&app, &format!("/api/v2/threads/{thread_id}/turns"), "hello").await?;
+    events.next_of("turn/started").await?;
+
+    let (status, body) =
+        post_turn(&app, &interrupt_uri, json!({"turn_id": "some-other-turn"})).await?;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], json!("turn_mismatch"));
+    assert_eq!(body["active_turn_id"], sent["turn_id"]);
+
+    let (status, body) =
+        post_turn(&app, &interrupt_uri, json!({"turn_id": sent["turn_id"]})).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["interrupted_turn_id"], sent["turn_id"]);
+    let completed = events.next_of("turn/completed").await?;
+    assert_eq!(completed["turn"]["status"], json!("interrupted"));
+
+    let (status, body) = post_turn(&app, &interrupt_uri, json!({})).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["interrupted_turn_id"], Value::Null);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_steer_adds_input_to_the_running_turn() -> Result<()> {
+    let server = start_mock_server().await;
+    // Hold the first response open so the input arrives mid-turn.
+    mount_response_once(
+        &server,
+        sse_response(reply("resp-1", "first")).set_delay(Duration::from_millis(500)),
+    )
+    .await;
+    let follow_up = mount_sse_once(&server, reply("resp-2", "second")).await;
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let config = mock_provider_config(&fixture, &server).await?;
+    let thread_id = start_configured_thread(&state, config).await?.thread_id;
+    let app = turns_router(state);
+    let mut events = EventReader::open(&app, thread_id).await?;
+    let steer_uri = format!("/api/v2/threads/{thread_id}/turns/steer");
+    let steer = |turn_id: Value| {
+        json!({
+            "input": [{"type": "text", "text": "also check the tests"}],
+            "turn_id": turn_id,
+        })
+    };
+
+    let (status, body) = post_turn(&app, &steer_uri, steer(Value::Null)).await?;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], json!("no_active_turn"));
+
+    let sent = send_turn(&app, &format!("/api/v2/threads/{thread_id}/turns"), "hello").await?;
+    events.next_of("turn/started").await?;
+
+    let (status, body) = post_turn(&app, &steer_uri, steer(json!("some-other-turn"))).await?;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(body["code"], json!("turn_mismatch"));
+    assert_eq!(body["active_turn_id"], sent["turn_id"]);
+
+    let (status, body) = post_turn(&app, &steer_uri, steer(sent["turn_id"].clone())).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["turn_id"], sent["turn_id"]);
+
+    let completed = events.next_of("turn/completed").await?;
+    assert_eq!(completed["turn"]["id"], sent["turn_id"]);
+    assert_eq!(completed["turn"]["status"], json!("completed"));
+    // The steered input reached the model within the same turn.
+    let request = follow_up.single_request().body_json();
+    assert!(request.to_string().contains("also check the tests"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rollback_drops_the_last_turns_and_notifies_streams() -> Result<()> {
+    let server = start_mock_server().await;
+    for id in ["resp-1", "resp-2", "resp-3"] {
+        mount_sse_once(&server, reply(id, id)).await;
+    }
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let config = mock_provider_config(&fixture, &server).await?;
+    let thread_id = start_configured_thread(&state, config).await?.thread_id;
+    let app = turns_router(state.clone()).merge(
+        Router::new()
+            .route(
+                "/api/v2/threads/{id}/rollback",
+                post(handlers::threads::rollback_thread),
+            )
+            .with_state(state),
+    );
+    let mut events = EventReader::open(&app, thread_id).await?;
+    let uri = format!("/api/v2/threads/{thread_id}/turns");
+    let rollback_uri = format!("/api/v2/threads/{thread_id}/rollback");
+
+    let mut turn_ids = Vec::new();
+    for text in ["one", "two", "three"] {
+        turn_ids.push(send_turn(&app, &uri, text).await?["turn_id"].clone());
+        events.next_of("turn/completed").await?;
+    }
+
+    for invalid in [
+        json!({ "num_turns": 0 }),
+        json!({ "num_turns": 3 }),
+        json!({}),
+        json!({ "num_turns": 1, "turn_id": turn_ids[1] }),
+    ] {
+        let (status, body) = post_turn(&app, &rollback_uri, invalid.clone()).await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{invalid}: {body}");
+    }
+
+    let (status, body) = post_turn(&app, &rollback_uri, json!({ "num_turns": 1 })).await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    assert_eq!(body["current_turn_id"], turn_ids[1]);
+    assert_eq!(body["num_turns"], json!(1));
+    let rolled_back = events.next_of("thread/rolledBack").await?;
+    assert_eq!(
+        rolled_back["params"]["threadId"],
+        json!(thread_id.to_string())
+    );
+    assert_eq!(rolled_back["params"]["numTurns"], json!(1));
+    Ok(())
+}
+
+/// Uploads `data` as `name` and returns the attachment id.
+async fn upload(app: &Router, name: &str, data: &str) -> Result<String> {
+    let body = format!(
+        "--x\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{name}\"\r\nContent-Type: image/png\r\n\r\n{data}\r\n--x--\r\n"
+    );
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/attachments")
+                .header("content-type", "multipart/form-data; boundary=x")
+                .body(Body::from(body))?,
+        )
+        .await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+    Ok(body["attachment_id"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string())
+}
+
+/// The attachments of the first user message in the thread's item history,
+/// once its rollout has been written.
+async fn history_attachments(app: &Router, thread_id: ThreadId) -> Result<Value> {
+    for _ in 0..50 {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/api/v2/threads/{thread_id}/items"))
+                    .body(Body::empty())?,
+            )
+            .await?;
+        if response.status() == StatusCode::OK {
+            let page: Value =
+                serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+            let attachments = page["data"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .find(|item| item["item"]["type"] == json!("userMessage"))
+                .map(|item| item["attachments"].clone());
+            if let Some(attachments) = attachments {
+                return Ok(attachments);
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    anyhow::bail!("the user message never reached the history")
+}
+
+#[tokio::test]
+async fn test_history_lists_turn_attachments_across_a_restart() -> Result<()> {
+    let server = start_mock_server().await;
+    mount_sse_once(&server, reply("resp-1", "seen")).await;
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let config = mock_provider_config(&fixture, &server).await?;
+    let thread_id = start_configured_thread(&state, config).await?.thread_id;
+    let app = turns_router(state.clone()).merge(
+        Router::new()
+            .route("/api/v1/attachments", post(attachments::upload_attachment))
+            .with_state(state),
+    );
+
+    let attachment_id = upload(&app, "diagram.png", "not really a png").await?;
+    let mut events = EventReader::open(&app, thread_id).await?;
+    let (status, body) = post_turn(
+        &app,
+        &format!("/api/v2/threads/{thread_id}/turns"),
+        json!({"input": [
+            {"type": "text", "text": "what is this?"},
+            {"type": "attachment", "attachment_id": attachment_id},
+        ]}),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    events.next_of("turn/completed").await?;
+
+    // A fresh state over the same codex home, as after a restart.
+    let restarted = Router::new()
+        .route(
+            "/api/v2/threads/{id}/items",
+            get(handlers::threads::list_thread_items),
+        )
+        .with_state(create_test_state(&fixture, "test-token"));
+    assert_eq!(
+        history_attachments(&restarted, thread_id).await?,
+        json!([{
+            "attachment_id": attachment_id,
+            "filename": "diagram.png",
+            "size": 16,
+            "download_url": format!("/api/v1/attachments/{attachment_id}"),
+            "deleted": false,
+        }])
+    );
+
+    // A deleted file leaves a tombstone instead of a broken link.
+    let mut files = std::fs::read_dir(fixture.attachments_dir.path())?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir());
+    let Some(namespace) = files.next() else {
+        anyhow::bail!("the upload has no namespace directory");
+    };
+    std::fs::remove_file(namespace.path().join(&attachment_id))?;
+    let attachments = history_attachments(&restarted, thread_id).await?;
+    assert_eq!(attachments[0]["deleted"], json!(true));
+    assert_eq!(attachments[0]["download_url"], Value::Null);
+    assert_eq!(attachments[0]["filename"], json!("diagram.png"));
+    Ok(())
+}
@@ -0,0 +1,320 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::delete;
+use axum::routing::get;
+use axum::routing::post;
+use codex_protocol::ThreadId;
+use codex_web_server::handlers;
+use codex_web_server::mock_model::MockModelServer;
+use codex_web_server::mock_model::MockScript;
+use serde_json::Value;
+use serde_json::json;
+use std::path::PathBuf;
+use tower::ServiceExt;
+
+use crate::common::EventReader;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+/// The fixture script stops every turn on a command approval, which keeps
+/// the thread busy until the test answers it.
+const APPROVAL_CONFIG: &str = r#"
+model = "test-model"
+approval_policy = "on-request"
+sandbox_mode = "workspace-write"
+"#;
+
+async fn request(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> Result<(StatusCode, Value)> {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("content-type", "application/json");
+    let request = match body {
+        Some(body) => request.body(Body::from(body.to_string()))?,
+        None => request.body(Body::empty())?,
+    };
+    let response = app.clone().oneshot(request).await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, serde_json::from_slice(&body)?))
+}
+
+fn text_input(text: &str) -> Value {
+    json!({ "input": [{ "type": "text", "text": text }] })
+}
+
+fn approval_id(approval: &Value) -> String {
+    approval["approvalId"]
+        .as_str()
+        .or(approval["itemId"].as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_queued_inputs_can_be_listed_cancelled_and_run_in_order() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(APPROVAL_CONFIG)?;
+    let script = MockScript::load(
+        &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mock_thread.json"),
+    )?;
+    let server = MockModelServer::start(script).await?;
+    let state = create_test_state(&fixture, "test-token")
+        .with_config_loader(server.config_loader(fixture.codex_home_path()));
+    let app = Router::new()
+        .route("/api/v2/threads", post(handlers::threads::create_thread))
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(handlers::turns::send_turn),
+        )
+        .route(
+            "/api/v2/threads/{id}/queue",
+            get(handlers::turns::list_queue),
+        )
+        .route(
+            "/api/v2/threads/{id}/queue/{entry_id}",
+            delete(handlers::turns::cancel_queued),
+        )
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .route(
+            "/api/v2/threads/{thread_id}/approvals/{approval_id}",
+            post(handlers::approvals::respond_to_approval),
+        )
+        .with_state(state);
+
+    let (_, created) = request(
+        &app,
+        "POST",
+        "/api/v2/threads",
+        Some(json!({ "cwd": fixture.codex_home.path() })),
+    )
+    .await?;
+    let thread_id = ThreadId::from_string(created["thread_id"].as_str().unwrap_or_default())?;
+    let turns = format!("/api/v2/threads/{thread_id}/turns");
+    let queue = format!("/api/v2/threads/{thread_id}/queue");
+    let mut events = EventReader::open(&app, thread_id).await?;
+
+    let (status, running) = request(
+        &app,
+        "POST",
+        &turns,
+        Some(text_input("Check the workspace")),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{running}");
+    let approval = events
+        .next_of("item/commandExecution/requestApproval")
+        .await?;
+
+    // The thread is busy, so both inputs wait instead of being steered in.
+    let (_, first) = request(
+        &app,
+        "POST",
+        &format!("{turns}?queue=true"),
+        Some(text_input("first queued")),
+    )
+    .await?;
+    assert_eq!(first["turn_id"], Value::Null, "{first}");
+    assert_eq!(first["steered"], json!(false));
+    let first_id = first["queue_entry_id"].clone();
+    let (_, second) = request(
+        &app,
+        "POST",
+        &format!("{turns}?queue=true"),
+        Some(text_input("second queued")),
+    )
+    .await?;
+    let second_id = second["queue_entry_id"].clone();
+    assert_eq!(
+        events.next_of("turn/queued").await?["params"]["entry"]["queue_entry_id"],
+        first_id
+    );
+    assert_eq!(
+        events.next_of("turn/queued").await?["params"]["entry"]["queue_entry_id"],
+        second_id
+    );
+
+    let (status, listed) = request(&app, "GET", &queue, None).await?;
+    assert_eq!(status, StatusCode::OK, "{listed}");
+    let entries = listed["entries"].as_array().cloned().unwrap_or_default();
+    assert_eq!(entries.len(), 2, "{listed}");
+    assert_eq!(entries[0]["queue_entry_id"], first_id);
+    assert_eq!(
+        entries[0]["input"],
+        json!([{ "type": "text", "text": "first queued" }])
+    );
+    assert!(entries[0]["enqueued_at"].is_i64());
+    assert_eq!(entries[1]["queue_entry_id"], second_id);
+
+    let first_id_str = first_id.as_str().unwrap_or_default();
+    let (status, cancelled) =
+        request(&app, "DELETE", &format!("{queue}/{first_id_str}"), None).await?;
+    assert_eq!(status, StatusCode::OK, "{cancelled}");
+    assert_eq!(cancelled["input"], entries[0]["input"]);
+    let dequeued = events.next_of("turn/dequeued").await?;
+    assert_eq!(dequeued["params"]["queueEntryId"], first_id);
+    assert_eq!(dequeued["params"]["reason"], json!("cancelled"));
+    let (status, _) = request(&app, "DELETE", &format!("{queue}/{first_id_str}"), None).await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (_, listed) = request(&app, "GET", &queue, None).await?;
+    assert_eq!(
+        listed["entries"].as_array().map(Vec::len),
+        Some(1),
+        "{listed}"
+    );
+
+    // Finishing the running turn starts the remaining input.
+    request(
+        &app,
+        "POST",
+        &format!(
+            "/api/v2/threads/{thread_id}/approvals/{}",
+            approval_id(&approval)
+        ),
+        Some(json!({ "decision": "approve" })),
+    )
+    .await?;
+    let completed = events.next_of("turn/completed").await?;
+    assert_eq!(completed["turn"]["id"], running["turn_id"]);
+    let started = events.next_of("turn/dequeued").await?;
+    assert_eq!(started["params"]["queueEntryId"], second_id);
+    assert_eq!(started["params"]["reason"], json!("started"));
+    let queued_turn_id = started["params"]["turnId"].clone();
+    assert!(queued_turn_id.is_string(), "{started}");
+    assert_ne!(queued_turn_id, running["turn_id"]);
+
+    // The queued input runs as its own turn, which stops on the same approval.
+    let approval = events
+        .next_of("item/commandExecution/requestApproval")
+        .await?;
+    assert_eq!(approval["turnId"], queued_turn_id);
+    let (_, listed) = request(&app, "GET", &queue, None).await?;
+    assert_eq!(listed["entries"], json!([]));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_queue_routes_reject_unknown_threads_and_entries() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let app = Router::new()
+        .route(
+            "/api/v2/threads/{id}/queue",
+            get(handlers::turns::list_queue),
+        )
+        .route(
+            "/api/v2/threads/{id}/queue/{entry_id}",
+            delete(handlers::turns::cancel_queued),
+        )
+        .with_state(state);
+    let thread_id = ThreadId::new();
+
+    let (status, _) = request(
+        &app,
+        "GET",
+        &format!("/api/v2/threads/{thread_id}/queue"),
+        None,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = request(
+        &app,
+        "DELETE",
+        &format!("/api/v2/threads/{thread_id}/queue/missing"),
+        None,
+    )
+    .await?;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_busy_thread_rejects_input_and_drains_the_queue_after_interrupt() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(APPROVAL_CONFIG)?;
+    let script = MockScript::load(
+        &PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/mock_thread.json"),
+    )?;
+    let server = MockModelServer::start(script).await?;
+    let state = create_test_state(&fixture, "test-token")
+        .with_config_loader(server.config_loader(fixture.codex_home_path()));
+    let app = Router::new()
+        .route("/api/v2/threads", post(handlers::threads::create_thread))
+        .route(
+            "/api/v2/threads/{id}/turns",
+            post(handlers::turns::send_turn),
+        )
+        .route(
+            "/api/v2/threads/{id}/turns/interrupt",
+            post(handlers::turns::interrupt_turn),
+        )
+        .route("/api/v2/threads/{id}/events", get(handlers::stream_events))
+        .with_state(state);
+
+    let (_, created) = request(
+        &app,
+        "POST",
+        "/api/v2/threads",
+        Some(json!({ "cwd": fixture.codex_home.path() })),
+    )
+    .await?;
+    let thread_id = ThreadId::from_string(created["thread_id"].as_str().unwrap_or_default())?;
+    let turns = format!("/api/v2/threads/{thread_id}/turns");
+    let mut events = EventReader::open(&app, thread_id).await?;
+
+    let (status, running) = request(
+        &app,
+        "POST",
+        &turns,
+        Some(text_input("Check the workspace")),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{running}");
+    events
+        .next_of("item/commandExecution/requestApproval")
+        .await?;
+
+    let (status, rejected) = request(&app, "POST", &turns, Some(text_input("too early"))).await?;
+    assert_eq!(status, StatusCode::CONFLICT, "{rejected}");
+    assert_eq!(rejected["code"], json!("turn_in_progress"));
+    assert_eq!(rejected["active_turn_id"], running["turn_id"]);
+
+    let (status, queued) = request(
+        &app,
+        "POST",
+        &format!("{turns}?queue=true"),
+        Some(text_input("after the interrupt")),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK, "{queued}");
+    let queued_id = queued["queue_entry_id"].clone();
+    assert!(queued_id.is_string(), "{queued}");
+
+    // An interrupted turn drains the queue like a completed one.
+    let (status, _) = request(
+        &app,
+        "POST",
+        &format!("/api/v2/threads/{thread_id}/turns/interrupt"),
+        Some(json!({})),
+    )
+    .await?;
+    assert_eq!(status, StatusCode::OK);
+    let completed = events.next_of("turn/completed").await?;
+    assert_eq!(completed["turn"]["id"], running["turn_id"]);
+    assert_eq!(completed["turn"]["status"], json!("interrupted"));
+    let started = events.next_of("turn/dequeued").await?;
+    assert_eq!(started["params"]["queueEntryId"], queued_id);
+    assert_eq!(started["params"]["reason"], json!("started"));
+    assert_ne!(started["params"]["turnId"], running["turn_id"]);
+    Ok(())
+}
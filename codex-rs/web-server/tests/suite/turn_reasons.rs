@@ -0,0 +1,108 @@
+use anyhow::Result;
+use codex_app_server_protocol::ServerNotification;
+use codex_app_server_protocol::ThreadItem;
+use codex_app_server_protocol::TurnReasonCode;
+use codex_protocol::ThreadId;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::ExitedReviewModeEvent;
+use codex_protocol::protocol::ReviewOutputEvent;
+use codex_protocol::protocol::ReviewRequest;
+use codex_protocol::protocol::ReviewTarget;
+use codex_protocol::protocol::TurnAbortReason;
+use codex_protocol::protocol::TurnAbortedEvent;
+use codex_web_server::event_stream::EventStreamProcessor;
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+async fn process(msg: EventMsg) -> Result<Vec<ServerNotification>> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let processor = EventStreamProcessor::new(ThreadId::new(), Arc::new(state));
+    Ok(processor
+        .process_event(Event {
+            id: "turn-1".to_string(),
+            msg,
+        })
+        .await)
+}
+
+async fn review_text(msg: EventMsg) -> Result<String> {
+    let notifications = process(msg).await?;
+    match notifications.first() {
+        Some(ServerNotification::ItemStarted(started)) => match &started.item {
+            ThreadItem::EnteredReviewMode { review, .. }
+            | ThreadItem::ExitedReviewMode { review, .. } => Ok(review.clone()),
+            item => anyhow::bail!("expected a review item, got {item:?}"),
+        },
+        other => anyhow::bail!("expected item/started, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_aborted_turn_carries_reason_code() -> Result<()> {
+    let cases = [
+        (TurnAbortReason::Interrupted, TurnReasonCode::UserInterrupt),
+        (TurnAbortReason::Replaced, TurnReasonCode::ReplacedByNewTurn),
+        (TurnAbortReason::ReviewEnded, TurnReasonCode::ReviewEnded),
+    ];
+    for (reason, code) in cases {
+        let debug_text = format!("{reason:?}");
+        let notifications = process(EventMsg::TurnAborted(TurnAbortedEvent {
+            turn_id: Some("turn-1".to_string()),
+            reason,
+        }))
+        .await?;
+        let [ServerNotification::TurnCompleted(completed)] = notifications.as_slice() else {
+            panic!("expected a single turn/completed notification, got {notifications:?}");
+        };
+        let error = completed
+            .turn
+            .error
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("interrupted turn has no error"))?;
+        assert_eq!(error.reason_code, Some(code));
+        assert!(!error.message.contains(&debug_text), "{}", error.message);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reason_code_is_snake_case_on_the_wire() -> Result<()> {
+    let notifications = process(EventMsg::TurnAborted(TurnAbortedEvent {
+        turn_id: Some("turn-1".to_string()),
+        reason: TurnAbortReason::Replaced,
+    }))
+    .await?;
+    let value = serde_json::to_value(&notifications)?;
+    assert_eq!(
+        value[0]["params"]["turn"]["error"]["reasonCode"],
+        json!("replaced_by_new_turn")
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_review_items_use_readable_text() -> Result<()> {
+    let entered = review_text(EventMsg::EnteredReviewMode(ReviewRequest {
+        target: ReviewTarget::BaseBranch {
+            branch: "main".to_string(),
+        },
+        user_facing_hint: None,
+    }))
+    .await?;
+    assert_eq!(entered, "changes against 'main'");
+
+    let exited = review_text(EventMsg::ExitedReviewMode(ExitedReviewModeEvent {
+        review_output: Some(ReviewOutputEvent {
+            overall_explanation: "Looks good.".to_string(),
+            ..Default::default()
+        }),
+    }))
+    .await?;
+    assert_eq!(exited, "Looks good.");
+    Ok(())
+}
@@ -0,0 +1,250 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::routing::get;
+use codex_protocol::ThreadId;
+use codex_protocol::config_types::ReasoningSummary;
+use codex_protocol::protocol::AskForApproval;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::RolloutItem;
+use codex_protocol::protocol::RolloutLine;
+use codex_protocol::protocol::SandboxPolicy;
+use codex_protocol::protocol::TokenCountEvent;
+use codex_protocol::protocol::TokenUsage;
+use codex_protocol::protocol::TokenUsageInfo;
+use codex_protocol::protocol::TurnContextItem;
+use codex_web_server::handlers;
+use codex_web_server::usage::ModelPrice;
+use codex_web_server::usage::ModelPricing;
+use serde_json::Value;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::Path;
+use tower::ServiceExt;
+
+use crate::common::TEST_CONFIG;
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+fn usage(input_tokens: i64, output_tokens: i64) -> TokenUsage {
+    TokenUsage {
+        input_tokens,
+        cached_input_tokens: 0,
+        output_tokens,
+        reasoning_output_tokens: 0,
+        total_tokens: input_tokens + output_tokens,
+    }
+}
+
+fn add(total: &TokenUsage, last: &TokenUsage) -> TokenUsage {
+    TokenUsage {
+        input_tokens: total.input_tokens + last.input_tokens,
+        cached_input_tokens: total.cached_input_tokens + last.cached_input_tokens,
+        output_tokens: total.output_tokens + last.output_tokens,
+        reasoning_output_tokens: total.reasoning_output_tokens + last.reasoning_output_tokens,
+        total_tokens: total.total_tokens + last.total_tokens,
+    }
+}
+
+/// Writes a rollout for `model` with one token count per `(timestamp, usage)`.
+/// A `None` usage repeats the previous count, as a rate-limit refresh does.
+fn write_rollout(
+    codex_home: &Path,
+    thread_id: ThreadId,
+    model: &str,
+    counts: &[(&str, Option<TokenUsage>)],
+) -> Result<()> {
+    let dir = codex_home.join("sessions/2025/03/01");
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("rollout-2025-03-01T00-00-00-{thread_id}.jsonl"));
+    let mut lines = vec![RolloutLine {
+        timestamp: "2025-03-01T00:00:00.000Z".to_string(),
+        item: RolloutItem::TurnContext(TurnContextItem {
+            turn_id: Some("turn-1".to_string()),
+            cwd: codex_home.to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            network: None,
+            model: model.to_string(),
+            personality: None,
+            collaboration_mode: None,
+            effort: None,
+            summary: ReasoningSummary::Auto,
+            user_instructions: None,
+            developer_instructions: None,
+            final_output_json_schema: None,
+            truncation_policy: None,
+        }),
+    }];
+    let mut total = usage(0, 0);
+    let mut last = usage(0, 0);
+    for (timestamp, count) in counts {
+        if let Some(count) = count {
+            total = add(&total, count);
+            last = count.clone();
+        }
+        lines.push(RolloutLine {
+            timestamp: timestamp.to_string(),
+            item: RolloutItem::EventMsg(EventMsg::TokenCount(TokenCountEvent {
+                info: Some(TokenUsageInfo {
+                    total_token_usage: total.clone(),
+                    last_token_usage: last.clone(),
+                    model_context_window: None,
+                }),
+                rate_limits: None,
+            })),
+        });
+    }
+    let mut contents = String::new();
+    for line in lines {
+        contents.push_str(&serde_json::to_string(&line)?);
+        contents.push('\n');
+    }
+    std::fs::write(path, contents)?;
+    Ok(())
+}
+
+async fn get_report(app: &Router, query: &str, accept: &str) -> Result<(StatusCode, String)> {
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/v2/usage?{query}"))
+                .header("accept", accept)
+                .body(Body::empty())?,
+        )
+        .await?;
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await?;
+    Ok((status, String::from_utf8(body.to_vec())?))
+}
+
+async fn get_json(app: &Router, query: &str) -> Result<Value> {
+    let (status, body) = get_report(app, query, "application/json").await?;
+    assert_eq!(status, StatusCode::OK, "{body}");
+    Ok(serde_json::from_str(&body)?)
+}
+
+/// `(key, total_tokens)` per group.
+fn totals(report: &Value) -> Vec<(String, i64)> {
+    report["groups"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|group| {
+            (
+                group["key"].as_str().unwrap_or_default().to_string(),
+                group["total_tokens"].as_i64().unwrap_or_default(),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_usage_report_groups_rollout_usage() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let codex_home = fixture.codex_home_path();
+    let thread_a = ThreadId::new();
+    let thread_b = ThreadId::new();
+    write_rollout(
+        &codex_home,
+        thread_a,
+        "model-a",
+        &[
+            ("2025-03-01T10:00:00.000Z", Some(usage(100, 50))),
+            ("2025-03-01T10:01:00.000Z", None),
+            ("2025-03-02T09:00:00.000Z", Some(usage(200, 100))),
+        ],
+    )?;
+    write_rollout(
+        &codex_home,
+        thread_b,
+        "model-b",
+        &[
+            ("2025-03-01T12:00:00.000Z", Some(usage(1000, 0))),
+            ("2025-03-05T12:00:00.000Z", Some(usage(5, 5))),
+        ],
+    )?;
+    let pricing = ModelPricing::new(HashMap::from([(
+        "model-a".to_string(),
+        ModelPrice {
+            input: 1.0,
+            cached_input: None,
+            output: 2.0,
+        },
+    )]));
+    let state = create_test_state(&fixture, "test-token").with_model_pricing(pricing);
+    let app = Router::new()
+        .route("/api/v2/usage", get(handlers::usage::get_usage))
+        .with_state(state);
+    let range = "from=2025-03-01&to=2025-03-03";
+
+    let by_day = get_json(&app, &format!("{range}&group_by=day")).await?;
+    assert_eq!(
+        totals(&by_day),
+        vec![
+            ("2025-03-01".to_string(), 1150),
+            ("2025-03-02".to_string(), 300),
+        ]
+    );
+    assert_eq!(by_day["total"]["total_tokens"], json!(1450));
+    // model-b has no price, so nothing that includes it gets a cost.
+    assert_eq!(by_day["total"]["estimated_cost_usd"], Value::Null);
+
+    let by_model = get_json(&app, &format!("{range}&group_by=model")).await?;
+    assert_eq!(
+        totals(&by_model),
+        vec![("model-a".to_string(), 450), ("model-b".to_string(), 1000)]
+    );
+    let cost = by_model["groups"][0]["estimated_cost_usd"]
+        .as_f64()
+        .unwrap_or_default();
+    assert!((cost - 0.0006).abs() < 1e-12, "{by_model}");
+    assert_eq!(by_model["groups"][1]["estimated_cost_usd"], Value::Null);
+
+    let by_thread = get_json(&app, &format!("{range}&group_by=thread")).await?;
+    let mut expected = vec![(thread_a.to_string(), 450), (thread_b.to_string(), 1000)];
+    expected.sort();
+    assert_eq!(totals(&by_thread), expected);
+
+    let (status, csv) = get_report(&app, &format!("{range}&group_by=model"), "text/csv").await?;
+    assert_eq!(status, StatusCode::OK, "{csv}");
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(
+        lines,
+        vec![
+            "model,input_tokens,cached_input_tokens,output_tokens,reasoning_output_tokens,total_tokens,estimated_cost_usd",
+            "model-a,300,0,150,0,450,0.000600",
+            "model-b,1000,0,0,0,1000,",
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_usage_report_rejects_bad_ranges() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    fixture.create_test_config(TEST_CONFIG)?;
+    let app = Router::new()
+        .route("/api/v2/usage", get(handlers::usage::get_usage))
+        .with_state(create_test_state(&fixture, "test-token"));
+
+    for query in [
+        "from=yesterday",
+        "from=2025-03-02&to=2025-03-01",
+        "group_by=week",
+    ] {
+        let (status, body) = get_report(&app, query, "application/json").await?;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{query}: {body}");
+    }
+
+    let report = get_json(&app, "").await?;
+    assert_eq!(report["group_by"], json!("day"));
+    assert_eq!(report["groups"], json!([]));
+    assert_eq!(report["total"]["estimated_cost_usd"], Value::Null);
+    Ok(())
+}
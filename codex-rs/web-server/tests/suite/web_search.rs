@@ -0,0 +1,98 @@
+use anyhow::Result;
+use codex_protocol::ThreadId;
+use codex_protocol::items::TurnItem;
+use codex_protocol::items::WebSearchItem;
+use codex_protocol::models::WebSearchAction;
+use codex_protocol::protocol::Event;
+use codex_protocol::protocol::EventMsg;
+use codex_protocol::protocol::ItemCompletedEvent;
+use codex_protocol::protocol::ItemStartedEvent;
+use codex_protocol::protocol::WebSearchBeginEvent;
+use codex_web_server::event_stream::EventStreamProcessor;
+use serde_json::json;
+use std::sync::Arc;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+fn search(query: &str) -> WebSearchItem {
+    WebSearchItem {
+        id: "ws-1".to_string(),
+        query: query.to_string(),
+        action: WebSearchAction::Search {
+            query: (!query.is_empty()).then(|| query.to_string()),
+            queries: None,
+        },
+    }
+}
+
+#[tokio::test]
+async fn test_web_search_is_streamed_once_as_a_web_search_item() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let state = create_test_state(&fixture, "test-token");
+    let thread_id = ThreadId::new();
+    let processor = EventStreamProcessor::new(thread_id, Arc::new(state));
+    let event = |msg| Event {
+        id: "turn-1".to_string(),
+        msg,
+    };
+
+    let started = processor
+        .process_event(event(EventMsg::ItemStarted(ItemStartedEvent {
+            thread_id,
+            turn_id: "turn-1".to_string(),
+            item: TurnItem::WebSearch(search("")),
+        })))
+        .await;
+    let completed = processor
+        .process_event(event(EventMsg::ItemCompleted(ItemCompletedEvent {
+            thread_id,
+            turn_id: "turn-1".to_string(),
+            item: TurnItem::WebSearch(search("rust sse replay")),
+        })))
+        .await;
+
+    let [started] = started.as_slice() else {
+        panic!("expected a single notification, got {started:?}");
+    };
+    assert_eq!(
+        EventStreamProcessor::event_type_name(started),
+        "item/started"
+    );
+    assert_eq!(
+        serde_json::to_value(started)?["params"]["item"],
+        json!({"type": "webSearch", "id": "ws-1", "query": "", "action": {"type": "search", "query": null, "queries": null}})
+    );
+    let [completed] = completed.as_slice() else {
+        panic!("expected a single notification, got {completed:?}");
+    };
+    assert_eq!(
+        EventStreamProcessor::event_type_name(completed),
+        "item/completed"
+    );
+    assert_eq!(
+        serde_json::to_value(completed)?["params"],
+        json!({
+            "threadId": thread_id.to_string(),
+            "turnId": "turn-1",
+            "item": {
+                "type": "webSearch",
+                "id": "ws-1",
+                "query": "rust sse replay",
+                "action": {"type": "search", "query": "rust sse replay", "queries": null}
+            }
+        })
+    );
+
+    // Core repeats each search as legacy events; they must not add items.
+    for legacy in [
+        EventMsg::WebSearchBegin(WebSearchBeginEvent {
+            call_id: "ws-1".to_string(),
+        }),
+        search("rust sse replay").as_legacy_event(),
+    ] {
+        let notifications = processor.process_event(event(legacy)).await;
+        assert!(notifications.is_empty(), "{notifications:?}");
+    }
+    Ok(())
+}
@@ -0,0 +1,179 @@
+use anyhow::Result;
+use axum::Router;
+use axum::body::Body;
+use axum::body::to_bytes;
+use axum::http::Request;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use codex_web_server::error::ApiError;
+use codex_web_server::handlers::capabilities::get_capabilities;
+use codex_web_server::workspace_trust::TrustedRoots;
+use serde_json::Value;
+use serde_json::json;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tower::ServiceExt;
+
+use crate::common::TestFixture;
+use crate::common::create_test_state;
+
+/// A temp dir holding `repo/project` and a sibling `repo-evil`, with `repo`
+/// as the only trusted root.
+fn workspace() -> Result<(TempDir, PathBuf, TrustedRoots)> {
+    let dir = TempDir::new()?;
+    let repo = dir.path().join("repo");
+    std::fs::create_dir_all(repo.join("project"))?;
+    std::fs::create_dir_all(dir.path().join("repo-evil"))?;
+    let roots = TrustedRoots::new([repo.clone()]);
+    Ok((dir, repo, roots))
+}
+
+fn assert_untrusted(roots: &TrustedRoots, cwd: &Path) {
+    assert!(
+        matches!(roots.check(cwd), Err(ApiError::UntrustedCwd { .. })),
+        "{} should be rejected",
+        cwd.display()
+    );
+}
+
+#[test]
+fn test_no_roots_accepts_any_cwd() {
+    let roots = TrustedRoots::default();
+    assert!(roots.is_unrestricted());
+    let cwd = Path::new("/definitely/not/a/real/path");
+    assert_eq!(roots.check(cwd).ok(), Some(cwd.to_path_buf()));
+}
+
+#[test]
+fn test_cwd_under_root_is_accepted_canonicalized() -> Result<()> {
+    let (_dir, repo, roots) = workspace()?;
+
+    let project = repo.join("project");
+    assert_eq!(roots.check(&project).ok(), Some(project.canonicalize()?));
+    assert_eq!(roots.check(&repo).ok(), Some(repo.canonicalize()?));
+    // `..` is resolved before the comparison.
+    assert_eq!(
+        roots.check(&project.join("..")).ok(),
+        Some(repo.canonicalize()?)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_sibling_sharing_root_prefix_is_rejected() -> Result<()> {
+    let (dir, repo, roots) = workspace()?;
+
+    assert_untrusted(&roots, &dir.path().join("repo-evil"));
+    assert_untrusted(&roots, &repo.join("..").join("repo-evil"));
+    assert_untrusted(&roots, dir.path());
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlink_escaping_root_is_rejected() -> Result<()> {
+    let (dir, repo, roots) = workspace()?;
+    let escape = repo.join("escape");
+    std::os::unix::fs::symlink(dir.path().join("repo-evil"), &escape)?;
+    let inside = repo.join("inside");
+    std::os::unix::fs::symlink(repo.join("project"), &inside)?;
+
+    assert_untrusted(&roots, &escape);
+    assert_eq!(
+        roots.check(&inside).ok(),
+        Some(repo.join("project").canonicalize()?)
+    );
+    Ok(())
+}
+
+#[test]
+fn test_missing_cwd_is_rejected_like_an_untrusted_one() -> Result<()> {
+    let (_dir, repo, roots) = workspace()?;
+    assert_untrusted(&roots, &repo.join("missing"));
+    Ok(())
+}
+
+#[test]
+fn test_roots_are_read_from_the_web_server_section() -> Result<()> {
+    let (_dir, repo, roots) = workspace()?;
+
+    let config = format!(
+        "model = \"gpt-5.1-codex\"\n\n[web_server]\ntrusted_roots = [{:?}]\n",
+        repo.display().to_string()
+    );
+    assert_eq!(TrustedRoots::parse(&config)?, roots);
+    assert!(TrustedRoots::parse("model = \"gpt-5.1-codex\"\n")?.is_unrestricted());
+    assert!(TrustedRoots::parse("[web_server]\ntrusted_roots = []\n")?.is_unrestricted());
+    assert!(TrustedRoots::parse("[web_server]\ntrusted_roots = \"/srv/repos\"\n").is_err());
+    Ok(())
+}
+
+#[test]
+fn test_roots_load_from_codex_home() -> Result<()> {
+    let (dir, repo, roots) = workspace()?;
+    let codex_home = dir.path().join("codex-home");
+    std::fs::create_dir_all(&codex_home)?;
+    assert!(TrustedRoots::load(&codex_home)?.is_unrestricted());
+
+    std::fs::write(
+        codex_home.join("config.toml"),
+        format!(
+            "[web_server]\ntrusted_roots = [{:?}]\n",
+            repo.display().to_string()
+        ),
+    )?;
+    assert_eq!(TrustedRoots::load(&codex_home)?, roots);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_rejection_lists_trusted_roots() -> Result<()> {
+    let (dir, repo, roots) = workspace()?;
+    let evil = dir.path().join("repo-evil");
+    let Err(err) = roots.check(&evil) else {
+        anyhow::bail!("repo-evil should be rejected");
+    };
+
+    let response = err.into_response();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let body: Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+    assert_eq!(
+        body["trusted_roots"],
+        json!([repo.canonicalize()?.display().to_string()])
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_capabilities_advertise_trusted_roots() -> Result<()> {
+    let fixture = TestFixture::new().await?;
+    let (_dir, repo, roots) = workspace()?;
+    let mut state = create_test_state(&fixture, "test-token");
+    let app = |state| {
+        Router::new()
+            .route("/api/v2/capabilities", get(get_capabilities))
+            .with_state(state)
+    };
+
+    for (trusted_roots, expected) in [
+        (TrustedRoots::default(), json!([])),
+        (roots, json!([repo.canonicalize()?.display().to_string()])),
+    ] {
+        state.trusted_roots = Arc::new(trusted_roots);
+        let response = app(state.clone())
+            .oneshot(
+                Request::builder()
+                    .uri("/api/v2/capabilities")
+                    .body(Body::empty())?,
+            )
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: Value =
+            serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await?)?;
+        assert_eq!(body["trusted_roots"], expected);
+    }
+    Ok(())
+}